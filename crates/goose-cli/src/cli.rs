@@ -1,25 +1,51 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand};
 
+use goose::agents::ReplyOutcome;
 use goose::config::{Config, ExtensionConfig};
 
+use crate::commands::batch::{run_batch, BatchOptions};
 use crate::commands::bench::agent_generator;
+use crate::commands::bench_compare::{run_compare, CompareOptions};
+use crate::commands::config_archive::{handle_config_export, handle_config_import};
+use crate::commands::config_values::{
+    handle_config_get, handle_config_list, handle_config_set, handle_config_unset,
+};
 use crate::commands::configure::handle_configure;
-use crate::commands::info::handle_info;
+use crate::commands::extensions::{
+    handle_extension_add, handle_extension_install, handle_extension_list, handle_extension_remove,
+    handle_extension_secrets, handle_extension_set_enabled, AddOptions, InstallOptions,
+};
+use crate::commands::hints::handle_hints;
+use crate::commands::info::{
+    handle_config_show, handle_config_validate, handle_info, warn_about_config_findings,
+};
+use crate::commands::logs::{handle_extension_logs, handle_logs};
 use crate::commands::mcp::run_server;
+use crate::commands::mcp_serve::run_agent_server;
+use crate::commands::profiles::{
+    ensure_profile_exists, handle_profile_create, handle_profile_delete, handle_profile_show,
+    handle_profiles_list,
+};
 use crate::commands::project::{handle_project_default, handle_projects_interactive};
 use crate::commands::recipe::{handle_deeplink, handle_list, handle_validate};
+use crate::commands::recipe_run::RunTarget;
 // Import the new handlers from commands::schedule
 use crate::commands::schedule::{
     handle_schedule_add, handle_schedule_cron_help, handle_schedule_list, handle_schedule_remove,
     handle_schedule_run_now, handle_schedule_services_status, handle_schedule_services_stop,
-    handle_schedule_sessions,
+    handle_schedule_sessions, start_run_heartbeats,
+};
+use crate::commands::session::{
+    handle_session_list, handle_session_remove, SessionListOptions, SessionSort,
 };
-use crate::commands::session::{handle_session_list, handle_session_remove};
+use crate::commands::stats::handle_tool_stats;
+use crate::commands::webhooks::{handle_webhooks_list, handle_webhooks_test};
 use crate::recipes::extract_from_cli::extract_recipe_info_from_cli;
 use crate::recipes::recipe::{explain_recipe, render_recipe_as_yaml};
 use crate::session;
-use crate::session::{build_session, SessionBuilderConfig, SessionSettings};
+use crate::session::env::collect_session_env;
+use crate::session::{build_session, OutputFormat, SessionBuilderConfig, SessionSettings};
 use goose_bench::bench_config::BenchRunConfig;
 use goose_bench::runners::bench_runner::BenchRunner;
 use goose_bench::runners::eval_runner::EvalRunner;
@@ -31,6 +57,25 @@ use std::path::PathBuf;
 #[derive(Parser)]
 #[command(author, version, display_name = "", about, long_about = None)]
 struct Cli {
+    /// Configuration profile to use
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Use a configuration profile (overrides GOOSE_PROFILE)",
+        long_help = "Layer the named profile from the profiles directory next to config.yaml over the configuration, e.g. to switch between a local and a cloud provider. Manage profiles with `goose configure profiles`."
+    )]
+    profile: Option<String>,
+
+    /// Plain text output for CI and logs
+    #[arg(
+        long,
+        global = true,
+        help = "Plain text output without colors, spinners or progress bars",
+        long_help = "Write line oriented plain text so output stays readable in CI logs: disables colors, spinners, progress bars, thinking indicators and markdown highlighting. Can also be enabled with GOOSE_CLI_PLAIN=true."
+    )]
+    plain: bool,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -90,12 +135,48 @@ enum SessionCommand {
         )]
         format: String,
 
+        #[arg(long, help = "Output as JSON, same as --format json")]
+        json: bool,
+
         #[arg(
             long = "ascending",
-            help = "Sort by date in ascending order (oldest first)",
-            long_help = "Sort sessions by date in ascending order (oldest first). Default is descending order (newest first)."
+            help = "Sort in ascending order (oldest or smallest first)",
+            long_help = "Sort sessions in ascending order (oldest or smallest first). Default is descending order (newest or largest first)."
         )]
         ascending: bool,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = SessionSort::Date,
+            help = "Column to sort by",
+            long_help = "Column to sort sessions by. Titles sort from A to Z, sessions without a value for the column are listed last."
+        )]
+        sort: SessionSort,
+
+        #[arg(
+            long,
+            value_name = "DATE_OR_AGE",
+            help = "Only list sessions modified since a date or within an age (e.g. 2025-01-31, 12h, 7d, 2w)"
+        )]
+        since: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "PATH",
+            num_args = 0..=1,
+            default_missing_value = ".",
+            help = "Only list sessions started in this directory or below it (default: the current directory)"
+        )]
+        project: Option<PathBuf>,
+
+        #[arg(
+            long = "tag",
+            value_name = "TAG",
+            help = "Only list sessions with this tag (can be repeated)",
+            long_help = "Only list sessions tagged with TAG. When given more than once, sessions must have all of the tags. Tags are generated automatically after the first few messages of a session."
+        )]
+        tags: Vec<String>,
     },
     #[command(about = "Remove sessions. Runs interactively if no ID or regex is provided.")]
     Remove {
@@ -122,6 +203,82 @@ enum SessionCommand {
         )]
         output: Option<PathBuf>,
     },
+    #[command(about = "Show a session's details and stored summary checkpoint")]
+    Info {
+        #[command(flatten)]
+        identifier: Option<Identifier>,
+
+        #[arg(long, help = "Print the session metadata as JSON")]
+        json: bool,
+    },
+    #[command(about = "Report problems in a session's conversation and how they are repaired")]
+    Doctor {
+        #[arg(help = "ID of the session to check")]
+        id: String,
+
+        #[arg(long, help = "Print the report as JSON")]
+        json: bool,
+    },
+    #[command(about = "Write a session to a bundle with secrets redacted, for bug reports")]
+    Share {
+        #[arg(help = "ID of the session to share")]
+        id: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Output file path (default: goose-session-<id>.json)"
+        )]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Replace the working directory and home directory in the bundle",
+            long_help = "Replace the session's working directory with <working_dir> and the home directory with ~ everywhere in the bundle, so the paths don't reveal user or project names."
+        )]
+        anonymize_paths: bool,
+    },
+    #[command(
+        about = "Remove old sessions",
+        long_about = "Remove sessions older than an age or beyond a number of sessions, newest kept first. Without a limit, applies the retention policy from config."
+    )]
+    Prune {
+        #[arg(
+            long,
+            value_name = "AGE",
+            help = "Remove sessions last modified longer ago than this (e.g. 90d, 12h, 2w)"
+        )]
+        older_than: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "NUMBER",
+            help = "Keep at most this many sessions, removing the oldest"
+        )]
+        max_sessions: Option<usize>,
+
+        #[arg(long, help = "Keep sessions that have tags")]
+        keep_tagged: bool,
+
+        #[arg(long, help = "Only list the sessions that would be removed")]
+        dry_run: bool,
+
+        #[arg(short, long, help = "Remove without asking for confirmation")]
+        yes: bool,
+    },
+    #[command(about = "Fork a session into a new one that shares its history")]
+    Fork {
+        #[arg(help = "ID of the session to fork")]
+        id: String,
+
+        #[arg(
+            long,
+            value_name = "MESSAGE_INDEX",
+            help = "Only keep the messages before this index (default: keep all)",
+            long_help = "Fork the conversation at this message index. Messages before the index are copied into the new session, later ones stay only in the original."
+        )]
+        at: Option<usize>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -229,6 +386,216 @@ pub enum BenchCommand {
         )]
         benchmark_dir: PathBuf,
     },
+
+    #[command(
+        about = "Compare providers and models on a suite of eval recipes",
+        long_about = "Run each eval recipe on each provider/model, collecting success rate, latency, turns and cost into a comparison table written as JSON and markdown"
+    )]
+    Compare {
+        #[arg(
+            long = "recipe",
+            value_name = "PATH",
+            required = true,
+            help = "Eval recipe file, or directory of recipe files (can be specified multiple times)",
+            action = clap::ArgAction::Append
+        )]
+        recipes: Vec<PathBuf>,
+
+        #[arg(
+            long = "model",
+            value_name = "PROVIDER:MODEL",
+            required = true,
+            help = "Provider and model to compare, e.g. anthropic:claude-sonnet-4 (can be specified multiple times)",
+            action = clap::ArgAction::Append
+        )]
+        models: Vec<String>,
+
+        #[arg(
+            long,
+            default_value = "1",
+            help = "How many times to run each recipe on each model"
+        )]
+        repeat: usize,
+
+        #[arg(
+            long = "max-turns",
+            value_name = "NUMBER",
+            help = "Maximum turns per run"
+        )]
+        max_turns: Option<u32>,
+
+        #[arg(
+            short,
+            long = "output-dir",
+            value_name = "DIR",
+            default_value = ".",
+            help = "Directory for compare-results.json and compare-results.md"
+        )]
+        output_dir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigureCommand {
+    /// Manage configuration profiles
+    #[command(about = "Manage configuration profiles")]
+    Profiles {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    #[command(about = "Print a configuration value")]
+    Get {
+        #[arg(help = "Configuration key, e.g. GOOSE_MODEL")]
+        key: String,
+
+        #[arg(long, help = "Read a secret instead of a configuration value")]
+        secret: bool,
+    },
+
+    #[command(about = "Set a configuration value")]
+    Set {
+        #[arg(help = "Configuration key, e.g. GOOSE_MODEL")]
+        key: String,
+
+        #[arg(help = "Value to set, parsed as JSON for keys that don't hold strings")]
+        value: String,
+
+        #[arg(long, help = "Store the value as a secret")]
+        secret: bool,
+    },
+
+    #[command(about = "Remove a configuration value")]
+    Unset {
+        #[arg(help = "Configuration key, e.g. GOOSE_MODEL")]
+        key: String,
+
+        #[arg(long, help = "Remove a secret instead of a configuration value")]
+        secret: bool,
+    },
+
+    #[command(about = "List configuration values")]
+    List {
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+
+        #[arg(long, help = "Also list the names of stored secrets")]
+        secrets: bool,
+    },
+
+    #[command(about = "Show configuration values")]
+    Show {
+        #[arg(
+            long,
+            help = "Show values as goose resolves them, merged from the environment, project .goose/config.yaml, profile and config file, with their sources"
+        )]
+        effective: bool,
+
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+    },
+
+    #[command(about = "Check the config file for unknown keys, wrong types and deprecated keys")]
+    Validate,
+
+    #[command(
+        about = "Export the configuration to an archive",
+        long_about = "Export config.yaml, permissions, custom providers and local recipes to a tar archive, to move them to another machine or share a team baseline"
+    )]
+    Export {
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Archive to write",
+            default_value = "goose-config.tar"
+        )]
+        file: PathBuf,
+
+        #[arg(
+            long,
+            help = "Include secrets, encrypted with a passphrase (prompted for, or read from GOOSE_CONFIG_PASSPHRASE)"
+        )]
+        include_secrets: bool,
+    },
+
+    #[command(
+        about = "Import a configuration archive",
+        long_about = "Import an archive made by `goose config export`. Configuration values, permissions and secrets are merged over the current ones"
+    )]
+    Import {
+        #[arg(long, value_name = "FILE", help = "Archive to import")]
+        file: PathBuf,
+
+        #[arg(
+            long,
+            help = "Replace existing custom providers and recipes with the same name"
+        )]
+        overwrite: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum WebhooksCommand {
+    #[command(about = "List the configured webhooks and the events they get")]
+    List {},
+    #[command(
+        about = "Send a test event to the webhooks",
+        long_about = "Send a test event to every configured webhook, or to the one named, and report whether it was delivered. Deliveries are retried like real events."
+    )]
+    Test {
+        /// Webhook to test
+        #[arg(help = "Name of the webhook to test (defaults to all of them)")]
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommand {
+    #[command(about = "List configuration profiles")]
+    List {},
+
+    #[command(about = "Create a profile from the current provider and model")]
+    Create {
+        #[arg(help = "Name of the profile")]
+        name: String,
+
+        #[arg(long, help = "Provider for the profile, defaults to the current one")]
+        provider: Option<String>,
+
+        #[arg(long, help = "Model for the profile, defaults to the current one")]
+        model: Option<String>,
+
+        #[arg(
+            long = "with-extensions",
+            help = "Copy the current extension settings into the profile"
+        )]
+        with_extensions: bool,
+    },
+
+    #[command(about = "Show the settings of a profile")]
+    Show {
+        #[arg(help = "Name of the profile")]
+        name: String,
+    },
+
+    #[command(about = "Delete a profile")]
+    Delete {
+        #[arg(help = "Name of the profile")]
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -241,35 +608,316 @@ enum RecipeCommand {
         recipe_name: String,
     },
 
-    /// Generate a deeplink for a recipe file
-    #[command(about = "Generate a deeplink for a recipe")]
-    Deeplink {
-        /// Recipe name to get recipe file to generate deeplink
+    /// Generate a deeplink for a recipe file
+    #[command(about = "Generate a deeplink for a recipe")]
+    Deeplink {
+        /// Recipe name to get recipe file to generate deeplink
+        #[arg(
+            help = "recipe name to get recipe file or full path to the recipe file to generate deeplink"
+        )]
+        recipe_name: String,
+    },
+
+    /// Schedule a recipe from its schedule block
+    #[command(about = "Schedule a recipe that declares a schedule block")]
+    Install {
+        /// Recipe name or path to the recipe file to install
+        #[arg(help = "recipe name to get recipe file or full path to the recipe file to install")]
+        recipe_name: String,
+
+        /// ID of the scheduled job, the recipe file name by default
+        #[arg(
+            long,
+            value_name = "ID",
+            help = "ID of the scheduled job (defaults to the recipe file name)"
+        )]
+        id: Option<String>,
+    },
+
+    /// List available recipes
+    #[command(about = "List available recipes")]
+    List {
+        /// Output format (text, json)
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+
+        /// Show verbose information including recipe descriptions
+        #[arg(
+            short,
+            long,
+            help = "Show verbose information including recipe descriptions"
+        )]
+        verbose: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsCommand {
+    /// Show how often tools were called
+    #[command(about = "Show how often tools were called, in this project and overall")]
+    Tools {
+        /// Output format (text, json)
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+
+        /// Maximum number of tools to list per scope
+        #[arg(
+            short,
+            long,
+            help = "Maximum number of tools to list per scope",
+            default_value = "20"
+        )]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum UsageCommand {
+    /// Report token usage and spend for a month
+    #[command(about = "Report token usage and spend per model for a month")]
+    Report {
+        /// Month to report on, the current month by default
+        #[arg(
+            long,
+            value_name = "YYYY-MM",
+            help = "Month to report on, e.g. 2025-01 (defaults to the current month)"
+        )]
+        month: Option<String>,
+
+        /// Output format (text, json)
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExtensionsCommand {
+    /// Install an extension from a package spec
+    #[command(
+        about = "Install an extension from an npm, uvx or docker spec",
+        long_about = "Install an MCP server packaged for npm (npm:<package>), uv (uvx:<package>) or docker (docker:<image>). Checks the runtime is installed and that the server answers before saving it.\n\nExample: goose extensions install npm:@modelcontextprotocol/server-filesystem -- /tmp"
+    )]
+    Install {
+        /// Package spec of the extension
+        #[arg(
+            value_name = "SPEC",
+            help = "npm:<package>, uvx:<package> or docker:<image>"
+        )]
+        spec: String,
+
+        /// Name of the extension
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Name of the extension (defaults to one derived from the package)"
+        )]
+        name: Option<String>,
+
+        /// Environment variables for the extension
+        #[arg(
+            long = "env",
+            value_name = "KEY=VALUE",
+            help = "Environment variable for the extension, stored as a secret (can be repeated)",
+            action = clap::ArgAction::Append
+        )]
+        env: Vec<String>,
+
+        /// Timeout in seconds
+        #[arg(
+            long,
+            value_name = "SECS",
+            help = "Timeout for the extension in seconds"
+        )]
+        timeout: Option<u64>,
+
+        /// Skip the test handshake
+        #[arg(long, help = "Save the extension without starting it first")]
+        skip_check: bool,
+
+        /// Arguments for the server
+        #[arg(
+            last = true,
+            value_name = "ARGS",
+            help = "Arguments passed to the server"
+        )]
+        args: Vec<String>,
+    },
+
+    /// Add an extension without prompts
+    #[command(
+        about = "Add an extension from flags or a YAML file",
+        long_about = "Add a stdio, sse or streamable_http extension without prompts, for scripts, dotfiles and CI. With --file, read one extension or a list of them written like the entries under `extensions` in config.yaml.\n\nExample: goose extensions add --name fetch --cmd uvx -- mcp-server-fetch"
+    )]
+    Add {
+        /// YAML file with the extensions to add
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "YAML file with one extension or a list of them",
+            conflicts_with_all = ["extension_type", "name", "cmd", "uri"]
+        )]
+        file: Option<PathBuf>,
+
+        /// Type of the extension
+        #[arg(
+            long = "type",
+            value_name = "TYPE",
+            value_parser = ["stdio", "sse", "streamable_http"],
+            help = "Type of the extension (default: stdio)"
+        )]
+        extension_type: Option<String>,
+
+        /// Name of the extension
+        #[arg(long, value_name = "NAME", help = "Name of the extension")]
+        name: Option<String>,
+
+        /// Command of a stdio extension
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            help = "Command that runs a stdio extension"
+        )]
+        cmd: Option<String>,
+
+        /// URI of a remote extension
+        #[arg(
+            long,
+            value_name = "URI",
+            help = "Endpoint of an sse or streamable_http extension"
+        )]
+        uri: Option<String>,
+
+        /// Environment variables for the extension
+        #[arg(
+            long = "env",
+            value_name = "KEY=VALUE",
+            help = "Environment variable for the extension, stored as a secret (can be repeated)",
+            action = clap::ArgAction::Append
+        )]
+        env: Vec<String>,
+
+        /// HTTP headers of a streamable_http extension
+        #[arg(
+            long = "header",
+            value_name = "NAME=VALUE",
+            help = "HTTP header sent to a streamable_http extension (can be repeated)",
+            action = clap::ArgAction::Append
+        )]
+        headers: Vec<String>,
+
+        /// Description of the extension
+        #[arg(long, value_name = "TEXT", help = "Description of the extension")]
+        description: Option<String>,
+
+        /// Timeout in seconds
+        #[arg(
+            long,
+            value_name = "SECS",
+            help = "Timeout for the extension in seconds"
+        )]
+        timeout: Option<u64>,
+
+        /// Add the extension disabled
+        #[arg(long, help = "Add the extension without enabling it")]
+        disabled: bool,
+
+        /// Arguments of a stdio extension
+        #[arg(last = true, value_name = "ARGS", help = "Arguments for the command")]
+        args: Vec<String>,
+    },
+
+    /// Remove an extension
+    #[command(about = "Remove an extension from the config")]
+    Remove {
+        #[arg(help = "Name of the extension")]
+        name: String,
+    },
+
+    /// Enable an extension
+    #[command(about = "Enable a configured extension")]
+    Enable {
+        #[arg(help = "Name of the extension")]
+        name: String,
+    },
+
+    /// Disable an extension
+    #[command(about = "Disable a configured extension")]
+    Disable {
+        #[arg(help = "Name of the extension")]
+        name: String,
+    },
+
+    /// List the configured extensions
+    #[command(about = "List the configured extensions")]
+    List {
+        #[arg(long, help = "Print the extensions and their configs as JSON")]
+        json: bool,
+    },
+
+    /// Manage the secrets of an extension
+    #[command(
+        about = "List, set or delete the secrets of an extension",
+        long_about = "List the environment variables an extension reads and whether their values are stored for the extension, stored globally or missing. Secrets set here are only passed to this extension.\n\nExample: goose extensions secrets github --set GITHUB_TOKEN=ghp_..."
+    )]
+    Secrets {
+        #[arg(help = "Name of the extension")]
+        name: String,
+
         #[arg(
-            help = "recipe name to get recipe file or full path to the recipe file to generate deeplink"
+            long = "set",
+            value_name = "KEY=VALUE",
+            help = "Store a secret for this extension (can be repeated)",
+            action = clap::ArgAction::Append
         )]
-        recipe_name: String,
+        set: Vec<String>,
+
+        #[arg(
+            long = "delete",
+            value_name = "KEY",
+            help = "Delete a secret of this extension (can be repeated)",
+            action = clap::ArgAction::Append
+        )]
+        delete: Vec<String>,
     },
 
-    /// List available recipes
-    #[command(about = "List available recipes")]
-    List {
-        /// Output format (text, json)
+    /// Show what an extension wrote to stderr
+    #[command(about = "Show what an extension wrote to stderr in a session")]
+    Logs {
+        /// Name of the extension
+        #[arg(help = "Name of the extension, e.g. developer")]
+        name: String,
+
+        /// Session to show the output of
         #[arg(
-            long = "format",
-            value_name = "FORMAT",
-            help = "Output format (text, json)",
-            default_value = "text"
+            long,
+            value_name = "SESSION_ID",
+            help = "Session to show the output of (defaults to the most recent one the extension ran in)"
         )]
-        format: String,
+        session: Option<String>,
 
-        /// Show verbose information including recipe descriptions
+        /// Number of lines to show
         #[arg(
-            short,
+            short = 'n',
             long,
-            help = "Show verbose information including recipe descriptions"
+            help = "Number of lines to show",
+            default_value = "50"
         )]
-        verbose: bool,
+        lines: usize,
     },
 }
 
@@ -277,7 +925,17 @@ enum RecipeCommand {
 enum Command {
     /// Configure Goose settings
     #[command(about = "Configure Goose settings")]
-    Configure {},
+    Configure {
+        #[command(subcommand)]
+        command: Option<ConfigureCommand>,
+    },
+
+    /// Inspect the configuration
+    #[command(about = "Inspect the configuration")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
 
     /// Display Goose configuration information
     #[command(about = "Display Goose information")]
@@ -287,9 +945,81 @@ enum Command {
         verbose: bool,
     },
 
+    /// Preview the hints the developer extension loads
+    #[command(
+        about = "Show the effective .goosehints",
+        long_about = "Show the hints goose loads for a directory: the global hints and every hints file from the repository root down to the directory, with @include directives expanded and the GOOSE_HINTS_MAX_TOKENS budget applied."
+    )]
+    Hints {
+        /// Directory to show the hints of, defaults to the current directory
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+
+    /// Manage webhooks
+    #[command(
+        about = "List and test webhooks",
+        long_about = "List and test the webhooks goose notifies of session starts and ends, tool approvals, exceeded budgets and failed scheduled runs. Webhooks are configured under `webhooks` in config.yaml."
+    )]
+    Webhooks {
+        #[command(subcommand)]
+        command: WebhooksCommand,
+    },
+
+    /// Manage extensions
+    #[command(about = "Install, add and manage extensions")]
+    Extensions {
+        #[command(subcommand)]
+        command: ExtensionsCommand,
+    },
+
+    /// Show session logs
+    #[command(
+        about = "Show the logs of a session",
+        long_about = "Show the logs of a session, by default the one that logged last. Tool failures and extension stderr are tagged with the id of the tool call they belong to."
+    )]
+    Logs {
+        /// Session to show the logs of
+        #[arg(
+            long,
+            value_name = "SESSION_ID",
+            help = "Session to show the logs of (defaults to the most recent)"
+        )]
+        session: Option<String>,
+
+        /// Keep printing new log lines
+        #[arg(short, long, help = "Keep printing log lines as they are written")]
+        follow: bool,
+
+        /// Least severe level to show
+        #[arg(
+            long,
+            value_name = "LEVEL",
+            default_value = "info",
+            value_parser = ["error", "warn", "info", "debug", "trace"],
+            help = "Least severe level to show"
+        )]
+        level: String,
+    },
+
     /// Manage system prompts and behaviors
-    #[command(about = "Run one of the mcp servers bundled with goose")]
-    Mcp { name: String },
+    #[command(
+        about = "Run one of the mcp servers bundled with goose",
+        long_about = "Run one of the mcp servers bundled with goose. Use `goose mcp serve` to expose goose itself as an mcp server, with tools to ask goose, run recipes and list sessions"
+    )]
+    Mcp {
+        name: String,
+
+        /// Serve over streamable HTTP instead of stdio
+        #[arg(
+            long = "http",
+            value_name = "ADDR",
+            num_args = 0..=1,
+            default_missing_value = "3001",
+            help = "With `serve`, listen for streamable HTTP instead of using stdio, on localhost at a port (3001 by default) or at an address. Clients send the key printed at startup, or set in GOOSE_MCP_SERVE__SECRET_KEY, in the X-Secret-Key header"
+        )]
+        http: Option<String>,
+    },
 
     /// Start or resume interactive chat sessions
     #[command(
@@ -328,6 +1058,14 @@ enum Command {
         )]
         debug: bool,
 
+        /// Print the system prompt and exit
+        #[arg(
+            long = "show-system-prompt",
+            help = "Print the fully rendered system prompt and exit",
+            long_help = "Start the session's extensions, print the system prompt goose would send including their instructions, and exit without starting a conversation."
+        )]
+        show_system_prompt: bool,
+
         /// Maximum number of consecutive identical tool calls allowed
         #[arg(
             long = "max-tool-repetitions",
@@ -385,6 +1123,96 @@ enum Command {
             value_delimiter = ','
         )]
         builtins: Vec<String>,
+
+        /// Files, images or URLs to attach to the first message
+        #[arg(
+            long = "attach",
+            value_name = "PATH_OR_URL",
+            help = "Attach a file, image or URL to the first message (can be specified multiple times)",
+            long_help = "Attach a file, image or URL to the first message of the session. Large images are downscaled and re-encoded to fit provider limits. Other content is previewed in the message and made available to goose as a resource. Can be specified multiple times.",
+            action = clap::ArgAction::Append
+        )]
+        attachments: Vec<String>,
+
+        /// Environment variables for this session only
+        #[arg(
+            long = "env",
+            value_name = "KEY=VALUE",
+            help = "Set an environment variable for the shell and extensions of this session (can be specified multiple times)",
+            long_help = "Set an environment variable for the developer shell and the stdio extensions of this session only, without changing the config. Can be specified multiple times.",
+            action = clap::ArgAction::Append,
+            value_parser = parse_key_val,
+        )]
+        env: Vec<(String, String)>,
+
+        /// Secret environment variables for this session only
+        #[arg(
+            long = "secret-env",
+            value_name = "KEY=VALUE",
+            help = "Like --env, with the value masked in the session transcript",
+            long_help = "Set an environment variable like --env, and mask its value wherever it would be recorded or sent to the model. Can be specified multiple times.",
+            action = clap::ArgAction::Append,
+            value_parser = parse_key_val,
+        )]
+        secret_env: Vec<(String, String)>,
+
+        /// Env files with variables for this session only
+        #[arg(
+            long = "env-file",
+            value_name = "FILE",
+            help = "Read environment variables for this session from a file (can be specified multiple times)",
+            long_help = "Read KEY=VALUE lines for this session from a file such as .env.goose. Blank lines and # comments are skipped, an 'export' prefix is allowed and a 'secret' prefix masks the value like --secret-env. Variables given with --env or --secret-env win over those from files.",
+            action = clap::ArgAction::Append
+        )]
+        env_files: Vec<PathBuf>,
+
+        /// More roots of the workspace for this session
+        #[arg(
+            long = "workspace-root",
+            value_name = "DIR",
+            help = "Add a root to the workspace of this session, such as a folder of a monorepo (can be specified multiple times)",
+            long_help = "Add a root to the workspace of this session, on top of the working directory and the workspace_roots of the project config. Goose is told about every root, searches them and applies the .gooseignore of each. Can be specified multiple times.",
+            action = clap::ArgAction::Append
+        )]
+        workspace_roots: Vec<PathBuf>,
+
+        /// Run the developer tools inside a dev container
+        #[arg(
+            long,
+            value_name = "NAME",
+            num_args = 0..=1,
+            default_missing_value = "auto",
+            help = "Run shell commands inside a dev container: a running container by name, or the one of the workspace's .devcontainer (default)",
+            long_help = "Route the developer shell through `docker exec` into a running container, so goose builds and tests in the same environment as you. Give the name of the container, or leave it out to use the container started for the .devcontainer of the workspace. File edits reach the container through its bind mounts. Same as setting GOOSE_DEVCONTAINER."
+        )]
+        devcontainer: Option<String>,
+    },
+
+    /// Start or resume a session in the full screen dashboard
+    #[command(
+        about = "Start or resume a session in a full screen terminal dashboard",
+        long_about = "Run a session in a full screen dashboard with panes for the conversation, live tool activity, token and cost usage, and the plan and TODO list. Uses the same sessions as `goose session`, so a conversation can be resumed in either."
+    )]
+    Tui {
+        /// Identifier for the chat session
+        #[command(flatten)]
+        identifier: Option<Identifier>,
+
+        /// Resume a previous session
+        #[arg(
+            short,
+            long,
+            help = "Resume a previous session (last used or specified by --name)"
+        )]
+        resume: bool,
+
+        /// Maximum number of turns (iterations) allowed in a single response
+        #[arg(
+            long = "max-turns",
+            value_name = "NUMBER",
+            help = "Maximum number of turns allowed without user input (default: 1000)"
+        )]
+        max_turns: Option<u32>,
     },
 
     /// Open the last project directory
@@ -453,6 +1281,26 @@ enum Command {
         )]
         params: Vec<(String, String)>,
 
+        /// Run the recipe once per row of a file
+        #[arg(
+            long = "batch",
+            value_name = "FILE",
+            help = "Run the recipe once per row of a CSV or JSONL file, binding its columns to recipe parameters",
+            long_help = "Run the recipe once per row of a CSV file (parameter names in the header) or a JSONL file (one object per line). Each row runs in its own session, and a results file with each row's status, token usage, estimated cost and output is written to --output-file, or next to the batch file as <name>.results.jsonl.",
+            requires = "recipe"
+        )]
+        batch: Option<PathBuf>,
+
+        /// How many batch rows run at once
+        #[arg(
+            long = "concurrency",
+            value_name = "NUMBER",
+            default_value = "4",
+            help = "How many batch rows run at the same time",
+            requires = "batch"
+        )]
+        concurrency: usize,
+
         /// Continue in interactive mode after processing input
         #[arg(
             short = 's',
@@ -572,6 +1420,26 @@ enum Command {
         )]
         quiet: bool,
 
+        /// Output format for headless runs
+        #[arg(
+            long = "output-format",
+            value_name = "FORMAT",
+            value_enum,
+            default_value = "text",
+            help = "How to report the run: rendered text, or ndjson with one JSON event per line",
+            long_help = "Output format for non-interactive runs. 'ndjson' writes one JSON event per line (messages, tool calls, tool results, notifications, usage and a final status), for scripts and CI to consume. Tool calls that need approval are denied."
+        )]
+        output_format: OutputFormat,
+
+        /// File for the recipe's structured output
+        #[arg(
+            long = "output-file",
+            value_name = "FILE",
+            help = "Write the recipe's final structured output to this file",
+            long_help = "Write the structured output the agent returned through the recipe's response schema to this file as JSON, so CI jobs can read the result without parsing the transcript. Only recipes with a response schema produce structured output. With --batch, this is where the results file is written."
+        )]
+        output_file: Option<PathBuf>,
+
         /// Scheduled job ID (used internally for scheduled executions)
         #[arg(
             long = "scheduled-job-id",
@@ -618,6 +1486,20 @@ enum Command {
         command: RecipeCommand,
     },
 
+    /// Usage statistics
+    #[command(about = "Show usage statistics")]
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommand,
+    },
+
+    /// Token usage and spend
+    #[command(about = "Show token usage and spend")]
+    Usage {
+        #[command(subcommand)]
+        command: UsageCommand,
+    },
+
     /// Manage scheduled jobs
     #[command(about = "Manage scheduled jobs", visible_alias = "sched")]
     Schedule {
@@ -697,19 +1579,73 @@ pub struct RecipeInfo {
     pub retry_config: Option<goose::agents::types::RetryConfig>,
 }
 
+/// Exit codes of headless runs, so CI jobs can tell why a run did not succeed. Errors that
+/// end the run early exit with 1
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_SUCCESS_CHECKS_FAILED: i32 = 2;
+const EXIT_MAX_TURNS_REACHED: i32 = 3;
+const EXIT_PROVIDER_ERROR: i32 = 4;
+
+fn outcome_exit_code(outcome: ReplyOutcome) -> i32 {
+    match outcome {
+        ReplyOutcome::Completed => EXIT_SUCCESS,
+        ReplyOutcome::SuccessChecksFailed => EXIT_SUCCESS_CHECKS_FAILED,
+        ReplyOutcome::MaxTurnsReached => EXIT_MAX_TURNS_REACHED,
+        ReplyOutcome::ProviderError => EXIT_PROVIDER_ERROR,
+    }
+}
+
+async fn write_final_output(session: &crate::Session, path: &std::path::Path) -> Result<()> {
+    match session.final_output().await {
+        Some(output) => std::fs::write(path, output)
+            .with_context(|| format!("Failed to write output file {}", path.display())),
+        None => {
+            eprintln!(
+                "Warning: no structured output was produced, {} was not written",
+                path.display()
+            );
+            Ok(())
+        }
+    }
+}
+
 pub async fn cli() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Some(profile) = &cli.profile {
+        ensure_profile_exists(profile)?;
+        // Read by the config on every lookup, and inherited by goose subprocesses
+        std::env::set_var("GOOSE_PROFILE", profile);
+    }
+
     // Track the current directory in projects.json
     if let Err(e) = crate::project_tracker::update_project_tracker(None, None) {
         eprintln!("Warning: Failed to update project tracker: {}", e);
     }
 
+    crate::session::apply_appearance();
+    if crate::session::plain_output_requested(cli.plain) {
+        // Also keeps colors out of the output of extensions and other subprocesses
+        std::env::set_var("NO_COLOR", "1");
+        crate::session::enable_plain_output();
+    }
+
+    // `goose config` reports on the config itself
+    if !matches!(cli.command, Some(Command::Config { .. })) {
+        warn_about_config_findings();
+    }
+
     let command_name = match &cli.command {
-        Some(Command::Configure {}) => "configure",
+        Some(Command::Configure { .. }) => "configure",
+        Some(Command::Config { .. }) => "config",
         Some(Command::Info { .. }) => "info",
+        Some(Command::Hints { .. }) => "hints",
+        Some(Command::Webhooks { .. }) => "webhooks",
+        Some(Command::Extensions { .. }) => "extensions",
+        Some(Command::Logs { .. }) => "logs",
         Some(Command::Mcp { .. }) => "mcp",
         Some(Command::Session { .. }) => "session",
+        Some(Command::Tui { .. }) => "tui",
         Some(Command::Project {}) => "project",
         Some(Command::Projects) => "projects",
         Some(Command::Run { .. }) => "run",
@@ -717,6 +1653,8 @@ pub async fn cli() -> Result<()> {
         Some(Command::Update { .. }) => "update",
         Some(Command::Bench { .. }) => "bench",
         Some(Command::Recipe { .. }) => "recipe",
+        Some(Command::Stats { .. }) => "stats",
+        Some(Command::Usage { .. }) => "usage",
         Some(Command::Web { .. }) => "web",
         None => "default_session",
     };
@@ -728,16 +1666,173 @@ pub async fn cli() -> Result<()> {
     );
 
     match cli.command {
-        Some(Command::Configure {}) => {
-            let _ = handle_configure().await;
+        Some(Command::Configure { command }) => {
+            match command {
+                Some(ConfigureCommand::Profiles { command }) => match command {
+                    ProfileCommand::List {} => handle_profiles_list()?,
+                    ProfileCommand::Create {
+                        name,
+                        provider,
+                        model,
+                        with_extensions,
+                    } => handle_profile_create(&name, provider, model, with_extensions)?,
+                    ProfileCommand::Show { name } => handle_profile_show(&name)?,
+                    ProfileCommand::Delete { name } => handle_profile_delete(&name)?,
+                },
+                None => {
+                    let _ = handle_configure().await;
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Config { command }) => {
+            match command {
+                ConfigCommand::Show { effective, format } => {
+                    handle_config_show(effective, &format)?
+                }
+                ConfigCommand::Get { key, secret } => handle_config_get(&key, secret)?,
+                ConfigCommand::Set { key, value, secret } => {
+                    handle_config_set(&key, &value, secret)?
+                }
+                ConfigCommand::Unset { key, secret } => handle_config_unset(&key, secret)?,
+                ConfigCommand::List { format, secrets } => handle_config_list(&format, secrets)?,
+                ConfigCommand::Validate => handle_config_validate()?,
+                ConfigCommand::Export {
+                    file,
+                    include_secrets,
+                } => handle_config_export(&file, include_secrets)?,
+                ConfigCommand::Import { file, overwrite } => {
+                    handle_config_import(&file, overwrite)?
+                }
+            }
             return Ok(());
         }
         Some(Command::Info { verbose }) => {
             handle_info(verbose)?;
             return Ok(());
         }
-        Some(Command::Mcp { name }) => {
-            let _ = run_server(&name).await;
+        Some(Command::Hints { dir }) => {
+            handle_hints(dir)?;
+            return Ok(());
+        }
+        Some(Command::Webhooks { command }) => {
+            match command {
+                WebhooksCommand::List {} => handle_webhooks_list()?,
+                WebhooksCommand::Test { name } => handle_webhooks_test(name).await?,
+            }
+            return Ok(());
+        }
+        Some(Command::Extensions { command }) => {
+            match command {
+                ExtensionsCommand::Install {
+                    spec,
+                    name,
+                    env,
+                    timeout,
+                    skip_check,
+                    args,
+                } => {
+                    handle_extension_install(
+                        &spec,
+                        InstallOptions {
+                            name,
+                            env,
+                            timeout,
+                            args,
+                            skip_check,
+                        },
+                    )
+                    .await?
+                }
+                ExtensionsCommand::Add {
+                    file,
+                    extension_type,
+                    name,
+                    cmd,
+                    uri,
+                    env,
+                    headers,
+                    description,
+                    timeout,
+                    disabled,
+                    args,
+                } => handle_extension_add(AddOptions {
+                    file,
+                    extension_type,
+                    name,
+                    cmd,
+                    args,
+                    uri,
+                    env,
+                    headers,
+                    description,
+                    timeout,
+                    disabled,
+                })?,
+                ExtensionsCommand::Remove { name } => handle_extension_remove(&name)?,
+                ExtensionsCommand::Enable { name } => handle_extension_set_enabled(&name, true)?,
+                ExtensionsCommand::Disable { name } => handle_extension_set_enabled(&name, false)?,
+                ExtensionsCommand::List { json } => handle_extension_list(json)?,
+                ExtensionsCommand::Secrets { name, set, delete } => {
+                    handle_extension_secrets(&name, &set, &delete)?
+                }
+                ExtensionsCommand::Logs {
+                    name,
+                    session,
+                    lines,
+                } => handle_extension_logs(&name, session, lines)?,
+            }
+            return Ok(());
+        }
+        Some(Command::Logs {
+            session,
+            follow,
+            level,
+        }) => {
+            handle_logs(session, follow, &level).await?;
+            return Ok(());
+        }
+        Some(Command::Mcp { name, http }) => {
+            if name == "serve" {
+                run_agent_server(http).await?;
+            } else {
+                let _ = run_server(&name).await;
+            }
+        }
+        Some(Command::Tui {
+            identifier,
+            resume,
+            max_turns,
+        }) => {
+            let mut session = build_session(SessionBuilderConfig {
+                identifier: identifier.map(extract_identifier),
+                resume,
+                no_session: false,
+                extensions: Vec::new(),
+                remote_extensions: Vec::new(),
+                streamable_http_extensions: Vec::new(),
+                builtins: Vec::new(),
+                extensions_override: None,
+                additional_system_prompt: None,
+                settings: None,
+                provider: None,
+                model: None,
+                debug: false,
+                max_tool_repetitions: None,
+                max_turns,
+                scheduled_job_id: None,
+                interactive: true,
+                quiet: false,
+                output_format: OutputFormat::Text,
+                sub_recipes: None,
+                final_output_response: None,
+                retry_config: None,
+                env: Vec::new(),
+                workspace_roots: Vec::new(),
+            })
+            .await;
+            session.tui().await?;
+            return Ok(());
         }
         Some(Command::Session {
             command,
@@ -745,20 +1840,47 @@ pub async fn cli() -> Result<()> {
             resume,
             history,
             debug,
+            show_system_prompt,
             max_tool_repetitions,
             max_turns,
             extensions,
             remote_extensions,
             streamable_http_extensions,
             builtins,
+            attachments,
+            env,
+            secret_env,
+            env_files,
+            workspace_roots,
+            devcontainer,
         }) => {
+            // The developer extension reads the choice of container from its environment
+            let env: Vec<(String, String)> = devcontainer
+                .map(|container| ("GOOSE_DEVCONTAINER".to_string(), container))
+                .into_iter()
+                .chain(env)
+                .collect();
             return match command {
                 Some(SessionCommand::List {
                     verbose,
                     format,
+                    json,
                     ascending,
+                    sort,
+                    since,
+                    project,
+                    tags,
                 }) => {
-                    handle_session_list(verbose, format, ascending)?;
+                    handle_session_list(SessionListOptions {
+                        verbose,
+                        format: if json { "json".to_string() } else { format },
+                        ascending,
+                        tags,
+                        since,
+                        project,
+                        sort,
+                    })
+                    .await?;
                     Ok(())
                 }
                 Some(SessionCommand::Remove { id, regex }) => {
@@ -782,6 +1904,76 @@ pub async fn cli() -> Result<()> {
                     crate::commands::session::handle_session_export(session_identifier, output)?;
                     Ok(())
                 }
+                Some(SessionCommand::Info { identifier, json }) => {
+                    crate::commands::session::handle_session_info(
+                        identifier.map(extract_identifier),
+                        json,
+                    )?;
+                    Ok(())
+                }
+                Some(SessionCommand::Doctor { id, json }) => {
+                    crate::commands::session::handle_session_doctor(id, json)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Share {
+                    id,
+                    output,
+                    anonymize_paths,
+                }) => {
+                    crate::commands::session::handle_session_share(id, output, anonymize_paths)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Prune {
+                    older_than,
+                    max_sessions,
+                    keep_tagged,
+                    dry_run,
+                    yes,
+                }) => {
+                    crate::commands::session::handle_session_prune(
+                        older_than,
+                        max_sessions,
+                        keep_tagged,
+                        dry_run,
+                        yes,
+                    )?;
+                    Ok(())
+                }
+                Some(SessionCommand::Fork { id, at }) => {
+                    crate::commands::session::handle_session_fork(id, at)?;
+                    Ok(())
+                }
+                None if show_system_prompt => {
+                    let env = collect_session_env(&env_files, env, secret_env)?;
+                    let session = build_session(SessionBuilderConfig {
+                        identifier: identifier.map(extract_identifier),
+                        resume,
+                        no_session: !resume,
+                        extensions,
+                        remote_extensions,
+                        streamable_http_extensions,
+                        builtins,
+                        extensions_override: None,
+                        additional_system_prompt: None,
+                        settings: None,
+                        provider: None,
+                        model: None,
+                        debug,
+                        max_tool_repetitions,
+                        max_turns,
+                        scheduled_job_id: None,
+                        interactive: false,
+                        quiet: true,
+                        output_format: OutputFormat::Text,
+                        sub_recipes: None,
+                        final_output_response: None,
+                        retry_config: None,
+                        env,
+                        workspace_roots,
+                    })
+                    .await;
+                    session.show_system_prompt().await
+                }
                 None => {
                     let session_start = std::time::Instant::now();
                     let session_type = if resume { "resumed" } else { "new" };
@@ -794,6 +1986,7 @@ pub async fn cli() -> Result<()> {
                     );
 
                     // Run session command by default
+                    let env = collect_session_env(&env_files, env, secret_env)?;
                     let mut session: crate::Session = build_session(SessionBuilderConfig {
                         identifier: identifier.map(extract_identifier),
                         resume,
@@ -813,12 +2006,22 @@ pub async fn cli() -> Result<()> {
                         scheduled_job_id: None,
                         interactive: true,
                         quiet: false,
+                        output_format: OutputFormat::Text,
                         sub_recipes: None,
                         final_output_response: None,
                         retry_config: None,
+                        env,
+                        workspace_roots,
                     })
                     .await;
 
+                    for path in &attachments {
+                        if let Err(e) = session.attach(path).await {
+                            eprintln!("Error: {:#}", e);
+                            std::process::exit(1);
+                        }
+                    }
+
                     // Render previous messages if resuming a session and history flag is set
                     if resume && history {
                         session.render_message_history();
@@ -890,14 +2093,36 @@ pub async fn cli() -> Result<()> {
             streamable_http_extensions,
             builtins,
             params,
+            batch,
+            concurrency,
             explain,
             render_recipe,
             scheduled_job_id,
             quiet,
+            output_format,
+            output_file,
             additional_sub_recipes,
             provider,
             model,
         }) => {
+            if let (Some(batch_file), Some(recipe)) = (&batch, &recipe) {
+                return run_batch(
+                    batch_file,
+                    BatchOptions {
+                        recipe: recipe.clone(),
+                        params,
+                        concurrency,
+                        results_file: output_file,
+                        target: RunTarget {
+                            provider,
+                            model,
+                            max_turns,
+                        },
+                    },
+                )
+                .await;
+            }
+
             let (input_config, recipe_info) = match (instructions, input_text, recipe) {
                 (Some(file), _, _) if file == "-" => {
                     let mut input = String::new();
@@ -967,6 +2192,7 @@ pub async fn cli() -> Result<()> {
                 }
             };
 
+            let heartbeat_job_id = scheduled_job_id.clone();
             let mut session = build_session(SessionBuilderConfig {
                 identifier: identifier.map(extract_identifier),
                 resume,
@@ -988,11 +2214,14 @@ pub async fn cli() -> Result<()> {
                 scheduled_job_id,
                 interactive, // Use the interactive flag from the Run command
                 quiet,
+                output_format,
                 sub_recipes: recipe_info.as_ref().and_then(|r| r.sub_recipes.clone()),
                 final_output_response: recipe_info
                     .as_ref()
                     .and_then(|r| r.final_output_response.clone()),
                 retry_config: recipe_info.as_ref().and_then(|r| r.retry_config.clone()),
+                env: Vec::new(),
+                workspace_roots: Vec::new(),
             })
             .await;
 
@@ -1013,7 +2242,12 @@ pub async fn cli() -> Result<()> {
                     "Headless session started"
                 );
 
+                let heartbeats =
+                    start_run_heartbeats(heartbeat_job_id, session.session_file()).await;
                 let result = session.headless(contents).await;
+                if let Some(heartbeats) = heartbeats {
+                    heartbeats.abort();
+                }
 
                 let session_duration = session_start.elapsed();
                 let exit_type = if result.is_ok() { "normal" } else { "error" };
@@ -1049,6 +2283,14 @@ pub async fn cli() -> Result<()> {
                 }
 
                 result?;
+
+                if let Some(path) = output_file {
+                    write_final_output(&session, &path).await?;
+                }
+                let exit_code = outcome_exit_code(session.reply_outcome().await);
+                if exit_code != EXIT_SUCCESS {
+                    std::process::exit(exit_code);
+                }
             } else {
                 eprintln!("Error: no text provided for prompt in headless mode");
                 std::process::exit(1);
@@ -1116,6 +2358,22 @@ pub async fn cli() -> Result<()> {
                 BenchCommand::GenerateLeaderboard { benchmark_dir } => {
                     MetricAggregator::generate_csv_from_benchmark_dir(&benchmark_dir)?
                 }
+                BenchCommand::Compare {
+                    recipes,
+                    models,
+                    repeat,
+                    max_turns,
+                    output_dir,
+                } => {
+                    run_compare(CompareOptions {
+                        recipes,
+                        models,
+                        repeat,
+                        max_turns,
+                        output_dir,
+                    })
+                    .await?
+                }
             }
             return Ok(());
         }
@@ -1127,12 +2385,31 @@ pub async fn cli() -> Result<()> {
                 RecipeCommand::Deeplink { recipe_name } => {
                     handle_deeplink(&recipe_name)?;
                 }
+                RecipeCommand::Install { recipe_name, id } => {
+                    crate::commands::recipe::handle_install(&recipe_name, id).await?;
+                }
                 RecipeCommand::List { format, verbose } => {
                     handle_list(&format, verbose)?;
                 }
             }
             return Ok(());
         }
+        Some(Command::Stats { command }) => {
+            match command {
+                StatsCommand::Tools { format, limit } => {
+                    handle_tool_stats(&format, limit)?;
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Usage { command }) => {
+            match command {
+                UsageCommand::Report { month, format } => {
+                    crate::commands::usage::handle_usage_report(month, &format).await?;
+                }
+            }
+            return Ok(());
+        }
         Some(Command::Web { port, host, open }) => {
             crate::commands::web::handle_web(port, host, open).await?;
             return Ok(());
@@ -1162,9 +2439,12 @@ pub async fn cli() -> Result<()> {
                     scheduled_job_id: None,
                     interactive: true, // Default case is always interactive
                     quiet: false,
+                    output_format: OutputFormat::Text,
                     sub_recipes: None,
                     final_output_response: None,
                     retry_config: None,
+                    env: Vec::new(),
+                    workspace_roots: Vec::new(),
                 })
                 .await;
                 if let Err(e) = session.interactive(None).await {
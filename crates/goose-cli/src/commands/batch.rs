@@ -0,0 +1,212 @@
+use anyhow::{anyhow, Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::recipe_run::{run_recipe_headless, RunTarget};
+
+/// Result of running the recipe for one row of a batch, written as one line of the results file
+#[derive(Debug, Serialize)]
+pub struct BatchRowResult {
+    pub row: usize,
+    pub params: BTreeMap<String, String>,
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub session_name: String,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub total_tokens: Option<i32>,
+    pub cost_usd: Option<f64>,
+    pub output: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// Everything a batch run needs besides its rows
+pub struct BatchOptions {
+    pub recipe: String,
+    pub params: Vec<(String, String)>,
+    pub concurrency: usize,
+    pub results_file: Option<PathBuf>,
+    pub target: RunTarget,
+}
+
+/// Read the rows of a batch file, one map of recipe parameters per row. `.jsonl` files hold a
+/// JSON object per line, anything else is read as CSV with the parameter names in its header
+pub fn read_batch_inputs(path: &Path) -> Result<Vec<BTreeMap<String, String>>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read batch file {}", path.display()))?;
+    let is_jsonl = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"));
+    if is_jsonl {
+        parse_jsonl_rows(&contents)
+    } else {
+        parse_csv_rows(&contents)
+    }
+}
+
+fn parse_jsonl_rows(contents: &str) -> Result<Vec<BTreeMap<String, String>>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let value: Value = serde_json::from_str(line)
+                .with_context(|| format!("Line {} is not valid JSON", index + 1))?;
+            let Value::Object(object) = value else {
+                return Err(anyhow!("Line {} is not a JSON object", index + 1));
+            };
+            Ok(object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (key, value)
+                })
+                .collect())
+        })
+        .collect()
+}
+
+fn parse_csv_rows(contents: &str) -> Result<Vec<BTreeMap<String, String>>> {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let headers = reader.headers()?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+/// Where results go when no file was given: next to the batch file, as `<name>.results.jsonl`
+fn default_results_file(batch_file: &Path) -> PathBuf {
+    let stem = batch_file
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("batch");
+    batch_file.with_file_name(format!("{}.results.jsonl", stem))
+}
+
+/// Run the recipe once per row of the batch file, at most `concurrency` rows at a time, and
+/// write one result per row to the results file
+pub async fn run_batch(batch_file: &Path, options: BatchOptions) -> Result<()> {
+    let rows = read_batch_inputs(batch_file)?;
+    if rows.is_empty() {
+        return Err(anyhow!("Batch file {} has no rows", batch_file.display()));
+    }
+    let results_file = options
+        .results_file
+        .clone()
+        .unwrap_or_else(|| default_results_file(batch_file));
+    let output_dir = tempfile::tempdir()?;
+    let batch_id = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+
+    eprintln!(
+        "Running {} rows of {} with concurrency {}",
+        rows.len(),
+        options.recipe,
+        options.concurrency
+    );
+
+    let results: Vec<BatchRowResult> = stream::iter(rows.into_iter().enumerate())
+        .map(|(index, row)| {
+            let session_name = format!("batch_{}_{}", batch_id, index + 1);
+            let output_file = output_dir.path().join(format!("row-{}.json", index + 1));
+            run_row(&options, index + 1, row, session_name, output_file)
+        })
+        .buffered(options.concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut lines = String::new();
+    for result in &results {
+        lines.push_str(&serde_json::to_string(result)?);
+        lines.push('\n');
+    }
+    std::fs::write(&results_file, lines)
+        .with_context(|| format!("Failed to write results file {}", results_file.display()))?;
+
+    let completed = results.iter().filter(|r| r.status == "completed").count();
+    let total_cost: f64 = results.iter().filter_map(|r| r.cost_usd).sum();
+    eprintln!(
+        "{} of {} rows completed, estimated cost ${:.4}. Results written to {}",
+        completed,
+        results.len(),
+        total_cost,
+        results_file.display()
+    );
+    Ok(())
+}
+
+async fn run_row(
+    options: &BatchOptions,
+    row: usize,
+    params: BTreeMap<String, String>,
+    session_name: String,
+    output_file: PathBuf,
+) -> BatchRowResult {
+    let params: BTreeMap<String, String> = options.params.iter().cloned().chain(params).collect();
+    let report = run_recipe_headless(
+        &options.recipe,
+        &params,
+        &session_name,
+        Some(&output_file),
+        &options.target,
+    )
+    .await;
+
+    // The structured output when the recipe has a response schema, its last answer otherwise
+    let output = match std::fs::read_to_string(&output_file) {
+        Ok(contents) => Some(serde_json::from_str(&contents).unwrap_or(Value::String(contents))),
+        Err(_) => report.last_assistant_text.clone().map(Value::String),
+    };
+    BatchRowResult {
+        row,
+        params,
+        status: report.status.clone(),
+        exit_code: report.exit_code,
+        session_name,
+        input_tokens: report.input_tokens,
+        output_tokens: report.output_tokens,
+        total_tokens: report.total_tokens,
+        cost_usd: report.cost_usd(&options.target).await,
+        output,
+        error: report.error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_batch_rows() {
+        let rows = parse_jsonl_rows("{\"name\": \"alice\", \"age\": 30}\n\n{\"name\": \"bob\"}\n")
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["name"], "alice");
+        assert_eq!(rows[0]["age"], "30");
+        assert!(parse_jsonl_rows("[1, 2]").is_err());
+
+        let rows = parse_csv_rows("name,greeting\nalice,\"hello, there\"\nbob,hi\n").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["greeting"], "hello, there");
+        assert_eq!(rows[1]["name"], "bob");
+
+        assert_eq!(
+            default_results_file(Path::new("/tmp/inputs.csv")),
+            PathBuf::from("/tmp/inputs.results.jsonl")
+        );
+    }
+}
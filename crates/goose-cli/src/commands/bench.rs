@@ -1,5 +1,5 @@
 use crate::session::build_session;
-use crate::session::SessionBuilderConfig;
+use crate::session::{OutputFormat, SessionBuilderConfig};
 use crate::{logging, session, Session};
 use async_trait::async_trait;
 use goose::conversation::Conversation;
@@ -50,9 +50,12 @@ pub async fn agent_generator(
         scheduled_job_id: None,
         max_turns: None,
         quiet: false,
+        output_format: OutputFormat::Text,
         sub_recipes: None,
         final_output_response: None,
         retry_config: None,
+        env: Vec::new(),
+        workspace_roots: Vec::new(),
     })
     .await;
 
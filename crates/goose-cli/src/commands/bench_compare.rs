@@ -0,0 +1,274 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::recipe_run::{run_recipe_headless, RunTarget};
+
+const RESULTS_JSON: &str = "compare-results.json";
+const RESULTS_MARKDOWN: &str = "compare-results.md";
+
+/// A suite of eval recipes and the provider/model matrix to run them on
+pub struct CompareOptions {
+    /// Recipe files, or directories of recipe files
+    pub recipes: Vec<PathBuf>,
+    /// Models as `provider:model`
+    pub models: Vec<String>,
+    pub repeat: usize,
+    pub max_turns: Option<u32>,
+    pub output_dir: PathBuf,
+}
+
+/// One run of one recipe on one model
+#[derive(Debug, Serialize)]
+pub struct CompareRun {
+    pub provider: String,
+    pub model: String,
+    pub recipe: String,
+    pub attempt: usize,
+    pub status: String,
+    pub duration_secs: f64,
+    pub turns: usize,
+    pub total_tokens: Option<i32>,
+    pub cost_usd: Option<f64>,
+}
+
+/// The runs of one model, summed up for the comparison table
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ModelSummary {
+    pub provider: String,
+    pub model: String,
+    pub runs: usize,
+    pub successes: usize,
+    pub success_rate: f64,
+    pub avg_duration_secs: f64,
+    pub avg_turns: f64,
+    pub total_tokens: i64,
+    pub total_cost_usd: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct CompareResults<'a> {
+    summaries: &'a [ModelSummary],
+    runs: &'a [CompareRun],
+}
+
+fn parse_model(model: &str) -> Result<(String, String)> {
+    model
+        .split_once(':')
+        .filter(|(provider, model)| !provider.is_empty() && !model.is_empty())
+        .map(|(provider, model)| (provider.to_string(), model.to_string()))
+        .ok_or_else(|| anyhow!("Invalid model '{}', expected provider:model", model))
+}
+
+/// The recipe files of the suite, with directories expanded to the recipes they contain
+fn collect_recipes(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut recipes = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found: Vec<PathBuf> = std::fs::read_dir(path)
+                .with_context(|| format!("Failed to read recipe directory {}", path.display()))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| matches!(ext, "yaml" | "yml" | "json"))
+                })
+                .collect();
+            found.sort();
+            recipes.extend(found);
+        } else {
+            recipes.push(path.clone());
+        }
+    }
+    if recipes.is_empty() {
+        return Err(anyhow!("No recipes found to compare"));
+    }
+    Ok(recipes)
+}
+
+fn summarize(runs: &[CompareRun]) -> Vec<ModelSummary> {
+    let mut by_model: BTreeMap<(String, String), Vec<&CompareRun>> = BTreeMap::new();
+    for run in runs {
+        by_model
+            .entry((run.provider.clone(), run.model.clone()))
+            .or_default()
+            .push(run);
+    }
+
+    by_model
+        .into_iter()
+        .map(|((provider, model), runs)| {
+            let count = runs.len();
+            let successes = runs.iter().filter(|r| r.status == "completed").count();
+            let costs: Vec<f64> = runs.iter().filter_map(|r| r.cost_usd).collect();
+            ModelSummary {
+                provider,
+                model,
+                runs: count,
+                successes,
+                success_rate: successes as f64 / count as f64,
+                avg_duration_secs: runs.iter().map(|r| r.duration_secs).sum::<f64>() / count as f64,
+                avg_turns: runs.iter().map(|r| r.turns).sum::<usize>() as f64 / count as f64,
+                total_tokens: runs
+                    .iter()
+                    .filter_map(|r| r.total_tokens)
+                    .map(i64::from)
+                    .sum(),
+                total_cost_usd: (!costs.is_empty()).then(|| costs.iter().sum()),
+            }
+        })
+        .collect()
+}
+
+fn render_markdown(summaries: &[ModelSummary]) -> String {
+    let mut table = String::from(
+        "| Provider | Model | Success rate | Avg latency (s) | Avg turns | Tokens | Cost (USD) |\n\
+         |---|---|---|---|---|---|---|\n",
+    );
+    for summary in summaries {
+        let cost = summary
+            .total_cost_usd
+            .map(|cost| format!("{:.4}", cost))
+            .unwrap_or_else(|| "-".to_string());
+        table.push_str(&format!(
+            "| {} | {} | {:.0}% ({}/{}) | {:.1} | {:.1} | {} | {} |\n",
+            summary.provider,
+            summary.model,
+            summary.success_rate * 100.0,
+            summary.successes,
+            summary.runs,
+            summary.avg_duration_secs,
+            summary.avg_turns,
+            summary.total_tokens,
+            cost
+        ));
+    }
+    table
+}
+
+fn recipe_label(recipe: &Path) -> String {
+    recipe
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("recipe")
+        .to_string()
+}
+
+/// Run every recipe of the suite on every model, `repeat` times each, and write the
+/// comparison as JSON and as a markdown table to the output directory
+pub async fn run_compare(options: CompareOptions) -> Result<()> {
+    let recipes = collect_recipes(&options.recipes)?;
+    let models = options
+        .models
+        .iter()
+        .map(|model| parse_model(model))
+        .collect::<Result<Vec<_>>>()?;
+    std::fs::create_dir_all(&options.output_dir)?;
+    let compare_id = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+
+    let mut runs = Vec::new();
+    for (provider, model) in &models {
+        let target = RunTarget {
+            provider: Some(provider.clone()),
+            model: Some(model.clone()),
+            max_turns: options.max_turns,
+        };
+        for recipe in &recipes {
+            let label = recipe_label(recipe);
+            for attempt in 1..=options.repeat.max(1) {
+                eprintln!(
+                    "Running {} on {}:{} ({}/{})",
+                    label, provider, model, attempt, options.repeat
+                );
+                let session_name = format!(
+                    "compare_{}_{}_{}_{}_{}",
+                    compare_id,
+                    provider,
+                    model.replace(['/', ':', '.'], "-"),
+                    label,
+                    attempt
+                );
+                let report = run_recipe_headless(
+                    &recipe.to_string_lossy(),
+                    &BTreeMap::new(),
+                    &session_name,
+                    None,
+                    &target,
+                )
+                .await;
+                runs.push(CompareRun {
+                    provider: provider.clone(),
+                    model: model.clone(),
+                    recipe: label.clone(),
+                    attempt,
+                    status: report.status.clone(),
+                    duration_secs: report.duration.as_secs_f64(),
+                    turns: report.turns,
+                    total_tokens: report.total_tokens,
+                    cost_usd: report.cost_usd(&target).await,
+                });
+            }
+        }
+    }
+
+    let summaries = summarize(&runs);
+    let json_path = options.output_dir.join(RESULTS_JSON);
+    std::fs::write(
+        &json_path,
+        serde_json::to_string_pretty(&CompareResults {
+            summaries: &summaries,
+            runs: &runs,
+        })?,
+    )?;
+    let markdown = render_markdown(&summaries);
+    std::fs::write(options.output_dir.join(RESULTS_MARKDOWN), &markdown)?;
+
+    println!("{}", markdown);
+    eprintln!("Results written to {}", options.output_dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(model: &str, status: &str, duration_secs: f64, cost_usd: Option<f64>) -> CompareRun {
+        CompareRun {
+            provider: "openai".to_string(),
+            model: model.to_string(),
+            recipe: "create_file".to_string(),
+            attempt: 1,
+            status: status.to_string(),
+            duration_secs,
+            turns: 4,
+            total_tokens: Some(100),
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn test_summarize_runs() {
+        let runs = vec![
+            run("gpt-4o", "completed", 10.0, Some(0.5)),
+            run("gpt-4o", "max_turns_reached", 20.0, Some(0.25)),
+            run("gpt-4o-mini", "completed", 4.0, None),
+        ];
+        let summaries = summarize(&runs);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].model, "gpt-4o");
+        assert_eq!(summaries[0].successes, 1);
+        assert_eq!(summaries[0].success_rate, 0.5);
+        assert_eq!(summaries[0].avg_duration_secs, 15.0);
+        assert_eq!(summaries[0].total_tokens, 200);
+        assert_eq!(summaries[0].total_cost_usd, Some(0.75));
+        assert_eq!(summaries[1].total_cost_usd, None);
+
+        let markdown = render_markdown(&summaries);
+        assert!(markdown.contains("| openai | gpt-4o | 50% (1/2) | 15.0 | 4.0 | 200 | 0.7500 |"));
+        assert!(markdown.contains("| openai | gpt-4o-mini | 100% (1/1) | 4.0 | 4.0 | 100 | - |"));
+
+        assert!(parse_model("anthropic:claude-sonnet-4").is_ok());
+        assert!(parse_model("claude-sonnet-4").is_err());
+    }
+}
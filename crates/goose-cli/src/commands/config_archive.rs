@@ -0,0 +1,343 @@
+use anyhow::{anyhow, Context, Result};
+use console::style;
+use etcetera::{choose_app_strategy, AppStrategy};
+use goose::config::custom_providers::custom_providers_dir;
+use goose::config::encrypted_secrets::{self, PassphraseSealed};
+use goose::config::Config;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::recipes::search_recipe::local_recipes_dir;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const CONFIG_FILE: &str = "config.yaml";
+const PERMISSION_FILE: &str = "permission.yaml";
+const SECRETS_FILE: &str = "secrets.enc";
+const CUSTOM_PROVIDERS_DIR: &str = "custom_providers";
+const RECIPES_DIR: &str = "recipes";
+
+const ARCHIVE_VERSION: u32 = 1;
+/// Key derivation of the secrets, the same as for the encrypted secrets file
+const SECRETS_KDF: &str = "argon2id";
+
+/// Lets scripts supply the secrets passphrase instead of being prompted for it
+const PASSPHRASE_ENV_VAR: &str = "GOOSE_CONFIG_PASSPHRASE";
+
+/// Describes what an exported archive holds
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveManifest {
+    version: u32,
+    created_at: String,
+    goose_version: String,
+    files: Vec<String>,
+    includes_secrets: bool,
+}
+
+/// Secrets encrypted with a key derived from the export passphrase
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedSecrets {
+    kdf: String,
+    #[serde(flatten)]
+    sealed: PassphraseSealed,
+}
+
+fn config_dir() -> Result<PathBuf> {
+    Ok(choose_app_strategy(crate::APP_STRATEGY.clone())
+        .context("goose requires a home dir")?
+        .config_dir())
+}
+
+fn encrypt_secrets(secrets: &HashMap<String, Value>, passphrase: &str) -> Result<EncryptedSecrets> {
+    let sealed = encrypted_secrets::seal_with_passphrase(secrets, passphrase)?;
+    Ok(EncryptedSecrets {
+        kdf: SECRETS_KDF.to_string(),
+        sealed,
+    })
+}
+
+fn decrypt_secrets(
+    encrypted: &EncryptedSecrets,
+    passphrase: &str,
+) -> Result<HashMap<String, Value>> {
+    if encrypted.kdf != SECRETS_KDF {
+        return Err(anyhow!(
+            "The secrets were encrypted with {}, which this goose can't read",
+            encrypted.kdf
+        ));
+    }
+    encrypted_secrets::open_with_passphrase(&encrypted.sealed, passphrase)
+        .map_err(|_| anyhow!("Could not decrypt secrets, is the passphrase correct?"))
+}
+
+fn read_passphrase(prompt: &str) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+    let passphrase = cliclack::password(prompt).mask('▪').interact()?;
+    if passphrase.is_empty() {
+        return Err(anyhow!("A passphrase is required to protect secrets"));
+    }
+    Ok(passphrase)
+}
+
+/// Add the files of `dir` to the archive under `name`, when the directory exists
+fn append_dir(
+    builder: &mut tar::Builder<File>,
+    dir: &Path,
+    name: &str,
+    files: &mut Vec<String>,
+) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    entries.sort();
+    for path in entries {
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let archive_path = format!("{}/{}", name, file_name);
+        builder.append_path_with_name(&path, &archive_path)?;
+        files.push(archive_path);
+    }
+    Ok(())
+}
+
+fn append_bytes(
+    builder: &mut tar::Builder<File>,
+    name: &str,
+    contents: &[u8],
+    files: &mut Vec<String>,
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, contents)?;
+    files.push(name.to_string());
+    Ok(())
+}
+
+/// Write the configuration, permissions, custom providers and local recipes to a tar archive,
+/// and with `include_secrets` the secrets too, encrypted with a passphrase
+pub fn handle_config_export(file: &Path, include_secrets: bool) -> Result<()> {
+    let config = Config::global();
+    let config_dir = config_dir()?;
+    let mut builder = tar::Builder::new(
+        File::create(file).with_context(|| format!("Failed to create {}", file.display()))?,
+    );
+    let mut files = Vec::new();
+
+    let values = config.load_values()?;
+    append_bytes(
+        &mut builder,
+        CONFIG_FILE,
+        serde_yaml::to_string(&values)?.as_bytes(),
+        &mut files,
+    )?;
+    let permission_path = config_dir.join(PERMISSION_FILE);
+    if permission_path.is_file() {
+        builder.append_path_with_name(&permission_path, PERMISSION_FILE)?;
+        files.push(PERMISSION_FILE.to_string());
+    }
+    append_dir(
+        &mut builder,
+        &custom_providers_dir(),
+        CUSTOM_PROVIDERS_DIR,
+        &mut files,
+    )?;
+    append_dir(&mut builder, &local_recipes_dir(), RECIPES_DIR, &mut files)?;
+
+    if include_secrets {
+        let secrets = config.load_secrets()?;
+        let passphrase = read_passphrase("Passphrase to encrypt secrets with")?;
+        let encrypted = encrypt_secrets(&secrets, &passphrase)?;
+        append_bytes(
+            &mut builder,
+            SECRETS_FILE,
+            &serde_json::to_vec_pretty(&encrypted)?,
+            &mut files,
+        )?;
+    }
+
+    let manifest = ArchiveManifest {
+        version: ARCHIVE_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        goose_version: env!("CARGO_PKG_VERSION").to_string(),
+        files: files.clone(),
+        includes_secrets: include_secrets,
+    };
+    append_bytes(
+        &mut builder,
+        MANIFEST_FILE,
+        &serde_json::to_vec_pretty(&manifest)?,
+        &mut files,
+    )?;
+    builder.finish()?;
+
+    println!(
+        "Exported {} files to {}{}",
+        manifest.files.len(),
+        file.display(),
+        if include_secrets {
+            " (secrets encrypted)"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Copy the files of an unpacked archive directory into `target`, keeping existing files
+/// unless `overwrite` is set. Returns how many were written
+fn import_dir(source: &Path, target: &Path, overwrite: bool) -> Result<usize> {
+    if !source.is_dir() {
+        return Ok(0);
+    }
+    std::fs::create_dir_all(target)?;
+    let mut imported = 0;
+    for entry in std::fs::read_dir(source)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+        let destination = target.join(file_name);
+        if destination.exists() && !overwrite {
+            println!(
+                "  {} {} already exists",
+                style("skipped").yellow(),
+                destination.display()
+            );
+            continue;
+        }
+        std::fs::copy(&path, &destination)?;
+        imported += 1;
+    }
+    Ok(imported)
+}
+
+/// Merge the top level entries of an imported YAML mapping file into an existing one
+fn merge_yaml_file(imported: &Path, target: &Path) -> Result<()> {
+    let imported: serde_yaml::Mapping = serde_yaml::from_str(&std::fs::read_to_string(imported)?)?;
+    let mut merged: serde_yaml::Mapping = if target.is_file() {
+        serde_yaml::from_str(&std::fs::read_to_string(target)?).unwrap_or_default()
+    } else {
+        serde_yaml::Mapping::new()
+    };
+    merged.extend(imported);
+    std::fs::write(target, serde_yaml::to_string(&merged)?)?;
+    Ok(())
+}
+
+/// Apply an archive made by `goose config export`. Configuration values, permissions and
+/// secrets are merged over the current ones; provider and recipe files are only replaced
+/// with `overwrite`
+pub fn handle_config_import(file: &Path, overwrite: bool) -> Result<()> {
+    let config = Config::global();
+    let unpacked = tempfile::tempdir()?;
+    tar::Archive::new(
+        File::open(file).with_context(|| format!("Failed to open {}", file.display()))?,
+    )
+    .unpack(unpacked.path())
+    .with_context(|| format!("{} is not a goose config archive", file.display()))?;
+
+    let manifest: ArchiveManifest = serde_json::from_str(
+        &std::fs::read_to_string(unpacked.path().join(MANIFEST_FILE))
+            .with_context(|| format!("{} has no {}", file.display(), MANIFEST_FILE))?,
+    )?;
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(anyhow!(
+            "{} was exported by a newer goose ({}), upgrade to import it",
+            file.display(),
+            manifest.goose_version
+        ));
+    }
+
+    let imported_config = unpacked.path().join(CONFIG_FILE);
+    if imported_config.is_file() {
+        let imported: HashMap<String, Value> =
+            serde_yaml::from_str(&std::fs::read_to_string(&imported_config)?)?;
+        let mut values = config.load_values()?;
+        let count = imported.len();
+        values.extend(imported);
+        config.save_values(values)?;
+        println!(
+            "  {} {} configuration values",
+            style("merged").green(),
+            count
+        );
+    }
+
+    let config_dir = config_dir()?;
+    let imported_permissions = unpacked.path().join(PERMISSION_FILE);
+    if imported_permissions.is_file() {
+        merge_yaml_file(&imported_permissions, &config_dir.join(PERMISSION_FILE))?;
+        println!("  {} permissions", style("merged").green());
+    }
+
+    let providers = import_dir(
+        &unpacked.path().join(CUSTOM_PROVIDERS_DIR),
+        &custom_providers_dir(),
+        overwrite,
+    )?;
+    if providers > 0 {
+        goose::providers::refresh_custom_providers()?;
+        println!(
+            "  {} {} custom providers",
+            style("imported").green(),
+            providers
+        );
+    }
+    let recipes = import_dir(
+        &unpacked.path().join(RECIPES_DIR),
+        &local_recipes_dir(),
+        overwrite,
+    )?;
+    if recipes > 0 {
+        println!("  {} {} recipes", style("imported").green(), recipes);
+    }
+
+    let secrets_path = unpacked.path().join(SECRETS_FILE);
+    if secrets_path.is_file() {
+        let encrypted: EncryptedSecrets =
+            serde_json::from_str(&std::fs::read_to_string(&secrets_path)?)?;
+        let passphrase = read_passphrase("Passphrase the secrets were exported with")?;
+        let secrets = decrypt_secrets(&encrypted, &passphrase)?;
+        let count = secrets.len();
+        for (key, value) in secrets {
+            config.set_secret(&key, value)?;
+        }
+        println!("  {} {} secrets", style("imported").green(), count);
+    }
+
+    println!("Imported {}", file.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secrets_round_trip() {
+        let secrets = HashMap::from([(
+            "OPENAI_API_KEY".to_string(),
+            Value::String("sk-test".to_string()),
+        )]);
+        let encrypted = encrypt_secrets(&secrets, "correct horse").unwrap();
+        assert!(!encrypted.sealed.ciphertext.contains("sk-test"));
+
+        assert_eq!(
+            decrypt_secrets(&encrypted, "correct horse").unwrap(),
+            secrets
+        );
+        assert!(decrypt_secrets(&encrypted, "wrong horse").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::{anyhow, Result};
+use console::style;
+use goose::config::schema::{self, known_key, parse_value, type_error};
+use goose::config::{Config, ConfigError};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+fn print_value(value: &Value) {
+    match value {
+        Value::String(s) => println!("{}", s),
+        other => println!("{}", other),
+    }
+}
+
+/// Print a configuration value or secret as goose resolves it, failing when it isn't set so
+/// scripts can tell the difference
+pub fn handle_config_get(key: &str, secret: bool) -> Result<()> {
+    let config = Config::global();
+    let value = if secret {
+        config.get_secret::<Value>(key)
+    } else {
+        config.get_param::<Value>(key)
+    };
+    match value {
+        Ok(value) => {
+            print_value(&value);
+            Ok(())
+        }
+        Err(ConfigError::NotFound(_)) => Err(anyhow!("{} is not set", key)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Store a configuration value or secret. Values of known keys are checked against the
+/// config schema before anything is written
+pub fn handle_config_set(key: &str, value: &str, secret: bool) -> Result<()> {
+    let config = Config::global();
+    if secret {
+        config.set_secret(key, Value::String(value.to_string()))?;
+        println!("Stored secret {}", key);
+        return Ok(());
+    }
+
+    let value = parse_value(key, value);
+    if let Some(message) = type_error(key, &value) {
+        return Err(anyhow!(message));
+    }
+    if known_key(key).is_none() && !schema::provider_keys().contains(key) {
+        eprintln!(
+            "{}: {} is not a known configuration key",
+            style("warning").yellow(),
+            key
+        );
+    }
+    config.set_param(key, value)?;
+    println!("Set {}", key);
+    Ok(())
+}
+
+pub fn handle_config_unset(key: &str, secret: bool) -> Result<()> {
+    let config = Config::global();
+    if secret {
+        config.delete_secret(key)?;
+    } else {
+        config.delete(key)?;
+    }
+    println!("Unset {}", key);
+    Ok(())
+}
+
+/// List the values in the config file, and the names of stored secrets without their values
+pub fn handle_config_list(format: &str, secrets: bool) -> Result<()> {
+    let config = Config::global();
+    let values: BTreeMap<String, Value> = config.load_values()?.into_iter().collect();
+    let secret_names: Vec<String> = if secrets {
+        let mut names: Vec<String> = config.load_secrets()?.into_keys().collect();
+        names.sort();
+        names
+    } else {
+        Vec::new()
+    };
+
+    if format == "json" {
+        let mut output = serde_json::json!({ "values": values });
+        if secrets {
+            output["secrets"] = serde_json::json!(secret_names);
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    for (key, value) in &values {
+        let value = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        println!("{} = {}", key, value);
+    }
+    for name in secret_names {
+        println!("{} = {}", name, style("********").dim());
+    }
+    Ok(())
+}
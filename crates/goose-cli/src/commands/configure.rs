@@ -1,23 +1,34 @@
 use crate::recipes::github_recipe::GOOSE_RECIPE_GITHUB_REPO_CONFIG_KEY;
+use crate::session::{
+    get_appearance, get_theme, preview_markdown, set_appearance, set_theme, SpinnerStyle, Theme,
+    ASCII_KEY, BAT_CACHE_DIR_KEY, BAT_THEME_KEY, DEFAULT_NOTIFY_AFTER_SECONDS, NOTIFICATIONS_KEY,
+    NOTIFY_AFTER_KEY, RENDER_MARKDOWN_KEY, SPINNER_KEY,
+};
 use cliclack::spinner;
 use console::style;
 use goose::agents::extension::ToolInfo;
 use goose::agents::extension_manager::get_parameter_names;
 use goose::agents::platform_tools::{
     PLATFORM_LIST_RESOURCES_TOOL_NAME, PLATFORM_READ_RESOURCE_TOOL_NAME,
+    PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME,
 };
-use goose::agents::Agent;
 use goose::agents::{extension::Envs, ExtensionConfig};
-use goose::config::custom_providers::CustomProviderConfig;
+use goose::agents::{Agent, ExtensionManager};
+use goose::config::custom_providers::{
+    CustomProviderAuth, CustomProviderConfig, CustomProviderOptions,
+};
 use goose::config::extensions::name_to_key;
 use goose::config::permission::PermissionLevel;
 use goose::config::{
     Config, ConfigError, ExperimentManager, ExtensionConfigManager, ExtensionEntry,
-    PermissionManager,
+    ExtensionRegistry, PermissionManager,
 };
 use goose::conversation::message::Message;
 use goose::model::ModelConfig;
+use goose::providers::base::ModelInfo;
+use goose::providers::ollama::OllamaProvider;
 use goose::providers::{create, providers};
+use indicatif::{ProgressBar, ProgressStyle};
 use rmcp::model::{Tool, ToolAnnotations};
 use rmcp::object;
 use serde_json::Value;
@@ -128,6 +139,8 @@ pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
                         // This operation is best-effort and errors are ignored
                         ExtensionConfigManager::set(ExtensionEntry {
                             enabled: true,
+                            eager: false,
+                            sampling: None,
                             config: ExtensionConfig::Builtin {
                                 name: "developer".to_string(),
                                 display_name: Some(goose::config::DEFAULT_DISPLAY_NAME.to_string()),
@@ -254,6 +267,11 @@ pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
                 "Toggle Extensions",
                 "Enable or disable connected extensions",
             )
+            .item(
+                "tools",
+                "Toggle Extension Tools",
+                "Enable or disable single tools of an extension",
+            )
             .item("remove", "Remove Extension", "Remove an extension")
             .item(
                 "settings",
@@ -264,11 +282,12 @@ pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
 
         match action {
             "toggle" => toggle_extensions_dialog(),
-            "add" => configure_extensions_dialog(),
+            "tools" => toggle_extension_tools_dialog().await,
+            "add" => configure_extensions_dialog().await,
             "remove" => remove_extension_dialog(),
             "settings" => configure_settings_dialog().await.and(Ok(())),
             "providers" => configure_provider_dialog().await.and(Ok(())),
-            "custom_providers" => configure_custom_provider_dialog(),
+            "custom_providers" => configure_custom_provider_dialog().await,
             _ => unreachable!(),
         }
     }
@@ -447,7 +466,8 @@ pub async fn configure_provider_dialog() -> Result<bool, Box<dyn Error>> {
     // Get global config instance
     let config = Config::global();
 
-    // Get all available providers and their metadata
+    // Get all available providers and their metadata, with fresh custom provider model lists
+    goose::config::custom_providers::refresh_stale_models().await;
     let available_providers = providers();
 
     // Create selection items from provider metadata
@@ -573,6 +593,10 @@ pub async fn configure_provider_dialog() -> Result<bool, Box<dyn Error>> {
         }
     }
 
+    if provider_name == "gcp_vertex_ai" {
+        configure_gcp_auth()?;
+    }
+
     // Attempt to fetch supported models for this provider
     let spin = spinner();
     spin.start("Attempting to fetch supported models...");
@@ -590,6 +614,9 @@ pub async fn configure_provider_dialog() -> Result<bool, Box<dyn Error>> {
             cliclack::outro(style(e.to_string()).on_red().white())?;
             return Ok(false);
         }
+        Ok(Some(models)) if provider_name == "ollama" => {
+            select_or_pull_ollama_model(&models, provider_meta).await?
+        }
         Ok(Some(models)) => select_model_from_list(&models, provider_meta)?,
         Ok(None) => {
             let default_model =
@@ -655,6 +682,9 @@ pub async fn configure_provider_dialog() -> Result<bool, Box<dyn Error>> {
             // Update config with new values only if the test succeeds
             config.set_param("GOOSE_PROVIDER", Value::String(provider_name.to_string()))?;
             config.set_param("GOOSE_MODEL", Value::String(model.clone()))?;
+            if provider_name == "ollama" {
+                show_ollama_load(&model).await;
+            }
             cliclack::outro("Configuration saved successfully")?;
             Ok(true)
         }
@@ -666,6 +696,176 @@ pub async fn configure_provider_dialog() -> Result<bool, Box<dyn Error>> {
     }
 }
 
+/// Choose how to authenticate with Google Cloud: application default credentials, a service
+/// account key or workload identity federation, optionally impersonating a service account
+fn configure_gcp_auth() -> Result<(), Box<dyn Error>> {
+    let config = Config::global();
+    let current = if config
+        .get_param::<String>("GCP_SERVICE_ACCOUNT_KEY_PATH")
+        .is_ok()
+    {
+        "GCP_SERVICE_ACCOUNT_KEY_PATH"
+    } else if config
+        .get_param::<String>("GCP_WORKLOAD_IDENTITY_CONFIG_PATH")
+        .is_ok()
+    {
+        "GCP_WORKLOAD_IDENTITY_CONFIG_PATH"
+    } else {
+        ""
+    };
+    let auth_key = cliclack::select("How should goose authenticate with Google Cloud?")
+        .initial_value(current)
+        .item(
+            "",
+            "Application default credentials",
+            "gcloud auth application-default login, or the metadata server",
+        )
+        .item(
+            "GCP_SERVICE_ACCOUNT_KEY_PATH",
+            "Service account key",
+            "A JSON key file",
+        )
+        .item(
+            "GCP_WORKLOAD_IDENTITY_CONFIG_PATH",
+            "Workload identity federation",
+            "A credential configuration file",
+        )
+        .interact()?;
+
+    for key in [
+        "GCP_SERVICE_ACCOUNT_KEY_PATH",
+        "GCP_WORKLOAD_IDENTITY_CONFIG_PATH",
+    ] {
+        if key != auth_key {
+            let _ = config.delete(key);
+        }
+    }
+    if !auth_key.is_empty() {
+        let existing: String = config.get_param(auth_key).unwrap_or_default();
+        let path: String = cliclack::input("Path to the file:")
+            .default_input(&existing)
+            .validate(|path: &String| {
+                if std::path::Path::new(path).is_file() {
+                    Ok(())
+                } else {
+                    Err("File not found")
+                }
+            })
+            .interact()?;
+        config.set_param(auth_key, Value::String(path))?;
+    }
+
+    let existing: String = config
+        .get_param("GCP_IMPERSONATE_SERVICE_ACCOUNT")
+        .unwrap_or_default();
+    let service_account: String =
+        cliclack::input("Service account to impersonate (leave empty for none):")
+            .default_input(&existing)
+            .required(false)
+            .interact()?;
+    if service_account.trim().is_empty() {
+        let _ = config.delete("GCP_IMPERSONATE_SERVICE_ACCOUNT");
+    } else {
+        config.set_param(
+            "GCP_IMPERSONATE_SERVICE_ACCOUNT",
+            Value::String(service_account.trim().to_string()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Pick one of the models pulled on the Ollama server, or pull another one
+async fn select_or_pull_ollama_model(
+    models: &[String],
+    provider_meta: &goose::providers::base::ProviderMetadata,
+) -> Result<String, Box<dyn Error>> {
+    let model = if models.is_empty() {
+        let _ = cliclack::log::info("No models have been pulled on the Ollama server yet");
+        cliclack::input("Enter a model to pull:")
+            .default_input(&provider_meta.default_model)
+            .interact()?
+    } else {
+        let mut items: Vec<(String, String, &str)> =
+            models.iter().map(|m| (m.clone(), m.clone(), "")).collect();
+        items.push((
+            String::new(),
+            "Pull another model...".to_string(),
+            "Download a model from the Ollama library",
+        ));
+        let selection = cliclack::select("Select a model:")
+            .items(&items)
+            .interact()?;
+        if !selection.is_empty() {
+            return Ok(selection);
+        }
+        cliclack::input("Enter a model to pull:")
+            .placeholder(&provider_meta.default_model)
+            .interact()?
+    };
+
+    if models.contains(&model) || models.contains(&format!("{}:latest", model)) {
+        return Ok(model);
+    }
+    pull_ollama_model(&model).await?;
+    Ok(model)
+}
+
+/// Pull a model on the Ollama server, showing the download progress
+async fn pull_ollama_model(model: &str) -> Result<(), Box<dyn Error>> {
+    let provider = OllamaProvider::from_env(ModelConfig::new(model)?)?;
+    let bar = ProgressBar::new(0).with_style(ProgressStyle::with_template(
+        "{msg} {bar:40} {bytes}/{total_bytes}",
+    )?);
+    bar.set_message(format!("Pulling {}", model));
+
+    let result = provider
+        .pull_model(model, |progress| {
+            match (progress.total, progress.completed) {
+                (Some(total), completed) => {
+                    bar.set_length(total);
+                    bar.set_position(completed.unwrap_or(0));
+                }
+                _ => bar.set_message(format!("{} ({})", model, progress.status)),
+            }
+        })
+        .await;
+    bar.finish_and_clear();
+    result?;
+
+    let _ = cliclack::log::success(format!("Pulled {}", model));
+    Ok(())
+}
+
+/// Show whether the model the configuration check loaded runs on the GPU or the CPU
+async fn show_ollama_load(model: &str) {
+    let Ok(model_config) = ModelConfig::new(model) else {
+        return;
+    };
+    let Ok(provider) = OllamaProvider::from_env(model_config) else {
+        return;
+    };
+    match provider.loaded_models().await {
+        Ok(loaded) => {
+            for loaded_model in loaded
+                .iter()
+                .filter(|m| m.name == model || m.name == format!("{}:latest", model))
+            {
+                let _ = cliclack::log::info(format!(
+                    "{} is loaded on {}",
+                    loaded_model.name,
+                    loaded_model.processor()
+                ));
+                if loaded_model.gpu_percent() < 100 {
+                    let _ = cliclack::log::warning(
+                        "Part of the model runs on the CPU, responses will be slower. A smaller model or OLLAMA_NUM_CTX may help",
+                    );
+                }
+            }
+        }
+        Err(e) => tracing::debug!("Failed to read the models loaded by Ollama: {}", e),
+    }
+}
+
 /// Configure extensions that can be used with goose
 /// Dialog for toggling which extensions are enabled/disabled
 pub fn toggle_extensions_dialog() -> Result<(), Box<dyn Error>> {
@@ -720,8 +920,181 @@ pub fn toggle_extensions_dialog() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
+/// Start an extension to list its tools and let the user choose which of them the agent sees
+pub async fn toggle_extension_tools_dialog() -> Result<(), Box<dyn Error>> {
+    let mut extensions = ExtensionConfigManager::get_all()?;
+    if extensions.is_empty() {
+        cliclack::outro(
+            "No extensions configured yet. Run configure and add some extensions first.",
+        )?;
+        return Ok(());
+    }
+    extensions.sort_by(|a, b| a.config.name().cmp(&b.config.name()));
+
+    let key = cliclack::select("Choose an extension to toggle tools of")
+        .items(
+            &extensions
+                .iter()
+                .map(|entry| (entry.config.key(), entry.config.name(), ""))
+                .collect::<Vec<_>>(),
+        )
+        .interact()?;
+    let config = extensions
+        .into_iter()
+        .find(|entry| entry.config.key() == key)
+        .map(|entry| entry.config)
+        .expect("selected extension is configured");
+
+    // Start it without its allowlist so disabled tools are listed too
+    let mut unrestricted = config.clone();
+    unrestricted.set_available_tools(Vec::new());
+    let spin = spinner();
+    spin.start(format!("Starting {} to list its tools", config.name()));
+    let manager = ExtensionManager::default();
+    let tools = match manager.add_extension(unrestricted).await {
+        Ok(_) => manager.list_all_tools(&config.name()).await,
+        Err(e) => Err(e),
+    };
+    let mut tools: Vec<String> = match tools {
+        Ok(tools) => {
+            spin.stop(style(format!("{} started", config.name())).green());
+            tools
+                .into_iter()
+                .map(|tool| tool.name.to_string())
+                .collect()
+        }
+        Err(e) => {
+            spin.stop(style(format!("Failed to start {}: {}", config.name(), e)).red());
+            return Ok(());
+        }
+    };
+    tools.sort();
+    if tools.is_empty() {
+        cliclack::outro(format!("{} has no tools", config.name()))?;
+        return Ok(());
+    }
+
+    let enabled: Vec<&String> = tools
+        .iter()
+        .filter(|tool| config.is_tool_available(tool))
+        .collect();
+    let selected =
+        cliclack::multiselect("enable tools: (use \"space\" to toggle and \"enter\" to submit)")
+            .required(true)
+            .items(
+                &tools
+                    .iter()
+                    .map(|tool| (tool, tool.as_str(), MULTISELECT_VISIBILITY_HINT))
+                    .collect::<Vec<_>>(),
+            )
+            .initial_values(enabled)
+            .interact()?;
+
+    let available_tools = if selected.len() == tools.len() {
+        Vec::new()
+    } else {
+        selected.into_iter().cloned().collect()
+    };
+    ExtensionConfigManager::set_available_tools(&key, available_tools)?;
+
+    cliclack::outro(format!("Tools of {} updated successfully", config.name()))?;
+    Ok(())
+}
+
+/// Pick an extension from the registry, ask for the environment variables it reads and add it
+async fn configure_registry_extension_dialog() -> Result<(), Box<dyn Error>> {
+    let spin = spinner();
+    spin.start("Loading the extension registry");
+    let registry = match ExtensionRegistry::load().await {
+        Ok(registry) => {
+            spin.stop(style("Extension registry loaded").green());
+            registry
+        }
+        Err(e) => {
+            spin.stop(style(e.to_string()).red());
+            return Ok(());
+        }
+    };
+
+    let query: String = cliclack::input("Search for an extension:")
+        .placeholder("leave empty to list all of them")
+        .required(false)
+        .interact()?;
+    let matches = registry.search(&query);
+    if matches.is_empty() {
+        cliclack::outro(format!("No extensions in the registry match '{}'", query))?;
+        return Ok(());
+    }
+
+    let mut select = cliclack::select("Which extension would you like to install?");
+    for extension in &matches {
+        select = select.item(
+            extension.id.as_str(),
+            extension.name.as_str(),
+            extension.description.as_str(),
+        );
+    }
+    let id = select.interact()?;
+    let Some(extension) = registry.get(id) else {
+        return Ok(());
+    };
+
+    if ExtensionConfigManager::get_all_names()?.contains(&extension.config.key()) {
+        cliclack::outro(format!(
+            "{} is already configured",
+            style(&extension.name).green()
+        ))?;
+        return Ok(());
+    }
+    if let Some(homepage) = &extension.homepage {
+        cliclack::log::info(format!("Homepage: {}", homepage))?;
+    }
+    if !extension.env_keys.is_empty() {
+        let keys: Vec<String> = extension
+            .env_keys
+            .iter()
+            .map(|key| {
+                let required = if key.required { "required" } else { "optional" };
+                format!("{} ({}) {}", key.name, required, key.description)
+            })
+            .collect();
+        cliclack::log::info(format!(
+            "{} reads these environment variables, they are stored as secrets:\n{}",
+            extension.name,
+            keys.join("\n")
+        ))?;
+    }
+
+    let mut env_values = Vec::new();
+    for key in &extension.env_keys {
+        if !key.required
+            && !cliclack::confirm(format!("Set {}?", key.name))
+                .initial_value(false)
+                .interact()?
+        {
+            continue;
+        }
+        let value: String = cliclack::password(format!("{}:", key.name))
+            .mask('▪')
+            .interact()?;
+        env_values.push((key.name.clone(), value));
+    }
+
+    extension.install(&env_values)?;
+    cliclack::outro(format!(
+        "Added {} extension",
+        style(&extension.name).green()
+    ))?;
+    Ok(())
+}
+
+pub async fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
     let extension_type = cliclack::select("What type of extension would you like to add?")
+        .item(
+            "registry",
+            "From the Extension Registry",
+            "Browse known MCP servers and install one",
+        )
         .item(
             "built-in",
             "Built-in Extension",
@@ -745,6 +1118,7 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
         .interact()?;
 
     match extension_type {
+        "registry" => return configure_registry_extension_dialog().await,
         // TODO we'll want a place to collect all these options, maybe just an enum in goose-mcp
         "built-in" => {
             let extension = cliclack::select("Which built-in extension would you like to enable?")
@@ -789,6 +1163,8 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             ExtensionConfigManager::set(ExtensionEntry {
                 enabled: true,
+                eager: false,
+                sampling: None,
                 config: ExtensionConfig::Builtin {
                     name: extension.clone(),
                     display_name: Some(display_name),
@@ -861,7 +1237,6 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             let mut envs = HashMap::new();
             let mut env_keys = Vec::new();
-            let config = Config::global();
 
             if add_env {
                 loop {
@@ -873,9 +1248,13 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                         .mask('▪')
                         .interact()?;
 
-                    // Try to store in keychain
+                    // Try to store in keychain, for this extension only
                     let keychain_key = key.to_string();
-                    match config.set_secret(&keychain_key, Value::String(value.clone())) {
+                    match ExtensionConfigManager::set_secret(
+                        &name_to_key(&name),
+                        &keychain_key,
+                        &value,
+                    ) {
                         Ok(_) => {
                             // Successfully stored in keychain, add to env_keys
                             env_keys.push(keychain_key);
@@ -894,6 +1273,8 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             ExtensionConfigManager::set(ExtensionEntry {
                 enabled: true,
+                eager: false,
+                sampling: None,
                 config: ExtensionConfig::Stdio {
                     name: name.clone(),
                     cmd,
@@ -965,7 +1346,6 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             let mut envs = HashMap::new();
             let mut env_keys = Vec::new();
-            let config = Config::global();
 
             if add_env {
                 loop {
@@ -977,9 +1357,13 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                         .mask('▪')
                         .interact()?;
 
-                    // Try to store in keychain
+                    // Try to store in keychain, for this extension only
                     let keychain_key = key.to_string();
-                    match config.set_secret(&keychain_key, Value::String(value.clone())) {
+                    match ExtensionConfigManager::set_secret(
+                        &name_to_key(&name),
+                        &keychain_key,
+                        &value,
+                    ) {
                         Ok(_) => {
                             // Successfully stored in keychain, add to env_keys
                             env_keys.push(keychain_key);
@@ -998,6 +1382,8 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             ExtensionConfigManager::set(ExtensionEntry {
                 enabled: true,
+                eager: false,
+                sampling: None,
                 config: ExtensionConfig::Sse {
                     name: name.clone(),
                     uri,
@@ -1092,7 +1478,6 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             let mut envs = HashMap::new();
             let mut env_keys = Vec::new();
-            let config = Config::global();
 
             if add_env {
                 loop {
@@ -1104,9 +1489,13 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                         .mask('▪')
                         .interact()?;
 
-                    // Try to store in keychain
+                    // Try to store in keychain, for this extension only
                     let keychain_key = key.to_string();
-                    match config.set_secret(&keychain_key, Value::String(value.clone())) {
+                    match ExtensionConfigManager::set_secret(
+                        &name_to_key(&name),
+                        &keychain_key,
+                        &value,
+                    ) {
                         Ok(_) => {
                             // Successfully stored in keychain, add to env_keys
                             env_keys.push(keychain_key);
@@ -1125,6 +1514,8 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
 
             ExtensionConfigManager::set(ExtensionEntry {
                 enabled: true,
+                eager: false,
+                sampling: None,
                 config: ExtensionConfig::StreamableHttp {
                     name: name.clone(),
                     uri,
@@ -1219,6 +1610,16 @@ pub async fn configure_settings_dialog() -> Result<(), Box<dyn Error>> {
             "Tool Output",
             "Show more or less tool output",
         )
+        .item(
+            "appearance",
+            "Appearance",
+            "Theme, syntax highlighting, spinner and ASCII fallback for the CLI",
+        )
+        .item(
+            "notifications",
+            "Desktop Notifications",
+            "Notify when goose needs attention and the terminal is in the background",
+        )
         .item(
             "max_turns",
             "Max Turns",
@@ -1254,6 +1655,12 @@ pub async fn configure_settings_dialog() -> Result<(), Box<dyn Error>> {
         "tool_output" => {
             configure_tool_output_dialog()?;
         }
+        "appearance" => {
+            configure_appearance_dialog()?;
+        }
+        "notifications" => {
+            configure_notifications_dialog()?;
+        }
         "max_turns" => {
             configure_max_turns_dialog()?;
         }
@@ -1334,6 +1741,11 @@ pub fn configure_goose_router_strategy_dialog() -> Result<(), Box<dyn Error>> {
             "Enable Router",
             "Use LLM-based intelligence to select tools",
         )
+        .item(
+            "vector",
+            "Enable Vector Router",
+            "Match tools with local embeddings, no extra model calls",
+        )
         .item(
             "false",
             "Disable Router",
@@ -1344,8 +1756,14 @@ pub fn configure_goose_router_strategy_dialog() -> Result<(), Box<dyn Error>> {
     match enable_router {
         "true" => {
             config.set_param("GOOSE_ENABLE_ROUTER", Value::String("true".to_string()))?;
+            config.set_param("GOOSE_ROUTER_STRATEGY", Value::String("llm".to_string()))?;
             cliclack::outro("Router enabled - using LLM-based intelligence for tool selection")?;
         }
+        "vector" => {
+            config.set_param("GOOSE_ENABLE_ROUTER", Value::String("true".to_string()))?;
+            config.set_param("GOOSE_ROUTER_STRATEGY", Value::String("vector".to_string()))?;
+            cliclack::outro("Router enabled - using local embeddings for tool selection")?;
+        }
         "false" => {
             config.set_param("GOOSE_ENABLE_ROUTER", Value::String("false".to_string()))?;
             cliclack::outro("Router disabled - using default tool selection")?;
@@ -1386,6 +1804,142 @@ pub fn configure_tool_output_dialog() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Dialog for how the CLI looks: the color theme, previewed before it is saved, the syntax
+/// theme for markdown, markdown rendering, the spinner and ASCII-only output
+pub fn configure_appearance_dialog() -> Result<(), Box<dyn Error>> {
+    let config = Config::global();
+    let mut appearance = get_appearance();
+
+    let mut theme = get_theme();
+    loop {
+        let choice = cliclack::select("Which color theme would you like to use?")
+            .initial_value(theme.as_config_string())
+            .item("dark".to_string(), "Dark", "")
+            .item("light".to_string(), "Light", "")
+            .item("ansi".to_string(), "Ansi", "Uses the terminal's own colors")
+            .interact()?;
+        theme = Theme::from_config_str(&choice);
+        preview_markdown(theme);
+        if cliclack::confirm("Use this theme?")
+            .initial_value(true)
+            .interact()?
+        {
+            break;
+        }
+    }
+    set_theme(theme);
+
+    let custom_bat_theme = cliclack::select("Which syntax highlighting should markdown use?")
+        .initial_value(appearance.bat_theme.is_some())
+        .item(false, "The color theme's default", "")
+        .item(
+            true,
+            "A bat theme",
+            "One of bat's bundled themes, or a custom theme from a bat cache",
+        )
+        .interact()?;
+    if custom_bat_theme {
+        let current_dir = appearance
+            .bat_cache_dir
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let cache_dir: String = cliclack::input(
+            "Directory of a bat cache with custom themes (built with `bat cache --build`), empty for the bundled themes",
+        )
+        .default_input(&current_dir)
+        .required(false)
+        .interact()?;
+        appearance.bat_cache_dir = Some(cache_dir.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .map(Into::into);
+
+        let themes = appearance.available_bat_themes();
+        let mut select = cliclack::select("Which bat theme?");
+        if let Some(current) = appearance.bat_theme.clone().filter(|t| themes.contains(t)) {
+            select = select.initial_value(current);
+        }
+        for name in &themes {
+            select = select.item(name.clone(), name, "");
+        }
+        appearance.bat_theme = Some(select.interact()?);
+        set_appearance(appearance.clone());
+        preview_markdown(theme);
+    } else {
+        appearance.bat_theme = None;
+        appearance.bat_cache_dir = None;
+    }
+
+    appearance.render_markdown = cliclack::confirm("Render markdown with syntax highlighting?")
+        .initial_value(appearance.render_markdown)
+        .interact()?;
+
+    let mut spinner_select = cliclack::select("Which spinner should show while goose works?")
+        .initial_value(appearance.spinner.as_config_str());
+    for spinner in SpinnerStyle::ALL {
+        spinner_select = spinner_select.item(spinner.as_config_str(), spinner.as_config_str(), "");
+    }
+    appearance.spinner = SpinnerStyle::from_config_str(spinner_select.interact()?);
+
+    appearance.ascii = cliclack::confirm(
+        "Use ASCII symbols only? Helps terminals that can't show Unicode symbols",
+    )
+    .initial_value(appearance.ascii)
+    .interact()?;
+
+    match &appearance.bat_theme {
+        Some(bat_theme) => config.set_param(BAT_THEME_KEY, Value::String(bat_theme.clone()))?,
+        None => config.delete(BAT_THEME_KEY)?,
+    }
+    match &appearance.bat_cache_dir {
+        Some(dir) => {
+            config.set_param(BAT_CACHE_DIR_KEY, Value::String(dir.display().to_string()))?
+        }
+        None => config.delete(BAT_CACHE_DIR_KEY)?,
+    }
+    config.set_param(RENDER_MARKDOWN_KEY, Value::Bool(appearance.render_markdown))?;
+    config.set_param(
+        SPINNER_KEY,
+        Value::String(appearance.spinner.as_config_str().to_string()),
+    )?;
+    config.set_param(ASCII_KEY, Value::Bool(appearance.ascii))?;
+    set_appearance(appearance);
+
+    cliclack::outro("Appearance saved.")?;
+    Ok(())
+}
+
+/// Dialog for desktop notifications on approval prompts, errors and long turns
+pub fn configure_notifications_dialog() -> Result<(), Box<dyn Error>> {
+    let config = Config::global();
+    let enabled = cliclack::confirm(
+        "Show desktop notifications when goose needs attention and the terminal is in the background?",
+    )
+    .initial_value(config.get_param(NOTIFICATIONS_KEY).unwrap_or(false))
+    .interact()?;
+    config.set_param(NOTIFICATIONS_KEY, Value::Bool(enabled))?;
+    if !enabled {
+        cliclack::outro("Desktop notifications turned off.")?;
+        return Ok(());
+    }
+
+    let notify_after: u64 = config
+        .get_param(NOTIFY_AFTER_KEY)
+        .unwrap_or(DEFAULT_NOTIFY_AFTER_SECONDS);
+    let notify_after: String =
+        cliclack::input("Notify when a turn finishes after at least how many seconds?")
+            .default_input(&notify_after.to_string())
+            .validate(|input: &String| match input.parse::<u64>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err("Please enter a valid number"),
+            })
+            .interact()?;
+    config.set_param(NOTIFY_AFTER_KEY, Value::from(notify_after.parse::<u64>()?))?;
+
+    cliclack::outro("Desktop notifications turned on.")?;
+    Ok(())
+}
+
 /// Configure experiment features that can be used with goose
 /// Dialog for toggling which experiments are enabled/disabled
 pub fn toggle_experiments_dialog() -> Result<(), Box<dyn Error>> {
@@ -1492,6 +2046,7 @@ pub async fn configure_tool_permissions_dialog() -> Result<(), Box<dyn Error>> {
         .filter(|tool| {
             tool.name != PLATFORM_LIST_RESOURCES_TOOL_NAME
                 && tool.name != PLATFORM_READ_RESOURCE_TOOL_NAME
+                && tool.name != PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME
         })
         .map(|tool| {
             ToolInfo::new(
@@ -1755,6 +2310,8 @@ pub async fn handle_openrouter_auth() -> Result<(), Box<dyn Error>> {
                             if !has_developer {
                                 match ExtensionConfigManager::set(ExtensionEntry {
                                     enabled: true,
+                                    eager: false,
+                                    sampling: None,
                                     config: ExtensionConfig::Builtin {
                                         name: "developer".to_string(),
                                         display_name: Some(
@@ -1858,6 +2415,8 @@ pub async fn handle_tetrate_auth() -> Result<(), Box<dyn Error>> {
                             if !has_developer {
                                 match ExtensionConfigManager::set(ExtensionEntry {
                                     enabled: true,
+                                    eager: false,
+                                    sampling: None,
                                     config: ExtensionConfig::Builtin {
                                         name: "developer".to_string(),
                                         display_name: Some(
@@ -1899,7 +2458,7 @@ pub async fn handle_tetrate_auth() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn add_provider() -> Result<(), Box<dyn Error>> {
+async fn add_provider() -> Result<(), Box<dyn Error>> {
     let provider_type = cliclack::select("What type of API is this?")
         .item(
             "openai_compatible",
@@ -1940,8 +2499,121 @@ fn add_provider() -> Result<(), Box<dyn Error>> {
         })
         .interact()?;
 
-    let api_key: String = cliclack::password("API key:").mask('▪').interact()?;
+    let auth = select_custom_provider_auth()?;
+    let api_key: String = if auth == Some(CustomProviderAuth::None) {
+        String::new()
+    } else {
+        cliclack::password("API key:").mask('▪').interact()?
+    };
+
+    let models_endpoint: String =
+        cliclack::input("Models endpoint, to fetch the model list (empty to type it in):")
+            .placeholder("/v1/models")
+            .required(false)
+            .interact()?;
+    let models_endpoint = Some(models_endpoint.trim().to_string()).filter(|e| !e.is_empty());
+
+    // With an endpoint the models are fetched once the provider and its key are saved
+    let models = match models_endpoint {
+        Some(_) => Vec::new(),
+        None => input_custom_provider_models()?,
+    };
+
+    let supports_streaming = cliclack::confirm("Does this provider support streaming responses?")
+        .initial_value(true)
+        .interact()?;
+
+    let mut headers = HashMap::new();
+    if cliclack::confirm("Would you like to add headers sent with every request?")
+        .initial_value(false)
+        .interact()?
+    {
+        loop {
+            let key: String = cliclack::input("Header name:")
+                .placeholder("X-Team")
+                .interact()?;
+            let value: String = cliclack::input("Header value:").interact()?;
+            headers.insert(key, value);
 
+            if !cliclack::confirm("Add another header?").interact()? {
+                break;
+            }
+        }
+    }
+
+    let mut provider = CustomProviderConfig::create_and_save(
+        provider_type,
+        display_name.clone(),
+        api_url,
+        api_key,
+        models,
+        CustomProviderOptions {
+            headers: (!headers.is_empty()).then_some(headers),
+            auth,
+            model_params: HashMap::new(),
+            supports_streaming: Some(supports_streaming),
+            models_endpoint,
+        },
+    )?;
+
+    if provider.models_endpoint.is_some() {
+        let spin = spinner();
+        spin.start("Fetching models...");
+        let result = provider.refresh_models().await;
+        spin.stop("");
+        match result {
+            Ok(()) => {
+                let _ = cliclack::log::info(format!("Found {} models", provider.models.len()));
+            }
+            Err(e) => {
+                let _ = cliclack::log::warning(format!("Could not fetch the models: {}", e));
+                provider.models = input_custom_provider_models()?
+                    .into_iter()
+                    .map(|name| ModelInfo::new(name, 128000))
+                    .collect();
+            }
+        }
+    }
+
+    if cliclack::confirm("Would you like to set default request parameters for a model?")
+        .initial_value(false)
+        .interact()?
+    {
+        let model_items: Vec<(String, String, &str)> = provider
+            .models
+            .iter()
+            .map(|m| (m.name.clone(), m.name.clone(), ""))
+            .collect();
+        loop {
+            let model: String = cliclack::select("Which model?")
+                .items(&model_items)
+                .interact()?;
+            let params: String = cliclack::input("Parameters as a JSON object:")
+                .placeholder(r#"{"temperature": 0.2, "top_p": 0.9}"#)
+                .validate(
+                    |input: &String| match serde_json::from_str::<Value>(input) {
+                        Ok(Value::Object(_)) => Ok(()),
+                        _ => Err("Please enter a JSON object"),
+                    },
+                )
+                .interact()?;
+            provider
+                .model_params
+                .insert(model, serde_json::from_str(&params)?);
+
+            if !cliclack::confirm("Set parameters for another model?").interact()? {
+                break;
+            }
+        }
+    }
+    provider.save()?;
+
+    cliclack::outro(format!("Custom provider added: {}", display_name))?;
+    Ok(())
+}
+
+/// Ask for the models of a custom provider as a comma separated list
+fn input_custom_provider_models() -> Result<Vec<String>, Box<dyn Error>> {
     let models_input: String = cliclack::input("Available models (seperate with commas):")
         .placeholder("model-a, model-b, model-c")
         .validate(|input: &String| {
@@ -1953,29 +2625,103 @@ fn add_provider() -> Result<(), Box<dyn Error>> {
         })
         .interact()?;
 
-    let models: Vec<String> = models_input
+    Ok(models_input
         .split(',')
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty())
-        .collect();
+        .collect())
+}
 
-    let supports_streaming = cliclack::confirm("Does this provider support streaming responses?")
-        .initial_value(true)
-        .interact()?;
+/// Fetch the model list of a custom provider again from its models endpoint
+async fn refresh_provider_models() -> Result<(), Box<dyn Error>> {
+    let custom_providers_dir = goose::config::custom_providers::custom_providers_dir();
+    let custom_providers: Vec<_> =
+        goose::config::custom_providers::load_custom_providers(&custom_providers_dir)?
+            .into_iter()
+            .filter(|p| p.models_endpoint.is_some())
+            .collect();
 
-    CustomProviderConfig::create_and_save(
-        provider_type,
-        display_name.clone(),
-        api_url,
-        api_key,
-        models,
-        Some(supports_streaming),
-    )?;
+    if custom_providers.is_empty() {
+        cliclack::outro("No custom providers have a models endpoint.")?;
+        return Ok(());
+    }
 
-    cliclack::outro(format!("Custom provider added: {}", display_name))?;
+    let provider_items: Vec<_> = custom_providers
+        .iter()
+        .map(|p| (p.name.as_str(), p.display_name.as_str(), ""))
+        .collect();
+    let selected_id = cliclack::select("Which custom provider's models should be fetched?")
+        .items(&provider_items)
+        .interact()?;
+
+    let mut provider = CustomProviderConfig::load(selected_id)?;
+    let spin = spinner();
+    spin.start("Fetching models...");
+    let result = provider.refresh_models().await;
+    spin.stop("");
+    match result {
+        Ok(()) => {
+            goose::providers::refresh_custom_providers()?;
+            cliclack::outro(format!(
+                "{} now lists {} models",
+                provider.display_name,
+                provider.models.len()
+            ))?;
+        }
+        Err(e) => {
+            cliclack::outro(format!(
+                "Could not fetch the models, keeping the current list: {}",
+                e
+            ))?;
+        }
+    }
     Ok(())
 }
 
+/// Ask how the API key is sent, `None` keeping the API type's usual scheme
+fn select_custom_provider_auth() -> Result<Option<CustomProviderAuth>, Box<dyn Error>> {
+    let scheme = cliclack::select("How is the API key sent?")
+        .item(
+            "default",
+            "The API's usual way",
+            "Authorization: Bearer, or x-api-key for Anthropic",
+        )
+        .item(
+            "header",
+            "A custom header",
+            "For example x-api-key or api-key",
+        )
+        .item("query", "A query parameter", "For example ?key=...")
+        .item("none", "No API key", "")
+        .interact()?;
+
+    Ok(match scheme {
+        "header" => {
+            let name: String = cliclack::input("Header name:")
+                .placeholder("x-api-key")
+                .interact()?;
+            let format: String = cliclack::input(
+                "Header value, with {key} where the key goes (empty for the key alone):",
+            )
+            .placeholder("Token {key}")
+            .required(false)
+            .interact()?;
+            Some(CustomProviderAuth::Header {
+                name,
+                format: (!format.trim().is_empty()).then_some(format),
+            })
+        }
+        "query" => {
+            let param: String = cliclack::input("Query parameter name:")
+                .placeholder("key")
+                .interact()?;
+            Some(CustomProviderAuth::Query { param })
+        }
+        "none" => Some(CustomProviderAuth::None),
+        _ => None,
+    })
+}
+
 fn remove_provider() -> Result<(), Box<dyn Error>> {
     let custom_providers_dir = goose::config::custom_providers::custom_providers_dir();
     let custom_providers = if custom_providers_dir.exists() {
@@ -2003,13 +2749,18 @@ fn remove_provider() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn configure_custom_provider_dialog() -> Result<(), Box<dyn Error>> {
+pub async fn configure_custom_provider_dialog() -> Result<(), Box<dyn Error>> {
     let action = cliclack::select("What would you like to do?")
         .item(
             "add",
             "Add A Custom Provider",
             "Add a new OpenAI/Anthropic/Ollama compatible Provider",
         )
+        .item(
+            "refresh",
+            "Refresh Custom Provider Models",
+            "Fetch the model list again from the provider's models endpoint",
+        )
         .item(
             "remove",
             "Remove Custom Provider",
@@ -2018,7 +2769,8 @@ pub fn configure_custom_provider_dialog() -> Result<(), Box<dyn Error>> {
         .interact()?;
 
     match action {
-        "add" => add_provider(),
+        "add" => add_provider().await,
+        "refresh" => refresh_provider_models().await,
         "remove" => remove_provider(),
         _ => unreachable!(),
     }
@@ -0,0 +1,588 @@
+use anyhow::{anyhow, Result};
+use console::style;
+use goose::agents::extension::Envs;
+use goose::agents::{ExtensionConfig, ExtensionManager};
+use goose::config::extensions::name_to_key;
+use goose::config::{Config, ExtensionConfigManager, ExtensionEntry};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// A command, the arguments that check it works, and where to get it
+type RuntimeCheck = (&'static str, &'static [&'static str], &'static str);
+
+const NPM_RUNTIME: &[RuntimeCheck] = &[
+    ("node", &["--version"], "https://nodejs.org"),
+    ("npx", &["--version"], "https://nodejs.org"),
+];
+const UVX_RUNTIME: &[RuntimeCheck] = &[(
+    "uvx",
+    &["--version"],
+    "https://docs.astral.sh/uv/getting-started/installation/",
+)];
+// `docker info` also fails when the daemon isn't running
+const DOCKER_RUNTIME: &[RuntimeCheck] =
+    &[("docker", &["info"], "https://docs.docker.com/get-docker/")];
+
+/// How an MCP server is packaged, parsed from the spec given to `goose extensions install`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionSpec {
+    /// An npm package, run with npx
+    Npm(String),
+    /// A Python package, run with uvx
+    Uvx(String),
+    /// A docker image, run with its stdio attached
+    Docker(String),
+}
+
+impl ExtensionSpec {
+    /// Parse `npm:<package>`, `uvx:<package>` or `docker:<image>`. A bare scoped npm package
+    /// such as `@modelcontextprotocol/server-github` is also accepted
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let (kind, package) = match spec.split_once(':') {
+            Some((kind, package)) if ["npm", "npx", "uvx", "pypi", "docker"].contains(&kind) => {
+                (kind, package)
+            }
+            _ if spec.starts_with('@') => ("npm", spec),
+            _ => {
+                return Err(anyhow!(
+                "Unknown extension spec '{}', use npm:<package>, uvx:<package> or docker:<image>",
+                spec
+            ))
+            }
+        };
+        if package.is_empty() {
+            return Err(anyhow!("The spec '{}' names no package", spec));
+        }
+        let package = package.to_string();
+        Ok(match kind {
+            "npm" | "npx" => ExtensionSpec::Npm(package),
+            "uvx" | "pypi" => ExtensionSpec::Uvx(package),
+            _ => ExtensionSpec::Docker(package),
+        })
+    }
+
+    /// The commands that have to work for the extension to run
+    fn runtime_checks(&self) -> &'static [RuntimeCheck] {
+        match self {
+            ExtensionSpec::Npm(_) => NPM_RUNTIME,
+            ExtensionSpec::Uvx(_) => UVX_RUNTIME,
+            ExtensionSpec::Docker(_) => DOCKER_RUNTIME,
+        }
+    }
+
+    /// Check the runtime of the extension is installed and working
+    pub fn check_runtime(&self) -> Result<()> {
+        for &(command, args, install_url) in self.runtime_checks() {
+            let works = Command::new(command)
+                .args(args)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success());
+            if !works {
+                return Err(anyhow!(
+                    "`{} {}` failed, install {} first: {}",
+                    command,
+                    args.join(" "),
+                    command,
+                    install_url
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The command and arguments that start the server
+    pub fn command(&self, extra_args: &[String]) -> (String, Vec<String>) {
+        let (cmd, mut args) = match self {
+            ExtensionSpec::Npm(package) => ("npx", vec!["-y".to_string(), package.clone()]),
+            ExtensionSpec::Uvx(package) => ("uvx", vec![package.clone()]),
+            ExtensionSpec::Docker(image) => (
+                "docker",
+                vec![
+                    "run".to_string(),
+                    "-i".to_string(),
+                    "--rm".to_string(),
+                    image.clone(),
+                ],
+            ),
+        };
+        args.extend(extra_args.iter().cloned());
+        (cmd.to_string(), args)
+    }
+
+    /// A short name from the package, without scope, registry, version and the usual
+    /// `server-` and `mcp-` prefixes
+    pub fn default_name(&self) -> String {
+        let package = match self {
+            ExtensionSpec::Npm(package) => {
+                // A version follows the last @ that isn't the scope's
+                match package[1..].rfind('@') {
+                    Some(at) => &package[..at + 1],
+                    None => package.as_str(),
+                }
+            }
+            ExtensionSpec::Uvx(package) => package
+                .split(['=', '@', '<', '>', '[', '~'])
+                .next()
+                .unwrap_or(package),
+            ExtensionSpec::Docker(image) => {
+                let image = image.split('@').next().unwrap_or(image);
+                let last = image.rsplit('/').next().unwrap_or(image);
+                last.split(':').next().unwrap_or(last)
+            }
+        };
+        let mut name = package.rsplit('/').next().unwrap_or(package);
+        for prefix in ["mcp-server-", "server-", "mcp-"] {
+            if let Some(rest) = name.strip_prefix(prefix) {
+                if !rest.is_empty() {
+                    name = rest;
+                    break;
+                }
+            }
+        }
+        name.to_string()
+    }
+}
+
+fn parse_env(env: &[String]) -> Result<HashMap<String, String>> {
+    env.iter()
+        .map(|pair| {
+            pair.split_once('=')
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .ok_or_else(|| anyhow!("Expected KEY=VALUE, got '{}'", pair))
+        })
+        .collect()
+}
+
+/// Start the extension and list its tools, so a config that can't work is never saved
+async fn test_handshake(config: ExtensionConfig) -> Result<usize> {
+    let manager = ExtensionManager::new();
+    manager
+        .add_extension(config.clone())
+        .await
+        .map_err(|e| anyhow!("The extension failed to start: {}", e))?;
+    let tools = manager.get_prefixed_tools(None).await?;
+    let _ = manager.remove_extension(&config.key()).await;
+    Ok(tools.len())
+}
+
+pub struct InstallOptions {
+    pub name: Option<String>,
+    pub env: Vec<String>,
+    pub timeout: Option<u64>,
+    pub args: Vec<String>,
+    pub skip_check: bool,
+}
+
+/// Add an extension from an npm, uvx or docker spec after checking its runtime is present and
+/// that it answers an MCP handshake. Environment values are stored as secrets
+pub async fn handle_extension_install(spec: &str, options: InstallOptions) -> Result<()> {
+    let spec = ExtensionSpec::parse(spec)?;
+    let name = options.name.unwrap_or_else(|| spec.default_name());
+    if ExtensionConfigManager::get_all_names()?.contains(&name_to_key(&name)) {
+        return Err(anyhow!("An extension named {} already exists", name));
+    }
+    spec.check_runtime()?;
+
+    let envs = parse_env(&options.env)?;
+    let (cmd, args) = spec.command(&options.args);
+    let stdio_config =
+        |envs: HashMap<String, String>, env_keys: Vec<String>| ExtensionConfig::Stdio {
+            name: name.clone(),
+            cmd: cmd.clone(),
+            args: args.clone(),
+            envs: Envs::new(envs),
+            env_keys,
+            timeout: Some(
+                options
+                    .timeout
+                    .unwrap_or(goose::config::DEFAULT_EXTENSION_TIMEOUT),
+            ),
+            description: None,
+            bundled: None,
+            available_tools: Vec::new(),
+        };
+
+    if !options.skip_check {
+        println!("Starting {} to check it works...", style(&name).cyan());
+        let tools = test_handshake(stdio_config(envs.clone(), Vec::new())).await?;
+        println!("{} answered with {} tools", name, tools);
+    }
+
+    let mut env_keys = Vec::new();
+    for (key, value) in envs {
+        ExtensionConfigManager::set_secret(&name_to_key(&name), &key, &value)?;
+        env_keys.push(key);
+    }
+    env_keys.sort();
+    ExtensionConfigManager::set(ExtensionEntry {
+        enabled: true,
+        eager: false,
+        sampling: None,
+        config: stdio_config(HashMap::new(), env_keys),
+    })?;
+    println!("Added {} extension", style(&name).green());
+    Ok(())
+}
+
+/// An extension in a file given to `goose extensions add --file`, written like the entries
+/// under `extensions` in config.yaml
+#[derive(Debug, Deserialize)]
+struct ExtensionFileEntry {
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    #[serde(flatten)]
+    config: ExtensionConfig,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ExtensionFile {
+    Many(Vec<ExtensionFileEntry>),
+    One(Box<ExtensionFileEntry>),
+}
+
+fn read_extension_file(path: &Path) -> Result<Vec<ExtensionFileEntry>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let file: ExtensionFile = serde_yaml::from_str(&content)
+        .map_err(|e| anyhow!("{} is not a valid extension config: {}", path.display(), e))?;
+    Ok(match file {
+        ExtensionFile::Many(entries) => entries,
+        ExtensionFile::One(entry) => vec![*entry],
+    })
+}
+
+/// An extension described with `goose extensions add` flags
+pub struct AddOptions {
+    pub file: Option<std::path::PathBuf>,
+    pub extension_type: Option<String>,
+    pub name: Option<String>,
+    pub cmd: Option<String>,
+    pub args: Vec<String>,
+    pub uri: Option<String>,
+    pub env: Vec<String>,
+    pub headers: Vec<String>,
+    pub description: Option<String>,
+    pub timeout: Option<u64>,
+    pub disabled: bool,
+}
+
+fn config_from_flags(options: AddOptions) -> Result<ExtensionConfig> {
+    let name = options
+        .name
+        .ok_or_else(|| anyhow!("--name is required without --file"))?;
+    let timeout = Some(
+        options
+            .timeout
+            .unwrap_or(goose::config::DEFAULT_EXTENSION_TIMEOUT),
+    );
+
+    let envs = parse_env(&options.env)?;
+    let mut env_keys: Vec<String> = envs.keys().cloned().collect();
+    env_keys.sort();
+
+    let extension_type = options.extension_type.as_deref().unwrap_or("stdio");
+    if extension_type != "streamable_http" && !options.headers.is_empty() {
+        return Err(anyhow!(
+            "--header only applies to streamable_http extensions"
+        ));
+    }
+    let require_uri = || {
+        options
+            .uri
+            .clone()
+            .ok_or_else(|| anyhow!("--uri is required for {} extensions", extension_type))
+    };
+    let extension = match extension_type {
+        "stdio" => ExtensionConfig::Stdio {
+            name,
+            cmd: options
+                .cmd
+                .ok_or_else(|| anyhow!("--cmd is required for stdio extensions"))?,
+            args: options.args,
+            envs: Envs::default(),
+            env_keys,
+            timeout,
+            description: options.description,
+            bundled: None,
+            available_tools: Vec::new(),
+        },
+        "sse" => ExtensionConfig::Sse {
+            name,
+            uri: require_uri()?,
+            envs: Envs::default(),
+            env_keys,
+            description: options.description,
+            timeout,
+            bundled: None,
+            available_tools: Vec::new(),
+        },
+        "streamable_http" => ExtensionConfig::StreamableHttp {
+            name,
+            uri: require_uri()?,
+            envs: Envs::default(),
+            env_keys,
+            headers: parse_env(&options.headers)?,
+            description: options.description,
+            timeout,
+            bundled: None,
+            available_tools: Vec::new(),
+        },
+        other => return Err(anyhow!("Unknown extension type {}", other)),
+    };
+
+    // Values are stored as the extension's secrets and read through env_keys, as the
+    // interactive dialog does
+    for (key, value) in envs {
+        ExtensionConfigManager::set_secret(&extension.key(), &key, &value)?;
+    }
+    Ok(extension)
+}
+
+/// Add extensions from a YAML file or from flags, replacing any with the same name
+pub fn handle_extension_add(options: AddOptions) -> Result<()> {
+    let entries = match &options.file {
+        Some(path) => read_extension_file(path)?,
+        None => vec![ExtensionFileEntry {
+            enabled: !options.disabled,
+            config: config_from_flags(options)?,
+        }],
+    };
+    for entry in entries {
+        let name = entry.config.name();
+        ExtensionConfigManager::set(ExtensionEntry {
+            enabled: entry.enabled,
+            eager: false,
+            sampling: None,
+            config: entry.config,
+        })?;
+        println!("Added {} extension", style(name).green());
+    }
+    Ok(())
+}
+
+/// The config key of a configured extension, failing when there is none by that name
+fn existing_key(name: &str) -> Result<String> {
+    let key = name_to_key(name);
+    if ExtensionConfigManager::get_all_names()?.contains(&key) {
+        Ok(key)
+    } else {
+        Err(anyhow!("No extension named {} is configured", name))
+    }
+}
+
+pub fn handle_extension_remove(name: &str) -> Result<()> {
+    ExtensionConfigManager::remove(&existing_key(name)?)?;
+    println!("Removed {} extension", name);
+    Ok(())
+}
+
+pub fn handle_extension_set_enabled(name: &str, enabled: bool) -> Result<()> {
+    ExtensionConfigManager::set_enabled(&existing_key(name)?, enabled)?;
+    println!(
+        "{} {} extension",
+        if enabled { "Enabled" } else { "Disabled" },
+        name
+    );
+    Ok(())
+}
+
+fn env_keys_mut(config: &mut ExtensionConfig) -> Option<&mut Vec<String>> {
+    match config {
+        ExtensionConfig::Sse { env_keys, .. }
+        | ExtensionConfig::Stdio { env_keys, .. }
+        | ExtensionConfig::StreamableHttp { env_keys, .. } => Some(env_keys),
+        _ => None,
+    }
+}
+
+/// Set or delete the secrets of an extension, then list its environment variables and where
+/// their values come from. Secrets set here are only passed to this extension
+pub fn handle_extension_secrets(name: &str, set: &[String], delete: &[String]) -> Result<()> {
+    let key = existing_key(name)?;
+    ExtensionConfigManager::migrate_secrets()?;
+    let mut entry = ExtensionConfigManager::get_all()?
+        .into_iter()
+        .find(|entry| entry.config.key() == key)
+        .ok_or_else(|| anyhow!("No extension named {} is configured", name))?;
+
+    let values = parse_env(set)?;
+    if !values.is_empty() {
+        let kind = extension_type(&entry.config);
+        let env_keys = env_keys_mut(&mut entry.config)
+            .ok_or_else(|| anyhow!("{} extensions don't read secrets", kind))?;
+        for env_key in values.keys() {
+            if !env_keys.contains(env_key) {
+                env_keys.push(env_key.clone());
+            }
+        }
+        for (env_key, value) in &values {
+            ExtensionConfigManager::set_secret(&key, env_key, value)?;
+        }
+        ExtensionConfigManager::set(entry.clone())?;
+    }
+    for env_key in delete {
+        ExtensionConfigManager::delete_secret(&key, env_key)?;
+    }
+
+    let stored = ExtensionConfigManager::list_secrets(&key)?;
+    let mut names: Vec<String> = env_keys_mut(&mut entry.config)
+        .map(|env_keys| env_keys.clone())
+        .unwrap_or_default();
+    names.extend(stored.iter().cloned());
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        println!("{} has no secrets", name);
+        return Ok(());
+    }
+    let config = Config::global();
+    for env_key in names {
+        let source = if stored.contains(&env_key) {
+            style("set for this extension").green()
+        } else if config.get_secret::<Value>(&env_key).is_ok() {
+            style("set globally").yellow()
+        } else {
+            style("not set").red()
+        };
+        println!("{:<32} {}", env_key, source);
+    }
+    Ok(())
+}
+
+fn extension_type(config: &ExtensionConfig) -> &'static str {
+    match config {
+        ExtensionConfig::Sse { .. } => "sse",
+        ExtensionConfig::Stdio { .. } => "stdio",
+        ExtensionConfig::Builtin { .. } => "builtin",
+        ExtensionConfig::StreamableHttp { .. } => "streamable_http",
+        ExtensionConfig::Frontend { .. } => "frontend",
+        ExtensionConfig::InlinePython { .. } => "inline_python",
+    }
+}
+
+/// List the configured extensions, as JSON with their full configs for scripts
+pub fn handle_extension_list(json_output: bool) -> Result<()> {
+    let mut entries = ExtensionConfigManager::get_all()?;
+    entries.sort_by_key(|entry| entry.config.key());
+
+    if json_output {
+        let output: Vec<Value> = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "name": entry.config.name(),
+                    "key": entry.config.key(),
+                    "type": extension_type(&entry.config),
+                    "enabled": entry.enabled,
+                    "config": entry.config,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No extensions configured");
+        return Ok(());
+    }
+    for entry in entries {
+        let target = match &entry.config {
+            ExtensionConfig::Stdio { cmd, args, .. } => std::iter::once(cmd.clone())
+                .chain(args.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(" "),
+            ExtensionConfig::Sse { uri, .. } | ExtensionConfig::StreamableHttp { uri, .. } => {
+                uri.clone()
+            }
+            _ => String::new(),
+        };
+        let status = if entry.enabled {
+            style("enabled").green()
+        } else {
+            style("disabled").dim()
+        };
+        println!(
+            "{:<20} {:<16} {:<8} {}",
+            entry.config.name(),
+            extension_type(&entry.config),
+            status,
+            style(target).dim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        let github = ExtensionSpec::parse("@modelcontextprotocol/server-github@1.2.0").unwrap();
+        assert_eq!(
+            github,
+            ExtensionSpec::Npm("@modelcontextprotocol/server-github@1.2.0".to_string())
+        );
+        assert_eq!(github.default_name(), "github");
+        assert_eq!(
+            github.command(&[]),
+            (
+                "npx".to_string(),
+                vec![
+                    "-y".to_string(),
+                    "@modelcontextprotocol/server-github@1.2.0".to_string()
+                ]
+            )
+        );
+
+        let fetch = ExtensionSpec::parse("uvx:mcp-server-fetch==0.6").unwrap();
+        assert_eq!(fetch.default_name(), "fetch");
+
+        let docker = ExtensionSpec::parse("docker:ghcr.io/acme/mcp-postgres:1.0").unwrap();
+        assert_eq!(docker.default_name(), "postgres");
+        assert_eq!(
+            docker.command(&["--read-only".to_string()]).1,
+            vec![
+                "run",
+                "-i",
+                "--rm",
+                "ghcr.io/acme/mcp-postgres:1.0",
+                "--read-only"
+            ]
+        );
+
+        assert!(ExtensionSpec::parse("some-package").is_err());
+        assert!(ExtensionSpec::parse("npm:").is_err());
+    }
+
+    #[test]
+    fn test_read_extension_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("extensions.yaml");
+        std::fs::write(
+            &path,
+            "- type: stdio\n  name: fetch\n  cmd: uvx\n  args: [mcp-server-fetch]\n\
+             - type: streamable_http\n  name: docs\n  uri: https://example.com/mcp\n  enabled: false\n",
+        )
+        .unwrap();
+
+        let entries = read_extension_file(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].enabled);
+        assert_eq!(entries[0].config.name(), "fetch");
+        assert!(!entries[1].enabled);
+        assert_eq!(extension_type(&entries[1].config), "streamable_http");
+    }
+}
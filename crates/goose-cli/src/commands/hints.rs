@@ -0,0 +1,30 @@
+use anyhow::Result;
+use console::style;
+use std::path::PathBuf;
+
+/// Print the hints goose's developer extension would load in `dir`, by default the current
+/// directory, after includes are expanded and the hints budget is applied
+pub fn handle_hints(dir: Option<PathBuf>) -> Result<()> {
+    let dir = match dir {
+        Some(dir) => dir.canonicalize()?,
+        None => std::env::current_dir()?,
+    };
+    let hints = goose_mcp::effective_hints(&dir);
+    if hints.trim().is_empty() {
+        println!("No hints apply to {}", dir.display());
+        return Ok(());
+    }
+
+    println!("{}", hints.trim());
+    println!();
+    println!(
+        "{}",
+        style(format!(
+            "~{} tokens of hints for {}",
+            hints.len() / 4,
+            dir.display()
+        ))
+        .dim()
+    );
+    Ok(())
+}
@@ -1,8 +1,11 @@
 use anyhow::Result;
 use console::style;
 use etcetera::{choose_app_strategy, AppStrategy};
-use goose::config::Config;
+use goose::config::schema::{FindingKind, FindingSeverity, ValidationFinding};
+use goose::config::{Config, ConfigSource, ProjectOverlay};
+use serde_json::Value;
 use serde_yaml;
+use std::collections::BTreeMap;
 
 fn print_aligned(label: &str, value: &str, width: usize) {
     println!("  {:<width$} {}", label, value, width = width);
@@ -20,11 +23,17 @@ pub fn handle_info(verbose: bool) -> Result<()> {
     let config_file = config.path();
 
     // Define the labels and their corresponding path values once.
-    let paths = [
+    let mut paths = vec![
         ("Config file:", config_file.to_string()),
         ("Sessions dir:", sessions_dir.display().to_string()),
         ("Logs dir:", logs_dir.display().to_string()),
     ];
+    if let Some(profile) = config.active_profile() {
+        paths.push((
+            "Profile:",
+            config.profile_path(&profile).display().to_string(),
+        ));
+    }
 
     // Calculate padding: use the max length of the label plus extra space.
     let basic_padding = paths.iter().map(|(l, _)| l.len()).max().unwrap_or(0) + 4;
@@ -68,3 +77,148 @@ pub fn handle_info(verbose: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// Print the configuration file, or with `effective` every value as goose resolves it
+/// through the environment, project, profile and configuration file, with its source
+pub fn handle_config_show(effective: bool, format: &str) -> Result<()> {
+    let config = Config::global();
+    if !effective {
+        let values: BTreeMap<String, Value> = config.load_values()?.into_iter().collect();
+        match format {
+            "json" => println!("{}", serde_json::to_string_pretty(&values)?),
+            _ => print!("{}", serde_yaml::to_string(&values)?),
+        }
+        return Ok(());
+    }
+
+    let values = config.effective_values()?;
+    let project = ProjectOverlay::current();
+    if format == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "values": values,
+                "project": project.as_ref().map(|p| serde_json::json!({
+                    "path": p.path,
+                    "workspace_root": p.workspace_root(),
+                    "workspace_roots": p.workspace_roots(),
+                    "extensions": p.config.extensions,
+                    "goosehints": p.config.goosehints,
+                    "permissions": p.config.permissions,
+                })),
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!("{}", style("Effective Configuration:").cyan().bold());
+    if values.is_empty() {
+        println!("  No configuration values set");
+    }
+    for (key, entry) in &values {
+        let value = match &entry.value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        println!(
+            "  {} = {} {}",
+            key,
+            value,
+            style(format!("({})", describe_source(&entry.source))).dim()
+        );
+    }
+
+    if let Some(project) = project {
+        println!(
+            "\n{} {}",
+            style("Project:").cyan().bold(),
+            style(project.path.display()).dim()
+        );
+        print_aligned(
+            "Workspace root:",
+            &project.workspace_root().display().to_string(),
+            18,
+        );
+        if !project.config.workspace_roots.is_empty() {
+            let roots: Vec<String> = project
+                .workspace_roots()
+                .iter()
+                .skip(1)
+                .map(|root| root.display().to_string())
+                .collect();
+            print_aligned("Other roots:", &roots.join(", "), 18);
+        }
+        if !project.config.extensions.is_empty() {
+            print_aligned("Extensions:", &project.config.extensions.join(", "), 18);
+        }
+        if let Some(hints) = &project.config.goosehints {
+            print_aligned("Hints:", hints.lines().next().unwrap_or_default(), 18);
+        }
+        let mut permissions: Vec<_> = project.config.permissions.iter().collect();
+        permissions.sort_by(|a, b| a.0.cmp(b.0));
+        for (tool, level) in permissions {
+            let level = serde_json::to_value(level)?;
+            print_aligned(
+                "Permission:",
+                &format!("{} {}", tool, level.as_str().unwrap_or_default()),
+                18,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn describe_source(source: &ConfigSource) -> String {
+    match source {
+        ConfigSource::Environment { variable } => format!("environment {}", variable),
+        ConfigSource::Project { path } => format!("project {}", path.display()),
+        ConfigSource::Profile { name, .. } => format!("profile {}", name),
+        ConfigSource::File { path } => format!("config {}", path.display()),
+    }
+}
+
+fn finding_label(finding: &ValidationFinding) -> String {
+    match finding.severity {
+        FindingSeverity::Error => style("error").red().bold().to_string(),
+        FindingSeverity::Warning => style("warning").yellow().to_string(),
+    }
+}
+
+/// Check the config file against the known configuration keys and list what was found
+pub fn handle_config_validate() -> Result<()> {
+    let config = Config::global();
+    let findings = config.validate()?;
+    if findings.is_empty() {
+        println!("{} is valid", config.path());
+        return Ok(());
+    }
+    for finding in &findings {
+        println!("{}: {}", finding_label(finding), finding.message);
+    }
+    if findings
+        .iter()
+        .any(|finding| finding.severity == FindingSeverity::Error)
+    {
+        return Err(anyhow::anyhow!("{} has errors", config.path()));
+    }
+    Ok(())
+}
+
+/// Point out wrongly typed and deprecated keys when goose starts, leaving unknown keys to
+/// the log and to `goose config validate`
+pub fn warn_about_config_findings() {
+    let findings = match Config::global().validate() {
+        Ok(findings) => findings,
+        Err(e) => {
+            tracing::warn!("Could not validate config: {}", e);
+            return;
+        }
+    };
+    for finding in findings {
+        if finding.kind == FindingKind::UnknownKey {
+            tracing::warn!("{}", finding.message);
+        } else {
+            eprintln!("{}: {}", finding_label(&finding), finding.message);
+        }
+    }
+}
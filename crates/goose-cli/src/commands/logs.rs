@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+use console::style;
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::Level;
+
+use crate::logging::get_session_log_directory;
+use goose::agents::extension_stderr::STDERR_LOG_TARGET;
+
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The log files of a session, oldest first, or of the session that logged last
+fn session_log_files(dir: &Path, session: Option<&str>) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+
+    let session = match session {
+        Some(session) => session.to_string(),
+        None => {
+            let latest = files
+                .iter()
+                .max_by_key(|path| path.metadata().and_then(|m| m.modified()).ok())
+                .ok_or_else(|| anyhow!("No session logs in {}", dir.display()))?;
+            session_id(latest).to_string()
+        }
+    };
+
+    files.retain(|path| session_id(path) == session);
+    if files.is_empty() {
+        return Err(anyhow!("No logs for session {}", session));
+    }
+    // Rotated files are named <session id>.<date>.log, so this sorts them by date
+    files.sort();
+    Ok(files)
+}
+
+fn session_id(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(|stem| stem.rsplit_once('.').map_or(stem, |(id, _date)| id))
+        .unwrap_or_default()
+}
+
+/// The correlation id of the tool call a log line belongs to, from its fields or its spans
+fn tool_call_id(entry: &Value) -> Option<String> {
+    if let Some(ids) = entry["fields"]["tool_call_ids"].as_str() {
+        return (!ids.is_empty()).then(|| ids.to_string());
+    }
+    std::iter::once(&entry["span"])
+        .chain(entry["spans"].as_array().into_iter().flatten())
+        .find_map(|span| span["tool_call_id"].as_str())
+        .map(str::to_string)
+}
+
+/// Print a JSON log line if it is at least as severe as `level`
+fn print_entry(line: &str, level: Level) {
+    let Ok(entry) = serde_json::from_str::<Value>(line) else {
+        println!("{}", line.trim_end());
+        return;
+    };
+    let entry_level = entry["level"]
+        .as_str()
+        .and_then(|l| l.parse::<Level>().ok())
+        .unwrap_or(Level::INFO);
+    if entry_level > level {
+        return;
+    }
+
+    let padded_level = format!("{:>5}", entry_level.as_str());
+    let styled_level = match entry_level {
+        Level::ERROR => style(padded_level).red(),
+        Level::WARN => style(padded_level).yellow(),
+        Level::INFO => style(padded_level).green(),
+        _ => style(padded_level).dim(),
+    };
+    let mut output = format!(
+        "{} {} {}: {}",
+        style(entry["timestamp"].as_str().unwrap_or_default()).dim(),
+        styled_level,
+        entry["target"].as_str().unwrap_or_default(),
+        entry["fields"]["message"].as_str().unwrap_or_default()
+    );
+    if let Some(extension) = entry["fields"]["extension"].as_str() {
+        output.push_str(&format!(
+            " {}",
+            style(format!("extension={}", extension)).cyan()
+        ));
+    }
+    if let Some(id) = tool_call_id(&entry) {
+        output.push_str(&format!(" {}", style(format!("tool_call={}", id)).cyan()));
+    }
+    println!("{}", output);
+}
+
+/// Print the lines of `path` from `offset` on, returning where it stopped reading
+fn print_from(path: &Path, offset: u64, level: Level) -> Result<u64> {
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+    let mut position = offset;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        // A line without a newline is still being written, read it on the next poll
+        if read == 0 || !line.ends_with('\n') {
+            return Ok(position);
+        }
+        position += read as u64;
+        print_entry(&line, level);
+    }
+}
+
+/// Show the logs of a session, by default the one that logged last, keeping lines at `level`
+/// or more severe. With `follow`, keep printing lines as they are written
+pub async fn handle_logs(session: Option<String>, follow: bool, level: &str) -> Result<()> {
+    let level: Level = level
+        .parse()
+        .map_err(|_| anyhow!("Unknown log level {}", level))?;
+    let dir = get_session_log_directory()?;
+    let files = session_log_files(&dir, session.as_deref())?;
+
+    let mut current = files[files.len() - 1].clone();
+    for file in &files[..files.len() - 1] {
+        print_from(file, 0, level)?;
+    }
+    let mut offset = print_from(&current, 0, level)?;
+    if !follow {
+        return Ok(());
+    }
+
+    let session = session_id(&current).to_string();
+    loop {
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+        offset = print_from(&current, offset, level)?;
+        // Continue in the next file once the log has rotated
+        if let Some(newest) = session_log_files(&dir, Some(&session))?.pop() {
+            if newest != current {
+                current = newest;
+                offset = print_from(&current, 0, level)?;
+            }
+        }
+    }
+}
+
+/// The stderr lines of `extension` in a log file, oldest first
+fn extension_stderr_lines(path: &Path, extension: &str) -> Result<Vec<String>> {
+    let file = std::fs::File::open(path)?;
+    let mut lines = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let Ok(entry) = serde_json::from_str::<Value>(&line?) else {
+            continue;
+        };
+        if entry["target"].as_str() == Some(STDERR_LOG_TARGET)
+            && entry["fields"]["extension"]
+                .as_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(extension))
+        {
+            lines.push(
+                entry["fields"]["message"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+        }
+    }
+    Ok(lines)
+}
+
+/// Show the last `lines` lines an extension wrote to stderr, from the given session or else
+/// the most recent session the extension ran in
+pub fn handle_extension_logs(name: &str, session: Option<String>, lines: usize) -> Result<()> {
+    let dir = get_session_log_directory()?;
+    let mut files = match &session {
+        Some(session) => session_log_files(&dir, Some(session))?,
+        None => {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+                .collect();
+            files.sort_by_key(|path| path.metadata().and_then(|m| m.modified()).ok());
+            files
+        }
+    };
+
+    // Newest first, stopping at the first file with output from the extension
+    let mut stderr = Vec::new();
+    while let Some(file) = files.pop() {
+        stderr = extension_stderr_lines(&file, name)?;
+        if !stderr.is_empty() {
+            break;
+        }
+    }
+    if stderr.is_empty() {
+        println!("No stderr output logged for extension {}", name);
+        return Ok(());
+    }
+    for line in &stderr[stderr.len().saturating_sub(lines)..] {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "20250101_1.2025-01-02.log",
+            "20250101_1.2025-01-01.log",
+            "20250101_2.2025-01-01.log",
+            "notes.txt",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let files = session_log_files(dir.path(), Some("20250101_1")).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                dir.path().join("20250101_1.2025-01-01.log"),
+                dir.path().join("20250101_1.2025-01-02.log"),
+            ]
+        );
+        assert!(session_log_files(dir.path(), Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_tool_call_id_from_span() {
+        let entry: Value = serde_json::from_str(
+            r#"{"level":"WARN","fields":{"message":"Tool call failed"},"span":{"name":"dispatch_tool_call","tool_call_id":"toolu_1"}}"#,
+        )
+        .unwrap();
+        assert_eq!(tool_call_id(&entry).as_deref(), Some("toolu_1"));
+
+        let stderr: Value = serde_json::from_str(
+            r#"{"level":"DEBUG","fields":{"message":"boom","extension":"developer","tool_call_ids":"toolu_2"}}"#,
+        )
+        .unwrap();
+        assert_eq!(tool_call_id(&stderr).as_deref(), Some("toolu_2"));
+    }
+
+    #[test]
+    fn test_extension_stderr_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("20250101_1.2025-01-01.log");
+        std::fs::write(
+            &path,
+            format!(
+                "{}\n{}\n{}\n",
+                r#"{"level":"DEBUG","target":"goose::extension_stderr","fields":{"message":"listening","extension":"developer"}}"#,
+                r#"{"level":"DEBUG","target":"goose::extension_stderr","fields":{"message":"other","extension":"memory"}}"#,
+                r#"{"level":"INFO","target":"goose::agents","fields":{"message":"not stderr","extension":"developer"}}"#,
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            extension_stderr_lines(&path, "Developer").unwrap(),
+            vec!["listening"]
+        );
+    }
+}
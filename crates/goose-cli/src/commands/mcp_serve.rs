@@ -0,0 +1,514 @@
+use anyhow::Result;
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::post,
+    Json,
+};
+use goose::session::info::{get_valid_sorted_sessions, SortOrder};
+use mcp_core::{
+    handler::{PromptError, ResourceError},
+    protocol::ServerCapabilities,
+};
+use mcp_server::router::{CapabilitiesBuilder, McpRequest, RouterService};
+use mcp_server::{ByteTransport, Router, Server};
+use rand::{distributions::Alphanumeric, Rng};
+use rmcp::model::{
+    Content, ErrorCode, ErrorData, JsonRpcError, JsonRpcMessage, JsonRpcVersion2_0, Prompt,
+    Resource, Tool, ToolAnnotations,
+};
+use rmcp::object;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::{future::Future, pin::Pin};
+use tokio::io::{stdin, stdout};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tower_service::Service;
+
+const DEFAULT_SESSION_LIMIT: usize = 20;
+
+/// Shared secret HTTP clients send in the `X-Secret-Key` header, generated when unset
+pub const SECRET_KEY_ENV: &str = "GOOSE_MCP_SERVE__SECRET_KEY";
+/// Comma separated origins allowed besides localhost, for browser based clients
+pub const ALLOWED_ORIGINS_ENV: &str = "GOOSE_MCP_SERVE__ALLOWED_ORIGINS";
+const SECRET_KEY_HEADER: &str = "X-Secret-Key";
+
+/// Exposes goose itself as an MCP server, so other MCP clients can hand it work. Each task
+/// runs as a `goose run` of the current executable, using the configured provider and
+/// extensions
+#[derive(Clone)]
+pub struct GooseAgentRouter {
+    tools: Vec<Tool>,
+}
+
+impl Default for GooseAgentRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GooseAgentRouter {
+    pub fn new() -> Self {
+        let ask_goose = Tool::new(
+            "ask_goose".to_string(),
+            "Ask goose to carry out a task and return its final answer. Goose works with the \
+             tools of its configured extensions, so it can read and edit files, run commands \
+             and more on this machine."
+                .to_string(),
+            object!({
+                "type": "object",
+                "required": ["prompt"],
+                "properties": {
+                    "prompt": {"type": "string", "description": "The task or question for goose"},
+                    "working_dir": {"type": "string", "description": "Directory to work in, defaults to the server's"},
+                    "session_name": {"type": "string", "description": "Named session to continue, created if it doesn't exist"}
+                }
+            }),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Ask goose".to_string()),
+            read_only_hint: Some(false),
+            destructive_hint: Some(true),
+            idempotent_hint: Some(false),
+            open_world_hint: Some(true),
+        });
+
+        let run_recipe = Tool::new(
+            "run_recipe".to_string(),
+            "Run a goose recipe, given by name or path, and return its final answer.".to_string(),
+            object!({
+                "type": "object",
+                "required": ["recipe"],
+                "properties": {
+                    "recipe": {"type": "string", "description": "Recipe name or path to the recipe file"},
+                    "params": {
+                        "type": "object",
+                        "description": "Values for the recipe's parameters",
+                        "additionalProperties": {"type": "string"}
+                    },
+                    "working_dir": {"type": "string", "description": "Directory to work in, defaults to the server's"}
+                }
+            }),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Run a recipe".to_string()),
+            read_only_hint: Some(false),
+            destructive_hint: Some(true),
+            idempotent_hint: Some(false),
+            open_world_hint: Some(true),
+        });
+
+        let list_sessions = Tool::new(
+            "list_sessions".to_string(),
+            "List goose sessions, most recently used first.".to_string(),
+            object!({
+                "type": "object",
+                "properties": {
+                    "limit": {"type": "integer", "description": "How many sessions to return (defaults to 20)"}
+                }
+            }),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("List sessions".to_string()),
+            read_only_hint: Some(true),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        });
+
+        Self {
+            tools: vec![ask_goose, run_recipe, list_sessions],
+        }
+    }
+
+    async fn ask_goose(&self, arguments: &Value) -> Result<String, ErrorData> {
+        let prompt = require_str(arguments, "prompt")?;
+        let mut args = vec!["-t".to_string(), prompt.to_string()];
+        if let Some(name) = arguments.get("session_name").and_then(|v| v.as_str()) {
+            args.extend(["--name".to_string(), name.to_string()]);
+            let existing =
+                goose::session::get_path(goose::session::Identifier::Name(name.to_string()))
+                    .is_ok_and(|path| path.exists());
+            if existing {
+                args.push("--resume".to_string());
+            }
+        }
+        run_goose(args, working_dir(arguments)).await
+    }
+
+    async fn run_recipe(&self, arguments: &Value) -> Result<String, ErrorData> {
+        let recipe = require_str(arguments, "recipe")?;
+        let mut args = vec!["--recipe".to_string(), recipe.to_string()];
+        if let Some(params) = arguments.get("params").and_then(|v| v.as_object()) {
+            for (key, value) in params {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                args.extend(["--params".to_string(), format!("{}={}", key, value)]);
+            }
+        }
+        run_goose(args, working_dir(arguments)).await
+    }
+
+    fn list_sessions(&self, arguments: &Value) -> Result<String, ErrorData> {
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|limit| limit as usize)
+            .unwrap_or(DEFAULT_SESSION_LIMIT);
+        let sessions = get_valid_sorted_sessions(SortOrder::Descending)
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let sessions: Vec<_> = sessions.into_iter().take(limit).collect();
+        serde_json::to_string_pretty(&sessions)
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))
+    }
+}
+
+fn require_str<'a>(arguments: &'a Value, name: &str) -> Result<&'a str, ErrorData> {
+    arguments.get(name).and_then(|v| v.as_str()).ok_or_else(|| {
+        ErrorData::new(
+            ErrorCode::INVALID_PARAMS,
+            format!("Missing '{}' parameter", name),
+            None,
+        )
+    })
+}
+
+fn working_dir(arguments: &Value) -> Option<PathBuf> {
+    arguments
+        .get("working_dir")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from)
+}
+
+/// Run `goose run --quiet` with the given arguments, returning what it printed
+async fn run_goose(args: Vec<String>, working_dir: Option<PathBuf>) -> Result<String, ErrorData> {
+    let exe = std::env::current_exe()
+        .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+    let mut command = Command::new(exe);
+    command.arg("run").arg("--quiet").args(&args);
+    if let Some(dir) = working_dir {
+        command.current_dir(dir);
+    }
+
+    let output = command.output().await.map_err(|e| {
+        ErrorData::new(
+            ErrorCode::INTERNAL_ERROR,
+            format!("Failed to start goose: {}", e),
+            None,
+        )
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(ErrorData::new(
+            ErrorCode::INTERNAL_ERROR,
+            format!(
+                "goose exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            None,
+        ))
+    }
+}
+
+impl Router for GooseAgentRouter {
+    fn name(&self) -> String {
+        "goose".to_string()
+    }
+
+    fn instructions(&self) -> String {
+        "Goose is a local AI agent. Use ask_goose to delegate a task to it, run_recipe to run \
+         one of its recipes, and list_sessions to find earlier sessions to continue."
+            .to_string()
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        CapabilitiesBuilder::new().with_tools(false).build()
+    }
+
+    fn list_tools(&self) -> Vec<Tool> {
+        self.tools.clone()
+    }
+
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        _notifier: mpsc::Sender<JsonRpcMessage>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ErrorData>> + Send + 'static>> {
+        let this = self.clone();
+        let tool_name = tool_name.to_string();
+
+        Box::pin(async move {
+            let text = match tool_name.as_str() {
+                "ask_goose" => this.ask_goose(&arguments).await?,
+                "run_recipe" => this.run_recipe(&arguments).await?,
+                "list_sessions" => this.list_sessions(&arguments)?,
+                _ => {
+                    return Err(ErrorData::new(
+                        ErrorCode::RESOURCE_NOT_FOUND,
+                        format!("Tool {} not found", tool_name),
+                        None,
+                    ))
+                }
+            };
+            Ok(vec![Content::text(text)])
+        })
+    }
+
+    fn list_resources(&self) -> Vec<Resource> {
+        Vec::new()
+    }
+
+    fn read_resource(
+        &self,
+        _uri: &str,
+    ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>> {
+        Box::pin(async move { Ok("".to_string()) })
+    }
+
+    fn list_prompts(&self) -> Vec<Prompt> {
+        vec![]
+    }
+
+    fn get_prompt(
+        &self,
+        prompt_name: &str,
+    ) -> Pin<Box<dyn Future<Output = Result<String, PromptError>> + Send + 'static>> {
+        let prompt_name = prompt_name.to_string();
+        Box::pin(async move {
+            Err(PromptError::NotFound(format!(
+                "Prompt {} not found",
+                prompt_name
+            )))
+        })
+    }
+}
+
+/// The address to listen on: a bare port listens on localhost only
+fn listen_addr(addr: &str) -> String {
+    if addr.parse::<u16>().is_ok() {
+        format!("127.0.0.1:{}", addr)
+    } else {
+        addr.to_string()
+    }
+}
+
+#[derive(Clone)]
+struct HttpState {
+    router: GooseAgentRouter,
+    secret_key: String,
+    allowed_origins: Vec<String>,
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let Some(host) = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    else {
+        return false;
+    };
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// Requests must carry the secret key, and when made from a browser, come from localhost or an
+/// allowed origin, so a web page can't drive the agent through the user's browser
+fn check_request(headers: &HeaderMap, state: &HttpState) -> Result<(), StatusCode> {
+    let secret_key = headers
+        .get(SECRET_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    if secret_key != state.secret_key {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    if let Some(origin) = headers.get(axum::http::header::ORIGIN) {
+        let origin = origin.to_str().map_err(|_| StatusCode::FORBIDDEN)?;
+        if !is_local_origin(origin) && !state.allowed_origins.iter().any(|o| o == origin) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+    Ok(())
+}
+
+/// Serve the agent over stdio, or over streamable HTTP at `http_addr` when given
+pub async fn run_agent_server(http_addr: Option<String>) -> Result<()> {
+    crate::logging::setup_logging(Some("mcp-serve"), None)?;
+    let router = GooseAgentRouter::new();
+
+    match http_addr {
+        Some(addr) => {
+            let addr = listen_addr(&addr);
+            let secret_key = match std::env::var(SECRET_KEY_ENV) {
+                Ok(key) if !key.is_empty() => key,
+                _ => {
+                    let key: String = rand::thread_rng()
+                        .sample_iter(&Alphanumeric)
+                        .take(32)
+                        .map(char::from)
+                        .collect();
+                    eprintln!(
+                        "Send this key in the {} header (set {} to choose it): {}",
+                        SECRET_KEY_HEADER, SECRET_KEY_ENV, key
+                    );
+                    key
+                }
+            };
+            let allowed_origins = std::env::var(ALLOWED_ORIGINS_ENV)
+                .map(|origins| {
+                    origins
+                        .split(',')
+                        .map(|origin| origin.trim().to_string())
+                        .filter(|origin| !origin.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let state = HttpState {
+                router,
+                secret_key,
+                allowed_origins,
+            };
+
+            let app = axum::Router::new()
+                .route("/mcp", post(handle_http_message))
+                .with_state(state);
+            let listener = tokio::net::TcpListener::bind(&addr).await?;
+            tracing::info!("Serving goose over MCP at http://{}/mcp", addr);
+            axum::serve(listener, app)
+                .with_graceful_shutdown(crate::signal::shutdown_signal())
+                .await?;
+            Ok(())
+        }
+        None => {
+            let server = Server::new(RouterService(router));
+            let transport = ByteTransport::new(stdin(), stdout());
+            tokio::select! {
+                result = server.run(transport) => Ok(result?),
+                _ = crate::signal::shutdown_signal() => Ok(()),
+            }
+        }
+    }
+}
+
+/// Streamable HTTP endpoint answering each request with a single JSON response
+async fn handle_http_message(
+    State(state): State<HttpState>,
+    headers: HeaderMap,
+    Json(message): Json<JsonRpcMessage>,
+) -> impl IntoResponse {
+    if let Err(status) = check_request(&headers, &state) {
+        return status.into_response();
+    }
+    let JsonRpcMessage::Request(request) = message else {
+        // Notifications and responses need no answer
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let id = request.id.clone();
+    // Progress notifications can't be streamed back in a plain JSON response
+    let (notifier, _) = mpsc::channel(1);
+    let message: JsonRpcMessage = match RouterService(state.router)
+        .call(McpRequest { request, notifier })
+        .await
+    {
+        Ok(response) => JsonRpcMessage::Response(response),
+        Err(e) => JsonRpcMessage::Error(JsonRpcError {
+            jsonrpc: JsonRpcVersion2_0,
+            id,
+            error: ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None),
+        }),
+    };
+    Json(message).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_agent_router_tools() {
+        let router = GooseAgentRouter::new();
+        let names: Vec<String> = router
+            .list_tools()
+            .iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["ask_goose", "run_recipe", "list_sessions"]);
+
+        let (notifier, _) = mpsc::channel(1);
+        let err = router
+            .call_tool("ask_goose", json!({}), notifier.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert!(router
+            .call_tool("unknown", json!({}), notifier)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_http_requests_need_key_and_local_origin() {
+        let state = HttpState {
+            router: GooseAgentRouter::new(),
+            secret_key: "s3cret".to_string(),
+            allowed_origins: vec!["https://tools.example.com".to_string()],
+        };
+        let headers = |pairs: &[(&'static str, &'static str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(*name, value.parse().unwrap());
+            }
+            headers
+        };
+
+        assert_eq!(
+            check_request(&headers(&[]), &state),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            check_request(&headers(&[("x-secret-key", "wrong")]), &state),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            check_request(&headers(&[("x-secret-key", "s3cret")]), &state),
+            Ok(())
+        );
+        for origin in [
+            "http://localhost:5173",
+            "http://[::1]:8080",
+            "https://tools.example.com",
+        ] {
+            let mut allowed = headers(&[("x-secret-key", "s3cret")]);
+            allowed.insert("origin", origin.parse().unwrap());
+            assert_eq!(check_request(&allowed, &state), Ok(()), "{}", origin);
+        }
+        for origin in ["https://evil.example.com", "http://localhost.evil.com"] {
+            let mut denied = headers(&[("x-secret-key", "s3cret")]);
+            denied.insert("origin", origin.parse().unwrap());
+            assert_eq!(
+                check_request(&denied, &state),
+                Err(StatusCode::FORBIDDEN),
+                "{}",
+                origin
+            );
+        }
+    }
+
+    #[test]
+    fn test_listen_addr_defaults_to_localhost() {
+        assert_eq!(listen_addr("3001"), "127.0.0.1:3001");
+        assert_eq!(listen_addr("0.0.0.0:3001"), "0.0.0.0:3001");
+    }
+}
@@ -1,10 +1,23 @@
+pub mod batch;
 pub mod bench;
+pub mod bench_compare;
+pub mod config_archive;
+pub mod config_values;
 pub mod configure;
+pub mod extensions;
+pub mod hints;
 pub mod info;
+pub mod logs;
 pub mod mcp;
+pub mod mcp_serve;
+pub mod profiles;
 pub mod project;
 pub mod recipe;
+pub mod recipe_run;
 pub mod schedule;
 pub mod session;
+pub mod stats;
 pub mod update;
+pub mod usage;
 pub mod web;
+pub mod webhooks;
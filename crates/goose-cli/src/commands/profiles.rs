@@ -0,0 +1,121 @@
+use anyhow::{anyhow, Context, Result};
+use cliclack::confirm;
+use console::style;
+use goose::config::Config;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Keys a new profile takes from the current configuration unless given explicitly
+const PROFILE_PROVIDER_KEY: &str = "GOOSE_PROVIDER";
+const PROFILE_MODEL_KEY: &str = "GOOSE_MODEL";
+const PROFILE_EXTENSIONS_KEY: &str = "extensions";
+
+pub fn handle_profiles_list() -> Result<()> {
+    let config = Config::global();
+    let profiles = config.list_profiles()?;
+    if profiles.is_empty() {
+        println!("No profiles found in {}", config.profiles_dir().display());
+        return Ok(());
+    }
+
+    let active = config.active_profile();
+    for name in profiles {
+        let values = config.load_profile(&name).unwrap_or_default();
+        let summary = [PROFILE_PROVIDER_KEY, PROFILE_MODEL_KEY]
+            .iter()
+            .filter_map(|key| values.get(*key).and_then(|v| v.as_str()))
+            .collect::<Vec<_>>()
+            .join("/");
+        let marker = if active.as_deref() == Some(name.as_str()) {
+            style("*").green().to_string()
+        } else {
+            " ".to_string()
+        };
+        println!("{} {} {}", marker, name, style(summary).dim());
+    }
+    Ok(())
+}
+
+/// Save a profile with the given provider and model, taking whichever is missing from the
+/// current configuration, and optionally a copy of the current extensions
+pub fn handle_profile_create(
+    name: &str,
+    provider: Option<String>,
+    model: Option<String>,
+    with_extensions: bool,
+) -> Result<()> {
+    let config = Config::global();
+    if config.profile_path(name).exists() {
+        return Err(anyhow!(
+            "Profile '{}' already exists, edit {} or delete it first",
+            name,
+            config.profile_path(name).display()
+        ));
+    }
+
+    let mut values = HashMap::new();
+    let provider = provider.or_else(|| config.get_param(PROFILE_PROVIDER_KEY).ok());
+    let model = model.or_else(|| config.get_param(PROFILE_MODEL_KEY).ok());
+    if let Some(provider) = provider {
+        values.insert(PROFILE_PROVIDER_KEY.to_string(), Value::String(provider));
+    }
+    if let Some(model) = model {
+        values.insert(PROFILE_MODEL_KEY.to_string(), Value::String(model));
+    }
+    if with_extensions {
+        if let Ok(extensions) = config.get_param_unresolved::<Value>(PROFILE_EXTENSIONS_KEY) {
+            values.insert(PROFILE_EXTENSIONS_KEY.to_string(), extensions);
+        }
+    }
+
+    config.save_profile(name, &values)?;
+    println!(
+        "Created profile '{}' at {}\nUse it with `goose --profile {}` or GOOSE_PROFILE={}",
+        name,
+        config.profile_path(name).display(),
+        name,
+        name
+    );
+    Ok(())
+}
+
+pub fn handle_profile_show(name: &str) -> Result<()> {
+    let config = Config::global();
+    let values = config
+        .load_profile(name)
+        .with_context(|| format!("Failed to load profile '{}'", name))?;
+    print!("{}", serde_yaml::to_string(&values)?);
+    Ok(())
+}
+
+pub fn handle_profile_delete(name: &str) -> Result<()> {
+    let config = Config::global();
+    let should_delete = confirm(format!("Delete profile '{}'?", name))
+        .initial_value(false)
+        .interact()?;
+    if should_delete {
+        config.delete_profile(name)?;
+        println!("Profile '{}' deleted.", name);
+    }
+    Ok(())
+}
+
+/// Fail early when `--profile` names a profile that doesn't exist, rather than silently
+/// running with the base configuration
+pub fn ensure_profile_exists(name: &str) -> Result<()> {
+    let config = Config::global();
+    if config.profile_path(name).exists() {
+        return Ok(());
+    }
+    let available = config.list_profiles().unwrap_or_default();
+    Err(anyhow!(
+        "Profile '{}' not found. Available profiles: {}. Create one with `goose configure profiles create {}`",
+        name,
+        if available.is_empty() {
+            "none".to_string()
+        } else {
+            available.join(", ")
+        },
+        name
+    ))
+}
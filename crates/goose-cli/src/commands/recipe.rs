@@ -1,10 +1,12 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use console::style;
 
 use crate::recipes::github_recipe::RecipeSource;
 use crate::recipes::recipe::load_recipe_for_validation;
-use crate::recipes::search_recipe::list_available_recipes;
+use crate::recipes::search_recipe::{list_available_recipes, retrieve_recipe_file};
 use goose::recipe_deeplink;
+use goose::scheduler::{get_default_scheduler_storage_path, parse_timezone, ScheduledJob};
+use goose::scheduler_factory::SchedulerFactory;
 
 /// Validates a recipe file
 ///
@@ -68,6 +70,87 @@ pub fn handle_deeplink(recipe_name: &str) -> Result<String> {
     }
 }
 
+/// Registers a recipe that declares a `schedule:` block with the configured scheduler. The job
+/// is named after the recipe file unless an ID is given, and installing the recipe again
+/// replaces the job from the earlier install
+pub async fn handle_install(recipe_name: &str, id: Option<String>) -> Result<()> {
+    let recipe = load_recipe_for_validation(recipe_name)?;
+    let schedule = recipe.schedule.clone().ok_or_else(|| {
+        anyhow!(
+            "Recipe '{}' has no schedule: block, add one or use `goose schedule add`",
+            recipe.title
+        )
+    })?;
+    if let Some(timezone) = &schedule.timezone {
+        parse_timezone(timezone)?;
+    }
+
+    let recipe_file = retrieve_recipe_file(recipe_name)?;
+    let origin = recipe_file
+        .file_path
+        .canonicalize()
+        .unwrap_or_else(|_| recipe_file.file_path.clone());
+    let id = match id {
+        Some(id) => id,
+        None => recipe_file
+            .file_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(|stem| stem.to_string())
+            .context("Could not name the job after the recipe file, pass --id")?,
+    };
+
+    let scheduler = SchedulerFactory::create(get_default_scheduler_storage_path()?)
+        .await
+        .context("Failed to initialize scheduler")?;
+    if let Some(existing) = scheduler
+        .list_scheduled_jobs()
+        .await?
+        .into_iter()
+        .find(|job| job.id == id)
+    {
+        if existing.recipe_origin.is_none() {
+            bail!(
+                "A scheduled job '{}' that wasn't installed from a recipe already exists, pass --id to pick another name",
+                id
+            );
+        }
+        scheduler.remove_scheduled_job(&id).await?;
+    }
+
+    let job = ScheduledJob {
+        id: id.clone(),
+        source: recipe_file.file_path.to_string_lossy().to_string(),
+        cron: schedule.cron.clone(),
+        last_run: None,
+        currently_running: false,
+        paused: false,
+        current_session_id: None,
+        process_start_time: None,
+        execution_mode: Some("background".to_string()),
+        timezone: schedule.timezone.clone(),
+        notify: schedule.notify.clone(),
+        recipe_origin: Some(origin.to_string_lossy().to_string()),
+    };
+    scheduler
+        .add_scheduled_job(job)
+        .await
+        .with_context(|| format!("Failed to schedule recipe '{}'", recipe.title))?;
+
+    println!(
+        "{} Installed {} as scheduled job '{}', running on '{}' ({})",
+        style("✓").green().bold(),
+        recipe.title,
+        id,
+        schedule.cron,
+        schedule.timezone.as_deref().unwrap_or("UTC")
+    );
+    if let Some(notify) = &schedule.notify {
+        println!("  Runs are reported to the {} webhook", notify);
+    }
+    Ok(())
+}
+
 /// Lists all available recipes from local paths and GitHub repositories
 ///
 /// # Arguments
@@ -0,0 +1,216 @@
+use goose::config::Config;
+use goose::session::{self, Identifier};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+use crate::commands::usage::ledger_cost_usd;
+use crate::session::estimate_cost_usd;
+
+/// Provider settings and limits passed through to each `goose run`
+#[derive(Debug, Clone, Default)]
+pub struct RunTarget {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub max_turns: Option<u32>,
+}
+
+impl RunTarget {
+    /// The provider and model the run uses, falling back to the configured ones
+    pub fn resolved(&self) -> Option<(String, String)> {
+        let config = Config::global();
+        let provider = self
+            .provider
+            .clone()
+            .or_else(|| config.get_param::<String>("GOOSE_PROVIDER").ok())?;
+        let model = self
+            .model
+            .clone()
+            .or_else(|| config.get_param::<String>("GOOSE_MODEL").ok())?;
+        Some((provider, model))
+    }
+}
+
+/// What a headless run of a recipe reported through its ndjson events
+#[derive(Debug, Clone)]
+pub struct RecipeRunReport {
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+    pub turns: usize,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub total_tokens: Option<i32>,
+    pub last_assistant_text: Option<String>,
+    pub error: Option<String>,
+    /// The session the run was recorded in
+    pub session_file: Option<PathBuf>,
+}
+
+impl RecipeRunReport {
+    /// A report for a run that has not finished, until its events say otherwise
+    fn new(duration: Duration) -> Self {
+        Self {
+            status: "error".to_string(),
+            exit_code: None,
+            duration,
+            turns: 0,
+            input_tokens: None,
+            output_tokens: None,
+            total_tokens: None,
+            last_assistant_text: None,
+            error: None,
+            session_file: None,
+        }
+    }
+
+    fn failed(error: String, duration: Duration) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new(duration)
+        }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.status == "completed"
+    }
+
+    /// Cost in USD, when pricing is known for the run's model. Priced call by call from the
+    /// session's usage ledger when there is one, otherwise estimated from the reported totals
+    pub async fn cost_usd(&self, target: &RunTarget) -> Option<f64> {
+        if let Some(session_file) = &self.session_file {
+            if let Some(cost) = ledger_cost_usd(session_file).await {
+                return Some(cost);
+            }
+        }
+        let (provider, model) = target.resolved()?;
+        estimate_cost_usd(
+            &provider,
+            &model,
+            self.input_tokens?.max(0) as usize,
+            self.output_tokens?.max(0) as usize,
+        )
+        .await
+    }
+
+    fn record_event(&mut self, event: &Value) {
+        match event.get("type").and_then(|t| t.as_str()) {
+            Some("usage") => {
+                let tokens = |key: &str| event.get(key).and_then(|v| v.as_i64()).map(|v| v as i32);
+                self.input_tokens = tokens("input_tokens");
+                self.output_tokens = tokens("output_tokens");
+                self.total_tokens = tokens("total_tokens");
+            }
+            Some("finish") => {
+                if let Some(status) = event.get("status").and_then(|s| s.as_str()) {
+                    self.status = status.to_string();
+                }
+            }
+            Some("error") => {
+                self.error = event
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .map(|m| m.to_string());
+            }
+            Some("message") if event["message"]["role"] == "assistant" => {
+                self.turns += 1;
+                let text: String = event["message"]["content"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|c| c.get("text").and_then(|t| t.as_str()))
+                    .collect();
+                if !text.is_empty() {
+                    self.last_assistant_text = Some(text);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Run a recipe as a `goose run` of the current executable in its own named session, reading
+/// the run's status and usage from its ndjson events
+pub async fn run_recipe_headless(
+    recipe: &str,
+    params: &BTreeMap<String, String>,
+    session_name: &str,
+    output_file: Option<&Path>,
+    target: &RunTarget,
+) -> RecipeRunReport {
+    let start = Instant::now();
+    let mut command = match std::env::current_exe() {
+        Ok(exe) => Command::new(exe),
+        Err(e) => return RecipeRunReport::failed(e.to_string(), start.elapsed()),
+    };
+    command
+        .arg("run")
+        .args(["--recipe", recipe])
+        .args(["--name", session_name])
+        .args(["--output-format", "ndjson"]);
+    if let Some(output_file) = output_file {
+        command.arg("--output-file").arg(output_file);
+    }
+    for (key, value) in params {
+        command.args(["--params", &format!("{}={}", key, value)]);
+    }
+    if let Some(max_turns) = target.max_turns {
+        command.args(["--max-turns", &max_turns.to_string()]);
+    }
+    if let Some(provider) = &target.provider {
+        command.args(["--provider", provider]);
+    }
+    if let Some(model) = &target.model {
+        command.args(["--model", model]);
+    }
+
+    let output = match command.output().await {
+        Ok(output) => output,
+        Err(e) => {
+            return RecipeRunReport::failed(
+                format!("Failed to start goose: {}", e),
+                start.elapsed(),
+            )
+        }
+    };
+
+    let mut report = RecipeRunReport::new(start.elapsed());
+    report.exit_code = output.status.code();
+    report.session_file = session::get_path(Identifier::Name(session_name.to_string())).ok();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Ok(event) = serde_json::from_str::<Value>(line) {
+            report.record_event(&event);
+        }
+    }
+    if !output.status.success() && report.error.is_none() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        report.error = Some(stderr).filter(|s| !s.is_empty());
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_events() {
+        let mut report = RecipeRunReport::new(Duration::ZERO);
+        for event in [
+            json!({"type": "start", "schema_version": 1, "session_id": "s"}),
+            json!({"type": "message", "message": {"role": "user", "content": [{"type": "text", "text": "hi"}]}}),
+            json!({"type": "message", "message": {"role": "assistant", "content": [{"type": "text", "text": "hello"}]}}),
+            json!({"type": "usage", "input_tokens": 10, "output_tokens": 5, "total_tokens": 15}),
+            json!({"type": "finish", "status": "completed"}),
+        ] {
+            report.record_event(&event);
+        }
+        assert!(report.succeeded());
+        assert_eq!(report.turns, 1);
+        assert_eq!(report.total_tokens, Some(15));
+        assert_eq!(report.last_assistant_text.as_deref(), Some("hello"));
+    }
+}
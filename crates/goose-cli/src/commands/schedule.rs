@@ -4,9 +4,10 @@ use goose::scheduler::{
     get_default_scheduled_recipes_dir, get_default_scheduler_storage_path, ScheduledJob,
     SchedulerError,
 };
-use goose::scheduler_factory::SchedulerFactory;
+use goose::scheduler_factory::{SchedulerFactory, SchedulerType};
 use goose::temporal_scheduler::TemporalScheduler;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tokio::task::JoinHandle;
 
 // Base64 decoding function - might be needed if recipe_source_arg can be base64
 // For now, handle_schedule_add will assume it's a path.
@@ -100,6 +101,9 @@ pub async fn handle_schedule_add(
         current_session_id: None,
         process_start_time: None,
         execution_mode: Some("background".to_string()), // Default to background for CLI
+        timezone: None,
+        notify: None,
+        recipe_origin: None,
     };
 
     let scheduler_storage_path =
@@ -168,14 +172,24 @@ pub async fn handle_schedule_list() -> Result<()> {
             };
 
             println!(
-                "- ID: {}\n  Status: {}\n  Cron: {}\n  Recipe Source (in store): {}\n  Last Run: {}",
+                "- ID: {}\n  Status: {}\n  Cron: {}{}\n  Recipe Source (in store): {}\n  Last Run: {}",
                 job.id,
                 status,
                 job.cron,
+                job.timezone
+                    .as_deref()
+                    .map_or_else(String::new, |tz| format!(" ({})", tz)),
                 job.source, // This source is now the path within scheduled_recipes_dir
                 job.last_run
                     .map_or_else(|| "Never".to_string(), |dt| dt.to_rfc3339())
             );
+            match &job.recipe_origin {
+                Some(origin) => println!("  Origin: installed from {}", origin),
+                None => println!("  Origin: scheduled directly"),
+            }
+            if let Some(notify) = &job.notify {
+                println!("  Notifies: {}", notify);
+            }
         }
     }
     Ok(())
@@ -226,6 +240,9 @@ pub async fn handle_schedule_sessions(id: String, limit: Option<u32>) -> Result<
                         metadata.message_count,
                         metadata.schedule_id.as_deref().unwrap_or("N/A")
                     );
+                    if let Some(run_url) = &metadata.schedule_run_url {
+                        println!("    Run: {}", run_url);
+                    }
                 }
             }
         }
@@ -236,6 +253,25 @@ pub async fn handle_schedule_sessions(id: String, limit: Option<u32>) -> Result<
     Ok(())
 }
 
+/// Send heartbeats for a run of a scheduled job while it lasts, when the job was started by the
+/// Temporal scheduler. The returned task is aborted once the run is over
+pub async fn start_run_heartbeats(
+    job_id: Option<String>,
+    session_file: Option<PathBuf>,
+) -> Option<JoinHandle<()>> {
+    let (job_id, session_file) = (job_id?, session_file?);
+    if !matches!(SchedulerType::from_config(), SchedulerType::Temporal) {
+        return None;
+    }
+    match TemporalScheduler::connect().await {
+        Ok(scheduler) => Some(tokio::spawn(scheduler.keep_run_alive(job_id, session_file))),
+        Err(e) => {
+            tracing::warn!("Not sending heartbeats for job '{}': {}", job_id, e);
+            None
+        }
+    }
+}
+
 pub async fn handle_schedule_run_now(id: String) -> Result<()> {
     let scheduler_storage_path =
         get_default_scheduler_storage_path().context("Failed to get scheduler storage path")?;
@@ -1,12 +1,21 @@
-use crate::session::message_to_markdown;
+use crate::session::{estimate_cost_usd, message_to_markdown};
 use anyhow::{Context, Result};
 use cliclack::{confirm, multiselect, select};
+use console::style;
+use goose::conversation::diagnostics::diagnose_conversation;
+use goose::redaction::Redactor;
 use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
+use goose::session::retention::{self, RetentionPolicy};
+use goose::session::share::{SessionBundle, ShareOptions};
+use goose::session::usage::usage_path;
 use goose::session::{self, Identifier};
 use goose::utils::safe_truncate;
 use regex::Regex;
+use serde::Serialize;
+use std::cmp::Ordering;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 const TRUNCATED_DESC_LENGTH: usize = 60;
 
@@ -24,6 +33,7 @@ pub fn remove_sessions(sessions: Vec<SessionInfo>) -> Result<()> {
         for session in sessions {
             fs::remove_file(session.path.clone())
                 .with_context(|| format!("Failed to remove session file '{}'", session.path))?;
+            let _ = fs::remove_file(usage_path(Path::new(&session.path)));
             println!("Session `{}` removed.", session.id);
         }
     } else {
@@ -115,50 +125,124 @@ pub fn handle_session_remove(id: Option<String>, regex_string: Option<String>) -
     remove_sessions(matched_sessions)
 }
 
-pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Result<()> {
-    let sort_order = if ascending {
-        SortOrder::Ascending
-    } else {
-        SortOrder::Descending
-    };
+/// Column sessions are sorted by in `goose session list`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SessionSort {
+    #[default]
+    Date,
+    Title,
+    Turns,
+    Tokens,
+    Cost,
+}
+
+pub struct SessionListOptions {
+    pub verbose: bool,
+    pub format: String,
+    pub ascending: bool,
+    pub tags: Vec<String>,
+    pub since: Option<String>,
+    pub project: Option<PathBuf>,
+    pub sort: SessionSort,
+}
+
+/// A session as listed, with the cost estimated from its token counts
+#[derive(Serialize)]
+struct SessionRow {
+    #[serde(flatten)]
+    info: SessionInfo,
+    cost: Option<f64>,
+}
 
-    let sessions = match get_valid_sorted_sessions(sort_order) {
+pub async fn handle_session_list(options: SessionListOptions) -> Result<()> {
+    let sessions = match get_valid_sorted_sessions(SortOrder::Descending) {
         Ok(sessions) => sessions,
         Err(e) => {
             tracing::error!("Failed to list sessions: {:?}", e);
             return Err(anyhow::anyhow!("Failed to list sessions"));
         }
     };
+    let mut sessions = filter_sessions_by_tags(sessions, &options.tags);
+    if let Some(since) = &options.since {
+        let since = parse_since(since, chrono::Utc::now())?;
+        sessions.retain(|session| modified_at(session).is_some_and(|modified| modified >= since));
+    }
+    if let Some(project) = &options.project {
+        let project = project
+            .canonicalize()
+            .with_context(|| format!("Project directory {} not found", project.display()))?;
+        sessions.retain(|session| {
+            let working_dir = &session.metadata.working_dir;
+            working_dir
+                .canonicalize()
+                .unwrap_or_else(|_| working_dir.clone())
+                .starts_with(&project)
+        });
+    }
 
-    match format.as_str() {
-        "json" => {
-            println!("{}", serde_json::to_string(&sessions)?);
-        }
-        _ => {
-            if sessions.is_empty() {
-                println!("No sessions found");
-                return Ok(());
-            } else {
-                println!("Available sessions:");
-                for SessionInfo {
-                    id,
-                    path,
-                    metadata,
-                    modified,
-                } in sessions
-                {
-                    let description = if metadata.description.is_empty() {
-                        "(none)"
-                    } else {
-                        &metadata.description
-                    };
-                    let output = format!("{} - {} - {}", id, description, modified);
-                    if verbose {
-                        println!("  {}", output);
-                        println!("    Path: {}", path);
-                    } else {
-                        println!("{}", output);
-                    }
+    let mut rows = Vec::with_capacity(sessions.len());
+    for info in sessions {
+        let cost = session_cost(&info.metadata).await;
+        rows.push(SessionRow { info, cost });
+    }
+    sort_rows(&mut rows, options.sort, options.ascending);
+
+    if options.format == "json" {
+        println!("{}", serde_json::to_string(&rows)?);
+        return Ok(());
+    }
+    if rows.is_empty() {
+        println!("No sessions found");
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        style(format!(
+            "{:<24} {:<32} {:<16} {:<28} {:>5} {:>9} {:>8}  {}",
+            "ID", "TITLE", "MODIFIED", "MODEL", "TURNS", "TOKENS", "COST", "DIRECTORY"
+        ))
+        .bold()
+    );
+    for SessionRow { info, cost } in &rows {
+        let metadata = &info.metadata;
+        let title = if metadata.description.is_empty() {
+            "(none)"
+        } else {
+            &metadata.description
+        };
+        let model = match (&metadata.provider, &metadata.model) {
+            (Some(provider), Some(model)) => format!("{}/{}", provider, model),
+            (None, Some(model)) => model.clone(),
+            _ => "-".to_string(),
+        };
+        let tokens = metadata
+            .accumulated_total_tokens
+            .map(|tokens| tokens.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let cost = cost
+            .map(|cost| format!("${:.2}", cost))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<24} {:<32} {:<16} {:<28} {:>5} {:>9} {:>8}  {}",
+            info.id,
+            safe_truncate(title, 32),
+            info.modified.get(..16).unwrap_or(&info.modified),
+            safe_truncate(&model, 28),
+            metadata.turn_count,
+            tokens,
+            cost,
+            style(metadata.working_dir.display()).dim()
+        );
+        if options.verbose {
+            println!("    Path: {}", info.path);
+            if !metadata.tags.is_empty() {
+                println!("    Tags: {}", metadata.tags.join(", "));
+            }
+            if let Some(parent) = &metadata.parent_session_id {
+                match metadata.forked_at {
+                    Some(at) => println!("    Forked from: {} at message {}", parent, at),
+                    None => println!("    Forked from: {}", parent),
                 }
             }
         }
@@ -166,6 +250,339 @@ pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Re
     Ok(())
 }
 
+/// Cost of the tokens the session used, when its provider and model have known pricing
+async fn session_cost(metadata: &session::SessionMetadata) -> Option<f64> {
+    let (Some(provider), Some(model)) = (&metadata.provider, &metadata.model) else {
+        return None;
+    };
+    estimate_cost_usd(
+        provider,
+        model,
+        metadata.accumulated_input_tokens.unwrap_or(0).max(0) as usize,
+        metadata.accumulated_output_tokens.unwrap_or(0).max(0) as usize,
+    )
+    .await
+}
+
+pub(crate) fn modified_at(session: &SessionInfo) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDateTime::parse_from_str(&session.modified, "%Y-%m-%d %H:%M:%S UTC")
+        .ok()
+        .map(|modified| modified.and_utc())
+}
+
+/// Parse `--since`, either a date (`2025-01-31`) or an age such as `12h`, `7d` or `2w`
+fn parse_since(
+    since: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    parse_age(since).map(|age| now - age).with_context(|| {
+        format!(
+            "Invalid --since value '{}', use a date like 2025-01-31 or an age",
+            since
+        )
+    })
+}
+
+/// Parse an age such as `30m`, `12h`, `7d` or `2w`
+fn parse_age(age: &str) -> Result<chrono::Duration> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid age '{}', use a number followed by m, h, d or w",
+            age
+        )
+    };
+    let split = age.len().checked_sub(1).ok_or_else(invalid)?;
+    let (amount, unit) = age.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    match unit {
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Sort the rows by the column, newest or largest first unless ascending and titles from A to Z.
+/// Sessions without a value for the column go last
+fn sort_rows(rows: &mut [SessionRow], sort: SessionSort, ascending: bool) {
+    rows.sort_by(|a, b| {
+        if sort == SessionSort::Title {
+            return title_key(a).cmp(&title_key(b));
+        }
+        match (sort_value(a, sort), sort_value(b, sort)) {
+            (Some(a), Some(b)) if ascending => a.total_cmp(&b),
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    });
+}
+
+fn title_key(row: &SessionRow) -> (bool, String) {
+    let description = &row.info.metadata.description;
+    (description.is_empty(), description.to_lowercase())
+}
+
+fn sort_value(row: &SessionRow, sort: SessionSort) -> Option<f64> {
+    let metadata = &row.info.metadata;
+    match sort {
+        SessionSort::Date => modified_at(&row.info).map(|modified| modified.timestamp() as f64),
+        SessionSort::Turns => Some(metadata.turn_count as f64),
+        SessionSort::Tokens => metadata.accumulated_total_tokens.map(f64::from),
+        SessionSort::Cost => row.cost,
+        SessionSort::Title => None,
+    }
+}
+
+/// Remove old sessions, by the limits given or else by the retention policy in config
+pub fn handle_session_prune(
+    older_than: Option<String>,
+    max_sessions: Option<usize>,
+    keep_tagged: bool,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let policy = if older_than.is_some() || max_sessions.is_some() {
+        let older_than = older_than
+            .map(|age| {
+                parse_age(&age)?
+                    .to_std()
+                    .context("The age can't be negative")
+            })
+            .transpose()?;
+        RetentionPolicy {
+            older_than,
+            max_sessions,
+            max_total_bytes: None,
+            keep_tagged,
+        }
+    } else {
+        let mut policy = RetentionPolicy::from_config().context(
+            "Nothing to prune by, pass --older-than or --max-sessions or configure a retention policy",
+        )?;
+        policy.keep_tagged |= keep_tagged;
+        policy
+    };
+
+    let sessions = retention::scan_sessions(&session::ensure_session_dir()?)?;
+    let removed = retention::select_for_pruning(&sessions, &policy, None, SystemTime::now());
+    if removed.is_empty() {
+        println!("No sessions to prune");
+        return Ok(());
+    }
+
+    let freed: u64 = removed.iter().map(|session| session.size).sum();
+    for session in &removed {
+        let modified = chrono::DateTime::<chrono::Local>::from(session.modified);
+        println!(
+            "- {} {}",
+            session.id,
+            style(modified.format("%Y-%m-%d %H:%M")).dim()
+        );
+    }
+    let summary = format!(
+        "{} of {} sessions, {:.1} MB",
+        removed.len(),
+        sessions.len(),
+        freed as f64 / (1024.0 * 1024.0)
+    );
+    if dry_run {
+        println!("Would remove {}", summary);
+        return Ok(());
+    }
+    if !yes
+        && !confirm(format!("Remove {}?", summary))
+            .initial_value(false)
+            .interact()?
+    {
+        println!("Skipping pruning of the sessions.");
+        return Ok(());
+    }
+
+    retention::remove_sessions(&removed)?;
+    println!("Removed {}", summary);
+    Ok(())
+}
+
+/// Keep the sessions that have every one of the given tags, compared case-insensitively
+fn filter_sessions_by_tags(sessions: Vec<SessionInfo>, tags: &[String]) -> Vec<SessionInfo> {
+    if tags.is_empty() {
+        return sessions;
+    }
+    sessions
+        .into_iter()
+        .filter(|session| {
+            tags.iter().all(|tag| {
+                session
+                    .metadata
+                    .tags
+                    .iter()
+                    .any(|t| t.eq_ignore_ascii_case(tag.trim_start_matches('#')))
+            })
+        })
+        .collect()
+}
+
+/// Fork a session, leaving the original transcript untouched
+pub fn handle_session_fork(id: String, at: Option<usize>) -> Result<()> {
+    let session_file = session::get_path(Identifier::Name(id.clone()))?;
+    let (forked_id, _) = session::fork_session(&session_file, at)
+        .with_context(|| format!("Failed to fork session {}", id))?;
+
+    println!("Forked session {} into {}", id, forked_id);
+    println!(
+        "Continue it with: goose session --resume --name {}",
+        forked_id
+    );
+    Ok(())
+}
+
+/// Show a session's metadata, including the summary stored when it was last compacted on
+/// resume. Defaults to the most recent session
+pub fn handle_session_info(identifier: Option<Identifier>, json: bool) -> Result<()> {
+    let session_file = match identifier {
+        Some(identifier) => session::get_path(identifier)?,
+        None => session::get_most_recent_session().context("No sessions found")?,
+    };
+    if !session_file.exists() {
+        return Err(anyhow::anyhow!(
+            "Session file not found (expected path: {})",
+            session_file.display()
+        ));
+    }
+    let metadata = session::read_metadata(&session_file)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&metadata)?);
+        return Ok(());
+    }
+
+    let id = session_file
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    println!("Session:      {}", id);
+    println!("Path:         {}", session_file.display());
+    println!("Description:  {}", metadata.description);
+    println!("Working dir:  {}", metadata.working_dir.display());
+    if !metadata.tags.is_empty() {
+        println!("Tags:         {}", metadata.tags.join(", "));
+    }
+    println!("Messages:     {}", metadata.message_count);
+    if let Some(tokens) = metadata.accumulated_total_tokens {
+        println!("Tokens used:  {}", tokens);
+    }
+    if let Some(parent) = &metadata.parent_session_id {
+        println!("Forked from:  {}", parent);
+    }
+
+    match &metadata.checkpoint {
+        Some(checkpoint) => {
+            let created = chrono::DateTime::from_timestamp(checkpoint.created_at, 0)
+                .map(|time| {
+                    time.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            println!(
+                "\nSummary checkpoint from {}, covering {} messages:\n\n{}",
+                created,
+                checkpoint.summarized_messages,
+                checkpoint.summary.trim()
+            );
+        }
+        None => {
+            println!("\nNo summary checkpoint, the conversation has not been compacted on resume")
+        }
+    }
+    Ok(())
+}
+
+/// Report the issues `fix_conversation` repairs in a session before sending it to the model,
+/// with the index of the message each was found in
+pub fn handle_session_doctor(id: String, json: bool) -> Result<()> {
+    let session_file = session::get_path(Identifier::Name(id.clone()))?;
+    if !session_file.exists() {
+        return Err(anyhow::anyhow!(
+            "Session file not found (expected path: {})",
+            session_file.display()
+        ));
+    }
+    let messages = session::read_messages(&session_file)?;
+    let report = diagnose_conversation(messages.messages());
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if report.is_healthy() {
+        println!(
+            "Session {} is healthy, its {} messages need no repair",
+            id, report.message_count
+        );
+        return Ok(());
+    }
+
+    println!("Issues found in session {}:", id);
+    for issue in &report.issues {
+        println!(
+            "  [message {}] {}{}",
+            issue.message_index,
+            issue.detail,
+            issue
+                .message_id
+                .as_ref()
+                .map(|id| format!(" (id {})", id))
+                .unwrap_or_default()
+        );
+    }
+    println!("\nChanges made when the session is sent to the model:");
+    if report.changes.is_empty() {
+        println!("  (none)");
+    }
+    for change in &report.changes {
+        println!("  - {}", change);
+    }
+    println!(
+        "\n{} messages before repair, {} after",
+        report.message_count, report.repaired_message_count
+    );
+    Ok(())
+}
+
+/// Write a sanitized bundle of a session for attaching to bug reports
+pub fn handle_session_share(
+    id: String,
+    output_path: Option<PathBuf>,
+    anonymize_paths: bool,
+) -> Result<()> {
+    let session_file = session::get_path(Identifier::Name(id.clone()))?;
+    if !session_file.exists() {
+        return Err(anyhow::anyhow!(
+            "Session file not found (expected path: {})",
+            session_file.display()
+        ));
+    }
+
+    let bundle = SessionBundle::collect(&id, &session_file)?;
+    let json =
+        bundle.to_sanitized_json(&Redactor::from_config(), &ShareOptions { anonymize_paths })?;
+
+    let output = output_path.unwrap_or_else(|| PathBuf::from(format!("goose-session-{}.json", id)));
+    fs::write(&output, json)
+        .with_context(|| format!("Failed to write to output file: {}", output.display()))?;
+    println!("Session bundle written to {}", output.display());
+    println!("Secrets were redacted, review the bundle before sharing it");
+    Ok(())
+}
+
 /// Export a session to Markdown without creating a full Session object
 ///
 /// This function directly reads messages from the session file and converts them to Markdown
@@ -274,6 +691,9 @@ fn export_session_to_markdown(
 
         // Add the message content
         markdown_output.push_str(&message_to_markdown(message, false));
+        if let Some(metadata) = &message.metadata {
+            markdown_output.push_str(&format!("\n\n*{}*", metadata.summary()));
+        }
         markdown_output.push_str("\n\n---\n\n");
 
         // Check if this message has any tool requests, to handle the next message differently
@@ -351,3 +771,67 @@ pub fn prompt_interactive_session_selection() -> Result<session::Identifier> {
         Err(anyhow::anyhow!("Invalid selection"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn row(id: &str, modified: &str, tokens: Option<i32>, cost: Option<f64>) -> SessionRow {
+        let metadata = session::SessionMetadata {
+            description: id.to_string(),
+            accumulated_total_tokens: tokens,
+            ..Default::default()
+        };
+        SessionRow {
+            info: SessionInfo {
+                id: id.to_string(),
+                path: String::new(),
+                modified: modified.to_string(),
+                metadata,
+            },
+            cost,
+        }
+    }
+
+    fn ids(rows: &[SessionRow]) -> Vec<&str> {
+        rows.iter().map(|row| row.info.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = chrono::Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+        assert_eq!(
+            parse_since("2d", now).unwrap(),
+            chrono::Utc.with_ymd_and_hms(2025, 3, 8, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_since("2025-03-01", now).unwrap(),
+            chrono::Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap()
+        );
+        assert!(parse_since("2y", now).is_err());
+        assert!(parse_since("", now).is_err());
+        assert_eq!(parse_age("90d").unwrap(), chrono::Duration::days(90));
+        assert!(parse_age("d").is_err());
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let mut rows = vec![
+            row("b", "2025-03-02 10:00:00 UTC", None, Some(0.5)),
+            row("a", "2025-03-03 10:00:00 UTC", Some(100), None),
+            row("c", "Unknown", Some(300), Some(2.0)),
+        ];
+
+        sort_rows(&mut rows, SessionSort::Date, false);
+        assert_eq!(ids(&rows), ["a", "b", "c"]);
+        sort_rows(&mut rows, SessionSort::Date, true);
+        assert_eq!(ids(&rows), ["b", "a", "c"]);
+        sort_rows(&mut rows, SessionSort::Tokens, false);
+        assert_eq!(ids(&rows), ["c", "a", "b"]);
+        sort_rows(&mut rows, SessionSort::Cost, true);
+        assert_eq!(ids(&rows), ["b", "c", "a"]);
+        sort_rows(&mut rows, SessionSort::Title, false);
+        assert_eq!(ids(&rows), ["a", "b", "c"]);
+    }
+}
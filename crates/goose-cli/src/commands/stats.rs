@@ -0,0 +1,46 @@
+use anyhow::Result;
+use console::style;
+use goose::agents::tool_usage_stats::{ToolUsage, ToolUsageStats};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn print_usage(title: &str, usage: &HashMap<String, ToolUsage>, limit: usize) {
+    println!("{}", style(title).bold());
+    if usage.is_empty() {
+        println!("  No tool calls recorded yet");
+        return;
+    }
+    for (name, usage) in ToolUsageStats::most_used(usage).into_iter().take(limit) {
+        println!(
+            "  {:>6}  {}  {}",
+            usage.count,
+            name,
+            style(usage.last_used.format("%Y-%m-%d %H:%M")).dim()
+        );
+    }
+}
+
+/// Show how often tools were called, in the current project and overall
+pub fn handle_tool_stats(format: &str, limit: usize) -> Result<()> {
+    let stats = ToolUsageStats::load_from(&ToolUsageStats::path()?);
+    let project = std::env::current_dir()?.display().to_string();
+    let empty = HashMap::new();
+    let project_usage = stats.projects.get(&project).unwrap_or(&empty);
+
+    match format {
+        "json" => {
+            let output = json!({
+                "project": project,
+                "project_tools": project_usage,
+                "global_tools": stats.global,
+            });
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        _ => {
+            print_usage(&format!("Tools used in {}", project), project_usage, limit);
+            println!();
+            print_usage("Tools used overall", &stats.global, limit);
+        }
+    }
+    Ok(())
+}
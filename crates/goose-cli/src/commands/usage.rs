@@ -0,0 +1,330 @@
+use crate::commands::session::modified_at;
+use crate::session::estimate_cost_usd;
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate};
+use console::style;
+use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
+use goose::session::usage::read_usage;
+use goose::utils::safe_truncate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Token usage and spend of one model over the month
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelUsage {
+    pub provider: String,
+    pub model: String,
+    pub sessions: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// None when no price is known for the model
+    pub cost: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageReport {
+    pub month: String,
+    pub sessions: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Spend on the models with a known price
+    pub total_cost: f64,
+    pub models: Vec<ModelUsage>,
+}
+
+/// The first day of the month and of the month after, from `YYYY-MM`
+fn parse_month(month: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .with_context(|| format!("Invalid month '{}', use a month like 2025-01", month))?;
+    let end = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .context("Month out of range")?;
+    Ok((start, end))
+}
+
+/// The tokens of one session and model in the month
+struct SessionUsage {
+    provider: String,
+    model: String,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// What the session used in the month. Sessions with a usage ledger count each call in the month
+/// it was made with the model that served it; older sessions count their totals in the month they
+/// were last active in, with the last model they used
+fn session_usage(session: &SessionInfo, start: NaiveDate, end: NaiveDate) -> Vec<SessionUsage> {
+    let metadata = &session.metadata;
+    let default_provider = metadata
+        .provider
+        .clone()
+        .unwrap_or_else(|| "unknown".into());
+    let in_month = |date: NaiveDate| date >= start && date < end;
+
+    let ledger = read_usage(Path::new(&session.path)).unwrap_or_default();
+    if !ledger.is_empty() {
+        let mut by_model: BTreeMap<(String, String), SessionUsage> = BTreeMap::new();
+        for record in ledger {
+            let made_at = chrono::DateTime::from_timestamp(record.timestamp, 0);
+            if !made_at.is_some_and(|at| in_month(at.date_naive())) {
+                continue;
+            }
+            let provider = record.provider.unwrap_or_else(|| default_provider.clone());
+            let usage = by_model
+                .entry((provider.clone(), record.model.clone()))
+                .or_insert_with(|| SessionUsage {
+                    provider,
+                    model: record.model,
+                    input_tokens: 0,
+                    output_tokens: 0,
+                });
+            usage.input_tokens += record.input_tokens.unwrap_or(0).max(0) as u64;
+            usage.output_tokens += record.output_tokens.unwrap_or(0).max(0) as u64;
+        }
+        return by_model.into_values().collect();
+    }
+
+    if !modified_at(session).is_some_and(|at| in_month(at.date_naive())) {
+        return Vec::new();
+    }
+    vec![SessionUsage {
+        provider: default_provider,
+        model: metadata.model.clone().unwrap_or_else(|| "unknown".into()),
+        input_tokens: metadata.accumulated_input_tokens.unwrap_or(0).max(0) as u64,
+        output_tokens: metadata.accumulated_output_tokens.unwrap_or(0).max(0) as u64,
+    }]
+}
+
+/// Tokens used per model in the month, most tokens first. Costs are filled in separately
+fn aggregate(sessions: &[SessionInfo], start: NaiveDate, end: NaiveDate) -> Vec<ModelUsage> {
+    let mut by_model: BTreeMap<(String, String), ModelUsage> = BTreeMap::new();
+    for session in sessions {
+        for session_usage in session_usage(session, start, end) {
+            let usage = by_model
+                .entry((session_usage.provider.clone(), session_usage.model.clone()))
+                .or_insert_with(|| ModelUsage {
+                    provider: session_usage.provider,
+                    model: session_usage.model,
+                    sessions: 0,
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    cost: None,
+                });
+            usage.sessions += 1;
+            usage.input_tokens += session_usage.input_tokens;
+            usage.output_tokens += session_usage.output_tokens;
+        }
+    }
+
+    let mut models: Vec<ModelUsage> = by_model.into_values().collect();
+    models.sort_by_key(|usage| std::cmp::Reverse(usage.input_tokens + usage.output_tokens));
+    models
+}
+
+/// The spend of a session from its usage ledger, pricing each call with the model that served
+/// it. None when the session has no ledger or used a model without a known price
+pub async fn ledger_cost_usd(session_file: &Path) -> Option<f64> {
+    let ledger = read_usage(session_file).ok()?;
+    if ledger.is_empty() {
+        return None;
+    }
+    let mut tokens: BTreeMap<(String, String), (usize, usize)> = BTreeMap::new();
+    for record in ledger {
+        let entry = tokens.entry((record.provider?, record.model)).or_default();
+        entry.0 += record.input_tokens.unwrap_or(0).max(0) as usize;
+        entry.1 += record.output_tokens.unwrap_or(0).max(0) as usize;
+    }
+    let mut cost = 0.0;
+    for ((provider, model), (input_tokens, output_tokens)) in tokens {
+        cost += estimate_cost_usd(&provider, &model, input_tokens, output_tokens).await?;
+    }
+    Some(cost)
+}
+
+/// Report the spend of a month, the current one by default, from the token usage recorded for
+/// the sessions
+pub async fn handle_usage_report(month: Option<String>, format: &str) -> Result<()> {
+    let month = month.unwrap_or_else(|| chrono::Utc::now().format("%Y-%m").to_string());
+    let (start, end) = parse_month(&month)?;
+    let sessions = get_valid_sorted_sessions(SortOrder::Descending)
+        .map_err(|e| anyhow::anyhow!("Failed to list sessions: {}", e))?;
+
+    let mut models = aggregate(&sessions, start, end);
+    for usage in &mut models {
+        usage.cost = estimate_cost_usd(
+            &usage.provider,
+            &usage.model,
+            usage.input_tokens as usize,
+            usage.output_tokens as usize,
+        )
+        .await;
+    }
+    let report = UsageReport {
+        month,
+        sessions: models.iter().map(|usage| usage.sessions).sum(),
+        input_tokens: models.iter().map(|usage| usage.input_tokens).sum(),
+        output_tokens: models.iter().map(|usage| usage.output_tokens).sum(),
+        total_cost: models.iter().filter_map(|usage| usage.cost).sum(),
+        models,
+    };
+
+    if format == "json" {
+        println!("{}", serde_json::to_string(&report)?);
+        return Ok(());
+    }
+    if report.models.is_empty() {
+        println!("No sessions in {}", report.month);
+        return Ok(());
+    }
+
+    println!("{}", style(format!("Usage in {}", report.month)).bold());
+    println!(
+        "{}",
+        style(format!(
+            "{:<40} {:>8} {:>12} {:>12} {:>10}",
+            "MODEL", "SESSIONS", "INPUT", "OUTPUT", "COST"
+        ))
+        .bold()
+    );
+    for usage in &report.models {
+        let cost = usage
+            .cost
+            .map(|cost| format!("${:.2}", cost))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<40} {:>8} {:>12} {:>12} {:>10}",
+            safe_truncate(&format!("{}/{}", usage.provider, usage.model), 40),
+            usage.sessions,
+            usage.input_tokens,
+            usage.output_tokens,
+            cost
+        );
+    }
+    println!(
+        "{:<40} {:>8} {:>12} {:>12} {:>10}",
+        style("TOTAL").bold(),
+        report.sessions,
+        report.input_tokens,
+        report.output_tokens,
+        format!("${:.2}", report.total_cost)
+    );
+    if report.models.iter().any(|usage| usage.cost.is_none()) {
+        println!(
+            "{}",
+            style("Models without a known price are left out of the total, set their prices with GOOSE_PRICING_OVERRIDES").dim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::providers::base::{ProviderUsage, Usage};
+    use goose::session::usage::{append_usage, UsageRecord};
+    use goose::session::SessionMetadata;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn session(modified: &str, model: &str, input: i32, output: i32) -> SessionInfo {
+        let mut metadata = SessionMetadata::new(PathBuf::from("/tmp"));
+        metadata.provider = Some("anthropic".to_string());
+        metadata.model = Some(model.to_string());
+        metadata.accumulated_input_tokens = Some(input);
+        metadata.accumulated_output_tokens = Some(output);
+        SessionInfo {
+            id: modified.to_string(),
+            path: String::new(),
+            modified: modified.to_string(),
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_parse_month() {
+        let (start, end) = parse_month("2025-12").unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2025, 12, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+        assert!(parse_month("2025-13").is_err());
+        assert!(parse_month("january").is_err());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let sessions = vec![
+            session("2025-01-31 23:59:00 UTC", "claude-sonnet-4", 100, 10),
+            session("2025-01-02 08:00:00 UTC", "claude-sonnet-4", 50, 5),
+            session("2025-01-15 12:00:00 UTC", "claude-opus-4", 1000, 100),
+            session("2025-02-01 00:00:00 UTC", "claude-sonnet-4", 7, 7),
+        ];
+        let (start, end) = parse_month("2025-01").unwrap();
+        let models = aggregate(&sessions, start, end);
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].model, "claude-opus-4");
+        assert_eq!(
+            (
+                models[1].sessions,
+                models[1].input_tokens,
+                models[1].output_tokens
+            ),
+            (2, 150, 15)
+        );
+    }
+
+    #[test]
+    fn test_aggregate_from_ledger() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("20250131_230000.jsonl");
+        let record = |timestamp: &str, model: &str, input: i32| UsageRecord {
+            timestamp: chrono::DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .timestamp(),
+            ..UsageRecord::new(
+                Some("anthropic".to_string()),
+                &ProviderUsage::new(model.to_string(), Usage::new(Some(input), Some(1), None)),
+                Duration::from_millis(100),
+            )
+        };
+        // A session over midnight at the end of the month that switched models
+        append_usage(&path, &record("2025-01-31T23:00:00Z", "claude-opus-4", 100)).unwrap();
+        append_usage(
+            &path,
+            &record("2025-01-31T23:30:00Z", "claude-sonnet-4", 20),
+        )
+        .unwrap();
+        append_usage(
+            &path,
+            &record("2025-02-01T00:10:00Z", "claude-sonnet-4", 30),
+        )
+        .unwrap();
+
+        // The totals in the metadata are left out when there is a ledger
+        let mut session = session("2025-02-01 00:10:00 UTC", "claude-sonnet-4", 150, 3);
+        session.path = path.to_string_lossy().to_string();
+
+        let (start, end) = parse_month("2025-01").unwrap();
+        let january = aggregate(std::slice::from_ref(&session), start, end);
+        assert_eq!(january.len(), 2);
+        assert_eq!(
+            (january[0].model.as_str(), january[0].input_tokens),
+            ("claude-opus-4", 100)
+        );
+        assert_eq!(
+            (january[1].model.as_str(), january[1].input_tokens),
+            ("claude-sonnet-4", 20)
+        );
+
+        let (start, end) = parse_month("2025-02").unwrap();
+        let february = aggregate(&[session], start, end);
+        assert_eq!(february.len(), 1);
+        assert_eq!(
+            (february[0].input_tokens, february[0].output_tokens),
+            (30, 1)
+        );
+    }
+}
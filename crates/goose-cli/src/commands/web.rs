@@ -492,6 +492,7 @@ async fn process_message_streaming(
         execution_mode: None,
         max_turns: None,
         retry_config: None,
+        confirmation_timeout: None,
     };
 
     match agent
@@ -499,6 +500,8 @@ async fn process_message_streaming(
         .await
     {
         Ok(mut stream) => {
+            // Every message is saved as it comes, so a turn that ran to the end is saved
+            let mut finished = true;
             while let Some(result) = stream.next().await {
                 match result {
                     Ok(AgentEvent::Message(message)) => {
@@ -666,6 +669,13 @@ async fn process_message_streaming(
                         // Log model change
                         tracing::info!("Model changed to {} in {} mode", model, mode);
                     }
+                    Ok(AgentEvent::PlanUpdate(plan)) => {
+                        let (completed, total) = plan.progress();
+                        tracing::info!("Plan progress: {}/{} steps completed", completed, total);
+                    }
+                    Ok(AgentEvent::TodoUpdate(todos)) => {
+                        tracing::info!("TODO list updated, {} items", todos.len());
+                    }
 
                     Err(e) => {
                         error!("Error in message stream: {}", e);
@@ -679,10 +689,14 @@ async fn process_message_streaming(
                                 .into(),
                             ))
                             .await;
+                        finished = false;
                         break;
                     }
                 }
             }
+            if finished {
+                session::journal::commit(&session_file);
+            }
         }
         Err(e) => {
             error!("Error calling agent: {}", e);
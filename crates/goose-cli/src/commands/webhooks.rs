@@ -0,0 +1,73 @@
+use anyhow::{anyhow, Result};
+use console::style;
+use goose::config::Config;
+use goose::webhooks::{self, WebhookEvent};
+use serde_json::json;
+
+pub fn handle_webhooks_list() -> Result<()> {
+    let configured = webhooks::configured();
+    if configured.is_empty() {
+        println!(
+            "No webhooks configured, add them under `{}` in config.yaml",
+            webhooks::WEBHOOKS_KEY
+        );
+        return Ok(());
+    }
+
+    for webhook in configured {
+        let events = if webhook.events.is_empty() {
+            "all events".to_string()
+        } else {
+            webhook
+                .events
+                .iter()
+                .map(|event| event.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let signed = Config::global()
+            .get_secret::<String>(&webhook.secret_key())
+            .is_ok();
+        println!(
+            "{} {} {}",
+            style(&webhook.name).bold(),
+            webhook.url,
+            style(format!(
+                "({}{})",
+                events,
+                if signed { ", signed" } else { "" }
+            ))
+            .dim()
+        );
+    }
+    Ok(())
+}
+
+pub async fn handle_webhooks_test(name: Option<String>) -> Result<()> {
+    let configured: Vec<_> = webhooks::configured()
+        .into_iter()
+        .filter(|webhook| name.as_ref().is_none_or(|name| &webhook.name == name))
+        .collect();
+    if configured.is_empty() {
+        return Err(match name {
+            Some(name) => anyhow!("No webhook named {}", name),
+            None => anyhow!("No webhooks configured"),
+        });
+    }
+
+    let mut failed = 0;
+    for webhook in configured {
+        let data = json!({"message": "Test event from goose", "webhook": webhook.name});
+        match webhooks::deliver(&webhook, WebhookEvent::Test, data).await {
+            Ok(()) => println!("{} {}", style("✓").green(), webhook.name),
+            Err(e) => {
+                failed += 1;
+                println!("{} {}: {}", style("✗").red(), webhook.name, e);
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow!("{} webhook(s) failed", failed));
+    }
+    Ok(())
+}
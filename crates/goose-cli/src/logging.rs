@@ -1,11 +1,12 @@
 use anyhow::{Context, Result};
 use etcetera::{choose_app_strategy, AppStrategy};
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Once;
 use tokio::sync::Mutex;
-use tracing_appender::rolling::Rotation;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{
     filter::LevelFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
     Registry,
@@ -24,18 +25,92 @@ fn get_log_directory() -> Result<PathBuf> {
     get_log_directory_with_date(None)
 }
 
+/// Daily rotated files of a session's logs are kept for this many days
+const SESSION_LOG_MAX_FILES: usize = 14;
+
+/// Where the current session's logs are written, once there is a session
+static SESSION_LOG: std::sync::Mutex<Option<RollingFileAppender>> = std::sync::Mutex::new(None);
+
+fn get_base_log_directory() -> Result<PathBuf> {
+    let home_dir = choose_app_strategy(crate::APP_STRATEGY.clone())
+        .context("HOME environment variable not set")?;
+    Ok(home_dir
+        .in_state_dir("logs/cli")
+        .unwrap_or_else(|| home_dir.in_data_dir("logs/cli")))
+}
+
+/// Returns the directory holding the per-session log files, named `<session id>.<date>.log`
+pub fn get_session_log_directory() -> Result<PathBuf> {
+    let dir = get_base_log_directory()?.join("sessions");
+    fs::create_dir_all(&dir).context("Failed to create session log directory")?;
+    Ok(dir)
+}
+
+/// Write the logs from here on to the log file of `session_id` as well
+pub fn set_session_log(session_id: &str) -> Result<()> {
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(session_id)
+        .filename_suffix("log")
+        .max_log_files(SESSION_LOG_MAX_FILES)
+        .build(get_session_log_directory()?)
+        .context("Failed to create session log file")?;
+    *SESSION_LOG.lock().unwrap_or_else(|e| e.into_inner()) = Some(appender);
+    Ok(())
+}
+
+/// Writes to the current session's log file, or nowhere before a session has started
+struct SessionLogWriter;
+
+impl Write for SessionLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match SESSION_LOG
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_mut()
+        {
+            Some(appender) => appender.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match SESSION_LOG
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_mut()
+        {
+            Some(appender) => appender.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The levels logged to files unless RUST_LOG says otherwise
+fn default_file_filter() -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        // Set default levels for different modules
+        EnvFilter::new("")
+            // Set mcp-server module to DEBUG
+            .add_directive("mcp_server=debug".parse().unwrap())
+            // Set mcp-client to DEBUG
+            .add_directive("mcp_client=debug".parse().unwrap())
+            // Set goose module to DEBUG
+            .add_directive("goose=debug".parse().unwrap())
+            // Set goose-cli to INFO
+            .add_directive("goose_cli=info".parse().unwrap())
+            // Set everything else to WARN
+            .add_directive(LevelFilter::WARN.into())
+    })
+}
+
 /// Internal function that allows specifying a custom date string for testing
 fn get_log_directory_with_date(test_date: Option<String>) -> Result<PathBuf> {
     // choose_app_strategy().state_dir()
     // - macOS/Linux: ~/.local/state/goose/logs/cli
     // - Windows:     ~\AppData\Roaming\Block\goose\data\logs\cli
     // - Windows has no convention for state_dir, use data_dir instead
-    let home_dir = choose_app_strategy(crate::APP_STRATEGY.clone())
-        .context("HOME environment variable not set")?;
-
-    let base_log_dir = home_dir
-        .in_state_dir("logs/cli")
-        .unwrap_or_else(|| home_dir.in_data_dir("logs/cli"));
+    let base_log_dir = get_base_log_directory()?;
 
     // Create date-based subdirectory
     let date_str = test_date.unwrap_or_else(|| {
@@ -53,6 +128,7 @@ fn get_log_directory_with_date(test_date: Option<String>) -> Result<PathBuf> {
 /// Sets up the logging infrastructure for the application.
 /// This includes:
 /// - File-based logging with JSON formatting (DEBUG level)
+/// - The same logs in a daily rotated file per session, see `set_session_log`
 /// - Console output for development (INFO level)
 /// - Optional Langfuse integration (DEBUG level)
 /// - Optional error capture layer for benchmarking
@@ -104,6 +180,14 @@ fn setup_logging_internal(
                 .with_ansi(false)
                 .json();
 
+            // The same logs, for the session that is running
+            let session_layer = fmt::layer()
+                .with_target(true)
+                .with_level(true)
+                .with_writer(|| SessionLogWriter)
+                .with_ansi(false)
+                .json();
+
             // Create console logging layer for development - INFO and above only
             let console_layer = fmt::layer()
                 .with_target(true)
@@ -113,25 +197,10 @@ fn setup_logging_internal(
                 .with_line_number(true)
                 .pretty();
 
-            // Base filter
-            let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-                // Set default levels for different modules
-                EnvFilter::new("")
-                    // Set mcp-server module to DEBUG
-                    .add_directive("mcp_server=debug".parse().unwrap())
-                    // Set mcp-client to DEBUG
-                    .add_directive("mcp_client=debug".parse().unwrap())
-                    // Set goose module to DEBUG
-                    .add_directive("goose=debug".parse().unwrap())
-                    // Set goose-cli to INFO
-                    .add_directive("goose_cli=info".parse().unwrap())
-                    // Set everything else to WARN
-                    .add_directive(LevelFilter::WARN.into())
-            });
-
             // Start building the subscriber
             let mut layers = vec![
-                file_layer.with_filter(env_filter).boxed(),
+                file_layer.with_filter(default_file_filter()).boxed(),
+                session_layer.with_filter(default_file_filter()).boxed(),
                 console_layer.with_filter(LevelFilter::WARN).boxed(),
             ];
 
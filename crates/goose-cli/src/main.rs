@@ -1,5 +1,10 @@
 use anyhow::Result;
+use goose::shutdown;
 use goose_cli::cli::cli;
+use std::time::Duration;
+
+/// How long a running session gets to record its turn after SIGTERM before goose exits anyway
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -7,7 +12,18 @@ async fn main() -> Result<()> {
         eprintln!("Warning: Failed to initialize telemetry: {}", e);
     }
 
+    tokio::spawn(async {
+        goose_cli::signal::terminate_signal().await;
+        shutdown::request();
+        // A session waiting on the prompt can't notice the request, so exit after the grace period
+        tokio::time::sleep(SHUTDOWN_GRACE).await;
+        shutdown::terminate_process_groups(shutdown::TERMINATE_GRACE).await;
+        std::process::exit(143);
+    });
+
+    goose::config::Config::global().load_remote_policy().await;
     let result = cli().await;
+    shutdown::terminate_process_groups(shutdown::TERMINATE_GRACE).await;
 
     // Only wait for telemetry flush if OTLP is configured
     let should_wait = goose::config::Config::global()
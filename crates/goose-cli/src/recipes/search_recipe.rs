@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Result};
+use etcetera::{choose_app_strategy, AppStrategy};
 use goose::config::Config;
 use goose::recipe::read_recipe_file_content::{read_recipe_file, RecipeFile};
 use goose::recipe::template_recipe::parse_recipe_content;
@@ -64,7 +65,17 @@ fn read_recipe_in_dir(dir: &Path, recipe_name: &str) -> Result<RecipeFile> {
     )))
 }
 
-fn retrieve_recipe_from_local_path(recipe_name: &str) -> Result<RecipeFile> {
+/// The recipes directory in the goose config dir, where `goose config import` puts recipes
+pub fn local_recipes_dir() -> PathBuf {
+    choose_app_strategy(crate::APP_STRATEGY.clone())
+        .expect("goose requires a home dir")
+        .config_dir()
+        .join("recipes")
+}
+
+/// Directories searched for recipes by name: the current directory, GOOSE_RECIPE_PATH and
+/// the local recipes directory
+fn local_search_dirs() -> Vec<PathBuf> {
     let mut search_dirs = vec![PathBuf::from(".")];
     if let Ok(recipe_path_env) = env::var(GOOSE_RECIPE_PATH_ENV_VAR) {
         let path_separator = if cfg!(windows) { ';' } else { ':' };
@@ -74,6 +85,12 @@ fn retrieve_recipe_from_local_path(recipe_name: &str) -> Result<RecipeFile> {
             .collect();
         search_dirs.extend(recipe_path_env_dirs);
     }
+    search_dirs.push(local_recipes_dir());
+    search_dirs
+}
+
+fn retrieve_recipe_from_local_path(recipe_name: &str) -> Result<RecipeFile> {
+    let search_dirs = local_search_dirs();
     for dir in &search_dirs {
         if let Ok(result) = read_recipe_in_dir(dir, recipe_name) {
             return Ok(result);
@@ -121,19 +138,7 @@ pub fn list_available_recipes() -> Result<Vec<RecipeInfo>> {
 
 fn discover_local_recipes() -> Result<Vec<RecipeInfo>> {
     let mut recipes = Vec::new();
-    let mut search_dirs = vec![PathBuf::from(".")];
-
-    // Add GOOSE_RECIPE_PATH directories
-    if let Ok(recipe_path_env) = env::var(GOOSE_RECIPE_PATH_ENV_VAR) {
-        let path_separator = if cfg!(windows) { ';' } else { ':' };
-        let recipe_path_env_dirs: Vec<PathBuf> = recipe_path_env
-            .split(path_separator)
-            .map(PathBuf::from)
-            .collect();
-        search_dirs.extend(recipe_path_env_dirs);
-    }
-
-    for dir in search_dirs {
+    for dir in local_search_dirs() {
         if let Ok(dir_recipes) = scan_directory_for_recipes(&dir) {
             recipes.extend(dir_recipes);
         }
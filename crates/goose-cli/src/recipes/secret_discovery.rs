@@ -173,6 +173,7 @@ mod tests {
             response: None,
             sub_recipes: None,
             retry: None,
+            schedule: None,
         }
     }
 
@@ -217,6 +218,7 @@ mod tests {
             response: None,
             sub_recipes: None,
             retry: None,
+            schedule: None,
         };
 
         let secrets = discover_recipe_secrets(&recipe);
@@ -262,6 +264,7 @@ mod tests {
             response: None,
             sub_recipes: None,
             retry: None,
+            schedule: None,
         };
 
         let secrets = discover_recipe_secrets(&recipe);
@@ -315,6 +318,7 @@ mod tests {
             parameters: None,
             response: None,
             retry: None,
+            schedule: None,
         };
 
         let secrets = discover_recipe_secrets(&recipe);
@@ -211,6 +211,7 @@ where
             Arc::new(Mutex::new(Box::new(mock_client))),
             None,
             None,
+            None,
         )
         .await;
 
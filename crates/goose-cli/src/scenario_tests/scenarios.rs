@@ -77,6 +77,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "the recordings predate the compaction retry, record them again with API keys"]
     async fn test_context_length_exceeded_error() -> Result<()> {
         run_scenario(
             "context_length_exceeded",
@@ -90,12 +91,12 @@ mod tests {
             }),
             Some(&["OpenAI"]),
             |result| {
-                // this is unfortunate; we don't seem to actually catch the errors in this path,
-                // but instead eat it:
-                assert_eq!(
-                    result.messages.len(),
-                    0,
-                    "Expected no messages due to compaction"
+                // A single message has nothing to summarize, so compacting cannot make it fit
+                let error = result.error.as_deref().unwrap_or_default();
+                assert!(
+                    error.contains("Context length exceeded"),
+                    "Expected the context length error: {:?}",
+                    result.error
                 );
                 Ok(())
             },
@@ -1,19 +1,24 @@
 use console::style;
+use goose::agents::script_hooks::{hooks_dir, load_script_hooks};
 use goose::agents::types::RetryConfig;
 use goose::agents::Agent;
-use goose::config::{Config, ExtensionConfig, ExtensionConfigManager};
+use goose::config::extensions::name_to_key;
+use goose::config::{Config, ExtensionConfig, ExtensionConfigManager, ProjectOverlay};
 use goose::providers::create;
 use goose::recipe::{Response, SubRecipe};
+use goose::redaction::Redactor;
 use goose::session;
 use goose::session::Identifier;
 use rustyline::EditMode;
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::process;
 use std::sync::Arc;
 use tokio::task::JoinSet;
 
+use super::env::SessionEnvVar;
 use super::output;
-use super::Session;
+use super::{OutputFormat, Session};
 
 /// Configuration for building a new Goose session
 ///
@@ -57,12 +62,18 @@ pub struct SessionBuilderConfig {
     pub interactive: bool,
     /// Quiet mode - suppress non-response output
     pub quiet: bool,
+    /// How headless runs report the agent's events
+    pub output_format: OutputFormat,
     /// Sub-recipes to add to the session
     pub sub_recipes: Option<Vec<SubRecipe>>,
     /// Final output expected response
     pub final_output_response: Option<Response>,
     /// Retry configuration for automated validation and recovery
     pub retry_config: Option<RetryConfig>,
+    /// Environment variables for the shell and stdio extensions of this session only
+    pub env: Vec<SessionEnvVar>,
+    /// Roots of the workspace declared for this session, on top of those of the project config
+    pub workspace_roots: Vec<PathBuf>,
 }
 
 /// Offers to help debug an extension failure by creating a minimal debugging session
@@ -212,6 +223,36 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     // Create the agent
     let agent: Agent = Agent::new();
 
+    // Set before any extension starts, so they all get the variables
+    if !session_config.env.is_empty() {
+        Redactor::register_secrets(
+            session_config
+                .env
+                .iter()
+                .filter(|var| var.secret)
+                .map(|var| var.value.clone()),
+        );
+        agent
+            .extension_manager
+            .set_session_env(
+                session_config
+                    .env
+                    .iter()
+                    .map(|var| (var.key.clone(), var.value.clone()))
+                    .collect(),
+            )
+            .await;
+    }
+
+    // Also before any extension starts, so the developer extension sees every root
+    if let Err(e) = agent
+        .set_workspace_roots(&session_config.workspace_roots)
+        .await
+    {
+        output::render_error(&format!("Invalid workspace root: {}", e));
+        process::exit(1);
+    }
+
     if let Some(sub_recipes) = session_config.sub_recipes {
         agent.add_sub_recipes(sub_recipes).await;
     }
@@ -220,6 +261,10 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         agent.add_final_output_tool(final_output_response).await;
     }
 
+    for hook in load_script_hooks(&hooks_dir()) {
+        agent.register_hook(hook).await;
+    }
+
     let new_provider = match create(&provider_name, model_config) {
         Ok(provider) => provider,
         Err(e) => {
@@ -309,6 +354,18 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         }
     };
 
+    if !session_config.no_session {
+        if let Err(e) = session::retention::apply_configured_policy(session_file.as_deref()) {
+            tracing::warn!("Failed to apply the session retention policy: {}", e);
+        }
+    }
+
+    if let Some(session_id) = session_file.as_ref().and_then(|file| file.file_stem()) {
+        if let Err(e) = crate::logging::set_session_log(&session_id.to_string_lossy()) {
+            tracing::warn!("Failed to set up the session log: {}", e);
+        }
+    }
+
     if session_config.resume {
         if let Some(session_file) = session_file.as_ref() {
             // Read the session metadata
@@ -339,21 +396,75 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
                     }
                 }
             }
+
+            // Files attached earlier in the session stay readable as resources
+            for attachment in metadata.attachments {
+                agent.add_attachment(attachment).await;
+            }
         }
     }
 
+    // A journal left behind means goose stopped mid-turn, reconcile before loading the messages
+    let replay_turn = match session_file.as_ref().filter(|_| session_config.resume) {
+        Some(session_file) => match session::journal::recover(session_file) {
+            Ok(Some(recovery)) => {
+                output::render_turn_recovery(&recovery);
+                recovery
+                    .conversation
+                    .last()
+                    .is_some_and(|message| message.role == rmcp::model::Role::User)
+            }
+            Ok(None) => false,
+            Err(e) => {
+                output::render_error(&format!("Failed to recover the interrupted turn: {}", e));
+                false
+            }
+        },
+        None => false,
+    };
+
     // Setup extensions for the agent
+    if let Err(e) = ExtensionConfigManager::migrate_secrets() {
+        tracing::warn!("Failed to copy extension secrets into their scopes: {}", e);
+    }
+
     // Extensions need to be added after the session is created because we change directory when resuming a session
     // If we get extensions_override, only run those extensions and none other
-    let extensions_to_run: Vec<_> = if let Some(extensions) = session_config.extensions_override {
+    let project = ProjectOverlay::current();
+    // Each with whether it comes from the extension config, which `/extensions reload` manages
+    let extensions_to_run: Vec<(ExtensionConfig, bool)> = if let Some(extensions) =
+        session_config.extensions_override
+    {
         agent.disable_router_for_recipe().await;
-        extensions.into_iter().collect()
+        extensions.into_iter().map(|ext| (ext, false)).collect()
     } else {
-        ExtensionConfigManager::get_all()
-            .expect("should load extensions")
+        // Extensions the project asks for run even when they are disabled globally
+        let project_extensions: HashSet<String> = project
+            .as_ref()
+            .map(|p| p.config.extensions.iter().map(|e| name_to_key(e)).collect())
+            .unwrap_or_default();
+        let all_extensions = ExtensionConfigManager::get_all().expect("should load extensions");
+        for name in &project_extensions {
+            if !all_extensions
+                .iter()
+                .any(|ext| name_to_key(&ext.config.name()) == *name)
+            {
+                eprintln!(
+                    "{}",
+                    style(format!(
+                        "Warning: extension '{}' from the project config is not configured, run 'goose configure' to add it",
+                        name
+                    ))
+                    .yellow()
+                );
+            }
+        }
+        all_extensions
             .into_iter()
-            .filter(|ext| ext.enabled)
-            .map(|ext| ext.config)
+            .filter(|ext| {
+                ext.enabled || project_extensions.contains(&name_to_key(&ext.config.name()))
+            })
+            .map(|ext| (ext.config, ext.enabled))
             .collect()
     };
 
@@ -361,14 +472,17 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     let agent_ptr = Arc::new(agent);
 
     let mut waiting_on = HashSet::new();
-    for extension in extensions_to_run {
+    for (extension, from_config) in extensions_to_run {
         waiting_on.insert(extension.name());
         let agent_ptr = agent_ptr.clone();
         set.spawn(async move {
-            (
-                extension.name(),
-                agent_ptr.add_extension(extension.clone()).await,
-            )
+            let name = extension.name();
+            let result = if from_config {
+                agent_ptr.add_configured_extension(extension).await
+            } else {
+                agent_ptr.add_extension(extension).await
+            };
+            (name, result)
         });
     }
 
@@ -378,22 +492,32 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         format!("starting {} extensions: {}", names.len(), names.join(", "))
     };
 
-    let spinner = cliclack::spinner();
-    spinner.start(get_message(&waiting_on));
+    let spinner = if output::is_plain_output() {
+        eprintln!("{}", get_message(&waiting_on));
+        None
+    } else {
+        let spinner = cliclack::spinner();
+        spinner.start(get_message(&waiting_on));
+        Some(spinner)
+    };
 
     let mut offer_debug = Vec::new();
     while let Some(result) = set.join_next().await {
         match result {
             Ok((name, Ok(_))) => {
                 waiting_on.remove(&name);
-                spinner.set_message(get_message(&waiting_on));
+                if let Some(spinner) = &spinner {
+                    spinner.set_message(get_message(&waiting_on));
+                }
             }
             Ok((name, Err(e))) => offer_debug.push((name, e)),
             Err(e) => tracing::error!("failed to add extension: {}", e),
         }
     }
 
-    spinner.clear();
+    if let Some(spinner) = spinner {
+        spinner.clear();
+    }
 
     for (name, err) in offer_debug {
         if let Err(debug_err) = offer_extension_debugging_help(
@@ -431,6 +555,9 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         edit_mode,
         session_config.retry_config.clone(),
     );
+    if replay_turn && session_config.interactive {
+        session.replay_interrupted_turn();
+    }
 
     // Add extensions if provided
     for extension_str in session_config.extensions {
@@ -577,16 +704,42 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         session.agent.extend_system_prompt(additional_prompt).await;
     }
 
-    // Only override system prompt if a system override exists
-    let system_prompt_file: Option<String> = config.get_param("GOOSE_SYSTEM_PROMPT_FILE_PATH").ok();
-    if let Some(ref path) = system_prompt_file {
-        let override_prompt =
-            std::fs::read_to_string(path).expect("Failed to read system prompt file");
-        session.agent.override_system_prompt(override_prompt).await;
+    if let Some(hints) = project.as_ref().and_then(|p| p.config.goosehints.clone()) {
+        session
+            .agent
+            .extend_system_prompt(format!("Hints for this project:\n{}", hints))
+            .await;
+    }
+
+    // Only override system prompt if a system override exists, a project's own template wins
+    let project_prompt_file = std::env::current_dir()
+        .ok()
+        .and_then(|dir| ProjectOverlay::find_system_prompt(&dir));
+    if let Some(path) = project_prompt_file {
+        match std::fs::read_to_string(&path) {
+            Ok(override_prompt) => session.agent.override_system_prompt(override_prompt).await,
+            Err(e) => tracing::warn!("Ignoring system prompt {}: {}", path.display(), e),
+        }
+    } else {
+        let system_prompt_file: Option<String> =
+            config.get_param("GOOSE_SYSTEM_PROMPT_FILE_PATH").ok();
+        if let Some(ref path) = system_prompt_file {
+            let override_prompt =
+                std::fs::read_to_string(path).expect("Failed to read system prompt file");
+            session.agent.override_system_prompt(override_prompt).await;
+        }
+    }
+
+    session.set_output_format(session_config.output_format);
+
+    if session_config.resume {
+        if let Err(e) = session.compact_resumed_history().await {
+            tracing::warn!("Failed to summarize the resumed conversation: {}", e);
+        }
     }
 
-    // Display session information unless in quiet mode
-    if !session_config.quiet {
+    // Display session information unless in quiet mode or writing ndjson
+    if !session_config.quiet && session_config.output_format == OutputFormat::Text {
         output::display_session_info(
             session_config.resume,
             &provider_name,
@@ -623,9 +776,12 @@ mod tests {
             scheduled_job_id: None,
             interactive: true,
             quiet: false,
+            output_format: OutputFormat::Text,
             sub_recipes: None,
             final_output_response: None,
             retry_config: None,
+            env: Vec::new(),
+            workspace_roots: Vec::new(),
         };
 
         assert_eq!(config.extensions.len(), 1);
@@ -0,0 +1,392 @@
+use anyhow::{Context, Result};
+use etcetera::{choose_app_strategy, AppStrategy};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// A slash command built into the CLI session
+pub struct BuiltinCommand {
+    /// Names the command can be invoked with, the first one is the canonical name
+    pub names: &'static [&'static str],
+    /// Argument synopsis shown in help, empty when the command takes no arguments
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+/// Built-in commands in the order they are listed by /help
+pub const BUILTIN_COMMANDS: &[BuiltinCommand] = &[
+    BuiltinCommand {
+        names: &["/exit", "/quit"],
+        usage: "",
+        description: "Exit the session",
+    },
+    BuiltinCommand {
+        names: &["/t"],
+        usage: "[name]",
+        description: "Toggle Light/Dark/Ansi theme, or set it directly (light, dark, ansi)",
+    },
+    BuiltinCommand {
+        names: &["/extension"],
+        usage: "<command>",
+        description: "Add a stdio extension (format: ENV1=val1 command args...)",
+    },
+    BuiltinCommand {
+        names: &["/builtin"],
+        usage: "<names>",
+        description: "Add builtin extensions by name (comma-separated)",
+    },
+    BuiltinCommand {
+        names: &["/extensions"],
+        usage: "reload",
+        description: "Start, restart and stop extensions to match the extension config",
+    },
+    BuiltinCommand {
+        names: &["/prompts"],
+        usage: "[--extension <name>]",
+        description: "List all available prompts, optionally filtered by extension",
+    },
+    BuiltinCommand {
+        names: &["/prompt"],
+        usage: "[<extension>:]<n> [--info] [key=value...]",
+        description: "Get prompt info or execute a prompt, asking for any missing arguments",
+    },
+    BuiltinCommand {
+        names: &["/mode"],
+        usage: "<name>",
+        description: "Set the goose mode to use ('auto', 'approve', 'chat', 'smart_approve')",
+    },
+    BuiltinCommand {
+        names: &["/plan"],
+        usage: "<message_text>",
+        description: " Enters 'plan' mode with optional message. Create a plan based on the current messages and asks user if they want to act on it.
+                        If user acts on the plan, goose mode is set to 'auto' and returns to 'normal' goose mode.
+                        To warm up goose before using '/plan', we recommend setting '/mode approve' & putting appropriate context into goose.
+                        The model is used based on $GOOSE_PLANNER_PROVIDER and $GOOSE_PLANNER_MODEL environment variables.
+                        If no model is set, the default model is used.",
+    },
+    BuiltinCommand {
+        names: &["/endplan"],
+        usage: "",
+        description: "Exit plan mode and return to 'normal' goose mode.",
+    },
+    BuiltinCommand {
+        names: &["/recipe"],
+        usage: "[filepath]",
+        description: "Generate a recipe from the current conversation and save it to the specified filepath (must end with .yaml).
+                       If no filepath is provided, it will be saved to ./recipe.yaml.",
+    },
+    BuiltinCommand {
+        names: &["/summarize"],
+        usage: "",
+        description: "Summarize the current conversation to reduce context length while preserving key information.",
+    },
+    BuiltinCommand {
+        names: &["/system"],
+        usage: "",
+        description: "Show the system prompt goose sends, with the instructions of the running extensions",
+    },
+    BuiltinCommand {
+        names: &["/attach"],
+        usage: "<path-or-url>",
+        description: "Attach a file, image or web page to your next message.
+                        Images are downscaled to fit provider limits. Other content is previewed in the message
+                        and the full content is available to goose as a resource.",
+    },
+    BuiltinCommand {
+        names: &["/cd"],
+        usage: "<path>",
+        description: "Change the working directory of the session. Goose runs commands there and extensions are told about it.",
+    },
+    BuiltinCommand {
+        names: &["/?", "/help"],
+        usage: "",
+        description: "Display this help message",
+    },
+    BuiltinCommand {
+        names: &["/clear"],
+        usage: "",
+        description: "Clears the current chat history",
+    },
+    BuiltinCommand {
+        names: &["/undo"],
+        usage: "",
+        description: "Remove your last message and everything goose did in response to it",
+    },
+];
+
+/// A tool to call before sending a custom command's prompt, its output is available to the
+/// prompt template as `tool_results`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolPreCall {
+    /// Full tool name including the extension prefix, e.g. `developer__shell`
+    pub name: String,
+    #[serde(default = "empty_arguments")]
+    pub arguments: Value,
+}
+
+fn empty_arguments() -> Value {
+    Value::Object(Map::new())
+}
+
+/// A user-defined command loaded from `~/.config/goose/commands/*.yaml`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomCommand {
+    /// Name without the leading slash
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Jinja template rendered with `args`, `argv`, `tool_results` and any `key=value` arguments
+    pub prompt: String,
+    #[serde(default)]
+    pub tools: Vec<ToolPreCall>,
+}
+
+impl CustomCommand {
+    /// Render the prompt for an invocation of this command
+    pub fn render_prompt(&self, args: &str, tool_results: &str) -> Result<String> {
+        let argv = shlex::split(args).unwrap_or_else(|| {
+            args.split_whitespace()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        });
+
+        let mut context = Map::new();
+        for arg in &argv {
+            if let Some((key, value)) = arg.split_once('=') {
+                context.insert(key.to_string(), Value::String(value.to_string()));
+            }
+        }
+        context.insert("args".to_string(), Value::String(args.to_string()));
+        context.insert(
+            "argv".to_string(),
+            Value::Array(argv.into_iter().map(Value::String).collect()),
+        );
+        context.insert(
+            "tool_results".to_string(),
+            Value::String(tool_results.to_string()),
+        );
+
+        goose::prompt_template::render_inline_once(&self.prompt, &context)
+            .with_context(|| format!("Failed to render prompt for /{}", self.name))
+    }
+}
+
+/// An invocation of a custom command typed by the user
+#[derive(Debug)]
+pub struct CustomCommandInvocation {
+    pub name: String,
+    pub args: String,
+}
+
+/// All slash commands available in a session: the built-in ones plus user-defined commands
+#[derive(Debug, Default)]
+pub struct CommandRegistry {
+    custom: Vec<CustomCommand>,
+}
+
+impl CommandRegistry {
+    /// Directory user-defined commands are loaded from
+    pub fn commands_dir() -> Result<PathBuf> {
+        Ok(choose_app_strategy(crate::APP_STRATEGY.clone())
+            .context("goose requires a home dir")?
+            .config_dir()
+            .join("commands"))
+    }
+
+    /// Load user-defined commands, warning about (and skipping) any that are invalid
+    pub fn load() -> Self {
+        match Self::commands_dir() {
+            Ok(dir) => Self::load_from_dir(&dir),
+            Err(e) => {
+                eprintln!("Warning: not loading custom commands: {:#}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn load_from_dir(dir: &Path) -> Self {
+        let mut registry = Self::default();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return registry;
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml")
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match Self::load_command(&path) {
+                Ok(command) => {
+                    if let Err(e) = registry.register(command) {
+                        eprintln!("Warning: Skipping command {}: {}", path.display(), e);
+                    }
+                }
+                Err(e) => eprintln!("Warning: Skipping command {}: {:#}", path.display(), e),
+            }
+        }
+        registry
+    }
+
+    fn load_command(path: &Path) -> Result<CustomCommand> {
+        let contents = std::fs::read_to_string(path)?;
+        let command: CustomCommand = serde_yaml::from_str(&contents)?;
+        Ok(command)
+    }
+
+    /// Add a user-defined command, refusing names that are invalid or already taken
+    pub fn register(&mut self, mut command: CustomCommand) -> Result<()> {
+        command.name = command.name.trim_start_matches('/').to_string();
+        if command.name.is_empty() || command.name.contains(char::is_whitespace) {
+            anyhow::bail!("'{}' is not a valid command name", command.name);
+        }
+
+        let slash_name = format!("/{}", command.name);
+        if BUILTIN_COMMANDS
+            .iter()
+            .any(|builtin| builtin.names.contains(&slash_name.as_str()))
+        {
+            anyhow::bail!("{} is a built-in command", slash_name);
+        }
+        if self.get(&command.name).is_some() {
+            anyhow::bail!("{} is already defined", slash_name);
+        }
+
+        self.custom.push(command);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CustomCommand> {
+        self.custom.iter().find(|c| c.name == name)
+    }
+
+    pub fn custom_commands(&self) -> &[CustomCommand] {
+        &self.custom
+    }
+
+    /// Names of every command, built-in and custom, with their leading slash
+    pub fn command_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for builtin in BUILTIN_COMMANDS {
+            for name in builtin.names {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.extend(self.custom.iter().map(|c| format!("/{}", c.name)));
+        names
+    }
+
+    /// Match input like `/name some args` against the user-defined commands
+    pub fn parse(&self, input: &str) -> Option<CustomCommandInvocation> {
+        let input = input.trim().strip_prefix('/')?;
+        let (name, args) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        self.get(name).map(|command| CustomCommandInvocation {
+            name: command.name.clone(),
+            args: args.trim().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn command(name: &str, prompt: &str) -> CustomCommand {
+        CustomCommand {
+            name: name.to_string(),
+            description: String::new(),
+            prompt: prompt.to_string(),
+            tools: vec![],
+        }
+    }
+
+    #[test]
+    fn test_load_from_dir() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("review.yaml"),
+            "name: review\ndescription: Review a file\nprompt: Review {{ args }}\ntools:\n  - name: developer__shell\n    arguments:\n      command: git diff\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("broken.yaml"), "name: [").unwrap();
+        std::fs::write(dir.path().join("exit.yaml"), "name: exit\nprompt: bye").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let registry = CommandRegistry::load_from_dir(dir.path());
+        assert_eq!(registry.custom_commands().len(), 1);
+
+        let review = registry.get("review").unwrap();
+        assert_eq!(review.description, "Review a file");
+        assert_eq!(review.tools.len(), 1);
+        assert_eq!(review.tools[0].name, "developer__shell");
+        assert_eq!(review.tools[0].arguments["command"], "git diff");
+    }
+
+    #[test]
+    fn test_load_from_missing_dir() {
+        let registry = CommandRegistry::load_from_dir(Path::new("/does/not/exist"));
+        assert!(registry.custom_commands().is_empty());
+    }
+
+    #[test]
+    fn test_register_rejects_duplicates_and_builtins() {
+        let mut registry = CommandRegistry::default();
+        assert!(registry.register(command("/standup", "x")).is_ok());
+        assert!(registry.get("standup").is_some());
+        assert!(registry.register(command("standup", "y")).is_err());
+        assert!(registry.register(command("help", "y")).is_err());
+        assert!(registry.register(command("two words", "y")).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut registry = CommandRegistry::default();
+        registry.register(command("review", "x")).unwrap();
+
+        let invocation = registry.parse("/review src/main.rs  ").unwrap();
+        assert_eq!(invocation.name, "review");
+        assert_eq!(invocation.args, "src/main.rs");
+
+        let invocation = registry.parse("/review").unwrap();
+        assert_eq!(invocation.args, "");
+
+        assert!(registry.parse("/unknown").is_none());
+        assert!(registry.parse("review").is_none());
+    }
+
+    #[test]
+    fn test_command_names() {
+        let mut registry = CommandRegistry::default();
+        registry.register(command("review", "x")).unwrap();
+        let names = registry.command_names();
+        assert!(names.contains(&"/exit".to_string()));
+        assert!(names.contains(&"/review".to_string()));
+        assert_eq!(names.iter().filter(|n| *n == "/t").count(), 1);
+        assert_eq!(
+            BUILTIN_COMMANDS
+                .iter()
+                .filter(|command| command.names.contains(&"/t"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_render_prompt() {
+        let command = command(
+            "review",
+            "Review {{ argv[0] }} focusing on {{ focus }}\n{{ tool_results }}",
+        );
+        let prompt = command
+            .render_prompt("src/main.rs focus=errors", "diff output")
+            .unwrap();
+        assert_eq!(prompt, "Review src/main.rs focusing on errors\ndiff output");
+    }
+}
@@ -31,15 +31,27 @@ impl GooseCompleter {
         // Get available prompts from cache
         let cache = self.completion_cache.read().unwrap();
 
+        // Offer `<extension>:<name>` once the extension part has been typed
+        let prefix = prefix.trim();
+        let qualified = prefix.contains(':');
+
         // Create completion candidates that match the prefix
         let candidates: Vec<Pair> = cache
             .prompts
             .iter()
-            .flat_map(|(_, names)| names)
-            .filter(|name| name.starts_with(prefix.trim()))
+            .flat_map(|(extension, names)| {
+                names.iter().map(move |name| {
+                    if qualified {
+                        format!("{}:{}", extension, name)
+                    } else {
+                        name.clone()
+                    }
+                })
+            })
+            .filter(|name| name.starts_with(prefix))
             .map(|name| Pair {
                 display: name.clone(),
-                replacement: name.clone(),
+                replacement: name,
             })
             .collect();
 
@@ -121,20 +133,9 @@ impl GooseCompleter {
 
     /// Complete slash commands
     fn complete_slash_commands(&self, line: &str) -> Result<(usize, Vec<Pair>)> {
-        // Define available slash commands
-        let commands = [
-            "/exit",
-            "/quit",
-            "/help",
-            "/?",
-            "/t",
-            "/extension",
-            "/builtin",
-            "/prompts",
-            "/prompt",
-            "/mode",
-            "/recipe",
-        ];
+        // Built-in and user-defined commands are both kept in the cache
+        let cache = self.completion_cache.read().unwrap();
+        let commands = &cache.commands;
 
         // Find commands that match the prefix
         let matching_commands: Vec<Pair> = commands
@@ -372,6 +373,14 @@ impl Completer for GooseCompleter {
                 return self.complete_mode_flags(line);
             }
 
+            // Commands that take a path as their argument
+            if line.starts_with("/attach ")
+                || line.starts_with("/recipe ")
+                || line.starts_with("/cd ")
+            {
+                return self.complete_file_path(line, ctx);
+            }
+
             return Ok((pos, vec![]));
         }
 
@@ -390,7 +399,7 @@ impl Hinter for GooseCompleter {
     fn hint(&self, line: &str, _pos: usize, _ctx: &Context<'_>) -> Option<Self::Hint> {
         // Only show hint when line is empty
         if line.is_empty() {
-            Some("Press Enter to send, Ctrl-J for new line".to_string())
+            Some("Press Enter to send, Ctrl-J for new line, Ctrl-R to search history".to_string())
         } else {
             None
         }
@@ -424,9 +433,13 @@ impl Highlighter for GooseCompleter {
 impl Validator for GooseCompleter {
     fn validate(
         &self,
-        _ctx: &mut rustyline::validate::ValidationContext,
+        ctx: &mut rustyline::validate::ValidationContext,
     ) -> Result<rustyline::validate::ValidationResult> {
-        Ok(rustyline::validate::ValidationResult::Valid(None))
+        if super::input::is_incomplete(ctx.input()) {
+            Ok(rustyline::validate::ValidationResult::Incomplete)
+        } else {
+            Ok(rustyline::validate::ValidationResult::Valid(None))
+        }
     }
 }
 
@@ -560,6 +573,14 @@ mod tests {
             .unwrap();
         assert_eq!(pos, 8);
         assert_eq!(candidates.len(), 0);
+
+        // Test with a name qualified by its extension
+        let (pos, candidates) = completer
+            .complete_prompt_names("/prompt extension1:test_prompt2")
+            .unwrap();
+        assert_eq!(pos, 8);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].display, "extension1:test_prompt2");
     }
 
     #[test]
@@ -639,4 +660,19 @@ mod tests {
             .unwrap();
         assert_eq!(candidates.len(), 0);
     }
+
+    #[test]
+    fn test_complete_attach_path() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("screenshot.png"), "").unwrap();
+        let completer = GooseCompleter::new(create_test_cache());
+        let history = rustyline::history::DefaultHistory::new();
+        let ctx = Context::new(&history);
+
+        let line = format!("/attach {}/scr", dir.path().display());
+        let (pos, candidates) = completer.complete(&line, line.len(), &ctx).unwrap();
+        assert_eq!(pos, "/attach ".len());
+        assert_eq!(candidates.len(), 1);
+        assert!(candidates[0].replacement.ends_with("screenshot.png"));
+    }
 }
@@ -0,0 +1,172 @@
+//! Environment variables for one session, given on the command line or in an env file. They are
+//! passed to the developer shell and the stdio extensions of the session, and never written to
+//! the config.
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionEnvVar {
+    pub key: String,
+    pub value: String,
+    /// Masked wherever the session is recorded, like other secrets
+    pub secret: bool,
+}
+
+impl fmt::Debug for SessionEnvVar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = if self.secret {
+            goose::redaction::REDACTED
+        } else {
+            self.value.as_str()
+        };
+        f.debug_struct("SessionEnvVar")
+            .field("key", &self.key)
+            .field("value", &value)
+            .field("secret", &self.secret)
+            .finish()
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Read an env file of `KEY=VALUE` lines. Blank lines and `#` comments are skipped, an `export`
+/// prefix is allowed, values may be quoted, and a `secret` prefix marks the value as secret
+pub fn parse_env_file(content: &str) -> Result<Vec<SessionEnvVar>> {
+    let mut vars = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (secret, line) = match line.strip_prefix("secret ") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("Line {}: expected KEY=VALUE", number + 1);
+        };
+        let key = key.trim();
+        if !is_valid_key(key) {
+            bail!(
+                "Line {}: '{}' is not a valid variable name",
+                number + 1,
+                key
+            );
+        }
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| {
+                value
+                    .strip_prefix(*quote)
+                    .and_then(|rest| rest.strip_suffix(*quote))
+            })
+            .unwrap_or(value);
+        vars.push(SessionEnvVar {
+            key: key.to_string(),
+            value: value.to_string(),
+            secret,
+        });
+    }
+    Ok(vars)
+}
+
+/// The variables of the session from its env files, then `--env`, then `--secret-env`, a later
+/// value of a variable replacing an earlier one
+pub fn collect_session_env(
+    env_files: &[PathBuf],
+    env: Vec<(String, String)>,
+    secret_env: Vec<(String, String)>,
+) -> Result<Vec<SessionEnvVar>> {
+    let mut vars = Vec::new();
+    for path in env_files {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read env file {}", path.display()))?;
+        vars.extend(
+            parse_env_file(&content)
+                .with_context(|| format!("Invalid env file {}", path.display()))?,
+        );
+    }
+    let given = env
+        .into_iter()
+        .map(|var| (var, false))
+        .chain(secret_env.into_iter().map(|var| (var, true)));
+    for ((key, value), secret) in given {
+        if !is_valid_key(&key) {
+            bail!("'{}' is not a valid variable name", key);
+        }
+        vars.push(SessionEnvVar { key, value, secret });
+    }
+
+    let mut deduped: Vec<SessionEnvVar> = Vec::new();
+    for var in vars {
+        deduped.retain(|earlier| earlier.key != var.key);
+        deduped.push(var);
+    }
+    Ok(deduped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse_env_file(
+            "# deploy settings\n\nREGION=eu-west-1\nexport STAGE=\"staging\"\nsecret DEPLOY_TOKEN='abc=123'\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                SessionEnvVar {
+                    key: "REGION".to_string(),
+                    value: "eu-west-1".to_string(),
+                    secret: false,
+                },
+                SessionEnvVar {
+                    key: "STAGE".to_string(),
+                    value: "staging".to_string(),
+                    secret: false,
+                },
+                SessionEnvVar {
+                    key: "DEPLOY_TOKEN".to_string(),
+                    value: "abc=123".to_string(),
+                    secret: true,
+                },
+            ]
+        );
+        assert!(format!("{:?}", vars[2]).contains("[REDACTED]"));
+
+        assert!(parse_env_file("NO_VALUE").is_err());
+        assert!(parse_env_file("1BAD=value").is_err());
+    }
+
+    #[test]
+    fn test_collect_session_env_later_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".env.goose");
+        std::fs::write(&file, "STAGE=staging\nAPI_TOKEN=from-file\n").unwrap();
+
+        let vars = collect_session_env(
+            &[file],
+            vec![("STAGE".to_string(), "prod".to_string())],
+            vec![("API_TOKEN".to_string(), "from-flag".to_string())],
+        )
+        .unwrap();
+        assert_eq!(vars.len(), 2);
+        assert_eq!((vars[0].value.as_str(), vars[0].secret), ("prod", false));
+        assert_eq!(
+            (vars[1].value.as_str(), vars[1].secret),
+            ("from-flag", true)
+        );
+    }
+}
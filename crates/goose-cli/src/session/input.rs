@@ -1,3 +1,4 @@
+use super::commands::{CommandRegistry, CustomCommandInvocation, BUILTIN_COMMANDS};
 use super::completion::GooseCompleter;
 use anyhow::Result;
 use rustyline::Editor;
@@ -10,6 +11,7 @@ pub enum InputResult {
     Exit,
     AddExtension(String),
     AddBuiltin(String),
+    ReloadExtensions,
     ToggleTheme,
     SelectTheme(String),
     Retry,
@@ -19,8 +21,13 @@ pub enum InputResult {
     Plan(PlanCommandOptions),
     EndPlan,
     Clear,
+    Undo,
     Recipe(Option<String>),
     Summarize,
+    ShowSystemPrompt,
+    Attach(String),
+    ChangeDir(String),
+    CustomCommand(CustomCommandInvocation),
 }
 
 #[derive(Debug)]
@@ -56,12 +63,23 @@ impl rustyline::ConditionalEventHandler for CtrlCHandler {
 
 pub fn get_input(
     editor: &mut Editor<GooseCompleter, rustyline::history::DefaultHistory>,
+    commands: &CommandRegistry,
 ) -> Result<InputResult> {
-    // Ensure Ctrl-J binding is set for newlines
+    // Ensure Ctrl-J and Alt-Enter bindings are set for newlines
     editor.bind_sequence(
         rustyline::KeyEvent(rustyline::KeyCode::Char('j'), rustyline::Modifiers::CTRL),
         rustyline::EventHandler::Simple(rustyline::Cmd::Newline),
     );
+    editor.bind_sequence(
+        rustyline::KeyEvent(rustyline::KeyCode::Enter, rustyline::Modifiers::ALT),
+        rustyline::EventHandler::Simple(rustyline::Cmd::Newline),
+    );
+
+    // Ctrl-R searches the history in both emacs and vi mode
+    editor.bind_sequence(
+        rustyline::KeyEvent(rustyline::KeyCode::Char('r'), rustyline::Modifiers::CTRL),
+        rustyline::EventHandler::Simple(rustyline::Cmd::ReverseSearchHistory),
+    );
 
     editor.bind_sequence(
         rustyline::KeyEvent(rustyline::KeyCode::Char('c'), rustyline::Modifiers::CTRL),
@@ -71,7 +89,7 @@ pub fn get_input(
     let prompt = format!("{} ", console::style("( O)>").cyan().bold());
 
     let input = match editor.readline(&prompt) {
-        Ok(text) => text,
+        Ok(text) => join_continuation_lines(&text),
         Err(e) => match e {
             rustyline::error::ReadlineError::Interrupted => return Ok(InputResult::Exit),
             _ => return Err(e.into()),
@@ -99,13 +117,57 @@ pub fn get_input(
         return Ok(InputResult::Message(trimmed.to_string()));
     }
 
-    // Handle slash commands
-    match handle_slash_command(&input) {
-        Some(result) => Ok(result),
+    // Handle slash commands, built-in ones take precedence over user-defined ones
+    if let Some(result) = handle_slash_command(&input) {
+        if matches!(input.trim(), "/?" | "/help") {
+            print_custom_commands_help(commands);
+        }
+        return Ok(result);
+    }
+    match commands.parse(&input) {
+        Some(invocation) => Ok(InputResult::CustomCommand(invocation)),
         None => Ok(InputResult::Message(input.trim().to_string())),
     }
 }
 
+/// Whether the input should continue on another line instead of being submitted: its last
+/// line ends with a backslash after a space, or it has an unclosed ``` code fence. A backslash
+/// right after other text, as in a path like `C:\work\`, doesn't continue the input
+pub fn is_incomplete(input: &str) -> bool {
+    input.matches("```").count() % 2 == 1 || input.lines().last().is_some_and(continues)
+}
+
+/// Whether a line ends with the backslash that continues input onto the next line
+fn continues(line: &str) -> bool {
+    line.strip_suffix('\\')
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with(char::is_whitespace))
+}
+
+/// Remove the backslashes used to continue input onto the next line. Lines inside code fences
+/// are kept as they are, so a pasted shell snippet keeps its own continuations
+fn join_continuation_lines(input: &str) -> String {
+    let mut joined = String::with_capacity(input.len());
+    let mut in_fence = false;
+    let mut lines = input.split('\n').peekable();
+    while let Some(line) = lines.next() {
+        let fence_line = line.contains("```");
+        if fence_line {
+            in_fence ^= line.matches("```").count() % 2 == 1;
+        }
+        let is_last = lines.peek().is_none();
+        match line.strip_suffix('\\') {
+            Some(rest) if !in_fence && !fence_line && !is_last && continues(line) => {
+                joined.push_str(rest)
+            }
+            _ => joined.push_str(line),
+        }
+        if !is_last {
+            joined.push('\n');
+        }
+    }
+    joined
+}
+
 fn handle_slash_command(input: &str) -> Option<InputResult> {
     let input = input.trim();
 
@@ -115,12 +177,17 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
     const CMD_PROMPT_WITH_SPACE: &str = "/prompt ";
     const CMD_EXTENSION: &str = "/extension ";
     const CMD_BUILTIN: &str = "/builtin ";
+    const CMD_EXTENSIONS_RELOAD: &str = "/extensions reload";
     const CMD_MODE: &str = "/mode ";
     const CMD_PLAN: &str = "/plan";
     const CMD_ENDPLAN: &str = "/endplan";
     const CMD_CLEAR: &str = "/clear";
+    const CMD_UNDO: &str = "/undo";
     const CMD_RECIPE: &str = "/recipe";
     const CMD_SUMMARIZE: &str = "/summarize";
+    const CMD_SYSTEM: &str = "/system";
+    const CMD_ATTACH: &str = "/attach ";
+    const CMD_CD: &str = "/cd ";
 
     match input {
         "/exit" | "/quit" => Some(InputResult::Exit),
@@ -170,6 +237,7 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
         s if s.starts_with(CMD_EXTENSION) => Some(InputResult::AddExtension(
             s[CMD_EXTENSION.len()..].to_string(),
         )),
+        s if s == CMD_EXTENSIONS_RELOAD => Some(InputResult::ReloadExtensions),
         s if s.starts_with(CMD_BUILTIN) => {
             Some(InputResult::AddBuiltin(s[CMD_BUILTIN.len()..].to_string()))
         }
@@ -179,8 +247,12 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
         s if s.starts_with(CMD_PLAN) => parse_plan_command(s[CMD_PLAN.len()..].trim().to_string()),
         s if s == CMD_ENDPLAN => Some(InputResult::EndPlan),
         s if s == CMD_CLEAR => Some(InputResult::Clear),
+        s if s == CMD_UNDO => Some(InputResult::Undo),
         s if s.starts_with(CMD_RECIPE) => parse_recipe_command(s),
         s if s == CMD_SUMMARIZE => Some(InputResult::Summarize),
+        s if s == CMD_SYSTEM => Some(InputResult::ShowSystemPrompt),
+        s if s.starts_with(CMD_ATTACH) => parse_attach_command(&s[CMD_ATTACH.len()..]),
+        s if s.starts_with(CMD_CD) => parse_cd_command(&s[CMD_CD.len()..]),
         _ => None,
     }
 }
@@ -210,6 +282,35 @@ fn parse_recipe_command(s: &str) -> Option<InputResult> {
     Some(InputResult::Recipe(Some(filepath.to_string())))
 }
 
+fn parse_attach_command(args: &str) -> Option<InputResult> {
+    // Allow quoted paths so files with spaces in their names can be attached
+    let path = match shlex::split(args) {
+        Some(parts) if parts.len() == 1 => parts.into_iter().next().unwrap(),
+        _ => args.trim().to_string(),
+    };
+
+    if path.is_empty() {
+        println!("Usage: /attach <path-or-url>");
+        return Some(InputResult::Retry);
+    }
+
+    Some(InputResult::Attach(path))
+}
+
+fn parse_cd_command(args: &str) -> Option<InputResult> {
+    let path = match shlex::split(args) {
+        Some(parts) if parts.len() == 1 => parts.into_iter().next().unwrap(),
+        _ => args.trim().to_string(),
+    };
+
+    if path.is_empty() {
+        println!("Usage: /cd <path>");
+        return Some(InputResult::Retry);
+    }
+
+    Some(InputResult::ChangeDir(path))
+}
+
 fn parse_prompts_command(args: &str) -> Option<InputResult> {
     let parts: Vec<String> = shlex::split(args).unwrap_or_default();
 
@@ -272,35 +373,49 @@ fn parse_plan_command(input: String) -> Option<InputResult> {
 }
 
 fn print_help() {
+    println!("Available commands:");
+    for command in BUILTIN_COMMANDS {
+        let names = command.names.join(" or ");
+        if command.usage.is_empty() {
+            println!("{} - {}", names, command.description);
+        } else {
+            println!("{} {} - {}", names, command.usage, command.description);
+        }
+    }
     println!(
-        "Available commands:
-/exit or /quit - Exit the session
-/t - Toggle Light/Dark/Ansi theme
-/t <name> - Set theme directly (light, dark, ansi)
-/extension <command> - Add a stdio extension (format: ENV1=val1 command args...)
-/builtin <names> - Add builtin extensions by name (comma-separated)
-/prompts [--extension <name>] - List all available prompts, optionally filtered by extension
-/prompt <n> [--info] [key=value...] - Get prompt info or execute a prompt
-/mode <name> - Set the goose mode to use ('auto', 'approve', 'chat', 'smart_approve')
-/plan <message_text> -  Enters 'plan' mode with optional message. Create a plan based on the current messages and asks user if they want to act on it.
-                        If user acts on the plan, goose mode is set to 'auto' and returns to 'normal' goose mode.
-                        To warm up goose before using '/plan', we recommend setting '/mode approve' & putting appropriate context into goose.
-                        The model is used based on $GOOSE_PLANNER_PROVIDER and $GOOSE_PLANNER_MODEL environment variables.
-                        If no model is set, the default model is used.
-/endplan - Exit plan mode and return to 'normal' goose mode.
-/recipe [filepath] - Generate a recipe from the current conversation and save it to the specified filepath (must end with .yaml).
-                       If no filepath is provided, it will be saved to ./recipe.yaml.
-/summarize - Summarize the current conversation to reduce context length while preserving key information.
-/? or /help - Display this help message
-/clear - Clears the current chat history
-
+        "
 Navigation:
 Ctrl+C - Clear current line if text is entered, otherwise exit the session
-Ctrl+J - Add a newline
-Up/Down arrows - Navigate through command history"
+         While goose is working, the first press cancels the running tool, the second stops goose
+         and the third exits
+Ctrl+J or Alt+Enter - Add a newline (a trailing \\ after a space or an open ``` code block also continues the input)
+Ctrl+R - Search command history, which is shared across sessions
+Tab - Complete slash commands, prompt names and file paths
+Up/Down arrows - Navigate through command history
+Typing while goose is working - Press Enter to queue a follow-up message for the current turn"
     );
 }
 
+fn print_custom_commands_help(commands: &CommandRegistry) {
+    if commands.custom_commands().is_empty() {
+        return;
+    }
+    match CommandRegistry::commands_dir() {
+        Ok(dir) => println!(
+            "
+Custom commands (from {}):",
+            dir.display()
+        ),
+        Err(_) => println!(
+            "
+Custom commands:"
+        ),
+    }
+    for command in commands.custom_commands() {
+        println!("/{} [args...] - {}", command.name, command.description);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -347,6 +462,12 @@ mod tests {
             panic!("Expected AddBuiltin");
         }
 
+        // Test extensions reload command
+        assert!(matches!(
+            handle_slash_command("/extensions reload"),
+            Some(InputResult::ReloadExtensions)
+        ));
+
         // Test unknown commands
         assert!(handle_slash_command("/unknown").is_none());
     }
@@ -536,4 +657,97 @@ mod tests {
         let result = handle_slash_command("  /summarize  ");
         assert!(matches!(result, Some(InputResult::Summarize)));
     }
+
+    #[test]
+    fn test_system_command() {
+        assert!(matches!(
+            handle_slash_command("/system"),
+            Some(InputResult::ShowSystemPrompt)
+        ));
+        assert!(handle_slash_command("/systemx").is_none());
+    }
+
+    #[test]
+    fn test_attach_command() {
+        if let Some(InputResult::Attach(path)) = handle_slash_command("/attach /tmp/shot.png") {
+            assert_eq!(path, "/tmp/shot.png");
+        } else {
+            panic!("Expected Attach");
+        }
+
+        // Quoted paths keep their spaces
+        if let Some(InputResult::Attach(path)) =
+            handle_slash_command("/attach \"/tmp/my screenshot.png\"")
+        {
+            assert_eq!(path, "/tmp/my screenshot.png");
+        } else {
+            panic!("Expected Attach with quoted path");
+        }
+    }
+
+    #[test]
+    fn test_cd_command() {
+        if let Some(InputResult::ChangeDir(path)) = handle_slash_command("/cd ../service") {
+            assert_eq!(path, "../service");
+        } else {
+            panic!("Expected ChangeDir");
+        }
+
+        if let Some(InputResult::ChangeDir(path)) = handle_slash_command("/cd '~/My Projects'") {
+            assert_eq!(path, "~/My Projects");
+        } else {
+            panic!("Expected ChangeDir with quoted path");
+        }
+
+        assert!(matches!(
+            handle_slash_command("/cd \"\""),
+            Some(InputResult::Retry)
+        ));
+    }
+
+    #[test]
+    fn test_undo_command() {
+        assert!(matches!(
+            handle_slash_command("/undo"),
+            Some(InputResult::Undo)
+        ));
+        assert!(matches!(
+            handle_slash_command("  /undo  "),
+            Some(InputResult::Undo)
+        ));
+    }
+
+    #[test]
+    fn test_multiline_continuation() {
+        assert!(is_incomplete("first line \\"));
+        assert!(is_incomplete("look at this:\n```rust\nfn main() {}"));
+        assert!(!is_incomplete("look at this:\n```rust\nfn main() {}\n```"));
+        assert!(!is_incomplete("a complete message"));
+
+        assert_eq!(
+            join_continuation_lines("first line \\\nsecond line"),
+            "first line \nsecond line"
+        );
+    }
+
+    #[test]
+    fn test_continuation_kept_in_code_fences() {
+        let input = "run this:\n```sh\ncargo test \\\n  --workspace\n```\nthanks \\\nbye";
+        assert_eq!(
+            join_continuation_lines(input),
+            "run this:\n```sh\ncargo test \\\n  --workspace\n```\nthanks \nbye"
+        );
+        assert!(is_incomplete("```sh\ncargo test \\"));
+    }
+
+    #[test]
+    fn test_trailing_path_backslash_submits() {
+        assert!(!is_incomplete("list the files in C:\\work\\"));
+        assert!(is_incomplete("list the files in C:\\work\\ \\"));
+        assert!(is_incomplete("\\"));
+        assert_eq!(
+            join_continuation_lines("C:\\work\\\nnext"),
+            "C:\\work\\\nnext"
+        );
+    }
 }
@@ -1,11 +1,17 @@
 mod builder;
+mod commands;
 mod completion;
+pub mod env;
 mod export;
 mod input;
+mod ndjson;
+mod notify;
 mod output;
 mod prompt;
+mod queued_input;
 mod task_execution_display;
 mod thinking;
+mod tui;
 
 use crate::session::task_execution_display::{
     format_task_execution_notification, TASK_EXECUTION_NOTIFICATION_TYPE,
@@ -16,6 +22,7 @@ use std::io::Write;
 pub use self::export::message_to_markdown;
 pub use builder::{build_session, SessionBuilderConfig, SessionSettings};
 use console::Color;
+use goose::agents::plan_tools::Plan;
 use goose::agents::AgentEvent;
 use goose::permission::permission_confirmation::PrincipalType;
 use goose::permission::Permission;
@@ -23,17 +30,33 @@ use goose::permission::PermissionConfirmation;
 use goose::providers::base::Provider;
 pub use goose::session::Identifier;
 use goose::utils::safe_truncate;
+use goose::webhooks::{self, WebhookEvent};
+pub use ndjson::OutputFormat;
+pub use notify::{DEFAULT_NOTIFY_AFTER_SECONDS, NOTIFICATIONS_KEY, NOTIFY_AFTER_KEY};
+pub use output::estimate_cost_usd;
+pub use output::{
+    apply_appearance, enable_plain_output, get_appearance, get_theme, plain_output_requested,
+    preview_markdown, set_appearance, set_theme, Appearance, SpinnerStyle, Theme, ASCII_KEY,
+    BAT_CACHE_DIR_KEY, BAT_THEME_KEY, RENDER_MARKDOWN_KEY, SPINNER_KEY,
+};
 
 use anyhow::{Context, Result};
+use commands::{CommandRegistry, CustomCommand};
 use completion::GooseCompleter;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::agents::extension::{Envs, ExtensionConfig};
 use goose::agents::types::RetryConfig;
-use goose::agents::{Agent, SessionConfig};
+use goose::agents::{Agent, ReplyOutcome, SessionConfig};
+use goose::config::permission::PermissionLevel;
 use goose::config::Config;
 use goose::providers::pricing::initialize_pricing_cache;
+use goose::providers::utils::prepare_image_attachment;
 use goose::session;
+use goose::session::attachment::{attachments_dir, store_attachment};
 use input::InputResult;
+use mcp_core::tool::ToolCall;
+use ndjson::NdjsonEvent;
+use queued_input::QueuedInputReader;
 use rmcp::model::PromptMessage;
 use rmcp::model::ServerNotification;
 use rmcp::model::{ErrorCode, ErrorData};
@@ -43,12 +66,15 @@ use rand::{distributions::Alphanumeric, Rng};
 use rustyline::EditMode;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio;
 use tokio_util::sync::CancellationToken;
 
+/// Number of entries kept in the command history shared by all sessions
+const MAX_HISTORY_ENTRIES: usize = 10_000;
+
 pub enum RunMode {
     Normal,
     Plan,
@@ -66,11 +92,15 @@ pub struct Session {
     max_turns: Option<u32>,
     edit_mode: Option<EditMode>,
     retry_config: Option<RetryConfig>,
+    pending_attachments: Vec<MessageContent>, // Content attached via --attach or /attach, sent with the next message
+    output_format: OutputFormat,
+    replay_turn: bool, // The last turn was cut short by a crash and is run again on start
 }
 
 // Cache structure for completion data
 struct CompletionCache {
     prompts: HashMap<String, Vec<String>>,
+    commands: Vec<String>,
     prompt_info: HashMap<String, output::PromptInfo>,
     last_updated: Instant,
 }
@@ -79,6 +109,7 @@ impl CompletionCache {
     fn new() -> Self {
         Self {
             prompts: HashMap::new(),
+            commands: CommandRegistry::default().command_names(),
             prompt_info: HashMap::new(),
             last_updated: Instant::now(),
         }
@@ -152,7 +183,255 @@ impl Session {
             max_turns,
             edit_mode,
             retry_config,
+            pending_attachments: Vec::new(),
+            output_format: OutputFormat::Text,
+            replay_turn: false,
+        }
+    }
+
+    /// Run the turn cut short by a crash again when the interactive session starts
+    pub fn replay_interrupted_turn(&mut self) {
+        self.replay_turn = true;
+    }
+
+    pub fn set_output_format(&mut self, output_format: OutputFormat) {
+        self.output_format = output_format;
+    }
+
+    /// Attach a file, image or URL and hold it until the next user message is sent
+    ///
+    /// Images are downscaled and sent as image content. Anything else is stored in the session
+    /// data dir, previewed in the next message and exposed to the agent as a resource.
+    pub async fn attach(&mut self, source: &str) -> Result<()> {
+        let path = Path::new(source);
+        if is_image_path(path) {
+            let image = prepare_image_attachment(path)
+                .with_context(|| format!("Failed to attach {}", source))?;
+            self.pending_attachments.push(MessageContent::Image(image));
+            return Ok(());
+        }
+
+        // Attachments are kept with the session, so they need one
+        let Some(session_file) = self.session_file.clone() else {
+            return Err(anyhow::anyhow!(
+                "Only images can be attached without a session"
+            ));
+        };
+        let session_id = session_file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .context("The session file has no name")?;
+        let dir = attachments_dir(session_id)?;
+        let attachment = store_attachment(source, &dir)
+            .await
+            .with_context(|| format!("Failed to attach {}", source))?;
+
+        let mut metadata = session::read_metadata(&session_file)?;
+        metadata.attachments.push(attachment.clone());
+        session::update_metadata(&session_file, &metadata).await?;
+
+        self.pending_attachments
+            .push(MessageContent::text(attachment.preview()?));
+        self.agent.add_attachment(attachment).await;
+        Ok(())
+    }
+
+    /// Send text typed by the user (with any pending attachments) to the agent and render the reply
+    async fn send_user_input(&mut self, content: &str) -> Result<()> {
+        let message = self.user_message(content);
+        self.push_message(message);
+
+        // Track the current directory and last instruction in projects.json
+        let session_id = self
+            .session_file
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string());
+
+        if let Err(e) =
+            crate::project_tracker::update_project_tracker(Some(content), session_id.as_deref())
+        {
+            eprintln!(
+                "Warning: Failed to update project tracker with instruction: {}",
+                e
+            );
+        }
+
+        let provider = self.agent.provider().await?;
+
+        // Persist messages with provider for automatic description generation
+        if let Some(session_file) = &self.session_file {
+            let working_dir = Some(std::env::current_dir().unwrap_or_default());
+
+            session::persist_messages_with_schedule_id(
+                session_file,
+                &self.messages,
+                Some(provider),
+                self.scheduled_job_id.clone(),
+                working_dir,
+            )
+            .await?;
+        }
+
+        output::show_thinking();
+        self.process_agent_response(true, CancellationToken::default())
+            .await?;
+        output::hide_thinking();
+        Ok(())
+    }
+
+    /// Run a user-defined slash command: execute its tool pre-calls, render its prompt and send it.
+    /// The pre-calls need the same approval as the tool calls of the model
+    async fn run_custom_command(&mut self, command: &CustomCommand, args: &str) -> Result<()> {
+        let session_config = self.session_config();
+        let mut tool_results = Vec::new();
+        for pre_call in &command.tools {
+            output::render_custom_command_tool(&pre_call.name);
+            let mut tool_call = ToolCall::new(&pre_call.name, pre_call.arguments.clone());
+            let request_id = format!("/{}-{}", command.name, tool_results.len());
+            if let Err(e) = self
+                .agent
+                .run_tool_call_hooks(&request_id, &mut tool_call)
+                .await
+            {
+                output::render_error(&format!(
+                    "/{}: tool {} failed: {}",
+                    command.name, pre_call.name, e.message
+                ));
+                return Ok(());
+            }
+            if !self
+                .approve_custom_command_tool(command, &tool_call, &session_config)
+                .await?
+            {
+                return Ok(());
+            }
+            let (_, result) = self
+                .agent
+                .dispatch_tool_call(tool_call, request_id, None, &session_config)
+                .await;
+
+            let contents = match result {
+                Ok(call_result) => call_result.result.await,
+                Err(e) => Err(e),
+            };
+            match contents {
+                Ok(contents) => tool_results.push(
+                    contents
+                        .iter()
+                        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+                Err(e) => {
+                    output::render_error(&format!(
+                        "/{}: tool {} failed: {}",
+                        command.name, pre_call.name, e.message
+                    ));
+                    return Ok(());
+                }
+            }
+        }
+
+        match command.render_prompt(args, &tool_results.join("\n\n")) {
+            Ok(prompt) => self.send_user_input(&prompt).await,
+            Err(e) => {
+                output::render_error(&format!("{:#}", e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether a tool pre-call of a custom command may run, asking the user when the goose mode
+    /// or the tool's permission calls for it
+    async fn approve_custom_command_tool(
+        &self,
+        command: &CustomCommand,
+        tool_call: &ToolCall,
+        session_config: &Option<SessionConfig>,
+    ) -> Result<bool> {
+        match self
+            .agent
+            .check_tool_permission(tool_call, session_config)
+            .await?
+        {
+            PermissionLevel::AlwaysAllow => Ok(true),
+            PermissionLevel::NeverAllow => {
+                output::render_error(&format!(
+                    "/{}: tool {} is not allowed in this session",
+                    command.name, tool_call.name
+                ));
+                Ok(false)
+            }
+            PermissionLevel::AskBefore => {
+                notify::approval_needed(&tool_call.name);
+                let permission = cliclack::select(format!(
+                    "/{} would like to call {}, do you allow?",
+                    command.name, tool_call.name
+                ))
+                .item(Permission::AllowOnce, "Allow", "Allow the tool call once")
+                .item(
+                    Permission::AlwaysAllow,
+                    "Always Allow",
+                    "Always allow the tool call",
+                )
+                .item(Permission::DenyOnce, "Deny", "Deny the tool call")
+                .interact();
+                let permission = match permission {
+                    Ok(permission) => permission,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => Permission::DenyOnce,
+                    Err(e) => return Err(e.into()),
+                };
+                if permission == Permission::DenyOnce {
+                    output::render_text(
+                        &format!("/{} cancelled.", command.name),
+                        Some(Color::Yellow),
+                        true,
+                    );
+                    return Ok(false);
+                }
+                self.agent
+                    .record_tool_approval(&tool_call.name, permission)
+                    .await;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Remove the last user message and everything goose did in response to it
+    async fn undo_last_exchange(&mut self) -> Result<()> {
+        let Some((removed, AgentEvent::HistoryReplaced(messages))) =
+            Agent::undo_last_exchange(&mut self.messages)
+        else {
+            output::render_text("Nothing to undo.", Some(Color::Yellow), true);
+            return Ok(());
+        };
+        self.messages = Conversation::new_unvalidated(messages);
+
+        if let Some(session_file) = &self.session_file {
+            let working_dir = std::env::current_dir().ok();
+            session::persist_messages_with_schedule_id(
+                session_file,
+                &self.messages,
+                None,
+                self.scheduled_job_id.clone(),
+                working_dir,
+            )
+            .await?;
         }
+
+        output::render_undo(&removed[0].as_concat_text(), removed.len());
+        Ok(())
+    }
+
+    /// Build a user message from text, including any attachments waiting to be sent
+    fn user_message(&mut self, text: &str) -> Message {
+        self.pending_attachments
+            .drain(..)
+            .fold(Message::user().with_text(text), |message, content| {
+                message.with_content(content)
+            })
     }
 
     /// Helper function to summarize context messages
@@ -346,21 +625,17 @@ impl Session {
     }
 
     pub async fn get_prompt_info(&mut self, name: &str) -> Result<Option<output::PromptInfo>> {
-        let prompts = self.agent.list_extension_prompts().await;
-
-        // Find which extension has this prompt
-        for (extension, prompt_list) in prompts {
-            if let Some(prompt) = prompt_list.iter().find(|p| p.name == name) {
-                return Ok(Some(output::PromptInfo {
-                    name: prompt.name.clone(),
-                    description: prompt.description.clone(),
-                    arguments: prompt.arguments.clone(),
-                    extension: Some(extension),
-                }));
-            }
-        }
-
-        Ok(None)
+        Ok(self
+            .agent
+            .find_prompt(name)
+            .await
+            .ok()
+            .map(|(extension, prompt)| output::PromptInfo {
+                name: prompt.name,
+                description: prompt.description,
+                arguments: prompt.arguments,
+                extension: Some(extension),
+            }))
     }
 
     pub async fn get_prompt(&mut self, name: &str, arguments: Value) -> Result<Vec<PromptMessage>> {
@@ -414,15 +689,31 @@ impl Session {
             );
         }
 
-        self.process_agent_response(false, cancel_token).await?;
+        let started = Instant::now();
+        match self.output_format {
+            OutputFormat::Text => self.process_agent_response(false, cancel_token).await?,
+            OutputFormat::Ndjson => self.stream_ndjson_response(cancel_token).await?,
+        }
+        notify::turn_finished(started.elapsed());
         Ok(())
     }
 
     /// Start an interactive session, optionally with an initial message
     pub async fn interactive(&mut self, prompt: Option<String>) -> Result<()> {
+        self.notify_webhooks(WebhookEvent::SessionStart, "interactive")
+            .await;
+
+        if std::mem::take(&mut self.replay_turn) {
+            println!("{}", console::style("Replaying the interrupted turn").dim());
+            output::show_thinking();
+            self.process_agent_response(true, CancellationToken::default())
+                .await?;
+            output::hide_thinking();
+        }
+
         // Process initial message if provided
         if let Some(prompt) = prompt {
-            let msg = Message::user().with_text(&prompt);
+            let msg = self.user_message(&prompt);
             self.process_message(msg, CancellationToken::default())
                 .await?;
         }
@@ -431,8 +722,11 @@ impl Session {
         self.update_completion_cache().await?;
 
         // Create a new editor with our custom completer
-        let builder =
-            rustyline::Config::builder().completion_type(rustyline::CompletionType::Circular);
+        let builder = rustyline::Config::builder()
+            .completion_type(rustyline::CompletionType::Circular)
+            .max_history_size(MAX_HISTORY_ENTRIES)?
+            .history_ignore_dups(true)?
+            .history_ignore_space(true);
         let builder = if let Some(edit_mode) = self.edit_mode {
             builder.edit_mode(edit_mode)
         } else {
@@ -445,6 +739,10 @@ impl Session {
                 config,
             )?;
 
+        // Load user-defined slash commands and make them available for completion
+        let commands = CommandRegistry::load();
+        self.completion_cache.write().unwrap().commands = commands.command_names();
+
         // Set up the completer with a reference to the completion cache
         let completer = GooseCompleter::new(self.completion_cache.clone());
         editor.set_helper(Some(completer));
@@ -481,63 +779,61 @@ impl Session {
 
         output::display_greeting();
         loop {
+            if goose::shutdown::requested() {
+                break;
+            }
             // Display context usage before each prompt
             self.display_context_usage().await?;
 
-            match input::get_input(&mut editor)? {
-                InputResult::Message(content) => {
-                    match self.run_mode {
-                        RunMode::Normal => {
-                            save_history(&mut editor);
-
-                            self.push_message(Message::user().with_text(&content));
-
-                            // Track the current directory and last instruction in projects.json
-                            let session_id = self
-                                .session_file
-                                .as_ref()
-                                .and_then(|p| p.file_stem())
-                                .and_then(|s| s.to_str())
-                                .map(|s| s.to_string());
-
-                            if let Err(e) = crate::project_tracker::update_project_tracker(
-                                Some(&content),
-                                session_id.as_deref(),
-                            ) {
-                                eprintln!("Warning: Failed to update project tracker with instruction: {}", e);
-                            }
+            match input::get_input(&mut editor, &commands)? {
+                InputResult::Message(content) => match self.run_mode {
+                    RunMode::Normal => {
+                        save_history(&mut editor);
 
-                            let provider = self.agent.provider().await?;
+                        self.send_user_input(&content).await?;
+                    }
+                    RunMode::Plan => {
+                        let mut plan_messages = self.messages.clone();
+                        plan_messages.push(self.user_message(&content));
+                        let reasoner = get_reasoner()?;
+                        self.plan_with_reasoner_model(plan_messages, reasoner)
+                            .await?;
+                    }
+                },
+                input::InputResult::Exit => break,
+                input::InputResult::CustomCommand(invocation) => {
+                    save_history(&mut editor);
 
-                            // Persist messages with provider for automatic description generation
-                            if let Some(session_file) = &self.session_file {
-                                let working_dir = Some(std::env::current_dir().unwrap_or_default());
+                    if let Some(command) = commands.get(&invocation.name).cloned() {
+                        self.run_custom_command(&command, &invocation.args).await?;
+                    }
+                    continue;
+                }
+                input::InputResult::Attach(path) => {
+                    save_history(&mut editor);
 
-                                session::persist_messages_with_schedule_id(
-                                    session_file,
-                                    &self.messages,
-                                    Some(provider),
-                                    self.scheduled_job_id.clone(),
-                                    working_dir,
-                                )
-                                .await?;
-                            }
+                    match self.attach(&path).await {
+                        Ok(_) => output::render_attachment_success(&path),
+                        Err(e) => output::render_attachment_error(&path, &format!("{:#}", e)),
+                    }
+                    continue;
+                }
+                input::InputResult::ChangeDir(path) => {
+                    save_history(&mut editor);
 
-                            output::show_thinking();
-                            self.process_agent_response(true, CancellationToken::default())
-                                .await?;
-                            output::hide_thinking();
-                        }
-                        RunMode::Plan => {
-                            let mut plan_messages = self.messages.clone();
-                            plan_messages.push(Message::user().with_text(&content));
-                            let reasoner = get_reasoner()?;
-                            self.plan_with_reasoner_model(plan_messages, reasoner)
-                                .await?;
+                    match self.agent.set_working_dir(Path::new(&path)).await {
+                        Ok(dir) => {
+                            // The session metadata and later tool calls follow the process
+                            if let Err(e) = std::env::set_current_dir(&dir) {
+                                output::render_change_dir_error(&path, &e.to_string());
+                            } else {
+                                output::render_change_dir_success(&dir);
+                            }
                         }
+                        Err(e) => output::render_change_dir_error(&path, &format!("{:#}", e)),
                     }
+                    continue;
                 }
-                input::InputResult::Exit => break,
                 input::InputResult::AddExtension(cmd) => {
                     save_history(&mut editor);
 
@@ -554,6 +850,18 @@ impl Session {
                         Err(e) => output::render_builtin_error(&names, &e.to_string()),
                     }
                 }
+                input::InputResult::ReloadExtensions => {
+                    save_history(&mut editor);
+
+                    match self.agent.reload_extensions().await {
+                        Ok(reload) => {
+                            self.invalidate_completion_cache().await;
+                            output::render_extensions_reload(&reload);
+                        }
+                        Err(e) => output::render_extensions_reload_error(&e.to_string()),
+                    }
+                    continue;
+                }
                 input::InputResult::ToggleTheme => {
                     save_history(&mut editor);
 
@@ -662,6 +970,11 @@ impl Session {
                     }
                     continue;
                 }
+                input::InputResult::Undo => {
+                    save_history(&mut editor);
+                    self.undo_last_exchange().await?;
+                    continue;
+                }
                 input::InputResult::PromptCommand(opts) => {
                     save_history(&mut editor);
                     self.handle_prompt_command(opts).await?;
@@ -699,6 +1012,14 @@ impl Session {
 
                     continue;
                 }
+                InputResult::ShowSystemPrompt => {
+                    save_history(&mut editor);
+
+                    if let Err(e) = self.show_system_prompt().await {
+                        output::render_error(&e.to_string());
+                    }
+                    continue;
+                }
                 InputResult::Summarize => {
                     save_history(&mut editor);
 
@@ -805,6 +1126,10 @@ impl Session {
             }
         }
 
+        self.agent.end_session(&self.session_config()).await;
+        self.agent.shutdown().await;
+        self.notify_webhooks(WebhookEvent::SessionEnd, "interactive")
+            .await;
         println!(
             "\nClosing session.{}",
             self.session_file
@@ -864,8 +1189,20 @@ impl Session {
 
                     // clear the messages before acting on the plan
                     self.messages.clear();
+                    // track progress on the plan's steps while acting on it
+                    let plan_text = plan_response.as_concat_text();
+                    if let Some(plan) = Plan::from_text(&plan_text) {
+                        output::render_plan(&plan);
+                        if let Err(e) = self
+                            .agent
+                            .set_plan(Some(plan), &self.session_config())
+                            .await
+                        {
+                            eprintln!("Failed to save the plan: {}", e);
+                        }
+                    }
                     // add the plan response as a user message
-                    let plan_message = Message::user().with_text(plan_response.as_concat_text());
+                    let plan_message = Message::user().with_text(plan_text);
                     self.push_message(plan_message);
                     // act on the plan
                     output::show_thinking();
@@ -897,20 +1234,185 @@ impl Session {
 
     /// Process a single message and exit
     pub async fn headless(&mut self, prompt: String) -> Result<()> {
-        let message = Message::user().with_text(&prompt);
-        self.process_message(message, CancellationToken::default())
+        self.notify_webhooks(WebhookEvent::SessionStart, "headless")
+            .await;
+        let message = self.user_message(&prompt);
+        let result = if self.output_format == OutputFormat::Ndjson {
+            self.headless_ndjson(message).await
+        } else {
+            self.process_message(message, CancellationToken::default())
+                .await
+        };
+        self.agent.end_session(&self.session_config()).await;
+        self.notify_webhooks(WebhookEvent::SessionEnd, "headless")
+            .await;
+        result
+    }
+
+    /// Send the webhook event. The end of the session waits a bounded time for the delivery,
+    /// since the process may exit right after
+    async fn notify_webhooks(&self, event: WebhookEvent, mode: &str) {
+        let session_id = self
+            .session_file
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string());
+        let data = serde_json::json!({"session_id": session_id, "mode": mode});
+        if event == WebhookEvent::SessionEnd {
+            webhooks::notify_and_wait(event, data, webhooks::SESSION_END_TIMEOUT).await;
+        } else {
+            webhooks::notify(event, data);
+        }
+    }
+
+    /// Run a headless prompt, reporting the run between a start and a finish event
+    async fn headless_ndjson(&mut self, message: Message) -> Result<()> {
+        let session_id = self
+            .session_file
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string());
+        ndjson::emit(&NdjsonEvent::Start {
+            schema_version: ndjson::NDJSON_SCHEMA_VERSION,
+            session_id: session_id.as_deref(),
+        });
+
+        let result = self
+            .process_message(message, CancellationToken::default())
+            .await;
+
+        if let Ok(metadata) = self.get_metadata() {
+            ndjson::emit(&NdjsonEvent::Usage {
+                input_tokens: metadata.accumulated_input_tokens,
+                output_tokens: metadata.accumulated_output_tokens,
+                total_tokens: metadata.accumulated_total_tokens,
+            });
+        }
+        if let Err(e) = &result {
+            ndjson::emit(&NdjsonEvent::Error {
+                message: e.to_string(),
+            });
+        }
+        let outcome = self.agent.reply_outcome().await;
+        ndjson::emit(&NdjsonEvent::Finish {
+            status: match (&result, outcome) {
+                (Err(_), _) => "error",
+                (Ok(_), ReplyOutcome::Completed) => "completed",
+                (Ok(_), ReplyOutcome::SuccessChecksFailed) => "success_checks_failed",
+                (Ok(_), ReplyOutcome::MaxTurnsReached) => "max_turns_reached",
+                (Ok(_), ReplyOutcome::ProviderError) => "provider_error",
+            },
+        });
+        result
+    }
+
+    /// Counterpart of process_agent_response for ndjson output, writing every agent event
+    /// to stdout instead of rendering it. Nobody is there to answer, so tool calls that
+    /// need approval are denied
+    async fn stream_ndjson_response(&mut self, cancel_token: CancellationToken) -> Result<()> {
+        let session_config = self.session_config();
+        let mut stream = self
+            .agent
+            .reply(
+                self.messages.clone(),
+                session_config.clone(),
+                Some(cancel_token),
+            )
             .await?;
+
+        use futures::StreamExt;
+        while let Some(event) = stream.next().await {
+            match event? {
+                AgentEvent::Message(message) => {
+                    for event in ndjson::message_events(&message) {
+                        ndjson::emit(&event);
+                    }
+                    match message.content.first() {
+                        Some(MessageContent::ToolConfirmationRequest(confirmation)) => {
+                            self.agent
+                                .handle_confirmation(
+                                    confirmation.id.clone(),
+                                    PermissionConfirmation {
+                                        principal_type: PrincipalType::Tool,
+                                        permission: Permission::DenyOnce,
+                                    },
+                                )
+                                .await;
+                        }
+                        Some(MessageContent::ContextLengthExceeded(_)) => {
+                            // summarize_context_messages renders to stdout, which is ours here
+                            let (summarized, _, _) = self
+                                .agent
+                                .summarize_context(self.messages.messages())
+                                .await?;
+                            self.messages = summarized;
+                            ndjson::emit(&NdjsonEvent::HistoryReplaced {
+                                messages: self.messages.messages(),
+                            });
+                            stream = self
+                                .agent
+                                .reply(self.messages.clone(), session_config.clone(), None)
+                                .await?;
+                        }
+                        _ => {
+                            self.messages.push(message.clone());
+                            if let Some(session_file) = &self.session_file {
+                                session::persist_messages_with_schedule_id(
+                                    session_file,
+                                    &self.messages,
+                                    None,
+                                    self.scheduled_job_id.clone(),
+                                    std::env::current_dir().ok(),
+                                )
+                                .await?;
+                            }
+                        }
+                    }
+                }
+                AgentEvent::McpNotification((extension, notification)) => {
+                    ndjson::emit(&NdjsonEvent::Notification {
+                        extension: &extension,
+                        notification: &notification,
+                    });
+                }
+                AgentEvent::ModelChange { model, mode } => {
+                    ndjson::emit(&NdjsonEvent::ModelChange {
+                        model: &model,
+                        mode: &mode,
+                    });
+                }
+                AgentEvent::HistoryReplaced(new_messages) => {
+                    ndjson::emit(&NdjsonEvent::HistoryReplaced {
+                        messages: &new_messages,
+                    });
+                    self.messages = Conversation::new_unvalidated(new_messages);
+                    if let Some(session_file) = &self.session_file {
+                        session::persist_messages_with_schedule_id(
+                            session_file,
+                            &self.messages,
+                            None,
+                            self.scheduled_job_id.clone(),
+                            std::env::current_dir().ok(),
+                        )
+                        .await?;
+                    }
+                }
+                AgentEvent::PlanUpdate(plan) => {
+                    ndjson::emit(&NdjsonEvent::PlanUpdate { plan: &plan });
+                }
+                AgentEvent::TodoUpdate(todos) => {
+                    ndjson::emit(&NdjsonEvent::TodoUpdate { todos: &todos });
+                }
+            }
+        }
+        self.commit_turn();
         Ok(())
     }
 
-    async fn process_agent_response(
-        &mut self,
-        interactive: bool,
-        cancel_token: CancellationToken,
-    ) -> Result<()> {
-        let cancel_token_clone = cancel_token.clone();
-
-        let session_config = self.session_file.as_ref().map(|s| {
+    fn session_config(&self) -> Option<SessionConfig> {
+        self.session_file.as_ref().map(|s| {
             let session_id = session::Identifier::Path(s.clone());
             SessionConfig {
                 id: session_id.clone(),
@@ -919,8 +1421,19 @@ impl Session {
                 execution_mode: None,
                 max_turns: self.max_turns,
                 retry_config: self.retry_config.clone(),
+                confirmation_timeout: None,
             }
-        });
+        })
+    }
+
+    async fn process_agent_response(
+        &mut self,
+        interactive: bool,
+        cancel_token: CancellationToken,
+    ) -> Result<()> {
+        let cancel_token_clone = cancel_token.clone();
+
+        let session_config = self.session_config();
         let mut stream = self
             .agent
             .reply(
@@ -931,6 +1444,12 @@ impl Session {
             .await?;
 
         let mut progress_bars = output::McpSpinners::new();
+        let mut queued_input = QueuedInputReader::new();
+        let mut queued_texts: Vec<String> = Vec::new();
+        let mut interrupts = 0;
+        // Whether every message of the turn made it to the session file
+        let mut turn_saved = true;
+        let shutdown = goose::shutdown::token();
 
         use futures::StreamExt;
         loop {
@@ -941,6 +1460,7 @@ impl Session {
                             // If it's a confirmation request, get approval but otherwise do not render/persist
                             if let Some(MessageContent::ToolConfirmationRequest(confirmation)) = message.content.first() {
                                 output::hide_thinking();
+                                notify::approval_needed(&confirmation.tool_name);
 
                                 // Format the confirmation prompt
                                 let prompt = "Goose would like to call the above tool, do you allow?".to_string();
@@ -1071,6 +1591,15 @@ impl Session {
                                     )
                                     .await?;
                             }
+                            // queued messages were already shown when they were typed
+                            else if message.role == rmcp::model::Role::User
+                                && queued_texts.first() == Some(&message.as_concat_text())
+                            {
+                                queued_texts.remove(0);
+                                self.messages.push(message.clone());
+                                // A queued message starts a new turn, Ctrl+C counts from scratch
+                                interrupts = 0;
+                            }
                             // otherwise we have a model/tool to render
                             else {
                                 for content in &message.content {
@@ -1164,11 +1693,7 @@ impl Session {
                                                     }
                                                     Some("response_generated") => {
                                                         // Check verbosity setting for subagent response content
-                                                        let config = Config::global();
-                                                        let min_priority = config
-                                                            .get_param::<f32>("GOOSE_CLI_MIN_PRIORITY")
-                                                            .ok()
-                                                            .unwrap_or(0.5);
+                                                        let min_priority = goose::conversation::tool_result_visibility::min_priority();
 
                                                         if min_priority > 0.1 && !self.debug {
                                                             // High/Medium verbosity: show truncated response
@@ -1210,18 +1735,17 @@ impl Session {
                                         } else {
                                             progress_bars.log(&formatted_message);
                                         }
-                                    } else if let Some(ref notification_type) = message_notification_type {
-                                        if notification_type == TASK_EXECUTION_NOTIFICATION_TYPE {
-                                            if interactive {
-                                                let _ = progress_bars.hide();
-                                                print!("{}", formatted_message);
-                                                std::io::stdout().flush().unwrap();
-                                            } else {
-                                                print!("{}", formatted_message);
-                                                std::io::stdout().flush().unwrap();
-                                            }
+                                    } else if message_notification_type.as_deref() == Some(TASK_EXECUTION_NOTIFICATION_TYPE) {
+                                        if interactive {
+                                            let _ = progress_bars.hide();
+                                            print!("{}", formatted_message);
+                                            std::io::stdout().flush().unwrap();
+                                        } else {
+                                            print!("{}", formatted_message);
+                                            std::io::stdout().flush().unwrap();
                                         }
                                     }
+                                    // Other typed notifications, like resource updates, show as progress
                                     else if output::is_showing_thinking() {
                                         output::set_thinking_message(&formatted_message);
                                     } else {
@@ -1262,6 +1786,12 @@ impl Session {
                                 }
                             }
                         }
+                        Some(Ok(AgentEvent::PlanUpdate(plan))) => {
+                            output::render_plan(&plan);
+                        }
+                        Some(Ok(AgentEvent::TodoUpdate(todos))) => {
+                            output::render_todo_list(&todos);
+                        }
                         Some(Ok(AgentEvent::ModelChange { model, mode })) => {
                             // Log model change if in debug mode
                             if self.debug {
@@ -1271,10 +1801,12 @@ impl Session {
 
                         Some(Err(e)) => {
                             eprintln!("Error: {}", e);
+                            notify::error(&e.to_string());
                             cancel_token_clone.cancel();
                             drop(stream);
                             if let Err(e) = self.handle_interrupted_messages(false).await {
                                 eprintln!("Error handling interruption: {}", e);
+                                turn_saved = false;
                             }
                             output::render_error(
                                 "The error above was an exception we were not able to handle.\n\
@@ -1282,26 +1814,116 @@ impl Session {
                                 We've removed the conversation up to the most recent user message\n\
                                 - depending on the error you may be able to continue",
                             );
+                            // Headless runs have nobody to continue, so they fail with the error
+                            if !interactive {
+                                if turn_saved {
+                                    self.commit_turn();
+                                }
+                                return Err(e);
+                            }
                             break;
                         }
-                        None => break,
+                        None => {
+                            if interrupts >= 2 {
+                                // The turn was stopped, messages queued meanwhile are dropped
+                                let discarded = self.agent.take_queued_messages().await;
+                                if !discarded.is_empty() {
+                                    output::render_queued_discarded(discarded.len());
+                                }
+                                drop(stream);
+                                if let Err(e) = self.handle_interrupted_messages(true).await {
+                                    eprintln!("Error handling interruption: {}", e);
+                                    turn_saved = false;
+                                }
+                                break;
+                            }
+                            // Messages queued after the agent's last safe point start another turn
+                            let queued = self.agent.take_queued_messages().await;
+                            if queued.is_empty() {
+                                break;
+                            }
+                            // and get their own Ctrl+C presses
+                            interrupts = 0;
+                            queued_texts.clear();
+                            for message in queued {
+                                self.messages.push(message);
+                            }
+                            if let Some(session_file) = &self.session_file {
+                                let working_dir = std::env::current_dir().ok();
+                                session::persist_messages_with_schedule_id(
+                                    session_file,
+                                    &self.messages,
+                                    None,
+                                    self.scheduled_job_id.clone(),
+                                    working_dir,
+                                )
+                                .await?;
+                            }
+                            stream = self
+                                .agent
+                                .reply(
+                                    self.messages.clone(),
+                                    session_config.clone(),
+                                    Some(cancel_token_clone.clone()),
+                                )
+                                .await?;
+                        }
                     }
                 }
+                line = queued_input.next_line(), if interactive => {
+                    let queued = self.agent.queue_message(Message::user().with_text(&line)).await;
+                    queued_texts.push(line.clone());
+                    output::render_queued_message(&line, queued);
+                }
                 _ = tokio::signal::ctrl_c() => {
+                    // The first Ctrl+C only stops the running tools, the second stops the turn
+                    // at the agent's next safe point and the third exits goose
+                    interrupts += 1;
+                    if interrupts == 1 {
+                        let cancelled = self.agent.cancel_running_tools().await;
+                        if cancelled > 0 {
+                            output::render_tools_cancelled(cancelled);
+                            continue;
+                        }
+                        interrupts += 1;
+                    }
+                    if interrupts == 2 {
+                        cancel_token_clone.cancel();
+                        self.agent.cancel_running_tools().await;
+                        output::render_turn_stopping();
+                    } else {
+                        // Handled below, like any other shutdown
+                        goose::shutdown::request();
+                    }
+                }
+                _ = shutdown.cancelled() => {
+                    // goose is being stopped, end the turn and record it before exiting
                     cancel_token_clone.cancel();
                     drop(stream);
+                    self.agent.take_queued_messages().await;
                     if let Err(e) = self.handle_interrupted_messages(true).await {
                         eprintln!("Error handling interruption: {}", e);
+                        turn_saved = false;
                     }
                     break;
                 }
             }
         }
         println!();
+        if turn_saved {
+            self.commit_turn();
+        }
 
         Ok(())
     }
 
+    /// The messages of the turn are saved, so the journal kept in case goose stops mid-turn can go
+    fn commit_turn(&self) {
+        if let Some(session_file) = &self.session_file {
+            session::journal::commit(session_file);
+        }
+    }
+
     async fn handle_interrupted_messages(&mut self, interrupt: bool) -> Result<()> {
         // First, get any tool requests from the last message if it exists
         let tool_requests = self
@@ -1445,15 +2067,16 @@ impl Session {
             cache.prompts.insert(extension.clone(), names);
 
             for prompt in prompt_list {
-                cache.prompt_info.insert(
-                    prompt.name.clone(),
-                    output::PromptInfo {
-                        name: prompt.name.clone(),
-                        description: prompt.description.clone(),
-                        arguments: prompt.arguments.clone(),
-                        extension: Some(extension.clone()),
-                    },
-                );
+                let info = output::PromptInfo {
+                    name: prompt.name.clone(),
+                    description: prompt.description.clone(),
+                    arguments: prompt.arguments.clone(),
+                    extension: Some(extension.clone()),
+                };
+                cache
+                    .prompt_info
+                    .insert(format!("{}:{}", extension, prompt.name), info.clone());
+                cache.prompt_info.insert(prompt.name.clone(), info);
             }
         }
 
@@ -1474,6 +2097,43 @@ impl Session {
         self.messages.clone()
     }
 
+    /// Summarize a resumed conversation that no longer fits the context and store the summary
+    /// as the session's checkpoint, so the next resume starts from it
+    pub async fn compact_resumed_history(&mut self) -> Result<()> {
+        let Some(session_file) = self.session_file.clone() else {
+            return Ok(());
+        };
+        let mut metadata = session::read_metadata(&session_file)?;
+
+        output::show_thinking();
+        let compacted = goose::context_mgmt::checkpoint::compact_for_resume(
+            &self.agent,
+            &self.messages,
+            metadata.checkpoint.as_ref(),
+        )
+        .await;
+        output::hide_thinking();
+
+        if let Some((messages, checkpoint)) = compacted? {
+            metadata.message_count = messages.len();
+            metadata.checkpoint = Some(checkpoint);
+            session::storage::save_messages_with_metadata(&session_file, &metadata, &messages)?;
+            self.messages = messages;
+            output::render_text(
+                "The earlier conversation was summarized to fit the context",
+                Some(Color::Yellow),
+                true,
+            );
+        }
+        Ok(())
+    }
+
+    /// Print the rendered system prompt
+    pub async fn show_system_prompt(&self) -> Result<()> {
+        println!("{}", self.agent.system_prompt().await?);
+        Ok(())
+    }
+
     /// Render all past messages from the session history
     pub fn render_message_history(&self) {
         if self.messages.is_empty() {
@@ -1499,6 +2159,16 @@ impl Session {
         );
     }
 
+    /// How the agent's last reply ended
+    pub async fn reply_outcome(&self) -> ReplyOutcome {
+        self.agent.reply_outcome().await
+    }
+
+    /// The recipe's structured output, if the agent produced it
+    pub async fn final_output(&self) -> Option<String> {
+        self.agent.final_output().await
+    }
+
     pub fn get_metadata(&self) -> Result<session::SessionMetadata> {
         if !self.session_file.as_ref().is_some_and(|f| f.exists()) {
             return Err(anyhow::anyhow!("Session file does not exist"));
@@ -1541,11 +2211,24 @@ impl Session {
             }
         }
 
+        let breakdown = match self.agent.context_breakdown(self.messages.messages()).await {
+            Ok(breakdown) => Some(breakdown),
+            Err(e) => {
+                tracing::debug!("Failed to estimate the context breakdown: {}", e);
+                None
+            }
+        };
+
         match self.get_metadata() {
             Ok(metadata) => {
-                let total_tokens = metadata.total_tokens.unwrap_or(0) as usize;
+                // Before the first reply there is no usage yet, the estimate still shows what the
+                // system prompt and tools take
+                let total_tokens = match metadata.total_tokens {
+                    Some(tokens) if tokens > 0 => tokens as usize,
+                    _ => breakdown.map_or(0, |b| b.total()),
+                };
 
-                output::display_context_usage(total_tokens, context_limit);
+                output::display_context_usage(total_tokens, context_limit, breakdown);
 
                 if show_cost {
                     let input_tokens = metadata.input_tokens.unwrap_or(0) as usize;
@@ -1560,7 +2243,11 @@ impl Session {
                 }
             }
             Err(_) => {
-                output::display_context_usage(0, context_limit);
+                output::display_context_usage(
+                    breakdown.map_or(0, |b| b.total()),
+                    context_limit,
+                    breakdown,
+                );
             }
         }
 
@@ -1581,8 +2268,15 @@ impl Session {
                 None => output::render_error(&format!("Prompt '{}' not found", opts.name)),
             }
         } else {
+            let mut arguments = opts.arguments;
+            if let Some(info) = self.get_prompt_info(&opts.name).await? {
+                if !ask_prompt_arguments(&info, &mut arguments)? {
+                    return Ok(());
+                }
+            }
+
             // Convert the arguments HashMap to a Value
-            let arguments = serde_json::to_value(opts.arguments)
+            let arguments = serde_json::to_value(arguments)
                 .map_err(|e| anyhow::anyhow!("Failed to serialize arguments: {}", e))?;
 
             match self.get_prompt(&opts.name, arguments).await {
@@ -1677,6 +2371,46 @@ impl Session {
     }
 }
 
+/// Whether a local path looks like an image that should be sent as image content
+fn is_image_path(path: &Path) -> bool {
+    let is_image_extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .is_some_and(|e| matches!(e.as_str(), "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp"));
+    is_image_extension && path.is_file()
+}
+
+/// Ask for the declared arguments of a prompt that weren't given on the command line.
+/// Returns false when the user cancels.
+fn ask_prompt_arguments(
+    info: &output::PromptInfo,
+    arguments: &mut HashMap<String, String>,
+) -> Result<bool> {
+    for arg in info.arguments.iter().flatten() {
+        if arguments.contains_key(&arg.name) {
+            continue;
+        }
+
+        let required = arg.required.unwrap_or(false);
+        let label = match &arg.description {
+            Some(description) => format!("{} ({})", arg.name, description),
+            None => arg.name.clone(),
+        };
+        let mut input = cliclack::input(label).required(required);
+        let value: String = match input.interact() {
+            Ok(value) => value,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        if !value.is_empty() {
+            arguments.insert(arg.name.clone(), value);
+        }
+    }
+    Ok(true)
+}
+
 fn get_reasoner() -> Result<Arc<dyn Provider>, anyhow::Error> {
     use goose::model::ModelConfig;
     use goose::providers::create;
@@ -1709,3 +2443,85 @@ fn get_reasoner() -> Result<Arc<dyn Provider>, anyhow::Error> {
 
     Ok(reasoner)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::model::ModelConfig;
+    use goose::providers::base::{ProviderMetadata, ProviderUsage, Usage};
+    use goose::providers::errors::ProviderError;
+    use rmcp::model::Tool;
+
+    struct ReplyProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for ReplyProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::new(
+                "mock",
+                "Mock Provider",
+                "Mock provider for testing",
+                "mock-model",
+                vec!["mock-model"],
+                "",
+                vec![],
+            )
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new_or_fail("mock-model")
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Ok((
+                Message::assistant().with_text("done"),
+                ProviderUsage::new("mock-model".to_string(), Usage::default()),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_turn_commits_journal() {
+        let home = tempfile::tempdir().unwrap();
+        let vars = [
+            ("HOME", Some(home.path().to_str().unwrap())),
+            ("XDG_DATA_HOME", None),
+        ];
+        temp_env::async_with_vars(vars, async {
+            let session_file = session::get_path(Identifier::Name("ndjson".to_string())).unwrap();
+
+            let agent = Agent::new();
+            agent
+                .update_provider(Arc::new(ReplyProvider))
+                .await
+                .unwrap();
+            let mut session = Session::new(
+                agent,
+                Some(session_file.clone()),
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            session.set_output_format(OutputFormat::Ndjson);
+            session.messages.push(Message::user().with_text("hi"));
+
+            session
+                .stream_ndjson_response(CancellationToken::default())
+                .await
+                .unwrap();
+
+            assert_eq!(session::read_messages(&session_file).unwrap().len(), 2);
+            // A finished turn leaves nothing for the next resume to recover
+            assert!(!session::journal::journal_path(&session_file).exists());
+        })
+        .await;
+    }
+}
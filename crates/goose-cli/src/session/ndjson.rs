@@ -0,0 +1,148 @@
+use goose::agents::plan_tools::Plan;
+use goose::agents::todo_tools::TodoItem;
+use goose::conversation::message::{Message, MessageContent};
+use rmcp::model::ServerNotification;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+
+/// Bumped whenever an event changes in a way that could break consumers
+pub const NDJSON_SCHEMA_VERSION: u32 = 1;
+
+/// How a headless run reports what the agent does
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Rendered for a person reading the terminal
+    #[default]
+    Text,
+    /// One JSON event per line, for scripts and CI
+    Ndjson,
+}
+
+/// A line of `goose run --output-format ndjson` output
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NdjsonEvent<'a> {
+    Start {
+        schema_version: u32,
+        session_id: Option<&'a str>,
+    },
+    Message {
+        message: &'a Message,
+    },
+    ToolCall {
+        id: &'a str,
+        name: &'a str,
+        arguments: &'a Value,
+    },
+    ToolResult {
+        id: &'a str,
+        is_error: bool,
+    },
+    Notification {
+        extension: &'a str,
+        notification: &'a ServerNotification,
+    },
+    ModelChange {
+        model: &'a str,
+        mode: &'a str,
+    },
+    HistoryReplaced {
+        messages: &'a [Message],
+    },
+    PlanUpdate {
+        plan: &'a Plan,
+    },
+    TodoUpdate {
+        todos: &'a [TodoItem],
+    },
+    Usage {
+        input_tokens: Option<i32>,
+        output_tokens: Option<i32>,
+        total_tokens: Option<i32>,
+    },
+    Error {
+        message: String,
+    },
+    Finish {
+        status: &'a str,
+    },
+}
+
+/// Write an event to stdout as one line of JSON
+pub fn emit(event: &NdjsonEvent) {
+    let line = serde_json::to_string(event).unwrap_or_else(|e| {
+        serde_json::json!({"type": "error", "message": format!("Failed to serialize event: {}", e)})
+            .to_string()
+    });
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+/// The events for a message: the message itself, followed by one for each tool call or
+/// tool result it holds
+pub fn message_events(message: &Message) -> Vec<NdjsonEvent<'_>> {
+    let mut events = vec![NdjsonEvent::Message { message }];
+    for content in &message.content {
+        match content {
+            MessageContent::ToolRequest(request) => {
+                if let Ok(tool_call) = &request.tool_call {
+                    events.push(NdjsonEvent::ToolCall {
+                        id: &request.id,
+                        name: &tool_call.name,
+                        arguments: &tool_call.arguments,
+                    });
+                }
+            }
+            MessageContent::ToolResponse(response) => {
+                events.push(NdjsonEvent::ToolResult {
+                    id: &response.id,
+                    is_error: response.tool_result.is_err(),
+                });
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::tool::ToolCall;
+    use rmcp::model::Content;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_events() {
+        let message = Message::assistant()
+            .with_text("Listing files")
+            .with_tool_request(
+                "call-1",
+                Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+            );
+        let events = message_events(&message);
+        assert_eq!(events.len(), 2);
+
+        let call = serde_json::to_value(&events[1]).unwrap();
+        assert_eq!(
+            call,
+            json!({
+                "type": "tool_call",
+                "id": "call-1",
+                "name": "developer__shell",
+                "arguments": {"command": "ls"}
+            })
+        );
+
+        let response =
+            Message::user().with_tool_response("call-1", Ok(vec![Content::text("README.md")]));
+        let events = message_events(&response);
+        assert_eq!(
+            serde_json::to_value(&events[1]).unwrap(),
+            json!({"type": "tool_result", "id": "call-1", "is_error": false})
+        );
+        assert_eq!(serde_json::to_value(&events[0]).unwrap()["type"], "message");
+    }
+}
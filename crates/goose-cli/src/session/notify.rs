@@ -0,0 +1,134 @@
+//! Desktop notifications for when goose needs attention and the terminal is in the background:
+//! a tool call waiting for approval, a long turn that finished, or an error.
+use goose::config::Config;
+use std::process::Command;
+use std::time::Duration;
+
+pub const NOTIFICATIONS_KEY: &str = "GOOSE_CLI_NOTIFICATIONS";
+pub const NOTIFY_AFTER_KEY: &str = "GOOSE_CLI_NOTIFY_AFTER_SECONDS";
+/// Turns shorter than this finish while the user is still watching
+pub const DEFAULT_NOTIFY_AFTER_SECONDS: u64 = 30;
+
+const TITLE: &str = "goose";
+
+pub fn enabled() -> bool {
+    Config::global()
+        .get_param(NOTIFICATIONS_KEY)
+        .unwrap_or(false)
+}
+
+pub fn approval_needed(tool_name: &str) {
+    send(format!("Waiting for approval to call {}", tool_name));
+}
+
+pub fn turn_finished(elapsed: Duration) {
+    let notify_after = Config::global()
+        .get_param(NOTIFY_AFTER_KEY)
+        .unwrap_or(DEFAULT_NOTIFY_AFTER_SECONDS);
+    if elapsed >= Duration::from_secs(notify_after) {
+        send(format!("Finished after {}s", elapsed.as_secs()));
+    }
+}
+
+pub fn error(message: &str) {
+    send(format!("Stopped with an error: {}", message));
+}
+
+/// Show the notification on its own thread, unless notifications are off or the terminal has
+/// focus. When focus can't be told the notification is shown
+fn send(body: String) {
+    if !enabled() {
+        return;
+    }
+    std::thread::spawn(move || {
+        if terminal_focused() == Some(true) {
+            return;
+        }
+        if let Err(e) = show(&body) {
+            tracing::debug!("Failed to show a desktop notification: {}", e);
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn show(body: &str) -> anyhow::Result<()> {
+    let script = format!(
+        "display notification {} with title {}",
+        applescript_quote(body),
+        applescript_quote(TITLE)
+    );
+    Command::new("osascript").arg("-e").arg(script).output()?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn show(body: &str) -> anyhow::Result<()> {
+    notify_rust::Notification::new()
+        .summary(TITLE)
+        .body(body)
+        .show()?;
+    Ok(())
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn applescript_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Whether the terminal goose runs in is the focused window, None when that can't be told
+fn terminal_focused() -> Option<bool> {
+    if cfg!(target_os = "macos") {
+        let app = terminal_app(&std::env::var("TERM_PROGRAM").ok()?)?;
+        let frontmost = command_output(
+            Command::new("osascript").arg("-e").arg(
+                "tell application \"System Events\" to get name of first application process whose frontmost is true",
+            ),
+        )?;
+        Some(frontmost.eq_ignore_ascii_case(app))
+    } else if cfg!(target_os = "linux") {
+        let window_id: u64 = std::env::var("WINDOWID").ok()?.parse().ok()?;
+        let active: u64 = command_output(Command::new("xdotool").arg("getactivewindow"))?
+            .parse()
+            .ok()?;
+        Some(active == window_id)
+    } else {
+        None
+    }
+}
+
+/// The process name macOS reports for the terminal in TERM_PROGRAM
+fn terminal_app(term_program: &str) -> Option<&'static str> {
+    match term_program {
+        "Apple_Terminal" => Some("Terminal"),
+        "iTerm.app" => Some("iTerm2"),
+        "vscode" => Some("Code"),
+        "WezTerm" => Some("wezterm-gui"),
+        "ghostty" => Some("ghostty"),
+        "Alacritty" | "alacritty" => Some("alacritty"),
+        _ => None,
+    }
+}
+
+fn command_output(command: &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applescript_quote() {
+        assert_eq!(applescript_quote(r#"say "hi"\"#), r#""say \"hi\"\\""#);
+    }
+
+    #[test]
+    fn test_terminal_app() {
+        assert_eq!(terminal_app("iTerm.app"), Some("iTerm2"));
+        assert_eq!(terminal_app("tmux"), None);
+    }
+}
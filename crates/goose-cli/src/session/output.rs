@@ -1,10 +1,17 @@
 use anstream::println;
 use bat::WrappingMode;
 use console::{style, Color};
+use goose::agents::plan_tools::{Plan, StepStatus};
+use goose::agents::todo_tools::{TodoItem, TodoStatus};
+use goose::agents::ExtensionReload;
 use goose::config::Config;
+use goose::context_mgmt::ContextBreakdown;
 use goose::conversation::message::{Message, MessageContent, ToolRequest, ToolResponse};
-use goose::providers::pricing::get_model_pricing;
+use goose::conversation::tool_result_visibility;
 use goose::providers::pricing::parse_model_id;
+use goose::providers::pricing::{get_model_pricing, price_override};
+use goose::session::journal::Recovery;
+use goose::utils::safe_truncate;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use mcp_core::tool::ToolCall;
 use regex::Regex;
@@ -14,6 +21,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::{Error, IsTerminal, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -34,7 +42,7 @@ impl Theme {
         }
     }
 
-    fn from_config_str(val: &str) -> Self {
+    pub fn from_config_str(val: &str) -> Self {
         if val.eq_ignore_ascii_case("light") {
             Theme::Light
         } else if val.eq_ignore_ascii_case("ansi") {
@@ -44,7 +52,7 @@ impl Theme {
         }
     }
 
-    fn as_config_string(&self) -> String {
+    pub fn as_config_string(&self) -> String {
         match self {
             Theme::Light => "light".to_string(),
             Theme::Dark => "dark".to_string(),
@@ -88,6 +96,238 @@ pub fn get_theme() -> Theme {
     CURRENT_THEME.with(|t| *t.borrow())
 }
 
+pub const BAT_THEME_KEY: &str = "GOOSE_CLI_BAT_THEME";
+pub const BAT_CACHE_DIR_KEY: &str = "GOOSE_CLI_BAT_CACHE_DIR";
+pub const RENDER_MARKDOWN_KEY: &str = "GOOSE_CLI_RENDER_MARKDOWN";
+pub const SPINNER_KEY: &str = "GOOSE_CLI_SPINNER";
+pub const ASCII_KEY: &str = "GOOSE_CLI_ASCII";
+pub const PLAIN_KEY: &str = "GOOSE_CLI_PLAIN";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpinnerStyle {
+    Dots,
+    Braille,
+    Line,
+    None,
+}
+
+impl SpinnerStyle {
+    pub const ALL: [SpinnerStyle; 4] = [
+        SpinnerStyle::Dots,
+        SpinnerStyle::Braille,
+        SpinnerStyle::Line,
+        SpinnerStyle::None,
+    ];
+
+    pub fn from_config_str(val: &str) -> Self {
+        match val.to_lowercase().as_str() {
+            "braille" => SpinnerStyle::Braille,
+            "line" => SpinnerStyle::Line,
+            "none" => SpinnerStyle::None,
+            _ => SpinnerStyle::Dots,
+        }
+    }
+
+    pub fn as_config_str(&self) -> &'static str {
+        match self {
+            SpinnerStyle::Dots => "dots",
+            SpinnerStyle::Braille => "braille",
+            SpinnerStyle::Line => "line",
+            SpinnerStyle::None => "none",
+        }
+    }
+
+    /// Frames of the spinner, ending with the frame shown once it stops
+    fn chars(&self, ascii: bool) -> &'static str {
+        match self {
+            SpinnerStyle::Dots if !ascii => "◒◐◓◑◇",
+            SpinnerStyle::Braille if !ascii => "⠋⠙⠚⠛⠓⠒⠊⠉⠿",
+            _ => "-\\|/-",
+        }
+    }
+}
+
+/// How the CLI draws its output besides the color theme: the syntax theme used for markdown,
+/// whether markdown is highlighted at all, the spinner and whether to stick to ASCII
+#[derive(Clone, Debug)]
+pub struct Appearance {
+    /// A bat theme name replacing the one the color theme picks
+    pub bat_theme: Option<String>,
+    /// A bat cache directory, built with `bat cache --build`, holding custom themes
+    pub bat_cache_dir: Option<PathBuf>,
+    pub render_markdown: bool,
+    pub spinner: SpinnerStyle,
+    /// Draw with ASCII only, for terminals that can't show Unicode symbols
+    pub ascii: bool,
+}
+
+impl Appearance {
+    pub fn from_config() -> Self {
+        let config = Config::global();
+        Appearance {
+            bat_theme: config
+                .get_param::<String>(BAT_THEME_KEY)
+                .ok()
+                .filter(|t| !t.is_empty()),
+            bat_cache_dir: config
+                .get_param::<String>(BAT_CACHE_DIR_KEY)
+                .ok()
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from),
+            render_markdown: config.get_param(RENDER_MARKDOWN_KEY).unwrap_or(true),
+            spinner: config
+                .get_param::<String>(SPINNER_KEY)
+                .map(|s| SpinnerStyle::from_config_str(&s))
+                .unwrap_or(SpinnerStyle::Dots),
+            ascii: config.get_param(ASCII_KEY).unwrap_or(false),
+        }
+    }
+
+    /// The bat themes that can be picked: the bundled ones and those in the cache directory
+    pub fn available_bat_themes(&self) -> Vec<String> {
+        let mut themes: Vec<String> = match self.highlighting_assets() {
+            Some(assets) => assets.themes().map(|t| t.to_string()).collect(),
+            None => bat::PrettyPrinter::new()
+                .themes()
+                .map(|t| t.to_string())
+                .collect(),
+        };
+        themes.sort();
+        themes.dedup();
+        themes
+    }
+
+    fn highlighting_assets(&self) -> Option<bat::assets::HighlightingAssets> {
+        let dir = self.bat_cache_dir.as_ref()?;
+        match bat::assets::HighlightingAssets::from_cache(dir) {
+            Ok(assets) => Some(assets),
+            Err(e) => {
+                tracing::warn!("Could not load bat themes from {}: {}", dir.display(), e);
+                None
+            }
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT_APPEARANCE: RefCell<Appearance> = RefCell::new(Appearance::from_config());
+}
+
+pub fn get_appearance() -> Appearance {
+    CURRENT_APPEARANCE.with(|a| a.borrow().clone())
+}
+
+/// Use new appearance settings for the rest of the session, including for cliclack prompts
+pub fn set_appearance(appearance: Appearance) {
+    cliclack::set_theme(AppearanceTheme {
+        spinner: appearance.spinner,
+        ascii: appearance.ascii,
+    });
+    CURRENT_APPEARANCE.with(|a| *a.borrow_mut() = appearance);
+}
+
+/// Apply the configured appearance to cliclack prompts and spinners
+pub fn apply_appearance() {
+    set_appearance(get_appearance());
+}
+
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether plain output was asked for with `--plain` or GOOSE_CLI_PLAIN
+pub fn plain_output_requested(flag: bool) -> bool {
+    flag || Config::global()
+        .get_param::<bool>(PLAIN_KEY)
+        .unwrap_or(false)
+}
+
+/// Write line oriented plain text for logs and CI: no colors, spinners, progress bars,
+/// thinking indicators or markdown highlighting
+pub fn enable_plain_output() {
+    PLAIN_OUTPUT.store(true, Ordering::Relaxed);
+    console::set_colors_enabled(false);
+    console::set_colors_enabled_stderr(false);
+    set_appearance(Appearance {
+        render_markdown: false,
+        spinner: SpinnerStyle::None,
+        ascii: true,
+        ..get_appearance()
+    });
+}
+
+pub fn is_plain_output() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Whether output goes to a terminal that can show styling and animation
+pub fn is_interactive_output() -> bool {
+    !is_plain_output() && std::io::stdout().is_terminal()
+}
+
+/// The cliclack theme with the configured spinner and, in ASCII mode, plain symbols
+struct AppearanceTheme {
+    spinner: SpinnerStyle,
+    ascii: bool,
+}
+
+impl cliclack::Theme for AppearanceTheme {
+    fn spinner_chars(&self) -> String {
+        self.spinner.chars(self.ascii).to_string()
+    }
+
+    fn state_symbol(&self, state: &cliclack::ThemeState) -> String {
+        if !self.ascii {
+            return cliclack::Theme::state_symbol(&cliclack_default_theme(), state);
+        }
+        let symbol = match state {
+            cliclack::ThemeState::Active => "*",
+            cliclack::ThemeState::Cancel => "x",
+            cliclack::ThemeState::Submit => "o",
+            cliclack::ThemeState::Error(_) => "!",
+        };
+        self.state_symbol_color(state).apply_to(symbol).to_string()
+    }
+
+    fn info_symbol(&self) -> String {
+        if self.ascii {
+            style("i").blue().to_string()
+        } else {
+            cliclack_default_theme().info_symbol()
+        }
+    }
+
+    fn warning_symbol(&self) -> String {
+        if self.ascii {
+            style("!").yellow().to_string()
+        } else {
+            cliclack_default_theme().warning_symbol()
+        }
+    }
+
+    fn error_symbol(&self) -> String {
+        if self.ascii {
+            style("x").red().to_string()
+        } else {
+            cliclack_default_theme().error_symbol()
+        }
+    }
+}
+
+/// cliclack's own theme, to fall back to for the symbols that aren't overridden
+fn cliclack_default_theme() -> impl cliclack::Theme {
+    struct DefaultTheme;
+    impl cliclack::Theme for DefaultTheme {}
+    DefaultTheme
+}
+
+/// A Unicode symbol, or its stand-in when the appearance is limited to ASCII
+fn glyph(unicode: &'static str, ascii: &'static str) -> &'static str {
+    if get_appearance().ascii {
+        ascii
+    } else {
+        unicode
+    }
+}
+
 // Simple wrapper around spinner to manage its state
 #[derive(Default)]
 pub struct ThinkingIndicator {
@@ -96,6 +336,9 @@ pub struct ThinkingIndicator {
 
 impl ThinkingIndicator {
     pub fn show(&mut self) {
+        if get_appearance().spinner == SpinnerStyle::None {
+            return;
+        }
         let spinner = cliclack::spinner();
         if Config::global()
             .get_param("RANDOM_THINKING_MESSAGES")
@@ -136,13 +379,13 @@ thread_local! {
 }
 
 pub fn show_thinking() {
-    if std::io::stdout().is_terminal() {
+    if is_interactive_output() {
         THINKING.with(|t| t.borrow_mut().show());
     }
 }
 
 pub fn hide_thinking() {
-    if std::io::stdout().is_terminal() {
+    if is_interactive_output() {
         THINKING.with(|t| t.borrow_mut().hide());
     }
 }
@@ -152,7 +395,7 @@ pub fn is_showing_thinking() -> bool {
 }
 
 pub fn set_thinking_message(s: &String) {
-    if std::io::stdout().is_terminal() {
+    if is_interactive_output() {
         THINKING.with(|t| {
             if let Some(spinner) = t.borrow_mut().spinner.as_mut() {
                 spinner.set_message(s);
@@ -173,9 +416,7 @@ pub fn render_message(message: &Message, debug: bool) {
                 println!("Image: [data: {}, type: {}]", image.data, image.mime_type);
             }
             MessageContent::Thinking(thinking) => {
-                if std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok()
-                    && std::io::stdout().is_terminal()
-                {
+                if std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok() && is_interactive_output() {
                     println!("\n{}", style("Thinking:").dim().italic());
                     print_markdown(&thinking.thinking, theme);
                 }
@@ -194,6 +435,12 @@ pub fn render_message(message: &Message, debug: bool) {
         }
     }
 
+    if debug {
+        if let Some(metadata) = &message.metadata {
+            println!("{}", style(metadata.summary()).dim());
+        }
+    }
+
     let _ = std::io::stdout().flush();
 }
 
@@ -202,7 +449,7 @@ pub fn render_text(text: &str, color: Option<Color>, dim: bool) {
 }
 
 pub fn render_text_no_newlines(text: &str, color: Option<Color>, dim: bool) {
-    if !std::io::stdout().is_terminal() {
+    if !is_interactive_output() {
         println!("{}", text);
         return;
     }
@@ -259,27 +506,11 @@ fn render_tool_request(req: &ToolRequest, theme: Theme, debug: bool) {
 }
 
 fn render_tool_response(resp: &ToolResponse, theme: Theme, debug: bool) {
-    let config = Config::global();
-
     match &resp.tool_result {
         Ok(contents) => {
+            let min_priority = tool_result_visibility::min_priority();
             for content in contents {
-                if let Some(audience) = content.audience() {
-                    if !audience.contains(&rmcp::model::Role::User) {
-                        continue;
-                    }
-                }
-
-                let min_priority = config
-                    .get_param::<f32>("GOOSE_CLI_MIN_PRIORITY")
-                    .ok()
-                    .unwrap_or(0.5);
-
-                if content
-                    .priority()
-                    .is_some_and(|priority| priority < min_priority)
-                    || (content.priority().is_none() && !debug)
-                {
+                if !tool_result_visibility::is_shown_to_user(content, min_priority, debug) {
                     continue;
                 }
 
@@ -298,6 +529,29 @@ pub fn render_error(message: &str) {
     println!("\n  {} {}\n", style("error:").red().bold(), message);
 }
 
+/// Tell the user how a turn cut short by a crash was reconciled
+pub fn render_turn_recovery(recovery: &Recovery) {
+    println!(
+        "\n  {} goose stopped in the middle of the last turn of this session",
+        style("warning:").yellow().bold()
+    );
+    for call in &recovery.aborted_tool_calls {
+        println!(
+            "    {} {} was marked as aborted, its effects are unknown",
+            style("-").dim(),
+            style(&call.name).cyan()
+        );
+    }
+    if !recovery.partial_text.is_empty() {
+        println!(
+            "    {} {} characters of goose's reply were lost",
+            style("-").dim(),
+            recovery.partial_text.chars().count()
+        );
+    }
+    println!();
+}
+
 pub fn render_prompts(prompts: &HashMap<String, Vec<String>>) {
     println!();
     for (extension, prompts) in prompts {
@@ -365,6 +619,240 @@ pub fn render_extension_error(name: &str, error: &str) {
     println!();
 }
 
+pub fn render_extensions_reload(reload: &ExtensionReload) {
+    println!();
+    if reload.started.is_empty()
+        && reload.restarted.is_empty()
+        && reload.stopped.is_empty()
+        && reload.failed.is_empty()
+    {
+        println!("  extensions already match the config");
+    }
+    for (label, names) in [
+        (style("started").green(), &reload.started),
+        (style("restarted").green(), &reload.restarted),
+        (style("stopped").yellow(), &reload.stopped),
+    ] {
+        if !names.is_empty() {
+            println!("  {} {}", label, style(names.join(", ")).cyan());
+        }
+    }
+    for (name, error) in &reload.failed {
+        println!("  {} to start {}", style("failed").red(), style(name).red());
+        println!("{}", style(error).dim());
+    }
+    println!();
+}
+
+pub fn render_extensions_reload_error(error: &str) {
+    println!();
+    println!("  {} to reload extensions", style("failed").red());
+    println!();
+    println!("{}", style(error).dim());
+    println!();
+}
+
+pub fn render_queued_message(text: &str, queued: usize) {
+    let was_thinking = is_showing_thinking();
+    hide_thinking();
+    println!(
+        "  {} {} {}",
+        style("queued").yellow(),
+        style(format!("({})", queued)).dim(),
+        style(text).dim()
+    );
+    if was_thinking {
+        show_thinking();
+    }
+}
+
+pub fn render_plan(plan: &Plan) {
+    let was_thinking = is_showing_thinking();
+    hide_thinking();
+    let (completed, total) = plan.progress();
+    println!();
+    println!(
+        "  {} {}",
+        style(&plan.title).bold(),
+        style(format!("({}/{})", completed, total)).dim()
+    );
+    for (index, step) in plan.steps.iter().enumerate() {
+        let marker = match step.status {
+            StepStatus::Pending => style(step.status.marker()).dim(),
+            StepStatus::InProgress => style(step.status.marker()).yellow(),
+            StepStatus::Completed => style(step.status.marker()).green(),
+            StepStatus::Failed => style(step.status.marker()).red(),
+        };
+        let note = step
+            .note
+            .as_ref()
+            .map(|note| format!(" ({})", note))
+            .unwrap_or_default();
+        println!(
+            "  {} {}. {}{}",
+            marker,
+            index + 1,
+            step.description,
+            style(note).dim()
+        );
+    }
+    println!();
+    if was_thinking {
+        show_thinking();
+    }
+}
+
+pub fn render_todo_list(todos: &[TodoItem]) {
+    let was_thinking = is_showing_thinking();
+    hide_thinking();
+    let completed = todos
+        .iter()
+        .filter(|item| item.status == TodoStatus::Completed)
+        .count();
+    println!();
+    println!(
+        "  {} {}",
+        style("TODO").bold(),
+        style(format!("({}/{})", completed, todos.len())).dim()
+    );
+    for item in todos {
+        let marker = match item.status {
+            TodoStatus::Pending => style(item.status.marker()).dim(),
+            TodoStatus::InProgress => style(item.status.marker()).yellow(),
+            TodoStatus::Completed => style(item.status.marker()).green(),
+            TodoStatus::Cancelled => style(item.status.marker()).dim(),
+        };
+        let title = if item.status == TodoStatus::Cancelled {
+            style(item.title.as_str()).dim().strikethrough()
+        } else {
+            style(item.title.as_str())
+        };
+        let owner = item
+            .owner
+            .as_ref()
+            .map(|owner| format!(" @{}", owner))
+            .unwrap_or_default();
+        let notes = item
+            .notes
+            .as_ref()
+            .map(|notes| format!(" ({})", notes))
+            .unwrap_or_default();
+        println!(
+            "  {} {}. {}{}{}",
+            marker,
+            item.id,
+            title,
+            style(owner).cyan(),
+            style(notes).dim()
+        );
+    }
+    println!();
+    if was_thinking {
+        show_thinking();
+    }
+}
+
+pub fn render_undo(user_text: &str, removed: usize) {
+    println!();
+    println!(
+        "  {} {} message{} starting with:",
+        style("undo").yellow(),
+        removed,
+        if removed == 1 { "" } else { "s" }
+    );
+    println!("  {}", style(safe_truncate(user_text, 200)).dim());
+    println!();
+}
+
+pub fn render_tools_cancelled(count: usize) {
+    let was_thinking = is_showing_thinking();
+    hide_thinking();
+    println!(
+        "  {}",
+        style(format!(
+            "Cancelled {} running tool call{}, press Ctrl+C again to stop goose",
+            count,
+            if count == 1 { "" } else { "s" }
+        ))
+        .yellow()
+    );
+    if was_thinking {
+        show_thinking();
+    }
+}
+
+pub fn render_turn_stopping() {
+    let was_thinking = is_showing_thinking();
+    hide_thinking();
+    println!(
+        "  {}",
+        style("Stopping goose, press Ctrl+C again to exit").yellow()
+    );
+    if was_thinking {
+        show_thinking();
+    }
+}
+
+pub fn render_queued_discarded(count: usize) {
+    println!(
+        "  {}",
+        style(format!(
+            "Discarded {} queued message{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        ))
+        .yellow()
+    );
+}
+
+pub fn render_attachment_success(path: &str) {
+    println!();
+    println!(
+        "  {} `{}`, it will be sent with your next message",
+        style("attached").green(),
+        style(path).cyan(),
+    );
+    println!();
+}
+
+pub fn render_attachment_error(path: &str, error: &str) {
+    println!();
+    println!(
+        "  {} to attach {}",
+        style("failed").red(),
+        style(path).red()
+    );
+    println!();
+    println!("{}", style(error).dim());
+    println!();
+}
+
+pub fn render_change_dir_success(dir: &Path) {
+    println!();
+    println!(
+        "  {} to `{}`",
+        style("switched").green(),
+        style(dir.display()).cyan(),
+    );
+    println!();
+}
+
+pub fn render_change_dir_error(path: &str, error: &str) {
+    println!();
+    println!(
+        "  {} to switch to {}",
+        style("failed").red(),
+        style(path).red()
+    );
+    println!();
+    println!("{}", style(error).dim());
+    println!();
+}
+
+pub fn render_custom_command_tool(tool_name: &str) {
+    println!("  {} {}", style("running").dim(), style(tool_name).cyan());
+}
+
 pub fn render_builtin_success(names: &str) {
     println!();
     println!(
@@ -480,7 +968,8 @@ fn render_default_request(call: &ToolCall, debug: bool) {
 fn print_tool_header(call: &ToolCall) {
     let parts: Vec<_> = call.name.rsplit("__").collect();
     let tool_header = format!(
-        "─── {} | {} ──────────────────────────",
+        "{} {} | {} {}",
+        glyph("───", "---"),
         style(parts.first().unwrap_or(&"unknown")),
         style(
             parts
@@ -490,6 +979,7 @@ fn print_tool_header(call: &ToolCall) {
         )
         .magenta()
         .dim(),
+        glyph("──────────────────────────", "--------------------------"),
     );
     println!();
     println!("{}", tool_header);
@@ -502,18 +992,51 @@ pub fn env_no_color() -> bool {
 }
 
 fn print_markdown(content: &str, theme: Theme) {
-    if std::io::stdout().is_terminal() {
-        bat::PrettyPrinter::new()
-            .input(bat::Input::from_bytes(content.as_bytes()))
-            .theme(theme.as_str())
-            .colored_output(env_no_color())
-            .language("Markdown")
-            .wrapping_mode(WrappingMode::NoWrapping(true))
-            .print()
-            .unwrap();
-    } else {
+    let appearance = get_appearance();
+    if !is_interactive_output() || !appearance.render_markdown {
         print!("{}", content);
+        return;
     }
+
+    let bat_theme = appearance
+        .bat_theme
+        .clone()
+        .unwrap_or_else(|| theme.as_str().to_string());
+    if let Some(assets) = appearance.highlighting_assets() {
+        let config = bat::config::Config {
+            language: Some("Markdown"),
+            colored_output: env_no_color(),
+            true_color: true,
+            theme: bat_theme.clone(),
+            wrapping_mode: WrappingMode::NoWrapping(true),
+            term_width: console::Term::stdout().size().1 as usize,
+            ..Default::default()
+        };
+        let input = bat::input::Input::from_reader(Box::new(content.as_bytes()));
+        if bat::controller::Controller::new(&config, &assets)
+            .run(vec![input], None)
+            .is_ok()
+        {
+            return;
+        }
+    }
+
+    bat::PrettyPrinter::new()
+        .input(bat::Input::from_bytes(content.as_bytes()))
+        .theme(bat_theme)
+        .colored_output(env_no_color())
+        .language("Markdown")
+        .wrapping_mode(WrappingMode::NoWrapping(true))
+        .print()
+        .unwrap();
+}
+
+/// Sample output for previewing a theme while configuring the appearance
+pub fn preview_markdown(theme: Theme) {
+    print_markdown(
+        "# Preview\n\nSome **bold** text, a [link](https://block.github.io/goose) and `code`:\n\n```rust\nfn main() {\n    println!(\"hello, goose\");\n}\n```\n",
+        theme,
+    );
 }
 
 const INDENT: &str = "    ";
@@ -727,7 +1250,11 @@ pub fn display_greeting() {
 }
 
 /// Display context window usage with both current and session totals
-pub fn display_context_usage(total_tokens: usize, context_limit: usize) {
+pub fn display_context_usage(
+    total_tokens: usize,
+    context_limit: usize,
+    breakdown: Option<ContextBreakdown>,
+) {
     use console::style;
 
     if context_limit == 0 {
@@ -745,8 +1272,8 @@ pub fn display_context_usage(total_tokens: usize, context_limit: usize) {
         (((percentage as f64 / 100.0) * dot_count as f64).round() as usize).min(dot_count);
     let empty_dots = dot_count - filled_dots;
 
-    let filled = "●".repeat(filled_dots);
-    let empty = "○".repeat(empty_dots);
+    let filled = glyph("●", "#").repeat(filled_dots);
+    let empty = glyph("○", ".").repeat(empty_dots);
 
     // Combine dots and apply color
     let dots = format!("{}{}", filled, empty);
@@ -763,6 +1290,39 @@ pub fn display_context_usage(total_tokens: usize, context_limit: usize) {
         "Context: {} {}% ({}/{} tokens)",
         colored_dots, percentage, total_tokens, context_limit
     );
+
+    if let Some(breakdown) = breakdown.filter(|b| b.total() > 0) {
+        let part = |label: &str, tokens: usize| {
+            format!(
+                "{} {} ({}%)",
+                label,
+                format_tokens(tokens),
+                tokens * 100 / breakdown.total()
+            )
+        };
+        println!(
+            "         {}",
+            style(
+                [
+                    part("system", breakdown.system_prompt),
+                    part("tools", breakdown.tool_schemas),
+                    part("conversation", breakdown.conversation),
+                    part("tool outputs", breakdown.tool_outputs),
+                ]
+                .join(&format!(" {} ", glyph("·", "|")))
+            )
+            .dim()
+        );
+    }
+}
+
+/// Token counts as shown in the context breakdown, e.g. 950 or 12.3k
+fn format_tokens(tokens: usize) -> String {
+    if tokens < 1000 {
+        tokens.to_string()
+    } else {
+        format!("{:.1}k", tokens as f64 / 1000.0)
+    }
 }
 
 fn normalize_model_name(model: &str) -> String {
@@ -788,7 +1348,7 @@ fn normalize_model_name(model: &str) -> String {
     result
 }
 
-async fn estimate_cost_usd(
+pub async fn estimate_cost_usd(
     provider: &str,
     model: &str,
     input_tokens: usize,
@@ -806,9 +1366,15 @@ async fn estimate_cost_usd(
         None => (provider, model),
     };
 
-    // Use the pricing module's get_model_pricing which handles model name mapping internally
-    let cleaned_model = normalize_model_name(model_to_use);
-    let pricing_info = get_model_pricing(provider_to_use, &cleaned_model).await;
+    // Prices set in config are keyed by the model name as configured, before any mapping
+    let pricing_info = match price_override(provider, model) {
+        Some(pricing) => Some(pricing),
+        None => {
+            // Use the pricing module's get_model_pricing which handles model name mapping internally
+            let cleaned_model = normalize_model_name(model_to_use);
+            get_model_pricing(provider_to_use, &cleaned_model).await
+        }
+    };
 
     match pricing_info {
         Some(pricing) => {
@@ -856,13 +1422,17 @@ impl McpSpinners {
     }
 
     pub fn log(&mut self, message: &str) {
+        if is_plain_output() {
+            eprintln!("{}", message);
+            return;
+        }
         let spinner = self.log_spinner.get_or_insert_with(|| {
             let bar = self.multi_bar.add(
                 ProgressBar::new_spinner()
                     .with_style(
                         ProgressStyle::with_template("{spinner:.green} {msg}")
                             .unwrap()
-                            .tick_chars("⠋⠙⠚⠛⠓⠒⠊⠉"),
+                            .tick_chars(glyph("⠋⠙⠚⠛⠓⠒⠊⠉", "-\\|/-")),
                     )
                     .with_message(message.to_string()),
             );
@@ -874,6 +1444,14 @@ impl McpSpinners {
     }
 
     pub fn update(&mut self, token: &str, value: f64, total: Option<f64>, message: Option<&str>) {
+        if is_plain_output() {
+            let progress = match total {
+                Some(total) => format!("{}/{}", value, total),
+                None => value.to_string(),
+            };
+            eprintln!("progress {}: {}", progress, message.unwrap_or_default());
+            return;
+        }
         let bar = self.bars.entry(token.to_string()).or_insert_with(|| {
             if let Some(total) = total {
                 self.multi_bar.add(
@@ -0,0 +1,99 @@
+use std::io::IsTerminal;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Reads lines the user types while the agent is working, so they can be queued for the
+/// running turn instead of waiting for the prompt to come back
+///
+/// Only complete lines are returned. The terminal is in line mode while the agent works, so
+/// nothing is read until the user presses Enter and the editor gets any partial input back.
+pub struct QueuedInputReader {
+    enabled: bool,
+    buffer: Vec<u8>,
+}
+
+impl QueuedInputReader {
+    pub fn new() -> Self {
+        Self {
+            enabled: cfg!(unix) && std::io::stdin().is_terminal(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Wait for the next non-empty line, never resolves when queueing is not supported
+    pub async fn next_line(&mut self) -> String {
+        if !self.enabled {
+            return std::future::pending().await;
+        }
+
+        loop {
+            if let Some(line) = self.take_line() {
+                return line;
+            }
+            if !self.read_available() {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+
+    fn take_line(&mut self) -> Option<String> {
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line).trim().to_string();
+            if !line.is_empty() {
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    /// Read whatever is waiting on stdin without blocking, returns false when nothing was read
+    #[cfg(unix)]
+    fn read_available(&mut self) -> bool {
+        use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
+        use std::os::fd::AsFd;
+
+        let stdin = std::io::stdin();
+        let mut fds = [PollFd::new(stdin.as_fd(), PollFlags::POLLIN)];
+        match poll(&mut fds, PollTimeout::ZERO) {
+            Ok(ready) if ready > 0 => {}
+            _ => return false,
+        }
+
+        let mut chunk = [0u8; 4096];
+        match nix::unistd::read(stdin.as_fd(), &mut chunk) {
+            Ok(read) if read > 0 => {
+                self.buffer.extend_from_slice(&chunk[..read]);
+                true
+            }
+            _ => {
+                // End of input or a read error, stop trying for the rest of this turn
+                self.enabled = false;
+                false
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn read_available(&mut self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_line() {
+        let mut reader = QueuedInputReader {
+            enabled: true,
+            buffer: b"first\n\n  second  \npartial".to_vec(),
+        };
+        assert_eq!(reader.take_line().as_deref(), Some("first"));
+        assert_eq!(reader.take_line().as_deref(), Some("second"));
+        assert_eq!(reader.take_line(), None);
+        assert_eq!(reader.buffer, b"partial");
+    }
+}
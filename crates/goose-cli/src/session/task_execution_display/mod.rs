@@ -2,6 +2,7 @@ use goose::agents::subagent_execution_tool::lib::TaskStatus;
 use goose::agents::subagent_execution_tool::notification_events::{
     TaskExecutionNotificationEvent, TaskInfo,
 };
+use goose::agents::subagent_execution_tool::utils::summarize_subagent_message;
 use goose::utils::safe_truncate;
 use serde_json::Value;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -59,6 +60,16 @@ pub fn format_task_execution_notification(
                 None,
                 Some(TASK_EXECUTION_NOTIFICATION_TYPE.to_string()),
             ),
+            TaskExecutionNotificationEvent::SubagentMessage {
+                task_name, message, ..
+            } => (
+                summarize_subagent_message(&message)
+                    .iter()
+                    .map(|line| format!("   │ {}: {}\n", task_name, line))
+                    .collect(),
+                None,
+                Some(TASK_EXECUTION_NOTIFICATION_TYPE.to_string()),
+            ),
             TaskExecutionNotificationEvent::TasksUpdate { .. } => {
                 let formatted_display = format_tasks_update_from_event(&event);
                 (
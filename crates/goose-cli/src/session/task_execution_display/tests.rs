@@ -2,6 +2,7 @@ use super::*;
 use goose::agents::subagent_execution_tool::notification_events::{
     FailedTaskInfo, TaskCompletionStats, TaskExecutionStats,
 };
+use goose::conversation::message::Message;
 use serde_json::json;
 
 #[test]
@@ -80,6 +81,20 @@ fn test_format_task_execution_notification_line_output() {
     assert_eq!(third, Some("task_execution".to_string()));
 }
 
+#[test]
+fn test_format_task_execution_notification_subagent_message() {
+    let event = TaskExecutionNotificationEvent::subagent_message(
+        "task-1".to_string(),
+        "weather".to_string(),
+        Message::assistant().with_text("Fetching the forecast"),
+    );
+
+    let (formatted, _, notification_type) =
+        format_task_execution_notification(&event.to_notification_data()).unwrap();
+    assert_eq!(formatted, "   │ weather: Fetching the forecast\n");
+    assert_eq!(notification_type, Some("task_execution".to_string()));
+}
+
 #[test]
 fn test_format_task_execution_notification_invalid_data() {
     let invalid_data = json!({
@@ -0,0 +1,870 @@
+//! `goose tui`, a full screen dashboard over the same session as `goose session`: the
+//! conversation, live tool activity, token and cost meters and the plan and TODO list, driven
+//! from the keyboard alone.
+use anyhow::Result;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use futures::StreamExt;
+use goose::agents::plan_tools::{Plan, StepStatus};
+use goose::agents::todo_tools::{TodoItem, TodoStatus};
+use goose::agents::AgentEvent;
+use goose::config::Config;
+use goose::conversation::message::{Message, MessageContent, ToolConfirmationRequest};
+use goose::conversation::Conversation;
+use goose::permission::permission_confirmation::PrincipalType;
+use goose::permission::{Permission, PermissionConfirmation};
+use goose::session;
+use goose::webhooks::WebhookEvent;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Clear, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use rmcp::model::Role;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use super::{estimate_cost_usd, notify, Session};
+
+/// How often the screen is redrawn while a turn runs, so tool timers move
+const TICK: Duration = Duration::from_millis(250);
+const SCROLL_PAGE: u16 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Conversation,
+    Tools,
+    Plan,
+}
+
+impl Pane {
+    const ALL: [Pane; 3] = [Pane::Conversation, Pane::Tools, Pane::Plan];
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|pane| pane == self).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToolStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone)]
+struct ToolActivity {
+    id: String,
+    name: String,
+    started: Instant,
+    elapsed: Option<Duration>,
+    status: ToolStatus,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Usage {
+    model: String,
+    context_tokens: usize,
+    context_limit: usize,
+    input_tokens: usize,
+    output_tokens: usize,
+    cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TuiAction {
+    None,
+    Quit,
+    /// Stop the running turn
+    Interrupt,
+    Submit(String),
+    Confirm(String, Permission),
+}
+
+/// Why a turn ended
+enum TurnEnd {
+    Finished,
+    Interrupted,
+    Failed(anyhow::Error),
+    Quit,
+}
+
+struct TuiApp {
+    input: String,
+    focus: Pane,
+    /// Lines scrolled up from the bottom of each pane, 0 follows new content
+    scroll: [u16; 3],
+    tools: Vec<ToolActivity>,
+    plan: Option<Plan>,
+    todos: Vec<TodoItem>,
+    usage: Usage,
+    confirmation: Option<ToolConfirmationRequest>,
+    running: bool,
+    status: String,
+}
+
+impl TuiApp {
+    fn new() -> Self {
+        Self {
+            input: String::new(),
+            focus: Pane::Conversation,
+            scroll: [0; 3],
+            tools: Vec::new(),
+            plan: None,
+            todos: Vec::new(),
+            usage: Usage::default(),
+            confirmation: None,
+            running: false,
+            status: String::new(),
+        }
+    }
+
+    /// Track the tool calls a message starts and finishes
+    fn record(&mut self, message: &Message) {
+        for content in &message.content {
+            match content {
+                MessageContent::ToolRequest(request) => {
+                    let name = match &request.tool_call {
+                        Ok(tool_call) => tool_call.name.clone(),
+                        Err(_) => "invalid tool call".to_string(),
+                    };
+                    self.tools.push(ToolActivity {
+                        id: request.id.clone(),
+                        name,
+                        started: Instant::now(),
+                        elapsed: None,
+                        status: ToolStatus::Running,
+                    });
+                }
+                MessageContent::ToolResponse(response) => {
+                    if let Some(tool) = self.tools.iter_mut().find(|tool| tool.id == response.id) {
+                        tool.elapsed = Some(tool.started.elapsed());
+                        tool.status = if response.tool_result.is_ok() {
+                            ToolStatus::Succeeded
+                        } else {
+                            ToolStatus::Failed
+                        };
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The turn ended, tools still running won't report back
+    fn finish_turn(&mut self) {
+        self.running = false;
+        self.confirmation = None;
+        for tool in &mut self.tools {
+            if tool.status == ToolStatus::Running {
+                tool.elapsed = Some(tool.started.elapsed());
+                tool.status = ToolStatus::Cancelled;
+            }
+        }
+    }
+
+    /// Add the queued messages dropped with an unfinished turn to the status
+    fn note_discarded(&mut self, count: usize) {
+        if count > 0 {
+            self.status = format!(
+                "{}, discarded {} queued message{}",
+                self.status,
+                count,
+                if count == 1 { "" } else { "s" }
+            );
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) -> TuiAction {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
+            Event::Paste(text) => {
+                self.input.push_str(&text);
+                TuiAction::None
+            }
+            _ => TuiAction::None,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> TuiAction {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('d')) {
+            return TuiAction::Quit;
+        }
+
+        if let Some(confirmation) = &self.confirmation {
+            let permission = match key.code {
+                KeyCode::Char('y') => Permission::AllowOnce,
+                KeyCode::Char('a') => Permission::AlwaysAllow,
+                KeyCode::Char('n') => Permission::DenyOnce,
+                KeyCode::Char('c') | KeyCode::Esc => Permission::Cancel,
+                _ => return TuiAction::None,
+            };
+            let id = confirmation.id.clone();
+            self.confirmation = None;
+            return TuiAction::Confirm(id, permission);
+        }
+
+        let scroll = &mut self.scroll[self.focus.index()];
+        match key.code {
+            KeyCode::Esc if self.running => return TuiAction::Interrupt,
+            KeyCode::Esc => self.input.clear(),
+            KeyCode::Tab => {
+                self.focus = Pane::ALL[(self.focus.index() + 1) % Pane::ALL.len()];
+            }
+            KeyCode::BackTab => {
+                self.focus =
+                    Pane::ALL[(self.focus.index() + Pane::ALL.len() - 1) % Pane::ALL.len()];
+            }
+            KeyCode::Up => *scroll = scroll.saturating_add(1),
+            KeyCode::Down => *scroll = scroll.saturating_sub(1),
+            KeyCode::PageUp => *scroll = scroll.saturating_add(SCROLL_PAGE),
+            KeyCode::PageDown => *scroll = scroll.saturating_sub(SCROLL_PAGE),
+            KeyCode::Home => *scroll = u16::MAX,
+            KeyCode::End => *scroll = 0,
+            KeyCode::Enter => {
+                let text = self.input.trim().to_string();
+                self.input.clear();
+                if !text.is_empty() {
+                    self.scroll[Pane::Conversation.index()] = 0;
+                    return TuiAction::Submit(text);
+                }
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) if !ctrl => self.input.push(c),
+            _ => {}
+        }
+        TuiAction::None
+    }
+
+    fn draw(&self, frame: &mut Frame, messages: &Conversation) {
+        let [main, usage, input, help] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [conversation, side] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(main);
+        let [tools, plan] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(side);
+
+        self.draw_pane(
+            frame,
+            conversation,
+            Pane::Conversation,
+            "Conversation",
+            conversation_lines(messages),
+        );
+        self.draw_pane(frame, tools, Pane::Tools, "Tools", self.tool_lines());
+        self.draw_pane(frame, plan, Pane::Plan, "Plan", self.plan_lines());
+
+        frame.render_widget(Paragraph::new(self.usage_line()), usage);
+
+        let title = if self.running {
+            "Message (queued until goose is ready for it)"
+        } else {
+            "Message"
+        };
+        frame.render_widget(
+            Paragraph::new(format!("> {}", self.input)).block(Block::bordered().title(title)),
+            input,
+        );
+        // Counted in usize, the input can be longer than a u16 before it is clamped
+        let cursor_x = (input.x as usize + 3 + self.input.chars().count())
+            .min(input.right().saturating_sub(2) as usize) as u16;
+        frame.set_cursor_position((cursor_x, input.y + 1));
+
+        let hint = if self.running {
+            "Tab: switch pane  ↑↓ PgUp PgDn Home End: scroll  Enter: queue  Esc: stop  Ctrl+C: quit"
+        } else {
+            "Tab: switch pane  ↑↓ PgUp PgDn Home End: scroll  Enter: send  Ctrl+C: quit"
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(hint, Style::new().fg(Color::DarkGray)),
+                Span::raw("  "),
+                Span::styled(self.status.as_str(), Style::new().fg(Color::Yellow)),
+            ])),
+            help,
+        );
+
+        if let Some(confirmation) = &self.confirmation {
+            draw_confirmation(frame, confirmation);
+        }
+    }
+
+    fn draw_pane(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        pane: Pane,
+        title: &str,
+        lines: Vec<Line<'static>>,
+    ) {
+        let border = if self.focus == pane {
+            Style::new().fg(Color::Cyan)
+        } else {
+            Style::new().fg(Color::DarkGray)
+        };
+        let visible = area.height.saturating_sub(2);
+        let height = wrapped_height(&lines, area.width.saturating_sub(2));
+        let bottom = height.saturating_sub(visible);
+        let top = bottom.saturating_sub(self.scroll[pane.index()]);
+        frame.render_widget(
+            Paragraph::new(Text::from(lines))
+                .block(Block::bordered().title(title).border_style(border))
+                .wrap(Wrap { trim: false })
+                .scroll((top, 0)),
+            area,
+        );
+    }
+
+    fn tool_lines(&self) -> Vec<Line<'static>> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                let (marker, color) = match tool.status {
+                    ToolStatus::Running => ("⋯", Color::Yellow),
+                    ToolStatus::Succeeded => ("✓", Color::Green),
+                    ToolStatus::Failed => ("✗", Color::Red),
+                    ToolStatus::Cancelled => ("-", Color::DarkGray),
+                };
+                let elapsed = tool.elapsed.unwrap_or_else(|| tool.started.elapsed());
+                Line::from(vec![
+                    Span::styled(marker, Style::new().fg(color)),
+                    Span::raw(format!(" {} ", tool.name)),
+                    Span::styled(
+                        format!("{:.1}s", elapsed.as_secs_f64()),
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                ])
+            })
+            .collect()
+    }
+
+    fn plan_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        if let Some(plan) = &self.plan {
+            let (completed, total) = plan.progress();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    plan.title.clone(),
+                    Style::new().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(" ({}/{})", completed, total),
+                    Style::new().fg(Color::DarkGray),
+                ),
+            ]));
+            for (index, step) in plan.steps.iter().enumerate() {
+                let color = match step.status {
+                    StepStatus::Pending => Color::DarkGray,
+                    StepStatus::InProgress => Color::Yellow,
+                    StepStatus::Completed => Color::Green,
+                    StepStatus::Failed => Color::Red,
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(step.status.marker(), Style::new().fg(color)),
+                    Span::raw(format!(" {}. {}", index + 1, step.description)),
+                ]));
+            }
+        }
+        if !self.todos.is_empty() {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(
+                "TODO",
+                Style::new().add_modifier(Modifier::BOLD),
+            ));
+            for item in &self.todos {
+                let color = match item.status {
+                    TodoStatus::Pending | TodoStatus::Cancelled => Color::DarkGray,
+                    TodoStatus::InProgress => Color::Yellow,
+                    TodoStatus::Completed => Color::Green,
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(item.status.marker(), Style::new().fg(color)),
+                    Span::raw(format!(" {}", item.title)),
+                ]));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::styled(
+                "No plan or TODO list yet",
+                Style::new().fg(Color::DarkGray),
+            ));
+        }
+        lines
+    }
+
+    fn usage_line(&self) -> Line<'static> {
+        let usage = &self.usage;
+        let percent = if usage.context_limit > 0 {
+            usage.context_tokens * 100 / usage.context_limit
+        } else {
+            0
+        };
+        let color = match percent {
+            0..=59 => Color::Green,
+            60..=84 => Color::Yellow,
+            _ => Color::Red,
+        };
+        let mut spans = vec![
+            Span::styled(
+                format!(" {} ", usage.model),
+                Style::new().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" context "),
+            Span::styled(
+                format!(
+                    "{}% ({}/{})",
+                    percent, usage.context_tokens, usage.context_limit
+                ),
+                Style::new().fg(color),
+            ),
+            Span::raw(format!(
+                "  tokens in {} out {}",
+                usage.input_tokens, usage.output_tokens
+            )),
+        ];
+        if let Some(cost) = usage.cost {
+            spans.push(Span::raw(format!("  ${:.4}", cost)));
+        }
+        if self.running {
+            spans.push(Span::styled("  working…", Style::new().fg(Color::Yellow)));
+        }
+        Line::from(spans)
+    }
+}
+
+fn conversation_lines(messages: &Conversation) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for message in messages.iter() {
+        let mut body = Vec::new();
+        for content in &message.content {
+            match content {
+                MessageContent::Text(text) => {
+                    body.extend(text.text.lines().map(|line| Line::raw(line.to_string())));
+                }
+                MessageContent::ToolRequest(request) => {
+                    if let Ok(tool_call) = &request.tool_call {
+                        body.push(Line::styled(
+                            format!("→ {}", tool_call.name),
+                            Style::new().fg(Color::Yellow),
+                        ));
+                    }
+                }
+                MessageContent::ToolResponse(response) => {
+                    let (text, color) = match &response.tool_result {
+                        Ok(_) => ("← done", Color::DarkGray),
+                        Err(_) => ("← failed", Color::Red),
+                    };
+                    body.push(Line::styled(text, Style::new().fg(color)));
+                }
+                MessageContent::Image(_) => body.push(Line::raw("[image]")),
+                _ => {}
+            }
+        }
+        if body.is_empty() {
+            continue;
+        }
+        let (who, color) = match message.role {
+            Role::User => ("you", Color::Cyan),
+            Role::Assistant => ("goose", Color::Green),
+        };
+        lines.push(Line::styled(
+            who,
+            Style::new().fg(color).add_modifier(Modifier::BOLD),
+        ));
+        lines.extend(body);
+        lines.push(Line::default());
+    }
+    lines
+}
+
+/// Rows the lines take once wrapped to the width
+fn wrapped_height(lines: &[Line], width: u16) -> u16 {
+    let width = width.max(1) as usize;
+    let rows: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(width).max(1))
+        .sum();
+    rows.min(u16::MAX as usize) as u16
+}
+
+fn draw_confirmation(frame: &mut Frame, confirmation: &ToolConfirmationRequest) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(80);
+    let height = area.height.saturating_sub(2).min(12);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let arguments = serde_json::to_string_pretty(&confirmation.arguments).unwrap_or_default();
+    let mut lines = vec![
+        Line::styled(
+            format!("goose would like to call {}", confirmation.tool_name),
+            Style::new().add_modifier(Modifier::BOLD),
+        ),
+        Line::default(),
+    ];
+    lines.extend(arguments.lines().map(|line| Line::raw(line.to_string())));
+    lines.push(Line::default());
+    lines.push(Line::styled(
+        "y: allow once  a: always allow  n: deny  c: cancel the turn",
+        Style::new().fg(Color::Yellow),
+    ));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(Text::from(lines))
+            .block(Block::bordered().title("Approve tool call"))
+            .wrap(Wrap { trim: false }),
+        popup,
+    );
+}
+
+impl Session {
+    /// Run the session in the full screen dashboard until the user quits
+    pub async fn tui(&mut self) -> Result<()> {
+        self.notify_webhooks(WebhookEvent::SessionStart, "tui")
+            .await;
+        let mut terminal = ratatui::init();
+        let result = self.run_tui(&mut terminal).await;
+        ratatui::restore();
+        self.agent.end_session(&self.session_config()).await;
+        self.agent.shutdown().await;
+        self.notify_webhooks(WebhookEvent::SessionEnd, "tui").await;
+        result
+    }
+
+    async fn run_tui(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let mut app = TuiApp::new();
+        let mut events = EventStream::new();
+        app.plan = self.agent.plan().await;
+        app.todos = self.agent.todos().await;
+        app.usage = self.tui_usage().await;
+
+        let shutdown = goose::shutdown::token();
+        loop {
+            terminal.draw(|frame| app.draw(frame, &self.messages))?;
+            let event = tokio::select! {
+                event = events.next() => event,
+                _ = shutdown.cancelled() => return Ok(()),
+            };
+            let Some(event) = event else {
+                return Ok(());
+            };
+            let text = match app.handle_event(event?) {
+                TuiAction::Quit => return Ok(()),
+                TuiAction::Submit(text) => text,
+                _ => continue,
+            };
+
+            let message = self.user_message(&text);
+            self.push_message(message);
+            if let Some(session_file) = &self.session_file {
+                session::persist_messages_with_schedule_id(
+                    session_file,
+                    &self.messages,
+                    self.agent.provider().await.ok(),
+                    self.scheduled_job_id.clone(),
+                    std::env::current_dir().ok(),
+                )
+                .await?;
+            }
+
+            let started = Instant::now();
+            let end = self.tui_turn(terminal, &mut app, &mut events).await?;
+            app.finish_turn();
+            // Messages for an interrupted turn are rendered as text, the redraw paints over them
+            match end {
+                TurnEnd::Finished => {
+                    app.status.clear();
+                    notify::turn_finished(started.elapsed());
+                }
+                TurnEnd::Interrupted => {
+                    // Messages queued for a turn that did not finish are dropped, as in the CLI
+                    let discarded = self.agent.take_queued_messages().await;
+                    self.handle_interrupted_messages(true).await?;
+                    terminal.clear()?;
+                    app.status = "Stopped".to_string();
+                    app.note_discarded(discarded.len());
+                }
+                TurnEnd::Failed(e) => {
+                    let discarded = self.agent.take_queued_messages().await;
+                    notify::error(&e.to_string());
+                    self.handle_interrupted_messages(false).await?;
+                    terminal.clear()?;
+                    app.status = format!("Error: {}", e);
+                    app.note_discarded(discarded.len());
+                }
+                TurnEnd::Quit => {
+                    self.agent.take_queued_messages().await;
+                    self.handle_interrupted_messages(true).await?;
+                    self.commit_turn();
+                    return Ok(());
+                }
+            }
+            self.commit_turn();
+            app.usage = self.tui_usage().await;
+        }
+    }
+
+    /// Run one reply, handling terminal input while it streams
+    async fn tui_turn(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        app: &mut TuiApp,
+        events: &mut EventStream,
+    ) -> Result<TurnEnd> {
+        let session_config = self.session_config();
+        let cancel_token = CancellationToken::new();
+        let mut stream = self
+            .agent
+            .reply(
+                self.messages.clone(),
+                session_config.clone(),
+                Some(cancel_token.clone()),
+            )
+            .await?;
+        let mut tick = tokio::time::interval(TICK);
+        let shutdown = goose::shutdown::token();
+        app.running = true;
+
+        loop {
+            terminal.draw(|frame| app.draw(frame, &self.messages))?;
+            tokio::select! {
+                event = stream.next() => match event {
+                    None => return Ok(TurnEnd::Finished),
+                    Some(Err(e)) => {
+                        cancel_token.cancel();
+                        return Ok(TurnEnd::Failed(e));
+                    }
+                    Some(Ok(AgentEvent::Message(message))) => match message.content.first() {
+                        Some(MessageContent::ToolConfirmationRequest(confirmation)) => {
+                            notify::approval_needed(&confirmation.tool_name);
+                            app.confirmation = Some(confirmation.clone());
+                        }
+                        Some(MessageContent::ContextLengthExceeded(_)) => {
+                            app.status = "Summarizing to fit the context…".to_string();
+                            terminal.draw(|frame| app.draw(frame, &self.messages))?;
+                            let (summarized, _, _) =
+                                self.agent.summarize_context(self.messages.messages()).await?;
+                            self.messages = summarized;
+                            app.status.clear();
+                            stream = self
+                                .agent
+                                .reply(self.messages.clone(), session_config.clone(), Some(cancel_token.clone()))
+                                .await?;
+                        }
+                        _ => {
+                            app.record(&message);
+                            self.messages.push(message);
+                            if let Some(session_file) = &self.session_file {
+                                session::persist_messages_with_schedule_id(
+                                    session_file,
+                                    &self.messages,
+                                    None,
+                                    self.scheduled_job_id.clone(),
+                                    std::env::current_dir().ok(),
+                                )
+                                .await?;
+                            }
+                        }
+                    },
+                    Some(Ok(AgentEvent::HistoryReplaced(messages))) => {
+                        self.messages = Conversation::new_unvalidated(messages);
+                        if let Some(session_file) = &self.session_file {
+                            session::persist_messages_with_schedule_id(
+                                session_file,
+                                &self.messages,
+                                None,
+                                self.scheduled_job_id.clone(),
+                                std::env::current_dir().ok(),
+                            )
+                            .await?;
+                        }
+                    }
+                    Some(Ok(AgentEvent::PlanUpdate(plan))) => app.plan = Some(plan),
+                    Some(Ok(AgentEvent::TodoUpdate(todos))) => app.todos = todos,
+                    Some(Ok(AgentEvent::ModelChange { model, mode })) => {
+                        app.usage.model = model;
+                        app.status = format!("Switched to {} mode", mode);
+                    }
+                    Some(Ok(AgentEvent::McpNotification(_))) => {}
+                },
+                event = events.next() => {
+                    let Some(event) = event else {
+                        cancel_token.cancel();
+                        return Ok(TurnEnd::Quit);
+                    };
+                    match app.handle_event(event?) {
+                        TuiAction::Quit => {
+                            cancel_token.cancel();
+                            return Ok(TurnEnd::Quit);
+                        }
+                        TuiAction::Interrupt | TuiAction::Confirm(_, Permission::Cancel) => {
+                            cancel_token.cancel();
+                            return Ok(TurnEnd::Interrupted);
+                        }
+                        TuiAction::Confirm(id, permission) => {
+                            self.agent
+                                .handle_confirmation(
+                                    id,
+                                    PermissionConfirmation {
+                                        principal_type: PrincipalType::Tool,
+                                        permission,
+                                    },
+                                )
+                                .await;
+                        }
+                        TuiAction::Submit(text) => {
+                            self.agent.queue_message(Message::user().with_text(&text)).await;
+                            app.status = "Message queued".to_string();
+                        }
+                        TuiAction::None => {}
+                    }
+                }
+                _ = shutdown.cancelled() => {
+                    cancel_token.cancel();
+                    return Ok(TurnEnd::Quit);
+                }
+                _ = tick.tick() => {}
+            }
+        }
+    }
+
+    async fn tui_usage(&self) -> Usage {
+        let config = Config::global();
+        let mut usage = Usage::default();
+        if let Ok(provider) = self.agent.provider().await {
+            let model_config = provider.get_model_config();
+            usage.model = model_config.model_name.clone();
+            usage.context_limit = model_config.context_limit();
+        }
+        if let Ok(metadata) = self.get_metadata() {
+            usage.context_tokens = metadata.total_tokens.unwrap_or(0).max(0) as usize;
+            usage.input_tokens = metadata.accumulated_input_tokens.unwrap_or(0).max(0) as usize;
+            usage.output_tokens = metadata.accumulated_output_tokens.unwrap_or(0).max(0) as usize;
+        }
+        if config
+            .get_param::<bool>("GOOSE_CLI_SHOW_COST")
+            .unwrap_or(false)
+        {
+            let provider_name = config
+                .get_param::<String>("GOOSE_PROVIDER")
+                .unwrap_or_default();
+            usage.cost = estimate_cost_usd(
+                &provider_name,
+                &usage.model,
+                usage.input_tokens,
+                usage.output_tokens,
+            )
+            .await;
+        }
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::tool::ToolCall;
+    use serde_json::json;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_record_tool_activity() {
+        let mut app = TuiApp::new();
+        app.record(&Message::assistant().with_tool_request(
+            "1",
+            Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+        ));
+        assert_eq!(app.tools[0].status, ToolStatus::Running);
+
+        app.record(&Message::user().with_tool_response("1", Ok(vec![])));
+        assert_eq!(app.tools[0].status, ToolStatus::Succeeded);
+        assert!(app.tools[0].elapsed.is_some());
+    }
+
+    #[test]
+    fn test_finish_turn_cancels_running_tools() {
+        let mut app = TuiApp::new();
+        app.running = true;
+        app.record(
+            &Message::assistant()
+                .with_tool_request("1", Ok(ToolCall::new("developer__shell", json!({})))),
+        );
+        app.finish_turn();
+        assert!(!app.running);
+        assert_eq!(app.tools[0].status, ToolStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_note_discarded() {
+        let mut app = TuiApp::new();
+        app.status = "Stopped".to_string();
+        app.note_discarded(0);
+        assert_eq!(app.status, "Stopped");
+        app.note_discarded(2);
+        assert_eq!(app.status, "Stopped, discarded 2 queued messages");
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut app = TuiApp::new();
+        app.handle_key(key(KeyCode::Char('h')));
+        app.handle_key(key(KeyCode::Char('i')));
+        assert_eq!(
+            app.handle_key(key(KeyCode::Enter)),
+            TuiAction::Submit("hi".to_string())
+        );
+        assert!(app.input.is_empty());
+
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.focus, Pane::Tools);
+        app.handle_key(key(KeyCode::PageUp));
+        assert_eq!(app.scroll[Pane::Tools.index()], SCROLL_PAGE);
+        app.handle_key(key(KeyCode::BackTab));
+        assert_eq!(app.focus, Pane::Conversation);
+
+        assert_eq!(
+            app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            TuiAction::Quit
+        );
+    }
+
+    #[test]
+    fn test_confirmation_keys() {
+        let mut app = TuiApp::new();
+        app.confirmation = Some(ToolConfirmationRequest {
+            id: "1".to_string(),
+            tool_name: "developer__shell".to_string(),
+            arguments: json!({}),
+            prompt: None,
+        });
+        assert_eq!(app.handle_key(key(KeyCode::Char('x'))), TuiAction::None);
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('a'))),
+            TuiAction::Confirm("1".to_string(), Permission::AlwaysAllow)
+        );
+        assert!(app.confirmation.is_none());
+    }
+
+    #[test]
+    fn test_wrapped_height() {
+        let lines = vec![Line::raw("a".repeat(25)), Line::default()];
+        assert_eq!(wrapped_height(&lines, 10), 4);
+    }
+}
@@ -34,3 +34,24 @@ pub fn shutdown_signal() -> Pin<Box<dyn Future<Output = ()> + Send>> {
             .expect("failed to install Ctrl+C handler");
     })
 }
+
+/// Resolves when goose is asked to stop by SIGTERM or SIGHUP. Ctrl+C is left to the session,
+/// where it interrupts the running turn
+#[cfg(unix)]
+pub async fn terminate_signal() {
+    use signal::unix::SignalKind;
+
+    let mut terminate =
+        signal::unix::signal(SignalKind::terminate()).expect("failed to install signal handler");
+    let mut hangup =
+        signal::unix::signal(SignalKind::hangup()).expect("failed to install signal handler");
+    tokio::select! {
+        _ = terminate.recv() => {},
+        _ = hangup.recv() => {},
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn terminate_signal() {
+    std::future::pending::<()>().await
+}
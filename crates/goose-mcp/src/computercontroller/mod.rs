@@ -4,6 +4,7 @@ use indoc::{formatdoc, indoc};
 use reqwest::{Client, Url};
 use serde_json::Value;
 use std::borrow::Cow;
+use std::io::Cursor;
 use std::{
     collections::HashMap, fs, future::Future, path::PathBuf, pin::Pin, sync::Arc, sync::Mutex,
 };
@@ -20,7 +21,7 @@ use mcp_server::router::CapabilitiesBuilder;
 use mcp_server::Router;
 use rmcp::model::{
     AnnotateAble, Content, ErrorCode, ErrorData, JsonRpcMessage, Prompt, RawResource, Resource,
-    Tool, ToolAnnotations,
+    Role, Tool, ToolAnnotations,
 };
 use rmcp::object;
 
@@ -29,7 +30,7 @@ mod pdf_tool;
 mod xlsx_tool;
 
 mod platform;
-use platform::{create_system_automation, SystemAutomation};
+use platform::{create_system_automation, SystemAutomation, WindowAction, OFFICE_APPS};
 
 /// An extension designed for non-developers to help them with common tasks like
 /// web scraping, data processing, and automation.
@@ -51,6 +52,10 @@ impl Default for ComputerControllerRouter {
 
 impl ComputerControllerRouter {
     pub fn new() -> Self {
+        let system_automation: Arc<Box<dyn SystemAutomation + Send + Sync>> =
+            Arc::new(create_system_automation());
+        let capabilities = system_automation.capabilities();
+
         let web_scrape_tool = Tool::new(
             "web_scrape",
             indoc! {r#"
@@ -152,6 +157,142 @@ impl ComputerControllerRouter {
             }),
         );
 
+        let window_control_tool = Tool::new(
+            "window_control",
+            indoc! {r#"
+                List and arrange the windows on the desktop.
+
+                Windows are picked by a case-insensitive part of their title, the first match is used.
+                Use "list" first to find the titles of the open windows.
+            "#},
+            object!({
+                "type": "object",
+                "required": ["action"],
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["list", "focus", "minimize", "maximize", "restore", "close", "move", "resize"],
+                        "description": "The operation to perform"
+                    },
+                    "title": {
+                        "type": "string",
+                        "description": "Part of the window title, required for every action but list"
+                    },
+                    "x": {"type": "integer", "description": "Left edge in pixels for move"},
+                    "y": {"type": "integer", "description": "Top edge in pixels for move"},
+                    "width": {"type": "integer", "description": "Width in pixels for resize"},
+                    "height": {"type": "integer", "description": "Height in pixels for resize"}
+                }
+            }),
+        );
+
+        let office_automation_tool = Tool::new(
+            "office_automation",
+            indoc! {r#"
+                Automate an installed Office application through its COM object model.
+
+                The script is PowerShell with `$app` already bound to the application object,
+                which is released when the script ends. For example, with excel:
+                    $wb = $app.Workbooks.Open('C:\data\report.xlsx'); $wb.Sheets(1).Range('A1').Value2; $wb.Close($false)
+                Applications are not made visible unless the script sets `$app.Visible = $true`.
+            "#},
+            object!({
+                "type": "object",
+                "required": ["app", "script"],
+                "properties": {
+                    "app": {
+                        "type": "string",
+                        "enum": OFFICE_APPS,
+                        "description": "The Office application to automate"
+                    },
+                    "script": {
+                        "type": "string",
+                        "description": "PowerShell using `$app`"
+                    }
+                }
+            }),
+        );
+
+        let screen_capture_tool = Tool::new(
+            "screen_capture",
+            indoc! {r#"
+                Take a screenshot of the whole screen, of a region of it, or of an area the user selects.
+                Use this to see what is on screen before and after controlling the computer.
+            "#},
+            object!({
+                "type": "object",
+                "properties": {
+                    "region": {
+                        "type": "object",
+                        "description": "Optional: crop to this rectangle, in pixels of the full screenshot",
+                        "required": ["x", "y", "width", "height"],
+                        "properties": {
+                            "x": {"type": "integer"},
+                            "y": {"type": "integer"},
+                            "width": {"type": "integer"},
+                            "height": {"type": "integer"}
+                        }
+                    },
+                    "interactive": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Let the user select the area to capture"
+                    }
+                }
+            }),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Capture the screen".to_string()),
+            read_only_hint: Some(true),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(false),
+            open_world_hint: Some(false),
+        });
+
+        let clipboard_get_tool = Tool::new(
+            "clipboard_get",
+            indoc! {r#"
+                Read the text on the system clipboard, to pick up data the user copied in another application.
+                The user is asked to allow this the first time it is used in a session.
+            "#},
+            object!({
+                "type": "object",
+                "properties": {}
+            }),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Read the clipboard".to_string()),
+            read_only_hint: Some(true),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        });
+
+        let clipboard_set_tool = Tool::new(
+            "clipboard_set",
+            indoc! {r#"
+                Replace the text on the system clipboard, so the user can paste it into another application.
+                The user is asked to allow this the first time it is used in a session.
+            "#},
+            object!({
+                "type": "object",
+                "required": ["text"],
+                "properties": {
+                    "text": {
+                        "type": "string",
+                        "description": "The text to put on the clipboard"
+                    }
+                }
+            }),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Write the clipboard".to_string()),
+            read_only_hint: Some(false),
+            destructive_hint: Some(true),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        });
+
         let quick_script_desc = match std::env::consts::OS {
             "windows" => indoc! {r#"
                 Create and run small PowerShell or Batch scripts for automation tasks.
@@ -436,9 +577,6 @@ impl ComputerControllerRouter {
             )
         });
 
-        let system_automation: Arc<Box<dyn SystemAutomation + Send + Sync>> =
-            Arc::new(create_system_automation());
-
         let os_specific_instructions = match std::env::consts::OS {
             "windows" => indoc! {r#"
             Here are some extra tools:
@@ -505,6 +643,35 @@ impl ComputerControllerRouter {
             "#},
         };
 
+        let mut capability_instructions = String::new();
+        if capabilities.window_control {
+            capability_instructions.push_str(indoc! {r#"
+            window_control
+              - List, focus, minimize, maximize, restore, move, resize and close windows by title
+              - Prefer this over scripting window changes with computer_control
+            "#});
+        }
+        if capabilities.screen_capture {
+            capability_instructions.push_str(indoc! {r#"
+            screen_capture
+              - Screenshot the screen, a region of it, or an area the user selects
+            "#});
+        }
+        if capabilities.clipboard {
+            capability_instructions.push_str(indoc! {r#"
+            clipboard_get / clipboard_set
+              - Read or replace the clipboard text to move data to and from other applications
+              - Prefer these over scripting the clipboard with computer_control
+            "#});
+        }
+        if capabilities.office_automation {
+            capability_instructions.push_str(indoc! {r#"
+            office_automation
+              - Drive Excel, Word, Outlook or PowerPoint through their COM object model
+              - Prefer this over UI automation for working with Office documents and mail
+            "#});
+        }
+
         let instructions = formatdoc! {r#"
             You are a helpful assistant to a power user who is not a professional developer, but you may use development tools to help assist them.
             The user may not know how to break down tasks, so you will need to ensure that you do, and run things in batches as needed.
@@ -521,7 +688,7 @@ impl ComputerControllerRouter {
             There is already a screenshot tool available you can use if needed to see what is on screen.
 
             {os_instructions}
-
+            {capability_instructions}
             web_scrape
               - Fetch content from html websites and APIs
               - Save as text, JSON, or binary files
@@ -536,19 +703,31 @@ impl ComputerControllerRouter {
             - File organization and cleanup
             "#,
             os_instructions = os_specific_instructions,
+            capability_instructions = capability_instructions,
             cache_dir = cache_dir.display()
         };
 
+        // Only advertise the tools the platform backend can actually run
+        let mut tools = vec![web_scrape_tool, quick_script_tool];
+        if capabilities.system_scripts {
+            tools.push(computer_control_tool);
+        }
+        if capabilities.window_control {
+            tools.push(window_control_tool);
+        }
+        if capabilities.office_automation {
+            tools.push(office_automation_tool);
+        }
+        if capabilities.screen_capture {
+            tools.push(screen_capture_tool);
+        }
+        if capabilities.clipboard {
+            tools.extend([clipboard_get_tool, clipboard_set_tool]);
+        }
+        tools.extend([cache_tool, pdf_tool, docx_tool, xlsx_tool]);
+
         Self {
-            tools: vec![
-                web_scrape_tool,
-                quick_script_tool,
-                computer_control_tool,
-                cache_tool,
-                pdf_tool,
-                docx_tool,
-                xlsx_tool,
-            ],
+            tools,
             cache_dir,
             active_resources: Arc::new(Mutex::new(HashMap::new())),
             http_client: Client::builder().user_agent("Goose/1.0").build().unwrap(),
@@ -889,6 +1068,164 @@ impl ComputerControllerRouter {
         Ok(vec![Content::text(result)])
     }
 
+    async fn window_control(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let action = parse_window_action(&params)?;
+        let system_automation = self.system_automation.clone();
+        let output = tokio::task::spawn_blocking(move || system_automation.window_control(&action))
+            .await
+            .map_err(|e| ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!("Window control task failed: {}", e)),
+                data: None,
+            })?
+            .map_err(|e| ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!("Failed to control window: {}", e)),
+                data: None,
+            })?;
+
+        Ok(vec![Content::text(output)])
+    }
+
+    async fn office_automation(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let app = require_str_parameter(&params, "app")?.to_string();
+        let script = require_str_parameter(&params, "script")?.to_string();
+        let system_automation = self.system_automation.clone();
+        let output =
+            tokio::task::spawn_blocking(move || system_automation.office_automation(&app, &script))
+                .await
+                .map_err(|e| ErrorData {
+                    code: ErrorCode::INTERNAL_ERROR,
+                    message: Cow::from(format!("Office automation task failed: {}", e)),
+                    data: None,
+                })?
+                .map_err(|e| ErrorData {
+                    code: ErrorCode::INTERNAL_ERROR,
+                    message: Cow::from(format!("Failed to run Office automation: {}", e)),
+                    data: None,
+                })?;
+
+        Ok(vec![Content::text(format!(
+            "Script completed successfully.\n\nOutput:\n{}",
+            output
+        ))])
+    }
+
+    async fn clipboard_get(&self) -> Result<Vec<Content>, ErrorData> {
+        let system_automation = self.system_automation.clone();
+        let text = tokio::task::spawn_blocking(move || system_automation.get_clipboard())
+            .await
+            .map_err(|e| ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!("Clipboard task failed: {}", e)),
+                data: None,
+            })?
+            .map_err(|e| ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!("Failed to read the clipboard: {}", e)),
+                data: None,
+            })?;
+
+        Ok(vec![Content::text(if text.is_empty() {
+            "The clipboard is empty".to_string()
+        } else {
+            text
+        })])
+    }
+
+    async fn clipboard_set(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let text = require_str_parameter(&params, "text")?.to_string();
+        let length = text.chars().count();
+        let system_automation = self.system_automation.clone();
+        tokio::task::spawn_blocking(move || system_automation.set_clipboard(&text))
+            .await
+            .map_err(|e| ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!("Clipboard task failed: {}", e)),
+                data: None,
+            })?
+            .map_err(|e| ErrorData {
+                code: ErrorCode::INTERNAL_ERROR,
+                message: Cow::from(format!("Failed to write the clipboard: {}", e)),
+                data: None,
+            })?;
+
+        Ok(vec![Content::text(format!(
+            "Copied {} characters to the clipboard",
+            length
+        ))])
+    }
+
+    async fn screen_capture(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let interactive = params
+            .get("interactive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let region = params.get("region");
+
+        let system_automation = self.system_automation.clone();
+        let png =
+            tokio::task::spawn_blocking(move || system_automation.capture_screen(interactive))
+                .await
+                .map_err(|e| ErrorData {
+                    code: ErrorCode::INTERNAL_ERROR,
+                    message: Cow::from(format!("Screen capture task failed: {}", e)),
+                    data: None,
+                })?
+                .map_err(|e| ErrorData {
+                    code: ErrorCode::INTERNAL_ERROR,
+                    message: Cow::from(format!("Failed to capture the screen: {}", e)),
+                    data: None,
+                })?;
+
+        let image_error = |e: xcap::image::ImageError| ErrorData {
+            code: ErrorCode::INTERNAL_ERROR,
+            message: Cow::from(format!("Failed to process the screenshot: {}", e)),
+            data: None,
+        };
+        let mut image = xcap::image::load_from_memory(&png).map_err(image_error)?;
+        if let Some(region) = region {
+            let x = require_u64_parameter(region, "x")? as u32;
+            let y = require_u64_parameter(region, "y")? as u32;
+            let width = require_u64_parameter(region, "width")? as u32;
+            let height = require_u64_parameter(region, "height")? as u32;
+            if x >= image.width() || y >= image.height() {
+                return Err(ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!(
+                        "The region is outside the {}x{} screenshot",
+                        image.width(),
+                        image.height()
+                    ),
+                    None,
+                ));
+            }
+            image = image.crop_imm(x, y, width, height);
+        }
+
+        // Resize to a reasonable width while maintaining aspect ratio, like the developer
+        // extension's screen capture
+        let max_width = 768;
+        if image.width() > max_width {
+            image = image.resize(
+                max_width,
+                u32::MAX,
+                xcap::image::imageops::FilterType::Lanczos3,
+            );
+        }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), xcap::image::ImageFormat::Png)
+            .map_err(image_error)?;
+
+        Ok(vec![
+            Content::text("Screenshot captured").with_audience(vec![Role::Assistant]),
+            Content::image(base64::prelude::BASE64_STANDARD.encode(bytes), "image/png")
+                .with_priority(0.0),
+        ])
+    }
+
     async fn xlsx_tool(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
         let path = params
             .get("path")
@@ -1287,6 +1624,44 @@ impl ComputerControllerRouter {
     }
 }
 
+fn parse_window_action(params: &Value) -> Result<WindowAction, ErrorData> {
+    let title = || require_str_parameter(params, "title").map(str::to_string);
+    let integer = |name: &str| {
+        params.get(name).and_then(Value::as_i64).ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The parameter {name} is required"),
+                None,
+            )
+        })
+    };
+    Ok(match require_str_parameter(params, "action")? {
+        "list" => WindowAction::List,
+        "focus" => WindowAction::Focus { title: title()? },
+        "minimize" => WindowAction::Minimize { title: title()? },
+        "maximize" => WindowAction::Maximize { title: title()? },
+        "restore" => WindowAction::Restore { title: title()? },
+        "close" => WindowAction::Close { title: title()? },
+        "move" => WindowAction::Move {
+            title: title()?,
+            x: integer("x")?,
+            y: integer("y")?,
+        },
+        "resize" => WindowAction::Resize {
+            title: title()?,
+            width: require_u64_parameter(params, "width")?,
+            height: require_u64_parameter(params, "height")?,
+        },
+        action => {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Unknown window action '{}'", action),
+                None,
+            ))
+        }
+    })
+}
+
 impl Router for ComputerControllerRouter {
     fn name(&self) -> String {
         "ComputerControllerExtension".to_string()
@@ -1320,6 +1695,11 @@ impl Router for ComputerControllerRouter {
                 "web_scrape" => this.web_scrape(arguments).await,
                 "automation_script" => this.quick_script(arguments).await,
                 "computer_control" => this.computer_control(arguments).await,
+                "window_control" => this.window_control(arguments).await,
+                "office_automation" => this.office_automation(arguments).await,
+                "screen_capture" => this.screen_capture(arguments).await,
+                "clipboard_get" => this.clipboard_get().await,
+                "clipboard_set" => this.clipboard_set(arguments).await,
                 "cache" => this.cache(arguments).await,
                 "pdf_tool" => this.pdf_tool(arguments).await,
                 "docx_tool" => this.docx_tool(arguments).await,
@@ -1,4 +1,4 @@
-use super::SystemAutomation;
+use super::{Capabilities, SystemAutomation, WindowAction};
 use std::io::Result;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
@@ -8,6 +8,40 @@ use std::sync::Once;
 
 static INIT: Once = Once::new();
 
+/// Asks xdg-desktop-portal for a screenshot and prints the path of the file it saved, for
+/// Wayland compositors without grim. The interactive flag is the first argument
+const PORTAL_SCREENSHOT_SCRIPT: &str = r#"
+import os, sys, urllib.parse
+import dbus
+from dbus.mainloop.glib import DBusGMainLoop
+from gi.repository import GLib
+
+DBusGMainLoop(set_as_default=True)
+bus = dbus.SessionBus()
+portal = bus.get_object("org.freedesktop.portal.Desktop", "/org/freedesktop/portal/desktop")
+token = "goose%d" % os.getpid()
+sender = bus.get_unique_name()[1:].replace(".", "_")
+request = "/org/freedesktop/portal/desktop/request/%s/%s" % (sender, token)
+loop = GLib.MainLoop()
+result = {}
+
+def on_response(code, results):
+    result["code"] = code
+    result["uri"] = results.get("uri")
+    loop.quit()
+
+bus.add_signal_receiver(on_response, "Response", "org.freedesktop.portal.Request", path=request)
+portal.Screenshot(
+    "",
+    {"handle_token": token, "interactive": sys.argv[1] == "1"},
+    dbus_interface="org.freedesktop.portal.Screenshot",
+)
+loop.run()
+if result.get("code") != 0 or not result.get("uri"):
+    sys.exit("The screenshot was cancelled or denied")
+print(urllib.parse.unquote(urllib.parse.urlparse(str(result["uri"])).path))
+"#;
+
 #[derive(Debug)]
 pub enum DisplayServer {
     X11,
@@ -17,6 +51,7 @@ pub enum DisplayServer {
 
 pub struct LinuxAutomation {
     display_server: DisplayServer,
+    capabilities: Capabilities,
 }
 
 impl Default for LinuxAutomation {
@@ -27,8 +62,11 @@ impl Default for LinuxAutomation {
 
 impl LinuxAutomation {
     pub fn new() -> Self {
+        let display_server = Self::detect_display_server();
+        let capabilities = Self::detect_capabilities(&display_server);
         let automation = LinuxAutomation {
-            display_server: Self::detect_display_server(),
+            display_server,
+            capabilities,
         };
 
         INIT.call_once(|| {
@@ -56,6 +94,30 @@ impl LinuxAutomation {
         DisplayServer::Unknown
     }
 
+    fn detect_capabilities(display_server: &DisplayServer) -> Capabilities {
+        let clipboard = match display_server {
+            DisplayServer::X11 => has_command("xclip"),
+            DisplayServer::Wayland => has_command("wl-copy") && has_command("wl-paste"),
+            DisplayServer::Unknown => false,
+        };
+        let (window_control, screen_capture) = match display_server {
+            DisplayServer::X11 => (
+                has_command("wmctrl") && has_command("xdotool"),
+                has_command("maim") || has_command("import"),
+            ),
+            // Wayland has no common protocol for controlling other clients' windows
+            DisplayServer::Wayland => (false, has_command("grim") || has_portal_bindings()),
+            DisplayServer::Unknown => (false, false),
+        };
+        Capabilities {
+            system_scripts: true,
+            window_control,
+            screen_capture,
+            clipboard,
+            ..Default::default()
+        }
+    }
+
     fn initialize(&self) -> Result<()> {
         // Check for common dependencies first
         self.check_common_dependencies()?;
@@ -170,6 +232,47 @@ impl LinuxAutomation {
         }
     }
 
+    fn capture_wayland(&self, interactive: bool) -> Result<Vec<u8>> {
+        if has_command("grim") {
+            let mut command = Command::new("grim");
+            if interactive {
+                let selection = checked_output(&mut Command::new("slurp"))?;
+                command
+                    .arg("-g")
+                    .arg(String::from_utf8_lossy(&selection).trim());
+            }
+            return checked_output(command.arg("-t").arg("png").arg("-"));
+        }
+
+        // The portal saves the screenshot itself, usually to the pictures folder
+        let mut command = Command::new("python3");
+        command
+            .arg("-c")
+            .arg(PORTAL_SCREENSHOT_SCRIPT)
+            .arg(if interactive { "1" } else { "0" });
+        let path = String::from_utf8_lossy(&checked_output(&mut command)?)
+            .trim()
+            .to_string();
+        let bytes = std::fs::read(&path)?;
+        let _ = std::fs::remove_file(&path);
+        Ok(bytes)
+    }
+
+    fn capture_x11(&self, interactive: bool) -> Result<Vec<u8>> {
+        if has_command("maim") {
+            let mut command = Command::new("maim");
+            if interactive {
+                command.arg("-s");
+            }
+            return checked_output(command.arg("--format").arg("png"));
+        }
+        let mut command = Command::new("import");
+        if !interactive {
+            command.arg("-window").arg("root");
+        }
+        checked_output(command.arg("png:-"))
+    }
+
     fn create_python_script(&self, commands: &[&str]) -> String {
         let mut script = String::from(
             r#"#!/usr/bin/env python3
@@ -249,4 +352,169 @@ impl SystemAutomation for LinuxAutomation {
     fn get_temp_path(&self) -> PathBuf {
         std::env::temp_dir()
     }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    fn window_control(&self, action: &WindowAction) -> Result<String> {
+        if !self.capabilities.window_control {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Window control needs X11 with wmctrl and xdotool",
+            ));
+        }
+        if let WindowAction::List = action {
+            let listing = checked_output(Command::new("wmctrl").arg("-l"))?;
+            return Ok(wmctrl_titles(&String::from_utf8_lossy(&listing)).join("\n"));
+        }
+        for args in window_commands(action) {
+            checked_output(Command::new(&args[0]).args(&args[1..]))?;
+        }
+        Ok(format!("Done: {}", action.title().unwrap_or_default()))
+    }
+
+    fn get_clipboard(&self) -> Result<String> {
+        self.execute_input_command("get clipboard")
+    }
+
+    fn set_clipboard(&self, text: &str) -> Result<()> {
+        self.execute_input_command(&format!("set clipboard {}", text))?;
+        Ok(())
+    }
+
+    fn capture_screen(&self, interactive: bool) -> Result<Vec<u8>> {
+        match self.display_server {
+            DisplayServer::Wayland => self.capture_wayland(interactive),
+            DisplayServer::X11 => self.capture_x11(interactive),
+            DisplayServer::Unknown => Err(std::io::Error::other("Unknown display server")),
+        }
+    }
+}
+
+fn has_command(name: &str) -> bool {
+    Command::new("which")
+        .arg(name)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Whether python can talk to xdg-desktop-portal
+fn has_portal_bindings() -> bool {
+    Command::new("python3")
+        .arg("-c")
+        .arg("import dbus, gi")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Stdout of a command, or its stderr as the error when it fails
+fn checked_output(command: &mut Command) -> Result<Vec<u8>> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(std::io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// The titles in `wmctrl -l` output, whose lines are id, desktop, host and then the title
+fn wmctrl_titles(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter_map(|line| {
+            let mut rest = line.trim_start();
+            for _ in 0..3 {
+                let end = rest.find(char::is_whitespace)?;
+                rest = rest[end..].trim_start();
+            }
+            (!rest.is_empty()).then(|| rest.to_string())
+        })
+        .collect()
+}
+
+/// Escape a title for the extended regular expressions xdotool searches with
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.^$|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The wmctrl and xdotool invocations for an action. wmctrl matches the window by a
+/// case-insensitive part of its title, like the other platforms
+fn window_commands(action: &WindowAction) -> Vec<Vec<String>> {
+    let title = action.title().unwrap_or_default().to_string();
+    let wmctrl = |args: &[&str]| -> Vec<String> {
+        let mut command = vec!["wmctrl".to_string(), "-r".to_string(), title.clone()];
+        command.extend(args.iter().map(|arg| arg.to_string()));
+        command
+    };
+    let activate = vec!["wmctrl".to_string(), "-a".to_string(), title.clone()];
+    match action {
+        WindowAction::List => vec![],
+        WindowAction::Focus { .. } => vec![activate],
+        WindowAction::Minimize { .. } => vec![vec![
+            "xdotool".to_string(),
+            "search".to_string(),
+            "--limit".to_string(),
+            "1".to_string(),
+            "--name".to_string(),
+            escape_regex(&title),
+            "windowminimize".to_string(),
+        ]],
+        WindowAction::Maximize { .. } => {
+            vec![wmctrl(&["-b", "add,maximized_vert,maximized_horz"])]
+        }
+        // Activating also brings a minimized window back
+        WindowAction::Restore { .. } => vec![
+            wmctrl(&["-b", "remove,maximized_vert,maximized_horz"]),
+            activate,
+        ],
+        WindowAction::Close { .. } => {
+            vec![vec!["wmctrl".to_string(), "-c".to_string(), title.clone()]]
+        }
+        WindowAction::Move { x, y, .. } => vec![wmctrl(&["-e", &format!("0,{},{},-1,-1", x, y)])],
+        WindowAction::Resize { width, height, .. } => {
+            vec![wmctrl(&["-e", &format!("0,-1,-1,{},{}", width, height)])]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wmctrl_titles() {
+        let listing =
+            "0x03a00003  0 host Terminal - ~/src\n0x04000001 -1 host  \n0x05000002  1 host Notes\n";
+        assert_eq!(wmctrl_titles(listing), vec!["Terminal - ~/src", "Notes"]);
+    }
+
+    #[test]
+    fn test_window_commands() {
+        let commands = window_commands(&WindowAction::Move {
+            title: "Notes".to_string(),
+            x: 10,
+            y: 20,
+        });
+        assert_eq!(
+            commands,
+            vec![vec!["wmctrl", "-r", "Notes", "-e", "0,10,20,-1,-1"]]
+        );
+
+        let commands = window_commands(&WindowAction::Minimize {
+            title: "a.b (1)".to_string(),
+        });
+        assert_eq!(commands[0][5], r"a\.b \(1\)");
+    }
 }
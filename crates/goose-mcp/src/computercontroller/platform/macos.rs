@@ -1,4 +1,4 @@
-use super::SystemAutomation;
+use super::{Capabilities, SystemAutomation};
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -18,4 +18,51 @@ impl SystemAutomation for MacOSAutomation {
     fn get_temp_path(&self) -> PathBuf {
         PathBuf::from("/tmp")
     }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            system_scripts: true,
+            screen_capture: true,
+            clipboard: true,
+            ..Default::default()
+        }
+    }
+
+    fn get_clipboard(&self) -> std::io::Result<String> {
+        let output = Command::new("pbpaste").output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn set_clipboard(&self, text: &str) -> std::io::Result<()> {
+        let mut child = Command::new("pbcopy")
+            .stdin(std::process::Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            use std::io::Write;
+            stdin.write_all(text.as_bytes())?;
+        }
+        child.wait()?;
+        Ok(())
+    }
+
+    fn capture_screen(&self, interactive: bool) -> std::io::Result<Vec<u8>> {
+        let path = self
+            .get_temp_path()
+            .join(format!("goose_screen_{}.png", std::process::id()));
+        let mut command = Command::new("screencapture");
+        command.arg("-x").arg("-t").arg("png");
+        if interactive {
+            command.arg("-i");
+        }
+        let output = command.arg(&path).output()?;
+        if !output.status.success() || !path.exists() {
+            return Err(std::io::Error::other(format!(
+                "screencapture failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        let bytes = std::fs::read(&path)?;
+        std::fs::remove_file(&path)?;
+        Ok(bytes)
+    }
 }
@@ -11,16 +11,117 @@ pub use self::macos::MacOSAutomation;
 #[cfg(target_os = "linux")]
 pub use self::linux::LinuxAutomation;
 
+/// What a platform backend can do, so the router only advertises tools that work
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Runs scripts in the platform's automation language with computer_control
+    pub system_scripts: bool,
+    /// Lists, focuses, arranges and closes windows with window_control
+    pub window_control: bool,
+    /// Drives Office applications through their automation objects with office_automation
+    pub office_automation: bool,
+    /// Takes screenshots with screen_capture
+    pub screen_capture: bool,
+    /// Reads and writes the clipboard text with clipboard_get and clipboard_set
+    pub clipboard: bool,
+}
+
+/// A window operation, windows are picked by a case-insensitive part of their title
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowAction {
+    List,
+    Focus {
+        title: String,
+    },
+    Minimize {
+        title: String,
+    },
+    Maximize {
+        title: String,
+    },
+    Restore {
+        title: String,
+    },
+    Close {
+        title: String,
+    },
+    Move {
+        title: String,
+        x: i64,
+        y: i64,
+    },
+    Resize {
+        title: String,
+        width: u64,
+        height: u64,
+    },
+}
+
+impl WindowAction {
+    /// The title to match, for every action but listing
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            WindowAction::List => None,
+            WindowAction::Focus { title }
+            | WindowAction::Minimize { title }
+            | WindowAction::Maximize { title }
+            | WindowAction::Restore { title }
+            | WindowAction::Close { title }
+            | WindowAction::Move { title, .. }
+            | WindowAction::Resize { title, .. } => Some(title),
+        }
+    }
+}
+
+/// Office applications that can be automated
+pub const OFFICE_APPS: &[&str] = &["excel", "word", "outlook", "powerpoint"];
+
+fn unsupported(what: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("{} is not supported on this platform", what),
+    )
+}
+
 pub trait SystemAutomation: Send + Sync {
     fn execute_system_script(&self, script: &str) -> std::io::Result<String>;
     fn get_shell_command(&self) -> (&'static str, &'static str); // (shell, arg)
     fn get_temp_path(&self) -> std::path::PathBuf;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            system_scripts: true,
+            ..Default::default()
+        }
+    }
+
+    fn window_control(&self, _action: &WindowAction) -> std::io::Result<String> {
+        Err(unsupported("Window control"))
+    }
+
+    /// Run a script with `$app` bound to the automation object of an Office application
+    fn office_automation(&self, _app: &str, _script: &str) -> std::io::Result<String> {
+        Err(unsupported("Office automation"))
+    }
+
+    /// A PNG of the whole screen, or of an area the user selects when interactive
+    fn capture_screen(&self, _interactive: bool) -> std::io::Result<Vec<u8>> {
+        Err(unsupported("Screen capture"))
+    }
+
+    fn get_clipboard(&self) -> std::io::Result<String> {
+        Err(unsupported("Clipboard access"))
+    }
+
+    fn set_clipboard(&self, _text: &str) -> std::io::Result<()> {
+        Err(unsupported("Clipboard access"))
+    }
 }
 
 pub fn create_system_automation() -> Box<dyn SystemAutomation + Send + Sync> {
     #[cfg(target_os = "windows")]
     {
-        Box::new(WindowsAutomation)
+        Box::new(WindowsAutomation::new())
     }
     #[cfg(target_os = "macos")]
     {
@@ -1,8 +1,43 @@
-use super::SystemAutomation;
+use super::{Capabilities, SystemAutomation, WindowAction, OFFICE_APPS};
 use std::path::PathBuf;
 use std::process::Command;
 
-pub struct WindowsAutomation;
+pub struct WindowsAutomation {
+    capabilities: Capabilities,
+}
+
+impl Default for WindowsAutomation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowsAutomation {
+    pub fn new() -> Self {
+        let office_automation = OFFICE_APPS
+            .iter()
+            .any(|app| Self::is_com_registered(com_prog_id(app).unwrap_or_default()));
+        WindowsAutomation {
+            capabilities: Capabilities {
+                system_scripts: true,
+                window_control: true,
+                office_automation,
+                clipboard: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Whether a COM class is registered, which it is when the application is installed
+    fn is_com_registered(prog_id: &str) -> bool {
+        Command::new("reg")
+            .arg("query")
+            .arg(format!(r"HKCR\{}", prog_id))
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+}
 
 impl SystemAutomation for WindowsAutomation {
     fn execute_system_script(&self, script: &str) -> std::io::Result<String> {
@@ -14,7 +49,13 @@ impl SystemAutomation for WindowsAutomation {
             .env("GOOSE_TERMINAL", "1")
             .output()?;
 
-        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(std::io::Error::other(
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ))
+        }
     }
 
     fn get_shell_command(&self) -> (&'static str, &'static str) {
@@ -26,4 +67,153 @@ impl SystemAutomation for WindowsAutomation {
             .map(PathBuf::from)
             .unwrap_or_else(|_| PathBuf::from(r"C:\Windows\Temp"))
     }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    fn window_control(&self, action: &WindowAction) -> std::io::Result<String> {
+        self.execute_system_script(&window_control_script(action))
+    }
+
+    fn get_clipboard(&self) -> std::io::Result<String> {
+        self.execute_system_script("Get-Clipboard -Raw")
+    }
+
+    fn set_clipboard(&self, text: &str) -> std::io::Result<()> {
+        self.execute_system_script(&format!("Set-Clipboard -Value {}", ps_quote(text)))?;
+        Ok(())
+    }
+
+    fn office_automation(&self, app: &str, script: &str) -> std::io::Result<String> {
+        let prog_id = com_prog_id(app).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Unknown Office application '{}'", app),
+            )
+        })?;
+        self.execute_system_script(&office_script(prog_id, script))
+    }
+}
+
+fn com_prog_id(app: &str) -> Option<&'static str> {
+    match app.to_lowercase().as_str() {
+        "excel" => Some("Excel.Application"),
+        "word" => Some("Word.Application"),
+        "outlook" => Some("Outlook.Application"),
+        "powerpoint" => Some("PowerPoint.Application"),
+        _ => None,
+    }
+}
+
+/// Quote a string for PowerShell, single quoted strings only need their quotes doubled
+fn ps_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Run the script with `$app` bound to the application's COM object, releasing it afterwards
+fn office_script(prog_id: &str, script: &str) -> String {
+    format!(
+        "$ErrorActionPreference = 'Stop'\n\
+         $app = New-Object -ComObject {}\n\
+         try {{\n{}\n}} finally {{\n\
+         [void][System.Runtime.InteropServices.Marshal]::ReleaseComObject($app)\n}}",
+        prog_id, script
+    )
+}
+
+/// A UI Automation script for the action, over the top level windows of the desktop
+fn window_control_script(action: &WindowAction) -> String {
+    const PRELUDE: &str = "$ErrorActionPreference = 'Stop'\n\
+        Add-Type -AssemblyName UIAutomationClient\n\
+        Add-Type -AssemblyName UIAutomationTypes\n\
+        $windows = [System.Windows.Automation.AutomationElement]::RootElement.FindAll(\
+        [System.Windows.Automation.TreeScope]::Children, \
+        [System.Windows.Automation.Condition]::TrueCondition)\n";
+
+    let body = match action {
+        WindowAction::List => {
+            return format!(
+                "{}$windows | Where-Object {{ $_.Current.Name }} | ForEach-Object {{ \
+                 \"{{0}}`t{{1}}\" -f $_.Current.ProcessId, $_.Current.Name }}",
+                PRELUDE
+            );
+        }
+        WindowAction::Focus { .. } => "$window.SetFocus()".to_string(),
+        WindowAction::Minimize { .. } => window_state("Minimized"),
+        WindowAction::Maximize { .. } => window_state("Maximized"),
+        WindowAction::Restore { .. } => window_state("Normal"),
+        WindowAction::Close { .. } => format!("{}.Close()", pattern("WindowPattern")),
+        WindowAction::Move { x, y, .. } => {
+            format!("{}.Move({}, {})", pattern("TransformPattern"), x, y)
+        }
+        WindowAction::Resize { width, height, .. } => {
+            format!(
+                "{}.Resize({}, {})",
+                pattern("TransformPattern"),
+                width,
+                height
+            )
+        }
+    };
+    let title = action.title().unwrap_or_default();
+    format!(
+        "{prelude}$title = {title}\n\
+         $window = $windows | Where-Object {{ $_.Current.Name.IndexOf($title, \
+         [System.StringComparison]::OrdinalIgnoreCase) -ge 0 }} | Select-Object -First 1\n\
+         if (-not $window) {{ throw \"No window with a title containing '$title'\" }}\n\
+         {body}\n\
+         \"Done: $($window.Current.Name)\"",
+        prelude = PRELUDE,
+        title = ps_quote(title),
+        body = body
+    )
+}
+
+fn pattern(name: &str) -> String {
+    format!(
+        "$window.GetCurrentPattern([System.Windows.Automation.{}]::Pattern)",
+        name
+    )
+}
+
+fn window_state(state: &str) -> String {
+    format!(
+        "{}.SetWindowVisualState([System.Windows.Automation.WindowVisualState]::{})",
+        pattern("WindowPattern"),
+        state
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ps_quote() {
+        assert_eq!(ps_quote("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn test_window_control_script() {
+        let script = window_control_script(&WindowAction::Resize {
+            title: "Bob's Notes".to_string(),
+            width: 800,
+            height: 600,
+        });
+        assert!(script.contains("$title = 'Bob''s Notes'"));
+        assert!(script.contains("TransformPattern]::Pattern).Resize(800, 600)"));
+
+        let script = window_control_script(&WindowAction::List);
+        assert!(!script.contains("$title"));
+    }
+
+    #[test]
+    fn test_office_script() {
+        assert_eq!(com_prog_id("Excel"), Some("Excel.Application"));
+        assert_eq!(com_prog_id("notepad"), None);
+        let script = office_script("Excel.Application", "$app.Workbooks.Count");
+        assert!(script.contains("New-Object -ComObject Excel.Application"));
+        assert!(script.contains("ReleaseComObject($app)"));
+    }
 }
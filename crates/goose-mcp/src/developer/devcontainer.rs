@@ -0,0 +1,313 @@
+//! Running the developer tools inside the dev container of the workspace, so the agent builds
+//! and tests in the same environment as the developer.
+//!
+//! Set `GOOSE_DEVCONTAINER` to the name of a running container, or to `auto` to use the
+//! container started for the `.devcontainer` of the workspace. Shell commands then run through
+//! `docker exec`, while file edits are made on the host and reach the container through its bind
+//! mounts. The session's environment variables (`goose session --env`) are passed on to them.
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+pub const DEVCONTAINER_ENV: &str = "GOOSE_DEVCONTAINER";
+/// The names of the session's environment variables, comma separated, which goose sets on the
+/// extension process next to the variables themselves
+pub const SESSION_ENV_KEYS_ENV: &str = "GOOSE_SESSION_ENV_KEYS";
+
+/// The label the devcontainer CLI and editors put on the container of a workspace folder
+const LOCAL_FOLDER_LABEL: &str = "devcontainer.local_folder";
+
+/// A directory of the host bind mounted into the container
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindMount {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DevContainer {
+    pub name: String,
+    pub mounts: Vec<BindMount>,
+}
+
+/// The session's environment variables to pass on to commands run in the container
+pub fn session_env_keys() -> Vec<String> {
+    std::env::var(SESSION_ENV_KEYS_ENV)
+        .map(|keys| {
+            keys.split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The folder holding the dev container configuration of `start`, looking in `start` and then in
+/// each of its parents
+pub fn find_workspace(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| {
+            dir.join(".devcontainer")
+                .join("devcontainer.json")
+                .is_file()
+                || dir.join(".devcontainer.json").is_file()
+        })
+        .map(Path::to_path_buf)
+}
+
+fn docker(args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("docker")
+        .args(args)
+        .output()
+        .context("Failed to run docker")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl DevContainer {
+    /// The container chosen with `GOOSE_DEVCONTAINER` for a session in `cwd`, None when the
+    /// option is off. A container that was asked for but can't be found or isn't running is an
+    /// error, so commands meant for the container never run on the host instead
+    pub fn from_env(cwd: &Path) -> Result<Option<Self>> {
+        let Ok(setting) = std::env::var(DEVCONTAINER_ENV) else {
+            return Ok(None);
+        };
+        let setting = setting.trim();
+        if setting.is_empty() || matches!(setting, "off" | "false" | "0") {
+            return Ok(None);
+        }
+        let found = if setting == "auto" {
+            Self::for_workspace(cwd)
+        } else {
+            Self::inspect(setting)
+        };
+        found
+            .map(Some)
+            .with_context(|| format!("{}={} can't be used", DEVCONTAINER_ENV, setting))
+    }
+
+    /// The running container of the dev container configuration around `cwd`
+    fn for_workspace(cwd: &Path) -> Result<Self> {
+        let workspace = find_workspace(cwd)
+            .ok_or_else(|| anyhow!("no .devcontainer found from {}", cwd.display()))?;
+        let filter = format!("label={}={}", LOCAL_FOLDER_LABEL, workspace.display());
+        let ids = docker(&["ps", "--quiet", "--filter", &filter])?;
+        let id = ids.lines().next().ok_or_else(|| {
+            anyhow!(
+                "the dev container of {} is not running, start it with `devcontainer up`",
+                workspace.display()
+            )
+        })?;
+        Self::inspect(id)
+    }
+
+    fn inspect(name: &str) -> Result<Self> {
+        Self::from_inspect(&docker(&["inspect", name])?)
+    }
+
+    /// Read a container from the output of `docker inspect`
+    pub fn from_inspect(json: &str) -> Result<Self> {
+        let details: Value = serde_json::from_str(json)?;
+        let container = details
+            .get(0)
+            .ok_or_else(|| anyhow!("docker inspect returned no container"))?;
+        let name = container
+            .get("Name")
+            .and_then(Value::as_str)
+            .map(|name| name.trim_start_matches('/').to_string())
+            .ok_or_else(|| anyhow!("docker inspect returned a container without a name"))?;
+        let running = container
+            .pointer("/State/Running")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if !running {
+            bail!("container {} is not running", name);
+        }
+        let mounts = container
+            .get("Mounts")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|mount| mount.get("Type").and_then(Value::as_str) == Some("bind"))
+            .filter_map(|mount| {
+                Some(BindMount {
+                    source: PathBuf::from(mount.get("Source")?.as_str()?),
+                    destination: PathBuf::from(mount.get("Destination")?.as_str()?),
+                })
+            })
+            .collect();
+        Ok(Self { name, mounts })
+    }
+
+    /// Where a path of the host is seen inside the container, through the most specific mount
+    pub fn to_container(&self, host: &Path) -> Option<PathBuf> {
+        self.mounts
+            .iter()
+            .filter_map(|mount| {
+                let rest = host.strip_prefix(&mount.source).ok()?;
+                Some((
+                    mount.source.components().count(),
+                    mount.destination.join(rest),
+                ))
+            })
+            .max_by_key(|(depth, _)| *depth)
+            .map(|(_, path)| path)
+    }
+
+    /// Where a path inside the container is on the host, through the most specific mount
+    pub fn to_host(&self, container: &Path) -> Option<PathBuf> {
+        self.mounts
+            .iter()
+            .filter_map(|mount| {
+                let rest = container.strip_prefix(&mount.destination).ok()?;
+                Some((
+                    mount.destination.components().count(),
+                    mount.source.join(rest),
+                ))
+            })
+            .max_by_key(|(depth, _)| *depth)
+            .map(|(_, path)| path)
+    }
+
+    /// The command running a shell command inside the container, in the container's view of
+    /// the host directory `cwd`. The variables named in `env_keys` are passed on by name, so
+    /// `docker exec` takes their values from its own environment and they stay out of its
+    /// arguments
+    pub fn shell_command(&self, cwd: &Path, command: &str, env_keys: &[String]) -> Result<Command> {
+        let workdir = self.to_container(cwd).ok_or_else(|| {
+            anyhow!(
+                "The working directory {} is not mounted in the container {}",
+                cwd.display(),
+                self.name
+            )
+        })?;
+        let mut shell = Command::new("docker");
+        shell.args(["exec", "--interactive", "--env", "GOOSE_TERMINAL=1"]);
+        for key in env_keys {
+            shell.arg("--env").arg(key);
+        }
+        shell
+            .arg("--workdir")
+            .arg(workdir)
+            .arg(&self.name)
+            .args(["sh", "-c", command]);
+        Ok(shell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSPECT: &str = r#"[{
+        "Name": "/vsc-app-1234",
+        "State": {"Running": true},
+        "Mounts": [
+            {"Type": "bind", "Source": "/home/dev/app", "Destination": "/workspaces/app"},
+            {"Type": "bind", "Source": "/home/dev/app/vendor", "Destination": "/opt/vendor"},
+            {"Type": "volume", "Source": "/var/lib/docker/volumes/x", "Destination": "/cache"}
+        ]
+    }]"#;
+
+    #[test]
+    fn test_from_inspect() {
+        let container = DevContainer::from_inspect(INSPECT).unwrap();
+        assert_eq!(container.name, "vsc-app-1234");
+        assert_eq!(container.mounts.len(), 2);
+
+        let stopped = INSPECT.replace("\"Running\": true", "\"Running\": false");
+        assert!(DevContainer::from_inspect(&stopped).is_err());
+    }
+
+    #[test]
+    fn test_path_mapping() {
+        let container = DevContainer::from_inspect(INSPECT).unwrap();
+        assert_eq!(
+            container.to_container(Path::new("/home/dev/app/src/main.rs")),
+            Some(PathBuf::from("/workspaces/app/src/main.rs"))
+        );
+        assert_eq!(
+            container.to_container(Path::new("/home/dev/app/vendor/lib")),
+            Some(PathBuf::from("/opt/vendor/lib"))
+        );
+        assert_eq!(container.to_container(Path::new("/etc/hosts")), None);
+        assert_eq!(
+            container.to_host(Path::new("/workspaces/app/Cargo.toml")),
+            Some(PathBuf::from("/home/dev/app/Cargo.toml"))
+        );
+        assert_eq!(container.to_host(Path::new("/cache/data")), None);
+    }
+
+    #[test]
+    fn test_shell_command_passes_session_env_by_name() {
+        let container = DevContainer::from_inspect(INSPECT).unwrap();
+        let env_keys = temp_env::with_var(SESSION_ENV_KEYS_ENV, Some("API_TOKEN, STAGE"), || {
+            session_env_keys()
+        });
+        assert_eq!(env_keys, vec!["API_TOKEN".to_string(), "STAGE".to_string()]);
+
+        let command = container
+            .shell_command(Path::new("/home/dev/app/src"), "make test", &env_keys)
+            .unwrap();
+        let args: Vec<_> = command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "--interactive",
+                "--env",
+                "GOOSE_TERMINAL=1",
+                "--env",
+                "API_TOKEN",
+                "--env",
+                "STAGE",
+                "--workdir",
+                "/workspaces/app/src",
+                "vsc-app-1234",
+                "sh",
+                "-c",
+                "make test",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_env() {
+        let dir = tempfile::tempdir().unwrap();
+        temp_env::with_var(DEVCONTAINER_ENV, Some("off"), || {
+            assert!(DevContainer::from_env(dir.path()).unwrap().is_none());
+        });
+        temp_env::with_var(DEVCONTAINER_ENV, None::<&str>, || {
+            assert!(DevContainer::from_env(dir.path()).unwrap().is_none());
+        });
+        // No .devcontainer to find, so the request fails rather than falling back to the host
+        temp_env::with_var(DEVCONTAINER_ENV, Some("auto"), || {
+            assert!(DevContainer::from_env(dir.path()).is_err());
+        });
+    }
+
+    #[test]
+    fn test_find_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("src").join("bin");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(find_workspace(&nested).is_none());
+
+        std::fs::create_dir(dir.path().join(".devcontainer")).unwrap();
+        std::fs::write(
+            dir.path().join(".devcontainer").join("devcontainer.json"),
+            "{}",
+        )
+        .unwrap();
+        assert_eq!(find_workspace(&nested), Some(dir.path().to_path_buf()));
+    }
+}
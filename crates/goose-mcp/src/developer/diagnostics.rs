@@ -0,0 +1,376 @@
+//! Running the build and linter of a project and reducing their output to the problems found,
+//! so the agent gets the file, line and message of each without reading the whole log.
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+
+/// Problems listed in a report, the rest are counted
+const MAX_LISTED: usize = 50;
+/// Lines of output shown when a failed run has no problems we recognise
+const TAIL_LINES: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    Build,
+    Lint,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::Build => write!(f, "build"),
+            Check::Lint => write!(f, "lint"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn parse(severity: &str) -> Self {
+        match severity.to_lowercase().as_str() {
+            "warning" | "warn" => Severity::Warning,
+            "note" | "info" | "hint" | "help" => Severity::Note,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}:{}", self.severity, self.file, self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+/// `file:line:col: error[code]: message`, as printed by gcc, clang, `cargo --message-format=short`,
+/// go, ruff and `eslint --format unix`
+static LOCATED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<file>[^\s:()][^:()]*?):(?P<line>\d+)(?::(?P<col>\d+))?:\s*(?:(?P<severity>fatal error|error|warning|warn|note|info|hint)(?:\[(?P<code>[^\]]+)\])?:\s*)?(?P<message>.+)$",
+    )
+    .unwrap()
+});
+
+/// `file(line,col): error TS1234: message`, as printed by tsc
+static PARENTHESIZED: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<file>[^\s(]+)\((?P<line>\d+),(?P<col>\d+)\):\s*(?P<severity>error|warning)\s+(?P<code>\w+):\s*(?P<message>.+)$",
+    )
+    .unwrap()
+});
+
+/// The first line of a problem in the long format of rustc, its location follows on a `-->` line
+static RUSTC_HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<severity>error|warning)(?:\[(?P<code>[^\]]+)\])?:\s*(?P<message>.+)$")
+        .unwrap()
+});
+
+static RUSTC_LOCATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*-->\s*(?P<file>.+?):(?P<line>\d+):(?P<col>\d+)$").unwrap());
+
+/// The severity eslint puts at the end of its unix format, like `[Error/no-undef]`
+static ESLINT_SEVERITY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\s*\[(?P<severity>Error|Warning)/(?P<rule>[^\]]+)\]$").unwrap());
+
+fn with_code(message: &str, code: Option<&str>) -> String {
+    match code {
+        Some(code) => format!("{} [{}]", message.trim(), code),
+        None => message.trim().to_string(),
+    }
+}
+
+fn located(line: &str) -> Option<Diagnostic> {
+    let captures = LOCATED.captures(line)?;
+    let file = captures["file"].trim();
+    // Leave out lines like timestamps, a file has an extension or a directory
+    if !file.contains('.') && !file.contains('/') && !file.contains('\\') {
+        return None;
+    }
+    let message = &captures["message"];
+    let (severity, message) = match ESLINT_SEVERITY.captures(message) {
+        Some(eslint) => (
+            Severity::parse(&eslint["severity"]),
+            with_code(
+                &message[..eslint.get(0).unwrap().start()],
+                Some(&eslint["rule"]),
+            ),
+        ),
+        None => (
+            captures
+                .name("severity")
+                .map(|severity| Severity::parse(severity.as_str()))
+                .unwrap_or(Severity::Error),
+            with_code(message, captures.name("code").map(|code| code.as_str())),
+        ),
+    };
+    Some(Diagnostic {
+        file: file.to_string(),
+        line: captures["line"].parse().ok()?,
+        column: captures
+            .name("col")
+            .and_then(|col| col.as_str().parse().ok()),
+        severity,
+        message,
+    })
+}
+
+fn parenthesized(line: &str) -> Option<Diagnostic> {
+    let captures = PARENTHESIZED.captures(line)?;
+    Some(Diagnostic {
+        file: captures["file"].to_string(),
+        line: captures["line"].parse().ok()?,
+        column: captures["col"].parse().ok(),
+        severity: Severity::parse(&captures["severity"]),
+        message: with_code(&captures["message"], Some(&captures["code"])),
+    })
+}
+
+/// The problems in the output of a build or linter, errors first, each one once
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut header: Option<(Severity, String)> = None;
+    for line in output.lines() {
+        let line = line.trim_end();
+        let found = if let Some(captures) = RUSTC_LOCATION.captures(line) {
+            header.take().and_then(|(severity, message)| {
+                Some(Diagnostic {
+                    file: captures["file"].to_string(),
+                    line: captures["line"].parse().ok()?,
+                    column: captures["col"].parse().ok(),
+                    severity,
+                    message,
+                })
+            })
+        } else if let Some(captures) = RUSTC_HEADER.captures(line) {
+            header = Some((
+                Severity::parse(&captures["severity"]),
+                with_code(
+                    &captures["message"],
+                    captures.name("code").map(|code| code.as_str()),
+                ),
+            ));
+            None
+        } else {
+            located(line).or_else(|| parenthesized(line))
+        };
+        if let Some(diagnostic) = found {
+            if !diagnostics.contains(&diagnostic) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.severity);
+    diagnostics
+}
+
+fn has_npm_script(dir: &Path, script: &str) -> bool {
+    std::fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .is_some_and(|package| package.pointer(&format!("/scripts/{}", script)).is_some())
+}
+
+/// The usual command to build or lint the project in `dir`, chosen from the files it has
+pub fn detect_command(dir: &Path, check: Check) -> Option<String> {
+    let has = |file: &str| dir.join(file).is_file();
+    let command = match check {
+        Check::Build if has("Cargo.toml") => "cargo build --all-targets --message-format=short",
+        Check::Lint if has("Cargo.toml") => "cargo clippy --all-targets --message-format=short",
+        Check::Build if has("go.mod") => "go build ./...",
+        Check::Lint if has("go.mod") => "go vet ./...",
+        Check::Build if has_npm_script(dir, "build") => "npm run build --silent",
+        Check::Lint if has_npm_script(dir, "lint") => "npm run lint --silent",
+        Check::Build if has("tsconfig.json") => "npx tsc --noEmit --pretty false",
+        Check::Lint if has("package.json") => "npx eslint --format unix .",
+        Check::Lint if has("pyproject.toml") || has("setup.py") => {
+            "ruff check --output-format concise ."
+        }
+        Check::Build if has("Makefile") => "make",
+        _ => return None,
+    };
+    Some(command.to_string())
+}
+
+/// A compact report of a run: the outcome, the counts of problems and one line for each
+pub fn summarize(check: Check, command: &str, exit_code: Option<i32>, output: &str) -> String {
+    let diagnostics = parse_diagnostics(output);
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+    let outcome = match exit_code {
+        Some(0) => "succeeded".to_string(),
+        Some(code) => format!("failed with exit code {}", code),
+        None => "was stopped".to_string(),
+    };
+
+    let counted = |severity, word: &str| match count(severity) {
+        1 => format!("1 {}", word),
+        n => format!("{} {}s", n, word),
+    };
+
+    let mut report = format!(
+        "The {} `{}` {}: {}, {}",
+        check,
+        command,
+        outcome,
+        counted(Severity::Error, "error"),
+        counted(Severity::Warning, "warning")
+    );
+    for diagnostic in diagnostics.iter().take(MAX_LISTED) {
+        report.push('\n');
+        report.push_str(&diagnostic.to_string());
+    }
+    if diagnostics.len() > MAX_LISTED {
+        report.push_str(&format!(
+            "\n... and {} more",
+            diagnostics.len() - MAX_LISTED
+        ));
+    }
+    if diagnostics.is_empty() && exit_code != Some(0) {
+        let lines: Vec<&str> = output.lines().collect();
+        let tail = &lines[lines.len().saturating_sub(TAIL_LINES)..];
+        report.push_str("\nNo problems were recognised in the output, it ends with:\n");
+        report.push_str(&tail.join("\n"));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_short_and_long_formats() {
+        let output = "   Compiling app v0.1.0 (/work/app)\n\
+            src/main.rs:3:18: error[E0308]: mismatched types\n\
+            src/lib.rs:10:9: warning: unused variable: `x`\n\
+            warning: unused import: `std::fs`\n  \
+            --> src/util.rs:1:5\n   \
+            |\n\
+            error: could not compile `app` (bin \"app\") due to 1 previous error\n";
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    file: "src/main.rs".to_string(),
+                    line: 3,
+                    column: Some(18),
+                    severity: Severity::Error,
+                    message: "mismatched types [E0308]".to_string(),
+                },
+                Diagnostic {
+                    file: "src/lib.rs".to_string(),
+                    line: 10,
+                    column: Some(9),
+                    severity: Severity::Warning,
+                    message: "unused variable: `x`".to_string(),
+                },
+                Diagnostic {
+                    file: "src/util.rs".to_string(),
+                    line: 1,
+                    column: Some(5),
+                    severity: Severity::Warning,
+                    message: "unused import: `std::fs`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_other_tools() {
+        let output = "src/app.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+            /work/web/index.js:4:7: 'unused' is assigned a value but never used. [Warning/no-unused-vars]\n\
+            app/models.py:1:8: F401 [*] `os` imported but unused\n\
+            ./main.go:9:2: undefined: fmt.Printn\n\
+            12:30:45: starting build\n";
+        let diagnostics = parse_diagnostics(output);
+        let lines: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "error src/app.ts:12:5 Type 'string' is not assignable to type 'number'. [TS2322]",
+                "error app/models.py:1:8 F401 [*] `os` imported but unused",
+                "error ./main.go:9:2 undefined: fmt.Printn",
+                "warning /work/web/index.js:4:7 'unused' is assigned a value but never used. [no-unused-vars]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let report = summarize(
+            Check::Build,
+            "cargo build",
+            Some(101),
+            "src/main.rs:3:18: error[E0308]: mismatched types\n",
+        );
+        assert_eq!(
+            report,
+            "The build `cargo build` failed with exit code 101: 1 error, 0 warnings\n\
+             error src/main.rs:3:18 mismatched types [E0308]"
+        );
+
+        let report = summarize(Check::Build, "make", Some(2), "make: *** [all] Error 2\n");
+        assert!(report.contains("No problems were recognised"));
+        assert!(report.ends_with("make: *** [all] Error 2"));
+    }
+
+    #[test]
+    fn test_detect_command() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_command(dir.path(), Check::Build), None);
+
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build": "tsc"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            detect_command(dir.path(), Check::Build).as_deref(),
+            Some("npm run build --silent")
+        );
+        assert_eq!(
+            detect_command(dir.path(), Check::Lint).as_deref(),
+            Some("npx eslint --format unix .")
+        );
+
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+        assert_eq!(
+            detect_command(dir.path(), Check::Lint).as_deref(),
+            Some("cargo clippy --all-targets --message-format=short")
+        );
+    }
+}
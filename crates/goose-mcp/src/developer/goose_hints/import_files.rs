@@ -10,6 +10,12 @@ static FILE_REFERENCE_REGEX: Lazy<regex::Regex> = Lazy::new(|| {
         .expect("Invalid file reference regex pattern")
 });
 
+/// A line holding only `@include relative/path.md`, replaced by the content of that file
+static INCLUDE_DIRECTIVE_REGEX: Lazy<regex::Regex> = Lazy::new(|| {
+    regex::Regex::new(r"(?m)^[ \t]*@include[ \t]+(\S+)[ \t]*$")
+        .expect("Invalid include directive regex pattern")
+});
+
 const MAX_DEPTH: usize = 3;
 
 fn sanitize_reference_path(
@@ -133,6 +139,48 @@ fn process_file_reference(
     Some((reference_pattern, replacement))
 }
 
+/// Replace each `@include` line with the content of the file it names, itself expanded. Unlike
+/// `@file` references the content is inlined without markers, for splitting hints into parts
+fn expand_includes(
+    content: &str,
+    including_file_path: &Path,
+    import_boundary: &Path,
+    visited: &mut HashSet<PathBuf>,
+    depth: usize,
+    ignore_patterns: &Gitignore,
+) -> String {
+    let mut result = content.to_string();
+    for cap in INCLUDE_DIRECTIVE_REGEX.captures_iter(content) {
+        let reference = PathBuf::from(&cap[1]);
+        let Some(safe_path) = should_process_reference(
+            &reference,
+            including_file_path,
+            import_boundary,
+            visited,
+            ignore_patterns,
+        ) else {
+            tracing::warn!("Skipping include of {:?}", reference);
+            continue;
+        };
+        if depth >= MAX_DEPTH {
+            tracing::warn!("Maximum reference depth {} exceeded", MAX_DEPTH);
+            continue;
+        }
+
+        visited.insert(reference.clone());
+        let included = read_referenced_files(
+            &safe_path,
+            import_boundary,
+            visited,
+            depth + 1,
+            ignore_patterns,
+        );
+        visited.remove(&reference);
+        result = result.replacen(&cap[0], included.trim_end(), 1);
+    }
+    result
+}
+
 pub fn read_referenced_files(
     file_path: &Path,
     import_boundary: &Path,
@@ -150,6 +198,14 @@ pub fn read_referenced_files(
 
     let including_file_path = file_path.parent().unwrap_or(file_path);
 
+    let content = expand_includes(
+        &content,
+        including_file_path,
+        import_boundary,
+        visited,
+        depth,
+        ignore_patterns,
+    );
     let references = parse_file_references(&content);
     let mut result = content.to_string();
 
@@ -290,6 +346,40 @@ mod tests {
             assert!(expanded.contains("More content"));
         }
 
+        #[test]
+        fn test_include_directive() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let import_boundary = temp_dir.path();
+            std::fs::create_dir_all(import_boundary.join("docs")).unwrap();
+
+            create_file(
+                import_boundary,
+                "docs/style.md",
+                "Use four spaces\n@include rules.md\n",
+            );
+            create_file(import_boundary, "docs/rules.md", "Never force push");
+            let main_file = create_file(
+                import_boundary,
+                "main.md",
+                "Main content\n  @include docs/style.md\nMore content\n@include missing.md",
+            );
+
+            let ignore_patterns = create_ignore_patterns(import_boundary);
+            let mut visited = HashSet::new();
+            let expanded = read_referenced_files(
+                &main_file,
+                import_boundary,
+                &mut visited,
+                0,
+                &ignore_patterns,
+            );
+
+            assert_eq!(
+                expanded,
+                "Main content\nUse four spaces\nNever force push\nMore content\n@include missing.md"
+            );
+        }
+
         #[test]
         fn test_nested_reference() {
             let temp_dir = tempfile::tempdir().unwrap();
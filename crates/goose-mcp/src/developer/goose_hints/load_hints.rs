@@ -8,6 +8,58 @@ use std::{
 use crate::developer::goose_hints::import_files::read_referenced_files;
 
 pub const GOOSE_HINTS_FILENAME: &str = ".goosehints";
+/// Environment variable capping the estimated tokens of all hints together
+pub const HINTS_MAX_TOKENS_ENV: &str = "GOOSE_HINTS_MAX_TOKENS";
+const DEFAULT_HINTS_MAX_TOKENS: usize = 8_000;
+/// Rough size of a token, hints are capped before any tokenizer is available
+const CHARS_PER_TOKEN: usize = 4;
+
+/// The hint file names to look for, from CONTEXT_FILE_NAMES when set
+pub fn hints_filenames() -> Vec<String> {
+    std::env::var("CONTEXT_FILE_NAMES")
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_else(|| vec!["AGENTS.md".to_string(), GOOSE_HINTS_FILENAME.to_string()])
+}
+
+fn hints_max_tokens() -> usize {
+    std::env::var(HINTS_MAX_TOKENS_ENV)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_HINTS_MAX_TOKENS)
+}
+
+/// Cut hints to fit `max_tokens` between them. The most specific hints, from the directory
+/// deepest down, keep their room first, and what is left out is replaced by a note naming the
+/// file it came from
+fn fit_to_budget(hints: &mut [(PathBuf, String)], max_tokens: usize) {
+    let mut remaining = max_tokens * CHARS_PER_TOKEN;
+    for (path, content) in hints.iter_mut().rev() {
+        if content.len() <= remaining {
+            remaining -= content.len();
+            continue;
+        }
+
+        let mut kept = String::new();
+        let mut kept_lines = 0;
+        for line in content.lines() {
+            if kept.len() + line.len() + 1 > remaining {
+                break;
+            }
+            kept.push_str(line);
+            kept.push('\n');
+            kept_lines += 1;
+        }
+        let left_out = content.lines().count() - kept_lines;
+        remaining -= kept.len();
+        kept.push_str(&format!(
+            "[{} more lines of {} left out to stay within the hints budget]",
+            left_out,
+            path.display()
+        ));
+        *content = kept;
+    }
+}
 
 fn find_git_root(start_dir: &Path) -> Option<&Path> {
     let mut check_dir = start_dir;
@@ -86,7 +138,7 @@ pub fn load_hint_files(
                 ignore_patterns,
             );
             if !expanded_content.is_empty() {
-                global_hints_contents.push(expanded_content);
+                global_hints_contents.push((global_hints_path, expanded_content));
             }
         }
     }
@@ -108,12 +160,20 @@ pub fn load_hint_files(
                     ignore_patterns,
                 );
                 if !expanded_content.is_empty() {
-                    local_hints_contents.push(expanded_content);
+                    local_hints_contents.push((hints_path, expanded_content));
                 }
             }
         }
     }
 
+    // Global hints are the first to be cut, then those of the outermost directories
+    let mut all_hints = global_hints_contents;
+    let global_count = all_hints.len();
+    all_hints.extend(local_hints_contents);
+    fit_to_budget(&mut all_hints, hints_max_tokens());
+    let contents: Vec<String> = all_hints.into_iter().map(|(_, content)| content).collect();
+    let (global_hints_contents, local_hints_contents) = contents.split_at(global_count);
+
     let mut hints = String::new();
     if !global_hints_contents.is_empty() {
         hints.push_str("\n### Global Hints\nThe developer extension includes some global hints that apply to all projects & directories.\n");
@@ -186,6 +246,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fit_to_budget() {
+        let mut hints = vec![
+            (PathBuf::from("/repo/.goosehints"), "a\n".repeat(40)),
+            (PathBuf::from("/repo/app/.goosehints"), "b\n".repeat(10)),
+        ];
+        // 10 tokens is room for 40 characters, the deeper file takes 20 of them
+        fit_to_budget(&mut hints, 10);
+
+        assert_eq!(hints[1].1, "b\n".repeat(10));
+        assert!(hints[0].1.starts_with(&"a\n".repeat(10)));
+        assert!(hints[0].1.ends_with(
+            "[30 more lines of /repo/.goosehints left out to stay within the hints budget]"
+        ));
+    }
+
     #[test]
     #[serial]
     fn test_goosehints_when_present() {
@@ -1,3 +1,5 @@
+mod devcontainer;
+mod diagnostics;
 mod editor_models;
 mod goose_hints;
 mod lang;
@@ -14,7 +16,7 @@ use std::{
     fs::File,
     future::Future,
     io::{Cursor, Read},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     pin::Pin,
 };
 use tokio::{
@@ -40,13 +42,15 @@ use rmcp::model::{
 };
 use rmcp::object;
 
-use crate::developer::goose_hints::load_hints::{load_hint_files, GOOSE_HINTS_FILENAME};
+use crate::developer::goose_hints::load_hints::{hints_filenames, load_hint_files};
 
+use self::devcontainer::DevContainer;
+use self::diagnostics::{detect_command, summarize, Check};
 use self::editor_models::{create_editor_model, EditorModel};
 use self::shell::{expand_path, get_shell_config, is_absolute_path, normalize_line_endings};
 use indoc::indoc;
 use std::process::Stdio;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use xcap::{Monitor, Window};
 
 use ignore::gitignore::{Gitignore, GitignoreBuilder};
@@ -117,7 +121,13 @@ pub struct DeveloperRouter {
     prompts: Arc<HashMap<String, Prompt>>,
     instructions: String,
     file_history: Arc<Mutex<HashMap<PathBuf, Vec<String>>>>,
-    ignore_patterns: Arc<Gitignore>,
+    /// Rebuilt when the working directory changes, for the `.gooseignore` of the new one
+    ignore_patterns: Arc<RwLock<Gitignore>>,
+    /// The ignore patterns of the other roots of the workspace, each applied to paths inside it
+    root_ignore_patterns: Arc<Vec<(PathBuf, Gitignore)>>,
+    /// The container shell commands run in, when `GOOSE_DEVCONTAINER` is set. An error when
+    /// that container can't be used, and shell commands are then refused
+    devcontainer: Result<Option<Arc<DevContainer>>, String>,
     editor_model: Option<EditorModel>,
 }
 
@@ -127,6 +137,78 @@ impl Default for DeveloperRouter {
     }
 }
 
+/// The `.gooseignore` patterns for `cwd`, falling back to its `.gitignore` and then to
+/// patterns hiding common secret files
+fn build_ignore_patterns(cwd: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(cwd);
+    let mut has_ignore_file = false;
+
+    // Initialize ignore patterns
+    let global_ignore_path = choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir(".gooseignore"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
+        });
+
+    // Create the directory if it doesn't exist
+    let _ = std::fs::create_dir_all(global_ignore_path.parent().unwrap());
+
+    // Read global ignores if they exist
+    if global_ignore_path.is_file() {
+        let _ = builder.add(global_ignore_path);
+        has_ignore_file = true;
+    }
+
+    // Check for local ignores in current directory
+    let local_ignore_path = cwd.join(".gooseignore");
+
+    // Read local ignores if they exist
+    if local_ignore_path.is_file() {
+        let _ = builder.add(local_ignore_path);
+        has_ignore_file = true;
+    } else {
+        // If no .gooseignore exists, check for .gitignore as fallback
+        let gitignore_path = cwd.join(".gitignore");
+        if gitignore_path.is_file() {
+            tracing::debug!(
+                "No .gooseignore found, using .gitignore as fallback for ignore patterns"
+            );
+            let _ = builder.add(gitignore_path);
+            has_ignore_file = true;
+        }
+    }
+
+    // Only use default patterns if no .gooseignore files were found
+    // AND no .gitignore was used as fallback
+    if !has_ignore_file {
+        // Add some sensible defaults
+        let _ = builder.add_line(None, "**/.env");
+        let _ = builder.add_line(None, "**/.env.*");
+        let _ = builder.add_line(None, "**/secrets.*");
+    }
+
+    builder.build().expect("Failed to build ignore patterns")
+}
+
+/// The roots of the workspace given by the session, as a list of paths like `PATH`
+const WORKSPACE_ROOTS_ENV: &str = "GOOSE_WORKSPACE_ROOTS";
+
+/// The roots of the workspace other than `cwd`, for sessions on a monorepo spread over folders
+fn workspace_roots(cwd: &Path) -> Vec<PathBuf> {
+    std::env::var_os(WORKSPACE_ROOTS_ENV)
+        .map(|paths| {
+            std::env::split_paths(&paths)
+                .filter(|root| root != cwd && root.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The hints the developer extension adds to its instructions when started in `cwd`
+pub fn effective_hints(cwd: &Path) -> String {
+    load_hint_files(cwd, &hints_filenames(), &build_ignore_patterns(cwd))
+}
+
 impl DeveloperRouter {
     pub fn new() -> Self {
         // TODO consider rust native search tools, we could use
@@ -364,6 +446,83 @@ impl DeveloperRouter {
             open_world_hint: Some(false),
         });
 
+        let set_working_dir_tool = Tool::new(
+            "set_working_dir",
+            indoc! {r#"
+                Change the working directory of the session. Shell commands run there from then on,
+                relative paths are resolved against it and its .gooseignore applies.
+
+                Use this when the user asks to work in another directory, instead of prefixing every
+                shell command with `cd`.
+            "#},
+            object!({
+                "type": "object",
+                "required": ["path"],
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "The directory to switch to, absolute or relative to the current working directory"
+                    }
+                }
+            }),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Set working directory".to_string()),
+            read_only_hint: Some(false),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        });
+
+        let check_schema = |check: &str| {
+            object!({
+                "type": "object",
+                "properties": {
+                    "command": {
+                        "type": "string",
+                        "description": format!("The {} command to run, when the one detected for the project is not the right one", check)
+                    }
+                }
+            })
+        };
+        let build_tool = Tool::new(
+            "build",
+            indoc! {r#"
+                Build the project in the working directory and get back the problems found, one line each
+                with severity, file, line and message, instead of the whole log.
+
+                The command is picked from the project files (cargo, go, npm, tsc or make). Pass `command`
+                to run another one. Use this rather than the shell to check that code compiles.
+            "#},
+            check_schema("build"),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Build project".to_string()),
+            read_only_hint: Some(false),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        });
+        let lint_tool = Tool::new(
+            "lint",
+            indoc! {r#"
+                Run the linter of the project in the working directory and get back the problems found,
+                one line each with severity, file, line and message, instead of the whole log.
+
+                The command is picked from the project files (clippy, go vet, npm, eslint or ruff). Pass
+                `command` to run another one.
+            "#},
+            check_schema("lint"),
+        )
+        .annotate(ToolAnnotations {
+            title: Some("Lint project".to_string()),
+            // Runs any command it is given, and linters run build scripts
+            read_only_hint: Some(false),
+            destructive_hint: Some(false),
+            idempotent_hint: Some(true),
+            open_world_hint: Some(false),
+        });
+
         // Get base instructions and working directory
         let cwd = std::env::current_dir().expect("should have a current working dir");
         let os = std::env::consts::OS;
@@ -407,60 +566,64 @@ impl DeveloperRouter {
             },
         };
 
-        let mut builder = GitignoreBuilder::new(cwd.clone());
-        let mut has_ignore_file = false;
-
-        // Initialize ignore patterns
-        let global_ignore_path = choose_app_strategy(crate::APP_STRATEGY.clone())
-            .map(|strategy| strategy.in_config_dir(".gooseignore"))
-            .unwrap_or_else(|_| {
-                PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
-            });
-
-        // Create the directory if it doesn't exist
-        let _ = std::fs::create_dir_all(global_ignore_path.parent().unwrap());
-
-        // Read global ignores if they exist
-        if global_ignore_path.is_file() {
-            let _ = builder.add(global_ignore_path);
-            has_ignore_file = true;
-        }
-
-        // Check for local ignores in current directory
-        let local_ignore_path = cwd.join(".gooseignore");
-
-        // Read local ignores if they exist
-        if local_ignore_path.is_file() {
-            let _ = builder.add(local_ignore_path);
-            has_ignore_file = true;
+        let roots = workspace_roots(&cwd);
+        let base_instructions = if roots.is_empty() {
+            base_instructions
         } else {
-            // If no .gooseignore exists, check for .gitignore as fallback
-            let gitignore_path = cwd.join(".gitignore");
-            if gitignore_path.is_file() {
-                tracing::debug!(
-                    "No .gooseignore found, using .gitignore as fallback for ignore patterns"
-                );
-                let _ = builder.add(gitignore_path);
-                has_ignore_file = true;
+            let listed: Vec<String> = roots
+                .iter()
+                .map(|root| format!("- {}", root.display()))
+                .collect();
+            formatdoc! {r#"
+                {base_instructions}
+                The workspace has other roots besides the current directory:
+                {roots}
+                When locating files or code, search every root, e.g. `rg 'class Example' {cwd} {first}`.
+
+                "#,
+                base_instructions=base_instructions.trim_end(),
+                roots=listed.join("\n"),
+                cwd=cwd.display(),
+                first=roots[0].display(),
             }
-        }
+        };
 
-        // Only use default patterns if no .gooseignore files were found
-        // AND no .gitignore was used as fallback
-        if !has_ignore_file {
-            // Add some sensible defaults
-            let _ = builder.add_line(None, "**/.env");
-            let _ = builder.add_line(None, "**/.env.*");
-            let _ = builder.add_line(None, "**/secrets.*");
-        }
+        let ignore_patterns = build_ignore_patterns(&cwd);
+        let root_ignore_patterns = roots
+            .into_iter()
+            .map(|root| {
+                let patterns = build_ignore_patterns(&root);
+                (root, patterns)
+            })
+            .collect();
+        let hints = load_hint_files(&cwd, &hints_filenames(), &ignore_patterns);
 
-        let ignore_patterns = builder.build().expect("Failed to build ignore patterns");
+        let devcontainer = DevContainer::from_env(&cwd).map_err(|e| format!("{:#}", e));
+        let base_instructions = match &devcontainer {
+            Ok(Some(container)) => formatdoc! {r#"
+                {base_instructions}
+                Shell commands run inside the dev container `{name}` of this workspace, where the current directory is {workdir}.
+                Build and test there. File edits are made on the host and reach the container through its bind mounts,
+                and paths inside the container are accepted by the text editor.
+
+                "#,
+                base_instructions=base_instructions.trim_end(),
+                name=container.name,
+                workdir=container
+                    .to_container(&cwd)
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_else(|| "not mounted".to_string()),
+            },
+            Ok(None) => base_instructions,
+            Err(error) => formatdoc! {r#"
+                {base_instructions}
+                Shell commands were asked to run inside a dev container, which is unavailable: {error}.
+                They are refused rather than run on the host. Tell the user to start the container.
 
-        let hints_filenames: Vec<String> = std::env::var("CONTEXT_FILE_NAMES")
-            .ok()
-            .and_then(|s| serde_json::from_str(&s).ok())
-            .unwrap_or_else(|| vec!["AGENTS.md".to_string(), GOOSE_HINTS_FILENAME.to_string()]);
-        let hints = load_hint_files(&cwd, &hints_filenames, &ignore_patterns);
+                "#,
+                base_instructions=base_instructions.trim_end(),
+            },
+        };
 
         // Return base instructions directly when no hints are found
         let instructions = if hints.is_empty() {
@@ -476,18 +639,54 @@ impl DeveloperRouter {
                 list_windows_tool,
                 screen_capture_tool,
                 image_processor_tool,
+                set_working_dir_tool,
+                build_tool,
+                lint_tool,
             ],
             prompts: Arc::new(load_prompt_files()),
             instructions,
             file_history: Arc::new(Mutex::new(HashMap::new())),
-            ignore_patterns: Arc::new(ignore_patterns),
+            ignore_patterns: Arc::new(RwLock::new(ignore_patterns)),
+            root_ignore_patterns: Arc::new(root_ignore_patterns),
+            devcontainer: devcontainer.map(|container| container.map(Arc::new)),
             editor_model,
         }
     }
 
     // Helper method to check if a path should be ignored
     fn is_ignored(&self, path: &Path) -> bool {
-        self.ignore_patterns.matched(path, false).is_ignore()
+        if self
+            .ignore_patterns
+            .read()
+            .unwrap()
+            .matched(path, false)
+            .is_ignore()
+        {
+            return true;
+        }
+        if self.root_ignore_patterns.is_empty() {
+            return false;
+        }
+        // Shell arguments such as `../backend/.env` are relative to the working directory
+        let path = match std::env::current_dir() {
+            Ok(cwd) => cwd.join(path),
+            Err(_) => path.to_path_buf(),
+        };
+        let path = path
+            .components()
+            .fold(PathBuf::new(), |mut normal, component| {
+                match component {
+                    Component::ParentDir => {
+                        normal.pop();
+                    }
+                    Component::CurDir => {}
+                    component => normal.push(component),
+                }
+                normal
+            });
+        self.root_ignore_patterns.iter().any(|(root, patterns)| {
+            path.starts_with(root) && patterns.matched(&path, false).is_ignore()
+        })
     }
 
     // shell output can be large, this will help manage that
@@ -551,6 +750,14 @@ impl DeveloperRouter {
         let suggestion = cwd.join(path);
 
         match is_absolute_path(&expanded) {
+            // A path inside the dev container is edited through the mount it comes from
+            true if !path.exists() => Ok(self
+                .devcontainer
+                .as_ref()
+                .ok()
+                .and_then(Option::as_ref)
+                .and_then(|container| container.to_host(path))
+                .unwrap_or_else(|| path.to_path_buf())),
             true => Ok(path.to_path_buf()),
             false => Err(ErrorData::new(
                 ErrorCode::INVALID_PARAMS,
@@ -564,6 +771,132 @@ impl DeveloperRouter {
         }
     }
 
+    /// Move the process, and with it every later shell command, to another directory and apply
+    /// the ignore patterns found there
+    async fn set_working_dir(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let path_str = params.get("path").and_then(|v| v.as_str()).ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                "Missing 'path' parameter".to_string(),
+                None,
+            )
+        })?;
+        let cwd = std::env::current_dir().expect("should have a current working dir");
+        let dir = cwd.join(expand_path(path_str));
+        let dir = dir.canonicalize().map_err(|e| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Cannot switch to {}: {}", dir.display(), e),
+                None,
+            )
+        })?;
+        if !dir.is_dir() {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("{} is not a directory", dir.display()),
+                None,
+            ));
+        }
+        if self.is_ignored(&dir) {
+            return Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!(
+                    "The directory '{}' is restricted by .gooseignore",
+                    dir.display()
+                ),
+                None,
+            ));
+        }
+
+        std::env::set_current_dir(&dir).map_err(|e| {
+            ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Failed to switch to {}: {}", dir.display(), e),
+                None,
+            )
+        })?;
+        *self.ignore_patterns.write().unwrap() = build_ignore_patterns(&dir);
+
+        Ok(vec![Content::text(format!(
+            "The working directory is now {}",
+            dir.display()
+        ))])
+    }
+
+    /// The process running a shell command, on the host or in the dev container
+    fn shell_command(&self, command: &str) -> Result<Command, ErrorData> {
+        match &self.devcontainer {
+            Ok(Some(container)) => {
+                let cwd = std::env::current_dir().expect("should have a current working dir");
+                return container
+                    .shell_command(&cwd, command, &devcontainer::session_env_keys())
+                    .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None));
+            }
+            Ok(None) => {}
+            Err(error) => {
+                return Err(ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!(
+                        "Not running the command on the host, since {}. Start the dev container or unset {}",
+                        error,
+                        devcontainer::DEVCONTAINER_ENV
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        // Get platform-specific shell configuration
+        let shell_config = get_shell_config();
+
+        let mut shell = Command::new(&shell_config.executable);
+        shell
+            .env("GOOSE_TERMINAL", "1")
+            .args(&shell_config.args)
+            .arg(command);
+        Ok(shell)
+    }
+
+    /// Run the build or linter of the project and report the problems it found
+    async fn check(&self, check: Check, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let cwd = std::env::current_dir().expect("should have a current working dir");
+        let command = match params.get("command").and_then(|v| v.as_str()) {
+            Some(command) if !command.trim().is_empty() => command.to_string(),
+            _ => detect_command(&cwd, check).ok_or_else(|| {
+                ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!(
+                        "No {} command found for {}, pass one in the 'command' parameter",
+                        check,
+                        cwd.display()
+                    ),
+                    None,
+                )
+            })?,
+        };
+
+        let output = self
+            .shell_command(&command)?
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let combined = format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let report = summarize(check, &command, output.status.code(), &combined);
+
+        Ok(vec![
+            Content::text(report.clone()).with_audience(vec![Role::Assistant]),
+            Content::text(report)
+                .with_audience(vec![Role::User])
+                .with_priority(0.0),
+        ])
+    }
+
     // Shell command execution with platform-specific handling
     async fn bash(
         &self,
@@ -606,18 +939,13 @@ impl DeveloperRouter {
             }
         }
 
-        // Get platform-specific shell configuration
-        let shell_config = get_shell_config();
-
-        // Execute the command using platform-specific shell
-        let mut child = Command::new(&shell_config.executable)
+        // Execute the command using platform-specific shell, or in the dev container
+        let mut child = self
+            .shell_command(command)?
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .stdin(Stdio::null())
             .kill_on_drop(true)
-            .env("GOOSE_TERMINAL", "1")
-            .args(&shell_config.args)
-            .arg(command)
             .spawn()
             .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
 
@@ -1677,6 +2005,9 @@ impl Router for DeveloperRouter {
                 "list_windows" => this.list_windows(arguments).await,
                 "screen_capture" => this.screen_capture(arguments).await,
                 "image_processor" => this.image_processor(arguments).await,
+                "set_working_dir" => this.set_working_dir(arguments).await,
+                "build" => this.check(Check::Build, arguments).await,
+                "lint" => this.check(Check::Lint, arguments).await,
                 _ => Err(ErrorData::new(
                     ErrorCode::METHOD_NOT_FOUND,
                     format!("Tool {} not found", tool_name),
@@ -1738,6 +2069,8 @@ impl Clone for DeveloperRouter {
             instructions: self.instructions.clone(),
             file_history: Arc::clone(&self.file_history),
             ignore_patterns: Arc::clone(&self.ignore_patterns),
+            root_ignore_patterns: Arc::clone(&self.root_ignore_patterns),
+            devcontainer: self.devcontainer.clone(),
             editor_model: create_editor_model(),
         }
     }
@@ -2094,6 +2427,118 @@ mod tests {
         temp_dir.close().unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    #[cfg(not(windows))]
+    async fn test_build_reports_problems() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+        let router = DeveloperRouter::new();
+
+        let result = router
+            .call_tool(
+                "build",
+                json!({
+                    "command": "echo '   Compiling app'; echo 'src/main.rs:3:18: error[E0308]: mismatched types' >&2; exit 101"
+                }),
+                dummy_sender(),
+            )
+            .await
+            .unwrap();
+        let report = &result[0].as_text().unwrap().text;
+        assert!(report.contains("failed with exit code 101: 1 error, 0 warnings"));
+        assert!(report.contains("error src/main.rs:3:18 mismatched types [E0308]"));
+        // The header names the command, the output below it keeps only the problems
+        assert!(report
+            .lines()
+            .skip(1)
+            .all(|line| !line.contains("Compiling")));
+
+        // Nothing to detect a build command from
+        let missing = router.call_tool("build", json!({}), dummy_sender()).await;
+        assert!(missing.is_err());
+
+        // Both run whatever command they are given, so neither may be auto-approved as read-only
+        for tool in router
+            .list_tools()
+            .iter()
+            .filter(|tool| tool.name == "build" || tool.name == "lint")
+        {
+            let annotations = tool.annotations.as_ref().unwrap();
+            assert_eq!(annotations.read_only_hint, Some(false), "{}", tool.name);
+        }
+
+        temp_dir.close().unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_set_working_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join(".gooseignore"), "credentials.json\n").unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+
+        let router = DeveloperRouter::new();
+        let result = router
+            .call_tool(
+                "set_working_dir",
+                json!({"path": "project"}),
+                dummy_sender(),
+            )
+            .await
+            .unwrap();
+        let project = project.canonicalize().unwrap();
+        assert!(result[0]
+            .as_text()
+            .unwrap()
+            .text
+            .contains(&project.display().to_string()));
+        assert_eq!(std::env::current_dir().unwrap(), project);
+        assert!(router.is_ignored(&project.join("credentials.json")));
+
+        let missing = router
+            .call_tool(
+                "set_working_dir",
+                json!({"path": "no-such-dir"}),
+                dummy_sender(),
+            )
+            .await;
+        assert!(missing.is_err());
+        assert_eq!(std::env::current_dir().unwrap(), project);
+
+        temp_dir.close().unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_workspace_roots() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path().canonicalize().unwrap();
+        let frontend = workspace.join("frontend");
+        let backend = workspace.join("backend");
+        std::fs::create_dir(&frontend).unwrap();
+        std::fs::create_dir(&backend).unwrap();
+        std::fs::write(backend.join(".gooseignore"), "credentials.json\n").unwrap();
+        std::env::set_current_dir(&frontend).unwrap();
+        let roots = std::env::join_paths([&frontend, &backend]).unwrap();
+        std::env::set_var(WORKSPACE_ROOTS_ENV, &roots);
+
+        let router = DeveloperRouter::new();
+        std::env::remove_var(WORKSPACE_ROOTS_ENV);
+
+        let instructions = router.instructions();
+        assert!(instructions.contains("The workspace has other roots"));
+        assert!(instructions.contains(&format!("- {}", backend.display())));
+        assert!(router.is_ignored(&backend.join("credentials.json")));
+        assert!(router.is_ignored(Path::new("../backend/credentials.json")));
+        assert!(!router.is_ignored(&backend.join("main.rs")));
+        assert!(!router.is_ignored(&frontend.join("credentials.json")));
+
+        temp_dir.close().unwrap();
+    }
+
     // Test GooseIgnore pattern matching
     #[tokio::test]
     #[serial]
@@ -2112,7 +2557,9 @@ mod tests {
             prompts: Arc::new(HashMap::new()),
             instructions: String::new(),
             file_history: Arc::new(Mutex::new(HashMap::new())),
-            ignore_patterns: Arc::new(ignore_patterns),
+            ignore_patterns: Arc::new(RwLock::new(ignore_patterns)),
+            root_ignore_patterns: Arc::new(Vec::new()),
+            devcontainer: Ok(None),
             editor_model: None,
         };
 
@@ -2147,6 +2594,38 @@ mod tests {
         temp_dir.close().unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_shell_refused_when_devcontainer_unavailable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+        let marker = temp_dir.path().join("ran-on-host");
+
+        let router = DeveloperRouter {
+            tools: vec![],
+            prompts: Arc::new(HashMap::new()),
+            instructions: String::new(),
+            file_history: Arc::new(Mutex::new(HashMap::new())),
+            ignore_patterns: Arc::new(RwLock::new(Gitignore::empty())),
+            root_ignore_patterns: Arc::new(Vec::new()),
+            devcontainer: Err("container app is not running".to_string()),
+            editor_model: None,
+        };
+
+        let result = router
+            .call_tool(
+                "shell",
+                json!({"command": format!("touch {}", marker.display())}),
+                dummy_sender(),
+            )
+            .await;
+        let error = result.unwrap_err();
+        assert!(error.message.contains("container app is not running"));
+        assert!(!marker.exists());
+
+        temp_dir.close().unwrap();
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_text_editor_respects_ignore_patterns() {
@@ -2163,7 +2642,9 @@ mod tests {
             prompts: Arc::new(HashMap::new()),
             instructions: String::new(),
             file_history: Arc::new(Mutex::new(HashMap::new())),
-            ignore_patterns: Arc::new(ignore_patterns),
+            ignore_patterns: Arc::new(RwLock::new(ignore_patterns)),
+            root_ignore_patterns: Arc::new(Vec::new()),
+            devcontainer: Ok(None),
             editor_model: None,
         };
 
@@ -2223,7 +2704,9 @@ mod tests {
             prompts: Arc::new(HashMap::new()),
             instructions: String::new(),
             file_history: Arc::new(Mutex::new(HashMap::new())),
-            ignore_patterns: Arc::new(ignore_patterns),
+            ignore_patterns: Arc::new(RwLock::new(ignore_patterns)),
+            root_ignore_patterns: Arc::new(Vec::new()),
+            devcontainer: Ok(None),
             editor_model: None,
         };
 
@@ -15,6 +15,6 @@ mod tutorial;
 
 pub use autovisualiser::AutoVisualiserRouter;
 pub use computercontroller::ComputerControllerRouter;
-pub use developer::DeveloperRouter;
+pub use developer::{effective_hints, DeveloperRouter};
 pub use memory::MemoryRouter;
 pub use tutorial::TutorialRouter;
@@ -4,9 +4,11 @@ use crate::configuration;
 use crate::state;
 use anyhow::Result;
 use etcetera::{choose_app_strategy, AppStrategy};
+use goose::agents::script_hooks::{hooks_dir, load_script_hooks};
 use goose::agents::Agent;
 use goose::config::APP_STRATEGY;
 use goose::scheduler_factory::SchedulerFactory;
+use goose::shutdown;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 
@@ -17,6 +19,7 @@ pub async fn run() -> Result<()> {
     crate::logging::setup_logging(Some("goosed"))?;
 
     let settings = configuration::Settings::new()?;
+    goose::config::Config::global().load_remote_policy().await;
 
     // Initialize pricing cache on startup
     tracing::info!("Initializing pricing cache...");
@@ -27,10 +30,21 @@ pub async fn run() -> Result<()> {
         );
     }
 
+    if let Err(e) = goose::config::ExtensionConfigManager::migrate_secrets() {
+        tracing::warn!("Failed to copy extension secrets into their scopes: {}", e);
+    }
+
+    if let Err(e) = goose::session::retention::apply_configured_policy(None) {
+        tracing::warn!("Failed to apply the session retention policy: {}", e);
+    }
+
     let secret_key =
         std::env::var("GOOSE_SERVER__SECRET_KEY").unwrap_or_else(|_| "test".to_string());
 
     let new_agent = Agent::new();
+    for hook in load_script_hooks(&hooks_dir()) {
+        new_agent.register_hook(hook).await;
+    }
     let agent_ref = Arc::new(new_agent);
 
     let app_state = state::AppState::new(agent_ref.clone(), secret_key.clone()).await;
@@ -43,17 +57,52 @@ pub async fn run() -> Result<()> {
     app_state.set_scheduler(scheduler_instance.clone()).await;
 
     // NEW: Provide scheduler access to the agent
-    agent_ref.set_scheduler(scheduler_instance).await;
+    agent_ref.set_scheduler(scheduler_instance.clone()).await;
 
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let app = crate::routes::configure(app_state).layer(cors);
+    let app = crate::routes::configure(app_state.clone()).layer(cors);
 
     let listener = tokio::net::TcpListener::bind(settings.socket_addr()).await?;
     info!("listening on {}", listener.local_addr()?);
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            shutdown_signal().await;
+            info!("shutting down");
+            shutdown::request();
+        })
+        .await?;
+
+    if let Err(e) = scheduler_instance.shutdown().await {
+        tracing::warn!("Failed to stop the scheduler: {}", e);
+    }
+    app_state.shutdown().await;
+    shutdown::terminate_process_groups(shutdown::TERMINATE_GRACE).await;
     Ok(())
 }
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -1,22 +1,31 @@
+use goose::agents::coverage_tool::{CoverageSnapshot, FileCoverage};
 use goose::agents::extension::Envs;
 use goose::agents::extension::ToolInfo;
+use goose::agents::plan_tools::{Plan, PlanStep, StepStatus};
+use goose::agents::todo_tools::{TodoItem, TodoStatus};
 use goose::agents::ExtensionConfig;
 use goose::config::permission::PermissionLevel;
-use goose::config::ExtensionEntry;
+use goose::config::schema::{FindingKind, FindingSeverity, ValidationFinding};
+use goose::config::{ExtensionEntry, SamplingPermission};
 use goose::permission::permission_confirmation::PrincipalType;
 use goose::providers::base::{ConfigKey, ModelInfo, ProviderMetadata};
 use goose::session::info::SessionInfo;
-use goose::session::SessionMetadata;
+use goose::session::{Attachment, SessionMetadata};
 use rmcp::model::{
     Annotations, Content, EmbeddedResource, ImageContent, RawEmbeddedResource, RawImageContent,
     RawTextContent, ResourceContents, Role, TextContent, Tool, ToolAnnotations,
 };
 use utoipa::{OpenApi, ToSchema};
 
+use goose::conversation::diagnostics::{
+    ConversationIssue, ConversationIssueKind, ConversationReport,
+};
 use goose::conversation::message::{
-    ContextLengthExceeded, FrontendToolRequest, Message, MessageContent, RedactedThinkingContent,
-    SummarizationRequested, ThinkingContent, ToolConfirmationRequest, ToolRequest, ToolResponse,
+    ContextLengthExceeded, FrontendToolRequest, GenerationMetadata, Message, MessageContent,
+    RedactedThinkingContent, SummarizationRequested, ThinkingContent, ToolConfirmationRequest,
+    ToolRequest, ToolResponse,
 };
+use goose::conversation::tool_result_visibility::ToolResultAnnotation;
 use utoipa::openapi::schema::{
     AdditionalProperties, AnyOfBuilder, ArrayBuilder, ObjectBuilder, OneOfBuilder, Schema,
     SchemaFormat, SchemaType,
@@ -370,16 +379,26 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::config_management::upsert_permissions,
         super::routes::config_management::create_custom_provider,
         super::routes::config_management::remove_custom_provider,
+        super::routes::config_management::refresh_custom_provider_models,
         super::routes::agent::get_tools,
         super::routes::agent::add_sub_recipes,
         super::routes::agent::extend_prompt,
         super::routes::agent::update_agent_provider,
         super::routes::agent::update_router_tool_selector,
         super::routes::agent::update_session_config,
+        super::routes::agent::queue_message,
+        super::routes::agent::get_queued_messages,
+        super::routes::agent::clear_queued_messages,
         super::routes::reply::confirm_permission,
         super::routes::context::manage_context,
         super::routes::session::list_sessions,
         super::routes::session::get_session_history,
+        super::routes::session::diagnose_session,
+        super::routes::session::undo_last_exchange,
+        super::routes::session::run_prompt,
+        super::routes::session::list_session_agents,
+        super::routes::session::cancel_session_reply,
+        super::routes::session::close_session_agent,
         super::routes::schedule::create_schedule,
         super::routes::schedule::list_schedules,
         super::routes::schedule::delete_schedule,
@@ -390,6 +409,9 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::schedule::kill_running_job,
         super::routes::schedule::inspect_running_job,
         super::routes::schedule::sessions_handler,
+        super::routes::schedule::get_schedule,
+        super::routes::schedule::services_status,
+        super::routes::schedule::stop_services,
         super::routes::recipe::create_recipe,
         super::routes::recipe::encode_recipe,
         super::routes::recipe::decode_recipe,
@@ -399,6 +421,10 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::config_management::UpsertConfigQuery,
         super::routes::config_management::ConfigKeyQuery,
         super::routes::config_management::ConfigResponse,
+        super::routes::config_management::ConfigValidationResponse,
+        ValidationFinding,
+        FindingSeverity,
+        FindingKind,
         super::routes::config_management::ProvidersResponse,
         super::routes::config_management::ProviderDetails,
         super::routes::config_management::ExtensionResponse,
@@ -406,12 +432,22 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::config_management::ToolPermission,
         super::routes::config_management::UpsertPermissionsQuery,
         super::routes::config_management::CreateCustomProviderRequest,
+        goose::config::custom_providers::CustomProviderAuth,
         super::routes::reply::PermissionConfirmationRequest,
         super::routes::context::ContextManageRequest,
         super::routes::context::ContextManageResponse,
         super::routes::session::SessionListResponse,
         super::routes::session::SessionHistoryResponse,
+        ConversationReport,
+        ConversationIssue,
+        ConversationIssueKind,
+        super::routes::session::UndoResponse,
+        super::routes::session::RunPromptRequest,
+        super::routes::session::RunPromptResponse,
+        super::routes::session::SessionAgentInfo,
         Message,
+        GenerationMetadata,
+        ToolResultAnnotation,
         MessageContent,
         ContentSchema,
         EmbeddedResourceSchema,
@@ -434,6 +470,7 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         RoleSchema,
         ProviderMetadata,
         ExtensionEntry,
+        SamplingPermission,
         ExtensionConfig,
         ConfigKey,
         Envs,
@@ -445,6 +482,14 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         ModelInfo,
         SessionInfo,
         SessionMetadata,
+        Attachment,
+        CoverageSnapshot,
+        FileCoverage,
+        Plan,
+        PlanStep,
+        StepStatus,
+        TodoItem,
+        TodoStatus,
         super::routes::schedule::CreateScheduleRequest,
         super::routes::schedule::UpdateScheduleRequest,
         super::routes::schedule::KillJobResponse,
@@ -454,6 +499,8 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::schedule::ListSchedulesResponse,
         super::routes::schedule::SessionsQuery,
         super::routes::schedule::SessionDisplayInfo,
+        super::routes::schedule::ServicesStatusResponse,
+        super::routes::schedule::StopServicesResponse,
         super::routes::recipe::CreateRecipeRequest,
         super::routes::recipe::AuthorRequest,
         super::routes::recipe::CreateRecipeResponse,
@@ -471,6 +518,7 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         goose::recipe::RecipeParameterRequirement,
         goose::recipe::Response,
         goose::recipe::SubRecipe,
+        goose::recipe::RecipeSchedule,
         goose::agents::types::RetryConfig,
         goose::agents::types::SuccessCheck,
         super::routes::agent::AddSubRecipesRequest,
@@ -481,6 +529,9 @@ impl<'__s> ToSchema<'__s> for AnnotatedSchema {
         super::routes::agent::SessionConfigRequest,
         super::routes::agent::GetToolsQuery,
         super::routes::agent::ErrorResponse,
+        super::routes::agent::QueueMessageRequest,
+        super::routes::agent::QueueMessageResponse,
+        super::routes::agent::QueuedMessagesResponse,
     ))
 )]
 pub struct ApiDoc;
@@ -1,4 +1,4 @@
-use super::utils::verify_secret_key;
+use super::utils::{session_header, verify_secret_key};
 use crate::state::AppState;
 use axum::{
     extract::{Query, State},
@@ -7,6 +7,7 @@ use axum::{
     Json, Router,
 };
 use goose::config::PermissionManager;
+use goose::conversation::message::Message;
 use goose::model::ModelConfig;
 use goose::providers::create;
 use goose::recipe::Response;
@@ -54,6 +55,22 @@ pub struct GetToolsQuery {
     extension_name: Option<String>,
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct QueueMessageRequest {
+    message: Message,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct QueueMessageResponse {
+    /// Number of messages waiting in the queue, including this one
+    queued: usize,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct QueuedMessagesResponse {
+    messages: Vec<Message>,
+}
+
 #[derive(Serialize, utoipa::ToSchema)]
 pub struct ErrorResponse {
     error: String,
@@ -77,7 +94,7 @@ async fn add_sub_recipes(
     verify_secret_key(&headers, &state)?;
 
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     agent.add_sub_recipes(payload.sub_recipes.clone()).await;
@@ -102,7 +119,7 @@ async fn extend_prompt(
     verify_secret_key(&headers, &state)?;
 
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     agent.extend_system_prompt(payload.extension.clone()).await;
@@ -132,7 +149,7 @@ async fn get_tools(
     let config = Config::global();
     let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     let permission_manager = PermissionManager::default();
@@ -190,7 +207,7 @@ async fn update_agent_provider(
     verify_secret_key(&headers, &state)?;
 
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_e| StatusCode::PRECONDITION_FAILED)?;
 
@@ -235,12 +252,15 @@ async fn update_router_tool_selector(
         })
     })?;
 
-    let agent = state.get_agent().await.map_err(|e| {
-        tracing::error!("Failed to get agent: {}", e);
-        Json(ErrorResponse {
-            error: format!("Failed to get agent: {}", e),
-        })
-    })?;
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get agent: {}", e);
+            Json(ErrorResponse {
+                error: format!("Failed to get agent: {}", e),
+            })
+        })?;
 
     agent
         .update_router_tool_selector(None, Some(true))
@@ -279,12 +299,15 @@ async fn update_session_config(
         })
     })?;
 
-    let agent = state.get_agent().await.map_err(|e| {
-        tracing::error!("Failed to get agent: {}", e);
-        Json(ErrorResponse {
-            error: format!("Failed to get agent: {}", e),
-        })
-    })?;
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get agent: {}", e);
+            Json(ErrorResponse {
+                error: format!("Failed to get agent: {}", e),
+            })
+        })?;
 
     if let Some(response) = payload.response {
         agent.add_final_output_tool(response).await;
@@ -298,6 +321,77 @@ async fn update_session_config(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/agent/queue",
+    request_body = QueueMessageRequest,
+    responses(
+        (status = 200, description = "Message queued, it is added to the running reply at the next safe point", body = QueueMessageResponse),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 424, description = "Agent not initialized"),
+    ),
+)]
+async fn queue_message(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<QueueMessageRequest>,
+) -> Result<Json<QueueMessageResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let queued = agent.queue_message(payload.message).await;
+    Ok(Json(QueueMessageResponse { queued }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/agent/queue",
+    responses(
+        (status = 200, description = "Messages waiting to be added to the conversation", body = QueuedMessagesResponse),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 424, description = "Agent not initialized"),
+    ),
+)]
+async fn get_queued_messages(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<QueuedMessagesResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let messages = agent.queued_messages().await;
+    Ok(Json(QueuedMessagesResponse { messages }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/agent/queue",
+    responses(
+        (status = 200, description = "Queue cleared, returns the messages that were removed", body = QueuedMessagesResponse),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 424, description = "Agent not initialized"),
+    ),
+)]
+async fn clear_queued_messages(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<QueuedMessagesResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let messages = agent.take_queued_messages().await;
+    Ok(Json(QueuedMessagesResponse { messages }))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/agent/prompt", post(extend_prompt))
@@ -309,5 +403,11 @@ pub fn routes(state: Arc<AppState>) -> Router {
         )
         .route("/agent/session_config", post(update_session_config))
         .route("/agent/add_sub_recipes", post(add_sub_recipes))
+        .route(
+            "/agent/queue",
+            post(queue_message)
+                .get(get_queued_messages)
+                .delete(clear_queued_messages),
+        )
         .with_state(state)
 }
@@ -7,6 +7,10 @@ use axum::{
     Json, Router,
 };
 use etcetera::{choose_app_strategy, AppStrategy};
+use goose::config::custom_providers::{
+    refresh_stale_models, CustomProviderAuth, CustomProviderConfig, CustomProviderOptions,
+};
+use goose::config::schema::{FindingSeverity, ValidationFinding};
 use goose::config::APP_STRATEGY;
 use goose::config::{Config, ConfigError};
 use goose::config::{ExtensionConfigManager, ExtensionEntry};
@@ -49,6 +53,13 @@ pub struct ConfigKeyQuery {
     pub is_secret: bool,
 }
 
+/// Findings about the config file; it is valid when none of them is an error
+#[derive(Serialize, ToSchema)]
+pub struct ConfigValidationResponse {
+    pub valid: bool,
+    pub findings: Vec<ValidationFinding>,
+}
+
 #[derive(Serialize, ToSchema)]
 pub struct ConfigResponse {
     pub config: HashMap<String, Value>,
@@ -83,8 +94,20 @@ pub struct CreateCustomProviderRequest {
     pub display_name: String,
     pub api_url: String,
     pub api_key: String,
+    /// The models, fetched from `models_endpoint` when it is set
+    #[serde(default)]
     pub models: Vec<String>,
     pub supports_streaming: Option<bool>,
+    /// Where the model list is fetched from, such as `/v1/models`
+    pub models_endpoint: Option<String>,
+    /// Static headers sent with every request
+    pub headers: Option<HashMap<String, String>>,
+    /// How the API key is sent, the API type's usual scheme when not set
+    pub auth: Option<CustomProviderAuth>,
+    /// Default request parameters of each model, keyed by model name
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub model_params: HashMap<String, Value>,
 }
 
 #[utoipa::path(
@@ -238,10 +261,15 @@ pub async fn add_extension(
         ExtensionConfigManager::get_all().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     let key = goose::config::extensions::name_to_key(&extension_query.name);
 
-    let is_update = extensions.iter().any(|e| e.config.key() == key);
+    let existing = extensions.iter().find(|e| e.config.key() == key);
+    let is_update = existing.is_some();
+    let eager = existing.is_some_and(|e| e.eager);
+    let sampling = existing.and_then(|e| e.sampling.clone());
 
     match ExtensionConfigManager::set(ExtensionEntry {
         enabled: extension_query.enabled,
+        eager,
+        sampling,
         config: extension_query.config,
     }) {
         Ok(_) => {
@@ -313,6 +341,7 @@ pub async fn providers(
 ) -> Result<Json<Vec<ProviderDetails>>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
+    refresh_stale_models().await;
     let mut providers_metadata = get_providers();
 
     let custom_providers_dir = goose::config::custom_providers::custom_providers_dir();
@@ -706,14 +735,14 @@ pub async fn recover_config(
     get,
     path = "/config/validate",
     responses(
-        (status = 200, description = "Config validation result", body = String),
+        (status = 200, description = "Config validation result", body = ConfigValidationResponse),
         (status = 422, description = "Config file is corrupted")
     )
 )]
 pub async fn validate_config(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
-) -> Result<Json<String>, StatusCode> {
+) -> Result<Json<ConfigValidationResponse>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
     let config_dir = choose_app_strategy(APP_STRATEGY.clone())
@@ -723,22 +752,35 @@ pub async fn validate_config(
     let config_path = config_dir.join("config.yaml");
 
     if !config_path.exists() {
-        return Ok(Json("Config file does not exist".to_string()));
+        return Ok(Json(ConfigValidationResponse {
+            valid: true,
+            findings: Vec::new(),
+        }));
     }
 
     match std::fs::read_to_string(&config_path) {
-        Ok(content) => match serde_yaml::from_str::<serde_yaml::Value>(&content) {
-            Ok(_) => Ok(Json("Config file is valid".to_string())),
-            Err(e) => {
+        Ok(content) => {
+            if let Err(e) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
                 tracing::warn!("Config validation failed: {}", e);
-                Err(StatusCode::UNPROCESSABLE_ENTITY)
+                return Err(StatusCode::UNPROCESSABLE_ENTITY);
             }
-        },
+        }
         Err(e) => {
             tracing::error!("Failed to read config file: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
     }
+
+    let findings = Config::global().validate().map_err(|e| {
+        tracing::error!("Failed to validate config: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(ConfigValidationResponse {
+        valid: !findings
+            .iter()
+            .any(|finding| finding.severity == FindingSeverity::Error),
+        findings,
+    }))
 }
 
 #[utoipa::path(
@@ -778,16 +820,32 @@ pub async fn create_custom_provider(
 ) -> Result<Json<String>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
-    let config = goose::config::custom_providers::CustomProviderConfig::create_and_save(
+    let mut config = CustomProviderConfig::create_and_save(
         &request.provider_type,
         request.display_name,
         request.api_url,
         request.api_key,
         request.models,
-        request.supports_streaming,
+        CustomProviderOptions {
+            headers: request.headers,
+            auth: request.auth,
+            model_params: request.model_params,
+            supports_streaming: request.supports_streaming,
+            models_endpoint: request.models_endpoint,
+        },
     )
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    if config.models_endpoint.is_some() {
+        if let Err(e) = config.refresh_models().await {
+            tracing::warn!("Failed to fetch the models of {}: {}", config.id(), e);
+        }
+    }
+    if config.models.is_empty() {
+        let _ = CustomProviderConfig::remove(config.id());
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     if let Err(e) = goose::providers::refresh_custom_providers() {
         tracing::warn!("Failed to refresh custom providers after creation: {}", e);
     }
@@ -821,6 +879,38 @@ pub async fn remove_custom_provider(
     Ok(Json(format!("Removed custom provider: {}", id)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/config/custom-providers/{id}/models",
+    responses(
+        (status = 200, description = "Models fetched from the provider's models endpoint", body = [String]),
+        (status = 404, description = "Provider not found"),
+        (status = 502, description = "The models endpoint could not be reached, the saved list is kept")
+    )
+)]
+pub async fn refresh_custom_provider_models(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<String>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let mut config = CustomProviderConfig::load(&id).map_err(|_| StatusCode::NOT_FOUND)?;
+    config.refresh_models().await.map_err(|e| {
+        tracing::warn!("Failed to fetch the models of {}: {}", id, e);
+        StatusCode::BAD_GATEWAY
+    })?;
+
+    if let Err(e) = goose::providers::refresh_custom_providers() {
+        tracing::warn!(
+            "Failed to refresh custom providers after fetching models: {}",
+            e
+        );
+    }
+
+    Ok(Json(config.models.into_iter().map(|m| m.name).collect()))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/config", get(read_all_config))
@@ -844,6 +934,10 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/config/custom-providers/{id}",
             delete(remove_custom_provider),
         )
+        .route(
+            "/config/custom-providers/{id}/models",
+            post(refresh_custom_provider_models),
+        )
         .with_state(state)
 }
 
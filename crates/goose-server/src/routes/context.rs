@@ -1,4 +1,4 @@
-use super::utils::verify_secret_key;
+use super::utils::{session_header, verify_secret_key};
 use crate::state::AppState;
 use axum::{
     extract::State,
@@ -54,7 +54,7 @@ async fn manage_context(
     verify_secret_key(&headers, &state)?;
 
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
 
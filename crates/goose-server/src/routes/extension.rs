@@ -3,10 +3,13 @@ use std::path::Path;
 use std::sync::Arc;
 use std::sync::OnceLock;
 
-use super::utils::verify_secret_key;
+use super::utils::{session_header, verify_secret_key};
 use crate::state::AppState;
 use axum::{extract::State, routing::post, Json, Router};
-use goose::agents::{extension::Envs, ExtensionConfig};
+use goose::agents::{
+    extension::{Envs, ExtensionToolStatus},
+    ExtensionConfig, ExtensionReload,
+};
 use http::{HeaderMap, StatusCode};
 use rmcp::model::Tool;
 use serde::{Deserialize, Serialize};
@@ -273,7 +276,7 @@ async fn add_extension(
 
     // Get a reference to the agent
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     let response = agent.add_extension(extension_config).await;
@@ -307,7 +310,7 @@ async fn remove_extension(
 
     // Get a reference to the agent
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     match agent.remove_extension(&name).await {
@@ -322,11 +325,87 @@ async fn remove_extension(
     }
 }
 
+/// Handler for reloading the session's extensions from the extension config
+async fn reload_extensions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ExtensionReload>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    agent.reload_extensions().await.map(Json).map_err(|e| {
+        tracing::error!("Failed to reload extensions: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Handler for listing the tools of a running extension, including disabled ones
+async fn list_extension_tools(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(name): Json<String>,
+) -> Result<Json<Vec<ExtensionToolStatus>>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    agent
+        .list_extension_tools(&name)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to list tools of {}: {:?}", name, e);
+            StatusCode::NOT_FOUND
+        })
+}
+
+#[derive(Deserialize)]
+struct ToggleToolRequest {
+    extension: String,
+    tool: String,
+    enabled: bool,
+}
+
+/// Handler for enabling or disabling one tool of a running extension
+async fn toggle_extension_tool(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ToggleToolRequest>,
+) -> Result<Json<ExtensionResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    match agent
+        .set_extension_tool_enabled(&request.extension, &request.tool, request.enabled)
+        .await
+    {
+        Ok(_) => Ok(Json(ExtensionResponse {
+            error: false,
+            message: None,
+        })),
+        Err(e) => Ok(Json(ExtensionResponse {
+            error: true,
+            message: Some(format!("Failed to toggle tool: {}", e)),
+        })),
+    }
+}
+
 /// Registers the extension management routes with the Axum router.
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/extensions/add", post(add_extension))
         .route("/extensions/remove", post(remove_extension))
+        .route("/extensions/reload", post(reload_extensions))
+        .route("/extensions/tools", post(list_extension_tools))
+        .route("/extensions/tools/toggle", post(toggle_extension_tool))
         .with_state(state)
 }
 
@@ -1,4 +1,4 @@
-use super::utils::verify_secret_key;
+use super::utils::{session_header, verify_secret_key};
 use crate::state::AppState;
 use axum::{
     extract::{DefaultBodyLimit, State},
@@ -10,9 +10,14 @@ use axum::{
 use bytes::Bytes;
 use futures::{stream::StreamExt, Stream};
 use goose::conversation::message::{Message, MessageContent};
+use goose::conversation::tool_result_visibility::{
+    annotate_tool_results, min_priority, ToolResultAnnotation,
+};
 use goose::conversation::Conversation;
 use goose::{
-    agents::{AgentEvent, SessionConfig},
+    agents::{
+        plan_tools::Plan, todo_tools::TodoItem, AgentEvent, ConfirmationTimeout, SessionConfig,
+    },
     permission::permission_confirmation::PrincipalType,
 };
 use goose::{
@@ -91,6 +96,9 @@ struct ChatRequest {
     session_id: Option<String>,
     session_working_dir: String,
     scheduled_job_id: Option<String>,
+    /// Overrides the configured tool confirmation timeout for this session
+    #[serde(default)]
+    confirmation_timeout: Option<ConfirmationTimeout>,
 }
 
 pub struct SseResponse {
@@ -132,6 +140,10 @@ impl IntoResponse for SseResponse {
 enum MessageEvent {
     Message {
         message: Message,
+        /// Priority and audience of the tool results in the message, so clients hide the same
+        /// content the CLI does
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        tool_result_annotations: Vec<ToolResultAnnotation>,
     },
     Error {
         error: String,
@@ -143,6 +155,12 @@ enum MessageEvent {
         model: String,
         mode: String,
     },
+    PlanUpdate {
+        plan: Plan,
+    },
+    TodoUpdate {
+        todos: Vec<TodoItem>,
+    },
     Notification {
         request_id: String,
         message: ServerNotification,
@@ -185,20 +203,42 @@ async fn reply_handler(
 
     let (tx, rx) = mpsc::channel(100);
     let stream = ReceiverStream::new(rx);
-    let cancel_token = CancellationToken::new();
-
     let messages = Conversation::new_unvalidated(request.messages);
     let session_working_dir = request.session_working_dir.clone();
 
+    // A request naming its session is served by the session's own agent, working in the
+    // session's directory
+    let session_agent = match session_header(&headers) {
+        Some(session_id) => Some(
+            state
+                .session_agent(session_id, Some(PathBuf::from(&session_working_dir)))
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to start the agent of the session: {}", e);
+                    StatusCode::PRECONDITION_FAILED
+                })?,
+        ),
+        None => None,
+    };
     let session_id = request
         .session_id
+        .or_else(|| session_header(&headers).map(str::to_string))
         .unwrap_or_else(session::generate_session_id);
 
+    // Replies stop when goosed shuts down, so the server can drain them and record the sessions
+    let cancel_token = match &session_agent {
+        Some(session_agent) => session_agent.start_reply().await,
+        None => goose::shutdown::token().child_token(),
+    };
     let task_cancel = cancel_token.clone();
     let task_tx = tx.clone();
 
     std::mem::drop(tokio::spawn(async move {
-        let agent = match state.get_agent().await {
+        let agent = match session_agent {
+            Some(session_agent) => Ok(session_agent.agent),
+            None => state.get_agent().await,
+        };
+        let agent = match agent {
             Ok(agent) => agent,
             Err(_) => {
                 let _ = stream_event(
@@ -212,6 +252,8 @@ async fn reply_handler(
                 return;
             }
         };
+        // A client asking for a reply is there to answer frontend tool calls
+        agent.attach_frontend().await;
 
         let session_config = SessionConfig {
             id: session::Identifier::Name(session_id.clone()),
@@ -220,6 +262,7 @@ async fn reply_handler(
             execution_mode: None,
             max_turns: None,
             retry_config: None,
+            confirmation_timeout: request.confirmation_timeout,
         };
 
         let mut stream = match agent
@@ -262,7 +305,10 @@ async fn reply_handler(
             }
         };
         let saved_message_count = all_messages.len();
+        let min_priority = min_priority();
 
+        // Whether the reply ran to its end, a stopped one keeps its journal for recovery
+        let mut finished = false;
         let mut heartbeat_interval = tokio::time::interval(Duration::from_millis(500));
         loop {
             tokio::select! {
@@ -281,7 +327,8 @@ async fn reply_handler(
                             }
 
                             all_messages.push(message.clone());
-                            stream_event(MessageEvent::Message { message }, &tx, &cancel_token).await;
+                            let tool_result_annotations = annotate_tool_results(&message, min_priority);
+                            stream_event(MessageEvent::Message { message, tool_result_annotations }, &tx, &cancel_token).await;
                         }
                         Ok(Some(Ok(AgentEvent::HistoryReplaced(new_messages)))) => {
                             // Replace the message history with the compacted messages
@@ -292,6 +339,12 @@ async fn reply_handler(
                         Ok(Some(Ok(AgentEvent::ModelChange { model, mode }))) => {
                             stream_event(MessageEvent::ModelChange { model, mode }, &tx, &cancel_token).await;
                         }
+                        Ok(Some(Ok(AgentEvent::PlanUpdate(plan)))) => {
+                            stream_event(MessageEvent::PlanUpdate { plan }, &tx, &cancel_token).await;
+                        }
+                        Ok(Some(Ok(AgentEvent::TodoUpdate(todos)))) => {
+                            stream_event(MessageEvent::TodoUpdate { todos }, &tx, &cancel_token).await;
+                        }
                         Ok(Some(Ok(AgentEvent::McpNotification((request_id, n))))) => {
                             stream_event(MessageEvent::Notification{
                                 request_id: request_id.clone(),
@@ -311,6 +364,7 @@ async fn reply_handler(
                             break;
                         }
                         Ok(None) => {
+                            finished = true;
                             break;
                         }
                         Err(_) => {
@@ -329,8 +383,8 @@ async fn reply_handler(
                 let provider = Arc::clone(&provider);
                 let session_path_clone = session_path.to_path_buf();
                 let all_messages_clone = all_messages.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = session::persist_messages(
+                let persist = tokio::spawn(async move {
+                    match session::persist_messages(
                         &session_path_clone,
                         &all_messages_clone,
                         Some(provider),
@@ -338,9 +392,14 @@ async fn reply_handler(
                     )
                     .await
                     {
-                        tracing::error!("Failed to store session history: {:?}", e);
+                        Ok(()) if finished => session::journal::commit(&session_path_clone),
+                        Ok(()) => {}
+                        Err(e) => tracing::error!("Failed to store session history: {:?}", e),
                     }
                 });
+                if goose::shutdown::requested() {
+                    let _ = persist.await;
+                }
             }
         }
 
@@ -438,7 +497,7 @@ pub async fn confirm_permission(
     verify_secret_key(&headers, &state)?;
 
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
 
@@ -492,13 +551,28 @@ async fn submit_tool_result(
     };
 
     let agent = state
-        .get_agent()
+        .agent_for(session_header(&headers))
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     agent.handle_tool_result(payload.id, payload.result).await;
     Ok(Json(json!({"status": "ok"})))
 }
 
+/// The frontend is going away. Frontend tool calls waiting on it fail instead of timing out
+async fn detach_frontend(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .agent_for(session_header(&headers))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    agent.detach_frontend().await;
+    Ok(Json(json!({"status": "ok"})))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route(
@@ -510,6 +584,7 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/tool_result",
             post(submit_tool_result).layer(DefaultBodyLimit::max(10 * 1024 * 1024)),
         )
+        .route("/frontend/detach", post(detach_frontend))
         .with_state(state)
 }
 
@@ -585,6 +660,7 @@ mod tests {
                         session_id: Some("test-session".to_string()),
                         session_working_dir: "test-working-dir".to_string(),
                         scheduled_job_id: None,
+                        confirmation_timeout: None,
                     })
                     .unwrap(),
                 ))
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use axum::{
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::{delete, get, post, put},
+    routing::{delete, get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
@@ -13,6 +13,8 @@ use chrono::NaiveDateTime;
 use crate::routes::utils::verify_secret_key;
 use crate::state::AppState;
 use goose::scheduler::ScheduledJob;
+use goose::scheduler_factory::SchedulerType;
+use goose::temporal_scheduler::TemporalScheduler;
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
 pub struct CreateScheduleRequest {
@@ -21,6 +23,12 @@ pub struct CreateScheduleRequest {
     cron: String,
     #[serde(default)]
     execution_mode: Option<String>, // "foreground" or "background"
+    /// IANA timezone the cron expression is read in, UTC when unset
+    #[serde(default)]
+    timezone: Option<String>,
+    /// Webhook URL told about each run, on top of the configured webhooks
+    #[serde(default)]
+    notify: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
@@ -48,6 +56,24 @@ pub struct InspectJobResponse {
     running_duration_seconds: Option<i64>,
 }
 
+// Response for the services status endpoint
+#[derive(Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServicesStatusResponse {
+    /// "temporal" or "legacy"
+    scheduler_type: String,
+    /// Whether the Temporal services are running, always false for the legacy scheduler
+    running: bool,
+    /// The Temporal UI, when the services are running
+    ui_url: Option<String>,
+}
+
+// Response for the services stop endpoint
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct StopServicesResponse {
+    message: String,
+}
+
 // Response for the run_now endpoint
 #[derive(Serialize, utoipa::ToSchema)]
 pub struct RunNowResponse {
@@ -81,6 +107,8 @@ pub struct SessionDisplayInfo {
     accumulated_total_tokens: Option<i32>,
     accumulated_input_tokens: Option<i32>,
     accumulated_output_tokens: Option<i32>,
+    /// Link to the scheduler's page for the run, e.g. the workflow run in the Temporal UI
+    run_url: Option<String>,
 }
 
 fn parse_session_name_to_iso(session_name: &str) -> String {
@@ -113,6 +141,10 @@ async fn create_schedule(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    if let Some(timezone) = &req.timezone {
+        goose::scheduler::parse_timezone(timezone).map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+
     tracing::info!(
         "Server: Calling scheduler.add_scheduled_job() for job '{}'",
         req.id
@@ -127,6 +159,9 @@ async fn create_schedule(
         current_session_id: None,
         process_start_time: None,
         execution_mode: req.execution_mode.or(Some("background".to_string())), // Default to background
+        timezone: req.timezone,
+        notify: req.notify,
+        recipe_origin: None,
     };
     scheduler
         .add_scheduled_job(job.clone())
@@ -172,6 +207,41 @@ async fn list_schedules(
     Ok(Json(ListSchedulesResponse { jobs }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/schedule/{id}",
+    params(
+        ("id" = String, Path, description = "ID of the schedule")
+    ),
+    responses(
+        (status = 200, description = "The scheduled job", body = ScheduledJob),
+        (status = 404, description = "Scheduled job not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "schedule"
+)]
+#[axum::debug_handler]
+async fn get_schedule(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<Json<ScheduledJob>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+    let scheduler = state
+        .scheduler()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let jobs = scheduler.list_scheduled_jobs().await.map_err(|e| {
+        eprintln!("Error listing schedules: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    jobs.into_iter()
+        .find(|job| job.id == id)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
 #[utoipa::path(
     delete,
     path = "/schedule/delete/{id}",
@@ -303,6 +373,7 @@ async fn sessions_handler(
                     accumulated_total_tokens: metadata.accumulated_total_tokens,
                     accumulated_input_tokens: metadata.accumulated_input_tokens,
                     accumulated_output_tokens: metadata.accumulated_output_tokens,
+                    run_url: metadata.schedule_run_url,
                 })
                 .collect();
             Ok(Json(display_infos))
@@ -529,17 +600,137 @@ pub async fn inspect_running_job(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/schedule/services/status",
+    responses(
+        (status = 200, description = "Which scheduler is in use and whether its services are running", body = ServicesStatusResponse),
+    ),
+    tag = "schedule"
+)]
+#[axum::debug_handler]
+async fn services_status(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ServicesStatusResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    if !matches!(SchedulerType::from_config(), SchedulerType::Temporal) {
+        return Ok(Json(ServicesStatusResponse {
+            scheduler_type: "legacy".to_string(),
+            running: false,
+            ui_url: None,
+        }));
+    }
+    let scheduler = TemporalScheduler::connect().await.ok();
+    Ok(Json(ServicesStatusResponse {
+        scheduler_type: "temporal".to_string(),
+        running: scheduler.is_some(),
+        ui_url: scheduler.map(|scheduler| format!("http://localhost:{}", scheduler.get_ui_port())),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/schedule/services/stop",
+    responses(
+        (status = 200, description = "Temporal services stopped", body = StopServicesResponse),
+        (status = 400, description = "The legacy scheduler has no services to stop"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "schedule"
+)]
+#[axum::debug_handler]
+async fn stop_services(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<StopServicesResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    if !matches!(SchedulerType::from_config(), SchedulerType::Temporal) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let Ok(scheduler) = TemporalScheduler::connect().await else {
+        return Ok(Json(StopServicesResponse {
+            message: "Temporal services are not running".to_string(),
+        }));
+    };
+    let message = scheduler.stop_services().await.map_err(|e| {
+        eprintln!("Error stopping Temporal services: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(StopServicesResponse { message }))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/schedule/create", post(create_schedule))
         .route("/schedule/list", get(list_schedules))
         .route("/schedule/delete/{id}", delete(delete_schedule)) // Corrected
-        .route("/schedule/{id}", put(update_schedule))
+        .route("/schedule/{id}", get(get_schedule).put(update_schedule))
         .route("/schedule/{id}/run_now", post(run_now_handler)) // Corrected
         .route("/schedule/{id}/pause", post(pause_schedule))
         .route("/schedule/{id}/unpause", post(unpause_schedule))
         .route("/schedule/{id}/kill", post(kill_running_job))
         .route("/schedule/{id}/inspect", get(inspect_running_job))
         .route("/schedule/{id}/sessions", get(sessions_handler)) // Corrected
+        .route("/schedule/services/status", get(services_status))
+        .route("/schedule/services/stop", post(stop_services))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use goose::scheduler::Scheduler;
+    use tower::ServiceExt;
+
+    async fn app() -> (Router, tempfile::TempDir) {
+        let state = AppState::new(
+            Arc::new(goose::agents::Agent::new()),
+            "test-secret".to_string(),
+        )
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let scheduler = Scheduler::new(dir.path().join("schedules.json"))
+            .await
+            .unwrap();
+        state.set_scheduler(scheduler).await;
+        (routes(state), dir)
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_schedule() {
+        let (app, _dir) = app().await;
+        let request = Request::builder()
+            .uri("/schedule/nightly")
+            .header("X-Secret-Key", "test-secret")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_create_schedule_rejects_unknown_timezone() {
+        let (app, _dir) = app().await;
+        let request = Request::builder()
+            .uri("/schedule/create")
+            .method("POST")
+            .header("X-Secret-Key", "test-secret")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "id": "nightly",
+                    "recipe_source": "/tmp/recipe.yaml",
+                    "cron": "0 0 2 * * *",
+                    "timezone": "Mars/Olympus_Mons"
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -7,9 +7,10 @@ use crate::state::AppState;
 use axum::{
     extract::{Path, State},
     http::{HeaderMap, StatusCode},
-    routing::{get, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use goose::conversation::diagnostics::{diagnose_conversation, ConversationReport};
 use goose::conversation::message::Message;
 use goose::session;
 use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
@@ -43,6 +44,36 @@ pub struct UpdateSessionMetadataRequest {
     description: String,
 }
 
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResponse {
+    /// Unique identifier for the session
+    session_id: String,
+    /// Messages removed from the end of the conversation, empty when there was nothing to undo
+    removed: Vec<Message>,
+    /// The conversation after the undo, which replaces the client's copy of the history
+    messages: Vec<Message>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RunPromptRequest {
+    /// Values for the arguments the prompt declares
+    #[serde(default)]
+    arguments: HashMap<String, String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RunPromptResponse {
+    /// Unique identifier for the session
+    session_id: String,
+    /// Extension that provides the prompt
+    extension: String,
+    /// Messages rendered by the prompt, to be appended to the conversation and sent to the agent
+    messages: Vec<Message>,
+}
+
 const MAX_DESCRIPTION_LENGTH: usize = 200;
 
 #[derive(Serialize, ToSchema, Debug)]
@@ -126,6 +157,17 @@ async fn get_session_history(
 
     let metadata = session::read_metadata(&session_path).map_err(|_| StatusCode::NOT_FOUND)?;
 
+    // A turn cut short when goosed stopped is reconciled before the session is loaded
+    match session::journal::recover(&session_path) {
+        Ok(Some(recovery)) => info!(
+            session_id,
+            aborted_tool_calls = recovery.aborted_tool_calls.len(),
+            "Recovered the interrupted turn of the session"
+        ),
+        Ok(None) => {}
+        Err(e) => tracing::error!("Failed to recover the interrupted turn: {:?}", e),
+    }
+
     let messages = match session::read_messages(&session_path) {
         Ok(messages) => messages,
         Err(e) => {
@@ -141,6 +183,43 @@ async fn get_session_history(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}/doctor",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Issues in the session's conversation and how they are repaired", body = ConversationReport),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Report what repairing a session's conversation finds and changes
+async fn diagnose_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<Json<ConversationReport>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let session_path = session::get_path(session::Identifier::Name(session_id))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !session_path.exists() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let messages = session::read_messages(&session_path).map_err(|e| {
+        error!("Failed to read session messages: {:?}", e);
+        StatusCode::NOT_FOUND
+    })?;
+
+    Ok(Json(diagnose_conversation(messages.messages())))
+}
+
 #[utoipa::path(
     get,
     path = "/sessions/insights",
@@ -310,16 +389,246 @@ async fn update_session_metadata(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/undo",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Last exchange removed from the session", body = UndoResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Remove the last user message and everything after it from a session
+async fn undo_last_exchange(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<Json<UndoResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let session_path = session::get_path(session::Identifier::Name(session_id.clone()))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !session_path.exists() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let (messages, removed) = session::undo_last_exchange(&session_path).map_err(|e| {
+        error!("Failed to undo last exchange: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(UndoResponse {
+        session_id,
+        removed,
+        messages: messages.messages().clone(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/prompts/{name}",
+    request_body = RunPromptRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ("name" = String, Path, description = "Prompt name, optionally qualified as <extension>:<name>")
+    ),
+    responses(
+        (status = 200, description = "Prompt rendered into messages", body = RunPromptResponse),
+        (status = 400, description = "Bad request - A required prompt argument is missing"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session or prompt not found"),
+        (status = 412, description = "Precondition failed - Agent not available"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Render an extension prompt into messages for a session
+async fn run_prompt(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path((session_id, name)): Path<(String, String)>,
+    Json(request): Json<RunPromptRequest>,
+) -> Result<Json<RunPromptResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let session_path = session::get_path(session::Identifier::Name(session_id.clone()))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !session_path.exists() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let agent = state
+        .agent_for(Some(&session_id))
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let (extension, prompt) = agent
+        .find_prompt(&name)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let missing_required = prompt
+        .arguments
+        .iter()
+        .flatten()
+        .any(|arg| arg.required.unwrap_or(false) && !request.arguments.contains_key(&arg.name));
+    if missing_required {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let arguments = serde_json::to_value(request.arguments).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let result = agent
+        .get_prompt(&format!("{}:{}", extension, prompt.name), arguments)
+        .await
+        .map_err(|e| {
+            error!("Failed to get prompt: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(RunPromptResponse {
+        session_id,
+        extension,
+        messages: result.messages.into_iter().map(Message::from).collect(),
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionAgentInfo {
+    session_id: String,
+    /// Directory the extensions of the session work in
+    #[schema(value_type = String)]
+    working_dir: std::path::PathBuf,
+    /// Extensions running for the session
+    extensions: Vec<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/sessions/agents",
+    responses(
+        (status = 200, description = "Sessions served by their own agent", body = Vec<SessionAgentInfo>),
+        (status = 401, description = "Unauthorized - Invalid or missing API key")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// List the sessions that have an agent of their own
+async fn list_session_agents(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SessionAgentInfo>>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let mut agents = Vec::new();
+    for (session_id, session_agent) in state.session_agents().await {
+        agents.push(SessionAgentInfo {
+            session_id,
+            working_dir: session_agent.working_dir,
+            extensions: session_agent
+                .agent
+                .extension_manager
+                .list_extensions()
+                .await
+                .unwrap_or_default(),
+        });
+    }
+    agents.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    Ok(Json(agents))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/cancel",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "The running reply of the session was stopped"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session has no agent of its own")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Stop the running reply of a session, leaving replies of other sessions running
+async fn cancel_session_reply(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let session_agent = state
+        .session_agents()
+        .await
+        .remove(&session_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    session_agent.cancel_reply().await;
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/sessions/{session_id}/agent",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "The agent of the session and its extensions were stopped"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session has no agent of its own")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Stop the agent of a session along with its extensions
+async fn close_session_agent(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    if !state.close_session_agent(&session_id).await {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    info!(session_id, "Closed the agent of the session");
+    Ok(StatusCode::OK)
+}
+
 // Configure routes for this module
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/sessions", get(list_sessions))
         .route("/sessions/{session_id}", get(get_session_history))
+        .route("/sessions/{session_id}/doctor", get(diagnose_session))
         .route("/sessions/insights", get(get_session_insights))
+        .route("/sessions/agents", get(list_session_agents))
+        .route("/sessions/{session_id}/cancel", post(cancel_session_reply))
+        .route("/sessions/{session_id}/agent", delete(close_session_agent))
         .route(
             "/sessions/{session_id}/metadata",
             put(update_session_metadata),
         )
+        .route("/sessions/{session_id}/undo", post(undo_last_exchange))
+        .route("/sessions/{session_id}/prompts/{name}", post(run_prompt))
         .with_state(state)
 }
 
@@ -37,6 +37,17 @@ pub fn verify_secret_key(headers: &HeaderMap, state: &AppState) -> Result<Status
     }
 }
 
+/// Header naming the session a request is for, served by that session's own agent
+pub const SESSION_ID_HEADER: &str = "X-Session-Id";
+
+/// The session named by the request, None for requests to the shared agent
+pub fn session_header(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+}
+
 /// Inspects a configuration key to determine if it's set, its location, and value (for non-secret keys)
 #[allow(dead_code)]
 pub fn inspect_key(key_name: &str, is_secret: bool) -> Result<KeyInfo, Box<dyn Error>> {
@@ -1,32 +1,238 @@
+use goose::agents::script_hooks::{hooks_dir, load_script_hooks};
 use goose::agents::Agent;
 use goose::scheduler_trait::SchedulerTrait;
+use goose::webhooks::{self, WebhookEvent};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 
 pub type AgentRef = Arc<Agent>;
 
+/// Session agents left unused for this long are stopped along with their extensions
+const SESSION_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+/// Most session agents running at once. Starting another stops the least recently used
+const MAX_SESSION_AGENTS: usize = 16;
+
+/// The agent serving one session, with its own extension processes and working directory
+#[derive(Clone)]
+pub struct SessionAgent {
+    pub agent: AgentRef,
+    pub working_dir: PathBuf,
+    /// Cancelled to stop the reply running in this session
+    pub cancel_token: Arc<Mutex<CancellationToken>>,
+    last_used: Arc<std::sync::Mutex<Instant>>,
+}
+
+impl SessionAgent {
+    fn touch(&self) {
+        *self.last_used.lock().unwrap() = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_used.lock().unwrap().elapsed()
+    }
+
+    /// Whether only the server's map holds the agent, so no request is using it
+    fn is_unused(&self) -> bool {
+        Arc::strong_count(&self.agent) == 1
+    }
+
+    /// A token for a new reply, replacing the one of the previous reply
+    pub async fn start_reply(&self) -> CancellationToken {
+        let token = goose::shutdown::token().child_token();
+        *self.cancel_token.lock().await = token.clone();
+        token
+    }
+
+    pub async fn cancel_reply(&self) {
+        self.cancel_token.lock().await.cancel();
+    }
+}
+
 #[derive(Clone)]
 pub struct AppState {
     agent: Option<AgentRef>,
+    /// Agents of the sessions that asked for their own, by session id
+    sessions: Arc<Mutex<HashMap<String, SessionAgent>>>,
     pub secret_key: String,
     pub scheduler: Arc<Mutex<Option<Arc<dyn SchedulerTrait>>>>,
+    session_idle_ttl: Duration,
+    max_session_agents: usize,
 }
 
 impl AppState {
     pub async fn new(agent: AgentRef, secret_key: String) -> Arc<AppState> {
         Arc::new(Self {
             agent: Some(agent.clone()),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
             secret_key,
             scheduler: Arc::new(Mutex::new(None)),
+            session_idle_ttl: SESSION_IDLE_TTL,
+            max_session_agents: MAX_SESSION_AGENTS,
         })
     }
 
+    /// The shared agent, serving requests that don't name a session
     pub async fn get_agent(&self) -> Result<Arc<Agent>, anyhow::Error> {
         self.agent
             .clone()
             .ok_or_else(|| anyhow::anyhow!("Agent needs to be created first."))
     }
 
+    /// The agent of the session, or the shared agent when no session is named
+    pub async fn agent_for(&self, session_id: Option<&str>) -> Result<AgentRef, anyhow::Error> {
+        match session_id {
+            Some(session_id) => Ok(self.session_agent(session_id, None).await?.agent),
+            None => self.get_agent().await,
+        }
+    }
+
+    /// The agent of the session, started on first use with the provider and extensions of the
+    /// shared agent. Its extensions are separate processes working in `working_dir`, which
+    /// defaults to the working directory of the server. Agents left unused are stopped, see
+    /// `SESSION_IDLE_TTL` and `MAX_SESSION_AGENTS`, and started again when next needed
+    pub async fn session_agent(
+        &self,
+        session_id: &str,
+        working_dir: Option<PathBuf>,
+    ) -> Result<SessionAgent, anyhow::Error> {
+        if let Some(session) = self.sessions.lock().await.get(session_id) {
+            session.touch();
+            return Ok(session.clone());
+        }
+        self.stop_unused_session_agents().await;
+
+        let working_dir = match working_dir {
+            Some(dir) => dir,
+            None => std::env::current_dir()?,
+        };
+        // Started without holding the lock, since extensions can take long to start
+        let agent = self.create_session_agent(&working_dir).await?;
+
+        let mut sessions = self.sessions.lock().await;
+        if let Some(session) = sessions.get(session_id) {
+            // Another request started the session meanwhile
+            let session = session.clone();
+            drop(sessions);
+            session.touch();
+            agent.shutdown().await;
+            return Ok(session);
+        }
+        let session = SessionAgent {
+            agent,
+            working_dir,
+            cancel_token: Arc::new(Mutex::new(CancellationToken::new())),
+            last_used: Arc::new(std::sync::Mutex::new(Instant::now())),
+        };
+        sessions.insert(session_id.to_string(), session.clone());
+        tracing::info!(session_id, "Started the agent of the session");
+        webhooks::notify(WebhookEvent::SessionStart, session_webhook_data(session_id));
+        Ok(session)
+    }
+
+    /// Stop the session agents idle for longer than the TTL, and the least recently used ones
+    /// beyond the cap, leaving room for one more. Agents a request is using are kept
+    async fn stop_unused_session_agents(&self) {
+        let stopped: Vec<(String, SessionAgent)> = {
+            let mut sessions = self.sessions.lock().await;
+            let mut unused: Vec<(Duration, String)> = sessions
+                .iter()
+                .filter(|(_, session)| session.is_unused())
+                .map(|(id, session)| (session.idle_for(), id.clone()))
+                .collect();
+            // Longest idle first
+            unused.sort_by(|a, b| b.0.cmp(&a.0));
+            let over_cap = (sessions.len() + 1).saturating_sub(self.max_session_agents);
+            let stale = unused
+                .iter()
+                .filter(|(idle, _)| *idle >= self.session_idle_ttl)
+                .count();
+            unused
+                .into_iter()
+                .take(stale.max(over_cap))
+                .filter_map(|(_, id)| sessions.remove(&id).map(|session| (id, session)))
+                .collect()
+        };
+        for (session_id, session) in stopped {
+            tracing::info!(session_id, "Stopping the unused agent of the session");
+            session.agent.shutdown().await;
+            webhooks::notify(WebhookEvent::SessionEnd, session_webhook_data(&session_id));
+        }
+    }
+
+    async fn create_session_agent(&self, working_dir: &Path) -> Result<AgentRef, anyhow::Error> {
+        let shared = self.get_agent().await?;
+        let agent = Agent::new();
+        for hook in load_script_hooks(&hooks_dir()) {
+            agent.register_hook(hook).await;
+        }
+        if let Ok(scheduler) = self.scheduler().await {
+            agent.set_scheduler(scheduler).await;
+        }
+        if let Ok(provider) = shared.provider().await {
+            agent.update_provider(provider).await?;
+        }
+        // Set first so the extensions start in the working directory
+        agent
+            .extension_manager
+            .set_extension_dir(working_dir.to_path_buf())
+            .await;
+        agent
+            .extension_manager
+            .set_roots(&[working_dir.to_path_buf()])
+            .await;
+        for config in shared.extension_manager.extension_configs().await {
+            let name = config.name();
+            if let Err(e) = agent.add_extension(config).await {
+                tracing::warn!("Failed to start extension {} for the session: {}", name, e);
+            }
+        }
+        Ok(Arc::new(agent))
+    }
+
+    /// The session agents, by session id
+    pub async fn session_agents(&self) -> HashMap<String, SessionAgent> {
+        self.sessions.lock().await.clone()
+    }
+
+    /// Stop the agent of the session along with its extensions. Returns false when the session
+    /// had no agent of its own
+    pub async fn close_session_agent(&self, session_id: &str) -> bool {
+        let Some(session) = self.sessions.lock().await.remove(session_id) else {
+            return false;
+        };
+        session.cancel_reply().await;
+        session.agent.shutdown().await;
+        webhooks::notify(WebhookEvent::SessionEnd, session_webhook_data(session_id));
+        true
+    }
+
+    /// Stop the shared agent and the agents of all sessions, waiting a bounded time for the
+    /// session end webhooks since the server exits right after
+    pub async fn shutdown(&self) {
+        let sessions: Vec<(String, SessionAgent)> = self.sessions.lock().await.drain().collect();
+        let mut ended = Vec::new();
+        for (session_id, session) in sessions {
+            session.cancel_reply().await;
+            session.agent.shutdown().await;
+            ended.push(session_id);
+        }
+        futures::future::join_all(ended.iter().map(|session_id| {
+            webhooks::notify_and_wait(
+                WebhookEvent::SessionEnd,
+                session_webhook_data(session_id),
+                webhooks::SESSION_END_TIMEOUT,
+            )
+        }))
+        .await;
+        if let Some(agent) = &self.agent {
+            agent.shutdown().await;
+        }
+    }
+
     pub async fn set_scheduler(&self, sched: Arc<dyn SchedulerTrait>) {
         let mut guard = self.scheduler.lock().await;
         *guard = Some(sched);
@@ -40,3 +246,69 @@ impl AppState {
             .ok_or_else(|| anyhow::anyhow!("Scheduler not initialized"))
     }
 }
+
+fn session_webhook_data(session_id: &str) -> serde_json::Value {
+    serde_json::json!({"session_id": session_id, "mode": "server"})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_session_agents_are_separate() {
+        let state = AppState::new(Arc::new(Agent::new()), "test-secret".to_string()).await;
+        let dir = std::env::temp_dir();
+
+        let first = state
+            .session_agent("first", Some(dir.clone()))
+            .await
+            .unwrap();
+        let second = state.agent_for(Some("second")).await.unwrap();
+        let shared = state.agent_for(None).await.unwrap();
+        assert!(!Arc::ptr_eq(&first.agent, &second));
+        assert!(!Arc::ptr_eq(&first.agent, &shared));
+        assert_eq!(first.working_dir, dir);
+
+        let again = state.agent_for(Some("first")).await.unwrap();
+        assert!(Arc::ptr_eq(&first.agent, &again));
+
+        let token = first.start_reply().await;
+        assert!(state.close_session_agent("first").await);
+        assert!(token.is_cancelled());
+        assert!(!state.close_session_agent("first").await);
+        assert_eq!(state.session_agents().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unused_session_agents_are_stopped() {
+        let mut state = AppState::new(Arc::new(Agent::new()), "test-secret".to_string()).await;
+        Arc::get_mut(&mut state).unwrap().max_session_agents = 2;
+        let dir = std::env::temp_dir();
+
+        // Held as a request would, so kept past the cap
+        let first = state
+            .session_agent("first", Some(dir.clone()))
+            .await
+            .unwrap();
+        state
+            .session_agent("second", Some(dir.clone()))
+            .await
+            .unwrap();
+        state
+            .session_agent("third", Some(dir.clone()))
+            .await
+            .unwrap();
+        let sessions = state.session_agents().await;
+        assert!(sessions.contains_key("first"));
+        assert!(!sessions.contains_key("second"));
+        assert!(sessions.contains_key("third"));
+        drop(sessions);
+        drop(first);
+
+        Arc::get_mut(&mut state).unwrap().session_idle_ttl = Duration::ZERO;
+        state.session_agent("fourth", Some(dir)).await.unwrap();
+        let sessions = state.session_agents().await;
+        assert_eq!(sessions.keys().collect::<Vec<_>>(), vec!["fourth"]);
+    }
+}
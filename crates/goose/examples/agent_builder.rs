@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use dotenvy::dotenv;
+use goose::agents::{AgentBuilder, ExtensionConfig};
+use goose::config::{DEFAULT_EXTENSION_DESCRIPTION, DEFAULT_EXTENSION_TIMEOUT};
+use goose::permission::Permission;
+use goose::providers::databricks::DatabricksProvider;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Setup a model provider from env vars
+    let _ = dotenv();
+
+    // An agent with the developer extension that may read files but not change anything
+    let agent = AgentBuilder::new()
+        .provider(Arc::new(DatabricksProvider::default()))
+        .extension(
+            ExtensionConfig::stdio(
+                "developer",
+                "./target/debug/goose",
+                DEFAULT_EXTENSION_DESCRIPTION,
+                DEFAULT_EXTENSION_TIMEOUT,
+            )
+            .with_args(vec!["mcp", "developer"]),
+        )
+        .system_prompt_extra("Answer in a single haiku")
+        .goose_mode("approve")
+        .confirm_with(|request| {
+            let viewing = request.arguments.get("command").and_then(|c| c.as_str()) == Some("view");
+            if viewing {
+                Permission::AllowOnce
+            } else {
+                Permission::DenyOnce
+            }
+        })
+        .build()
+        .await?;
+
+    let result = agent
+        .run("Summarize the README.md in this directory")
+        .await?;
+    println!("{}", result.text());
+
+    // The conversation carries over to the next run
+    let result = agent.run("Now one about the license").await?;
+    println!("\n{}", result.text());
+    Ok(())
+}
@@ -1,20 +1,31 @@
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
 use futures::stream::BoxStream;
 use futures::{stream, FutureExt, Stream, StreamExt, TryStreamExt};
 use uuid::Uuid;
 
-use crate::agents::extension::{ExtensionConfig, ExtensionError, ExtensionResult, ToolInfo};
+use crate::agents::coverage_tool::{coverage_report_tool, COVERAGE_REPORT_TOOL_NAME};
+use crate::agents::extension::{
+    ExtensionConfig, ExtensionError, ExtensionResult, ExtensionToolStatus, ToolInfo,
+};
 use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
 use crate::agents::final_output_tool::{FINAL_OUTPUT_CONTINUATION_MESSAGE, FINAL_OUTPUT_TOOL_NAME};
+use crate::agents::hooks::{Hook, HookRegistry, ToolCallDecision};
+use crate::agents::plan_tools::{
+    plan_propose_tool, plan_update_step_tool, Plan, StepStatus, PLAN_PROPOSE_TOOL_NAME,
+    PLAN_UPDATE_STEP_TOOL_NAME,
+};
 use crate::agents::platform_tools::{
     PLATFORM_LIST_RESOURCES_TOOL_NAME, PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME,
     PLATFORM_MANAGE_SCHEDULE_TOOL_NAME, PLATFORM_READ_RESOURCE_TOOL_NAME,
-    PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME,
+    PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME, PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME,
 };
 use crate::agents::prompt_manager::PromptManager;
 use crate::agents::recipe_tools::dynamic_task_tools::{
@@ -29,20 +40,31 @@ use crate::agents::subagent_execution_tool::subagent_execute_task_tool::{
 use crate::agents::subagent_execution_tool::tasks_manager::TasksManager;
 use crate::agents::tool_route_manager::ToolRouteManager;
 use crate::agents::tool_router_index_manager::ToolRouterIndexManager;
-use crate::agents::types::SessionConfig;
-use crate::agents::types::{FrontendTool, ToolResultReceiver};
+use crate::agents::types::{ConfirmationTimeout, SessionConfig};
+use crate::agents::types::{ExtensionReload, FrontendTool, ReplyOutcome, ToolResultReceiver};
+use crate::config::permission::PermissionLevel;
 use crate::config::{Config, ExtensionConfigManager, PermissionManager};
+use crate::content_filter::{ContentFilter, ContentFilterPipeline, FilterTarget};
 use crate::context_mgmt::auto_compact;
+use crate::context_mgmt::{get_context_breakdown, ContextBreakdown};
 use crate::conversation::{debug_conversation_fix, fix_conversation, Conversation};
-use crate::permission::permission_judge::{check_tool_permissions, PermissionCheckResult};
-use crate::permission::PermissionConfirmation;
-use crate::providers::base::Provider;
+use crate::permission::permission_judge::{
+    check_tool_permissions, require_first_use_confirmation, PermissionCheckResult,
+    CONFIRM_FIRST_USE_TOOLS,
+};
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::providers::base::{MessageStream, Provider};
 use crate::providers::errors::ProviderError;
 use crate::recipe::{Author, Recipe, Response, Settings, SubRecipe};
 use crate::scheduler_trait::SchedulerTrait;
 use crate::session;
+use crate::session::journal::TurnJournal;
+use crate::session::Attachment;
+use crate::token_counter::create_async_token_counter_for_model;
 use crate::tool_monitor::{ToolCall, ToolMonitor};
+use crate::tracing::tool_calls;
 use crate::utils::is_token_cancelled;
+use crate::webhooks::{self, WebhookEvent};
 use mcp_core::ToolResult;
 use regex::Regex;
 use rmcp::model::{
@@ -51,16 +73,22 @@ use rmcp::model::{
 use serde_json::Value;
 use tokio::sync::{mpsc, Mutex};
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn, Instrument, Span};
 
 use super::final_output_tool::FinalOutputTool;
 use super::platform_tools;
-use super::tool_execution::{ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE};
+use super::reply_parts::generation_metadata;
+use super::tool_execution::{
+    ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE, TOOL_CANCELLED_RESPONSE,
+};
+use super::working_dir::SET_WORKING_DIR_TOOL_NAME;
 use crate::agents::subagent_task_config::TaskConfig;
 use crate::agents::todo_tools::{
-    todo_read_tool, todo_write_tool, TODO_READ_TOOL_NAME, TODO_WRITE_TOOL_NAME,
+    add_todo, parse_todo_content, render_todos, todo_add_tool, todo_complete_tool, todo_content,
+    todo_read_tool, todo_update_tool, todo_write_tool, TodoItem, TodoStatus, TODO_ADD_TOOL_NAME,
+    TODO_COMPLETE_TOOL_NAME, TODO_READ_TOOL_NAME, TODO_UPDATE_TOOL_NAME, TODO_WRITE_TOOL_NAME,
 };
-use crate::conversation::message::{Message, ToolRequest};
+use crate::conversation::message::{Message, MessageContent, ToolRequest};
 
 const DEFAULT_MAX_TURNS: u32 = 1000;
 
@@ -86,7 +114,7 @@ pub struct ToolCategorizeResult {
 /// The main goose Agent
 pub struct Agent {
     pub(super) provider: Mutex<Option<Arc<dyn Provider>>>,
-    pub extension_manager: ExtensionManager,
+    pub extension_manager: Arc<ExtensionManager>,
     pub(super) sub_recipe_manager: Mutex<SubRecipeManager>,
     pub(super) tasks_manager: TasksManager,
     pub(super) final_output_tool: Arc<Mutex<Option<FinalOutputTool>>>,
@@ -97,10 +125,31 @@ pub struct Agent {
     pub(super) confirmation_rx: Mutex<mpsc::Receiver<(String, PermissionConfirmation)>>,
     pub(super) tool_result_tx: mpsc::Sender<(String, ToolResult<Vec<Content>>)>,
     pub(super) tool_result_rx: ToolResultReceiver,
+    /// Cancelled when the frontend has gone away, so frontend tool calls fail instead of waiting
+    pub(super) frontend_gone: Mutex<CancellationToken>,
+    /// Filters added by the embedder, applied after the configured content filters
+    pub(super) content_filters: Mutex<Vec<Arc<dyn ContentFilter>>>,
+    /// Tools that need confirming on first use which the user has allowed in this session
+    pub(super) confirmed_tools: Mutex<HashSet<String>>,
+    /// Goose mode for this agent, overriding GOOSE_MODE
+    pub(super) goose_mode: Mutex<Option<String>>,
+    /// Lifecycle callbacks registered by the embedder
+    pub(super) hooks: Arc<HookRegistry>,
     pub(super) tool_monitor: Arc<Mutex<Option<ToolMonitor>>>,
     pub(super) tool_route_manager: ToolRouteManager,
     pub(super) scheduler_service: Mutex<Option<Arc<dyn SchedulerTrait>>>,
     pub(super) retry_manager: RetryManager,
+    pub(super) queued_messages: Mutex<Vec<Message>>,
+    pub(super) running_tools: RunningTools,
+    pub(super) plan: Mutex<Option<Plan>>,
+    /// Whether steps were started since the plan was last saved
+    pub(super) plan_progress_unsaved: AtomicBool,
+    pub(super) todos: Mutex<Vec<TodoItem>>,
+    /// The directory the session was switched to, in place of the one it started in
+    pub(super) working_dir: Mutex<Option<PathBuf>>,
+    /// Roots of the workspace declared for the session, on top of those of the project
+    pub(super) workspace_roots: Mutex<Vec<PathBuf>>,
+    pub(super) reply_outcome: Mutex<ReplyOutcome>,
 }
 
 #[derive(Clone, Debug)]
@@ -109,6 +158,10 @@ pub enum AgentEvent {
     McpNotification((String, ServerNotification)),
     ModelChange { model: String, mode: String },
     HistoryReplaced(Vec<Message>),
+    // The plan was proposed or one of its steps changed status
+    PlanUpdate(Plan),
+    // Items of the TODO list were added or changed
+    TodoUpdate(Vec<TodoItem>),
 }
 
 impl Default for Agent {
@@ -124,6 +177,21 @@ pub enum ToolStreamItem<T> {
 
 pub type ToolStream = Pin<Box<dyn Stream<Item = ToolStreamItem<ToolResult<Vec<Content>>>> + Send>>;
 
+/// The cancellation tokens of the tool calls that are running, by request id
+type RunningTools = Arc<std::sync::Mutex<HashMap<String, CancellationToken>>>;
+
+/// Forgets a running tool call once its result is dropped, whether it finished or was abandoned
+struct RunningToolGuard {
+    running_tools: RunningTools,
+    request_id: String,
+}
+
+impl Drop for RunningToolGuard {
+    fn drop(&mut self) {
+        self.running_tools.lock().unwrap().remove(&self.request_id);
+    }
+}
+
 // tool_stream combines a stream of ServerNotifications with a future representing the
 // final result of the tool call. MCP notifications are not request-scoped, but
 // this lets us capture all notifications emitted during the tool call for
@@ -162,7 +230,7 @@ impl Agent {
 
         Self {
             provider: Mutex::new(None),
-            extension_manager: ExtensionManager::new(),
+            extension_manager: Arc::new(ExtensionManager::new()),
             sub_recipe_manager: Mutex::new(SubRecipeManager::new()),
             tasks_manager: TasksManager::new(),
             final_output_tool: Arc::new(Mutex::new(None)),
@@ -173,10 +241,23 @@ impl Agent {
             confirmation_rx: Mutex::new(confirm_rx),
             tool_result_tx: tool_tx,
             tool_result_rx: Arc::new(Mutex::new(tool_rx)),
+            frontend_gone: Mutex::new(CancellationToken::new()),
+            content_filters: Mutex::new(Vec::new()),
+            confirmed_tools: Mutex::new(HashSet::new()),
+            goose_mode: Mutex::new(None),
+            hooks: Arc::new(HookRegistry::new()),
             tool_monitor,
             tool_route_manager: ToolRouteManager::new(),
             scheduler_service: Mutex::new(None),
             retry_manager,
+            queued_messages: Mutex::new(Vec::new()),
+            running_tools: RunningTools::default(),
+            plan: Mutex::new(None),
+            plan_progress_unsaved: AtomicBool::new(false),
+            todos: Mutex::new(Vec::new()),
+            working_dir: Mutex::new(None),
+            workspace_roots: Mutex::new(Vec::new()),
+            reply_outcome: Mutex::new(ReplyOutcome::default()),
         }
     }
 
@@ -209,14 +290,22 @@ impl Agent {
     ) -> Result<bool> {
         let result = self
             .retry_manager
-            .handle_retry_logic(messages, session, initial_messages, &self.final_output_tool)
+            .handle_retry_logic(
+                messages,
+                session,
+                initial_messages,
+                &self.final_output_tool,
+                self.provider().await.ok(),
+            )
             .await?;
 
         match result {
             RetryResult::Retried => Ok(true),
-            RetryResult::Skipped
-            | RetryResult::MaxAttemptsReached
-            | RetryResult::SuccessChecksPassed => Ok(false),
+            RetryResult::MaxAttemptsReached => {
+                *self.reply_outcome.lock().await = ReplyOutcome::SuccessChecksFailed;
+                Ok(false)
+            }
+            RetryResult::Skipped | RetryResult::SuccessChecksPassed => Ok(false),
         }
     }
 
@@ -241,7 +330,11 @@ impl Agent {
         let config = Config::global();
 
         let (tools, toolshim_tools, system_prompt) = self.prepare_tools_and_prompt().await?;
-        let goose_mode = Self::determine_goose_mode(session.as_ref(), config);
+        let goose_mode = Self::determine_goose_mode(
+            session.as_ref(),
+            config,
+            self.goose_mode.lock().await.clone(),
+        );
 
         Ok(ReplyContext {
             messages: conversation,
@@ -365,8 +458,28 @@ impl Agent {
         sub_recipe_manager.add_sub_recipe_tools(sub_recipes);
     }
 
+    /// Run the tool call hooks, which may rewrite the call. Call this before checking the
+    /// permission of the call, so the rewritten call is the one approved. Errors if a hook vetoed it
+    pub async fn run_tool_call_hooks(
+        &self,
+        request_id: &str,
+        tool_call: &mut mcp_core::tool::ToolCall,
+    ) -> Result<(), ErrorData> {
+        match self.hooks.tool_call(request_id, tool_call).await {
+            ToolCallDecision::Allow => Ok(()),
+            ToolCallDecision::Veto(reason) => Err(ErrorData::new(
+                ErrorCode::INVALID_REQUEST,
+                format!("Tool call vetoed by a hook: {}", reason),
+                None,
+            )),
+        }
+    }
+
     /// Dispatch a single tool call to the appropriate client
-    #[instrument(skip(self, tool_call, request_id), fields(input, output))]
+    #[instrument(
+        skip(self, tool_call, request_id),
+        fields(input, output, tool_call_id = %request_id, tool_name = %tool_call.name)
+    )]
     pub async fn dispatch_tool_call(
         &self,
         tool_call: mcp_core::tool::ToolCall,
@@ -433,6 +546,52 @@ impl Agent {
             };
         }
 
+        if tool_call.name == COVERAGE_REPORT_TOOL_NAME {
+            let result = self
+                .handle_coverage_tool(&tool_call.arguments, session)
+                .await;
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
+        if tool_call.name == SET_WORKING_DIR_TOOL_NAME {
+            let result = self.handle_set_working_dir(&tool_call.arguments).await;
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
+        if matches!(
+            tool_call.name.as_str(),
+            TODO_READ_TOOL_NAME
+                | TODO_WRITE_TOOL_NAME
+                | TODO_ADD_TOOL_NAME
+                | TODO_UPDATE_TOOL_NAME
+                | TODO_COMPLETE_TOOL_NAME
+        ) {
+            let result = self.handle_todo_tool(&tool_call, session).await;
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
+        if tool_call.name == PLAN_PROPOSE_TOOL_NAME || tool_call.name == PLAN_UPDATE_STEP_TOOL_NAME
+        {
+            let result = self.handle_plan_tool(&tool_call, session).await;
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+        self.track_plan_progress().await;
+
+        // Each tool call gets its own token, a child of the turn's token, so it can be cancelled
+        // on its own while cancelling the turn still stops every tool
+        let tool_token = cancellation_token
+            .as_ref()
+            .map(CancellationToken::child_token)
+            .unwrap_or_default();
+        self.running_tools
+            .lock()
+            .unwrap()
+            .insert(request_id.clone(), tool_token.clone());
+        let running_guard = RunningToolGuard {
+            running_tools: self.running_tools.clone(),
+            request_id: request_id.clone(),
+        };
+
         let sub_recipe_manager = self.sub_recipe_manager.lock().await;
         let result: ToolCallResult = if sub_recipe_manager.is_sub_recipe_tool(&tool_call.name) {
             sub_recipe_manager
@@ -450,28 +609,36 @@ impl Agent {
                 tool_call.arguments.clone(),
                 task_config,
                 &self.tasks_manager,
-                cancellation_token,
+                Some(tool_token.clone()),
             )
             .await
         } else if tool_call.name == DYNAMIC_TASK_TOOL_NAME_PREFIX {
-            create_dynamic_task(tool_call.arguments.clone(), &self.tasks_manager).await
+            let provider = self.provider().await.ok();
+
+            create_dynamic_task(
+                tool_call.arguments.clone(),
+                &self.tasks_manager,
+                TaskConfig::new(provider),
+                Some(tool_token.clone()),
+            )
+            .await
         } else if tool_call.name == PLATFORM_READ_RESOURCE_TOOL_NAME {
             // Check if the tool is read_resource and handle it separately
             ToolCallResult::from(
                 self.extension_manager
-                    .read_resource(
-                        tool_call.arguments.clone(),
-                        cancellation_token.unwrap_or_default(),
-                    )
+                    .read_resource(tool_call.arguments.clone(), tool_token.clone())
                     .await,
             )
         } else if tool_call.name == PLATFORM_LIST_RESOURCES_TOOL_NAME {
             ToolCallResult::from(
                 self.extension_manager
-                    .list_resources(
-                        tool_call.arguments.clone(),
-                        cancellation_token.unwrap_or_default(),
-                    )
+                    .list_resources(tool_call.arguments.clone(), tool_token.clone())
+                    .await,
+            )
+        } else if tool_call.name == PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME {
+            ToolCallResult::from(
+                self.extension_manager
+                    .manage_resource_subscription(tool_call.arguments.clone(), tool_token.clone())
                     .await,
             )
         } else if tool_call.name == PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME {
@@ -483,93 +650,6 @@ impl Agent {
                 "Frontend tool execution required".to_string(),
                 None,
             )))
-        } else if tool_call.name == TODO_READ_TOOL_NAME {
-            // Handle task planner read tool
-            let session_file_path = if let Some(session_config) = session {
-                session::storage::get_path(session_config.id.clone()).ok()
-            } else {
-                None
-            };
-
-            let todo_content = if let Some(path) = session_file_path {
-                session::storage::read_metadata(&path)
-                    .ok()
-                    .and_then(|m| m.todo_content)
-                    .unwrap_or_default()
-            } else {
-                String::new()
-            };
-
-            ToolCallResult::from(Ok(vec![Content::text(todo_content)]))
-        } else if tool_call.name == TODO_WRITE_TOOL_NAME {
-            // Handle task planner write tool
-            let content = tool_call
-                .arguments
-                .get("content")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            // Character limit validation
-            let char_count = content.chars().count();
-            let max_chars = std::env::var("GOOSE_TODO_MAX_CHARS")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(50_000);
-
-            if max_chars > 0 && char_count > max_chars {
-                ToolCallResult::from(Err(ErrorData::new(
-                    ErrorCode::INTERNAL_ERROR,
-                    format!(
-                        "Todo list too large: {} chars (max: {})",
-                        char_count, max_chars
-                    ),
-                    None,
-                )))
-            } else if let Some(session_config) = session {
-                // Update session metadata with new TODO content
-                match session::storage::get_path(session_config.id.clone()) {
-                    Ok(path) => match session::storage::read_metadata(&path) {
-                        Ok(mut metadata) => {
-                            metadata.todo_content = Some(content);
-                            let path_clone = path.clone();
-                            let metadata_clone = metadata.clone();
-                            let update_result = tokio::task::spawn(async move {
-                                session::storage::update_metadata(&path_clone, &metadata_clone)
-                                    .await
-                            })
-                            .await;
-
-                            match update_result {
-                                Ok(Ok(_)) => ToolCallResult::from(Ok(vec![Content::text(
-                                    format!("Updated ({} chars)", char_count),
-                                )])),
-                                _ => ToolCallResult::from(Err(ErrorData::new(
-                                    ErrorCode::INTERNAL_ERROR,
-                                    "Failed to update session metadata".to_string(),
-                                    None,
-                                ))),
-                            }
-                        }
-                        Err(_) => ToolCallResult::from(Err(ErrorData::new(
-                            ErrorCode::INTERNAL_ERROR,
-                            "Failed to read session metadata".to_string(),
-                            None,
-                        ))),
-                    },
-                    Err(_) => ToolCallResult::from(Err(ErrorData::new(
-                        ErrorCode::INTERNAL_ERROR,
-                        "Failed to get session path".to_string(),
-                        None,
-                    ))),
-                }
-            } else {
-                ToolCallResult::from(Err(ErrorData::new(
-                    ErrorCode::INTERNAL_ERROR,
-                    "TODO tools require an active session to persist data".to_string(),
-                    None,
-                )))
-            }
         } else if tool_call.name == ROUTER_LLM_SEARCH_TOOL_NAME {
             match self
                 .tool_route_manager
@@ -583,7 +663,7 @@ impl Agent {
             // Clone the result to ensure no references to extension_manager are returned
             let result = self
                 .extension_manager
-                .dispatch_tool_call(tool_call.clone(), cancellation_token.unwrap_or_default())
+                .dispatch_tool_call(tool_call.clone(), tool_token.clone())
                 .await;
             result.unwrap_or_else(|e| {
                 ToolCallResult::from(Err(ErrorData::new(
@@ -594,19 +674,341 @@ impl Agent {
             })
         };
 
+        let output = result
+            .result
+            .map(super::large_response_handler::process_tool_response);
+        let output = async move {
+            let _running_guard = running_guard;
+            let output = tokio::select! {
+                biased;
+                output = output => output,
+                _ = tool_token.cancelled() => Err(ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    TOOL_CANCELLED_RESPONSE.to_string(),
+                    None,
+                )),
+            };
+            if let Err(e) = &output {
+                warn!(error = %e.message, "Tool call failed");
+            }
+            output
+        };
+        // The tool runs after this returns, keep it in this span and tagged with its id
+        let output =
+            tool_calls::with_tool_call_id(request_id.clone(), output).instrument(Span::current());
+
         (
             request_id,
             Ok(ToolCallResult {
                 notification_stream: result.notification_stream,
-                result: Box::new(
-                    result
-                        .result
-                        .map(super::large_response_handler::process_tool_response),
-                ),
+                result: Box::new(Box::pin(output)),
             }),
         )
     }
 
+    /// Cancel the tool calls that are currently running without stopping the turn, the model
+    /// gets a cancellation error for each of them. Returns how many were cancelled.
+    pub async fn cancel_running_tools(&self) -> usize {
+        let running_tools = self.running_tools.lock().unwrap();
+        for token in running_tools.values() {
+            token.cancel();
+        }
+        running_tools.len()
+    }
+
+    /// How the last reply ended
+    pub async fn reply_outcome(&self) -> ReplyOutcome {
+        *self.reply_outcome.lock().await
+    }
+
+    /// The structured output recorded by the final output tool, once the agent called it
+    pub async fn final_output(&self) -> Option<String> {
+        self.final_output_tool
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|tool| tool.final_output.clone())
+    }
+
+    /// The plan the agent is working through, if any
+    pub async fn plan(&self) -> Option<Plan> {
+        self.plan.lock().await.clone()
+    }
+
+    /// Replace the current plan, saving it in the session metadata when there is a session
+    pub async fn set_plan(
+        &self,
+        plan: Option<Plan>,
+        session: &Option<SessionConfig>,
+    ) -> Result<()> {
+        *self.plan.lock().await = plan.clone();
+        Self::persist_plan(session, plan).await
+    }
+
+    async fn persist_plan(session: &Option<SessionConfig>, plan: Option<Plan>) -> Result<()> {
+        let Some(session_config) = session else {
+            return Ok(());
+        };
+        let path = session::storage::get_path(session_config.id.clone())?;
+        let mut metadata = session::storage::read_metadata(&path)?;
+        metadata.plan = plan;
+        session::storage::update_metadata(&path, &metadata).await
+    }
+
+    /// The session's TODO list
+    pub async fn todos(&self) -> Vec<TodoItem> {
+        self.todos.lock().await.clone()
+    }
+
+    /// Save the TODO list in the session, along with its freeform `content` for readers of
+    /// the old format
+    async fn persist_todos(
+        session: &Option<SessionConfig>,
+        todos: Vec<TodoItem>,
+        content: String,
+    ) -> Result<()> {
+        let Some(session_config) = session else {
+            return Ok(());
+        };
+        let path = session::storage::get_path(session_config.id.clone())?;
+        let mut metadata = session::storage::read_metadata(&path)?;
+        metadata.todos = todos;
+        metadata.todo_content = Some(content);
+        session::storage::update_metadata(&path, &metadata).await
+    }
+
+    /// Pick up the plan and TODO list saved in the session so progress carries over between
+    /// replies, converting a TODO list written in the old freeform format
+    async fn load_session_state(&self, session: &Option<SessionConfig>) {
+        let Some(session_config) = session else {
+            return;
+        };
+        let metadata = session::storage::get_path(session_config.id.clone())
+            .ok()
+            .and_then(|path| session::storage::read_metadata(&path).ok())
+            .unwrap_or_default();
+
+        let todos = if metadata.todos.is_empty() {
+            metadata
+                .todo_content
+                .as_deref()
+                .map(parse_todo_content)
+                .unwrap_or_default()
+        } else {
+            metadata.todos
+        };
+        *self.plan.lock().await = metadata.plan;
+        *self.todos.lock().await = todos;
+    }
+
+    async fn handle_todo_tool(
+        &self,
+        tool_call: &mcp_core::tool::ToolCall,
+        session: &Option<SessionConfig>,
+    ) -> Result<Vec<Content>, ErrorData> {
+        let arguments = &tool_call.arguments;
+        let invalid_params =
+            |message: String| ErrorData::new(ErrorCode::INVALID_PARAMS, message, None);
+        let string_argument = |value: &Value, key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+
+        let mut todos = self.todos.lock().await;
+        if tool_call.name == TODO_READ_TOOL_NAME {
+            return Ok(vec![Content::text(render_todos(&todos))]);
+        }
+        if session.is_none() {
+            return Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                "TODO tools require an active session to persist data".to_string(),
+                None,
+            ));
+        }
+        // What todo__write was given is kept as written
+        let mut written_content = None;
+        match tool_call.name.as_str() {
+            TODO_WRITE_TOOL_NAME => {
+                let content = arguments
+                    .get("content")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+
+                // Character limit validation
+                let char_count = content.chars().count();
+                let max_chars = std::env::var("GOOSE_TODO_MAX_CHARS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(50_000);
+                if max_chars > 0 && char_count > max_chars {
+                    return Err(ErrorData::new(
+                        ErrorCode::INTERNAL_ERROR,
+                        format!(
+                            "Todo list too large: {} chars (max: {})",
+                            char_count, max_chars
+                        ),
+                        None,
+                    ));
+                }
+                *todos = parse_todo_content(content);
+                written_content = Some(content.to_string());
+            }
+            TODO_ADD_TOOL_NAME => {
+                let new_items: Vec<&Value> = arguments
+                    .get("items")
+                    .and_then(|v| v.as_array())
+                    .map(|items| items.iter().collect())
+                    .unwrap_or_default();
+                let mut added = 0;
+                for item in new_items {
+                    if let Some(title) = string_argument(item, "title") {
+                        add_todo(
+                            &mut todos,
+                            title,
+                            string_argument(item, "owner"),
+                            string_argument(item, "notes"),
+                        );
+                        added += 1;
+                    }
+                }
+                if added == 0 {
+                    return Err(invalid_params("No items with a title to add".to_string()));
+                }
+            }
+            _ => {
+                let id = arguments
+                    .get("id")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| invalid_params("Missing item id".to_string()))?;
+                let status = if tool_call.name == TODO_COMPLETE_TOOL_NAME {
+                    Some(TodoStatus::Completed)
+                } else {
+                    match arguments.get("status").and_then(|v| v.as_str()) {
+                        Some(status) => Some(TodoStatus::parse(status).ok_or_else(|| {
+                            invalid_params(format!("Unknown status '{}'", status))
+                        })?),
+                        None => None,
+                    }
+                };
+                let item = todos
+                    .iter_mut()
+                    .find(|item| u64::from(item.id) == id)
+                    .ok_or_else(|| {
+                        invalid_params(format!("There is no TODO item with id {}", id))
+                    })?;
+
+                if let Some(status) = status {
+                    item.status = status;
+                }
+                if let Some(title) = string_argument(arguments, "title") {
+                    item.title = title;
+                }
+                if let Some(owner) = string_argument(arguments, "owner") {
+                    item.owner = Some(owner);
+                }
+                if let Some(notes) = string_argument(arguments, "notes") {
+                    item.notes = Some(notes);
+                }
+            }
+        }
+        let items = todos.clone();
+        drop(todos);
+
+        let content = written_content.unwrap_or_else(|| todo_content(&items));
+        if let Err(e) = Self::persist_todos(session, items.clone(), content).await {
+            tracing::warn!("Failed to save TODO list in session metadata: {}", e);
+        }
+        Ok(vec![Content::text(render_todos(&items))])
+    }
+
+    async fn handle_plan_tool(
+        &self,
+        tool_call: &mcp_core::tool::ToolCall,
+        session: &Option<SessionConfig>,
+    ) -> Result<Vec<Content>, ErrorData> {
+        let arguments = &tool_call.arguments;
+        let invalid_params =
+            |message: String| ErrorData::new(ErrorCode::INVALID_PARAMS, message, None);
+
+        let mut current = self.plan.lock().await;
+        let plan = if tool_call.name == PLAN_PROPOSE_TOOL_NAME {
+            let steps: Vec<String> = arguments
+                .get("steps")
+                .and_then(|v| v.as_array())
+                .map(|steps| {
+                    steps
+                        .iter()
+                        .filter_map(|step| step.as_str())
+                        .map(|step| step.trim().to_string())
+                        .filter(|step| !step.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default();
+            if steps.is_empty() {
+                return Err(invalid_params("A plan needs at least one step".to_string()));
+            }
+            let title = arguments
+                .get("title")
+                .and_then(|v| v.as_str())
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or("Plan");
+            Plan::new(title.trim(), steps)
+        } else {
+            let mut plan = current.clone().ok_or_else(|| {
+                invalid_params(format!(
+                    "There is no plan to update, propose one with {} first",
+                    PLAN_PROPOSE_TOOL_NAME
+                ))
+            })?;
+            let step = arguments
+                .get("step")
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| invalid_params("Missing step number".to_string()))?;
+            let status = arguments
+                .get("status")
+                .and_then(|v| v.as_str())
+                .and_then(StepStatus::parse)
+                .ok_or_else(|| invalid_params("Missing or unknown step status".to_string()))?;
+            let note = arguments
+                .get("note")
+                .and_then(|v| v.as_str())
+                .map(|note| note.to_string());
+            plan.update_step(step as usize, status, note)
+                .map_err(invalid_params)?;
+            plan
+        };
+        *current = Some(plan.clone());
+        drop(current);
+
+        if let Err(e) = Self::persist_plan(session, Some(plan.clone())).await {
+            tracing::warn!("Failed to save plan in session metadata: {}", e);
+        }
+        Ok(vec![Content::text(plan.render())])
+    }
+
+    /// Mark the next pending step of the plan as in progress when the agent starts using
+    /// tools and no step is in progress
+    async fn track_plan_progress(&self) {
+        if let Some(plan) = self.plan.lock().await.as_mut() {
+            if plan.start_next_step() {
+                self.plan_progress_unsaved.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Save the step progress tracked during the turn, once its tool calls are done
+    async fn save_plan_progress(&self, session: &Option<SessionConfig>) {
+        if !self.plan_progress_unsaved.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        if let Err(e) = Self::persist_plan(session, self.plan().await).await {
+            tracing::warn!("Failed to save plan in session metadata: {}", e);
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     pub(super) async fn manage_extensions(
         &self,
@@ -796,11 +1198,27 @@ impl Agent {
             ]);
 
             // Add task planner tools
-            prefixed_tools.extend([todo_read_tool(), todo_write_tool()]);
+            prefixed_tools.extend([
+                todo_read_tool(),
+                todo_write_tool(),
+                todo_add_tool(),
+                todo_update_tool(),
+                todo_complete_tool(),
+                plan_propose_tool(),
+                plan_update_step_tool(),
+            ]);
 
             // Dynamic task tool
             prefixed_tools.push(create_dynamic_task_tool());
 
+            // Coverage reporting, when enabled
+            if Config::global()
+                .get_param::<bool>("GOOSE_COVERAGE_TOOL")
+                .unwrap_or(false)
+            {
+                prefixed_tools.push(coverage_report_tool());
+            }
+
             // Add resource tools if supported
             if self.extension_manager.supports_resources().await {
                 prefixed_tools.extend([
@@ -808,6 +1226,13 @@ impl Agent {
                     platform_tools::list_resources_tool(),
                 ]);
             }
+            if self
+                .extension_manager
+                .supports_resource_subscriptions()
+                .await
+            {
+                prefixed_tools.push(platform_tools::subscribe_resource_tool());
+            }
         }
 
         if extension_name.is_none() {
@@ -829,6 +1254,39 @@ impl Agent {
             .await
     }
 
+    /// Add an extension from the extension config, which reloading the config may later stop
+    /// or restart. Extensions added with `add_extension` are the session's own and are left
+    /// alone by reloads
+    pub async fn add_configured_extension(
+        &self,
+        extension: ExtensionConfig,
+    ) -> ExtensionResult<()> {
+        let name = extension.name();
+        self.add_extension(extension).await?;
+        self.extension_manager.mark_from_config(&name).await;
+        Ok(())
+    }
+
+    /// Re-index the tools of an extension whose tools may have changed, when LLM tool selection
+    /// is in use
+    async fn index_extension_tools(&self, name: &str) {
+        if !self.tool_route_manager.is_router_functional().await {
+            return;
+        }
+        if let Some(selector) = self.tool_route_manager.get_router_tool_selector().await {
+            if let Err(e) = ToolRouterIndexManager::update_extension_tools(
+                &selector,
+                &self.extension_manager,
+                name,
+                "add",
+            )
+            .await
+            {
+                warn!("Failed to index tools for extension {}: {}", name, e);
+            }
+        }
+    }
+
     pub async fn remove_extension(&self, name: &str) -> Result<()> {
         self.extension_manager.remove_extension(name).await?;
 
@@ -849,6 +1307,84 @@ impl Agent {
         Ok(())
     }
 
+    /// Bring the running extensions in line with the extension config: start newly enabled
+    /// ones, restart the ones whose config changed and stop disabled or removed ones. Tools
+    /// and the system prompt pick up the changes on the next reply
+    pub async fn reload_extensions(&self) -> Result<ExtensionReload> {
+        let entries = ExtensionConfigManager::get_all()?;
+        let diff = self.extension_manager.diff_with_config(&entries).await;
+        let mut reload = ExtensionReload::default();
+
+        for name in diff.to_stop {
+            self.remove_extension(&name).await?;
+            reload.stopped.push(name);
+        }
+        for config in diff.to_restart {
+            let name = config.name();
+            // The running instance stays up when the new one can't start
+            match self.extension_manager.reconfigure_extension(config).await {
+                Ok(()) => {
+                    self.index_extension_tools(&name).await;
+                    reload.restarted.push(name)
+                }
+                Err(e) => reload.failed.push((name, e.to_string())),
+            }
+        }
+        for config in diff.to_start {
+            let name = config.name();
+            match self.add_configured_extension(config).await {
+                Ok(()) => reload.started.push(name),
+                Err(e) => reload.failed.push((name, e.to_string())),
+            }
+        }
+
+        Ok(reload)
+    }
+
+    /// The tools of a running extension, including those its allowlist disables
+    pub async fn list_extension_tools(&self, name: &str) -> Result<Vec<ExtensionToolStatus>> {
+        let config = self.extension_manager.get_extension_config(name).await;
+        let tools = self.extension_manager.list_all_tools(name).await?;
+        Ok(tools
+            .into_iter()
+            .map(|tool| ExtensionToolStatus {
+                enabled: config
+                    .as_ref()
+                    .is_none_or(|config| config.is_tool_available(&tool.name)),
+                description: tool.description.unwrap_or_default().to_string(),
+                name: tool.name.to_string(),
+            })
+            .collect())
+    }
+
+    /// Enable or disable one tool of a running extension, for this session and in its config
+    pub async fn set_extension_tool_enabled(
+        &self,
+        name: &str,
+        tool: &str,
+        enabled: bool,
+    ) -> Result<()> {
+        let config = self
+            .extension_manager
+            .get_extension_config(name)
+            .await
+            .ok_or_else(|| anyhow!("{} is not running", name))?;
+        let all_tools: Vec<String> = self
+            .extension_manager
+            .list_all_tools(name)
+            .await?
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        let allowed = config.toggle_tool(&all_tools, tool, enabled)?;
+
+        self.extension_manager
+            .set_available_tools(name, allowed.clone())
+            .await?;
+        ExtensionConfigManager::set_available_tools(&config.key(), allowed)?;
+        Ok(())
+    }
+
     pub async fn list_extensions(&self) -> Vec<String> {
         self.extension_manager
             .list_extensions()
@@ -922,6 +1458,20 @@ impl Agent {
         Ok(None)
     }
 
+    /// Remove the last exchange from the conversation, see [`Conversation::undo_last_exchange`].
+    /// Returns the removed messages with the event that resyncs UIs with the shortened history,
+    /// or `None` when there was nothing to undo
+    pub fn undo_last_exchange(
+        conversation: &mut Conversation,
+    ) -> Option<(Vec<Message>, AgentEvent)> {
+        let removed = conversation.undo_last_exchange();
+        if removed.is_empty() {
+            return None;
+        }
+        let event = AgentEvent::HistoryReplaced(conversation.messages().clone());
+        Some((removed, event))
+    }
+
     #[instrument(skip(self, unfixed_conversation, session), fields(user_message))]
     pub async fn reply(
         &self,
@@ -929,6 +1479,16 @@ impl Agent {
         session: Option<SessionConfig>,
         cancel_token: Option<CancellationToken>,
     ) -> Result<BoxStream<'_, Result<AgentEvent>>> {
+        self.extension_manager.supervise().await;
+        if let Some(session) = &session {
+            let working_dir = self
+                .working_dir()
+                .await
+                .unwrap_or_else(|| session.working_dir.clone());
+            self.share_roots(&working_dir).await;
+            self.tool_route_manager.set_project(&working_dir).await;
+        }
+
         // Handle auto-compaction before processing
         let (messages, compaction_msg, _summarization_usage) = match self
             .handle_auto_compaction(unfixed_conversation.messages(), &session)
@@ -943,9 +1503,13 @@ impl Agent {
             }
         };
 
+        let journal_file = session
+            .as_ref()
+            .and_then(|session| session::storage::get_path(session.id.clone()).ok());
+
         // If we compacted, yield the compaction message and history replacement event
         if let Some(compaction_msg) = compaction_msg {
-            return Ok(Box::pin(async_stream::try_stream! {
+            return Ok(self.with_message_hooks(Self::with_turn_journal(journal_file, Box::pin(async_stream::try_stream! {
                 yield AgentEvent::Message(Message::assistant().with_summarization_requested(compaction_msg));
                 yield AgentEvent::HistoryReplaced(messages.messages().clone());
 
@@ -954,11 +1518,50 @@ impl Agent {
                 while let Some(event) = reply_stream.next().await {
                     yield event?;
                 }
-            }));
+            }))));
         }
 
         // No compaction needed, proceed with normal processing
-        self.reply_internal(messages, session, cancel_token).await
+        let stream = self.reply_internal(messages, session, cancel_token).await?;
+        Ok(self.with_message_hooks(Self::with_turn_journal(journal_file, stream)))
+    }
+
+    /// Run the message hooks on every message of the reply as it goes to the frontend
+    fn with_message_hooks<'a>(
+        &self,
+        stream: BoxStream<'a, Result<AgentEvent>>,
+    ) -> BoxStream<'a, Result<AgentEvent>> {
+        let hooks = self.hooks.clone();
+        Box::pin(stream.then(move |event| {
+            let hooks = hooks.clone();
+            async move {
+                if let Ok(AgentEvent::Message(message)) = &event {
+                    hooks.message(message).await;
+                }
+                event
+            }
+        }))
+    }
+
+    /// Keep a journal of the turn next to the session file while the reply streams, so a turn
+    /// cut short by a crash can be recovered when the session is resumed. The frontend removes it
+    /// with `session::journal::commit` once it saved the messages
+    fn with_turn_journal<'a>(
+        session_file: Option<PathBuf>,
+        mut stream: BoxStream<'a, Result<AgentEvent>>,
+    ) -> BoxStream<'a, Result<AgentEvent>> {
+        let Some(session_file) = session_file else {
+            return stream;
+        };
+        Box::pin(async_stream::stream! {
+            let mut journal = TurnJournal::begin(&session_file);
+            while let Some(event) = stream.next().await {
+                if let Ok(AgentEvent::Message(message)) = &event {
+                    journal.record(message);
+                }
+                yield event;
+            }
+        })
     }
 
     /// Main reply method that handles the actual agent processing
@@ -969,6 +1572,7 @@ impl Agent {
         cancel_token: Option<CancellationToken>,
     ) -> Result<BoxStream<'_, Result<AgentEvent>>> {
         let context = self.prepare_reply_context(messages, &session).await?;
+        self.load_session_state(&session).await;
         let ReplyContext {
             mut messages,
             mut tools,
@@ -980,6 +1584,7 @@ impl Agent {
         } = context;
         let reply_span = tracing::Span::current();
         self.reset_retry_attempts().await;
+        *self.reply_outcome.lock().await = ReplyOutcome::Completed;
 
         if let Some(content) = messages
             .last()
@@ -992,6 +1597,12 @@ impl Agent {
         Ok(Box::pin(async_stream::try_stream! {
             let _ = reply_span.enter();
             let mut turns_taken = 0u32;
+            let mut context_compacted = false;
+            let confirmation_timeout = ConfirmationTimeout::resolve(session.as_ref());
+            let content_filters = ContentFilterPipeline::from_config()
+                .with_filters(self.content_filters.lock().await.iter().cloned());
+            // Ideally the provider would know its own name, it is only in the config
+            let provider_name: Option<String> = config.get_param("GOOSE_PROVIDER").ok();
             let max_turns = session
                 .as_ref()
                 .and_then(|s| s.max_turns)
@@ -1016,23 +1627,56 @@ impl Agent {
 
                 turns_taken += 1;
                 if turns_taken > max_turns {
+                    *self.reply_outcome.lock().await = ReplyOutcome::MaxTurnsReached;
+                    webhooks::notify(WebhookEvent::BudgetExceeded, serde_json::json!({"budget": "max_turns", "limit": max_turns}));
                     yield AgentEvent::Message(Message::assistant().with_text(
                         "I've reached the maximum number of actions I can do without user input. Would you like me to continue?"
                     ));
                     break;
                 }
+                // Hook context goes in the system prompt of this turn only, not in the conversation
+                let turn_context = self.hooks.turn_start(turns_taken, session.as_ref()).await;
+                let turn_system_prompt = if turn_context.is_empty() {
+                    system_prompt.clone()
+                } else {
+                    format!("{}\n\n# Context for this turn\n\n{}", system_prompt, turn_context.join("\n\n"))
+                };
 
-                let mut stream = Self::stream_response_from_provider(
+                for (extension_name, notification) in self.extension_manager.take_recovery_notifications().await {
+                    yield AgentEvent::McpNotification((extension_name, notification));
+                }
+
+                // All the changes since the last turn go in one message
+                let updates = self.extension_manager.take_resource_updates().await;
+                for update in &updates {
+                    yield AgentEvent::McpNotification((update.extension.clone(), update.notification()));
+                }
+                if !updates.is_empty() {
+                    let text = updates.iter().map(|update| update.message_text()).collect::<Vec<_>>().join("\n\n");
+                    let message = Message::user().with_text(text);
+                    messages.push(message.clone());
+                    yield AgentEvent::Message(message);
+                }
+
+                let request_started = Instant::now();
+                let mut stream = match Self::stream_response_from_provider(
                     self.provider().await?,
-                    &system_prompt,
+                    &turn_system_prompt,
                     messages.messages(),
                     &tools,
                     &toolshim_tools,
-                ).await?;
+                ).await {
+                    // Handled with the errors of the stream, where it can be recovered from
+                    Err(ProviderError::ContextLengthExceeded(msg)) => {
+                        Box::pin(stream::once(async move { Err(ProviderError::ContextLengthExceeded(msg)) })) as MessageStream
+                    }
+                    result => result?,
+                };
 
                 let mut added_message = false;
                 let mut messages_to_add = Vec::new();
                 let mut tools_updated = false;
+                let mut retry_after_compaction = false;
 
                 while let Some(next) = stream.next().await {
                     if is_token_cancelled(&cancel_token) {
@@ -1041,6 +1685,22 @@ impl Agent {
 
                     match next {
                         Ok((response, usage)) => {
+                            let response = match (response, &usage) {
+                                (Some(response), Some(usage)) => {
+                                    let metadata = generation_metadata(provider_name.clone(), usage, request_started.elapsed(), Some(&response));
+                                    Some(response.with_metadata(metadata))
+                                }
+                                (None, Some(usage)) => {
+                                    // Streamed usage arrives after the content it belongs to
+                                    if let Some(last) = messages_to_add.iter_mut().rev().find(|m: &&mut Message| m.role == rmcp::model::Role::Assistant) {
+                                        let metadata = generation_metadata(provider_name.clone(), usage, request_started.elapsed(), Some(&*last));
+                                        last.metadata = Some(metadata);
+                                    }
+                                    None
+                                }
+                                (response, None) => response,
+                            };
+
                             // Emit model change event if provider is lead-worker
                             let provider = self.provider().await?;
                             if let Some(lead_worker) = provider.as_lead_worker() {
@@ -1065,12 +1725,13 @@ impl Agent {
                             // Record usage for the session
                             if let Some(ref session_config) = &session {
                                 if let Some(ref usage) = usage {
-                                    Self::update_session_metrics(session_config, usage, messages.len())
+                                    Self::update_session_metrics(session_config, usage, request_started.elapsed(), messages.len())
                                         .await?;
                                 }
                             }
 
                             if let Some(response) = response {
+                                let response = content_filters.filter_message(response, FilterTarget::AssistantMessage).await;
                                 let ToolCategorizeResult {
                                     frontend_requests,
                                     remaining_requests,
@@ -1088,9 +1749,12 @@ impl Agent {
 
                                 let num_tool_requests = frontend_requests.len() + remaining_requests.len();
                                 if num_tool_requests == 0 {
+                                    push_response_chunk(&mut messages_to_add, filtered_response);
                                     continue;
                                 }
 
+                                let plan_before = self.plan().await;
+                                let todos_before = self.todos().await;
                                 let message_tool_response = Arc::new(Mutex::new(Message::user().with_id(
                                     format!("msg_{}", Uuid::new_v4())
                                 )));
@@ -1115,16 +1779,33 @@ impl Agent {
                                         );
                                     }
                                 } else {
+                                    // The hooks see each call before its permission is checked
+                                    let mut hooked_requests = Vec::with_capacity(remaining_requests.len());
+                                    for mut request in remaining_requests {
+                                        if let Ok(tool_call) = request.tool_call.as_mut() {
+                                            if let Err(e) = self.run_tool_call_hooks(&request.id, tool_call).await {
+                                                let mut response = message_tool_response.lock().await;
+                                                *response = response.clone().with_tool_response(request.id.clone(), Err(e));
+                                                continue;
+                                            }
+                                        }
+                                        hooked_requests.push(request);
+                                    }
+
                                     let mut permission_manager = PermissionManager::default();
-                                    let (permission_check_result, enable_extension_request_ids) =
+                                    let (mut permission_check_result, enable_extension_request_ids) =
                                         check_tool_permissions(
-                                            &remaining_requests,
+                                            &hooked_requests,
                                             &mode,
                                             readonly_tools.clone(),
                                             regular_tools.clone(),
                                             &mut permission_manager,
                                             self.provider().await?,
                                         ).await;
+                                    require_first_use_confirmation(
+                                        &mut permission_check_result,
+                                        &*self.confirmed_tools.lock().await,
+                                    );
 
                                     let mut tool_futures = self.handle_approved_and_denied_tools(
                                         &permission_check_result,
@@ -1142,6 +1823,7 @@ impl Agent {
                                         &mut permission_manager,
                                         message_tool_response.clone(),
                                         cancel_token.clone(),
+                                        confirmation_timeout,
                                     );
 
                                     while let Some(msg) = tool_approval_stream.try_next().await? {
@@ -1174,6 +1856,7 @@ impl Agent {
                                                 {
                                                     all_install_successful = false;
                                                 }
+                                                self.hooks.tool_result(&request_id, &output).await;
                                                 let mut response = message_tool_response.lock().await;
                                                 *response =
                                                     response.clone().with_tool_response(request_id, output);
@@ -1191,7 +1874,21 @@ impl Agent {
                                     }
                                 }
 
-                                let final_message_tool_resp = message_tool_response.lock().await.clone();
+                                self.save_plan_progress(&session).await;
+                                let plan = self.plan().await;
+                                if plan != plan_before {
+                                    if let Some(plan) = plan {
+                                        yield AgentEvent::PlanUpdate(plan);
+                                    }
+                                }
+                                let todos = self.todos().await;
+                                if todos != todos_before {
+                                    yield AgentEvent::TodoUpdate(todos);
+                                }
+
+                                let final_message_tool_resp = content_filters
+                                    .filter_message(message_tool_response.lock().await.clone(), FilterTarget::ToolOutput)
+                                    .await;
                                 yield AgentEvent::Message(final_message_tool_resp.clone());
 
                                 added_message = true;
@@ -1200,6 +1897,28 @@ impl Agent {
                             }
                         }
                         Err(ProviderError::ContextLengthExceeded(_)) => {
+                            // Compact once and retry the turn, a second overflow means the
+                            // summary did not shrink the context enough
+                            if !context_compacted {
+                                context_compacted = true;
+                                match auto_compact::compact_messages(self, messages.messages()).await {
+                                    // Without a summary the history is the same and would overflow again
+                                    Ok(result) if result.compacted && result.summarization_usage.is_some() => {
+                                        yield AgentEvent::Message(Message::assistant().with_summarization_requested(
+                                            "The context length of the model was exceeded. Context has been summarized and reduced.\n\n",
+                                        ));
+                                        yield AgentEvent::HistoryReplaced(result.messages.messages().clone());
+                                        messages = result.messages;
+                                        messages_to_add.clear();
+                                        retry_after_compaction = true;
+                                        break;
+                                    }
+                                    Ok(_) => warn!("Compaction after exceeding the context length produced no summary"),
+                                    Err(e) => warn!("Compaction after exceeding the context length failed: {}", e),
+                                }
+                            }
+                            // The run could not go on, headless callers must not see it as a success
+                            *self.reply_outcome.lock().await = ReplyOutcome::ProviderError;
                             yield AgentEvent::Message(Message::assistant().with_context_length_exceeded(
                                     "The context length of the model has been exceeded. Please start a new session and try again.",
                                 ));
@@ -1207,6 +1926,7 @@ impl Agent {
                         }
                         Err(e) => {
                             error!("Error: {}", e);
+                            *self.reply_outcome.lock().await = ReplyOutcome::ProviderError;
                             yield AgentEvent::Message(Message::assistant().with_text(
                                     format!("Ran into this error: {e}.\n\nPlease retry if you think this is a transient or recoverable error.")
                                 ));
@@ -1214,10 +1934,24 @@ impl Agent {
                         }
                     }
                 }
+                if retry_after_compaction {
+                    continue;
+                }
                 if tools_updated {
                     (tools, toolshim_tools, system_prompt) = self.prepare_tools_and_prompt().await?;
                 }
                 if !added_message {
+                    // Messages queued while the model was answering keep the turn going
+                    let queued = self.take_queued_messages().await;
+                    if !queued.is_empty() {
+                        messages.extend(messages_to_add);
+                        for message in queued {
+                            messages.push(message.clone());
+                            yield AgentEvent::Message(message);
+                        }
+                        continue;
+                    }
+
                     if let Some(final_output_tool) = self.final_output_tool.lock().await.as_ref() {
                         if final_output_tool.final_output.is_none() {
                             tracing::warn!("Final output tool has not been called yet. Continuing agent loop.");
@@ -1251,20 +1985,96 @@ impl Agent {
 
                 messages.extend(messages_to_add);
 
+                // Tool results are in, so this is a safe point to add queued user messages
+                for message in self.take_queued_messages().await {
+                    messages.push(message.clone());
+                    yield AgentEvent::Message(message);
+                }
+
                 tokio::task::yield_now().await;
             }
         }))
     }
 
-    fn determine_goose_mode(session: Option<&SessionConfig>, config: &Config) -> String {
+    fn determine_goose_mode(
+        session: Option<&SessionConfig>,
+        config: &Config,
+        agent_mode: Option<String>,
+    ) -> String {
         let mode = session.and_then(|s| s.execution_mode.as_deref());
 
-        match mode {
-            Some("foreground") => "chat".to_string(),
-            Some("background") => "auto".to_string(),
+        let mode = match (mode, agent_mode) {
+            (Some("foreground"), _) => "chat".to_string(),
+            (Some("background"), _) => "auto".to_string(),
+            (_, Some(agent_mode)) => agent_mode,
             _ => config
                 .get_param("GOOSE_MODE")
                 .unwrap_or_else(|_| "auto".to_string()),
+        };
+        match config.policy() {
+            Some(policy) => policy.enforce_goose_mode(&mode).to_string(),
+            None => mode,
+        }
+    }
+
+    /// Use this goose mode for the agent's replies instead of the configured GOOSE_MODE, or go
+    /// back to the configured one with `None`. A policy's minimum mode still applies
+    pub async fn set_goose_mode(&self, mode: Option<String>) {
+        *self.goose_mode.lock().await = mode;
+    }
+
+    /// Check a tool call the user asked for outside of a reply, like the tool calls of a custom
+    /// command, the way the calls of the model are checked: by goose mode, stored permission and
+    /// first use confirmation. `AskBefore` means the user has to approve it first, which is then
+    /// recorded with `record_tool_approval`
+    pub async fn check_tool_permission(
+        &self,
+        tool_call: &mcp_core::tool::ToolCall,
+        session: &Option<SessionConfig>,
+    ) -> Result<PermissionLevel> {
+        let goose_mode = Self::determine_goose_mode(
+            session.as_ref(),
+            Config::global(),
+            self.goose_mode.lock().await.clone(),
+        );
+        let (readonly_tools, regular_tools) =
+            Self::categorize_tools_by_annotation(&self.list_tools(None).await);
+        let request = ToolRequest {
+            id: format!("user_{}", Uuid::new_v4()),
+            tool_call: Ok(tool_call.clone()),
+        };
+        let (mut result, _) = check_tool_permissions(
+            std::slice::from_ref(&request),
+            &goose_mode,
+            readonly_tools,
+            regular_tools,
+            &mut PermissionManager::default(),
+            self.provider().await?,
+        )
+        .await;
+        require_first_use_confirmation(&mut result, &*self.confirmed_tools.lock().await);
+
+        Ok(if !result.approved.is_empty() {
+            PermissionLevel::AlwaysAllow
+        } else if !result.needs_approval.is_empty() {
+            PermissionLevel::AskBefore
+        } else {
+            // Denied, or no tools run at all in chat mode
+            PermissionLevel::NeverAllow
+        })
+    }
+
+    /// Record that the user approved a call `check_tool_permission` asked about
+    pub async fn record_tool_approval(&self, tool_name: &str, permission: Permission) {
+        if permission == Permission::AlwaysAllow {
+            PermissionManager::default()
+                .update_user_permission(tool_name, PermissionLevel::AlwaysAllow);
+        }
+        if CONFIRM_FIRST_USE_TOOLS.contains(&tool_name) {
+            self.confirmed_tools
+                .lock()
+                .await
+                .insert(tool_name.to_string());
         }
     }
 
@@ -1277,6 +2087,7 @@ impl Agent {
     pub async fn update_provider(&self, provider: Arc<dyn Provider>) -> Result<()> {
         let mut current_provider = self.provider.lock().await;
         *current_provider = Some(provider.clone());
+        self.extension_manager.set_provider(provider.clone()).await;
 
         self.update_router_tool_selector(Some(provider), None)
             .await?;
@@ -1305,6 +2116,53 @@ impl Agent {
         prompt_manager.set_system_prompt_override(template);
     }
 
+    /// The system prompt the next request would be sent with, rendered with the instructions of
+    /// the running extensions
+    pub async fn system_prompt(&self) -> Result<String> {
+        let (_, _, system_prompt) = self.prepare_tools_and_prompt().await?;
+        Ok(system_prompt)
+    }
+
+    /// Estimate what the tokens of the next request with `messages` would be spent on
+    pub async fn context_breakdown(&self, messages: &[Message]) -> Result<ContextBreakdown> {
+        let (tools, _, system_prompt) = self.prepare_tools_and_prompt().await?;
+        let model_config = self.provider().await?.get_model_config();
+        let token_counter = create_async_token_counter_for_model(&model_config)
+            .await
+            .map_err(|e| anyhow!("Failed to create token counter: {}", e))?;
+        Ok(get_context_breakdown(
+            &token_counter,
+            &system_prompt,
+            &tools,
+            messages,
+        ))
+    }
+
+    /// Queue a user message while a reply is in progress
+    ///
+    /// Queued messages are added to the conversation at the next point where the model is not
+    /// waiting on tool results, and are emitted as regular message events when they are.
+    pub async fn queue_message(&self, message: Message) -> usize {
+        let mut queued = self.queued_messages.lock().await;
+        queued.push(message);
+        queued.len()
+    }
+
+    /// Messages queued but not yet added to the conversation
+    pub async fn queued_messages(&self) -> Vec<Message> {
+        self.queued_messages.lock().await.clone()
+    }
+
+    /// Remove and return all queued messages
+    pub async fn take_queued_messages(&self) -> Vec<Message> {
+        std::mem::take(&mut *self.queued_messages.lock().await)
+    }
+
+    /// Make an attachment readable through the platform resource tools
+    pub async fn add_attachment(&self, attachment: Attachment) {
+        self.extension_manager.add_attachment(attachment).await;
+    }
+
     pub async fn list_extension_prompts(&self) -> HashMap<String, Vec<Prompt>> {
         self.extension_manager
             .list_prompts(CancellationToken::default())
@@ -1312,27 +2170,46 @@ impl Agent {
             .expect("Failed to list prompts")
     }
 
-    pub async fn get_prompt(&self, name: &str, arguments: Value) -> Result<GetPromptResult> {
-        // First find which extension has this prompt
+    /// Find a prompt and the extension that provides it, by its bare name or qualified as
+    /// `<extension>:<name>` when several extensions share a prompt name
+    pub async fn find_prompt(&self, name: &str) -> Result<(String, Prompt)> {
         let prompts = self
             .extension_manager
             .list_prompts(CancellationToken::default())
             .await
             .map_err(|e| anyhow!("Failed to list prompts: {}", e))?;
 
-        if let Some(extension) = prompts
-            .iter()
-            .find(|(_, prompt_list)| prompt_list.iter().any(|p| p.name == name))
-            .map(|(extension, _)| extension)
-        {
-            return self
-                .extension_manager
-                .get_prompt(extension, name, arguments, CancellationToken::default())
-                .await
-                .map_err(|e| anyhow!("Failed to get prompt: {}", e));
+        if let Some((extension, prompt_name)) = name.split_once(':') {
+            if let Some(prompt) = prompts
+                .get(extension)
+                .and_then(|list| list.iter().find(|p| p.name == prompt_name))
+            {
+                return Ok((extension.to_string(), prompt.clone()));
+            }
         }
 
-        Err(anyhow!("Prompt '{}' not found", name))
+        prompts
+            .into_iter()
+            .find_map(|(extension, prompt_list)| {
+                prompt_list
+                    .into_iter()
+                    .find(|p| p.name == name)
+                    .map(|prompt| (extension, prompt))
+            })
+            .ok_or_else(|| anyhow!("Prompt '{}' not found", name))
+    }
+
+    pub async fn get_prompt(&self, name: &str, arguments: Value) -> Result<GetPromptResult> {
+        let (extension, prompt) = self.find_prompt(name).await?;
+        self.extension_manager
+            .get_prompt(
+                &extension,
+                &prompt.name,
+                arguments,
+                CancellationToken::default(),
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to get prompt: {}", e))
     }
 
     pub async fn get_plan_prompt(&self) -> Result<String> {
@@ -1363,6 +2240,46 @@ impl Agent {
         }
     }
 
+    /// The frontend has gone away. Pending frontend tool calls fail right away, as do new ones
+    /// until the frontend attaches again
+    pub async fn detach_frontend(&self) {
+        self.frontend_gone.lock().await.cancel();
+    }
+
+    /// Apply another content filter to tool outputs and assistant messages, next to the ones
+    /// configured in GOOSE_CONTENT_FILTERS
+    pub async fn add_content_filter(&self, filter: Arc<dyn ContentFilter>) {
+        self.content_filters.lock().await.push(filter);
+    }
+
+    /// Run a hook at the points of the agent loop it implements
+    pub async fn register_hook(&self, hook: Arc<dyn Hook>) {
+        self.hooks.register(hook).await;
+    }
+
+    /// The hooks of this agent, to unregister them or read their metrics
+    pub fn hooks(&self) -> &HookRegistry {
+        &self.hooks
+    }
+
+    /// The session is over, let the hooks know
+    pub async fn end_session(&self, session: &Option<SessionConfig>) {
+        self.hooks.session_end(session.as_ref()).await;
+    }
+
+    /// Stop the extensions of the agent when goose exits
+    pub async fn shutdown(&self) {
+        self.extension_manager.shutdown().await;
+    }
+
+    /// The frontend is back to answer frontend tool calls
+    pub async fn attach_frontend(&self) {
+        let mut frontend_gone = self.frontend_gone.lock().await;
+        if frontend_gone.is_cancelled() {
+            *frontend_gone = CancellationToken::new();
+        }
+    }
+
     pub async fn create_recipe(&self, mut messages: Conversation) -> Result<Recipe> {
         tracing::info!("Starting recipe creation with {} messages", messages.len());
 
@@ -1560,10 +2477,59 @@ impl Agent {
     }
 }
 
+/// Add a response to the messages of the turn. Streaming providers send a response in chunks,
+/// which are appended to the assistant message they continue so the model later sees it whole
+fn push_response_chunk(messages: &mut Vec<Message>, chunk: Message) {
+    let Some(last) = messages
+        .last_mut()
+        .filter(|last| last.role == chunk.role && last.id == chunk.id)
+    else {
+        messages.push(chunk);
+        return;
+    };
+    if chunk.metadata.is_some() {
+        last.metadata = chunk.metadata;
+    }
+    match (last.content.last_mut(), chunk.content.first()) {
+        (Some(MessageContent::Text(last_text)), Some(MessageContent::Text(text)))
+            if chunk.content.len() == 1 =>
+        {
+            last_text.text.push_str(&text.text);
+        }
+        _ => last.content.extend(chunk.content),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{ProviderMetadata, ProviderUsage};
     use crate::recipe::Response;
+    use rmcp::model::Role;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_undo_last_exchange_replaces_history() {
+        let mut conversation = Conversation::new_unvalidated(vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("first answer"),
+            Message::user().with_text("second"),
+            Message::assistant().with_text("second answer"),
+        ]);
+
+        let (removed, event) = Agent::undo_last_exchange(&mut conversation).unwrap();
+        assert_eq!(removed.len(), 2);
+        let AgentEvent::HistoryReplaced(messages) = event else {
+            panic!("expected the history to be replaced");
+        };
+        assert_eq!(&messages, conversation.messages());
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].as_concat_text(), "first answer");
+
+        Agent::undo_last_exchange(&mut conversation).unwrap();
+        assert!(Agent::undo_last_exchange(&mut conversation).is_none());
+    }
 
     #[tokio::test]
     async fn test_add_final_output_tool() -> Result<()> {
@@ -1601,6 +2567,380 @@ mod tests {
         Ok(())
     }
 
+    #[derive(Clone)]
+    struct EchoProvider {
+        model_config: ModelConfig,
+        calls: Arc<AtomicUsize>,
+        systems: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for EchoProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            system: &str,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.systems.lock().unwrap().push(system.to_string());
+            let last = messages
+                .last()
+                .map(|m| m.as_concat_text())
+                .unwrap_or_default();
+            Ok((
+                Message::assistant().with_text(format!("echo: {}", last)),
+                ProviderUsage::new("mock".to_string(), Default::default()),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queued_message_continues_turn() -> Result<()> {
+        let agent = Agent::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        agent
+            .update_provider(Arc::new(EchoProvider {
+                model_config: ModelConfig::new("test-model")
+                    .unwrap()
+                    .with_context_limit(Some(100_000)),
+                calls: calls.clone(),
+                systems: Default::default(),
+            }))
+            .await?;
+
+        assert_eq!(
+            agent
+                .queue_message(Message::user().with_text("and then"))
+                .await,
+            1
+        );
+
+        let conversation = Conversation::new_unvalidated(vec![Message::user().with_text("hi")]);
+        let mut stream = agent.reply(conversation, None, None).await?;
+        let mut texts = Vec::new();
+        while let Some(event) = stream.next().await {
+            if let AgentEvent::Message(message) = event? {
+                texts.push(message.as_concat_text());
+            }
+        }
+
+        assert_eq!(texts, vec!["echo: hi", "and then", "echo: and then"]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(agent.queued_messages().await.is_empty());
+        Ok(())
+    }
+
+    #[derive(Clone)]
+    struct StreamingProvider {
+        model_config: ModelConfig,
+        requests: Arc<std::sync::Mutex<Vec<Vec<Message>>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for StreamingProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Err(ProviderError::NotImplemented("only streams".to_string()))
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        async fn stream(
+            &self,
+            _system: &str,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<MessageStream, ProviderError> {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push(messages.to_vec());
+            let id = format!("resp_{}", requests.len());
+            let mut chunks: Vec<Result<(Option<Message>, Option<ProviderUsage>), ProviderError>> =
+                ["Hel", "lo"]
+                    .into_iter()
+                    .map(|text| {
+                        Ok((
+                            Some(Message::assistant().with_id(id.clone()).with_text(text)),
+                            None,
+                        ))
+                    })
+                    .collect();
+            chunks.push(Ok((
+                None,
+                Some(ProviderUsage::new("mock".to_string(), Default::default())),
+            )));
+            Ok(Box::pin(stream::iter(chunks)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queued_message_after_streamed_response() -> Result<()> {
+        let agent = Agent::new();
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        agent
+            .update_provider(Arc::new(StreamingProvider {
+                model_config: ModelConfig::new("test-model")
+                    .unwrap()
+                    .with_context_limit(Some(100_000)),
+                requests: requests.clone(),
+            }))
+            .await?;
+        agent
+            .queue_message(Message::user().with_text("and then"))
+            .await;
+
+        let conversation = Conversation::new_unvalidated(vec![Message::user().with_text("hi")]);
+        let mut stream = agent.reply(conversation, None, None).await?;
+        while let Some(event) = stream.next().await {
+            event?;
+        }
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let history: Vec<_> = requests[1]
+            .iter()
+            .map(|m| (m.role.clone(), m.as_concat_text()))
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                (Role::User, "hi".to_string()),
+                (Role::Assistant, "Hello".to_string()),
+                (Role::User, "and then".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    struct ContextHook;
+
+    #[async_trait::async_trait]
+    impl Hook for ContextHook {
+        fn name(&self) -> &str {
+            "context"
+        }
+
+        async fn on_turn_start(
+            &self,
+            turn: u32,
+            _session: Option<&SessionConfig>,
+        ) -> Option<String> {
+            Some(format!("hook context {}", turn))
+        }
+
+        async fn on_tool_call(
+            &self,
+            _request_id: &str,
+            tool_call: &mut mcp_core::tool::ToolCall,
+        ) -> ToolCallDecision {
+            if tool_call.name == "developer__shell" {
+                return ToolCallDecision::Veto("no shell".to_string());
+            }
+            tool_call.arguments = serde_json::json!({"rewritten": true});
+            ToolCallDecision::Allow
+        }
+    }
+
+    #[tokio::test]
+    async fn test_turn_start_context_is_ephemeral() -> Result<()> {
+        let agent = Agent::new();
+        let systems: Arc<std::sync::Mutex<Vec<String>>> = Default::default();
+        agent
+            .update_provider(Arc::new(EchoProvider {
+                model_config: ModelConfig::new("test-model")
+                    .unwrap()
+                    .with_context_limit(Some(100_000)),
+                calls: Arc::new(AtomicUsize::new(0)),
+                systems: systems.clone(),
+            }))
+            .await?;
+        agent.register_hook(Arc::new(ContextHook)).await;
+
+        let conversation = Conversation::new_unvalidated(vec![Message::user().with_text("hi")]);
+        let mut stream = agent.reply(conversation, None, None).await?;
+        let mut texts = Vec::new();
+        while let Some(event) = stream.next().await {
+            if let AgentEvent::Message(message) = event? {
+                texts.push(message.as_concat_text());
+            }
+        }
+
+        assert_eq!(texts, vec!["echo: hi"]);
+        let systems = systems.lock().unwrap();
+        assert_eq!(systems.len(), 1);
+        assert!(systems[0].ends_with("# Context for this turn\n\nhook context 1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_hooks_rewrite_and_veto() {
+        let agent = Agent::new();
+        agent.register_hook(Arc::new(ContextHook)).await;
+
+        let mut tool_call =
+            mcp_core::tool::ToolCall::new("developer__text_editor", serde_json::json!({}));
+        agent
+            .run_tool_call_hooks("request", &mut tool_call)
+            .await
+            .unwrap();
+        assert_eq!(tool_call.arguments, serde_json::json!({"rewritten": true}));
+
+        let mut tool_call =
+            mcp_core::tool::ToolCall::new("developer__shell", serde_json::json!({}));
+        let error = agent
+            .run_tool_call_hooks("request", &mut tool_call)
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("no shell"));
+    }
+
+    /// An extension whose only tool runs until the call is dropped
+    struct BlockingClient {
+        started: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait::async_trait]
+    impl mcp_client::McpClientTrait for BlockingClient {
+        fn get_info(&self) -> Option<&rmcp::model::InitializeResult> {
+            None
+        }
+
+        async fn list_resources(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<rmcp::model::ListResourcesResult, mcp_client::Error> {
+            Err(mcp_client::Error::TransportClosed)
+        }
+
+        async fn read_resource(
+            &self,
+            _uri: &str,
+            _cancellation_token: CancellationToken,
+        ) -> Result<rmcp::model::ReadResourceResult, mcp_client::Error> {
+            Err(mcp_client::Error::TransportClosed)
+        }
+
+        async fn list_tools(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<rmcp::model::ListToolsResult, mcp_client::Error> {
+            Err(mcp_client::Error::TransportClosed)
+        }
+
+        async fn call_tool(
+            &self,
+            _name: &str,
+            _arguments: Value,
+            _cancellation_token: CancellationToken,
+        ) -> Result<rmcp::model::CallToolResult, mcp_client::Error> {
+            self.started.notify_one();
+            std::future::pending().await
+        }
+
+        async fn list_prompts(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<rmcp::model::ListPromptsResult, mcp_client::Error> {
+            Err(mcp_client::Error::TransportClosed)
+        }
+
+        async fn get_prompt(
+            &self,
+            _name: &str,
+            _arguments: Value,
+            _cancellation_token: CancellationToken,
+        ) -> Result<rmcp::model::GetPromptResult, mcp_client::Error> {
+            Err(mcp_client::Error::TransportClosed)
+        }
+
+        async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+            mpsc::channel(1).1
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_tools() -> Result<()> {
+        let agent = Agent::new();
+        let started = Arc::new(tokio::sync::Notify::new());
+        let client: Box<dyn mcp_client::McpClientTrait> = Box::new(BlockingClient {
+            started: started.clone(),
+        });
+        agent
+            .extension_manager
+            .add_client(
+                "blocking".to_string(),
+                ExtensionConfig::Builtin {
+                    name: "blocking".to_string(),
+                    display_name: None,
+                    description: None,
+                    timeout: None,
+                    bundled: None,
+                    available_tools: vec![],
+                },
+                Arc::new(Mutex::new(client)),
+                None,
+                None,
+                None,
+            )
+            .await;
+
+        let turn_token = CancellationToken::new();
+        let call = || {
+            agent.dispatch_tool_call(
+                mcp_core::tool::ToolCall::new("blocking__wait", serde_json::json!({})),
+                "req_1".to_string(),
+                Some(turn_token.clone()),
+                &None,
+            )
+        };
+        let (_, result) = call().await;
+        let mut output = result.unwrap().result;
+        tokio::select! {
+            _ = &mut output => panic!("the tool should still be running"),
+            _ = started.notified() => {}
+        }
+
+        assert_eq!(agent.cancel_running_tools().await, 1);
+        let error = output.await.unwrap_err();
+        assert_eq!(error.message, TOOL_CANCELLED_RESPONSE);
+        // Cancelling a tool leaves the turn running and the cancelled tool is forgotten
+        assert!(!turn_token.is_cancelled());
+        assert_eq!(agent.cancel_running_tools().await, 0);
+
+        // A call whose result is dropped without running is forgotten too
+        let (_, result) = call().await;
+        drop(result);
+        assert_eq!(agent.cancel_running_tools().await, 0);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_todo_tools_integration() -> Result<()> {
         let agent = Agent::new();
@@ -1616,4 +2956,155 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_plan_tools() -> Result<()> {
+        let agent = Agent::new();
+        let call = |name: &str, arguments: Value| {
+            agent.dispatch_tool_call(
+                mcp_core::tool::ToolCall::new(name, arguments),
+                "req_1".to_string(),
+                None,
+                &None,
+            )
+        };
+
+        let (_, result) = call(
+            PLAN_UPDATE_STEP_TOOL_NAME,
+            serde_json::json!({"step": 1, "status": "completed"}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_err());
+
+        let (_, result) = call(
+            PLAN_PROPOSE_TOOL_NAME,
+            serde_json::json!({"title": "Fix bug", "steps": ["Reproduce", "Fix"]}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_ok());
+        assert_eq!(agent.plan().await.unwrap().current_step(), None);
+
+        // Using any other tool starts the first pending step
+        let (_, result) = call("missing__tool", serde_json::json!({})).await;
+        let _ = result.unwrap().result.await;
+        assert_eq!(agent.plan().await.unwrap().current_step(), Some(0));
+
+        let (_, result) = call(
+            PLAN_UPDATE_STEP_TOOL_NAME,
+            serde_json::json!({"step": 1, "status": "completed"}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_ok());
+        let plan = agent.plan().await.unwrap();
+        assert_eq!(plan.progress(), (1, 2));
+        assert_eq!(plan.current_step(), None);
+
+        let (_, result) = call(
+            PLAN_UPDATE_STEP_TOOL_NAME,
+            serde_json::json!({"step": 5, "status": "completed"}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_structured_todo_tools() -> Result<()> {
+        let agent = Agent::new();
+        let (_, result) = agent
+            .dispatch_tool_call(
+                mcp_core::tool::ToolCall::new(
+                    TODO_ADD_TOOL_NAME,
+                    serde_json::json!({"items": [{"title": "Research"}]}),
+                ),
+                "req_0".to_string(),
+                None,
+                &None,
+            )
+            .await;
+        assert!(result.unwrap().result.await.is_err());
+
+        let temp_dir = tempfile::tempdir()?;
+        let session_file = temp_dir.path().join("session.jsonl");
+        let session = Some(SessionConfig {
+            id: session::Identifier::Path(session_file.clone()),
+            working_dir: temp_dir.path().to_path_buf(),
+            schedule_id: None,
+            execution_mode: None,
+            max_turns: None,
+            retry_config: None,
+            confirmation_timeout: None,
+        });
+        let call = |name: &str, arguments: Value| {
+            agent.dispatch_tool_call(
+                mcp_core::tool::ToolCall::new(name, arguments),
+                "req_1".to_string(),
+                None,
+                &session,
+            )
+        };
+
+        let (_, result) = call(
+            TODO_ADD_TOOL_NAME,
+            serde_json::json!({"items": [
+                {"title": "Research"},
+                {"title": "Write summary", "owner": "writer"},
+                {"notes": "no title"}
+            ]}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_ok());
+        assert_eq!(agent.todos().await.len(), 2);
+
+        let (_, result) = call(
+            TODO_UPDATE_TOOL_NAME,
+            serde_json::json!({"id": 1, "status": "in_progress", "notes": "reading docs"}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_ok());
+        let (_, result) = call(TODO_COMPLETE_TOOL_NAME, serde_json::json!({"id": 2})).await;
+        assert!(result.unwrap().result.await.is_ok());
+
+        let todos = agent.todos().await;
+        assert_eq!(todos[0].status, TodoStatus::InProgress);
+        assert_eq!(todos[0].notes.as_deref(), Some("reading docs"));
+        assert_eq!(todos[1].status, TodoStatus::Completed);
+        assert_eq!(todos[1].owner.as_deref(), Some("writer"));
+        let metadata = session::storage::read_metadata(&session_file)?;
+        assert_eq!(metadata.todos, todos);
+        assert_eq!(
+            metadata.todo_content.as_deref(),
+            Some("- [~] Research\n- [x] Write summary\n")
+        );
+
+        let (_, result) = call(TODO_COMPLETE_TOOL_NAME, serde_json::json!({"id": 9})).await;
+        assert!(result.unwrap().result.await.is_err());
+        let (_, result) = call(
+            TODO_UPDATE_TOOL_NAME,
+            serde_json::json!({"id": 1, "status": "done"}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_err());
+
+        // The old freeform format still replaces the whole list
+        let (_, result) = call(
+            TODO_WRITE_TOOL_NAME,
+            serde_json::json!({"content": "- [x] Old item\n- [ ] Next"}),
+        )
+        .await;
+        assert!(result.unwrap().result.await.is_ok());
+        let todos = agent.todos().await;
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0].status, TodoStatus::Completed);
+        let metadata = session::storage::read_metadata(&session_file)?;
+        assert_eq!(
+            metadata.todo_content.as_deref(),
+            Some("- [x] Old item\n- [ ] Next")
+        );
+
+        let (_, result) = call(TODO_READ_TOOL_NAME, serde_json::json!({})).await;
+        let content = result.unwrap().result.await.unwrap();
+        assert!(content[0].as_text().unwrap().text.contains("[ ] 2. Next"));
+        Ok(())
+    }
 }
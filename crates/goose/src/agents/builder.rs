@@ -0,0 +1,361 @@
+//! A typed builder for embedding goose in other Rust programs, and a facade that runs a prompt
+//! to completion without the event handling goose-cli does.
+//!
+//! ```no_run
+//! use goose::agents::{AgentBuilder, ExtensionConfig, SessionBackend};
+//! use goose::permission::Permission;
+//! use goose::session::Identifier;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let agent = AgentBuilder::new()
+//!     .extension(ExtensionConfig::default())
+//!     .system_prompt_extra("Keep answers short")
+//!     .goose_mode("approve")
+//!     .confirm_with(|request| {
+//!         if request.tool_name.starts_with("developer__text_editor") {
+//!             Permission::AllowOnce
+//!         } else {
+//!             Permission::DenyOnce
+//!         }
+//!     })
+//!     .session(SessionBackend::File(Identifier::Name("embedded".to_string())))
+//!     .build()
+//!     .await?;
+//!
+//! let result = agent.run("Summarize the README in this directory").await?;
+//! println!("{}", result.text());
+//! # Ok(())
+//! # }
+//! ```
+use anyhow::{anyhow, Context, Result};
+use futures::StreamExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::{Agent, AgentEvent, ExtensionConfig, ReplyOutcome, SessionConfig};
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent, ToolConfirmationRequest};
+use crate::conversation::Conversation;
+use crate::model::ModelConfig;
+use crate::permission::permission_confirmation::PrincipalType;
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::providers::base::Provider;
+use crate::session;
+
+const GOOSE_MODES: &[&str] = &["auto", "approve", "smart_approve", "chat"];
+
+/// Decides tool calls that need the user's approval, since there is no user to ask
+pub type ConfirmationHandler = Arc<dyn Fn(&ToolConfirmationRequest) -> Permission + Send + Sync>;
+
+/// Where an embedded agent keeps its conversation
+#[derive(Debug, Clone, Default)]
+pub enum SessionBackend {
+    /// In memory only, nothing is written to the sessions directory
+    #[default]
+    Memory,
+    /// A session file, resumed when it exists and saved after every run. Sessions saved here
+    /// can be opened with `goose session --resume`
+    File(session::Identifier),
+}
+
+#[derive(Default)]
+pub struct AgentBuilder {
+    provider: Option<Arc<dyn Provider>>,
+    extensions: Vec<ExtensionConfig>,
+    system_prompt_extras: Vec<String>,
+    goose_mode: Option<String>,
+    confirmation_handler: Option<ConfirmationHandler>,
+    session: SessionBackend,
+    working_dir: Option<PathBuf>,
+    max_turns: Option<u32>,
+}
+
+impl AgentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The provider to answer with. Without one, the configured GOOSE_PROVIDER and GOOSE_MODEL
+    /// are used
+    pub fn provider(mut self, provider: Arc<dyn Provider>) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    pub fn extension(mut self, extension: ExtensionConfig) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    pub fn extensions(mut self, extensions: impl IntoIterator<Item = ExtensionConfig>) -> Self {
+        self.extensions.extend(extensions);
+        self
+    }
+
+    /// An instruction added to the system prompt
+    pub fn system_prompt_extra(mut self, instruction: impl Into<String>) -> Self {
+        self.system_prompt_extras.push(instruction.into());
+        self
+    }
+
+    /// One of auto, approve, smart_approve or chat, instead of the configured GOOSE_MODE
+    pub fn goose_mode(mut self, mode: impl Into<String>) -> Self {
+        self.goose_mode = Some(mode.into());
+        self
+    }
+
+    /// Decide the tool calls that need approval. Without a handler they are all declined
+    pub fn confirm_with<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&ToolConfirmationRequest) -> Permission + Send + Sync + 'static,
+    {
+        self.confirmation_handler = Some(Arc::new(handler));
+        self
+    }
+
+    pub fn session(mut self, session: SessionBackend) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// The directory tools work in, the current directory by default. Only sessions saved to a
+    /// file record it
+    pub fn working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(working_dir.into());
+        self
+    }
+
+    /// Most turns a run may take without a new prompt. Applies to sessions saved to a file,
+    /// in-memory sessions use GOOSE_MAX_TURNS
+    pub fn max_turns(mut self, max_turns: u32) -> Self {
+        self.max_turns = Some(max_turns);
+        self
+    }
+
+    /// Set up the agent: connect the provider, start the extensions and load the session
+    pub async fn build(self) -> Result<EmbeddedAgent> {
+        if let Some(mode) = &self.goose_mode {
+            if !GOOSE_MODES.contains(&mode.as_str()) {
+                return Err(anyhow!(
+                    "Unknown goose mode '{}', expected one of {}",
+                    mode,
+                    GOOSE_MODES.join(", ")
+                ));
+            }
+        }
+
+        let provider = match self.provider {
+            Some(provider) => provider,
+            None => configured_provider()?,
+        };
+        let agent = Agent::new();
+        agent.update_provider(provider.clone()).await?;
+        for extension in self.extensions {
+            let name = extension.name();
+            agent
+                .add_extension(extension)
+                .await
+                .with_context(|| format!("Failed to start extension {}", name))?;
+        }
+        for instruction in self.system_prompt_extras {
+            agent.extend_system_prompt(instruction).await;
+        }
+        agent.set_goose_mode(self.goose_mode).await;
+
+        let working_dir = match self.working_dir {
+            Some(working_dir) => working_dir,
+            None => std::env::current_dir()?,
+        };
+        let (session_config, session_file, conversation) = match self.session {
+            SessionBackend::Memory => (None, None, Conversation::empty()),
+            SessionBackend::File(id) => {
+                let path = session::get_path(id.clone())?;
+                let conversation = if path.exists() {
+                    session::read_messages(&path)?
+                } else {
+                    Conversation::empty()
+                };
+                let session_config = SessionConfig {
+                    id,
+                    working_dir: working_dir.clone(),
+                    schedule_id: None,
+                    execution_mode: None,
+                    max_turns: self.max_turns,
+                    retry_config: None,
+                    confirmation_timeout: None,
+                };
+                (Some(session_config), Some(path), conversation)
+            }
+        };
+
+        Ok(EmbeddedAgent {
+            agent,
+            provider,
+            conversation: Mutex::new(conversation),
+            session_config,
+            session_file,
+            working_dir,
+            confirmation_handler: self
+                .confirmation_handler
+                .unwrap_or_else(|| Arc::new(|_| Permission::DenyOnce)),
+        })
+    }
+}
+
+fn configured_provider() -> Result<Arc<dyn Provider>> {
+    let config = Config::global();
+    let provider: String = config
+        .get_param("GOOSE_PROVIDER")
+        .context("No provider was given and GOOSE_PROVIDER is not configured")?;
+    let model: String = config
+        .get_param("GOOSE_MODEL")
+        .context("No provider was given and GOOSE_MODEL is not configured")?;
+    crate::providers::create(&provider, ModelConfig::new(&model)?)
+}
+
+/// What a run produced
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    /// The messages added by the run, from the prompt to the agent's last answer
+    pub messages: Vec<Message>,
+    pub outcome: ReplyOutcome,
+}
+
+impl RunResult {
+    /// The text of the agent's last answer
+    pub fn text(&self) -> String {
+        self.messages
+            .iter()
+            .rev()
+            .find(|message| message.role == rmcp::model::Role::Assistant)
+            .map(|message| message.as_concat_text())
+            .unwrap_or_default()
+    }
+}
+
+/// An agent built by [`AgentBuilder`] that keeps its conversation between runs
+pub struct EmbeddedAgent {
+    agent: Agent,
+    provider: Arc<dyn Provider>,
+    conversation: Mutex<Conversation>,
+    session_config: Option<SessionConfig>,
+    session_file: Option<PathBuf>,
+    working_dir: PathBuf,
+    confirmation_handler: ConfirmationHandler,
+}
+
+impl EmbeddedAgent {
+    /// The agent underneath, for everything the facade doesn't cover
+    pub fn agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    pub async fn conversation(&self) -> Conversation {
+        self.conversation.lock().await.clone()
+    }
+
+    /// End the session, running the session end hooks
+    pub async fn finish(self) {
+        self.agent.end_session(&self.session_config).await;
+    }
+
+    /// Send a prompt and run the agent until it finishes its turn, answering confirmations
+    /// with the handler and saving the session if it has a file
+    pub async fn run(&self, prompt: impl Into<String>) -> Result<RunResult> {
+        let mut conversation = self.conversation.lock().await;
+        let start = conversation.len();
+        conversation.push(Message::user().with_text(prompt.into()));
+
+        let mut stream = self
+            .agent
+            .reply(conversation.clone(), self.session_config.clone(), None)
+            .await?;
+        while let Some(event) = stream.next().await {
+            match event? {
+                AgentEvent::Message(message) => match message.content.first() {
+                    Some(MessageContent::ToolConfirmationRequest(request)) => {
+                        let permission = (self.confirmation_handler)(request);
+                        self.agent
+                            .handle_confirmation(
+                                request.id.clone(),
+                                PermissionConfirmation {
+                                    principal_type: PrincipalType::Tool,
+                                    permission,
+                                },
+                            )
+                            .await;
+                    }
+                    Some(MessageContent::ContextLengthExceeded(_)) => {
+                        let (summarized, _, _) = self
+                            .agent
+                            .summarize_context(conversation.messages())
+                            .await?;
+                        *conversation = summarized;
+                        drop(stream);
+                        stream = self
+                            .agent
+                            .reply(conversation.clone(), self.session_config.clone(), None)
+                            .await?;
+                    }
+                    _ => conversation.push(message),
+                },
+                AgentEvent::HistoryReplaced(messages) => {
+                    *conversation = Conversation::new_unvalidated(messages);
+                }
+                _ => {}
+            }
+        }
+        drop(stream);
+
+        if let Some(session_file) = &self.session_file {
+            session::persist_messages(
+                session_file,
+                &conversation,
+                Some(self.provider.clone()),
+                Some(self.working_dir.clone()),
+            )
+            .await?;
+            session::journal::commit(session_file);
+        }
+
+        // A compacted history is shorter than before the run, so only the last answer is new
+        let messages = conversation
+            .messages()
+            .iter()
+            .skip(start.min(conversation.len().saturating_sub(1)))
+            .cloned()
+            .collect();
+        Ok(RunResult {
+            messages,
+            outcome: self.agent.reply_outcome().await,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_build_rejects_unknown_mode() {
+        let result = AgentBuilder::new().goose_mode("yolo").build().await;
+        assert!(result
+            .err()
+            .is_some_and(|e| e.to_string().contains("Unknown goose mode")));
+    }
+
+    #[test]
+    fn test_run_result_text() {
+        let result = RunResult {
+            messages: vec![
+                Message::user().with_text("hi"),
+                Message::assistant().with_text("first"),
+                Message::user().with_text("tool result"),
+                Message::assistant().with_text("last"),
+            ],
+            outcome: ReplyOutcome::Completed,
+        };
+        assert_eq!(result.text(), "last");
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Ok;
 
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
-use crate::token_counter::create_async_token_counter;
+use crate::token_counter::create_async_token_counter_for_model;
 
 use crate::context_mgmt::summarize::summarize_messages;
 use crate::context_mgmt::truncate::{truncate_messages, OldestFirstTruncation};
@@ -17,7 +17,7 @@ impl Agent {
         messages: &[Message], // last message is a user msg that led to assistant message with_context_length_exceeded
     ) -> Result<(Conversation, Vec<usize>), anyhow::Error> {
         let provider = self.provider().await?;
-        let token_counter = create_async_token_counter()
+        let token_counter = create_async_token_counter_for_model(&provider.get_model_config())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
         let target_context_limit = estimate_target_context_limit(provider);
@@ -0,0 +1,416 @@
+//! The optional coverage tool: runs the test suite with coverage and reports the line coverage
+//! of each file, with how it changed since the last run in the session, so work on improving
+//! coverage can check what it achieved.
+//!
+//! Enabled with `GOOSE_COVERAGE_TOOL: true`. The last run is kept in the session metadata.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use indoc::indoc;
+use mcp_core::ToolResult;
+use rmcp::model::{Content, ErrorCode, ErrorData, Tool, ToolAnnotations};
+use rmcp::object;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::process::Command;
+use utoipa::ToSchema;
+
+use crate::agents::types::SessionConfig;
+use crate::session;
+
+use super::Agent;
+
+pub const COVERAGE_REPORT_TOOL_NAME: &str = "coverage__report";
+
+/// Files listed in a report, the rest are counted
+const MAX_LISTED: usize = 40;
+
+/// The covered lines of one file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FileCoverage {
+    pub covered: u64,
+    pub total: u64,
+}
+
+impl FileCoverage {
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.covered as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+/// The coverage of a run, kept in the session to compare the next run with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CoverageSnapshot {
+    /// Unix timestamp of the run
+    pub taken_at: i64,
+    pub command: String,
+    /// Coverage by file, relative to the working directory when the file is inside it
+    pub files: BTreeMap<String, FileCoverage>,
+}
+
+impl CoverageSnapshot {
+    pub fn totals(&self) -> FileCoverage {
+        self.files.values().fold(
+            FileCoverage {
+                covered: 0,
+                total: 0,
+            },
+            |totals, file| FileCoverage {
+                covered: totals.covered + file.covered,
+                total: totals.total + file.total,
+            },
+        )
+    }
+}
+
+pub fn coverage_report_tool() -> Tool {
+    Tool::new(
+        COVERAGE_REPORT_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Run the tests with coverage and report the line coverage of each file, with how it
+            changed since the last run in this session.
+
+            Uses cargo llvm-cov for Rust projects and pytest-cov for Python projects. Pass `command`
+            to run something else that prints an llvm-cov or coverage.py JSON report on stdout.
+            Run it before and after adding tests to see what they covered.
+        "#}
+        .to_string(),
+        object!({
+            "type": "object",
+            "required": [],
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Command printing a JSON coverage report, instead of the one detected for the project"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Report test coverage".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+/// Read an llvm-cov export (`cargo llvm-cov --json`) or a coverage.py JSON report
+pub fn parse_report(json: &str, working_dir: &Path) -> Result<BTreeMap<String, FileCoverage>> {
+    let report: Value = serde_json::from_str(json)?;
+    let relative = |file: &str| {
+        Path::new(file)
+            .strip_prefix(working_dir)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|_| file.to_string())
+    };
+    let number = |value: &Value, pointer: &str| value.pointer(pointer).and_then(Value::as_u64);
+
+    let mut files = BTreeMap::new();
+    if let Some(exports) = report.get("data").and_then(Value::as_array) {
+        for file in exports
+            .iter()
+            .filter_map(|export| export.get("files").and_then(Value::as_array))
+            .flatten()
+        {
+            let (Some(name), Some(total), Some(covered)) = (
+                file.get("filename").and_then(Value::as_str),
+                number(file, "/summary/lines/count"),
+                number(file, "/summary/lines/covered"),
+            ) else {
+                continue;
+            };
+            files.insert(relative(name), FileCoverage { covered, total });
+        }
+    } else if let Some(reported) = report.get("files").and_then(Value::as_object) {
+        for (name, file) in reported {
+            let (Some(total), Some(covered)) = (
+                number(file, "/summary/num_statements"),
+                number(file, "/summary/covered_lines"),
+            ) else {
+                continue;
+            };
+            files.insert(relative(name), FileCoverage { covered, total });
+        }
+    } else {
+        bail!("Not an llvm-cov or coverage.py JSON report");
+    }
+    Ok(files)
+}
+
+fn delta(current: f64, previous: Option<f64>) -> String {
+    match previous {
+        None => " (new)".to_string(),
+        Some(previous) if (current - previous).abs() < 0.05 => String::new(),
+        Some(previous) => format!(" ({:+.1})", current - previous),
+    }
+}
+
+/// The report of a run: the total line coverage, then the files that changed since the previous
+/// run, then the least covered ones
+pub fn render_report(current: &CoverageSnapshot, previous: Option<&CoverageSnapshot>) -> String {
+    let totals = current.totals();
+    let mut report = format!(
+        "Line coverage {:.1}% ({} of {} lines)",
+        totals.percent(),
+        totals.covered,
+        totals.total
+    );
+    match previous {
+        Some(previous) => {
+            let change = totals.percent() - previous.totals().percent();
+            let at = chrono::DateTime::from_timestamp(previous.taken_at, 0)
+                .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_default();
+            report.push_str(&format!(", {:+.1} points since the run at {}", change, at));
+        }
+        None => report.push_str(", the first run in this session"),
+    }
+
+    let previous_percent = |name: &str| {
+        previous.and_then(|previous| previous.files.get(name).map(FileCoverage::percent))
+    };
+    let mut files: Vec<(&String, &FileCoverage, f64)> = current
+        .files
+        .iter()
+        .filter(|(_, file)| file.total > 0)
+        .map(|(name, file)| {
+            let change = previous_percent(name)
+                .map(|before| (file.percent() - before).abs())
+                .unwrap_or(0.0);
+            (name, file, change)
+        })
+        .collect();
+    files.sort_by(|a, b| {
+        b.2.total_cmp(&a.2)
+            .then(a.1.percent().total_cmp(&b.1.percent()))
+    });
+
+    for (name, file, _) in files.iter().take(MAX_LISTED) {
+        let change = if previous.is_some() {
+            delta(file.percent(), previous_percent(name))
+        } else {
+            String::new()
+        };
+        report.push_str(&format!(
+            "\n{} {:.1}% ({}/{}){}",
+            name,
+            file.percent(),
+            file.covered,
+            file.total,
+            change
+        ));
+    }
+    if files.len() > MAX_LISTED {
+        report.push_str(&format!(
+            "\n... and {} more files",
+            files.len() - MAX_LISTED
+        ));
+    }
+    if let Some(previous) = previous {
+        let removed: Vec<&str> = previous
+            .files
+            .keys()
+            .filter(|name| !current.files.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        if !removed.is_empty() {
+            report.push_str(&format!("\nNo longer reported: {}", removed.join(", ")));
+        }
+    }
+    report
+}
+
+async fn run(command: &str, working_dir: &Path) -> Result<String> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(working_dir)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        // pytest reports failing tests on stdout, cargo on stderr
+        let combined = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stderr),
+            String::from_utf8_lossy(&output.stdout)
+        );
+        let lines: Vec<&str> = combined.lines().collect();
+        bail!(
+            "`{}` failed with {}:\n{}",
+            command,
+            output.status,
+            lines[lines.len().saturating_sub(20)..].join("\n")
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Run the coverage of the project in `working_dir`, the detected command unless one is given
+async fn measure(command: Option<&str>, working_dir: &Path) -> Result<CoverageSnapshot> {
+    let has = |file: &str| working_dir.join(file).is_file();
+    let (command, json) = match command {
+        Some(command) => (command.to_string(), run(command, working_dir).await?),
+        None if has("Cargo.toml") => {
+            let command = "cargo llvm-cov --json --summary-only";
+            (command.to_string(), run(command, working_dir).await?)
+        }
+        None if has("pyproject.toml") || has("setup.py") || has("pytest.ini") => {
+            let report = tempfile::NamedTempFile::new()?;
+            let command = format!(
+                "pytest --cov=. --cov-report=json:{} -q",
+                report.path().display()
+            );
+            run(&command, working_dir).await?;
+            (command, std::fs::read_to_string(report.path())?)
+        }
+        None => bail!(
+            "No coverage command found for {}, pass one in the 'command' parameter",
+            working_dir.display()
+        ),
+    };
+    Ok(CoverageSnapshot {
+        taken_at: chrono::Utc::now().timestamp(),
+        files: parse_report(&json, working_dir)
+            .map_err(|e| anyhow!("Could not read the report of `{}`: {}", command, e))?,
+        command,
+    })
+}
+
+impl Agent {
+    /// Handle the coverage tool: measure, compare with the last run saved in the session and
+    /// save this run in its place
+    pub(super) async fn handle_coverage_tool(
+        &self,
+        arguments: &Value,
+        session: &Option<SessionConfig>,
+    ) -> ToolResult<Vec<Content>> {
+        let working_dir: PathBuf = match (self.working_dir().await, session) {
+            (Some(dir), _) => dir,
+            (None, Some(session)) => session.working_dir.clone(),
+            (None, None) => std::env::current_dir().unwrap_or_default(),
+        };
+        let command = arguments
+            .get("command")
+            .and_then(|v| v.as_str())
+            .filter(|command| !command.trim().is_empty());
+
+        let current = measure(command, &working_dir)
+            .await
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+
+        let metadata_path = session
+            .as_ref()
+            .and_then(|session| session::storage::get_path(session.id.clone()).ok());
+        let mut metadata = metadata_path
+            .as_ref()
+            .and_then(|path| session::storage::read_metadata(path).ok());
+        let report = render_report(
+            &current,
+            metadata
+                .as_ref()
+                .and_then(|metadata| metadata.coverage.as_ref()),
+        );
+
+        if let (Some(path), Some(metadata)) = (metadata_path, metadata.as_mut()) {
+            metadata.coverage = Some(current);
+            if let Err(e) = session::storage::update_metadata(&path, metadata).await {
+                tracing::warn!("Failed to save coverage in session metadata: {}", e);
+            }
+        }
+        Ok(vec![Content::text(report)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(taken_at: i64, files: &[(&str, u64, u64)]) -> CoverageSnapshot {
+        CoverageSnapshot {
+            taken_at,
+            command: "cargo llvm-cov --json --summary-only".to_string(),
+            files: files
+                .iter()
+                .map(|(name, covered, total)| {
+                    (
+                        name.to_string(),
+                        FileCoverage {
+                            covered: *covered,
+                            total: *total,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_llvm_cov_and_coverage_py() {
+        let llvm_cov = r#"{"type": "llvm.coverage.json.export", "data": [{"files": [
+            {"filename": "/work/app/src/lib.rs", "summary": {"lines": {"count": 40, "covered": 30, "percent": 75.0}}},
+            {"filename": "/rustc/library/core.rs", "summary": {"lines": {"count": 10, "covered": 1, "percent": 10.0}}}
+        ], "totals": {}}]}"#;
+        let files = parse_report(llvm_cov, Path::new("/work/app")).unwrap();
+        assert_eq!(
+            files.get("src/lib.rs"),
+            Some(&FileCoverage {
+                covered: 30,
+                total: 40
+            })
+        );
+        assert!(files.contains_key("/rustc/library/core.rs"));
+
+        let coverage_py = r#"{"meta": {}, "files": {
+            "app/models.py": {"summary": {"covered_lines": 9, "num_statements": 12, "percent_covered": 75.0}}
+        }, "totals": {}}"#;
+        let files = parse_report(coverage_py, Path::new("/work/app")).unwrap();
+        assert_eq!(files.get("app/models.py").unwrap().percent(), 75.0);
+
+        assert!(parse_report(r#"{"coverage": 80}"#, Path::new("/")).is_err());
+    }
+
+    #[test]
+    fn test_render_report_with_deltas() {
+        let previous = snapshot(
+            1_760_000_000,
+            &[
+                ("src/lib.rs", 20, 40),
+                ("src/util.rs", 5, 10),
+                ("src/old.rs", 1, 2),
+            ],
+        );
+        let current = snapshot(
+            1_760_000_600,
+            &[
+                ("src/lib.rs", 30, 40),
+                ("src/util.rs", 5, 10),
+                ("src/new.rs", 0, 8),
+            ],
+        );
+
+        let report = render_report(&current, Some(&previous));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Line coverage 60.3% (35 of 58 lines), +10.3 points since the run at 2025-10-09 08:53 UTC",
+                "src/lib.rs 75.0% (30/40) (+25.0)",
+                "src/new.rs 0.0% (0/8) (new)",
+                "src/util.rs 50.0% (5/10)",
+                "No longer reported: src/old.rs",
+            ]
+        );
+
+        let first = render_report(&current, None);
+        assert!(first.starts_with("Line coverage 60.3% (35 of 58 lines), the first run"));
+        assert!(first.contains("\nsrc/new.rs 0.0% (0/8)\n"));
+    }
+}
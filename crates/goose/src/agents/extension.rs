@@ -387,7 +387,16 @@ impl ExtensionConfig {
 
     /// Check if a tool should be available to the LLM
     pub fn is_tool_available(&self, tool_name: &str) -> bool {
-        let available_tools = match self {
+        let available_tools = self.available_tools();
+
+        // If no tools are specified, all tools are available
+        // If tools are specified, only those tools are available
+        available_tools.is_empty() || available_tools.contains(&tool_name.to_string())
+    }
+
+    /// The tools the extension is limited to, empty when all of its tools are available
+    pub fn available_tools(&self) -> &[String] {
+        match self {
             Self::Sse {
                 available_tools, ..
             }
@@ -406,11 +415,67 @@ impl ExtensionConfig {
             | Self::Frontend {
                 available_tools, ..
             } => available_tools,
-        };
+        }
+    }
 
-        // If no tools are specified, all tools are available
-        // If tools are specified, only those tools are available
-        available_tools.is_empty() || available_tools.contains(&tool_name.to_string())
+    pub fn set_available_tools(&mut self, tools: Vec<String>) {
+        match self {
+            Self::Sse {
+                available_tools, ..
+            }
+            | Self::StreamableHttp {
+                available_tools, ..
+            }
+            | Self::Stdio {
+                available_tools, ..
+            }
+            | Self::Builtin {
+                available_tools, ..
+            }
+            | Self::InlinePython {
+                available_tools, ..
+            }
+            | Self::Frontend {
+                available_tools, ..
+            } => *available_tools = tools,
+        }
+    }
+
+    /// The allowlist after enabling or disabling `tool`, given every tool the extension has.
+    /// It is empty again once all tools are enabled. Disabling the last tool can't be
+    /// expressed with an allowlist, so that fails and the extension should be disabled instead
+    pub fn toggle_tool(
+        &self,
+        all_tools: &[String],
+        tool: &str,
+        enabled: bool,
+    ) -> Result<Vec<String>, Box<ExtensionError>> {
+        if !all_tools.iter().any(|t| t == tool) {
+            return Err(Box::new(ExtensionError::ConfigError(format!(
+                "{} has no tool named {}",
+                self.name(),
+                tool
+            ))));
+        }
+        let mut allowed: Vec<String> = all_tools
+            .iter()
+            .filter(|t| *t != tool && self.is_tool_available(t))
+            .cloned()
+            .collect();
+        if enabled {
+            allowed.push(tool.to_string());
+        }
+        if allowed.is_empty() {
+            return Err(Box::new(ExtensionError::ConfigError(format!(
+                "Can't disable every tool of {}, disable the extension instead",
+                self.name()
+            ))));
+        }
+        if all_tools.iter().all(|t| allowed.contains(t)) {
+            return Ok(Vec::new());
+        }
+        allowed.sort();
+        Ok(allowed)
     }
 }
 
@@ -455,6 +520,14 @@ impl ExtensionInfo {
     }
 }
 
+/// A tool of an extension and whether the extension's allowlist makes it available
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ExtensionToolStatus {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
 /// Information about the tool used for building prompts
 #[derive(Clone, Debug, Serialize, ToSchema)]
 pub struct ToolInfo {
@@ -6,16 +6,18 @@ use futures::{future, FutureExt};
 use mcp_core::handler::require_str_parameter;
 use mcp_core::ToolCall;
 use rmcp::service::ClientInitializeError;
+use rmcp::transport::sse_client::SseClientConfig;
 use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
 use rmcp::transport::{
     ConfigureCommandExt, SseClientTransport, StreamableHttpClientTransport, TokioChildProcess,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tempfile::{tempdir, TempDir};
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio::task;
@@ -24,27 +26,56 @@ use tokio_util::sync::CancellationToken;
 use tracing::{error, warn};
 
 use super::extension::{ExtensionConfig, ExtensionError, ExtensionInfo, ExtensionResult, ToolInfo};
+use super::extension_manifest::ExtensionManifest;
+use super::extension_sampling::{ExtensionSampler, SharedProvider};
+use super::extension_stderr;
+use super::lazy_extension_client::{ConnectedExtension, Connector, LazyExtensionClient};
+use super::resource_subscriptions::{self, ResourceUpdate, SharedSubscriptions};
 use super::tool_execution::ToolCallResult;
 use crate::agents::extension::{Envs, ProcessExit};
 use crate::agents::extension_malware_check;
-use crate::config::{Config, ExtensionConfigManager};
-use crate::oauth::oauth_flow;
+use crate::config::extensions::ToolOverride;
+use crate::config::{Config, ExtensionConfigManager, ExtensionEntry, ExtensionRegistry};
+use crate::oauth::{cached_oauth, oauth_flow, persist_refreshed_credentials};
 use crate::prompt_template;
-use mcp_client::client::{McpClient, McpClientTrait};
+use crate::providers::base::Provider;
+use crate::session::attachment::{Attachment, ATTACHMENT_URI_SCHEME};
+use crate::shutdown::ProcessGroup;
+use crate::tracing::tool_calls;
+use mcp_client::client::{McpClient, McpClientTrait, SamplingHandler, SharedRoots};
 use rmcp::model::{
-    Content, ErrorCode, ErrorData, GetPromptResult, Prompt, ResourceContents, ServerInfo, Tool,
+    Content, ErrorCode, ErrorData, GetPromptResult, LoggingLevel, LoggingMessageNotification,
+    LoggingMessageNotificationMethod, LoggingMessageNotificationParam, Prompt, ResourceContents,
+    Root, ServerInfo, ServerNotification, Tool,
 };
 use rmcp::transport::auth::AuthClient;
 use serde_json::Value;
 
 type McpClientBox = Arc<Mutex<Box<dyn McpClientTrait>>>;
 
+/// Owner name reported for attachment resources by the list_resources tool
+const ATTACHMENTS_RESOURCE_OWNER: &str = "attachments";
+
+/// How long an extension has to answer a health check ping
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the supervisor checks that the running extensions still respond
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Delay before retrying a failed restart, doubled on every further failure
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(2);
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(300);
+
 struct Extension {
     pub config: ExtensionConfig,
+    /// Configured names and descriptions of its tools, keyed by the tool's own name
+    tool_overrides: HashMap<String, ToolOverride>,
+    /// The own name of each tool last listed under an alias, keyed by the alias
+    tool_aliases: HashMap<String, String>,
 
     client: McpClientBox,
     server_info: Option<ServerInfo>,
     _temp_dir: Option<tempfile::TempDir>,
+    /// Terminated with whatever the extension left running when the extension goes away
+    _process_group: Option<ProcessGroup>,
 }
 
 impl Extension {
@@ -53,12 +84,16 @@ impl Extension {
         client: McpClientBox,
         server_info: Option<ServerInfo>,
         temp_dir: Option<tempfile::TempDir>,
+        process_group: Option<ProcessGroup>,
     ) -> Self {
         Self {
             client,
+            tool_overrides: ExtensionConfigManager::get_tool_overrides(&config.key()),
+            tool_aliases: HashMap::new(),
             config,
             server_info,
             _temp_dir: temp_dir,
+            _process_group: process_group,
         }
     }
 
@@ -69,6 +104,14 @@ impl Extension {
             .is_some()
     }
 
+    fn supports_resource_subscriptions(&self) -> bool {
+        self.server_info
+            .as_ref()
+            .and_then(|info| info.capabilities.resources.as_ref())
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
     fn get_instructions(&self) -> Option<String> {
         self.server_info
             .as_ref()
@@ -80,9 +123,53 @@ impl Extension {
     }
 }
 
+/// Lists the names of the session's variables for the extension process
+const SESSION_ENV_KEYS_ENV: &str = "GOOSE_SESSION_ENV_KEYS";
+
+/// Environment variables given to the extension processes of one session
+type SessionEnv = Arc<Mutex<HashMap<String, String>>>;
+/// Directory the extension processes of one session start in, the one goose runs in when unset
+type ExtensionDir = Arc<Mutex<Option<PathBuf>>>;
+
+/// Failed restarts of an extension, so the next attempt can back off
+struct RestartBackoff {
+    failures: u32,
+    retry_at: Instant,
+}
+
 /// Manages Goose extensions / MCP clients and their interactions
 pub struct ExtensionManager {
     extensions: Mutex<HashMap<String, Extension>>,
+    attachments: Mutex<Vec<Attachment>>,
+    restart_backoff: Mutex<HashMap<String, RestartBackoff>>,
+    provider: SharedProvider,
+    /// Samplers of the extensions allowed to sample, kept across restarts so their token
+    /// budget is per session
+    samplers: Mutex<HashMap<String, Arc<ExtensionSampler>>>,
+    roots: SharedRoots,
+    env: SessionEnv,
+    extension_dir: ExtensionDir,
+    resource_subscriptions: SharedSubscriptions,
+    /// Tasks recording the resource updates of each extension with subscriptions
+    resource_listeners: Mutex<HashMap<String, task::JoinHandle<()>>>,
+    /// Extensions started from the extension config, the only ones reloading it may stop or
+    /// restart. Those added for the session alone are left running
+    config_extensions: Mutex<HashSet<String>>,
+    /// Background task checking the health of the extensions, see `supervise`
+    supervisor: Mutex<Option<task::JoinHandle<()>>>,
+    /// What the supervisor did since the last `take_recovery_notifications`
+    recovery_notifications: Mutex<Vec<(String, ServerNotification)>>,
+}
+
+/// How the running extensions differ from the configured ones
+#[derive(Debug, Default)]
+pub struct ExtensionConfigDiff {
+    /// Enabled in the config but not running
+    pub to_start: Vec<ExtensionConfig>,
+    /// Running with a config that has since changed
+    pub to_restart: Vec<ExtensionConfig>,
+    /// Running but disabled or removed in the config
+    pub to_stop: Vec<String>,
 }
 
 /// A flattened representation of a resource used by the agent to prepare inference
@@ -149,10 +236,86 @@ impl Default for ExtensionManager {
     }
 }
 
+/// Add what the extension wrote to stderr since it had written `since` lines to an error
+fn with_stderr(message: String, extension: &str, since: usize) -> String {
+    let lines =
+        extension_stderr::lines_since(extension, since, extension_stderr::TOOL_ERROR_STDERR_LINES);
+    if lines.is_empty() {
+        return message;
+    }
+    format!(
+        "{}\n\nLast lines the extension wrote to stderr:\n{}",
+        message,
+        lines.join("\n")
+    )
+}
+
+/// Rename the extension's tools and replace their descriptions as configured, returning the
+/// tools and the own name of each renamed tool by its alias. An alias that would clash with
+/// another tool of the extension is ignored
+fn apply_tool_overrides(
+    mut tools: Vec<Tool>,
+    overrides: &HashMap<String, ToolOverride>,
+) -> (Vec<Tool>, HashMap<String, String>) {
+    let mut aliases = HashMap::new();
+    if overrides.is_empty() {
+        return (tools, aliases);
+    }
+    let names: Vec<String> = tools.iter().map(|tool| tool.name.to_string()).collect();
+    for tool in &mut tools {
+        let Some(tool_override) = overrides.get(tool.name.as_ref()) else {
+            continue;
+        };
+        if let Some(description) = &tool_override.description {
+            tool.description = Some(description.clone().into());
+        }
+        if let Some(alias) = &tool_override.name {
+            let clashes = names.iter().any(|name| name == alias && name != &tool.name)
+                || overrides.iter().any(|(other, o)| {
+                    other.as_str() != tool.name && o.name.as_ref() == Some(alias)
+                });
+            if clashes {
+                warn!(
+                    "Ignoring alias {} of tool {}, another tool has that name",
+                    alias, tool.name
+                );
+            } else {
+                aliases.insert(alias.clone(), tool.name.to_string());
+                tool.name = alias.clone().into();
+            }
+        }
+    }
+    (tools, aliases)
+}
+
 async fn child_process_client(
+    name: &str,
     mut command: Command,
     timeout: &Option<u64>,
-) -> ExtensionResult<McpClient> {
+    sampling: Option<Arc<dyn SamplingHandler>>,
+    roots: SharedRoots,
+    env: SessionEnv,
+    extension_dir: ExtensionDir,
+) -> ExtensionResult<(McpClient, Option<ProcessGroup>)> {
+    // The variables of the session win over those configured for the extension. Their names
+    // are passed too, so the developer extension can forward them into a dev container
+    {
+        let env = env.lock().await;
+        command.envs(env.iter());
+        if !env.is_empty() {
+            let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            command.env(SESSION_ENV_KEYS_ENV, keys.join(","));
+        }
+    }
+    if let Some(dir) = extension_dir
+        .lock()
+        .await
+        .clone()
+        .filter(|dir| dir.is_dir())
+    {
+        command.current_dir(dir);
+    }
     #[cfg(unix)]
     command.process_group(0);
     #[cfg(windows)]
@@ -160,27 +323,40 @@ async fn child_process_client(
     let (transport, mut stderr) = TokioChildProcess::builder(command)
         .stderr(Stdio::piped())
         .spawn()?;
-    let mut stderr = stderr.take().ok_or_else(|| {
+    // The extension leads its own process group, so whatever it starts can be stopped with it
+    let process_group = transport.id().map(ProcessGroup::track);
+    let stderr = stderr.take().ok_or_else(|| {
         ExtensionError::SetupError("failed to attach child process stderr".to_owned())
     })?;
 
+    // Forward stderr to the log as it arrives, and keep all of it to explain a failed start
+    let extension_name = name.to_string();
     let stderr_task = tokio::spawn(async move {
-        let mut all_stderr = Vec::new();
-        stderr.read_to_end(&mut all_stderr).await?;
-        Ok::<String, std::io::Error>(String::from_utf8_lossy(&all_stderr).into())
+        let mut reader = BufReader::new(stderr);
+        let mut all_stderr = String::new();
+        let mut line = Vec::new();
+        while reader.read_until(b'\n', &mut line).await? > 0 {
+            let text = String::from_utf8_lossy(&line);
+            extension_stderr::record(&extension_name, text.trim_end());
+            all_stderr.push_str(&text);
+            line.clear();
+        }
+        Ok::<String, std::io::Error>(all_stderr)
     });
 
     let client_result = McpClient::connect(
         transport,
         Duration::from_secs(timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT)),
+        sampling,
+        roots,
     )
     .await;
 
     match client_result {
-        Ok(client) => Ok(client),
+        Ok(client) => Ok((client, process_group)),
         Err(error) => {
             let error_task_out = stderr_task.await?;
-            Err::<McpClient, ExtensionError>(match error_task_out {
+            Err::<_, ExtensionError>(match error_task_out {
                 Ok(stderr_content) => ProcessExit::new(stderr_content, error).into(),
                 Err(e) => e.into(),
             })
@@ -188,127 +364,193 @@ async fn child_process_client(
     }
 }
 
-impl ExtensionManager {
-    pub fn new() -> Self {
-        Self {
-            extensions: Mutex::new(HashMap::new()),
+/// Start an extension and connect to it, answering its sampling and roots requests with
+/// `sampling` and `roots`, giving it the variables in `env` when it runs as a process and,
+/// if it is remote and needs authorization, using the browser flow only when `interactive`
+async fn connect_extension(
+    config: &ExtensionConfig,
+    sampling: Option<Arc<dyn SamplingHandler>>,
+    roots: SharedRoots,
+    env: SessionEnv,
+    extension_dir: ExtensionDir,
+    interactive: bool,
+) -> ExtensionResult<ConnectedExtension> {
+    let sanitized_name = normalize(config.key().to_string());
+    let mut temp_dir = None;
+    let mut process_group = None;
+
+    /// Helper function to merge environment variables from direct envs and keychain-stored env_keys.
+    /// env_keys are only read from the secrets stored for this extension
+    async fn merge_environments(
+        envs: &Envs,
+        env_keys: &[String],
+        ext_key: &str,
+        ext_name: &str,
+    ) -> Result<HashMap<String, String>, ExtensionError> {
+        let config_instance = Config::global();
+        let mut all_envs = HashMap::new();
+        for (key, value) in envs.get_env() {
+            // Values may reference environment variables or secrets, e.g. ${keyring:API_KEY}
+            let value = config_instance.expand_references(&value).map_err(|e| {
+                ExtensionError::ConfigError(format!(
+                    "Failed to resolve environment variable '{}': {}",
+                    key, e
+                ))
+            })?;
+            all_envs.insert(key, value);
         }
-    }
 
-    pub async fn supports_resources(&self) -> bool {
-        self.extensions
-            .lock()
-            .await
-            .values()
-            .any(|ext| ext.supports_resources())
-    }
-
-    pub async fn add_extension(&self, config: ExtensionConfig) -> ExtensionResult<()> {
-        let config_name = config.key().to_string();
-        let sanitized_name = normalize(config_name.clone());
-        let mut temp_dir = None;
-
-        /// Helper function to merge environment variables from direct envs and keychain-stored env_keys
-        async fn merge_environments(
-            envs: &Envs,
-            env_keys: &[String],
-            ext_name: &str,
-        ) -> Result<HashMap<String, String>, ExtensionError> {
-            let mut all_envs = envs.get_env();
-            let config_instance = Config::global();
-
-            for key in env_keys {
-                // If the Envs payload already contains the key, prefer that value
-                // over looking into the keychain/secret store
-                if all_envs.contains_key(key) {
-                    continue;
-                }
-
-                match config_instance.get(key, true) {
-                    Ok(value) => {
-                        if value.is_null() {
-                            warn!(
-                                key = %key,
-                                ext_name = %ext_name,
-                                "Secret key not found in config (returned null)."
-                            );
-                            continue;
-                        }
+        for key in env_keys {
+            // If the Envs payload already contains the key, prefer that value
+            // over looking into the keychain/secret store
+            if all_envs.contains_key(key) {
+                continue;
+            }
 
-                        // Try to get string value
-                        if let Some(str_val) = value.as_str() {
-                            all_envs.insert(key.clone(), str_val.to_string());
-                        } else {
-                            warn!(
-                                key = %key,
-                                ext_name = %ext_name,
-                                value_type = %value.get("type").and_then(|t| t.as_str()).unwrap_or("unknown"),
-                                "Secret value is not a string; skipping."
-                            );
-                        }
+            match ExtensionConfigManager::get_secret(ext_key, key) {
+                Ok(value) => {
+                    if value.is_null() {
+                        warn!(
+                            key = %key,
+                            ext_name = %ext_name,
+                            "Secret key not found in config (returned null)."
+                        );
+                        continue;
                     }
-                    Err(e) => {
-                        error!(
+
+                    // Try to get string value
+                    if let Some(str_val) = value.as_str() {
+                        all_envs.insert(key.clone(), str_val.to_string());
+                    } else {
+                        warn!(
                             key = %key,
                             ext_name = %ext_name,
-                            error = %e,
-                            "Failed to fetch secret from config."
+                            value_type = %value.get("type").and_then(|t| t.as_str()).unwrap_or("unknown"),
+                            "Secret value is not a string; skipping."
                         );
-                        return Err(ExtensionError::ConfigError(format!(
-                            "Failed to fetch secret '{}' from config: {}",
-                            key, e
-                        )));
                     }
                 }
+                Err(e) => {
+                    error!(
+                        key = %key,
+                        ext_name = %ext_name,
+                        error = %e,
+                        "Failed to fetch secret from config."
+                    );
+                    return Err(ExtensionError::ConfigError(format!(
+                        "Failed to fetch secret '{}' from config: {}",
+                        key, e
+                    )));
+                }
             }
-
-            Ok(all_envs)
         }
 
-        let client: Box<dyn McpClientTrait> = match &config {
-            ExtensionConfig::Sse { uri, timeout, .. } => {
-                let transport = SseClientTransport::start(uri.to_string()).await.map_err(
-                    |transport_error| {
-                        ClientInitializeError::transport::<SseClientTransport<reqwest::Client>>(
-                            transport_error,
-                            "connect",
-                        )
-                    },
-                )?;
-                Box::new(
-                    McpClient::connect(
-                        transport,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
+        Ok(all_envs)
+    }
+
+    let authorize = move |uri: &String, name: &String| {
+        let (uri, name) = (uri.clone(), name.clone());
+        async move {
+            if interactive {
+                oauth_flow(&uri, &name).await
+            } else {
+                cached_oauth(&uri, &name).await
             }
-            ExtensionConfig::StreamableHttp {
-                uri,
-                timeout,
-                headers,
-                name,
-                ..
-            } => {
-                let mut default_headers = HeaderMap::new();
-                for (key, value) in headers {
-                    default_headers.insert(
-                        HeaderName::try_from(key).map_err(|_| {
-                            ExtensionError::ConfigError(format!("invalid header: {}", key))
-                        })?,
-                        value.parse().map_err(|_| {
-                            ExtensionError::ConfigError(format!("invalid header value: {}", key))
-                        })?,
-                    );
+        }
+    };
+
+    let client: Box<dyn McpClientTrait> = match config {
+        ExtensionConfig::Sse {
+            uri, timeout, name, ..
+        } => {
+            let timeout =
+                Duration::from_secs(timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT));
+            let client_res = match SseClientTransport::start(uri.to_string()).await {
+                Ok(transport) => {
+                    McpClient::connect(transport, timeout, sampling.clone(), roots.clone()).await
                 }
-                let client = reqwest::Client::builder()
-                    .default_headers(default_headers)
-                    .build()
-                    .map_err(|_| {
-                        ExtensionError::ConfigError("could not construct http client".to_string())
+                Err(transport_error) => Err(ClientInitializeError::transport::<
+                    SseClientTransport<reqwest::Client>,
+                >(transport_error, "connect")),
+            };
+            let client = match client_res {
+                Ok(client) => client,
+                Err(e) => {
+                    // as for streamable http, make an attempt at oauth, but failing that,
+                    // return the original error
+                    let am = match authorize(uri, name).await {
+                        Ok(am) => am,
+                        Err(_) => return Err(e.into()),
+                    };
+                    let client = AuthClient::new(reqwest::Client::default(), am);
+                    persist_refreshed_credentials(name, &client.auth_manager);
+                    let transport = SseClientTransport::start_with_client(
+                        client,
+                        SseClientConfig {
+                            sse_endpoint: uri.clone().into(),
+                            ..Default::default()
+                        },
+                    )
+                    .await
+                    .map_err(|transport_error| {
+                        ClientInitializeError::transport::<
+                            SseClientTransport<AuthClient<reqwest::Client>>,
+                        >(transport_error, "connect")
                     })?;
+                    McpClient::connect(transport, timeout, sampling.clone(), roots.clone()).await?
+                }
+            };
+            Box::new(client)
+        }
+        ExtensionConfig::StreamableHttp {
+            uri,
+            timeout,
+            headers,
+            name,
+            ..
+        } => {
+            let mut default_headers = HeaderMap::new();
+            for (key, value) in headers {
+                default_headers.insert(
+                    HeaderName::try_from(key).map_err(|_| {
+                        ExtensionError::ConfigError(format!("invalid header: {}", key))
+                    })?,
+                    value.parse().map_err(|_| {
+                        ExtensionError::ConfigError(format!("invalid header value: {}", key))
+                    })?,
+                );
+            }
+            let client = reqwest::Client::builder()
+                .default_headers(default_headers)
+                .build()
+                .map_err(|_| {
+                    ExtensionError::ConfigError("could not construct http client".to_string())
+                })?;
+            let transport = StreamableHttpClientTransport::with_client(
+                client,
+                StreamableHttpClientTransportConfig {
+                    uri: uri.clone().into(),
+                    ..Default::default()
+                },
+            );
+            let client_res = McpClient::connect(
+                transport,
+                Duration::from_secs(timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT)),
+                sampling.clone(),
+                roots.clone(),
+            )
+            .await;
+            let client = if let Err(e) = client_res {
+                // make an attempt at oauth, but failing that, return the original error,
+                // because this might not have been an auth error at all.
+                // TODO: when rmcp supports it, we should trigger this flow on 401s with
+                // WWW-Authenticate headers, not just any init error
+                let am = match authorize(uri, name).await {
+                    Ok(am) => am,
+                    Err(_) => return Err(e.into()),
+                };
+                let client = AuthClient::new(reqwest::Client::default(), am);
+                persist_refreshed_credentials(name, &client.auth_manager);
                 let transport = StreamableHttpClientTransport::with_client(
                     client,
                     StreamableHttpClientTransportConfig {
@@ -316,108 +558,417 @@ impl ExtensionManager {
                         ..Default::default()
                     },
                 );
-                let client_res = McpClient::connect(
+                McpClient::connect(
                     transport,
                     Duration::from_secs(
                         timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
                     ),
+                    sampling.clone(),
+                    roots.clone(),
                 )
-                .await;
-                let client = if let Err(e) = client_res {
-                    // make an attempt at oauth, but failing that, return the original error,
-                    // because this might not have been an auth error at all.
-                    // TODO: when rmcp supports it, we should trigger this flow on 401s with
-                    // WWW-Authenticate headers, not just any init error
-                    let am = match oauth_flow(uri, name).await {
-                        Ok(am) => am,
-                        Err(_) => return Err(e.into()),
-                    };
-                    let client = AuthClient::new(reqwest::Client::default(), am);
-                    let transport = StreamableHttpClientTransport::with_client(
-                        client,
-                        StreamableHttpClientTransportConfig {
-                            uri: uri.clone().into(),
-                            ..Default::default()
-                        },
-                    );
-                    McpClient::connect(
-                        transport,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?
-                } else {
-                    client_res?
-                };
-                Box::new(client)
-            }
-            ExtensionConfig::Stdio {
-                cmd,
-                args,
-                envs,
-                env_keys,
+                .await?
+            } else {
+                client_res?
+            };
+            Box::new(client)
+        }
+        ExtensionConfig::Stdio {
+            cmd,
+            args,
+            envs,
+            env_keys,
+            timeout,
+            ..
+        } => {
+            let all_envs =
+                merge_environments(envs, env_keys, &config.key(), &sanitized_name).await?;
+            let command = Command::new(cmd).configure(|command| {
+                command.args(args).envs(all_envs);
+            });
+
+            // Check for malicious packages before launching the process
+            extension_malware_check::deny_if_malicious_cmd_args(cmd, args).await?;
+
+            let (client, group) = child_process_client(
+                &sanitized_name,
+                command,
                 timeout,
-                ..
-            } => {
-                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
-                let command = Command::new(cmd).configure(|command| {
-                    command.args(args).envs(all_envs);
+                sampling.clone(),
+                roots.clone(),
+                env.clone(),
+                extension_dir.clone(),
+            )
+            .await?;
+            process_group = group;
+            Box::new(client)
+        }
+        ExtensionConfig::Builtin {
+            name,
+            display_name: _,
+            description: _,
+            timeout,
+            bundled: _,
+            available_tools: _,
+        } => {
+            let cmd = std::env::current_exe()
+                .expect("should find the current executable")
+                .to_str()
+                .expect("should resolve executable to string path")
+                .to_string();
+            let command = Command::new(cmd).configure(|command| {
+                command.arg("mcp").arg(name);
+            });
+            let (client, group) = child_process_client(
+                &sanitized_name,
+                command,
+                timeout,
+                sampling.clone(),
+                roots.clone(),
+                env.clone(),
+                extension_dir.clone(),
+            )
+            .await?;
+            process_group = group;
+            Box::new(client)
+        }
+        ExtensionConfig::InlinePython {
+            name,
+            code,
+            timeout,
+            dependencies,
+            ..
+        } => {
+            let dir = tempdir()?;
+            let file_path = dir.path().join(format!("{}.py", name));
+            temp_dir = Some(dir);
+            std::fs::write(&file_path, code)?;
+
+            let command = Command::new("uvx").configure(|command| {
+                command.arg("--with").arg("mcp");
+
+                dependencies.iter().flatten().for_each(|dep| {
+                    command.arg("--with").arg(dep);
                 });
 
-                // Check for malicious packages before launching the process
-                extension_malware_check::deny_if_malicious_cmd_args(cmd, args).await?;
+                command.arg("python").arg(file_path.to_str().unwrap());
+            });
 
-                let client = child_process_client(command, timeout).await?;
-                Box::new(client)
-            }
-            ExtensionConfig::Builtin {
-                name,
-                display_name: _,
-                description: _,
+            let (client, group) = child_process_client(
+                &sanitized_name,
+                command,
                 timeout,
-                bundled: _,
-                available_tools: _,
-            } => {
-                let cmd = std::env::current_exe()
-                    .expect("should find the current executable")
-                    .to_str()
-                    .expect("should resolve executable to string path")
-                    .to_string();
-                let command = Command::new(cmd).configure(|command| {
-                    command.arg("mcp").arg(name);
-                });
-                let client = child_process_client(command, timeout).await?;
-                Box::new(client)
+                sampling.clone(),
+                roots.clone(),
+                env.clone(),
+                extension_dir.clone(),
+            )
+            .await?;
+            process_group = group;
+
+            Box::new(client)
+        }
+        _ => unreachable!(),
+    };
+
+    Ok((client, temp_dir, process_group))
+}
+
+/// The root for a directory, as shared with extensions
+fn root_for(dir: &Path) -> Option<Root> {
+    let uri = url::Url::from_directory_path(dir).ok()?;
+    Some(Root {
+        uri: uri.to_string(),
+        name: dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+    })
+}
+
+/// Delay before the next restart attempt after the given number of failed ones
+fn restart_delay(failures: u32) -> Duration {
+    RESTART_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(RESTART_MAX_DELAY)
+}
+
+fn recovery_notification(
+    level: LoggingLevel,
+    extension: &str,
+    message: String,
+) -> ServerNotification {
+    ServerNotification::LoggingMessageNotification(LoggingMessageNotification {
+        method: LoggingMessageNotificationMethod,
+        params: LoggingMessageNotificationParam {
+            data: serde_json::json!({
+                "type": "extension_recovery",
+                "extension": extension,
+                "message": message,
+            }),
+            level,
+            logger: None,
+        },
+        extensions: Default::default(),
+    })
+}
+
+/// Whether extensions with a cached manifest are only started on first use
+fn lazy_extensions_enabled() -> bool {
+    Config::global()
+        .get_param::<String>("GOOSE_LAZY_EXTENSIONS")
+        .map(|value| value.to_lowercase() == "true")
+        .unwrap_or(false)
+}
+
+/// Cache what a freshly started extension advertises, for the next lazy start
+async fn save_manifest(config: &ExtensionConfig, client: &dyn McpClientTrait) {
+    let result = match ExtensionManifest::fetch(client).await {
+        Ok(manifest) => manifest.save(config),
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = result {
+        warn!(extension = %config.key(), error = %e, "Failed to cache extension manifest");
+    }
+}
+
+/// Starts the extension when a lazy client is first used, refreshing its manifest
+fn lazy_connector(
+    config: ExtensionConfig,
+    sampling: Option<Arc<dyn SamplingHandler>>,
+    roots: SharedRoots,
+    env: SessionEnv,
+    extension_dir: ExtensionDir,
+) -> Connector {
+    Arc::new(move || {
+        let config = config.clone();
+        let sampling = sampling.clone();
+        let roots = roots.clone();
+        let env = env.clone();
+        let extension_dir = extension_dir.clone();
+        async move {
+            let (client, temp_dir, process_group) =
+                connect_extension(&config, sampling, roots, env, extension_dir, true).await?;
+            save_manifest(&config, client.as_ref()).await;
+            Ok((client, temp_dir, process_group))
+        }
+        .boxed()
+    })
+}
+
+impl ExtensionManager {
+    pub fn new() -> Self {
+        Self {
+            extensions: Mutex::new(HashMap::new()),
+            attachments: Mutex::new(Vec::new()),
+            restart_backoff: Mutex::new(HashMap::new()),
+            provider: Arc::new(Mutex::new(None)),
+            samplers: Mutex::new(HashMap::new()),
+            resource_subscriptions: Arc::default(),
+            resource_listeners: Mutex::new(HashMap::new()),
+            config_extensions: Mutex::new(HashSet::new()),
+            supervisor: Mutex::new(None),
+            recovery_notifications: Mutex::new(Vec::new()),
+            roots: Arc::new(Mutex::new(
+                std::env::current_dir()
+                    .ok()
+                    .and_then(|dir| root_for(&dir))
+                    .into_iter()
+                    .collect(),
+            )),
+            env: Arc::default(),
+            extension_dir: Arc::default(),
+        }
+    }
+
+    /// Set the provider extensions sample with
+    pub async fn set_provider(&self, provider: Arc<dyn Provider>) {
+        *self.provider.lock().await = Some(provider);
+    }
+
+    /// Start the extension processes added from now on in `dir`, rather than in the directory
+    /// goose runs in, for agents serving a session in another directory
+    pub async fn set_extension_dir(&self, dir: PathBuf) {
+        *self.extension_dir.lock().await = Some(dir);
+    }
+
+    /// Share the given directories with extensions as their roots, telling the running
+    /// extensions when they change
+    pub async fn set_roots(&self, dirs: &[PathBuf]) {
+        let roots: Vec<Root> = dirs.iter().filter_map(|dir| root_for(dir)).collect();
+        {
+            let mut current = self.roots.lock().await;
+            if current
+                .iter()
+                .map(|r| &r.uri)
+                .eq(roots.iter().map(|r| &r.uri))
+            {
+                return;
             }
-            ExtensionConfig::InlinePython {
-                name,
-                code,
-                timeout,
-                dependencies,
-                ..
-            } => {
-                let dir = tempdir()?;
-                let file_path = dir.path().join(format!("{}.py", name));
-                temp_dir = Some(dir);
-                std::fs::write(&file_path, code)?;
+            *current = roots;
+        }
+
+        let clients: Vec<_> = self
+            .extensions
+            .lock()
+            .await
+            .iter()
+            .map(|(name, ext)| (name.clone(), ext.get_client()))
+            .collect();
+        for (name, client) in clients {
+            if let Err(e) = client.lock().await.notify_roots_list_changed().await {
+                warn!(extension = %name, error = %e, "Failed to notify extension of changed roots");
+            }
+        }
+    }
 
-                let command = Command::new("uvx").configure(|command| {
-                    command.arg("--with").arg("mcp");
+    /// Give the extension processes started from now on these environment variables, on top of
+    /// their own. Used for variables meant for one session rather than the global config
+    pub async fn set_session_env(&self, env: HashMap<String, String>) {
+        *self.env.lock().await = env;
+    }
 
-                    dependencies.iter().flatten().for_each(|dep| {
-                        command.arg("--with").arg(dep);
-                    });
+    /// Set one of the session's environment variables, keeping the others
+    pub async fn set_session_env_var(&self, key: &str, value: String) {
+        self.env.lock().await.insert(key.to_string(), value);
+    }
 
-                    command.arg("python").arg(file_path.to_str().unwrap());
-                });
+    /// The sampling handler for an extension, if its config allows it to sample
+    async fn sampler_for(&self, config_key: &str) -> Option<Arc<dyn SamplingHandler>> {
+        let permission = ExtensionConfigManager::get_sampling(config_key)?;
+        let sampler = self
+            .samplers
+            .lock()
+            .await
+            .entry(config_key.to_string())
+            .or_insert_with(|| {
+                Arc::new(ExtensionSampler::new(
+                    config_key.to_string(),
+                    &permission,
+                    self.provider.clone(),
+                ))
+            })
+            .clone();
+        Some(sampler)
+    }
+
+    pub async fn supports_resources(&self) -> bool {
+        if !self.attachments.lock().await.is_empty() {
+            return true;
+        }
+        self.extensions
+            .lock()
+            .await
+            .values()
+            .any(|ext| ext.supports_resources())
+    }
+
+    /// Expose a user attachment through the platform resource tools
+    pub async fn add_attachment(&self, attachment: Attachment) {
+        self.attachments.lock().await.push(attachment);
+    }
+
+    pub async fn list_attachments(&self) -> Vec<Attachment> {
+        self.attachments.lock().await.clone()
+    }
+
+    async fn read_attachment(&self, uri: &str) -> Result<Vec<Content>, ErrorData> {
+        let attachment = self
+            .attachments
+            .lock()
+            .await
+            .iter()
+            .find(|a| a.uri == uri)
+            .cloned()
+            .ok_or_else(|| {
+                ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!("Attachment with uri '{}' not found", uri),
+                    None,
+                )
+            })?;
+
+        match attachment.read_text() {
+            Ok(Some(text)) => Ok(vec![Content::text(format!("{}\n\n{}", uri, text))]),
+            Ok(None) => Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!(
+                    "Attachment '{}' is binary ({}) and cannot be read as text",
+                    attachment.name, attachment.mime_type
+                ),
+                None,
+            )),
+            Err(e) => Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Could not read attachment {}: {}", uri, e),
+                None,
+            )),
+        }
+    }
 
-                let client = child_process_client(command, timeout).await?;
+    async fn list_attachment_resources(&self) -> Vec<Content> {
+        let attachments = self.attachments.lock().await;
+        if attachments.is_empty() {
+            return vec![];
+        }
+        let resource_list = attachments
+            .iter()
+            .map(|a| {
+                format!(
+                    "{} - {}, uri: ({})",
+                    ATTACHMENTS_RESOURCE_OWNER, a.name, a.uri
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        vec![Content::text(resource_list)]
+    }
 
-                Box::new(client)
+    pub async fn add_extension(&self, config: ExtensionConfig) -> ExtensionResult<()> {
+        let config_key = config.key().to_string();
+        let sanitized_name = normalize(config_key.clone());
+
+        if lazy_extensions_enabled() {
+            if let Some(manifest) = ExtensionManifest::load(&config) {
+                let server_info = manifest.server_info.clone();
+                let client = LazyExtensionClient::new(
+                    sanitized_name.clone(),
+                    manifest,
+                    lazy_connector(
+                        config.clone(),
+                        self.sampler_for(&config_key).await,
+                        self.roots.clone(),
+                        self.env.clone(),
+                        self.extension_dir.clone(),
+                    ),
+                );
+                if ExtensionConfigManager::is_eager(&config_key) {
+                    client.warm_up();
+                }
+                self.add_client(
+                    sanitized_name,
+                    config,
+                    Arc::new(Mutex::new(Box::new(client))),
+                    server_info,
+                    None,
+                    None,
+                )
+                .await;
+                return Ok(());
             }
-            _ => unreachable!(),
-        };
+        }
+
+        let sampling = self.sampler_for(&config_key).await;
+        let (client, temp_dir, process_group) = connect_extension(
+            &config,
+            sampling,
+            self.roots.clone(),
+            self.env.clone(),
+            self.extension_dir.clone(),
+            true,
+        )
+        .await?;
+        if lazy_extensions_enabled() {
+            save_manifest(&config, client.as_ref()).await;
+        }
 
         let server_info = client.get_info().cloned();
         self.add_client(
@@ -426,6 +977,7 @@ impl ExtensionManager {
             Arc::new(Mutex::new(client)),
             server_info,
             temp_dir,
+            process_group,
         )
         .await;
 
@@ -439,11 +991,254 @@ impl ExtensionManager {
         client: McpClientBox,
         info: Option<ServerInfo>,
         temp_dir: Option<TempDir>,
+        process_group: Option<ProcessGroup>,
     ) {
-        self.extensions
+        self.extensions.lock().await.insert(
+            name,
+            Extension::new(config, client, info, temp_dir, process_group),
+        );
+    }
+
+    /// Check the health of the running extensions in the background from now on, so a turn
+    /// never waits on pings or restarts. The supervisor stops once the manager is dropped
+    pub async fn supervise(self: &Arc<Self>) {
+        let mut supervisor = self.supervisor.lock().await;
+        if supervisor.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+        let manager = Arc::downgrade(self);
+        *supervisor = Some(task::spawn(async move {
+            let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes at once, and the extensions were just started
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                let notifications = manager.check_health().await;
+                manager
+                    .recovery_notifications
+                    .lock()
+                    .await
+                    .extend(notifications);
+            }
+        }));
+    }
+
+    /// The notifications of the restarts the supervisor attempted since the last call
+    pub async fn take_recovery_notifications(&self) -> Vec<(String, ServerNotification)> {
+        std::mem::take(&mut *self.recovery_notifications.lock().await)
+    }
+
+    /// Ping the running extensions and restart the ones that crashed or stopped responding,
+    /// backing off exponentially while restarts keep failing. Returns a notification
+    /// describing each recovery attempt, keyed by extension name
+    pub async fn check_health(&self) -> Vec<(String, ServerNotification)> {
+        let candidates: Vec<_> = {
+            let backoff = self.restart_backoff.lock().await;
+            let now = Instant::now();
+            self.extensions
+                .lock()
+                .await
+                .iter()
+                .filter(|(_, ext)| !matches!(ext.config, ExtensionConfig::Frontend { .. }))
+                .filter(|(name, _)| backoff.get(*name).is_none_or(|b| b.retry_at <= now))
+                .map(|(name, ext)| (name.clone(), ext.config.clone(), ext.get_client()))
+                .collect()
+        };
+
+        let checks = candidates
+            .into_iter()
+            .map(|(name, config, client)| async move {
+                let health = match client.try_lock() {
+                    Ok(guard) => {
+                        match tokio::time::timeout(
+                            HEALTH_CHECK_TIMEOUT,
+                            guard.ping(CancellationToken::default()),
+                        )
+                        .await
+                        {
+                            Ok(result) => result.map_err(|e| e.to_string()),
+                            Err(_) => Err(format!(
+                                "no response within {}s",
+                                HEALTH_CHECK_TIMEOUT.as_secs()
+                            )),
+                        }
+                    }
+                    // Busy with a tool call, so it is still responding
+                    Err(_) => Ok(()),
+                };
+                (name, config, client, health)
+            });
+        let results = future::join_all(checks).await;
+
+        let mut notifications = Vec::new();
+        for (name, config, client, health) in results {
+            let Err(reason) = health else {
+                self.restart_backoff.lock().await.remove(&name);
+                continue;
+            };
+            warn!(extension = %name, reason = %reason, "Extension stopped responding, restarting");
+            let notification = match self.restart_extension(&name, &config, &client, false).await {
+                Ok(()) => {
+                    self.restart_backoff.lock().await.remove(&name);
+                    recovery_notification(
+                        LoggingLevel::Info,
+                        &name,
+                        format!(
+                            "Extension '{}' stopped responding ({}) and was restarted",
+                            name, reason
+                        ),
+                    )
+                }
+                Err(e) => {
+                    let mut backoff = self.restart_backoff.lock().await;
+                    let failures = backoff.get(&name).map_or(0, |b| b.failures) + 1;
+                    let delay = restart_delay(failures);
+                    backoff.insert(
+                        name.clone(),
+                        RestartBackoff {
+                            failures,
+                            retry_at: Instant::now() + delay,
+                        },
+                    );
+                    recovery_notification(
+                        LoggingLevel::Warning,
+                        &name,
+                        format!(
+                            "Extension '{}' stopped responding ({}) and could not be restarted: {}. Retrying in {}s",
+                            name,
+                            reason,
+                            e,
+                            delay.as_secs()
+                        ),
+                    )
+                }
+            };
+            notifications.push((name, notification));
+        }
+        notifications
+    }
+
+    /// Start a fresh instance of an extension in place of its current client, and replay
+    /// the server info it registered with and the resources it was subscribed to. The current
+    /// client is only replaced once the new one is connected
+    async fn restart_extension(
+        &self,
+        name: &str,
+        config: &ExtensionConfig,
+        client: &McpClientBox,
+        interactive: bool,
+    ) -> ExtensionResult<()> {
+        let sampling = self.sampler_for(&config.key()).await;
+        let (new_client, temp_dir, process_group) = connect_extension(
+            config,
+            sampling,
+            self.roots.clone(),
+            self.env.clone(),
+            self.extension_dir.clone(),
+            interactive,
+        )
+        .await?;
+        if lazy_extensions_enabled() {
+            save_manifest(config, new_client.as_ref()).await;
+        }
+        let server_info = new_client.get_info().cloned();
+        *client.lock().await = new_client;
+
+        if let Some(extension) = self.extensions.lock().await.get_mut(name) {
+            extension.server_info = server_info;
+            extension._temp_dir = temp_dir;
+            extension._process_group = process_group;
+        }
+
+        // The new server knows nothing of the old one's subscriptions
+        let uris = self.resource_subscriptions.lock().await.uris(name);
+        if !uris.is_empty() {
+            for uri in &uris {
+                if let Err(e) = client
+                    .lock()
+                    .await
+                    .subscribe_resource(uri, CancellationToken::default())
+                    .await
+                {
+                    warn!(extension = %name, uri = %uri, error = %e, "Failed to resubscribe to resource");
+                }
+            }
+            if let Some(listener) = self.resource_listeners.lock().await.remove(name) {
+                listener.abort();
+            }
+            self.listen_for_resource_updates(name, client).await;
+        }
+        Ok(())
+    }
+
+    /// Record that a running extension was started from the extension config
+    pub async fn mark_from_config(&self, name: &str) {
+        self.config_extensions
+            .lock()
+            .await
+            .insert(normalize(name.to_string()));
+    }
+
+    /// Move a running extension to a changed config, connecting the new instance before
+    /// dropping the old one, which keeps running when the new one fails to start
+    pub async fn reconfigure_extension(&self, config: ExtensionConfig) -> ExtensionResult<()> {
+        let name = normalize(config.key());
+        let client = self
+            .extensions
             .lock()
             .await
-            .insert(name, Extension::new(config, client, info, temp_dir));
+            .get(&name)
+            .map(|ext| ext.get_client())
+            .ok_or_else(|| ExtensionError::SetupError(format!("{} is not running", name)))?;
+        self.restart_extension(&name, &config, &client, true)
+            .await?;
+        if let Some(extension) = self.extensions.lock().await.get_mut(&name) {
+            extension.tool_overrides = ExtensionConfigManager::get_tool_overrides(&config.key());
+            extension.config = config;
+        }
+        Ok(())
+    }
+
+    /// Compare the extensions started from the config with the configured entries. Extensions
+    /// added for the session alone, and frontend extensions provided by the client, are left
+    /// alone
+    pub async fn diff_with_config(&self, entries: &[ExtensionEntry]) -> ExtensionConfigDiff {
+        let extensions = self.extensions.lock().await;
+        let from_config = self.config_extensions.lock().await;
+        let mut diff = ExtensionConfigDiff::default();
+
+        for entry in entries.iter().filter(|entry| entry.enabled) {
+            let name = normalize(entry.config.key());
+            match extensions.get(&name) {
+                None => diff.to_start.push(entry.config.clone()),
+                Some(extension)
+                    if from_config.contains(&name)
+                        && serde_json::to_value(&extension.config).ok()
+                            != serde_json::to_value(&entry.config).ok() =>
+                {
+                    diff.to_restart.push(entry.config.clone())
+                }
+                Some(_) => {}
+            }
+        }
+
+        diff.to_stop = extensions
+            .iter()
+            .filter(|(name, _)| from_config.contains(*name))
+            .filter(|(_, ext)| !matches!(ext.config, ExtensionConfig::Frontend { .. }))
+            .filter(|(name, _)| {
+                !entries
+                    .iter()
+                    .any(|entry| entry.enabled && normalize(entry.config.key()) == **name)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        diff
     }
 
     /// Get extensions info
@@ -466,6 +1261,82 @@ impl ExtensionManager {
     pub async fn remove_extension(&self, name: &str) -> ExtensionResult<()> {
         let sanitized_name = normalize(name.to_string());
         self.extensions.lock().await.remove(&sanitized_name);
+        self.config_extensions.lock().await.remove(&sanitized_name);
+        self.resource_subscriptions
+            .lock()
+            .await
+            .remove_extension(&sanitized_name);
+        if let Some(listener) = self.resource_listeners.lock().await.remove(&sanitized_name) {
+            listener.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop every extension, dropping its client ends the extension process and terminates the
+    /// background processes it started
+    pub async fn shutdown(&self) {
+        let stopped: Vec<(String, Extension)> = self.extensions.lock().await.drain().collect();
+        for (name, _) in &stopped {
+            self.resource_subscriptions
+                .lock()
+                .await
+                .remove_extension(name);
+        }
+        for (_, listener) in self.resource_listeners.lock().await.drain() {
+            listener.abort();
+        }
+        tracing::debug!("Stopped {} extensions", stopped.len());
+    }
+
+    /// The config a running extension was started with
+    pub async fn get_extension_config(&self, name: &str) -> Option<ExtensionConfig> {
+        self.extensions
+            .lock()
+            .await
+            .get(&normalize(name.to_string()))
+            .map(|ext| ext.config.clone())
+    }
+
+    /// The configs of all running extensions, to start the same extensions elsewhere
+    pub async fn extension_configs(&self) -> Vec<ExtensionConfig> {
+        self.extensions
+            .lock()
+            .await
+            .values()
+            .map(|ext| ext.config.clone())
+            .collect()
+    }
+
+    /// Every tool of a running extension, whether or not its allowlist makes it available
+    pub async fn list_all_tools(&self, name: &str) -> ExtensionResult<Vec<Tool>> {
+        let client = self
+            .get_server_client(normalize(name.to_string()))
+            .await
+            .ok_or_else(|| ExtensionError::ConfigError(format!("{} is not running", name)))?;
+        let client_guard = client.lock().await;
+        let mut tools = Vec::new();
+        let mut next_cursor = None;
+        loop {
+            let page = client_guard
+                .list_tools(next_cursor, CancellationToken::default())
+                .await?;
+            tools.extend(page.tools);
+            next_cursor = page.next_cursor;
+            if next_cursor.is_none() {
+                break;
+            }
+        }
+        Ok(tools)
+    }
+
+    /// Limit a running extension to the given tools without restarting it, an empty list
+    /// makes all of its tools available
+    pub async fn set_available_tools(&self, name: &str, tools: Vec<String>) -> ExtensionResult<()> {
+        let mut extensions = self.extensions.lock().await;
+        let extension = extensions
+            .get_mut(&normalize(name.to_string()))
+            .ok_or_else(|| ExtensionError::ConfigError(format!("{} is not running", name)))?;
+        extension.config.set_available_tools(tools);
         Ok(())
     }
 
@@ -523,45 +1394,55 @@ impl ExtensionManager {
                     true
                 }
             })
-            .map(|(name, ext)| (name.clone(), ext.config.clone(), ext.get_client()))
+            .map(|(name, ext)| {
+                (
+                    name.clone(),
+                    ext.config.clone(),
+                    ext.tool_overrides.clone(),
+                    ext.get_client(),
+                )
+            })
             .collect();
 
         let cancel_token = CancellationToken::default();
-        let client_futures = filtered_clients.into_iter().map(|(name, config, client)| {
-            let cancel_token = cancel_token.clone();
-            task::spawn(async move {
-                let mut tools = Vec::new();
-                let client_guard = client.lock().await;
-                let mut client_tools = client_guard.list_tools(None, cancel_token).await?;
-
-                loop {
-                    for tool in client_tools.tools {
-                        let is_available = config.is_tool_available(&tool.name);
-
-                        if is_available {
-                            tools.push(Tool {
-                                name: format!("{}__{}", name, tool.name).into(),
-                                description: tool.description,
-                                input_schema: tool.input_schema,
-                                annotations: tool.annotations,
-                                output_schema: tool.output_schema,
-                            });
+        let client_futures =
+            filtered_clients
+                .into_iter()
+                .map(|(name, config, overrides, client)| {
+                    let cancel_token = cancel_token.clone();
+                    task::spawn(async move {
+                        let mut available = Vec::new();
+                        let client_guard = client.lock().await;
+                        let mut client_tools = client_guard.list_tools(None, cancel_token).await?;
+
+                        loop {
+                            for tool in client_tools.tools {
+                                if config.is_tool_available(&tool.name) {
+                                    available.push(tool);
+                                }
+                            }
+
+                            // Exit loop when there are no more pages
+                            if client_tools.next_cursor.is_none() {
+                                break;
+                            }
+
+                            client_tools = client_guard
+                                .list_tools(client_tools.next_cursor, CancellationToken::default())
+                                .await?;
                         }
-                    }
-
-                    // Exit loop when there are no more pages
-                    if client_tools.next_cursor.is_none() {
-                        break;
-                    }
 
-                    client_tools = client_guard
-                        .list_tools(client_tools.next_cursor, CancellationToken::default())
-                        .await?;
-                }
-
-                Ok::<Vec<Tool>, ExtensionError>(tools)
-            })
-        });
+                        let (tools, aliases) = apply_tool_overrides(available, &overrides);
+                        let tools: Vec<Tool> = tools
+                            .into_iter()
+                            .map(|tool| Tool {
+                                name: format!("{}__{}", name, tool.name).into(),
+                                ..tool
+                            })
+                            .collect();
+                        Ok::<_, ExtensionError>((name, tools, aliases))
+                    })
+                });
 
         // Collect all results concurrently
         let results = future::join_all(client_futures).await;
@@ -570,7 +1451,13 @@ impl ExtensionManager {
         let mut tools = Vec::new();
         for result in results {
             match result {
-                Ok(Ok(client_tools)) => tools.extend(client_tools),
+                Ok(Ok((name, client_tools, aliases))) => {
+                    // Remember the aliases listed to the model to map its tool calls back
+                    if let Some(extension) = self.extensions.lock().await.get_mut(&name) {
+                        extension.tool_aliases = aliases;
+                    }
+                    tools.extend(client_tools)
+                }
                 Ok(Err(err)) => return Err(err),
                 Err(join_err) => return Err(ExtensionError::from(join_err)),
             }
@@ -606,6 +1493,10 @@ impl ExtensionManager {
         let uri = require_str_parameter(&params, "uri")?;
         let extension_name = params.get("extension_name").and_then(|v| v.as_str());
 
+        if uri.starts_with(ATTACHMENT_URI_SCHEME) {
+            return self.read_attachment(uri).await;
+        }
+
         // If extension name is provided, we can just look it up
         if extension_name.is_some() {
             let result = self
@@ -748,6 +1639,7 @@ impl ExtensionManager {
         let extension = params.get("extension").and_then(|v| v.as_str());
 
         match extension {
+            Some(ATTACHMENTS_RESOURCE_OWNER) => Ok(self.list_attachment_resources().await),
             Some(extension_name) => {
                 // Handle single extension case
                 self.list_resources_from_extension(extension_name, cancellation_token)
@@ -772,7 +1664,7 @@ impl ExtensionManager {
                         });
                     });
 
-                let mut all_resources = Vec::new();
+                let mut all_resources = self.list_attachment_resources().await;
                 let mut errors = Vec::new();
 
                 // Process results as they complete
@@ -803,6 +1695,153 @@ impl ExtensionManager {
         }
     }
 
+    pub async fn supports_resource_subscriptions(&self) -> bool {
+        self.extensions
+            .lock()
+            .await
+            .values()
+            .any(|ext| ext.supports_resource_subscriptions())
+    }
+
+    // Function that gets executed for the subscribe_resource tool
+    pub async fn manage_resource_subscription(
+        &self,
+        params: Value,
+        cancellation_token: CancellationToken,
+    ) -> Result<Vec<Content>, ErrorData> {
+        let uri = require_str_parameter(&params, "uri")?;
+        let extension_name = normalize(require_str_parameter(&params, "extension_name")?.into());
+        let unsubscribe = params.get("action").and_then(|v| v.as_str()) == Some("unsubscribe");
+
+        let client = {
+            let extensions = self.extensions.lock().await;
+            let extension = extensions.get(&extension_name).ok_or_else(|| {
+                ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!("Extension '{}' not found", extension_name),
+                    None,
+                )
+            })?;
+            if !extension.supports_resource_subscriptions() {
+                return Err(ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!(
+                        "Extension '{}' does not support resource subscriptions",
+                        extension_name
+                    ),
+                    None,
+                ));
+            }
+            extension.get_client()
+        };
+
+        if unsubscribe {
+            if self
+                .resource_subscriptions
+                .lock()
+                .await
+                .remove(&extension_name, uri)
+            {
+                client
+                    .lock()
+                    .await
+                    .unsubscribe_resource(uri, cancellation_token)
+                    .await
+                    .map_err(|e| {
+                        ErrorData::new(
+                            ErrorCode::INTERNAL_ERROR,
+                            format!("Could not unsubscribe from {}: {}", uri, e),
+                            None,
+                        )
+                    })?;
+            }
+            return Ok(vec![Content::text(format!("Unsubscribed from {}", uri))]);
+        }
+
+        client
+            .lock()
+            .await
+            .subscribe_resource(uri, cancellation_token)
+            .await
+            .map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Could not subscribe to {}: {}", uri, e),
+                    None,
+                )
+            })?;
+        self.resource_subscriptions
+            .lock()
+            .await
+            .add(&extension_name, uri);
+        self.listen_for_resource_updates(&extension_name, &client)
+            .await;
+
+        Ok(vec![Content::text(format!(
+            "Subscribed to {}, changes to it will be added to the conversation",
+            uri
+        ))])
+    }
+
+    /// Start recording the resource updates an extension sends, unless that is already
+    /// happening for its current client
+    async fn listen_for_resource_updates(&self, name: &str, client: &McpClientBox) {
+        let mut listeners = self.resource_listeners.lock().await;
+        if listeners
+            .get(name)
+            .is_some_and(|listener| !listener.is_finished())
+        {
+            return;
+        }
+        let notifications = client.lock().await.subscribe().await;
+        listeners.insert(
+            name.to_string(),
+            resource_subscriptions::listen_for_updates(
+                self.resource_subscriptions.clone(),
+                name.to_string(),
+                notifications,
+            ),
+        );
+    }
+
+    /// The subscribed resources that changed since the last call, read again so the
+    /// conversation can be given their new content. Resources whose content is what the
+    /// conversation was last given are left out
+    pub async fn take_resource_updates(&self) -> Vec<ResourceUpdate> {
+        let changed = self.resource_subscriptions.lock().await.take_changed();
+        let mut updates = Vec::new();
+        for (extension, uri) in changed {
+            match self
+                .read_resource_from_extension(&uri, &extension, CancellationToken::default())
+                .await
+            {
+                Ok(contents) => {
+                    let content = contents
+                        .iter()
+                        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+                        .collect::<Vec<_>>()
+                        .join("\n\n");
+                    if self
+                        .resource_subscriptions
+                        .lock()
+                        .await
+                        .is_new_content(&extension, &uri, &content)
+                    {
+                        updates.push(ResourceUpdate {
+                            content,
+                            extension,
+                            uri,
+                        });
+                    }
+                }
+                Err(e) => {
+                    warn!(extension = %extension, uri = %uri, error = ?e, "Failed to read updated resource")
+                }
+            }
+        }
+        updates
+    }
+
     pub async fn dispatch_tool_call(
         &self,
         tool_call: ToolCall,
@@ -817,7 +1856,7 @@ impl ExtensionManager {
                 })?;
 
         // rsplit returns the iterator in reverse, tool_name is then at 0
-        let tool_name = tool_call
+        let mut tool_name = tool_call
             .name
             .strip_prefix(client_name.as_str())
             .and_then(|s| s.strip_prefix("__"))
@@ -827,6 +1866,9 @@ impl ExtensionManager {
             .to_string();
 
         if let Some(extension) = self.extensions.lock().await.get(&client_name) {
+            if let Some(own_name) = extension.tool_aliases.get(&tool_name) {
+                tool_name = own_name.clone();
+            }
             if !extension.config.is_tool_available(&tool_name) {
                 return Err(ErrorData::new(
                     ErrorCode::RESOURCE_NOT_FOUND,
@@ -845,12 +1887,20 @@ impl ExtensionManager {
         let notifications_receiver = client.lock().await.subscribe().await;
 
         let fut = async move {
+            let _in_flight = tool_calls::track_in_extension(&client_name);
+            let stderr_lines = extension_stderr::line_count(&client_name);
             let client_guard = client.lock().await;
             client_guard
                 .call_tool(&tool_name, arguments, cancellation_token)
                 .await
                 .map(|call| call.content)
-                .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))
+                .map_err(|e| {
+                    ErrorData::new(
+                        ErrorCode::INTERNAL_ERROR,
+                        with_stderr(e.to_string(), &client_name, stderr_lines),
+                        None,
+                    )
+                })
         };
 
         Ok(ToolCallResult {
@@ -1027,6 +2077,41 @@ impl ExtensionManager {
             output_parts.push("No extensions that can be disabled.\n".to_string());
         }
 
+        // Known MCP servers from the registry that aren't configured yet
+        match ExtensionRegistry::cached() {
+            Ok(registry) => {
+                let configured = ExtensionConfigManager::get_all_names().unwrap_or_default();
+                let installable: Vec<String> = registry
+                    .extensions
+                    .iter()
+                    .filter(|extension| !configured.contains(&extension.config.key()))
+                    .map(|extension| {
+                        let required: Vec<&str> = extension
+                            .required_env_keys()
+                            .map(|key| key.name.as_str())
+                            .collect();
+                        if required.is_empty() {
+                            format!("- {} - {}", extension.name, extension.description)
+                        } else {
+                            format!(
+                                "- {} - {} (needs {})",
+                                extension.name,
+                                extension.description,
+                                required.join(", ")
+                            )
+                        }
+                    })
+                    .collect();
+                if !installable.is_empty() {
+                    output_parts.push(format!(
+                        "\n\nExtensions in the registry, the user can install them with `goose configure`:\n{}\n",
+                        installable.join("\n")
+                    ));
+                }
+            }
+            Err(e) => tracing::debug!("Extension registry unavailable: {}", e),
+        }
+
         Ok(vec![Content::text(output_parts.join("\n"))])
     }
 
@@ -1052,6 +2137,7 @@ mod tests {
     use rmcp::model::ListToolsResult;
     use rmcp::model::ReadResourceResult;
     use rmcp::model::ServerNotification;
+    use rmcp::object;
     use serde_json::json;
     use tokio::sync::mpsc;
 
@@ -1076,7 +2162,7 @@ mod tests {
                 bundled: None,
                 available_tools,
             };
-            let extension = Extension::new(config, client, None, None);
+            let extension = Extension::new(config, client, None, None, None);
             self.extensions
                 .lock()
                 .await
@@ -1181,6 +2267,74 @@ mod tests {
         }
     }
 
+    /// A client whose server has gone away
+    struct DeadClient {}
+
+    #[async_trait::async_trait]
+    impl McpClientTrait for DeadClient {
+        fn get_info(&self) -> Option<&InitializeResult> {
+            None
+        }
+
+        async fn list_resources(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListResourcesResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn read_resource(
+            &self,
+            _uri: &str,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ReadResourceResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn list_tools(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListToolsResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn call_tool(
+            &self,
+            _name: &str,
+            _arguments: Value,
+            _cancellation_token: CancellationToken,
+        ) -> Result<CallToolResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn list_prompts(
+            &self,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListPromptsResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn get_prompt(
+            &self,
+            _name: &str,
+            _arguments: Value,
+            _cancellation_token: CancellationToken,
+        ) -> Result<GetPromptResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+            mpsc::channel(1).1
+        }
+
+        async fn ping(&self, _cancellation_token: CancellationToken) -> Result<(), Error> {
+            Err(Error::TransportClosed)
+        }
+    }
+
     #[tokio::test]
     async fn test_get_client_for_tool() {
         let extension_manager = ExtensionManager::new();
@@ -1456,4 +2610,223 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_attachments_are_resources() {
+        let extension_manager = ExtensionManager::new();
+        assert!(!extension_manager.supports_resources().await);
+
+        let source_dir = tempfile::tempdir().unwrap();
+        let source = source_dir.path().join("spec.txt");
+        std::fs::write(&source, "the full specification").unwrap();
+        let attachment = crate::session::attachment::store_attachment(
+            source.to_str().unwrap(),
+            source_dir.path().join("store").as_path(),
+        )
+        .await
+        .unwrap();
+        extension_manager.add_attachment(attachment).await;
+        assert!(extension_manager.supports_resources().await);
+
+        let listed = extension_manager
+            .list_resources(json!({}), CancellationToken::default())
+            .await
+            .unwrap();
+        assert!(listed[0]
+            .as_text()
+            .unwrap()
+            .text
+            .contains("attachment://spec.txt"));
+
+        let read = extension_manager
+            .read_resource(
+                json!({"uri": "attachment://spec.txt"}),
+                CancellationToken::default(),
+            )
+            .await
+            .unwrap();
+        assert!(read[0]
+            .as_text()
+            .unwrap()
+            .text
+            .contains("the full specification"));
+
+        assert!(extension_manager
+            .read_resource(
+                json!({"uri": "attachment://missing.txt"}),
+                CancellationToken::default(),
+            )
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_restart_delay_backs_off() {
+        assert_eq!(restart_delay(1), RESTART_BASE_DELAY);
+        assert_eq!(restart_delay(2), RESTART_BASE_DELAY * 2);
+        assert_eq!(restart_delay(3), RESTART_BASE_DELAY * 4);
+        assert_eq!(restart_delay(64), RESTART_MAX_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_check_health_restarts_dead_extensions() {
+        let extension_manager = ExtensionManager::new();
+        extension_manager
+            .add_mock_extension(
+                "healthy".to_string(),
+                Arc::new(Mutex::new(Box::new(MockClient {}))),
+            )
+            .await;
+        // Nothing listens on the uri, so restarting fails
+        let config = ExtensionConfig::Sse {
+            name: "remote".to_string(),
+            uri: "http://127.0.0.1:1/sse".to_string(),
+            envs: Default::default(),
+            env_keys: vec![],
+            description: None,
+            timeout: Some(5),
+            bundled: None,
+            available_tools: vec![],
+        };
+        extension_manager
+            .add_client(
+                "remote".to_string(),
+                config,
+                Arc::new(Mutex::new(Box::new(DeadClient {}))),
+                None,
+                None,
+                None,
+            )
+            .await;
+
+        let notifications = extension_manager.check_health().await;
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].0, "remote");
+        let ServerNotification::LoggingMessageNotification(notification) = &notifications[0].1
+        else {
+            panic!("expected a logging notification");
+        };
+        assert_eq!(notification.params.level, LoggingLevel::Warning);
+        assert_eq!(notification.params.data["type"], "extension_recovery");
+
+        // Backing off, so the next check leaves it alone
+        assert!(extension_manager.check_health().await.is_empty());
+        assert_eq!(
+            extension_manager
+                .restart_backoff
+                .lock()
+                .await
+                .get("remote")
+                .unwrap()
+                .failures,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_with_config() {
+        let extension_manager = ExtensionManager::new();
+        for name in ["unchanged", "changed", "removed", "session_only"] {
+            extension_manager
+                .add_mock_extension(
+                    name.to_string(),
+                    Arc::new(Mutex::new(Box::new(MockClient {}))),
+                )
+                .await;
+        }
+        for name in ["unchanged", "changed", "removed"] {
+            extension_manager.mark_from_config(name).await;
+        }
+
+        let entry = |name: &str, enabled: bool, timeout: Option<u64>| ExtensionEntry {
+            enabled,
+            eager: false,
+            sampling: None,
+            config: ExtensionConfig::Builtin {
+                name: name.to_string(),
+                display_name: Some(name.to_string()),
+                description: None,
+                timeout,
+                bundled: None,
+                available_tools: vec![],
+            },
+        };
+        let diff = extension_manager
+            .diff_with_config(&[
+                entry("unchanged", true, None),
+                entry("changed", true, Some(60)),
+                entry("removed", false, None),
+                entry("added", true, None),
+                entry("disabled", false, None),
+                entry("session_only", false, Some(60)),
+            ])
+            .await;
+
+        let names = |configs: &[ExtensionConfig]| -> Vec<String> {
+            configs.iter().map(|config| config.name()).collect()
+        };
+        assert_eq!(names(&diff.to_start), vec!["added"]);
+        assert_eq!(names(&diff.to_restart), vec!["changed"]);
+        assert_eq!(diff.to_stop, vec!["removed"]);
+
+        // Added with --with-extension or a recipe, so neither stopped nor restarted
+        extension_manager.remove_extension("removed").await.unwrap();
+        let diff = extension_manager
+            .diff_with_config(&[entry("session_only", true, Some(60))])
+            .await;
+        assert!(diff.to_start.is_empty() && diff.to_restart.is_empty());
+        let mut stopped = diff.to_stop;
+        stopped.sort();
+        assert_eq!(stopped, vec!["changed", "unchanged"]);
+    }
+
+    #[test]
+    fn test_root_for_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = root_for(dir.path()).unwrap();
+        assert!(root.uri.starts_with("file://"));
+        assert!(root.uri.ends_with('/'));
+        assert_eq!(
+            root.name.as_deref(),
+            dir.path().file_name().and_then(|name| name.to_str())
+        );
+        assert!(root_for(Path::new("relative/dir")).is_none());
+    }
+
+    #[test]
+    fn test_apply_tool_overrides() {
+        let tool = |name: &str| Tool::new(name.to_string(), "original", object!({}));
+        let overrides = HashMap::from([
+            (
+                "create_issue".to_string(),
+                ToolOverride {
+                    name: Some("open_ticket".to_string()),
+                    description: Some("Open a ticket in the team tracker".to_string()),
+                },
+            ),
+            (
+                "search".to_string(),
+                ToolOverride {
+                    name: Some("list".to_string()),
+                    description: None,
+                },
+            ),
+        ]);
+
+        let (tools, aliases) = apply_tool_overrides(
+            vec![tool("create_issue"), tool("search"), tool("list")],
+            &overrides,
+        );
+        let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_ref()).collect();
+        assert_eq!(names, vec!["open_ticket", "search", "list"]);
+        assert_eq!(
+            tools[0].description.as_deref(),
+            Some("Open a ticket in the team tracker")
+        );
+        assert_eq!(tools[1].description.as_deref(), Some("original"));
+        assert_eq!(
+            aliases,
+            HashMap::from([("open_ticket".to_string(), "create_issue".to_string())])
+        );
+    }
 }
@@ -0,0 +1,202 @@
+use anyhow::Result;
+use etcetera::{choose_app_strategy, AppStrategy};
+use mcp_client::client::{Error, McpClientTrait};
+use rmcp::model::{Prompt, Resource, ServerInfo, Tool};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+use super::extension::ExtensionConfig;
+
+/// What an extension advertised the last time it was started, so its tools, prompts,
+/// resources, instructions and capabilities can be offered before the extension is running again
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtensionManifest {
+    pub tools: Vec<Tool>,
+    pub server_info: Option<ServerInfo>,
+    #[serde(default)]
+    pub prompts: Vec<Prompt>,
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+}
+
+impl ExtensionManifest {
+    /// Directory holding the manifests of all extensions, in the data dir
+    pub fn dir() -> Result<PathBuf> {
+        Ok(choose_app_strategy(crate::config::APP_STRATEGY.clone())?
+            .in_data_dir("extensions/manifests"))
+    }
+
+    fn path_in(dir: &Path, cache_key: &str) -> PathBuf {
+        dir.join(format!("{}.json", cache_key))
+    }
+
+    /// Hash of the extension config and the binary it runs, so a manifest is only
+    /// reused while neither has changed
+    pub fn cache_key(config: &ExtensionConfig) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_string(config).unwrap_or_default().as_bytes());
+        if let Some(binary) = binary_path(config) {
+            hasher.update(binary.to_string_lossy().as_bytes());
+            if let Ok(metadata) = std::fs::metadata(&binary) {
+                hasher.update(metadata.len().to_le_bytes());
+                if let Ok(modified) = metadata.modified() {
+                    hasher.update(format!("{:?}", modified).as_bytes());
+                }
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Read the manifest of a connected extension, following the list pages. Prompts and
+    /// resources are only listed when the extension has them
+    pub async fn fetch(client: &dyn McpClientTrait) -> Result<Self, Error> {
+        let mut tools = Vec::new();
+        let mut next_cursor = None;
+        loop {
+            let page = client
+                .list_tools(next_cursor, CancellationToken::default())
+                .await?;
+            tools.extend(page.tools);
+            next_cursor = page.next_cursor;
+            if next_cursor.is_none() {
+                break;
+            }
+        }
+
+        let server_info = client.get_info().cloned();
+        let capabilities = server_info.as_ref().map(|info| &info.capabilities);
+        let mut prompts = Vec::new();
+        if capabilities.is_some_and(|c| c.prompts.is_some()) {
+            let mut next_cursor = None;
+            loop {
+                let page = client
+                    .list_prompts(next_cursor, CancellationToken::default())
+                    .await?;
+                prompts.extend(page.prompts);
+                next_cursor = page.next_cursor;
+                if next_cursor.is_none() {
+                    break;
+                }
+            }
+        }
+        let mut resources = Vec::new();
+        if capabilities.is_some_and(|c| c.resources.is_some()) {
+            let mut next_cursor = None;
+            loop {
+                let page = client
+                    .list_resources(next_cursor, CancellationToken::default())
+                    .await?;
+                resources.extend(page.resources);
+                next_cursor = page.next_cursor;
+                if next_cursor.is_none() {
+                    break;
+                }
+            }
+        }
+
+        Ok(Self {
+            tools,
+            server_info,
+            prompts,
+            resources,
+        })
+    }
+
+    pub fn load(config: &ExtensionConfig) -> Option<Self> {
+        Self::load_from(&Self::dir().ok()?, &Self::cache_key(config))
+    }
+
+    pub fn load_from(dir: &Path, cache_key: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(Self::path_in(dir, cache_key)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn save(&self, config: &ExtensionConfig) -> Result<()> {
+        self.save_to(&Self::dir()?, &Self::cache_key(config))
+    }
+
+    pub fn save_to(&self, dir: &Path, cache_key: &str) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(Self::path_in(dir, cache_key), serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// The executable an extension is started from, if it runs locally
+fn binary_path(config: &ExtensionConfig) -> Option<PathBuf> {
+    match config {
+        ExtensionConfig::Stdio { cmd, .. } => {
+            let cmd = Path::new(cmd);
+            if cmd.components().count() > 1 {
+                return Some(cmd.to_path_buf());
+            }
+            std::env::var_os("PATH").and_then(|paths| {
+                std::env::split_paths(&paths)
+                    .map(|dir| dir.join(cmd))
+                    .find(|candidate| candidate.is_file())
+            })
+        }
+        ExtensionConfig::Builtin { .. } => std::env::current_exe().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::object;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = ExtensionManifest {
+            tools: vec![Tool::new(
+                "shell".to_string(),
+                "Run a shell command".to_string(),
+                object!({"type": "object"}),
+            )],
+            server_info: None,
+            ..Default::default()
+        };
+        manifest.save_to(temp_dir.path(), "developer").unwrap();
+
+        let loaded = ExtensionManifest::load_from(temp_dir.path(), "developer").unwrap();
+        assert_eq!(loaded.tools.len(), 1);
+        assert_eq!(loaded.tools[0].name, "shell");
+        assert!(ExtensionManifest::load_from(temp_dir.path(), "memory").is_none());
+
+        // Manifests cached before prompts and resources were kept still load
+        std::fs::write(
+            temp_dir.path().join("old.json"),
+            r#"{"tools": [], "server_info": null}"#,
+        )
+        .unwrap();
+        let old = ExtensionManifest::load_from(temp_dir.path(), "old").unwrap();
+        assert!(old.prompts.is_empty() && old.resources.is_empty());
+    }
+
+    #[test]
+    fn test_cache_key_changes_with_config() {
+        let config = |timeout| ExtensionConfig::Sse {
+            name: "remote".to_string(),
+            uri: "http://localhost:8080/sse".to_string(),
+            envs: Default::default(),
+            env_keys: vec![],
+            description: None,
+            timeout: Some(timeout),
+            bundled: None,
+            available_tools: vec![],
+        };
+        assert_eq!(
+            ExtensionManifest::cache_key(&config(300)),
+            ExtensionManifest::cache_key(&config(300))
+        );
+        assert_ne!(
+            ExtensionManifest::cache_key(&config(300)),
+            ExtensionManifest::cache_key(&config(600))
+        );
+    }
+}
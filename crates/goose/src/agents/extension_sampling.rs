@@ -0,0 +1,296 @@
+use async_trait::async_trait;
+use mcp_client::SamplingHandler;
+use rmcp::model::{
+    Content, CreateMessageRequestParam, CreateMessageResult, ErrorData, Role, SamplingMessage,
+};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::config::SamplingPermission;
+use crate::conversation::message::Message;
+use crate::model::ModelConfig;
+use crate::providers::base::{Provider, ProviderUsage};
+use crate::webhooks::{self, WebhookEvent};
+
+/// The agent's provider, shared with extensions so they sample with whichever one is current
+pub type SharedProvider = Arc<Mutex<Option<Arc<dyn Provider>>>>;
+
+/// Answers the sampling requests of one extension with the agent's provider, as long as the
+/// extension stays within the token budget of its sampling permission
+pub struct ExtensionSampler {
+    extension_name: String,
+    provider: SharedProvider,
+    token_budget: u32,
+    tokens_used: Mutex<u32>,
+}
+
+impl ExtensionSampler {
+    pub fn new(
+        extension_name: String,
+        permission: &SamplingPermission,
+        provider: SharedProvider,
+    ) -> Self {
+        Self {
+            extension_name,
+            provider,
+            token_budget: permission.token_budget,
+            tokens_used: Mutex::new(0),
+        }
+    }
+}
+
+impl ExtensionSampler {
+    async fn sample(
+        &self,
+        params: &CreateMessageRequestParam,
+    ) -> Result<(Message, ProviderUsage), ErrorData> {
+        let provider = self.provider.lock().await.clone().ok_or_else(|| {
+            ErrorData::internal_error("No provider is configured for sampling".to_string(), None)
+        })?;
+        let model_config = sampling_model(&provider.get_model_config(), params);
+        let messages: Vec<Message> = params.messages.iter().map(to_message).collect();
+        let system = params.system_prompt.clone().unwrap_or_default();
+        provider
+            .complete_with_model(&model_config, &system, &messages, &[])
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("Sampling failed: {}", e), None))
+    }
+}
+
+fn to_message(message: &SamplingMessage) -> Message {
+    let base = match message.role {
+        Role::User => Message::user(),
+        Role::Assistant => Message::assistant(),
+    };
+    if let Some(text) = message.content.as_text() {
+        base.with_text(text.text.clone())
+    } else if let Some(image) = message.content.as_image() {
+        base.with_image(image.data.clone(), image.mime_type.clone())
+    } else {
+        base
+    }
+}
+
+/// The model a request samples with: the agent's model, or its fast model when the request's
+/// model hints name it or it asks for speed or cost over intelligence, limited to the request's
+/// max tokens and at its temperature
+fn sampling_model(base: &ModelConfig, params: &CreateMessageRequestParam) -> ModelConfig {
+    let mut config = base.clone();
+    if let (Some(preferences), Some(fast_model)) = (&params.model_preferences, &base.fast_model) {
+        let hints: Vec<String> = preferences
+            .hints
+            .iter()
+            .flatten()
+            .filter_map(|hint| hint.name.as_ref())
+            .map(|name| name.to_lowercase())
+            .collect();
+        let matches = |model: &str| {
+            let model = model.to_lowercase();
+            hints.iter().any(|hint| model.contains(hint.as_str()))
+        };
+        let use_fast = if matches(&base.model_name) || matches(fast_model) {
+            !matches(&base.model_name)
+        } else {
+            let priority = |priority: Option<f32>| priority.unwrap_or(0.0);
+            priority(preferences.speed_priority).max(priority(preferences.cost_priority))
+                > priority(preferences.intelligence_priority)
+        };
+        if use_fast {
+            config = base.use_fast_model();
+        }
+    }
+    config.max_tokens = Some(i32::try_from(params.max_tokens).unwrap_or(i32::MAX));
+    if params.temperature.is_some() {
+        config.temperature = params.temperature;
+    }
+    config
+}
+
+#[async_trait]
+impl SamplingHandler for ExtensionSampler {
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        // The most the request can use is reserved up front, so concurrent requests can't
+        // overrun the budget together, and settled to what it used once it is answered
+        {
+            let mut tokens_used = self.tokens_used.lock().await;
+            if tokens_used.saturating_add(params.max_tokens) > self.token_budget {
+                webhooks::notify(
+                    WebhookEvent::BudgetExceeded,
+                    json!({
+                        "budget": "sampling_tokens",
+                        "extension": self.extension_name,
+                        "used": *tokens_used,
+                        "limit": self.token_budget,
+                    }),
+                );
+                return Err(ErrorData::invalid_request(
+                    format!(
+                        "Extension '{}' has used {} of its {} sampling tokens, not enough for another {}",
+                        self.extension_name, *tokens_used, self.token_budget, params.max_tokens
+                    ),
+                    None,
+                ));
+            }
+            *tokens_used += params.max_tokens;
+        }
+
+        let result = self.sample(&params).await;
+        let used = match &result {
+            Ok((_, usage)) => usage
+                .usage
+                .total_tokens
+                .map(|total| total.max(0) as u32)
+                .unwrap_or(params.max_tokens),
+            Err(_) => 0,
+        };
+        {
+            let mut tokens_used = self.tokens_used.lock().await;
+            *tokens_used = tokens_used
+                .saturating_sub(params.max_tokens)
+                .saturating_add(used);
+        }
+        let (response, usage) = result?;
+
+        Ok(CreateMessageResult {
+            model: usage.model,
+            stop_reason: Some("endTurn".to_string()),
+            message: SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text(response.as_concat_text()),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{ProviderMetadata, Usage};
+    use crate::providers::errors::ProviderError;
+    use rmcp::model::Tool;
+    use serde_json::json;
+
+    struct EchoProvider {
+        model_config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            system: &str,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            let last = messages
+                .last()
+                .map(|m| m.as_concat_text())
+                .unwrap_or_default();
+            Ok((
+                Message::assistant().with_text(format!("{}: {}", system, last)),
+                ProviderUsage::new("mock".to_string(), Usage::new(Some(30), Some(10), Some(40))),
+            ))
+        }
+    }
+
+    fn request(max_tokens: u32) -> CreateMessageRequestParam {
+        serde_json::from_value(json!({
+            "messages": [{"role": "user", "content": {"type": "text", "text": "hello"}}],
+            "systemPrompt": "echo",
+            "maxTokens": max_tokens,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sampling_within_budget() {
+        let provider: Arc<dyn Provider> = Arc::new(EchoProvider {
+            model_config: ModelConfig::new("test-model").unwrap(),
+        });
+        let sampler = ExtensionSampler::new(
+            "memory".to_string(),
+            &SamplingPermission { token_budget: 100 },
+            Arc::new(Mutex::new(Some(provider))),
+        );
+
+        let result = sampler.create_message(request(50)).await.unwrap();
+        assert_eq!(result.message.role, Role::Assistant);
+        assert_eq!(
+            result.message.content.as_text().unwrap().text,
+            "echo: hello"
+        );
+        assert_eq!(*sampler.tokens_used.lock().await, 40);
+
+        // 40 tokens used leaves room for 50 more, after which 70 no longer fits
+        assert!(sampler.create_message(request(50)).await.is_ok());
+        assert!(sampler.create_message(request(70)).await.is_err());
+    }
+
+    #[test]
+    fn test_sampling_model() {
+        let base = ModelConfig::new("claude-sonnet-4")
+            .unwrap()
+            .with_fast("claude-haiku-3-5".to_string());
+        let params = |preferences: serde_json::Value| -> CreateMessageRequestParam {
+            serde_json::from_value(json!({
+                "messages": [],
+                "maxTokens": 64,
+                "temperature": 0.2,
+                "modelPreferences": preferences,
+            }))
+            .unwrap()
+        };
+
+        let config = sampling_model(&base, &params(json!({})));
+        assert_eq!(config.model_name, "claude-sonnet-4");
+        assert_eq!(config.max_tokens, Some(64));
+        assert_eq!(config.temperature, Some(0.2));
+
+        let hinted = sampling_model(&base, &params(json!({"hints": [{"name": "haiku"}]})));
+        assert_eq!(hinted.model_name, "claude-haiku-3-5");
+        let hinted = sampling_model(&base, &params(json!({"hints": [{"name": "sonnet"}]})));
+        assert_eq!(hinted.model_name, "claude-sonnet-4");
+
+        let fast = sampling_model(
+            &base,
+            &params(json!({"speedPriority": 0.9, "intelligencePriority": 0.2})),
+        );
+        assert_eq!(fast.model_name, "claude-haiku-3-5");
+        let smart = sampling_model(
+            &base,
+            &params(json!({"costPriority": 0.1, "intelligencePriority": 0.8})),
+        );
+        assert_eq!(smart.model_name, "claude-sonnet-4");
+
+        // Without a fast model there is nothing to choose from
+        let single = ModelConfig::new("gpt-4o").unwrap();
+        let config = sampling_model(&single, &params(json!({"speedPriority": 1.0})));
+        assert_eq!(config.model_name, "gpt-4o");
+    }
+
+    #[tokio::test]
+    async fn test_sampling_without_provider() {
+        let sampler = ExtensionSampler::new(
+            "memory".to_string(),
+            &SamplingPermission { token_budget: 100 },
+            Arc::new(Mutex::new(None)),
+        );
+        assert!(sampler.create_message(request(10)).await.is_err());
+        // The failed request doesn't count against the budget
+        assert_eq!(*sampler.tokens_used.lock().await, 0);
+    }
+}
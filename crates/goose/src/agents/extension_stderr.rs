@@ -0,0 +1,97 @@
+//! The stderr of stdio extensions: logged under the `goose::extension_stderr` target tagged with
+//! the extension name, and the most recent lines kept to explain failed tool calls
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tracing::debug;
+
+use crate::tracing::tool_calls;
+
+/// Log target of extension stderr lines
+pub const STDERR_LOG_TARGET: &str = "goose::extension_stderr";
+/// Lines of stderr kept for each extension
+const KEPT_LINES: usize = 200;
+/// Lines of stderr added to the result of a failed tool call
+pub const TOOL_ERROR_STDERR_LINES: usize = 20;
+
+#[derive(Default)]
+struct StderrTail {
+    lines: VecDeque<String>,
+    /// Lines written since the extension first started, including dropped ones
+    total: usize,
+}
+
+static TAILS: Lazy<Mutex<HashMap<String, StderrTail>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Log a line the extension wrote to stderr and keep it in its tail
+pub fn record(extension: &str, line: &str) {
+    debug!(
+        target: STDERR_LOG_TARGET,
+        extension = %extension,
+        tool_call_ids = %tool_calls::in_flight_ids(extension),
+        "{}",
+        line
+    );
+    let mut tails = TAILS.lock().unwrap_or_else(|e| e.into_inner());
+    let tail = tails.entry(extension.to_string()).or_default();
+    if tail.lines.len() == KEPT_LINES {
+        tail.lines.pop_front();
+    }
+    tail.lines.push_back(line.to_string());
+    tail.total += 1;
+}
+
+/// How many lines the extension has written to stderr, to later ask for the ones after it
+pub fn line_count(extension: &str) -> usize {
+    TAILS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(extension)
+        .map_or(0, |tail| tail.total)
+}
+
+/// The last `max` lines the extension wrote after it had written `count` lines
+pub fn lines_since(extension: &str, count: usize, max: usize) -> Vec<String> {
+    let tails = TAILS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(tail) = tails.get(extension) else {
+        return Vec::new();
+    };
+    let new_lines = tail.total.saturating_sub(count).min(tail.lines.len());
+    tail.lines
+        .iter()
+        .skip(tail.lines.len() - new_lines.min(max))
+        .cloned()
+        .collect()
+}
+
+/// The last `max` lines the extension wrote to stderr
+pub fn tail(extension: &str, max: usize) -> Vec<String> {
+    lines_since(extension, 0, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_since() {
+        record("stderr_test", "starting");
+        let count = line_count("stderr_test");
+        record("stderr_test", "Traceback (most recent call last):");
+        record("stderr_test", "ValueError: bad input");
+
+        assert_eq!(
+            lines_since("stderr_test", count, 10),
+            vec![
+                "Traceback (most recent call last):",
+                "ValueError: bad input"
+            ]
+        );
+        assert_eq!(
+            lines_since("stderr_test", count, 1),
+            vec!["ValueError: bad input"]
+        );
+        assert_eq!(tail("stderr_test", 10).len(), 3);
+        assert!(tail("unknown_extension", 10).is_empty());
+    }
+}
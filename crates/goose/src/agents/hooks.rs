@@ -0,0 +1,285 @@
+//! Lifecycle callbacks for code embedding the agent and for scripting layers built on it. Hooks
+//! observe the agent loop: the start of each turn, tool calls and their results, every message
+//! and the end of the session. They can also add context before a turn and rewrite or veto tool
+//! calls. A hook that panics is logged and skipped, the reply goes on.
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+use super::types::SessionConfig;
+use crate::conversation::message::Message;
+use mcp_core::{ToolCall, ToolResult};
+use rmcp::model::Content;
+
+/// Hooks taking longer than this are logged, they hold up the agent loop
+const SLOW_HOOK: Duration = Duration::from_millis(500);
+
+/// Whether a tool call goes ahead
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ToolCallDecision {
+    #[default]
+    Allow,
+    /// Refuse the call, the reason is the tool's error
+    Veto(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HookPoint {
+    TurnStart,
+    ToolCall,
+    ToolResult,
+    Message,
+    SessionEnd,
+}
+
+/// Callbacks run by the agent. Every method does nothing by default, so a hook only implements
+/// the points it cares about
+#[async_trait]
+pub trait Hook: Send + Sync {
+    /// Identifies the hook in logs and metrics, and for unregistering it
+    fn name(&self) -> &str;
+
+    /// A turn is about to ask the provider for a response. Turns count from 1 in each reply.
+    /// Returned text is given to the model as system context for this turn only, it isn't added to
+    /// the conversation
+    async fn on_turn_start(&self, _turn: u32, _session: Option<&SessionConfig>) -> Option<String> {
+        None
+    }
+
+    /// The model asked for a tool call, before its permission is checked. The hook may change its
+    /// arguments, so the changed call is the one approved and run, or veto it so later hooks, the
+    /// approval and the tool don't run
+    async fn on_tool_call(&self, _request_id: &str, _tool_call: &mut ToolCall) -> ToolCallDecision {
+        ToolCallDecision::Allow
+    }
+
+    async fn on_tool_result(&self, _request_id: &str, _result: &ToolResult<Vec<Content>>) {}
+
+    /// A message the agent produced, as the frontend receives it
+    async fn on_message(&self, _message: &Message) {}
+
+    async fn on_session_end(&self, _session: Option<&SessionConfig>) {}
+}
+
+/// How often a hook ran at a point and how long it took
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookMetrics {
+    pub calls: u64,
+    pub panics: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+#[derive(Default)]
+pub struct HookRegistry {
+    hooks: RwLock<Vec<Arc<dyn Hook>>>,
+    metrics: Mutex<HashMap<(String, HookPoint), HookMetrics>>,
+}
+
+impl HookRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a hook, replacing one registered with the same name
+    pub async fn register(&self, hook: Arc<dyn Hook>) {
+        let mut hooks = self.hooks.write().await;
+        hooks.retain(|existing| existing.name() != hook.name());
+        hooks.push(hook);
+    }
+
+    pub async fn unregister(&self, name: &str) -> bool {
+        let mut hooks = self.hooks.write().await;
+        let before = hooks.len();
+        hooks.retain(|hook| hook.name() != name);
+        hooks.len() != before
+    }
+
+    pub async fn names(&self) -> Vec<String> {
+        self.hooks
+            .read()
+            .await
+            .iter()
+            .map(|hook| hook.name().to_string())
+            .collect()
+    }
+
+    /// Metrics of every hook at every point it ran, sorted by hook and point
+    pub async fn metrics(&self) -> Vec<(String, HookPoint, HookMetrics)> {
+        let mut metrics: Vec<_> = self
+            .metrics
+            .lock()
+            .await
+            .iter()
+            .map(|((name, point), metrics)| (name.clone(), *point, metrics.clone()))
+            .collect();
+        metrics.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+        metrics
+    }
+
+    /// The hooks to run, copied so a hook can register or unregister others
+    async fn snapshot(&self) -> Vec<Arc<dyn Hook>> {
+        self.hooks.read().await.clone()
+    }
+
+    /// The context the hooks add before the turn
+    pub(crate) async fn turn_start(
+        &self,
+        turn: u32,
+        session: Option<&SessionConfig>,
+    ) -> Vec<String> {
+        let mut context = Vec::new();
+        for hook in self.snapshot().await {
+            let call = hook.on_turn_start(turn, session);
+            if let Some(Some(text)) = self.run(&hook, HookPoint::TurnStart, call).await {
+                context.push(text);
+            }
+        }
+        context
+    }
+
+    /// Run the hooks in order on the call until one vetoes it
+    pub(crate) async fn tool_call(
+        &self,
+        request_id: &str,
+        tool_call: &mut ToolCall,
+    ) -> ToolCallDecision {
+        for hook in self.snapshot().await {
+            let call = hook.on_tool_call(request_id, tool_call);
+            if let Some(ToolCallDecision::Veto(reason)) =
+                self.run(&hook, HookPoint::ToolCall, call).await
+            {
+                tracing::info!("Hook {} vetoed {}: {}", hook.name(), tool_call.name, reason);
+                return ToolCallDecision::Veto(reason);
+            }
+        }
+        ToolCallDecision::Allow
+    }
+
+    pub(crate) async fn tool_result(&self, request_id: &str, result: &ToolResult<Vec<Content>>) {
+        for hook in self.snapshot().await {
+            let call = hook.on_tool_result(request_id, result);
+            self.run(&hook, HookPoint::ToolResult, call).await;
+        }
+    }
+
+    pub(crate) async fn message(&self, message: &Message) {
+        for hook in self.snapshot().await {
+            let call = hook.on_message(message);
+            self.run(&hook, HookPoint::Message, call).await;
+        }
+    }
+
+    pub(crate) async fn session_end(&self, session: Option<&SessionConfig>) {
+        for hook in self.snapshot().await {
+            let call = hook.on_session_end(session);
+            self.run(&hook, HookPoint::SessionEnd, call).await;
+        }
+    }
+
+    /// Run one hook, catching a panic and recording how long it took. None if it panicked
+    async fn run<T>(
+        &self,
+        hook: &Arc<dyn Hook>,
+        point: HookPoint,
+        call: BoxFuture<'_, T>,
+    ) -> Option<T> {
+        let started = Instant::now();
+        let result = AssertUnwindSafe(call).catch_unwind().await.ok();
+        let elapsed = started.elapsed();
+        let panicked = result.is_none();
+
+        if panicked {
+            tracing::error!("Hook {} panicked in {:?}", hook.name(), point);
+        } else if elapsed > SLOW_HOOK {
+            tracing::warn!("Hook {} took {:?} in {:?}", hook.name(), elapsed, point);
+        }
+
+        let mut metrics = self.metrics.lock().await;
+        let entry = metrics.entry((hook.name().to_string(), point)).or_default();
+        entry.calls += 1;
+        entry.panics += panicked as u64;
+        entry.total += elapsed;
+        entry.max = entry.max.max(elapsed);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct CountingHook {
+        turns: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Hook for CountingHook {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn on_turn_start(
+            &self,
+            turn: u32,
+            _session: Option<&SessionConfig>,
+        ) -> Option<String> {
+            self.turns.fetch_add(1, Ordering::SeqCst);
+            Some(format!("turn {}", turn))
+        }
+    }
+
+    struct PanickingHook;
+
+    #[async_trait]
+    impl Hook for PanickingHook {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        async fn on_turn_start(
+            &self,
+            _turn: u32,
+            _session: Option<&SessionConfig>,
+        ) -> Option<String> {
+            panic!("hook failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_hook_is_isolated() {
+        let registry = HookRegistry::new();
+        let counting = Arc::new(CountingHook {
+            turns: AtomicU32::new(0),
+        });
+        registry.register(Arc::new(PanickingHook)).await;
+        registry.register(counting.clone()).await;
+
+        registry.turn_start(1, None).await;
+        assert_eq!(registry.turn_start(2, None).await, vec!["turn 2"]);
+
+        assert_eq!(counting.turns.load(Ordering::SeqCst), 2);
+        let metrics = registry.metrics().await;
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].0, "counting");
+        assert_eq!(metrics[0].2.calls, 2);
+        assert_eq!(metrics[1].0, "panicking");
+        assert_eq!(metrics[1].2.panics, 2);
+    }
+
+    #[tokio::test]
+    async fn test_register_replaces_by_name() {
+        let registry = HookRegistry::new();
+        registry.register(Arc::new(PanickingHook)).await;
+        registry.register(Arc::new(PanickingHook)).await;
+        assert_eq!(registry.names().await, vec!["panicking"]);
+        assert!(registry.unregister("panicking").await);
+        assert!(registry.names().await.is_empty());
+    }
+}
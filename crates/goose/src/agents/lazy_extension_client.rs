@@ -0,0 +1,435 @@
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use mcp_client::client::{Error, McpClientTrait};
+use rmcp::model::{
+    CallToolResult, ErrorData, GetPromptResult, InitializeResult, ListPromptsResult,
+    ListResourcesResult, ListToolsResult, ReadResourceResult, ServerNotification,
+};
+use rmcp::ServiceError;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use tokio::sync::{mpsc, OnceCell};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use super::extension::ExtensionResult;
+use super::extension_manifest::ExtensionManifest;
+use crate::shutdown::ProcessGroup;
+
+/// A started extension, with the temp dir it runs from if it needs one and the process group
+/// it leads if it runs as a process
+pub type ConnectedExtension = (
+    Box<dyn McpClientTrait>,
+    Option<TempDir>,
+    Option<ProcessGroup>,
+);
+
+pub type Connector =
+    Arc<dyn Fn() -> BoxFuture<'static, ExtensionResult<ConnectedExtension>> + Send + Sync>;
+
+type Subscribers = Arc<Mutex<Vec<mpsc::Sender<ServerNotification>>>>;
+
+/// Client for an extension that is only started the first time it is used. Until then
+/// its tools, prompts, resources and server info come from the manifest cached the last time
+/// it ran
+pub struct LazyExtensionClient {
+    name: String,
+    manifest: ExtensionManifest,
+    connector: Connector,
+    connected: Arc<OnceCell<ConnectedExtension>>,
+    /// Subscribed to before the extension was started, given its notifications once it is
+    waiting_subscribers: Subscribers,
+}
+
+impl LazyExtensionClient {
+    pub fn new(name: String, manifest: ExtensionManifest, connector: Connector) -> Self {
+        Self {
+            name,
+            manifest,
+            connector,
+            connected: Arc::new(OnceCell::new()),
+            waiting_subscribers: Arc::default(),
+        }
+    }
+
+    /// Start the extension in the background so it is ready by the time it is used
+    pub fn warm_up(&self) -> JoinHandle<()> {
+        let name = self.name.clone();
+        let connector = self.connector.clone();
+        let connected = self.connected.clone();
+        let waiting_subscribers = self.waiting_subscribers.clone();
+        tokio::spawn(async move {
+            match connected.get_or_try_init(|| connector()).await {
+                Ok((client, ..)) => {
+                    forward_notifications(client.as_ref(), &waiting_subscribers).await
+                }
+                Err(e) => warn!(extension = %name, error = %e, "Failed to warm up extension"),
+            }
+        })
+    }
+
+    async fn client(&self) -> Result<&dyn McpClientTrait, Error> {
+        let (client, ..) = self
+            .connected
+            .get_or_try_init(|| (self.connector)())
+            .await
+            .map_err(|e| {
+                ServiceError::McpError(ErrorData::internal_error(
+                    format!("Failed to start extension '{}': {}", self.name, e),
+                    None,
+                ))
+            })?;
+        forward_notifications(client.as_ref(), &self.waiting_subscribers).await;
+        Ok(client.as_ref())
+    }
+}
+
+/// Hand the notifications of the started extension to those that subscribed before it started
+async fn forward_notifications(client: &dyn McpClientTrait, subscribers: &Subscribers) {
+    let waiting = std::mem::take(&mut *subscribers.lock().unwrap());
+    for subscriber in waiting {
+        if subscriber.is_closed() {
+            continue;
+        }
+        let mut notifications = client.subscribe().await;
+        tokio::spawn(async move {
+            while let Some(notification) = notifications.recv().await {
+                if subscriber.send(notification).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl McpClientTrait for LazyExtensionClient {
+    async fn list_resources(
+        &self,
+        next_cursor: Option<String>,
+        cancel_token: CancellationToken,
+    ) -> Result<ListResourcesResult, Error> {
+        match self.connected.get() {
+            Some((client, ..)) => client.list_resources(next_cursor, cancel_token).await,
+            None => Ok(ListResourcesResult {
+                resources: self.manifest.resources.clone(),
+                next_cursor: None,
+            }),
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<ReadResourceResult, Error> {
+        self.client().await?.read_resource(uri, cancel_token).await
+    }
+
+    async fn list_tools(
+        &self,
+        next_cursor: Option<String>,
+        cancel_token: CancellationToken,
+    ) -> Result<ListToolsResult, Error> {
+        match self.connected.get() {
+            Some((client, ..)) => client.list_tools(next_cursor, cancel_token).await,
+            None => Ok(ListToolsResult {
+                tools: self.manifest.tools.clone(),
+                next_cursor: None,
+            }),
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        name: &str,
+        arguments: Value,
+        cancel_token: CancellationToken,
+    ) -> Result<CallToolResult, Error> {
+        self.client()
+            .await?
+            .call_tool(name, arguments, cancel_token)
+            .await
+    }
+
+    async fn list_prompts(
+        &self,
+        next_cursor: Option<String>,
+        cancel_token: CancellationToken,
+    ) -> Result<ListPromptsResult, Error> {
+        match self.connected.get() {
+            Some((client, ..)) => client.list_prompts(next_cursor, cancel_token).await,
+            None => Ok(ListPromptsResult {
+                prompts: self.manifest.prompts.clone(),
+                next_cursor: None,
+            }),
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: Value,
+        cancel_token: CancellationToken,
+    ) -> Result<GetPromptResult, Error> {
+        self.client()
+            .await?
+            .get_prompt(name, arguments, cancel_token)
+            .await
+    }
+
+    async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+        if let Some((client, ..)) = self.connected.get() {
+            return client.subscribe().await;
+        }
+        // Subscribing doesn't start the extension, the notifications follow once a call does
+        let (tx, rx) = mpsc::channel(16);
+        self.waiting_subscribers.lock().unwrap().push(tx);
+        // Started meanwhile, after the waiting subscribers were handed over
+        if let Some((client, ..)) = self.connected.get() {
+            forward_notifications(client.as_ref(), &self.waiting_subscribers).await;
+        }
+        rx
+    }
+
+    fn get_info(&self) -> Option<&InitializeResult> {
+        match self.connected.get() {
+            Some((client, ..)) => client.get_info(),
+            None => self.manifest.server_info.as_ref(),
+        }
+    }
+
+    async fn ping(&self, cancel_token: CancellationToken) -> Result<(), Error> {
+        match self.connected.get() {
+            Some((client, ..)) => client.ping(cancel_token).await,
+            // Not started yet, so there is nothing that could have stopped responding
+            None => Ok(()),
+        }
+    }
+
+    async fn notify_roots_list_changed(&self) -> Result<(), Error> {
+        match self.connected.get() {
+            Some((client, ..)) => client.notify_roots_list_changed().await,
+            // Gets the current roots when it is started
+            None => Ok(()),
+        }
+    }
+
+    async fn subscribe_resource(
+        &self,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        self.client()
+            .await?
+            .subscribe_resource(uri, cancel_token)
+            .await
+    }
+
+    async fn unsubscribe_resource(
+        &self,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        match self.connected.get() {
+            Some((client, ..)) => client.unsubscribe_resource(uri, cancel_token).await,
+            // Never started, so it has no subscriptions
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use rmcp::model::{
+        AnnotateAble, Content, Prompt, RawResource, ResourceUpdatedNotification,
+        ResourceUpdatedNotificationMethod, ResourceUpdatedNotificationParam, Tool,
+    };
+    use rmcp::object;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct EchoClient;
+
+    #[async_trait]
+    impl McpClientTrait for EchoClient {
+        async fn list_resources(
+            &self,
+            _next_cursor: Option<String>,
+            _cancel_token: CancellationToken,
+        ) -> Result<ListResourcesResult, Error> {
+            Err(ServiceError::UnexpectedResponse)
+        }
+
+        async fn read_resource(
+            &self,
+            _uri: &str,
+            _cancel_token: CancellationToken,
+        ) -> Result<ReadResourceResult, Error> {
+            Err(ServiceError::UnexpectedResponse)
+        }
+
+        async fn list_tools(
+            &self,
+            _next_cursor: Option<String>,
+            _cancel_token: CancellationToken,
+        ) -> Result<ListToolsResult, Error> {
+            Ok(ListToolsResult {
+                tools: vec![],
+                next_cursor: None,
+            })
+        }
+
+        async fn call_tool(
+            &self,
+            name: &str,
+            _arguments: Value,
+            _cancel_token: CancellationToken,
+        ) -> Result<CallToolResult, Error> {
+            Ok(CallToolResult {
+                content: vec![Content::text(name)],
+                structured_content: None,
+                is_error: None,
+            })
+        }
+
+        async fn list_prompts(
+            &self,
+            _next_cursor: Option<String>,
+            _cancel_token: CancellationToken,
+        ) -> Result<ListPromptsResult, Error> {
+            Err(ServiceError::UnexpectedResponse)
+        }
+
+        async fn get_prompt(
+            &self,
+            _name: &str,
+            _arguments: Value,
+            _cancel_token: CancellationToken,
+        ) -> Result<GetPromptResult, Error> {
+            Err(ServiceError::UnexpectedResponse)
+        }
+
+        async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+            let (tx, rx) = mpsc::channel(1);
+            tx.try_send(ServerNotification::ResourceUpdatedNotification(
+                ResourceUpdatedNotification {
+                    params: ResourceUpdatedNotificationParam {
+                        uri: "echo://log".to_string(),
+                    },
+                    method: ResourceUpdatedNotificationMethod,
+                    extensions: Default::default(),
+                },
+            ))
+            .unwrap();
+            rx
+        }
+
+        fn get_info(&self) -> Option<&InitializeResult> {
+            None
+        }
+    }
+
+    fn lazy_client(connects: Arc<AtomicUsize>) -> LazyExtensionClient {
+        let manifest = ExtensionManifest {
+            tools: vec![Tool::new(
+                "echo".to_string(),
+                "Echo the tool name".to_string(),
+                object!({"type": "object"}),
+            )],
+            server_info: None,
+            prompts: vec![Prompt::new("review", Some("Review the changes"), None)],
+            resources: vec![RawResource::new("echo://log", "log").no_annotation()],
+        };
+        let connector: Connector = Arc::new(move || {
+            let connects = connects.clone();
+            async move {
+                connects.fetch_add(1, Ordering::SeqCst);
+                Ok((Box::new(EchoClient) as Box<dyn McpClientTrait>, None, None))
+            }
+            .boxed()
+        });
+        LazyExtensionClient::new("echo".to_string(), manifest, connector)
+    }
+
+    #[tokio::test]
+    async fn test_tools_come_from_manifest_until_first_call() {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let client = lazy_client(connects.clone());
+
+        let tools = client
+            .list_tools(None, CancellationToken::default())
+            .await
+            .unwrap();
+        assert_eq!(tools.tools.len(), 1);
+        assert!(!client.connected.initialized());
+        assert_eq!(connects.load(Ordering::SeqCst), 0);
+
+        client
+            .call_tool("echo", Value::Null, CancellationToken::default())
+            .await
+            .unwrap();
+        client
+            .call_tool("echo", Value::Null, CancellationToken::default())
+            .await
+            .unwrap();
+        assert!(client.connected.initialized());
+        assert_eq!(connects.load(Ordering::SeqCst), 1);
+
+        // Once running the live tool list is used
+        let tools = client
+            .list_tools(None, CancellationToken::default())
+            .await
+            .unwrap();
+        assert!(tools.tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prompts_and_resources_come_from_manifest() {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let client = lazy_client(connects.clone());
+
+        let prompts = client
+            .list_prompts(None, CancellationToken::default())
+            .await
+            .unwrap();
+        assert_eq!(prompts.prompts[0].name, "review");
+        let resources = client
+            .list_resources(None, CancellationToken::default())
+            .await
+            .unwrap();
+        assert_eq!(resources.resources[0].uri, "echo://log");
+        assert_eq!(connects.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_waits_for_first_call() {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let client = lazy_client(connects.clone());
+
+        let mut notifications = client.subscribe().await;
+        assert!(!client.connected.initialized());
+        assert_eq!(connects.load(Ordering::SeqCst), 0);
+
+        client
+            .call_tool("echo", Value::Null, CancellationToken::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            notifications.recv().await,
+            Some(ServerNotification::ResourceUpdatedNotification(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_connects_in_background() {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let client = lazy_client(connects.clone());
+        client.warm_up().await.unwrap();
+        assert!(client.connected.initialized());
+        assert_eq!(connects.load(Ordering::SeqCst), 1);
+    }
+}
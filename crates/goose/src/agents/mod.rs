@@ -1,18 +1,29 @@
 mod agent;
+mod builder;
 mod context;
+pub mod coverage_tool;
 pub mod extension;
 pub mod extension_malware_check;
 pub mod extension_manager;
+mod extension_manifest;
+mod extension_sampling;
+pub mod extension_stderr;
 pub mod final_output_tool;
+pub mod hooks;
 mod large_response_handler;
+mod lazy_extension_client;
+pub mod plan_tools;
 pub mod platform_tools;
 pub mod prompt_manager;
 mod recipe_tools;
 mod reply_parts;
+mod resource_subscriptions;
 pub mod retry;
 mod router_tool_selector;
 mod router_tools;
+mod router_vector_index;
 mod schedule_tool;
+pub mod script_hooks;
 pub mod sub_recipe_manager;
 pub mod subagent;
 pub mod subagent_execution_tool;
@@ -22,12 +33,19 @@ pub mod todo_tools;
 mod tool_execution;
 mod tool_route_manager;
 mod tool_router_index_manager;
+pub mod tool_usage_stats;
 pub mod types;
+mod working_dir;
 
 pub use agent::{Agent, AgentEvent};
+pub use builder::{AgentBuilder, ConfirmationHandler, EmbeddedAgent, RunResult, SessionBackend};
 pub use extension::ExtensionConfig;
 pub use extension_manager::ExtensionManager;
+pub use hooks::{Hook, HookMetrics, HookPoint, HookRegistry, ToolCallDecision};
 pub use prompt_manager::PromptManager;
 pub use subagent::{SubAgent, SubAgentProgress, SubAgentStatus};
-pub use subagent_task_config::TaskConfig;
-pub use types::{FrontendTool, RetryConfig, SessionConfig, SuccessCheck};
+pub use subagent_task_config::{SubagentMode, TaskConfig};
+pub use types::{
+    ConfirmationTimeout, ConfirmationTimeoutAction, ExtensionReload, FrontendTool, ReplyOutcome,
+    RetryConfig, SessionConfig, SuccessCheck,
+};
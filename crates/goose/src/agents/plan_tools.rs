@@ -0,0 +1,329 @@
+use indoc::indoc;
+use regex::Regex;
+use rmcp::model::{Tool, ToolAnnotations};
+use rmcp::object;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Tool name constant for proposing a plan
+pub const PLAN_PROPOSE_TOOL_NAME: &str = "plan__propose";
+
+/// Tool name constant for updating the status of a plan step
+pub const PLAN_UPDATE_STEP_TOOL_NAME: &str = "plan__update_step";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+}
+
+impl StepStatus {
+    pub fn parse(status: &str) -> Option<Self> {
+        match status.trim().to_lowercase().as_str() {
+            "pending" => Some(Self::Pending),
+            "in_progress" => Some(Self::InProgress),
+            "completed" => Some(Self::Completed),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    /// Checkbox shown in front of a step when the plan is rendered
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[~]",
+            Self::Completed => "[x]",
+            Self::Failed => "[!]",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PlanStep {
+    pub description: String,
+    pub status: StepStatus,
+    /// Short note from the agent, e.g. why a step failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// A plan the agent is working through, persisted in the session metadata so progress
+/// survives restarts and can be shown by any client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Plan {
+    pub title: String,
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    pub fn new(title: impl Into<String>, steps: Vec<String>) -> Self {
+        Self {
+            title: title.into(),
+            steps: steps
+                .into_iter()
+                .map(|description| PlanStep {
+                    description,
+                    status: StepStatus::Pending,
+                    note: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Extract a plan from freeform text such as the output of the planner model, using
+    /// the top level numbered items (or bullets if there are none) as steps
+    pub fn from_text(text: &str) -> Option<Self> {
+        let title = text
+            .lines()
+            .find_map(|line| line.strip_prefix('#'))
+            .map(|heading| heading.trim_start_matches('#').trim().to_string())
+            .filter(|heading| !heading.is_empty())
+            .unwrap_or_else(|| "Plan".to_string());
+
+        let steps_matching = |pattern: &Regex| -> Vec<String> {
+            text.lines()
+                .filter_map(|line| pattern.captures(line.trim_end()))
+                .map(|captures| captures[1].trim().to_string())
+                .collect()
+        };
+        let mut steps = steps_matching(&Regex::new(r"^\d+[.)]\s+(.+)$").unwrap());
+        if steps.is_empty() {
+            steps = steps_matching(&Regex::new(r"^[-*]\s+(.+)$").unwrap());
+        }
+
+        if steps.is_empty() {
+            None
+        } else {
+            Some(Self::new(title, steps))
+        }
+    }
+
+    /// Index of the step being worked on
+    pub fn current_step(&self) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|step| step.status == StepStatus::InProgress)
+    }
+
+    /// Mark the first pending step as in progress when no step is, returns true if a step
+    /// was started
+    pub fn start_next_step(&mut self) -> bool {
+        if self.current_step().is_some() {
+            return false;
+        }
+        match self
+            .steps
+            .iter_mut()
+            .find(|step| step.status == StepStatus::Pending)
+        {
+            Some(step) => {
+                step.status = StepStatus::InProgress;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set the status of a step, `step` is 1-based as shown to the model
+    pub fn update_step(
+        &mut self,
+        step: usize,
+        status: StepStatus,
+        note: Option<String>,
+    ) -> Result<(), String> {
+        let total = self.steps.len();
+        let entry = step
+            .checked_sub(1)
+            .and_then(|index| self.steps.get_mut(index))
+            .ok_or_else(|| format!("Step {} does not exist, the plan has {} steps", step, total))?;
+        entry.status = status;
+        if note.is_some() {
+            entry.note = note;
+        }
+        Ok(())
+    }
+
+    /// Number of completed steps and the total number of steps
+    pub fn progress(&self) -> (usize, usize) {
+        let completed = self
+            .steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Completed)
+            .count();
+        (completed, self.steps.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| matches!(step.status, StepStatus::Completed | StepStatus::Failed))
+    }
+
+    /// Render the plan as a checklist, used as the result of the plan tools
+    pub fn render(&self) -> String {
+        let (completed, total) = self.progress();
+        let mut rendered = format!("{} ({}/{} completed)\n", self.title, completed, total);
+        for (index, step) in self.steps.iter().enumerate() {
+            rendered.push_str(&format!(
+                "{} {}. {}",
+                step.status.marker(),
+                index + 1,
+                step.description
+            ));
+            if let Some(note) = &step.note {
+                rendered.push_str(&format!(" ({})", note));
+            }
+            rendered.push('\n');
+        }
+        rendered
+    }
+}
+
+/// Creates a tool for proposing a plan.
+///
+/// The proposed plan replaces any existing plan for the session.
+///
+/// # Returns
+/// A configured `Tool` instance for proposing a plan
+pub fn plan_propose_tool() -> Tool {
+    Tool::new(
+        PLAN_PROPOSE_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Propose a plan for a task that needs several steps, before starting on it.
+
+            The plan replaces any previous plan and is shown to the user as a checklist.
+            Steps should be concrete actions, in the order you will take them.
+            The first pending step is marked as in progress automatically when you start
+            using tools, use plan__update_step to mark steps completed or failed as you go.
+        "#}
+        .to_string(),
+        object!({
+            "type": "object",
+            "required": ["steps"],
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "description": "Short title for the plan"
+                },
+                "steps": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Ordered descriptions of the steps"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Propose plan".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+/// Creates a tool for updating the status of a plan step.
+///
+/// # Returns
+/// A configured `Tool` instance for updating a plan step
+pub fn plan_update_step_tool() -> Tool {
+    Tool::new(
+        PLAN_UPDATE_STEP_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Update the status of a step in the current plan.
+
+            Mark a step completed as soon as it is done, or failed with a note explaining
+            why. Steps are numbered from 1 as shown in the plan.
+        "#}
+        .to_string(),
+        object!({
+            "type": "object",
+            "required": ["step", "status"],
+            "properties": {
+                "step": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Number of the step, starting at 1"
+                },
+                "status": {
+                    "type": "string",
+                    "enum": ["pending", "in_progress", "completed", "failed"],
+                    "description": "New status of the step"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Optional short note, e.g. the reason a step failed"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Update plan step".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_from_text() {
+        let plan = Plan::from_text(
+            "# Add caching\n\nSome context\n1. Read the handler\n   1. nested detail\n2) Add a cache\n3. Write tests\n",
+        )
+        .unwrap();
+        assert_eq!(plan.title, "Add caching");
+        let steps: Vec<&str> = plan.steps.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(
+            steps,
+            vec!["Read the handler", "Add a cache", "Write tests"]
+        );
+
+        let plan = Plan::from_text("- first\n- second").unwrap();
+        assert_eq!(plan.title, "Plan");
+        assert_eq!(plan.steps.len(), 2);
+
+        assert!(Plan::from_text("What should the cache key be?").is_none());
+    }
+
+    #[test]
+    fn test_plan_step_tracking() {
+        let mut plan = Plan::new("Task", vec!["one".to_string(), "two".to_string()]);
+        assert!(plan.start_next_step());
+        assert_eq!(plan.current_step(), Some(0));
+        assert!(!plan.start_next_step());
+
+        plan.update_step(1, StepStatus::Completed, None).unwrap();
+        assert!(plan.start_next_step());
+        assert_eq!(plan.current_step(), Some(1));
+        assert_eq!(plan.progress(), (1, 2));
+
+        plan.update_step(2, StepStatus::Failed, Some("no access".to_string()))
+            .unwrap();
+        assert!(plan.is_finished());
+        assert!(!plan.start_next_step());
+        assert!(plan.update_step(3, StepStatus::Completed, None).is_err());
+        assert!(plan.update_step(0, StepStatus::Completed, None).is_err());
+
+        let rendered = plan.render();
+        assert!(rendered.starts_with("Task (1/2 completed)"));
+        assert!(rendered.contains("[!] 2. two (no access)"));
+    }
+
+    #[test]
+    fn test_step_status_parse() {
+        assert_eq!(
+            StepStatus::parse("In_Progress"),
+            Some(StepStatus::InProgress)
+        );
+        assert_eq!(StepStatus::parse("done"), None);
+    }
+}
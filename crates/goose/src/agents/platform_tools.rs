@@ -4,6 +4,7 @@ use rmcp::object;
 
 pub const PLATFORM_READ_RESOURCE_TOOL_NAME: &str = "platform__read_resource";
 pub const PLATFORM_LIST_RESOURCES_TOOL_NAME: &str = "platform__list_resources";
+pub const PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME: &str = "platform__subscribe_resource";
 pub const PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME: &str =
     "platform__search_available_extensions";
 pub const PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME: &str = "platform__manage_extensions";
@@ -65,6 +66,36 @@ pub fn list_resources_tool() -> Tool {
     })
 }
 
+pub fn subscribe_resource_tool() -> Tool {
+    Tool::new(
+        PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Subscribe to, or unsubscribe from, changes to a resource of an extension.
+
+            While subscribed, the new content of the resource is added to the conversation
+            whenever the extension reports that it changed. Use this to follow resources that
+            change while you work, such as a log file or a document being edited.
+        "#}
+        .to_string(),
+        object!({
+            "type": "object",
+            "required": ["uri", "extension_name"],
+            "properties": {
+                "uri": {"type": "string", "description": "Resource URI"},
+                "extension_name": {"type": "string", "description": "Name of the extension that provides the resource"},
+                "action": {"type": "string", "description": "The action to perform, defaults to subscribe", "enum": ["subscribe", "unsubscribe"]}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Subscribe to a resource".to_string()),
+        read_only_hint: Some(true),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
 pub fn search_available_extensions_tool() -> Tool {
     Tool::new(
         PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME.to_string(),
@@ -1,6 +1,7 @@
 use chrono::Utc;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use crate::agents::extension::ExtensionInfo;
 use crate::agents::router_tools::llm_search_tool_prompt;
@@ -10,6 +11,10 @@ use crate::{config::Config, prompt_template, utils::sanitize_unicode_tags};
 pub struct PromptManager {
     system_prompt_override: Option<String>,
     system_prompt_extras: Vec<String>,
+    /// The directory the session was switched to after it started
+    working_dir: Option<PathBuf>,
+    /// Every root of the workspace, listed when there is more than one
+    workspace_roots: Vec<PathBuf>,
     current_date_timestamp: String,
 }
 
@@ -24,6 +29,8 @@ impl PromptManager {
         PromptManager {
             system_prompt_override: None,
             system_prompt_extras: Vec::new(),
+            working_dir: None,
+            workspace_roots: Vec::new(),
             // Use the fixed current date time so that prompt cache can be used.
             current_date_timestamp: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
         }
@@ -34,6 +41,17 @@ impl PromptManager {
         self.system_prompt_extras.push(instruction);
     }
 
+    /// Tell the model the session has moved to another working directory, in place of the one
+    /// the extensions described when they started
+    pub fn set_working_dir(&mut self, dir: PathBuf) {
+        self.working_dir = Some(dir);
+    }
+
+    /// Tell the model about the roots of the workspace, such as the folders of a monorepo
+    pub fn set_workspace_roots(&mut self, roots: Vec<PathBuf>) {
+        self.workspace_roots = roots;
+    }
+
     /// Override the system prompt with custom text
     pub fn set_system_prompt_override(&mut self, template: String) {
         self.system_prompt_override = Some(template);
@@ -151,6 +169,25 @@ impl PromptManager {
                 .push("Right now you are *NOT* in the chat only mode and have access to tool use and system.".to_string());
         }
 
+        if let Some(dir) = &self.working_dir {
+            system_prompt_extras.push(format!(
+                "The working directory of this session is now {}. Run commands and resolve relative paths there.",
+                dir.display()
+            ));
+        }
+
+        if self.workspace_roots.len() > 1 {
+            let roots: Vec<String> = self
+                .workspace_roots
+                .iter()
+                .map(|root| format!("- {}", root.display()))
+                .collect();
+            system_prompt_extras.push(format!(
+                "This workspace has several roots. Look for files and search in each of them, not only the working directory:\n{}",
+                roots.join("\n")
+            ));
+        }
+
         let sanitized_system_prompt_extras: Vec<String> = system_prompt_extras
             .into_iter()
             .map(|extra| sanitize_unicode_tags(&extra))
@@ -294,6 +331,36 @@ mod tests {
         assert!(result.contains("emojis"));
     }
 
+    #[test]
+    fn test_build_system_prompt_names_working_dir() {
+        let mut manager = PromptManager::new();
+        manager.set_working_dir(PathBuf::from("/work/service"));
+
+        let result =
+            manager.build_system_prompt(vec![], None, Value::String("".to_string()), None, false);
+
+        assert!(result.contains("The working directory of this session is now /work/service"));
+    }
+
+    #[test]
+    fn test_build_system_prompt_lists_workspace_roots() {
+        let mut manager = PromptManager::new();
+        manager.set_workspace_roots(vec![PathBuf::from("/work")]);
+        let result =
+            manager.build_system_prompt(vec![], None, Value::String("".to_string()), None, false);
+        assert!(!result.contains("several roots"));
+
+        manager.set_workspace_roots(vec![
+            PathBuf::from("/work"),
+            PathBuf::from("/work/frontend"),
+            PathBuf::from("/work/backend"),
+        ]);
+        let result =
+            manager.build_system_prompt(vec![], None, Value::String("".to_string()), None, false);
+        assert!(result.contains("several roots"));
+        assert!(result.contains("- /work/frontend\n- /work/backend"));
+    }
+
     #[test]
     fn test_build_system_prompt_sanitizes_extension_instructions() {
         let manager = PromptManager::new();
@@ -2,13 +2,16 @@
 // Module: Dynamic Task Tools
 // Handles creation of tasks dynamically without sub-recipes
 // =======================================
+use crate::agents::subagent_execution_tool::subagent_execute_task_tool::run_tasks;
 use crate::agents::subagent_execution_tool::tasks_manager::TasksManager;
 use crate::agents::subagent_execution_tool::{lib::ExecutionMode, task_types::Task};
+use crate::agents::subagent_task_config::TaskConfig;
 use crate::agents::tool_execution::ToolCallResult;
 use rmcp::model::{Content, ErrorCode, ErrorData, Tool, ToolAnnotations};
 use rmcp::object;
 use serde_json::{json, Value};
 use std::borrow::Cow;
+use tokio_util::sync::CancellationToken;
 
 pub const DYNAMIC_TASK_TOOL_NAME_PREFIX: &str = "dynamic_task__create_task";
 
@@ -22,6 +25,8 @@ pub fn create_dynamic_task_tool() -> Tool {
             - Each resulting task will use the same instruction with different parameter values
             This is useful when performing the same operation across many inputs (e.g., getting weather for multiple cities, searching multiple slack channels, iterating through various linear tickets, etc).
             Once created, these tasks should be passed to the 'subagent__execute_task' tool for execution. Tasks can run sequentially or in parallel.
+            To fan out in a single call instead, set 'execute' to true: the tasks run right away in parallel, at most
+            'max_concurrency' at a time, and the result holds every task's result and a summary table.
             ---
             What is a 'subagent'?
             A 'subagent' is a stateless sub-process that executes a single task independently. Use subagents when:
@@ -35,6 +40,9 @@ pub fn create_dynamic_task_tool() -> Tool {
                 text_instruction: Get weather for Melbourne.
                 text_instruction: Get weather for Los Angeles.
                 text_instruction: Get weather for San Francisco.
+            Each task can optionally be limited to some extensions ('extensions'), a number of turns ('max_turns'),
+            a token budget ('max_tokens') and a tool mode ('mode'): 'auto' for all tools, 'read_only' for tools that
+            do not change anything, 'chat' for no tools at all.
             ".to_string(),
         object!({
             "type": "object",
@@ -52,9 +60,39 @@ pub fn create_dynamic_task_tool() -> Tool {
                                 "type": "string",
                                 "description": "The text instruction to execute"
                             },
+                            "extensions": {
+                                "type": "array",
+                                "items": {"type": "string"},
+                                "description": "Names of the enabled extensions the subagent may use, all of them if omitted"
+                            },
+                            "max_turns": {
+                                "type": "integer",
+                                "minimum": 1,
+                                "description": "Maximum number of turns the subagent may take"
+                            },
+                            "max_tokens": {
+                                "type": "integer",
+                                "minimum": 1,
+                                "description": "Total tokens the subagent may use before it is stopped"
+                            },
+                            "mode": {
+                                "type": "string",
+                                "enum": ["auto", "read_only", "chat"],
+                                "description": "Which tools the subagent may call"
+                            },
                         },
                         "required": ["text_instruction"]
                     }
+                },
+                "execute": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Run the tasks immediately and return their results instead of the task ids"
+                },
+                "max_concurrency": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Maximum number of tasks to run at the same time"
                 }
             }
         })
@@ -85,9 +123,14 @@ fn create_text_instruction_tasks_from_params(task_params: &[Value]) -> Vec<Task>
                 .unwrap_or("")
                 .to_string();
 
-            let payload = json!({
+            let mut payload = json!({
                 "text_instruction": text_instruction
             });
+            for key in ["extensions", "max_turns", "max_tokens", "mode"] {
+                if let Some(value) = task_param.get(key) {
+                    payload[key] = value.clone();
+                }
+            }
 
             Task {
                 id: uuid::Uuid::new_v4().to_string(),
@@ -98,15 +141,28 @@ fn create_text_instruction_tasks_from_params(task_params: &[Value]) -> Vec<Task>
         .collect()
 }
 
-fn create_task_execution_payload(tasks: Vec<Task>, execution_mode: ExecutionMode) -> Value {
+fn create_task_execution_payload(
+    tasks: Vec<Task>,
+    execution_mode: ExecutionMode,
+    max_concurrency: Option<&Value>,
+) -> Value {
     let task_ids: Vec<String> = tasks.iter().map(|task| task.id.clone()).collect();
-    json!({
+    let mut payload = json!({
         "task_ids": task_ids,
         "execution_mode": execution_mode
-    })
+    });
+    if let Some(max_concurrency) = max_concurrency {
+        payload["max_concurrency"] = max_concurrency.clone();
+    }
+    payload
 }
 
-pub async fn create_dynamic_task(params: Value, tasks_manager: &TasksManager) -> ToolCallResult {
+pub async fn create_dynamic_task(
+    params: Value,
+    tasks_manager: &TasksManager,
+    task_config: TaskConfig,
+    cancellation_token: Option<CancellationToken>,
+) -> ToolCallResult {
     let task_params_array = extract_task_parameters(&params);
 
     if task_params_array.is_empty() {
@@ -126,7 +182,23 @@ pub async fn create_dynamic_task(params: Value, tasks_manager: &TasksManager) ->
         ExecutionMode::Sequential
     };
 
-    let task_execution_payload = create_task_execution_payload(tasks.clone(), execution_mode);
+    let task_execution_payload =
+        create_task_execution_payload(tasks.clone(), execution_mode, params.get("max_concurrency"));
+
+    let execute = params
+        .get("execute")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if execute {
+        tasks_manager.save_tasks(tasks).await;
+        return run_tasks(
+            task_execution_payload,
+            task_config,
+            tasks_manager,
+            cancellation_token,
+        )
+        .await;
+    }
 
     let tasks_json = match serde_json::to_string(&task_execution_payload) {
         Ok(json) => json,
@@ -1,12 +1,14 @@
 use anyhow::Result;
 use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_stream::try_stream;
 use futures::stream::StreamExt;
 
 use super::super::agents::Agent;
-use crate::conversation::message::{Message, MessageContent, ToolRequest};
+use crate::config::Config;
+use crate::conversation::message::{GenerationMetadata, Message, MessageContent, ToolRequest};
 use crate::conversation::Conversation;
 use crate::providers::base::{stream_from_single_message, MessageStream, Provider, ProviderUsage};
 use crate::providers::errors::ProviderError;
@@ -14,6 +16,7 @@ use crate::providers::toolshim::{
     augment_message_with_tool_calls, convert_tool_messages_to_text,
     modify_system_prompt_for_tool_json, OllamaInterpreter,
 };
+use crate::redaction::Redactor;
 
 use crate::session;
 use rmcp::model::Tool;
@@ -31,6 +34,33 @@ async fn toolshim_postprocess(
         .map_err(|e| ProviderError::ExecutionError(format!("Failed to augment message: {}", e)))
 }
 
+/// What to record about how a response was generated. The finish reason is derived from the
+/// response, since providers don't report one in their usage
+pub(crate) fn generation_metadata(
+    provider: Option<String>,
+    usage: &ProviderUsage,
+    latency: Duration,
+    response: Option<&Message>,
+) -> GenerationMetadata {
+    let finish_reason = response.map(|message| {
+        let requests_tools = message.content.iter().any(|content| {
+            matches!(
+                content,
+                MessageContent::ToolRequest(_) | MessageContent::FrontendToolRequest(_)
+            )
+        });
+        if requests_tools { "tool_calls" } else { "stop" }.to_string()
+    });
+    GenerationMetadata {
+        provider,
+        model: Some(usage.model.clone()),
+        latency_ms: Some(latency.as_millis() as u64),
+        input_tokens: usage.usage.input_tokens,
+        output_tokens: usage.usage.output_tokens,
+        finish_reason,
+    }
+}
+
 impl Agent {
     /// Prepares tools and system prompt for a provider request
     pub async fn prepare_tools_and_prompt(&self) -> anyhow::Result<(Vec<Tool>, Vec<Tool>, String)> {
@@ -159,15 +189,22 @@ impl Agent {
     ) -> Result<MessageStream, ProviderError> {
         let config = provider.get_model_config();
 
+        // Mask configured secrets in everything the model sees
+        let redactor = Redactor::model_bound();
+        let messages: Vec<Message> = messages
+            .iter()
+            .map(|message| redactor.redact_message(message))
+            .collect();
+
         // Convert tool messages to text if toolshim is enabled
         let messages_for_provider = if config.toolshim {
-            convert_tool_messages_to_text(messages)
+            convert_tool_messages_to_text(&messages)
         } else {
-            Conversation::new_unvalidated(messages.to_vec())
+            Conversation::new_unvalidated(messages)
         };
 
         // Clone owned data to move into the async stream
-        let system_prompt = system_prompt.to_owned();
+        let system_prompt = redactor.redact(system_prompt);
         let tools = tools.to_owned();
         let toolshim_tools = toolshim_tools.to_owned();
         let provider = provider.clone();
@@ -203,7 +240,8 @@ impl Agent {
         };
 
         Ok(Box::pin(try_stream! {
-            while let Some(Ok((mut message, usage))) = stream.next().await {
+            while let Some(next) = stream.next().await {
+                let (mut message, usage) = next?;
                 // Store the model information in the global store
                 if let Some(usage) = usage.as_ref() {
                     crate::providers::base::set_current_model(&usage.model);
@@ -267,6 +305,7 @@ impl Agent {
             role: response.role.clone(),
             created: response.created,
             content: filtered_content,
+            metadata: response.metadata.clone(),
         };
 
         // Categorize tool requests
@@ -289,9 +328,11 @@ impl Agent {
         (frontend_requests, other_requests, filtered_message)
     }
 
+    /// Update the session's token totals and add the call to its usage ledger
     pub(crate) async fn update_session_metrics(
         session_config: &crate::agents::types::SessionConfig,
         usage: &ProviderUsage,
+        latency: Duration,
         messages_length: usize,
     ) -> Result<()> {
         let session_file_path = match session::storage::get_path(session_config.id.clone()) {
@@ -309,6 +350,10 @@ impl Agent {
         metadata.output_tokens = usage.usage.output_tokens;
 
         metadata.message_count = messages_length + 1;
+        metadata.model = Some(usage.model.clone());
+        if let Ok(provider) = Config::global().get_param::<String>("GOOSE_PROVIDER") {
+            metadata.provider = Some(provider);
+        }
 
         let accumulate = |a: Option<i32>, b: Option<i32>| -> Option<i32> {
             match (a, b) {
@@ -327,6 +372,11 @@ impl Agent {
 
         session::storage::update_metadata(&session_file_path, &metadata).await?;
 
+        let record = session::usage::UsageRecord::new(metadata.provider.clone(), usage, latency);
+        if let Err(e) = session::usage::append_usage(&session_file_path, &record) {
+            tracing::warn!("Failed to record usage in the session ledger: {}", e);
+        }
+
         Ok(())
     }
 }
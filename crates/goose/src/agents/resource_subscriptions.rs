@@ -0,0 +1,263 @@
+use rmcp::model::{
+    LoggingLevel, LoggingMessageNotification, LoggingMessageNotificationMethod,
+    LoggingMessageNotificationParam, ServerNotification,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// Most characters of a changed resource added to the conversation, the rest is left to read
+const MAX_UPDATE_CHARS: usize = 4000;
+
+/// A subscribed resource that changed, with its content as of the change
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceUpdate {
+    pub extension: String,
+    pub uri: String,
+    pub content: String,
+}
+
+impl ResourceUpdate {
+    /// Progress notification telling the user the resource changed
+    pub fn notification(&self) -> ServerNotification {
+        ServerNotification::LoggingMessageNotification(LoggingMessageNotification {
+            method: LoggingMessageNotificationMethod,
+            params: LoggingMessageNotificationParam {
+                data: serde_json::json!({
+                    "type": "resource_updated",
+                    "extension": self.extension,
+                    "uri": self.uri,
+                    "message": format!("Resource {} changed", self.uri),
+                }),
+                level: LoggingLevel::Info,
+                logger: None,
+            },
+            extensions: Default::default(),
+        })
+    }
+
+    /// Text added to the conversation so the model sees the new content. Long content is cut
+    /// short, and content that isn't text is left for the model to read
+    pub fn message_text(&self) -> String {
+        if self.content.is_empty() {
+            return format!(
+                "The resource {} from the {} extension has changed. Read it to see its new content.",
+                self.uri, self.extension
+            );
+        }
+        match self.content.char_indices().nth(MAX_UPDATE_CHARS) {
+            None => format!(
+                "The resource {} from the {} extension has changed. Its content is now:\n\n{}",
+                self.uri, self.extension, self.content
+            ),
+            Some((end, _)) => format!(
+                "The resource {} from the {} extension has changed. Its content now starts with:\n\n{}\n\n[{} more characters, read the resource for the rest]",
+                self.uri,
+                self.extension,
+                &self.content[..end],
+                self.content[end..].chars().count()
+            ),
+        }
+    }
+}
+
+/// The resources subscribed to on each extension, and which of them changed since the
+/// changes were last taken
+#[derive(Debug, Default)]
+pub struct ResourceSubscriptions {
+    subscribed: HashMap<String, HashSet<String>>,
+    changed: Vec<(String, String)>,
+    /// Hash of the content last added to the conversation, by extension and uri
+    delivered: HashMap<(String, String), u64>,
+}
+
+pub type SharedSubscriptions = Arc<Mutex<ResourceSubscriptions>>;
+
+impl ResourceSubscriptions {
+    /// Returns false if the resource was already subscribed to
+    pub fn add(&mut self, extension: &str, uri: &str) -> bool {
+        self.subscribed
+            .entry(extension.to_string())
+            .or_default()
+            .insert(uri.to_string())
+    }
+
+    /// Returns false if the resource wasn't subscribed to
+    pub fn remove(&mut self, extension: &str, uri: &str) -> bool {
+        self.changed.retain(|(e, u)| e != extension || u != uri);
+        self.delivered
+            .remove(&(extension.to_string(), uri.to_string()));
+        let removed = self
+            .subscribed
+            .get_mut(extension)
+            .is_some_and(|uris| uris.remove(uri));
+        if self.uris(extension).is_empty() {
+            self.subscribed.remove(extension);
+        }
+        removed
+    }
+
+    /// Drop all subscriptions of an extension that is no longer running
+    pub fn remove_extension(&mut self, extension: &str) {
+        self.subscribed.remove(extension);
+        self.changed.retain(|(e, _)| e != extension);
+        self.delivered.retain(|(e, _), _| e != extension);
+    }
+
+    pub fn uris(&self, extension: &str) -> Vec<String> {
+        let mut uris: Vec<String> = self
+            .subscribed
+            .get(extension)
+            .map(|uris| uris.iter().cloned().collect())
+            .unwrap_or_default();
+        uris.sort();
+        uris
+    }
+
+    /// Note a change reported by an extension, ignoring resources that aren't subscribed
+    /// to and collapsing repeated changes into one
+    pub fn record_change(&mut self, extension: &str, uri: &str) {
+        let subscribed = self
+            .subscribed
+            .get(extension)
+            .is_some_and(|uris| uris.contains(uri));
+        let pending = self.changed.iter().any(|(e, u)| e == extension && u == uri);
+        if subscribed && !pending {
+            self.changed.push((extension.to_string(), uri.to_string()));
+        }
+    }
+
+    /// The `(extension, uri)` pairs that changed since the last call
+    pub fn take_changed(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Whether the content differs from what was last added to the conversation for the
+    /// resource, remembering it if so. Changes that leave the content as it was are dropped
+    pub fn is_new_content(&mut self, extension: &str, uri: &str, content: &str) -> bool {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        let hash = hasher.finish();
+        self.delivered
+            .insert((extension.to_string(), uri.to_string()), hash)
+            != Some(hash)
+    }
+}
+
+/// Record the resource update notifications an extension sends until its client goes away
+pub fn listen_for_updates(
+    subscriptions: SharedSubscriptions,
+    extension: String,
+    mut notifications: mpsc::Receiver<ServerNotification>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(notification) = notifications.recv().await {
+            if let ServerNotification::ResourceUpdatedNotification(update) = notification {
+                subscriptions
+                    .lock()
+                    .await
+                    .record_change(&extension, &update.params.uri);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{
+        ResourceUpdatedNotification, ResourceUpdatedNotificationMethod,
+        ResourceUpdatedNotificationParam,
+    };
+
+    #[test]
+    fn test_changes_only_for_subscribed_resources() {
+        let mut subscriptions = ResourceSubscriptions::default();
+        assert!(subscriptions.add("files", "file:///tmp/log.txt"));
+        assert!(!subscriptions.add("files", "file:///tmp/log.txt"));
+
+        subscriptions.record_change("files", "file:///tmp/log.txt");
+        subscriptions.record_change("files", "file:///tmp/log.txt");
+        subscriptions.record_change("files", "file:///tmp/other.txt");
+        subscriptions.record_change("docs", "file:///tmp/log.txt");
+
+        assert_eq!(
+            subscriptions.take_changed(),
+            vec![("files".to_string(), "file:///tmp/log.txt".to_string())]
+        );
+        assert!(subscriptions.take_changed().is_empty());
+
+        subscriptions.record_change("files", "file:///tmp/log.txt");
+        assert!(subscriptions.remove("files", "file:///tmp/log.txt"));
+        assert!(!subscriptions.remove("files", "file:///tmp/log.txt"));
+        assert!(subscriptions.take_changed().is_empty());
+        assert!(subscriptions.uris("files").is_empty());
+    }
+
+    #[test]
+    fn test_unchanged_content_is_not_repeated() {
+        let mut subscriptions = ResourceSubscriptions::default();
+        subscriptions.add("files", "file:///tmp/log.txt");
+        assert!(subscriptions.is_new_content("files", "file:///tmp/log.txt", "one"));
+        assert!(!subscriptions.is_new_content("files", "file:///tmp/log.txt", "one"));
+        assert!(subscriptions.is_new_content("files", "file:///tmp/log.txt", "two"));
+        assert!(subscriptions.is_new_content("docs", "file:///tmp/log.txt", "two"));
+
+        // Subscribing again starts over
+        subscriptions.remove("files", "file:///tmp/log.txt");
+        assert!(subscriptions.is_new_content("files", "file:///tmp/log.txt", "two"));
+    }
+
+    #[test]
+    fn test_message_text_is_bounded() {
+        let update = |content: String| ResourceUpdate {
+            extension: "files".to_string(),
+            uri: "file:///tmp/log.txt".to_string(),
+            content,
+        };
+        let short = update("hello".to_string()).message_text();
+        assert!(short.ends_with("hello"));
+
+        let long = update("é".repeat(MAX_UPDATE_CHARS + 10)).message_text();
+        assert!(long.contains(&"é".repeat(MAX_UPDATE_CHARS)));
+        assert!(!long.contains(&"é".repeat(MAX_UPDATE_CHARS + 1)));
+        assert!(long.contains("[10 more characters, read the resource for the rest]"));
+
+        let binary = update(String::new()).message_text();
+        assert!(binary.contains("file:///tmp/log.txt"));
+        assert!(binary.contains("Read it to see its new content"));
+    }
+
+    #[tokio::test]
+    async fn test_listen_for_updates() {
+        let subscriptions: SharedSubscriptions = Arc::default();
+        subscriptions
+            .lock()
+            .await
+            .add("files", "file:///tmp/log.txt");
+
+        let (tx, rx) = mpsc::channel(4);
+        let listener = listen_for_updates(subscriptions.clone(), "files".to_string(), rx);
+        tx.send(ServerNotification::ResourceUpdatedNotification(
+            ResourceUpdatedNotification {
+                params: ResourceUpdatedNotificationParam {
+                    uri: "file:///tmp/log.txt".to_string(),
+                },
+                method: ResourceUpdatedNotificationMethod,
+                extensions: Default::default(),
+            },
+        ))
+        .await
+        .unwrap();
+        drop(tx);
+        listener.await.unwrap();
+
+        assert_eq!(
+            subscriptions.lock().await.take_changed(),
+            vec![("files".to_string(), "file:///tmp/log.txt".to_string())]
+        );
+    }
+}
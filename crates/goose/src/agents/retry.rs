@@ -1,4 +1,5 @@
 use anyhow::Result;
+use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::Duration;
@@ -13,7 +14,9 @@ use crate::agents::types::{
 use crate::config::Config;
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
+use crate::providers::base::Provider;
 use crate::tool_monitor::ToolMonitor;
+use rmcp::model::Role;
 
 /// Result of a retry logic evaluation
 #[derive(Debug, Clone, PartialEq)]
@@ -34,11 +37,25 @@ const GOOSE_RECIPE_RETRY_TIMEOUT_SECONDS: &str = "GOOSE_RECIPE_RETRY_TIMEOUT_SEC
 /// Environment variable for configuring on_failure timeout globally
 const GOOSE_RECIPE_ON_FAILURE_TIMEOUT_SECONDS: &str = "GOOSE_RECIPE_ON_FAILURE_TIMEOUT_SECONDS";
 
+const LLM_JUDGE_SYSTEM_PROMPT: &str = "You decide whether the output of a task meets the given \
+criteria. Answer with PASS or FAIL on the first line, followed by a short reason.";
+
+/// What the success checks look at besides the environment
+#[derive(Default)]
+pub struct SuccessCheckContext {
+    /// The final output of the run, or the text of the last assistant message
+    pub output: Option<String>,
+    /// Provider used by LLM judge checks
+    pub provider: Option<Arc<dyn Provider>>,
+}
+
 /// Manages retry state and operations for agent execution
 #[derive(Debug)]
 pub struct RetryManager {
     /// Current number of retry attempts
     attempts: Arc<Mutex<u32>>,
+    /// Number of failures of each success check, by index in the retry config
+    check_failures: Arc<Mutex<HashMap<usize, u32>>>,
     /// Optional tool monitor for reset operations
     tool_monitor: Option<Arc<Mutex<Option<ToolMonitor>>>>,
 }
@@ -54,6 +71,7 @@ impl RetryManager {
     pub fn new() -> Self {
         Self {
             attempts: Arc::new(Mutex::new(0)),
+            check_failures: Arc::new(Mutex::new(HashMap::new())),
             tool_monitor: None,
         }
     }
@@ -62,6 +80,7 @@ impl RetryManager {
     pub fn with_tool_monitor(tool_monitor: Arc<Mutex<Option<ToolMonitor>>>) -> Self {
         Self {
             attempts: Arc::new(Mutex::new(0)),
+            check_failures: Arc::new(Mutex::new(HashMap::new())),
             tool_monitor: Some(tool_monitor),
         }
     }
@@ -70,6 +89,7 @@ impl RetryManager {
     pub async fn reset_attempts(&self) {
         let mut attempts = self.attempts.lock().await;
         *attempts = 0;
+        self.check_failures.lock().await.clear();
 
         // Reset tool monitor if available
         if let Some(monitor) = &self.tool_monitor {
@@ -106,6 +126,26 @@ impl RetryManager {
         }
     }
 
+    /// Count a failure of each failed check, returning the first check that has now used
+    /// up its own max_attempts
+    async fn record_check_failures(
+        &self,
+        failed_checks: &[usize],
+        retry_config: &RetryConfig,
+    ) -> Option<usize> {
+        let mut check_failures = self.check_failures.lock().await;
+        let mut exhausted = None;
+        for &index in failed_checks {
+            let failures = check_failures.entry(index).or_insert(0);
+            *failures += 1;
+            let max_attempts = retry_config.checks[index].max_attempts();
+            if exhausted.is_none() && max_attempts.is_some_and(|max| *failures >= max) {
+                exhausted = Some(index);
+            }
+        }
+        exhausted
+    }
+
     /// Handle retry logic for the agent reply loop
     pub async fn handle_retry_logic(
         &self,
@@ -113,6 +153,7 @@ impl RetryManager {
         session: &Option<SessionConfig>,
         initial_messages: &[Message],
         final_output_tool: &Arc<Mutex<Option<crate::agents::final_output_tool::FinalOutputTool>>>,
+        provider: Option<Arc<dyn Provider>>,
     ) -> Result<RetryResult> {
         let Some(session_config) = session else {
             return Ok(RetryResult::Skipped);
@@ -122,13 +163,43 @@ impl RetryManager {
             return Ok(RetryResult::Skipped);
         };
 
-        let success = execute_success_checks(&retry_config.checks, retry_config).await?;
+        let final_output = final_output_tool
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|tool| tool.final_output.clone());
+        let context = SuccessCheckContext {
+            output: final_output.or_else(|| last_assistant_text(messages)),
+            provider,
+        };
+        let failed_checks =
+            failed_success_checks(&retry_config.checks, retry_config, &context).await?;
 
-        if success {
+        if failed_checks.is_empty() {
             info!("All success checks passed, no retry needed");
             return Ok(RetryResult::SuccessChecksPassed);
         }
 
+        if let Some(index) = self
+            .record_check_failures(&failed_checks, retry_config)
+            .await
+        {
+            let max_attempts = retry_config.checks[index]
+                .max_attempts()
+                .unwrap_or_default();
+            messages.push(Message::assistant().with_text(format!(
+                "Success check {} failed {} times, the maximum for that check. Unable to complete the task successfully.",
+                index + 1,
+                max_attempts
+            )));
+            warn!(
+                "Success check {} reached its {} attempts",
+                index + 1,
+                max_attempts
+            );
+            return Ok(RetryResult::MaxAttemptsReached);
+        }
+
         let current_attempts = self.get_attempts().await;
         if current_attempts >= retry_config.max_retries {
             let error_msg = Message::assistant().with_text(format!(
@@ -187,34 +258,123 @@ fn get_on_failure_timeout(retry_config: &RetryConfig) -> Duration {
     Duration::from_secs(timeout_seconds)
 }
 
+fn last_assistant_text(messages: &Conversation) -> Option<String> {
+    messages
+        .messages()
+        .iter()
+        .rev()
+        .find(|message| message.role == Role::Assistant)
+        .map(|message| message.as_concat_text())
+}
+
 /// Execute all success checks and return true if all pass
 pub async fn execute_success_checks(
     checks: &[SuccessCheck],
     retry_config: &RetryConfig,
 ) -> Result<bool> {
-    let timeout = get_retry_timeout(retry_config);
-
-    for check in checks {
-        match check {
-            SuccessCheck::Shell { command } => {
-                let result = execute_shell_command(command, timeout).await?;
-                if !result.status.success() {
-                    warn!(
-                        "Success check failed: command '{}' exited with status {}, stderr: {}",
-                        command,
-                        result.status,
-                        String::from_utf8_lossy(&result.stderr)
-                    );
+    let failed_checks =
+        failed_success_checks(checks, retry_config, &SuccessCheckContext::default()).await?;
+    Ok(failed_checks.is_empty())
+}
+
+/// Execute every success check and return the indices of the ones that failed
+pub async fn failed_success_checks(
+    checks: &[SuccessCheck],
+    retry_config: &RetryConfig,
+    context: &SuccessCheckContext,
+) -> Result<Vec<usize>> {
+    let mut failed_checks = Vec::new();
+    for (index, check) in checks.iter().enumerate() {
+        if !execute_success_check(check, retry_config, context).await? {
+            failed_checks.push(index);
+        }
+    }
+    Ok(failed_checks)
+}
+
+async fn execute_success_check(
+    check: &SuccessCheck,
+    retry_config: &RetryConfig,
+    context: &SuccessCheckContext,
+) -> Result<bool> {
+    match check {
+        SuccessCheck::Shell { command, .. } => {
+            let timeout = get_retry_timeout(retry_config);
+            let result = execute_shell_command(command, timeout).await?;
+            if !result.status.success() {
+                warn!(
+                    "Success check failed: command '{}' exited with status {}, stderr: {}",
+                    command,
+                    result.status,
+                    String::from_utf8_lossy(&result.stderr)
+                );
+                return Ok(false);
+            }
+            info!(
+                "Success check passed: command '{}' completed successfully",
+                command
+            );
+            Ok(true)
+        }
+        SuccessCheck::JsonSchema { schema, .. } => {
+            let Some(output) = &context.output else {
+                warn!("Success check failed: there is no output to validate");
+                return Ok(false);
+            };
+            let validator = jsonschema::validator_for(schema)
+                .map_err(|e| anyhow::anyhow!("Invalid JSON schema in success check: {}", e))?;
+            let value = match serde_json::from_str::<serde_json::Value>(output) {
+                Ok(value) => value,
+                Err(e) => {
+                    warn!("Success check failed: output is not valid JSON: {}", e);
                     return Ok(false);
                 }
-                info!(
-                    "Success check passed: command '{}' completed successfully",
-                    command
+            };
+            let errors: Vec<String> = validator
+                .iter_errors(&value)
+                .map(|error| error.to_string())
+                .collect();
+            if !errors.is_empty() {
+                warn!(
+                    "Success check failed: output does not match the schema: {}",
+                    errors.join(", ")
                 );
+                return Ok(false);
             }
+            info!("Success check passed: output matches the schema");
+            Ok(true)
+        }
+        SuccessCheck::LlmJudge { prompt, .. } => {
+            let provider = context
+                .provider
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("LLM judge success check needs a provider"))?;
+            let request = Message::user().with_text(format!(
+                "Criteria:\n{}\n\nOutput:\n{}",
+                prompt,
+                context.output.as_deref().unwrap_or("(no output)")
+            ));
+            let (response, _usage) = provider
+                .complete(LLM_JUDGE_SYSTEM_PROMPT, &[request], &[])
+                .await?;
+            let verdict = response.as_concat_text();
+            if !judge_passed(&verdict) {
+                warn!("Success check failed: judge answered {}", verdict.trim());
+                return Ok(false);
+            }
+            info!("Success check passed: judge accepted the output");
+            Ok(true)
         }
     }
-    Ok(true)
+}
+
+/// Whether the first non-empty line of the judge's answer is a PASS
+fn judge_passed(verdict: &str) -> bool {
+    verdict
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| line.to_uppercase().starts_with("PASS"))
 }
 
 /// Execute a shell command with cross-platform compatibility and mandatory timeout
@@ -349,9 +509,11 @@ mod tests {
         let checks = vec![
             SuccessCheck::Shell {
                 command: "echo 'test'".to_string(),
+                max_attempts: None,
             },
             SuccessCheck::Shell {
                 command: "true".to_string(),
+                max_attempts: None,
             },
         ];
         let retry_config = create_test_retry_config();
@@ -366,9 +528,11 @@ mod tests {
         let checks = vec![
             SuccessCheck::Shell {
                 command: "echo 'test'".to_string(),
+                max_attempts: None,
             },
             SuccessCheck::Shell {
                 command: "false".to_string(),
+                max_attempts: None,
             },
         ];
         let retry_config = create_test_retry_config();
@@ -497,4 +661,89 @@ mod tests {
         assert_eq!(on_failure_timeout, Duration::from_secs(300));
         assert_ne!(retry_timeout, on_failure_timeout);
     }
+
+    #[tokio::test]
+    async fn test_json_schema_success_check() {
+        let checks = vec![SuccessCheck::JsonSchema {
+            schema: serde_json::json!({
+                "type": "object",
+                "required": ["count"],
+                "properties": {"count": {"type": "integer"}}
+            }),
+            max_attempts: None,
+        }];
+        let retry_config = create_test_retry_config();
+        let context = |output: Option<&str>| SuccessCheckContext {
+            output: output.map(|output| output.to_string()),
+            provider: None,
+        };
+
+        let failed =
+            failed_success_checks(&checks, &retry_config, &context(Some(r#"{"count": 3}"#)))
+                .await
+                .unwrap();
+        assert!(failed.is_empty());
+
+        for output in [Some(r#"{"count": "three"}"#), Some("not json"), None] {
+            let failed = failed_success_checks(&checks, &retry_config, &context(output))
+                .await
+                .unwrap();
+            assert_eq!(failed, vec![0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_judge_without_provider_errors() {
+        let checks = vec![SuccessCheck::LlmJudge {
+            prompt: "The answer mentions the weather".to_string(),
+            max_attempts: None,
+        }];
+        let retry_config = create_test_retry_config();
+
+        let result = execute_success_checks(&checks, &retry_config).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_judge_passed() {
+        assert!(judge_passed("\nPASS\nThe output lists all files"));
+        assert!(judge_passed("pass: looks good"));
+        assert!(!judge_passed("FAIL\nMissing the summary"));
+        assert!(!judge_passed(""));
+    }
+
+    #[tokio::test]
+    async fn test_check_max_attempts_are_independent() {
+        let manager = RetryManager::new();
+        let mut retry_config = create_test_retry_config();
+        retry_config.checks = vec![
+            SuccessCheck::Shell {
+                command: "false".to_string(),
+                max_attempts: Some(2),
+            },
+            SuccessCheck::Shell {
+                command: "false".to_string(),
+                max_attempts: None,
+            },
+        ];
+
+        assert_eq!(
+            manager.record_check_failures(&[0, 1], &retry_config).await,
+            None
+        );
+        assert_eq!(
+            manager.record_check_failures(&[1], &retry_config).await,
+            None
+        );
+        assert_eq!(
+            manager.record_check_failures(&[0, 1], &retry_config).await,
+            Some(0)
+        );
+
+        manager.reset_attempts().await;
+        assert_eq!(
+            manager.record_check_failures(&[0], &retry_config).await,
+            None
+        );
+    }
 }
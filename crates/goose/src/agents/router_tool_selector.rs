@@ -11,10 +11,18 @@ use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::agents::router_vector_index::{embed, tool_text, VectorIndex};
+use crate::config::Config;
 use crate::conversation::message::Message;
 use crate::prompt_template::render_global_file;
 use crate::providers::base::Provider;
 
+/// Config key choosing how the router selects tools, "llm" (default) or "vector"
+pub const GOOSE_ROUTER_STRATEGY: &str = "GOOSE_ROUTER_STRATEGY";
+
+/// Number of tools returned by the vector router when the agent does not ask for a number
+const DEFAULT_VECTOR_SEARCH_LIMIT: usize = 5;
+
 #[derive(Serialize)]
 struct ToolSelectorContext {
     tools: String,
@@ -124,16 +132,7 @@ impl RouterToolSelector for LLMToolSelector {
         let mut tool_strings = self.tool_strings.write().await;
 
         for tool in tools {
-            let tool_string = format!(
-                "Tool: {}\nDescription: {}\nSchema: {}",
-                tool.name,
-                tool.description
-                    .as_ref()
-                    .map(|d| d.as_ref())
-                    .unwrap_or_default(),
-                serde_json::to_string_pretty(&tool.input_schema)
-                    .unwrap_or_else(|_| "{}".to_string())
-            );
+            let tool_string = tool_text(tool);
 
             // Use the provided extension_name instead of parsing from tool name
             let entry = tool_strings.entry(extension_name.to_string()).or_default();
@@ -172,10 +171,195 @@ impl RouterToolSelector for LLMToolSelector {
     }
 }
 
+/// Selects tools by embedding similarity. The embeddings come from the provider when it has
+/// an embedding model, and are computed locally otherwise
+pub struct VectorToolSelector {
+    index: Arc<RwLock<VectorIndex>>,
+    embedding_provider: Option<Arc<dyn Provider>>,
+    recent_tool_calls: Arc<RwLock<VecDeque<String>>>,
+}
+
+impl VectorToolSelector {
+    pub fn new(index: VectorIndex) -> Self {
+        Self {
+            index: Arc::new(RwLock::new(index)),
+            embedding_provider: None,
+            recent_tool_calls: Arc::new(RwLock::new(VecDeque::with_capacity(100))),
+        }
+    }
+
+    /// Use the provider's embeddings, if it has an embedding model
+    pub fn with_provider(index: VectorIndex, provider: Arc<dyn Provider>) -> Self {
+        let Some(model) = provider
+            .supports_embeddings()
+            .then(|| provider.embedding_model())
+            .flatten()
+        else {
+            return Self::new(index);
+        };
+        Self {
+            embedding_provider: Some(provider),
+            ..Self::new(index.with_model(model))
+        }
+    }
+
+    async fn embed_texts(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ErrorData> {
+        match &self.embedding_provider {
+            Some(provider) => provider
+                .create_embeddings(texts)
+                .await
+                .map_err(|e| ErrorData {
+                    code: ErrorCode::INTERNAL_ERROR,
+                    message: Cow::from(format!("Failed to embed: {}", e)),
+                    data: None,
+                }),
+            None => {
+                tokio::task::spawn_blocking(move || texts.iter().map(|text| embed(text)).collect())
+                    .await
+                    .map_err(|e| ErrorData {
+                        code: ErrorCode::INTERNAL_ERROR,
+                        message: Cow::from(format!("Failed to embed: {}", e)),
+                        data: None,
+                    })
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RouterToolSelector for VectorToolSelector {
+    async fn select_tools(&self, params: Value) -> Result<Vec<Content>, ErrorData> {
+        let query = params
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ErrorData {
+                code: ErrorCode::INVALID_PARAMS,
+                message: Cow::from("Missing 'query' parameter"),
+                data: None,
+            })?;
+        let extension_name = params.get("extension_name").and_then(|v| v.as_str());
+        let limit = params
+            .get("k")
+            .and_then(|v| v.as_u64())
+            .map(|k| k as usize)
+            .unwrap_or(DEFAULT_VECTOR_SEARCH_LIMIT);
+
+        let query_embedding = self
+            .embed_texts(vec![query.to_string()])
+            .await?
+            .pop()
+            .unwrap_or_default();
+        let index = self.index.read().await;
+        Ok(index
+            .search(&query_embedding, extension_name, limit)
+            .into_iter()
+            .map(|(tool, _score)| Content::text(tool.text.clone()))
+            .collect())
+    }
+
+    async fn index_tools(&self, tools: &[Tool], extension_name: &str) -> Result<(), ErrorData> {
+        let to_index_error = |e: anyhow::Error| ErrorData {
+            code: ErrorCode::INTERNAL_ERROR,
+            message: Cow::from(format!("Failed to index tools: {}", e)),
+            data: None,
+        };
+
+        // Embeddings are computed and the cache written without holding the lock
+        let texts = self.index.read().await.uncached_texts(tools);
+        let embeddings = if texts.is_empty() {
+            Vec::new()
+        } else {
+            self.embed_texts(texts.clone()).await?
+        };
+
+        let pending_save = {
+            let mut index = self.index.write().await;
+            index
+                .add_embeddings(texts, embeddings)
+                .map_err(to_index_error)?;
+            index
+                .index_tools(tools, extension_name)
+                .map_err(to_index_error)?;
+            index.pending_save().map_err(to_index_error)?
+        };
+        if let Some(pending_save) = pending_save {
+            tokio::task::spawn_blocking(move || pending_save.write())
+                .await
+                .map_err(|e| to_index_error(e.into()))?
+                .map_err(to_index_error)?;
+        }
+        Ok(())
+    }
+
+    async fn remove_tool(&self, tool_name: &str) -> Result<(), ErrorData> {
+        self.index.write().await.remove_tool(tool_name);
+        Ok(())
+    }
+
+    async fn record_tool_call(&self, tool_name: &str) -> Result<(), ErrorData> {
+        let mut recent_calls = self.recent_tool_calls.write().await;
+        if recent_calls.len() >= 100 {
+            recent_calls.pop_front();
+        }
+        recent_calls.push_back(tool_name.to_string());
+        Ok(())
+    }
+
+    async fn get_recent_tool_calls(&self, limit: usize) -> Result<Vec<String>, ErrorData> {
+        let recent_calls = self.recent_tool_calls.read().await;
+        Ok(recent_calls.iter().rev().take(limit).cloned().collect())
+    }
+}
+
 // Helper function to create a boxed tool selector
 pub async fn create_tool_selector(
     provider: Arc<dyn Provider>,
 ) -> Result<Box<dyn RouterToolSelector>> {
+    let strategy = Config::global()
+        .get_param::<String>(GOOSE_ROUTER_STRATEGY)
+        .unwrap_or_default();
+    if strategy.eq_ignore_ascii_case("vector") {
+        let index = tokio::task::spawn_blocking(VectorIndex::load).await?;
+        return Ok(Box::new(VectorToolSelector::with_provider(index, provider)));
+    }
+
     let selector = LLMToolSelector::new(provider).await?;
     Ok(Box::new(selector))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::object;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_vector_selector_selects_and_removes_tools() {
+        let selector = VectorToolSelector::new(VectorIndex::with_cache_path(None));
+        let tools = [
+            Tool::new("developer__shell", "Run a shell command", object!({})),
+            Tool::new("developer__text_editor", "View and edit files", object!({})),
+        ];
+        selector.index_tools(&tools, "developer").await.unwrap();
+
+        let selected = selector
+            .select_tools(json!({"query": "edit a file", "extension_name": "developer", "k": 1}))
+            .await
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        let text = selected[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("Tool: developer__text_editor"));
+
+        selector
+            .remove_tool("developer__text_editor")
+            .await
+            .unwrap();
+        let selected = selector
+            .select_tools(json!({"query": "edit a file", "extension_name": "developer"}))
+            .await
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+
+        assert!(selector.select_tools(json!({})).await.is_err());
+    }
+}
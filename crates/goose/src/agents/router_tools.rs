@@ -1,6 +1,7 @@
 use super::platform_tools::{
     PLATFORM_LIST_RESOURCES_TOOL_NAME, PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME,
     PLATFORM_READ_RESOURCE_TOOL_NAME, PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME,
+    PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME,
 };
 use indoc::indoc;
 use rmcp::model::{Tool, ToolAnnotations};
@@ -56,10 +57,12 @@ pub fn llm_search_tool_prompt() -> String {
     - {}
     - {}
     - {}
+    - {}
     "#,
         PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME,
         PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME,
         PLATFORM_READ_RESOURCE_TOOL_NAME,
-        PLATFORM_LIST_RESOURCES_TOOL_NAME
+        PLATFORM_LIST_RESOURCES_TOOL_NAME,
+        PLATFORM_SUBSCRIBE_RESOURCE_TOOL_NAME
     )
 }
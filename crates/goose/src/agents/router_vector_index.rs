@@ -0,0 +1,422 @@
+use crate::providers::embedding::rank_by_similarity;
+use anyhow::{anyhow, Result};
+use etcetera::{choose_app_strategy, AppStrategy};
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Size of the embedding vectors
+const EMBEDDING_DIMENSIONS: usize = 512;
+
+/// Bumped whenever the way embeddings are computed or cached changes, so stale caches are dropped
+const EMBEDDING_VERSION: u32 = 3;
+
+/// Most embeddings kept in the cache, the least recently used ones are dropped beyond that
+const MAX_CACHED_EMBEDDINGS: usize = 2000;
+
+/// Model name of the embeddings computed by [`embed`]
+pub const LOCAL_EMBEDDING_MODEL: &str = "local";
+
+/// Weight of character trigrams relative to whole words
+const TRIGRAM_WEIGHT: f32 = 0.5;
+
+/// FNV-1a, stable across Rust releases unlike the std hasher, so cached vectors stay valid
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.len() > 1)
+        .map(|token| token.to_lowercase())
+}
+
+fn add_feature(vector: &mut [f32], feature: &str, weight: f32) {
+    let hash = stable_hash(feature);
+    let index = (hash % EMBEDDING_DIMENSIONS as u64) as usize;
+    // The sign bit keeps colliding features from always adding up
+    let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+    vector[index] += sign * weight;
+}
+
+/// Embed text locally by hashing its words and character trigrams into a fixed size
+/// vector, so similar wording ends up close without calling a model
+pub fn embed(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; EMBEDDING_DIMENSIONS];
+    for token in tokenize(text) {
+        add_feature(&mut vector, &token, 1.0);
+        let chars: Vec<char> = format!("#{}#", token).chars().collect();
+        for trigram in chars.windows(3) {
+            add_feature(
+                &mut vector,
+                &trigram.iter().collect::<String>(),
+                TRIGRAM_WEIGHT,
+            );
+        }
+    }
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// Text of a tool as it is embedded and as it is returned to the agent
+pub fn tool_text(tool: &Tool) -> String {
+    format!(
+        "Tool: {}\nDescription: {}\nSchema: {}",
+        tool.name,
+        tool.description
+            .as_ref()
+            .map(|d| d.as_ref())
+            .unwrap_or_default(),
+        serde_json::to_string_pretty(&tool.input_schema).unwrap_or_else(|_| "{}".to_string())
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedTool {
+    pub name: String,
+    pub extension_name: String,
+    pub text: String,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedEmbedding {
+    embedding: Vec<f32>,
+    /// Unix time the embedding was last indexed
+    last_used: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct EmbeddingCache {
+    version: u32,
+    /// Embeddings by model and hash of the embedded text
+    embeddings: HashMap<String, CachedEmbedding>,
+}
+
+impl EmbeddingCache {
+    fn insert(&mut self, key: String, embedding: Vec<f32>) {
+        let last_used = chrono::Utc::now().timestamp();
+        self.embeddings.insert(
+            key,
+            CachedEmbedding {
+                embedding,
+                last_used,
+            },
+        );
+    }
+
+    /// Drop the least recently used embeddings beyond `max`
+    fn evict(&mut self, max: usize) {
+        if self.embeddings.len() <= max {
+            return;
+        }
+        let mut by_use: Vec<(i64, String)> = self
+            .embeddings
+            .iter()
+            .map(|(key, cached)| (cached.last_used, key.clone()))
+            .collect();
+        by_use.sort();
+        let excess = self.embeddings.len() - max;
+        for (_, key) in by_use.into_iter().take(excess) {
+            self.embeddings.remove(&key);
+        }
+    }
+}
+
+/// Cache contents to be written to disk, outside of any lock on the index
+pub struct PendingSave {
+    path: PathBuf,
+    contents: String,
+}
+
+impl PendingSave {
+    /// Write through a temporary file, so a crash never leaves a partial cache
+    pub fn write(self) -> Result<()> {
+        let parent = self
+            .path
+            .parent()
+            .ok_or_else(|| anyhow!("Invalid cache path {}", self.path.display()))?;
+        std::fs::create_dir_all(parent)?;
+        let mut file = tempfile::NamedTempFile::new_in(parent)?;
+        file.write_all(self.contents.as_bytes())?;
+        file.persist(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Index of tool embeddings, with the embeddings cached on disk so tools that did not
+/// change are not embedded again when the index is rebuilt. Embeddings are computed locally
+/// unless the index is for a provider's model, then they are added with `add_embeddings`
+pub struct VectorIndex {
+    tools: Vec<IndexedTool>,
+    cache: EmbeddingCache,
+    cache_path: Option<PathBuf>,
+    /// Whether the cache has embeddings that were not saved yet
+    dirty: bool,
+    model: String,
+}
+
+impl VectorIndex {
+    /// Index backed by the cache in the data dir
+    pub fn load() -> Self {
+        let cache_path = choose_app_strategy(crate::config::APP_STRATEGY.clone())
+            .map(|strategy| strategy.in_data_dir("router/tool_embeddings.json"))
+            .ok();
+        Self::with_cache_path(cache_path)
+    }
+
+    pub fn with_cache_path(cache_path: Option<PathBuf>) -> Self {
+        let cache = cache_path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str::<EmbeddingCache>(&contents).ok())
+            .filter(|cache| cache.version == EMBEDDING_VERSION)
+            .unwrap_or(EmbeddingCache {
+                version: EMBEDDING_VERSION,
+                embeddings: HashMap::new(),
+            });
+        Self {
+            tools: Vec::new(),
+            cache,
+            cache_path,
+            dirty: false,
+            model: LOCAL_EMBEDDING_MODEL.to_string(),
+        }
+    }
+
+    /// Use the embeddings of another model than the local one
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    fn cache_key(&self, text: &str) -> String {
+        format!("{}:{:016x}", self.model, stable_hash(text))
+    }
+
+    /// The texts of the tools that have no embedding yet
+    pub fn uncached_texts(&self, tools: &[Tool]) -> Vec<String> {
+        tools
+            .iter()
+            .map(tool_text)
+            .filter(|text| !self.cache.embeddings.contains_key(&self.cache_key(text)))
+            .collect()
+    }
+
+    /// Add embeddings of the index's model, computed without holding on to the index
+    pub fn add_embeddings(&mut self, texts: Vec<String>, embeddings: Vec<Vec<f32>>) -> Result<()> {
+        if texts.len() != embeddings.len() {
+            return Err(anyhow!(
+                "Got {} embeddings for {} texts",
+                embeddings.len(),
+                texts.len()
+            ));
+        }
+        for (text, embedding) in texts.into_iter().zip(embeddings) {
+            let key = self.cache_key(&text);
+            self.cache.insert(key, embedding);
+            self.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Add or replace the tools of an extension, returns how many had to be embedded. The
+    /// cache is not written, see `pending_save`
+    pub fn index_tools(&mut self, tools: &[Tool], extension_name: &str) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp();
+        let mut embedded = 0;
+        for tool in tools {
+            let text = tool_text(tool);
+            let key = self.cache_key(&text);
+            let embedding = match self.cache.embeddings.get_mut(&key) {
+                Some(cached) => {
+                    cached.last_used = now;
+                    cached.embedding.clone()
+                }
+                None if self.model != LOCAL_EMBEDDING_MODEL => {
+                    return Err(anyhow!("No {} embedding for {}", self.model, tool.name));
+                }
+                None => {
+                    embedded += 1;
+                    let embedding = embed(&text);
+                    self.cache.insert(key, embedding.clone());
+                    self.dirty = true;
+                    embedding
+                }
+            };
+
+            self.tools.retain(|indexed| indexed.name != tool.name);
+            self.tools.push(IndexedTool {
+                name: tool.name.to_string(),
+                extension_name: extension_name.to_string(),
+                text,
+                embedding,
+            });
+        }
+        Ok(embedded)
+    }
+
+    pub fn remove_tool(&mut self, tool_name: &str) {
+        self.tools.retain(|indexed| indexed.name != tool_name);
+    }
+
+    /// The `limit` tools closest to the embedded query, optionally only from one extension
+    pub fn search(
+        &self,
+        query_embedding: &[f32],
+        extension_name: Option<&str>,
+        limit: usize,
+    ) -> Vec<(&IndexedTool, f32)> {
+        let candidates: Vec<&IndexedTool> = self
+            .tools
+            .iter()
+            .filter(|tool| extension_name.is_none_or(|name| tool.extension_name == name))
+            .collect();
+        let embeddings: Vec<Vec<f32>> = candidates
+            .iter()
+            .map(|tool| tool.embedding.clone())
+            .collect();
+        rank_by_similarity(query_embedding, &embeddings, limit)
+            .into_iter()
+            .map(|(i, score)| (candidates[i], score))
+            .collect()
+    }
+
+    /// The cache to write if it has new embeddings, bounded to the most recently used ones
+    pub fn pending_save(&mut self) -> Result<Option<PendingSave>> {
+        let Some(path) = self.cache_path.clone().filter(|_| self.dirty) else {
+            return Ok(None);
+        };
+        self.cache.evict(MAX_CACHED_EMBEDDINGS);
+        let contents = serde_json::to_string(&self.cache)?;
+        self.dirty = false;
+        Ok(Some(PendingSave { path, contents }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::embedding::cosine_similarity;
+    use rmcp::object;
+    use tempfile::TempDir;
+
+    fn tool(name: &str, description: &str) -> Tool {
+        Tool::new(
+            name.to_string(),
+            description.to_string(),
+            object!({"type": "object"}),
+        )
+    }
+
+    #[test]
+    fn test_embed_is_normalized_and_similar_text_is_close() {
+        let files = embed("list the files in a directory");
+        let norm: f32 = files.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-4);
+
+        let listing = embed("list directory files");
+        let weather = embed("get the weather forecast");
+        assert!(cosine_similarity(&files, &listing) > cosine_similarity(&files, &weather));
+    }
+
+    #[test]
+    fn test_search_ranks_and_filters_by_extension() {
+        let mut index = VectorIndex::with_cache_path(None);
+        index
+            .index_tools(
+                &[
+                    tool("developer__shell", "Run a shell command"),
+                    tool("developer__list_files", "List files in a directory"),
+                ],
+                "developer",
+            )
+            .unwrap();
+        index
+            .index_tools(&[tool("weather__forecast", "Get the forecast")], "weather")
+            .unwrap();
+
+        let results = index.search(&embed("list the files"), None, 2);
+        assert_eq!(results[0].0.name, "developer__list_files");
+        assert_eq!(results.len(), 2);
+
+        let results = index.search(&embed("list the files"), Some("weather"), 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.name, "weather__forecast");
+
+        index.remove_tool("weather__forecast");
+        assert!(index
+            .search(&embed("forecast"), Some("weather"), 5)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_embeddings_are_cached_on_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_path = temp_dir.path().join("router/tool_embeddings.json");
+        let tools = [tool("developer__shell", "Run a shell command")];
+
+        let mut index = VectorIndex::with_cache_path(Some(cache_path.clone()));
+        assert_eq!(index.index_tools(&tools, "developer").unwrap(), 1);
+        assert_eq!(index.index_tools(&tools, "developer").unwrap(), 0);
+        assert!(!cache_path.exists());
+        index.pending_save().unwrap().unwrap().write().unwrap();
+        assert!(cache_path.exists());
+        assert!(index.pending_save().unwrap().is_none());
+
+        let mut reloaded = VectorIndex::with_cache_path(Some(cache_path));
+        assert_eq!(reloaded.index_tools(&tools, "developer").unwrap(), 0);
+        assert_eq!(reloaded.search(&embed("shell"), None, 5).len(), 1);
+    }
+
+    #[test]
+    fn test_provider_embeddings_are_kept_apart() {
+        let tools = [tool("developer__shell", "Run a shell command")];
+        let mut index = VectorIndex::with_cache_path(None);
+        index.index_tools(&tools, "developer").unwrap();
+
+        // The local embedding can't stand in for another model's
+        let mut index = VectorIndex {
+            tools: Vec::new(),
+            ..index
+        }
+        .with_model("text-embedding-3-small".to_string());
+        assert!(index.index_tools(&tools, "developer").is_err());
+
+        let texts = index.uncached_texts(&tools);
+        assert_eq!(texts.len(), 1);
+        index.add_embeddings(texts, vec![vec![0.0, 1.0]]).unwrap();
+        assert!(index.uncached_texts(&tools).is_empty());
+        assert_eq!(index.index_tools(&tools, "developer").unwrap(), 0);
+        assert_eq!(
+            index.search(&[0.0, 2.0], None, 5)[0].0.name,
+            "developer__shell"
+        );
+    }
+
+    #[test]
+    fn test_cache_keeps_the_most_recently_used_embeddings() {
+        let mut cache = EmbeddingCache::default();
+        for i in 0..5 {
+            cache.embeddings.insert(
+                format!("local:{i}"),
+                CachedEmbedding {
+                    embedding: vec![i as f32],
+                    last_used: i,
+                },
+            );
+        }
+        cache.evict(3);
+        let mut kept: Vec<&String> = cache.embeddings.keys().collect();
+        kept.sort();
+        assert_eq!(kept, vec!["local:2", "local:3", "local:4"]);
+    }
+}
@@ -0,0 +1,269 @@
+//! Hooks written as Rhai scripts, so users can add guardrails without recompiling goose. Every
+//! `*.rhai` file in the hooks directory of the goose config is a hook, defining any of these
+//! functions:
+//!
+//! ```rhai
+//! // Text returned is given to the model as context for the turn
+//! fn on_turn_start(turn) { if turn == 1 { "Run the tests before committing" } }
+//!
+//! // Return a string to veto the call with that reason, or a map to replace its arguments
+//! fn on_tool_call(tool) {
+//!     if tool.name == "developer__shell" && tool.arguments.command.contains("rm -rf") {
+//!         return "rm -rf is not allowed";
+//!     }
+//! }
+//!
+//! fn on_tool_result(result) { print(`${result.id} failed: ${result.is_error}`); }
+//! fn on_message(message) { print(`${message.role}: ${message.text}`); }
+//! fn on_session_end() { print("bye"); }
+//! ```
+//!
+//! A script that fails to compile is skipped, one that fails while running lets the agent go on.
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use etcetera::{choose_app_strategy, AppStrategy};
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::hooks::{Hook, ToolCallDecision};
+use super::types::SessionConfig;
+use crate::config::APP_STRATEGY;
+use crate::conversation::message::Message;
+use mcp_core::{ToolCall, ToolResult};
+use rmcp::model::{Content, Role};
+
+/// Operations a script may run per call, so a runaway loop can't hang the agent
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+pub fn hooks_dir() -> PathBuf {
+    choose_app_strategy(APP_STRATEGY.clone())
+        .expect("goose requires a home dir")
+        .config_dir()
+        .join("hooks")
+}
+
+/// The hooks of every script in the directory, in file name order
+pub fn load_script_hooks(dir: &Path) -> Vec<Arc<dyn Hook>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| match ScriptHook::load(&path) {
+            Ok(hook) => Some(Arc::new(hook) as Arc<dyn Hook>),
+            Err(e) => {
+                tracing::error!("Skipping hook script {}: {:#}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+pub struct ScriptHook {
+    name: String,
+    engine: Engine,
+    ast: AST,
+    functions: HashSet<String>,
+}
+
+impl ScriptHook {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::compile(&format!("script:{}", stem), &source)
+    }
+
+    pub fn compile(name: &str, source: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let script = name.to_string();
+        engine.on_print(move |text| tracing::info!("{}: {}", script, text));
+        let script = name.to_string();
+        engine.on_debug(move |text, _, _| tracing::debug!("{}: {}", script, text));
+
+        let ast = engine
+            .compile(source)
+            .with_context(|| format!("Failed to compile {}", name))?;
+        let functions = ast
+            .iter_functions()
+            .map(|function| function.name.to_string())
+            .collect();
+        Ok(Self {
+            name: name.to_string(),
+            engine,
+            ast,
+            functions,
+        })
+    }
+
+    /// Call a function of the script, None when it doesn't define it or fails
+    fn call(&self, function: &str, args: impl FuncArgs) -> Option<Dynamic> {
+        if !self.functions.contains(function) {
+            return None;
+        }
+        let options = CallFnOptions::new().eval_ast(false);
+        match self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut Scope::new(),
+            &self.ast,
+            function,
+            args,
+        ) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::error!("Hook script {} failed in {}: {}", self.name, function, e);
+                None
+            }
+        }
+    }
+}
+
+fn to_map(entries: impl IntoIterator<Item = (&'static str, Dynamic)>) -> Dynamic {
+    let map: Map = entries
+        .into_iter()
+        .map(|(key, value)| (key.into(), value))
+        .collect();
+    Dynamic::from_map(map)
+}
+
+/// What an on_tool_call result means for the call
+fn tool_call_decision(script: &str, result: Dynamic, tool_call: &mut ToolCall) -> ToolCallDecision {
+    if result.is_string() {
+        return ToolCallDecision::Veto(result.into_string().unwrap_or_default());
+    }
+    if result.as_bool() == Ok(false) {
+        return ToolCallDecision::Veto(format!("Refused by {}", script));
+    }
+    if result.is_map() {
+        match rhai::serde::from_dynamic(&result) {
+            Ok(arguments) => tool_call.arguments = arguments,
+            Err(e) => tracing::error!("Hook script {} returned invalid arguments: {}", script, e),
+        }
+    }
+    ToolCallDecision::Allow
+}
+
+#[async_trait]
+impl Hook for ScriptHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn on_turn_start(&self, turn: u32, _session: Option<&SessionConfig>) -> Option<String> {
+        let result = self.call("on_turn_start", (Dynamic::from_int(turn as i64),))?;
+        result
+            .into_string()
+            .ok()
+            .filter(|text| !text.trim().is_empty())
+    }
+
+    async fn on_tool_call(&self, request_id: &str, tool_call: &mut ToolCall) -> ToolCallDecision {
+        let arguments = rhai::serde::to_dynamic(&tool_call.arguments).unwrap_or(Dynamic::UNIT);
+        let call = to_map([
+            ("id", Dynamic::from(request_id.to_string())),
+            ("name", Dynamic::from(tool_call.name.clone())),
+            ("arguments", arguments),
+        ]);
+        match self.call("on_tool_call", (call,)) {
+            Some(result) => tool_call_decision(&self.name, result, tool_call),
+            None => ToolCallDecision::Allow,
+        }
+    }
+
+    async fn on_tool_result(&self, request_id: &str, result: &ToolResult<Vec<Content>>) {
+        let text = match result {
+            Ok(content) => content
+                .iter()
+                .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(e) => e.to_string(),
+        };
+        let result = to_map([
+            ("id", Dynamic::from(request_id.to_string())),
+            ("is_error", Dynamic::from_bool(result.is_err())),
+            ("text", Dynamic::from(text)),
+        ]);
+        self.call("on_tool_result", (result,));
+    }
+
+    async fn on_message(&self, message: &Message) {
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        let message = to_map([
+            ("role", Dynamic::from(role.to_string())),
+            ("text", Dynamic::from(message.as_concat_text())),
+        ]);
+        self.call("on_message", (message,));
+    }
+
+    async fn on_session_end(&self, _session: Option<&SessionConfig>) {
+        self.call("on_session_end", ());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SCRIPT: &str = r#"
+        fn on_turn_start(turn) { if turn == 1 { "Be careful" } }
+        fn on_tool_call(tool) {
+            if tool.arguments.command.contains("rm -rf") {
+                return "rm -rf is not allowed";
+            }
+            if tool.arguments.command == "ls" {
+                return #{ command: "ls -la" };
+            }
+        }
+    "#;
+
+    #[tokio::test]
+    async fn test_script_hook() {
+        let hook = ScriptHook::compile("script:test", SCRIPT).unwrap();
+        assert_eq!(
+            hook.on_turn_start(1, None).await,
+            Some("Be careful".to_string())
+        );
+        assert_eq!(hook.on_turn_start(2, None).await, None);
+
+        let mut tool_call = ToolCall::new("developer__shell", json!({"command": "rm -rf /"}));
+        assert_eq!(
+            hook.on_tool_call("1", &mut tool_call).await,
+            ToolCallDecision::Veto("rm -rf is not allowed".to_string())
+        );
+
+        let mut tool_call = ToolCall::new("developer__shell", json!({"command": "ls"}));
+        assert_eq!(
+            hook.on_tool_call("2", &mut tool_call).await,
+            ToolCallDecision::Allow
+        );
+        assert_eq!(tool_call.arguments, json!({"command": "ls -la"}));
+    }
+
+    #[tokio::test]
+    async fn test_failing_script_allows() {
+        let hook =
+            ScriptHook::compile("script:broken", "fn on_tool_call(tool) { tool.nope() }").unwrap();
+        let mut tool_call = ToolCall::new("developer__shell", json!({"command": "ls"}));
+        assert_eq!(
+            hook.on_tool_call("1", &mut tool_call).await,
+            ToolCallDecision::Allow
+        );
+        assert!(ScriptHook::compile("script:invalid", "fn (").is_err());
+    }
+}
@@ -1,21 +1,23 @@
-use crate::agents::subagent_task_config::DEFAULT_SUBAGENT_MAX_TURNS;
+use crate::agents::subagent_task_config::{SubagentMode, DEFAULT_SUBAGENT_MAX_TURNS};
 use crate::{
     agents::extension::ExtensionConfig,
-    agents::{extension_manager::ExtensionManager, Agent, TaskConfig},
+    agents::{extension_manager::ExtensionManager, Agent, AgentEvent, TaskConfig},
     config::ExtensionConfigManager,
     prompt_template::render_global_file,
     providers::errors::ProviderError,
+    webhooks::{self, WebhookEvent},
 };
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use rmcp::model::Tool;
 use rmcp::model::{ErrorCode, ErrorData};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 // use serde_json::{self};
 use crate::conversation::message::{Message, MessageContent, ToolRequest};
 use crate::conversation::Conversation;
 use std::{collections::HashMap, sync::Arc};
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, instrument};
 
@@ -58,19 +60,28 @@ impl SubAgent {
 
         // Create a new extension manager for this subagent
         let extension_manager = ExtensionManager::new();
+        // Extensions allowed to sample do so with the subagent's provider
+        if let Some(provider) = task_config.provider() {
+            extension_manager.set_provider(provider.clone()).await;
+        }
 
         // Add extensions based on task_type:
         // 1. If executing dynamic task (task_type = 'text_instruction'), default to using all enabled extensions
         // 2. (TODO) If executing a sub-recipe task, only use recipe extensions
 
         // Get all enabled extensions from config
-        let enabled_extensions = ExtensionConfigManager::get_all()
+        let mut enabled_extensions = ExtensionConfigManager::get_all()
             .unwrap_or_default()
             .into_iter()
             .filter(|ext| ext.enabled)
             .map(|ext| ext.config)
             .collect::<Vec<ExtensionConfig>>();
 
+        // A task limited to some extensions only sees those, not everything the parent has
+        if let Some(allowed) = &task_config.extensions {
+            enabled_extensions = restrict_extensions(enabled_extensions, allowed)?;
+        }
+
         // Add enabled extensions to the subagent's extension manager
         for extension in enabled_extensions {
             if let Err(e) = extension_manager.add_extension(extension).await {
@@ -103,14 +114,25 @@ impl SubAgent {
     }
 
     /// Process a message and generate a response using the subagent's provider
-    #[instrument(skip(self, message))]
+    ///
+    /// Each message the subagent produces is also sent to `events` as it happens, so the
+    /// parent can show the subagent's progress before the final result is ready
+    #[instrument(skip(self, message, events))]
     pub async fn reply_subagent(
         &self,
         message: String,
         task_config: TaskConfig,
+        events: Option<mpsc::UnboundedSender<AgentEvent>>,
     ) -> Result<Conversation, anyhow::Error> {
         debug!("Processing message for subagent {}", self.id);
 
+        let emit = |message: &Message| {
+            if let Some(events) = &events {
+                // The receiver going away only means nobody is watching anymore
+                let _ = events.send(AgentEvent::Message(message.clone()));
+            }
+        };
+
         // Get provider from task config
         let provider = self
             .config
@@ -141,7 +163,10 @@ impl SubAgent {
             .await
             .get_prefixed_tools(None)
             .await
-            .unwrap_or_default();
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|tool| tool_allowed_in_mode(tool, self.config.mode))
+            .collect();
 
         let toolshim_tools: Vec<Tool> = vec![];
 
@@ -152,6 +177,7 @@ impl SubAgent {
         let mut loop_count = 0;
         let max_turns = self.config.max_turns.unwrap_or(DEFAULT_SUBAGENT_MAX_TURNS);
         let mut last_error: Option<anyhow::Error> = None;
+        let mut tokens_used: i32 = 0;
 
         // Generate response from provider
         loop {
@@ -166,7 +192,13 @@ impl SubAgent {
             )
             .await
             {
-                Ok((response, _usage)) => {
+                Ok((response, usage)) => {
+                    tokens_used = tokens_used.saturating_add(usage.usage.total_tokens.unwrap_or(0));
+                    let budget_exhausted = self
+                        .config
+                        .max_tokens
+                        .is_some_and(|max_tokens| tokens_used >= max_tokens);
+
                     // Process any tool calls in the response
                     let tool_requests: Vec<ToolRequest> = response
                         .content
@@ -180,6 +212,8 @@ impl SubAgent {
                         })
                         .collect();
 
+                    emit(&response);
+
                     // If there are no tool requests, we're done
                     if tool_requests.is_empty() || loop_count >= max_turns {
                         self.add_message(response.clone()).await;
@@ -191,26 +225,68 @@ impl SubAgent {
                         break;
                     }
 
+                    if budget_exhausted {
+                        webhooks::notify(
+                            WebhookEvent::BudgetExceeded,
+                            json!({
+                                "budget": "subagent_tokens",
+                                "subagent": self.id,
+                                "used": tokens_used,
+                                "limit": self.config.max_tokens,
+                            }),
+                        );
+                        let note = Message::assistant().with_text(format!(
+                            "Stopped after using {} tokens, the task's budget is {} tokens",
+                            tokens_used,
+                            self.config.max_tokens.unwrap_or_default()
+                        ));
+                        self.add_message(response.clone()).await;
+                        messages.push(response.clone());
+                        emit(&note);
+                        messages.push(note);
+                        self.set_status(SubAgentStatus::Completed(
+                            "Token budget exhausted".to_string(),
+                        ))
+                        .await;
+                        break;
+                    }
+
                     // Add the assistant message with tool calls to the conversation
                     messages.push(response.clone());
 
                     // Process each tool request and create user response messages
                     for request in &tool_requests {
                         if let Ok(tool_call) = &request.tool_call {
-                            // Handle platform tools or dispatch to extension manager
-                            let tool_result = match self
-                                .extension_manager
-                                .read()
-                                .await
-                                .dispatch_tool_call(tool_call.clone(), CancellationToken::default())
-                                .await
-                            {
-                                Ok(result) => result.result.await,
-                                Err(e) => Err(ErrorData::new(
-                                    ErrorCode::INTERNAL_ERROR,
-                                    e.to_string(),
+                            // Tools outside the subagent's mode were not offered, refuse them
+                            // in case the model calls them anyway
+                            let tool_result = if !tools.iter().any(|t| t.name == tool_call.name) {
+                                Err(ErrorData::new(
+                                    ErrorCode::INVALID_REQUEST,
+                                    format!(
+                                        "Tool {} is not available to this subagent",
+                                        tool_call.name
+                                    ),
                                     None,
-                                )),
+                                ))
+                            } else {
+                                // Handle platform tools or dispatch to extension manager
+                                match self
+                                    .extension_manager
+                                    .read()
+                                    .await
+                                    .dispatch_tool_call(
+                                        tool_call.clone(),
+                                        CancellationToken::default(),
+                                    )
+                                    .await
+                                {
+                                    Ok(result) => result.result.await,
+                                    Err(e) => Err(ErrorData::new(
+                                        ErrorCode::INTERNAL_ERROR,
+                                        e.to_string(),
+                                        None,
+                                    )),
+                                }
                             };
 
                             match tool_result {
@@ -218,6 +294,7 @@ impl SubAgent {
                                     // Create a user message with the tool response
                                     let tool_response_message = Message::user()
                                         .with_tool_response(request.id.clone(), Ok(result.clone()));
+                                    emit(&tool_response_message);
                                     messages.push(tool_response_message);
                                 }
                                 Err(e) => {
@@ -230,6 +307,7 @@ impl SubAgent {
                                             None,
                                         )),
                                     );
+                                    emit(&tool_error_message);
                                     messages.push(tool_error_message);
                                 }
                             }
@@ -329,3 +407,79 @@ impl SubAgent {
         Ok(system_prompt)
     }
 }
+
+/// Keep the extensions named in `allowed`, failing if one of them is not enabled
+fn restrict_extensions(
+    extensions: Vec<ExtensionConfig>,
+    allowed: &[String],
+) -> Result<Vec<ExtensionConfig>, anyhow::Error> {
+    let unknown: Vec<&str> = allowed
+        .iter()
+        .filter(|name| !extensions.iter().any(|ext| &ext.name() == *name))
+        .map(|name| name.as_str())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(anyhow!(
+            "Extensions not enabled, the subagent cannot use them: {}",
+            unknown.join(", ")
+        ));
+    }
+    Ok(extensions
+        .into_iter()
+        .filter(|ext| allowed.contains(&ext.name()))
+        .collect())
+}
+
+fn tool_allowed_in_mode(tool: &Tool, mode: SubagentMode) -> bool {
+    match mode {
+        SubagentMode::Auto => true,
+        SubagentMode::ReadOnly => tool
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.read_only_hint)
+            .unwrap_or(false),
+        SubagentMode::Chat => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::ToolAnnotations;
+    use rmcp::object;
+
+    fn builtin(name: &str) -> ExtensionConfig {
+        ExtensionConfig::Builtin {
+            name: name.to_string(),
+            display_name: None,
+            timeout: None,
+            bundled: None,
+            description: None,
+            available_tools: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_restrict_extensions() {
+        let extensions = vec![builtin("developer"), builtin("memory")];
+        let restricted = restrict_extensions(extensions.clone(), &["memory".to_string()]).unwrap();
+        assert_eq!(restricted.len(), 1);
+        assert_eq!(restricted[0].name(), "memory");
+
+        assert!(restrict_extensions(extensions, &["slack".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_tool_allowed_in_mode() {
+        let read_only = Tool::new("developer__read", "", object!({})).annotate(ToolAnnotations {
+            read_only_hint: Some(true),
+            ..Default::default()
+        });
+        let write = Tool::new("developer__write", "", object!({}));
+
+        assert!(tool_allowed_in_mode(&write, SubagentMode::Auto));
+        assert!(tool_allowed_in_mode(&read_only, SubagentMode::ReadOnly));
+        assert!(!tool_allowed_in_mode(&write, SubagentMode::ReadOnly));
+        assert!(!tool_allowed_in_mode(&read_only, SubagentMode::Chat));
+    }
+}
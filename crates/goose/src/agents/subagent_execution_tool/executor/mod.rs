@@ -54,10 +54,13 @@ pub async fn execute_single_task(
     }
 }
 
+/// Run the tasks with at most `max_concurrency` of them at a time, `DEFAULT_MAX_WORKERS`
+/// when not given
 pub async fn execute_tasks_in_parallel(
     tasks: Vec<Task>,
     notifier: Sender<ServerNotification>,
     task_config: TaskConfig,
+    max_concurrency: Option<usize>,
     cancellation_token: Option<CancellationToken>,
 ) -> ExecutionResponse {
     let task_execution_tracker = Arc::new(TaskExecutionTracker::new(
@@ -89,7 +92,9 @@ pub async fn execute_tasks_in_parallel(
         cancellation_token.unwrap_or_default(),
     );
 
-    let worker_count = std::cmp::min(task_count, DEFAULT_MAX_WORKERS);
+    let worker_count = max_concurrency
+        .unwrap_or(DEFAULT_MAX_WORKERS)
+        .clamp(1, task_count);
     let mut worker_handles = Vec::new();
     for i in 0..worker_count {
         let handle = spawn_worker(shared_state.clone(), i, task_config.clone());
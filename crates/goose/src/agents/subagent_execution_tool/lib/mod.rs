@@ -6,6 +6,7 @@ use crate::agents::subagent_execution_tool::{
     tasks_manager::TasksManager,
 };
 use crate::agents::subagent_task_config::TaskConfig;
+use crate::utils::safe_truncate;
 use rmcp::model::ServerNotification;
 use serde_json::{json, Value};
 use tokio::sync::mpsc::Sender;
@@ -27,6 +28,11 @@ pub async fn execute_tasks(
     )
     .map_err(|e| format!("Failed to parse task_ids: {}", e))?;
 
+    let max_concurrency = input
+        .get("max_concurrency")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize);
+
     let tasks = tasks_manager.get_tasks(&task_ids).await?;
 
     let task_count = tasks.len();
@@ -35,7 +41,8 @@ pub async fn execute_tasks(
             if task_count == 1 {
                 let response =
                     execute_single_task(&tasks[0], notifier, task_config, cancellation_token).await;
-                handle_response(response)
+                let summary = format_summary_table(&tasks, &response.results);
+                with_summary(handle_response(response), summary)
             } else {
                 Err("Sequential execution mode requires exactly one task".to_string())
             }
@@ -51,13 +58,15 @@ pub async fn execute_tasks(
                 ))
             } else {
                 let response: ExecutionResponse = execute_tasks_in_parallel(
-                    tasks,
+                    tasks.clone(),
                     notifier.clone(),
                     task_config,
+                    max_concurrency,
                     cancellation_token,
                 )
                 .await;
-                handle_response(response)
+                let summary = format_summary_table(&tasks, &response.results);
+                with_summary(handle_response(response), summary)
             }
         }
     }
@@ -116,6 +125,66 @@ fn handle_response(response: ExecutionResponse) -> Result<Value, String> {
     serde_json::to_value(response).map_err(|e| format!("Failed to serialize response: {}", e))
 }
 
+/// Markdown table with one row per task, so the outcome of a fan-out can be read at a glance
+fn format_summary_table(tasks: &[Task], results: &[TaskResult]) -> String {
+    let cell = |text: &str, max_chars: usize| {
+        let first_line = text.lines().next().unwrap_or_default().trim();
+        safe_truncate(first_line, max_chars).replace('|', "\\|")
+    };
+
+    let mut table =
+        String::from("| # | Task | Status | Result |\n|---|------|--------|--------|\n");
+    for (index, task) in tasks.iter().enumerate() {
+        let name = task
+            .get_text_instruction()
+            .or_else(|| task.get_sub_recipe_name())
+            .unwrap_or(&task.id);
+        let result = results.iter().find(|result| result.task_id == task.id);
+        let (status, outcome) = match result {
+            Some(result) if matches!(result.status, TaskStatus::Completed) => {
+                let output = result
+                    .data
+                    .as_ref()
+                    .map(|data| match data.get("result").and_then(|v| v.as_str()) {
+                        Some(text) => text.to_string(),
+                        None => data.to_string(),
+                    })
+                    .unwrap_or_default();
+                ("completed", output)
+            }
+            Some(result) => (
+                "failed",
+                result
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+            ),
+            None => ("not run", String::new()),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            index + 1,
+            cell(name, 60),
+            status,
+            cell(&outcome, 80)
+        ));
+    }
+    table
+}
+
+fn with_summary(response: Result<Value, String>, summary: String) -> Result<Value, String> {
+    match response {
+        Ok(mut value) => {
+            value["summary"] = Value::String(summary);
+            Ok(value)
+        }
+        Err(error) => Err(format!("{}\n\n{}", error, summary)),
+    }
+}
+
 fn get_task_description(result: &TaskResult) -> String {
     format!("ID: {}", result.task_id)
 }
+
+#[cfg(test)]
+mod tests;
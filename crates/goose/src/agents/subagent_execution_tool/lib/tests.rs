@@ -1,9 +1,9 @@
 use super::{
-    extract_failed_tasks, format_error_summary, format_failed_task_error, get_task_description,
-    handle_response,
+    extract_failed_tasks, format_error_summary, format_failed_task_error, format_summary_table,
+    get_task_description, handle_response, with_summary,
 };
-use crate::agents::sub_recipe_execution_tool::lib::{
-    ExecutionResponse, ExecutionStats, TaskResult, TaskStatus,
+use crate::agents::subagent_execution_tool::lib::{
+    ExecutionResponse, ExecutionStats, Task, TaskResult, TaskStatus,
 };
 use serde_json::json;
 
@@ -214,3 +214,49 @@ fn test_get_task_description() {
 
     assert_eq!(description, "ID: test_task_123");
 }
+
+#[test]
+fn test_format_summary_table() {
+    let tasks: Vec<Task> = [
+        "Get weather for Paris",
+        "Get weather for Oslo | Norway",
+        "Unused",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(index, instruction)| Task {
+        id: format!("task{}", index + 1),
+        task_type: "text_instruction".to_string(),
+        payload: json!({"text_instruction": instruction}),
+    })
+    .collect();
+    let mut completed = create_test_task_result("task1", TaskStatus::Completed, None);
+    completed.data = Some(json!({"result": "Sunny, 21C\nwith a light breeze"}));
+    let results = vec![
+        completed,
+        create_test_task_result("task2", TaskStatus::Failed, Some("Timed out".to_string())),
+    ];
+
+    let table = format_summary_table(&tasks, &results);
+
+    let rows: Vec<&str> = table.lines().collect();
+    assert_eq!(rows.len(), 5);
+    assert_eq!(
+        rows[2],
+        "| 1 | Get weather for Paris | completed | Sunny, 21C |"
+    );
+    assert_eq!(
+        rows[3],
+        "| 2 | Get weather for Oslo \\| Norway | failed | Timed out |"
+    );
+    assert_eq!(rows[4], "| 3 | Unused | not run |  |");
+}
+
+#[test]
+fn test_with_summary() {
+    let value = with_summary(Ok(json!({"status": "completed"})), "table".to_string()).unwrap();
+    assert_eq!(value["summary"], "table");
+
+    let error = with_summary(Err("1/2 tasks failed".to_string()), "table".to_string());
+    assert_eq!(error.unwrap_err(), "1/2 tasks failed\n\ntable");
+}
@@ -1,4 +1,5 @@
 use crate::agents::subagent_execution_tool::task_types::TaskStatus;
+use crate::conversation::message::Message;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -7,6 +8,13 @@ use serde_json::Value;
 pub enum TaskExecutionNotificationEvent {
     #[serde(rename = "line_output")]
     LineOutput { task_id: String, output: String },
+    /// A message produced by the subagent running a task
+    #[serde(rename = "subagent_message")]
+    SubagentMessage {
+        task_id: String,
+        task_name: String,
+        message: Message,
+    },
     #[serde(rename = "tasks_update")]
     TasksUpdate {
         stats: TaskExecutionStats,
@@ -61,6 +69,14 @@ impl TaskExecutionNotificationEvent {
         Self::LineOutput { task_id, output }
     }
 
+    pub fn subagent_message(task_id: String, task_name: String, message: Message) -> Self {
+        Self::SubagentMessage {
+            task_id,
+            task_name,
+            message,
+        }
+    }
+
     pub fn tasks_update(stats: TaskExecutionStats, tasks: Vec<TaskInfo>) -> Self {
         Self::TasksUpdate { stats, tasks }
     }
@@ -141,6 +157,21 @@ mod tests {
         assert_eq!(notification_data["output"], "Hello World");
     }
 
+    #[test]
+    fn test_subagent_message_event_serialization() {
+        let event = TaskExecutionNotificationEvent::subagent_message(
+            "task-1".to_string(),
+            "task-1".to_string(),
+            Message::assistant().with_text("Looking at the config"),
+        );
+
+        let notification_data = event.to_notification_data();
+        assert_eq!(notification_data["type"], "task_execution");
+        assert_eq!(notification_data["subtype"], "subagent_message");
+        assert_eq!(notification_data["task_id"], "task-1");
+        assert_eq!(notification_data["message"]["role"], "assistant");
+    }
+
     #[test]
     fn test_tasks_update_event_serialization() {
         let stats = TaskExecutionStats::new(5, 2, 1, 1, 1);
@@ -48,6 +48,11 @@ pub fn create_subagent_execute_task_tool() -> Tool {
                         "type": "string",
                         "description": "Unique identifier for the task"
                     }
+                },
+                "max_concurrency": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Maximum number of tasks to run at the same time in parallel mode"
                 }
             },
             "required": ["task_ids"]
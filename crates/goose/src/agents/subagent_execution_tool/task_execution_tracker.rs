@@ -13,7 +13,10 @@ use crate::agents::subagent_execution_tool::notification_events::{
     TaskInfo as EventTaskInfo,
 };
 use crate::agents::subagent_execution_tool::task_types::{Task, TaskInfo, TaskResult, TaskStatus};
-use crate::agents::subagent_execution_tool::utils::{count_by_status, get_task_name};
+use crate::agents::subagent_execution_tool::utils::{
+    count_by_status, get_task_name, summarize_subagent_message,
+};
+use crate::agents::AgentEvent;
 use crate::utils::is_token_cancelled;
 use serde_json::Value;
 use tokio::sync::mpsc::Sender;
@@ -196,6 +199,36 @@ impl TaskExecutionTracker {
         }
     }
 
+    /// Show what a subagent is doing: a single task streams its messages as they are, while
+    /// the dashboard for multiple tasks shows a line per step under each task
+    pub async fn send_subagent_event(&self, task_id: &str, event: &AgentEvent) {
+        let AgentEvent::Message(message) = event else {
+            return;
+        };
+        match self.display_mode {
+            DisplayMode::SingleTaskOutput => {
+                let tasks = self.tasks.read().await;
+                let task_name = tasks
+                    .get(task_id)
+                    .map(|task_info| get_task_name(task_info).to_string())
+                    .unwrap_or_else(|| task_id.to_string());
+                drop(tasks);
+                let event = TaskExecutionNotificationEvent::subagent_message(
+                    task_id.to_string(),
+                    task_name,
+                    message.clone(),
+                );
+
+                self.try_send_notification(event, "subagent message");
+            }
+            DisplayMode::MultipleTasksOutput => {
+                for line in summarize_subagent_message(message) {
+                    self.send_live_output(task_id, &line).await;
+                }
+            }
+        }
+    }
+
     async fn should_throttle_refresh(&self) -> bool {
         let now = Instant::now();
         let mut last_refresh = self.last_refresh.write().await;
@@ -6,6 +6,7 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use crate::agents::subagent_execution_tool::task_execution_tracker::TaskExecutionTracker;
+use crate::agents::subagent_task_config::SubagentMode;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -53,6 +54,40 @@ impl Task {
             .and_then(|path| path.as_str())
     }
 
+    /// Extensions the task is limited to
+    pub fn get_extensions(&self) -> Option<Vec<String>> {
+        self.payload
+            .get("extensions")
+            .and_then(|v| v.as_array())
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| name.as_str())
+                    .map(|name| name.to_string())
+                    .collect()
+            })
+    }
+
+    pub fn get_max_turns(&self) -> Option<usize> {
+        self.payload
+            .get("max_turns")
+            .and_then(|v| v.as_u64())
+            .map(|turns| turns as usize)
+    }
+
+    pub fn get_max_tokens(&self) -> Option<i32> {
+        self.payload
+            .get("max_tokens")
+            .and_then(|v| v.as_u64())
+            .map(|tokens| tokens.min(i32::MAX as u64) as i32)
+    }
+
+    pub fn get_mode(&self) -> Option<SubagentMode> {
+        self.payload
+            .get("mode")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
     pub fn get_text_instruction(&self) -> Option<&str> {
         if self.task_type != "sub_recipe" {
             self.payload
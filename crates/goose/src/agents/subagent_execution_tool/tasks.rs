@@ -3,6 +3,7 @@ use std::process::Stdio;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use crate::agents::subagent_execution_tool::task_execution_tracker::TaskExecutionTracker;
@@ -88,12 +89,30 @@ async fn handle_text_instruction_task(
     // Start tracking the task
     task_execution_tracker.start_task(&task.id).await;
 
+    // Forward what the subagent does to the tracker so it shows up while the task runs
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let forwarder = {
+        let task_execution_tracker = task_execution_tracker.clone();
+        let task_id = task.id.clone();
+        tokio::spawn(async move {
+            while let Some(event) = event_rx.recv().await {
+                task_execution_tracker
+                    .send_subagent_event(&task_id, &event)
+                    .await;
+            }
+        })
+    };
+
+    let task_config = task_config.for_task(&task);
     let result = tokio::select! {
-        result = run_complete_subagent_task(text_instruction.to_string(), task_config) => result,
+        result = run_complete_subagent_task(text_instruction.to_string(), task_config, Some(event_tx)) => result,
         _ = cancellation_token.cancelled() => {
             return Err("Task cancelled".to_string());
         }
     };
+    // The sender is gone once the subagent finished, let the remaining events through
+    // before the task is reported as done
+    let _ = forwarder.await;
     match result {
         Ok(result_text) => Ok(serde_json::json!({
             "result": result_text
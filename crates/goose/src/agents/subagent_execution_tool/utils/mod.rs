@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::agents::subagent_execution_tool::task_types::{TaskInfo, TaskStatus};
+use crate::conversation::message::{Message, MessageContent};
 
 pub fn get_task_name(task_info: &TaskInfo) -> &str {
     task_info
@@ -23,6 +24,31 @@ pub fn count_by_status(tasks: &HashMap<String, TaskInfo>) -> (usize, usize, usiz
     (total, pending, running, completed, failed)
 }
 
+/// One line per thing a subagent did in a message, for showing its progress
+pub fn summarize_subagent_message(message: &Message) -> Vec<String> {
+    message
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            MessageContent::Text(text) => text
+                .text
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(|line| line.to_string()),
+            MessageContent::ToolRequest(request) => match &request.tool_call {
+                Ok(tool_call) => Some(format!("→ {}", tool_call.name)),
+                Err(e) => Some(format!("✗ invalid tool call: {}", e.message)),
+            },
+            MessageContent::ToolResponse(response) => match &response.tool_result {
+                Ok(_) => None,
+                Err(e) => Some(format!("✗ {}", e.message)),
+            },
+            _ => None,
+        })
+        .collect()
+}
+
 pub fn strip_ansi_codes(text: &str) -> String {
     let mut result = String::new();
     let mut chars = text.chars();
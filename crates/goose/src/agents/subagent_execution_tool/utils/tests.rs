@@ -1,7 +1,10 @@
 use crate::agents::subagent_execution_tool::task_types::{Task, TaskInfo, TaskStatus};
 use crate::agents::subagent_execution_tool::utils::{
-    count_by_status, get_task_name, strip_ansi_codes,
+    count_by_status, get_task_name, strip_ansi_codes, summarize_subagent_message,
 };
+use crate::conversation::message::Message;
+use mcp_core::tool::ToolCall;
+use rmcp::model::{ErrorCode, ErrorData};
 use serde_json::json;
 use std::collections::HashMap;
 
@@ -175,3 +178,40 @@ mod strip_ansi_codes {
         assert_eq!(strip_ansi_codes(""), "");
     }
 }
+
+mod summarize_subagent_message {
+    use super::*;
+
+    #[test]
+    fn test_summarizes_text_and_tool_calls() {
+        let message = Message::assistant()
+            .with_text("\n  Checking the config first\nthen the tests")
+            .with_tool_request(
+                "req-1",
+                Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+            );
+        assert_eq!(
+            summarize_subagent_message(&message),
+            vec!["Checking the config first", "→ developer__shell"]
+        );
+    }
+
+    #[test]
+    fn test_only_failed_tool_responses_are_shown() {
+        let ok = Message::user().with_tool_response("req-1", Ok(vec![]));
+        assert!(summarize_subagent_message(&ok).is_empty());
+
+        let failed = Message::user().with_tool_response(
+            "req-1",
+            Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                "command not found".to_string(),
+                None,
+            )),
+        );
+        assert_eq!(
+            summarize_subagent_message(&failed),
+            vec!["✗ command not found"]
+        );
+    }
+}
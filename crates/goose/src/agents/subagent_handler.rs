@@ -1,12 +1,16 @@
 use crate::agents::subagent::SubAgent;
 use crate::agents::subagent_task_config::TaskConfig;
+use crate::agents::AgentEvent;
 use anyhow::Result;
 use rmcp::model::{ErrorCode, ErrorData};
+use tokio::sync::mpsc;
 
-/// Standalone function to run a complete subagent task
+/// Standalone function to run a complete subagent task, streaming the subagent's
+/// messages to `events` while it runs
 pub async fn run_complete_subagent_task(
     text_instruction: String,
     task_config: TaskConfig,
+    events: Option<mpsc::UnboundedSender<AgentEvent>>,
 ) -> Result<String, anyhow::Error> {
     // Create the subagent with the parent agent's provider
     let subagent = SubAgent::new(task_config.clone()).await.map_err(|e| {
@@ -19,7 +23,7 @@ pub async fn run_complete_subagent_task(
 
     // Execute the subagent task
     let messages = subagent
-        .reply_subagent(text_instruction, task_config)
+        .reply_subagent(text_instruction, task_config, events)
         .await?;
 
     // Extract all text content from all messages
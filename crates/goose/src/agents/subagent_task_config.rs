@@ -1,4 +1,6 @@
+use crate::agents::subagent_execution_tool::task_types::Task;
 use crate::providers::base::Provider;
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::fmt;
 use std::sync::Arc;
@@ -10,12 +12,30 @@ pub const DEFAULT_SUBAGENT_MAX_TURNS: usize = 5;
 /// Environment variable name for configuring max turns
 pub const GOOSE_SUBAGENT_MAX_TURNS_ENV_VAR: &str = "GOOSE_SUBAGENT_MAX_TURNS";
 
+/// Which of its tools a subagent may call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubagentMode {
+    /// Every tool of the allowed extensions
+    #[default]
+    Auto,
+    /// Only tools annotated as read-only
+    ReadOnly,
+    /// No tools, the subagent only answers from the instruction
+    Chat,
+}
+
 /// Configuration for task execution with all necessary dependencies
 #[derive(Clone)]
 pub struct TaskConfig {
     pub id: String,
     pub provider: Option<Arc<dyn Provider>>,
     pub max_turns: Option<usize>,
+    /// Names of the extensions the subagent may use, every enabled extension when None
+    pub extensions: Option<Vec<String>>,
+    /// Total tokens the subagent may use across its turns
+    pub max_tokens: Option<i32>,
+    pub mode: SubagentMode,
 }
 
 impl fmt::Debug for TaskConfig {
@@ -24,6 +44,9 @@ impl fmt::Debug for TaskConfig {
             .field("id", &self.id)
             .field("provider", &"<dyn Provider>")
             .field("max_turns", &self.max_turns)
+            .field("extensions", &self.extensions)
+            .field("max_tokens", &self.max_tokens)
+            .field("mode", &self.mode)
             .finish()
     }
 }
@@ -40,7 +63,30 @@ impl TaskConfig {
                     .and_then(|val| val.parse::<usize>().ok())
                     .unwrap_or(DEFAULT_SUBAGENT_MAX_TURNS),
             ),
+            extensions: None,
+            max_tokens: None,
+            mode: SubagentMode::default(),
+        }
+    }
+
+    /// Config for running a task, with the limits the task declares in its payload applied
+    /// on top of this one
+    pub fn for_task(&self, task: &Task) -> Self {
+        let mut config = self.clone();
+        config.id = Uuid::new_v4().to_string();
+        if let Some(extensions) = task.get_extensions() {
+            config.extensions = Some(extensions);
+        }
+        if let Some(max_turns) = task.get_max_turns() {
+            config.max_turns = Some(max_turns);
+        }
+        if let Some(max_tokens) = task.get_max_tokens() {
+            config.max_tokens = Some(max_tokens);
         }
+        if let Some(mode) = task.get_mode() {
+            config.mode = mode;
+        }
+        config
     }
 
     /// Get a reference to the provider
@@ -48,3 +94,42 @@ impl TaskConfig {
         self.provider.as_ref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_for_task_applies_task_limits() {
+        let config = TaskConfig::new(None);
+        let task = Task {
+            id: "task".to_string(),
+            task_type: "text_instruction".to_string(),
+            payload: json!({
+                "text_instruction": "Summarize the README",
+                "extensions": ["developer"],
+                "max_turns": 3,
+                "max_tokens": 20000,
+                "mode": "read_only"
+            }),
+        };
+
+        let task_config = config.for_task(&task);
+        assert_eq!(task_config.extensions, Some(vec!["developer".to_string()]));
+        assert_eq!(task_config.max_turns, Some(3));
+        assert_eq!(task_config.max_tokens, Some(20000));
+        assert_eq!(task_config.mode, SubagentMode::ReadOnly);
+        assert_ne!(task_config.id, config.id);
+
+        // Tasks without limits keep the defaults
+        let task = Task {
+            payload: json!({"text_instruction": "Summarize the README"}),
+            ..task
+        };
+        let task_config = config.for_task(&task);
+        assert_eq!(task_config.extensions, None);
+        assert_eq!(task_config.max_turns, config.max_turns);
+        assert_eq!(task_config.mode, SubagentMode::Auto);
+    }
+}
@@ -1,6 +1,8 @@
 use indoc::indoc;
 use rmcp::model::{Tool, ToolAnnotations};
 use rmcp::object;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Tool name constant for reading task planner content
 pub const TODO_READ_TOOL_NAME: &str = "todo__read";
@@ -8,6 +10,155 @@ pub const TODO_READ_TOOL_NAME: &str = "todo__read";
 /// Tool name constant for writing task planner content
 pub const TODO_WRITE_TOOL_NAME: &str = "todo__write";
 
+/// Tool name constant for adding items to the TODO list
+pub const TODO_ADD_TOOL_NAME: &str = "todo__add";
+
+/// Tool name constant for updating an item of the TODO list
+pub const TODO_UPDATE_TOOL_NAME: &str = "todo__update";
+
+/// Tool name constant for completing an item of the TODO list
+pub const TODO_COMPLETE_TOOL_NAME: &str = "todo__complete";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Cancelled,
+}
+
+impl TodoStatus {
+    pub fn parse(status: &str) -> Option<Self> {
+        match status.trim().to_lowercase().as_str() {
+            "pending" => Some(Self::Pending),
+            "in_progress" => Some(Self::InProgress),
+            "completed" => Some(Self::Completed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Checkbox shown in front of an item when the list is rendered
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Self::Pending => "[ ]",
+            Self::InProgress => "[~]",
+            Self::Completed => "[x]",
+            Self::Cancelled => "[-]",
+        }
+    }
+}
+
+/// An item of the session-scoped TODO list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TodoItem {
+    /// Stable number the agent refers to the item by
+    pub id: u32,
+    pub title: String,
+    pub status: TodoStatus,
+    /// Subagent working on the item, if it was handed off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// Add an item to the list, returning its id
+pub fn add_todo(
+    items: &mut Vec<TodoItem>,
+    title: String,
+    owner: Option<String>,
+    notes: Option<String>,
+) -> u32 {
+    let id = items.iter().map(|item| item.id).max().unwrap_or(0) + 1;
+    items.push(TodoItem {
+        id,
+        title,
+        status: TodoStatus::Pending,
+        owner,
+        notes,
+    });
+    id
+}
+
+/// Convert the freeform content written by `todo__write` into items
+///
+/// Each non-empty line becomes an item, Markdown bullets are stripped and the checkboxes of
+/// `todo_content` set the status.
+pub fn parse_todo_content(content: &str) -> Vec<TodoItem> {
+    let mut items = Vec::new();
+    for line in content.lines() {
+        let mut text = line.trim();
+        if let Some(rest) = text.strip_prefix("- ").or_else(|| text.strip_prefix("* ")) {
+            text = rest.trim_start();
+        }
+        let status = if let Some(rest) = text.strip_prefix("[ ]") {
+            text = rest;
+            TodoStatus::Pending
+        } else if let Some(rest) = text
+            .strip_prefix("[x]")
+            .or_else(|| text.strip_prefix("[X]"))
+        {
+            text = rest;
+            TodoStatus::Completed
+        } else if let Some(rest) = text.strip_prefix("[~]") {
+            text = rest;
+            TodoStatus::InProgress
+        } else if let Some(rest) = text.strip_prefix("[-]") {
+            text = rest;
+            TodoStatus::Cancelled
+        } else {
+            TodoStatus::Pending
+        };
+
+        let title = text.trim();
+        if !title.is_empty() {
+            items.push(TodoItem {
+                id: items.len() as u32 + 1,
+                title: title.to_string(),
+                status,
+                owner: None,
+                notes: None,
+            });
+        }
+    }
+    items
+}
+
+/// The list as the Markdown checklist kept in the session's `todo_content`, for readers of the
+/// freeform format. `parse_todo_content` reads it back
+pub fn todo_content(items: &[TodoItem]) -> String {
+    items
+        .iter()
+        .map(|item| format!("- {} {}\n", item.status.marker(), item.title))
+        .collect()
+}
+
+/// Render the list as a checklist, used as the result of the TODO tools
+pub fn render_todos(items: &[TodoItem]) -> String {
+    if items.is_empty() {
+        return "The TODO list is empty".to_string();
+    }
+    let mut rendered = String::new();
+    for item in items {
+        rendered.push_str(&format!(
+            "{} {}. {}",
+            item.status.marker(),
+            item.id,
+            item.title
+        ));
+        if let Some(owner) = &item.owner {
+            rendered.push_str(&format!(" @{}", owner));
+        }
+        if let Some(notes) = &item.notes {
+            rendered.push_str(&format!(" ({})", notes));
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
 /// Creates a tool for reading task planner content.
 ///
 /// This tool reads the entire task planner file content as a string.
@@ -19,12 +170,10 @@ pub fn todo_read_tool() -> Tool {
     Tool::new(
         TODO_READ_TOOL_NAME.to_string(),
         indoc! {r#"
-            Read the entire TODO file content.
+            Read the TODO list.
 
-            This tool reads the complete TODO file and returns its content as a string.
-            Use this to view current tasks, notes, and any other information stored in the TODO file.
-
-            The tool will return an error if the TODO file doesn't exist or cannot be read.
+            Returns every item as a checklist line with its id, status, owner and notes.
+            Use the ids with todo__update and todo__complete.
         "#}
         .to_string(),
         object!({
@@ -53,16 +202,13 @@ pub fn todo_write_tool() -> Tool {
     Tool::new(
         TODO_WRITE_TOOL_NAME.to_string(),
         indoc! {r#"
-            Write or overwrite the entire TODO file content.
-
-            This tool replaces the complete TODO file content with the provided string.
-            Use this to update tasks, add new items, or reorganize the TODO file.
+            Replace the entire TODO list with Markdown checklist content.
 
-            WARNING: This operation completely replaces the file content. Make sure to include
-            all content you want to keep, not just the changes.
+            Each line becomes an item, `- [x]` marks an item completed. Prefer todo__add,
+            todo__update and todo__complete, which keep ids, owners and notes intact.
 
-            The tool will create the TODO file if it doesn't exist, or overwrite it if it does.
-            Returns an error if the file cannot be written due to permissions or other I/O issues.
+            WARNING: This operation completely replaces the list. Make sure to include
+            all items you want to keep, not just the changes.
         "#}
         .to_string(),
         object!({
@@ -85,6 +231,115 @@ pub fn todo_write_tool() -> Tool {
     })
 }
 
+/// Creates a tool for adding items to the TODO list.
+///
+/// # Returns
+/// A configured `Tool` instance for adding TODO items
+pub fn todo_add_tool() -> Tool {
+    Tool::new(
+        TODO_ADD_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Add one or more items to the TODO list.
+
+            Items start as pending and get an id that stays the same for the rest of the session.
+            Set owner to the name of a subagent when the item is handed off to one.
+        "#}
+        .to_string(),
+        object!({
+            "type": "object",
+            "required": ["items"],
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["title"],
+                        "properties": {
+                            "title": {"type": "string"},
+                            "owner": {"type": "string"},
+                            "notes": {"type": "string"}
+                        }
+                    },
+                    "description": "Items to add, in order"
+                }
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Add TODO items".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(false),
+        open_world_hint: Some(false),
+    })
+}
+
+/// Creates a tool for updating an item of the TODO list.
+///
+/// # Returns
+/// A configured `Tool` instance for updating a TODO item
+pub fn todo_update_tool() -> Tool {
+    Tool::new(
+        TODO_UPDATE_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Update an item of the TODO list by id.
+
+            Only the fields provided are changed. Mark an item in_progress when you start on it.
+        "#}
+        .to_string(),
+        object!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer", "description": "Id of the item"},
+                "title": {"type": "string"},
+                "status": {
+                    "type": "string",
+                    "enum": ["pending", "in_progress", "completed", "cancelled"]
+                },
+                "owner": {"type": "string", "description": "Subagent working on the item"},
+                "notes": {"type": "string", "description": "Blockers, dependencies or results"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Update TODO item".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
+/// Creates a tool for completing an item of the TODO list.
+///
+/// # Returns
+/// A configured `Tool` instance for completing a TODO item
+pub fn todo_complete_tool() -> Tool {
+    Tool::new(
+        TODO_COMPLETE_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Mark an item of the TODO list completed, optionally with a note on the result.
+        "#}
+        .to_string(),
+        object!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer", "description": "Id of the item"},
+                "notes": {"type": "string"}
+            }
+        }),
+    )
+    .annotate(ToolAnnotations {
+        title: Some("Complete TODO item".to_string()),
+        read_only_hint: Some(false),
+        destructive_hint: Some(false),
+        idempotent_hint: Some(true),
+        open_world_hint: Some(false),
+    })
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
@@ -156,5 +411,62 @@ mod unit_tests {
         assert!(TODO_WRITE_TOOL_NAME.starts_with("todo__"));
         assert_eq!(TODO_READ_TOOL_NAME, "todo__read");
         assert_eq!(TODO_WRITE_TOOL_NAME, "todo__write");
+        for tool in [todo_add_tool(), todo_update_tool(), todo_complete_tool()] {
+            assert!(tool.name.starts_with("todo__"));
+        }
+    }
+
+    #[test]
+    fn test_parse_todo_content() {
+        let items =
+            parse_todo_content("- [ ] Write tests\n- [x] Fix bug\n\n* Plain bullet\nFreeform");
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec!["Write tests", "Fix bug", "Plain bullet", "Freeform"]
+        );
+        assert_eq!(items[1].status, TodoStatus::Completed);
+        assert_eq!(items[0].status, TodoStatus::Pending);
+        assert_eq!(
+            items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_todo_content_round_trip() {
+        let mut items = Vec::new();
+        add_todo(&mut items, "Write tests".to_string(), None, None);
+        add_todo(&mut items, "Fix bug".to_string(), None, None);
+        add_todo(&mut items, "Ship it".to_string(), None, None);
+        items[0].status = TodoStatus::Completed;
+        items[1].status = TodoStatus::InProgress;
+
+        let content = todo_content(&items);
+        assert_eq!(content, "- [x] Write tests\n- [~] Fix bug\n- [ ] Ship it\n");
+        assert_eq!(parse_todo_content(&content), items);
+    }
+
+    #[test]
+    fn test_add_and_render_todos() {
+        let mut items = Vec::new();
+        assert_eq!(render_todos(&items), "The TODO list is empty");
+        assert_eq!(add_todo(&mut items, "Research".to_string(), None, None), 1);
+        assert_eq!(
+            add_todo(
+                &mut items,
+                "Summarize".to_string(),
+                Some("writer".to_string()),
+                Some("after research".to_string())
+            ),
+            2
+        );
+        items.remove(0);
+        // Ids are never reused
+        assert_eq!(add_todo(&mut items, "Review".to_string(), None, None), 3);
+        assert_eq!(
+            render_todos(&items),
+            "[ ] 2. Summarize @writer (after research)\n[ ] 3. Review\n"
+        );
     }
 }
@@ -1,5 +1,6 @@
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_stream::try_stream;
 use futures::stream::{self, BoxStream};
@@ -8,10 +9,13 @@ use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 
 use crate::config::permission::PermissionLevel;
-use crate::config::PermissionManager;
+use crate::config::{Config, PermissionManager};
+use crate::permission::permission_judge::CONFIRM_FIRST_USE_TOOLS;
 use crate::permission::Permission;
+use crate::webhooks::{self, WebhookEvent};
 use mcp_core::ToolResult;
-use rmcp::model::{Content, ServerNotification};
+use rmcp::model::{Content, ErrorCode, ErrorData, ServerNotification};
+use serde_json::json;
 
 // ToolCallResult combines the result of a tool call with an optional notification stream that
 // can be used to receive notifications from the tool.
@@ -30,6 +34,7 @@ impl From<ToolResult<Vec<Content>>> for ToolCallResult {
 }
 
 use super::agent::{tool_stream, ToolStream};
+use crate::agents::types::{ConfirmationTimeout, ConfirmationTimeoutAction};
 use crate::agents::Agent;
 use crate::conversation::message::{Message, ToolRequest};
 
@@ -37,6 +42,14 @@ pub const DECLINED_RESPONSE: &str = "The user has declined to run this tool. \
     DO NOT attempt to call this tool again. \
     If there are no alternative methods to proceed, clearly explain the situation and STOP.";
 
+/// Seconds to wait for the frontend to return the result of a frontend tool
+pub const FRONTEND_TOOL_TIMEOUT_KEY: &str = "GOOSE_FRONTEND_TOOL_TIMEOUT";
+const DEFAULT_FRONTEND_TOOL_TIMEOUT_SECS: u64 = 300;
+
+pub const TOOL_CANCELLED_RESPONSE: &str =
+    "The user cancelled this tool call while it was running. \
+    Do not retry it unless the user asks you to, continue with what you can do without it.";
+
 pub const CHAT_MODE_TOOL_SKIPPED_RESPONSE: &str = "Let the user know the tool call was skipped in Goose chat mode. \
                                         DO NOT apologize for skipping the tool call. DO NOT say sorry. \
                                         Provide an explanation of what the tool call would do, structured as a \
@@ -54,6 +67,7 @@ impl Agent {
         permission_manager: &'a mut PermissionManager,
         message_tool_response: Arc<Mutex<Message>>,
         cancellation_token: Option<CancellationToken>,
+        confirmation_timeout: Option<ConfirmationTimeout>,
     ) -> BoxStream<'a, anyhow::Result<Message>> {
         try_stream! {
             for request in tool_requests {
@@ -64,10 +78,35 @@ impl Agent {
                         tool_call.arguments.clone(),
                         Some("Goose would like to call the above tool. Allow? (y/n):".to_string()),
                     );
+                    webhooks::notify(
+                        WebhookEvent::ApprovalNeeded,
+                        json!({"request_id": request.id, "tool": tool_call.name}),
+                    );
                     yield confirmation;
 
                     let mut rx = self.confirmation_rx.lock().await;
-                    while let Some((req_id, confirmation)) = rx.recv().await {
+                    let deadline = confirmation_timeout
+                        .map(|timeout| tokio::time::Instant::now() + Duration::from_secs(timeout.seconds));
+                    loop {
+                        let received = match deadline {
+                            Some(deadline) => tokio::select! {
+                                received = rx.recv() => received,
+                                _ = tokio::time::sleep_until(deadline) => {
+                                    let timeout = confirmation_timeout.expect("deadline comes from the timeout");
+                                    tracing::warn!("Confirmation of {} timed out after {} seconds", tool_call.name, timeout.seconds);
+                                    let mut response = message_tool_response.lock().await;
+                                    *response = response.clone().with_tool_response(
+                                        request.id.clone(),
+                                        Ok(vec![Content::text(confirmation_timeout_response(&tool_call.name, timeout)).with_priority(1.0)]),
+                                    );
+                                    break;
+                                }
+                            },
+                            None => rx.recv().await,
+                        };
+                        let Some((req_id, confirmation)) = received else {
+                            break;
+                        };
                         if req_id == request.id {
                             if confirmation.permission == Permission::AllowOnce || confirmation.permission == Permission::AlwaysAllow {
                                 let (req_id, tool_result) = self.dispatch_tool_call(tool_call.clone(), request.id.clone(), cancellation_token.clone(), &None).await;
@@ -87,6 +126,9 @@ impl Agent {
                                 if confirmation.permission == Permission::AlwaysAllow {
                                     permission_manager.update_user_permission(&tool_call.name, PermissionLevel::AlwaysAllow);
                                 }
+                                if CONFIRM_FIRST_USE_TOOLS.contains(&tool_call.name.as_str()) {
+                                    self.confirmed_tools.lock().await.insert(tool_call.name.clone());
+                                }
                             } else {
                                 // User declined - add declined response
                                 let mut response = message_tool_response.lock().await;
@@ -109,6 +151,8 @@ impl Agent {
         message_tool_response: Arc<Mutex<Message>>,
     ) -> BoxStream<'a, anyhow::Result<Message>> {
         try_stream! {
+            let frontend_gone = self.frontend_gone.lock().await.clone();
+            let timeout = frontend_tool_timeout();
             for request in tool_requests {
                 if let Ok(tool_call) = request.tool_call.clone() {
                     if self.is_frontend_tool(&tool_call.name).await {
@@ -118,14 +162,194 @@ impl Agent {
                             Ok(tool_call.clone())
                         );
 
-                        if let Some((id, result)) = self.tool_result_rx.lock().await.recv().await {
-                            let mut response = message_tool_response.lock().await;
-                            *response = response.clone().with_tool_response(id, result);
-                        }
+                        let result = self
+                            .wait_for_frontend_tool_result(&request.id, &tool_call.name, &frontend_gone, timeout)
+                            .await;
+                        let mut response = message_tool_response.lock().await;
+                        *response = response.clone().with_tool_response(request.id.clone(), result);
                     }
                 }
             }
         }
         .boxed()
     }
+
+    /// Wait for the frontend to return the result of a frontend tool. Fails with a structured
+    /// error when it takes longer than the timeout or the frontend has gone away, so the turn
+    /// doesn't hang on a frontend that will never answer
+    async fn wait_for_frontend_tool_result(
+        &self,
+        request_id: &str,
+        tool_name: &str,
+        frontend_gone: &CancellationToken,
+        timeout: Duration,
+    ) -> ToolResult<Vec<Content>> {
+        let failure = |reason: &str, message: String| {
+            Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                message,
+                Some(json!({
+                    "reason": reason,
+                    "tool": tool_name,
+                    "timeoutSecs": timeout.as_secs(),
+                })),
+            ))
+        };
+        let gone = || {
+            failure(
+                "frontend_gone",
+                format!(
+                    "The frontend went away before returning a result for {}",
+                    tool_name
+                ),
+            )
+        };
+        if frontend_gone.is_cancelled() {
+            return gone();
+        }
+
+        let mut results = self.tool_result_rx.lock().await;
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            tokio::select! {
+                received = results.recv() => match received {
+                    Some((id, result)) if id == request_id => return result,
+                    Some((id, _)) => {
+                        // A result that arrived after its request timed out
+                        tracing::warn!("Ignoring late frontend tool result for {}", id);
+                    }
+                    None => return gone(),
+                },
+                _ = tokio::time::sleep_until(deadline) => {
+                    return failure(
+                        "timeout",
+                        format!(
+                            "The frontend did not return a result for {} within {} seconds",
+                            tool_name,
+                            timeout.as_secs()
+                        ),
+                    );
+                }
+                _ = frontend_gone.cancelled() => return gone(),
+            }
+        }
+    }
+}
+
+fn frontend_tool_timeout() -> Duration {
+    Duration::from_secs(
+        Config::global()
+            .get_param::<u64>(FRONTEND_TOOL_TIMEOUT_KEY)
+            .unwrap_or(DEFAULT_FRONTEND_TOOL_TIMEOUT_SECS),
+    )
+}
+
+/// Tells the model and the user why a tool that needed confirmation did not run
+fn confirmation_timeout_response(tool_name: &str, timeout: ConfirmationTimeout) -> String {
+    match timeout.action {
+        ConfirmationTimeoutAction::Deny => format!(
+            "Nobody confirmed the call to {} within {} seconds, so it was denied. \
+            DO NOT attempt to call this tool again. \
+            If there are no alternative methods to proceed, clearly explain the situation and STOP.",
+            tool_name, timeout.seconds
+        ),
+        ConfirmationTimeoutAction::Cancel => format!(
+            "Nobody confirmed the call to {} within {} seconds, so it was cancelled without running. \
+            Continue with what you can do without it, you may ask for it again later.",
+            tool_name, timeout.seconds
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_frontend_tool_result_fails_fast() {
+        let agent = Agent::new();
+        let frontend_gone = agent.frontend_gone.lock().await.clone();
+
+        agent
+            .handle_tool_result("stale".to_string(), Ok(vec![]))
+            .await;
+        agent
+            .handle_tool_result("call_1".to_string(), Ok(vec![Content::text("done")]))
+            .await;
+        let result = agent
+            .wait_for_frontend_tool_result(
+                "call_1",
+                "pick_file",
+                &frontend_gone,
+                Duration::from_secs(5),
+            )
+            .await;
+        assert_eq!(result.unwrap().len(), 1);
+
+        let error = agent
+            .wait_for_frontend_tool_result(
+                "call_2",
+                "pick_file",
+                &frontend_gone,
+                Duration::from_millis(20),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.data.unwrap()["reason"], "timeout");
+
+        agent.detach_frontend().await;
+        let error = agent
+            .wait_for_frontend_tool_result(
+                "call_3",
+                "pick_file",
+                &frontend_gone,
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.data.unwrap()["reason"], "frontend_gone");
+
+        agent.attach_frontend().await;
+        assert!(!agent.frontend_gone.lock().await.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_confirmation_times_out() {
+        let agent = Agent::new();
+        let dir = tempfile::tempdir().unwrap();
+        let mut permission_manager = PermissionManager::new(dir.path().join("permission.yaml"));
+        let requests = vec![ToolRequest {
+            id: "call_1".to_string(),
+            tool_call: Ok(mcp_core::tool::ToolCall::new(
+                "developer__shell",
+                json!({"command": "ls"}),
+            )),
+        }];
+        let message_tool_response = Arc::new(Mutex::new(Message::user()));
+
+        let yielded: Vec<_> = agent
+            .handle_approval_tool_requests(
+                &requests,
+                Arc::new(Mutex::new(Vec::new())),
+                &mut permission_manager,
+                message_tool_response.clone(),
+                None,
+                Some(ConfirmationTimeout {
+                    seconds: 0,
+                    action: ConfirmationTimeoutAction::Cancel,
+                }),
+            )
+            .collect()
+            .await;
+        assert_eq!(yielded.len(), 1);
+
+        let response = message_tool_response.lock().await.content[0]
+            .as_tool_response_text()
+            .unwrap();
+        assert!(
+            response.contains("cancelled without running"),
+            "{}",
+            response
+        );
+    }
 }
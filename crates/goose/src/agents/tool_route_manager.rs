@@ -3,35 +3,76 @@ use crate::agents::router_tool_selector::{create_tool_selector, RouterToolSelect
 use crate::agents::router_tools::{self};
 use crate::agents::tool_execution::ToolCallResult;
 use crate::agents::tool_router_index_manager::ToolRouterIndexManager;
+use crate::agents::tool_usage_stats::ToolUsageStats;
 use crate::config::Config;
 use crate::conversation::message::ToolRequest;
 use crate::providers::base::Provider;
 use anyhow::{anyhow, Result};
-use rmcp::model::{ErrorCode, ErrorData, Tool};
+use chrono::Utc;
+use rmcp::model::{Content, ErrorCode, ErrorData, Tool};
 use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::error;
 
+/// How much tool usage weighs against the selector's own ranking
+const USAGE_RANKING_WEIGHT: f64 = 0.5;
+
+/// How long tool uses are batched before they are written to disk
+const TOOL_USAGE_FLUSH_DELAY: Duration = Duration::from_secs(5);
+
 pub struct ToolRouteManager {
     router_tool_selector: Mutex<Option<Arc<Box<dyn RouterToolSelector>>>>,
     router_disabled_override: Mutex<bool>,
+    tool_usage: Arc<Mutex<ToolUsageStats>>,
+    /// Uses recorded since the stats were last written
+    unsaved_tool_usage: Arc<Mutex<ToolUsageStats>>,
+    tool_usage_path: Option<PathBuf>,
+    flush_scheduled: Arc<AtomicBool>,
+    /// Working dir of the session, the project tool uses are recorded for
+    project: Mutex<Option<String>>,
 }
 
 impl ToolRouteManager {
     pub fn new() -> Self {
+        Self::with_tool_usage_path(ToolUsageStats::path().ok())
+    }
+
+    fn with_tool_usage_path(tool_usage_path: Option<PathBuf>) -> Self {
+        let tool_usage = tool_usage_path
+            .as_deref()
+            .map(ToolUsageStats::load_from)
+            .unwrap_or_default();
         Self {
             router_tool_selector: Mutex::new(None),
             router_disabled_override: Mutex::new(false),
+            tool_usage: Arc::new(Mutex::new(tool_usage)),
+            unsaved_tool_usage: Arc::new(Mutex::new(ToolUsageStats::default())),
+            tool_usage_path,
+            flush_scheduled: Arc::new(AtomicBool::new(false)),
+            project: Mutex::new(None),
         }
     }
 
+    pub async fn set_project(&self, working_dir: &Path) {
+        *self.project.lock().await = Some(working_dir.display().to_string());
+    }
+
+    async fn project(&self) -> Option<String> {
+        self.project.lock().await.clone()
+    }
+
     pub async fn disable_router_for_recipe(&self) {
         *self.router_disabled_override.lock().await = true;
         *self.router_tool_selector.lock().await = None;
     }
 
     pub async fn record_tool_requests(&self, requests: &[ToolRequest]) {
+        self.record_tool_usage(requests).await;
+
         let selector = self.router_tool_selector.lock().await.clone();
         if let Some(selector) = selector {
             for request in requests {
@@ -44,6 +85,72 @@ impl ToolRouteManager {
         }
     }
 
+    async fn record_tool_usage(&self, requests: &[ToolRequest]) {
+        let tool_names: Vec<&str> = requests
+            .iter()
+            .filter_map(|request| request.tool_call.as_ref().ok())
+            .map(|tool_call| tool_call.name.as_str())
+            .collect();
+        if tool_names.is_empty() {
+            return;
+        }
+
+        let project = self.project().await;
+        let now = Utc::now();
+        {
+            let mut tool_usage = self.tool_usage.lock().await;
+            let mut unsaved = self.unsaved_tool_usage.lock().await;
+            for tool_name in tool_names {
+                tool_usage.record(tool_name, project.as_deref(), now);
+                unsaved.record(tool_name, project.as_deref(), now);
+            }
+        }
+        self.schedule_tool_usage_flush();
+    }
+
+    /// Write the recorded uses after a delay, so a burst of tool calls is a single write
+    fn schedule_tool_usage_flush(&self) {
+        let Some(path) = self.tool_usage_path.clone() else {
+            return;
+        };
+        if self.flush_scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let tool_usage = self.tool_usage.clone();
+        let unsaved = self.unsaved_tool_usage.clone();
+        let flush_scheduled = self.flush_scheduled.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(TOOL_USAGE_FLUSH_DELAY).await;
+            flush_scheduled.store(false, Ordering::SeqCst);
+            flush_tool_usage(path, tool_usage, unsaved).await;
+        });
+    }
+
+    /// Reorder selected tools so the ones used often and recently in this session and
+    /// project come first, while keeping the selector's order as the main signal
+    async fn rank_by_usage(&self, tools: Vec<Content>) -> Vec<Content> {
+        let project = self.project().await;
+        let now = Utc::now();
+        let tool_usage = self.tool_usage.lock().await;
+        let count = tools.len().max(1) as f64;
+        let mut ranked: Vec<(f64, Content)> = tools
+            .into_iter()
+            .enumerate()
+            .map(|(position, content)| {
+                let usage = content
+                    .as_text()
+                    .and_then(|text| text.text.lines().next())
+                    .and_then(|line| line.strip_prefix("Tool: "))
+                    .map(|name| tool_usage.score(name.trim(), project.as_deref(), now))
+                    .unwrap_or(0.0);
+                let relevance = 1.0 - position as f64 / count;
+                (relevance + USAGE_RANKING_WEIGHT * usage, content)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked.into_iter().map(|(_, content)| content).collect()
+    }
+
     pub async fn dispatch_route_search_tool(
         &self,
         arguments: Value,
@@ -51,7 +158,7 @@ impl ToolRouteManager {
         let selector = self.router_tool_selector.lock().await.clone();
         match selector.as_ref() {
             Some(selector) => match selector.select_tools(arguments).await {
-                Ok(tools) => Ok(ToolCallResult::from(Ok(tools))),
+                Ok(tools) => Ok(ToolCallResult::from(Ok(self.rank_by_usage(tools).await))),
                 Err(e) => Err(ErrorData::new(
                     ErrorCode::INTERNAL_ERROR,
                     format!("Failed to select tools: {}", e),
@@ -173,6 +280,156 @@ impl ToolRouteManager {
             }
         }
 
+        // Most used tools first after the search tool
+        let project = self.project().await;
+        let now = Utc::now();
+        let tool_usage = self.tool_usage.lock().await;
+        prefixed_tools[1..].sort_by(|a, b| {
+            let score = |tool: &Tool| tool_usage.score(&tool.name, project.as_deref(), now);
+            score(b).total_cmp(&score(a))
+        });
+
         prefixed_tools
     }
 }
+
+/// Add the uses recorded since the last write to the stats on disk, off the async runtime,
+/// and refresh the in-memory stats with what other sessions saved
+async fn flush_tool_usage(
+    path: PathBuf,
+    tool_usage: Arc<Mutex<ToolUsageStats>>,
+    unsaved: Arc<Mutex<ToolUsageStats>>,
+) {
+    let batch = std::mem::take(&mut *unsaved.lock().await);
+    if batch.is_empty() {
+        return;
+    }
+    let result = tokio::task::spawn_blocking(move || {
+        ToolUsageStats::add_to_file(&path, &batch).map_err(|e| (e, batch))
+    })
+    .await;
+    match result {
+        Ok(Ok(saved)) => {
+            let mut tool_usage = tool_usage.lock().await;
+            let unsaved = unsaved.lock().await;
+            tool_usage.global = saved.global;
+            tool_usage.projects = saved.projects;
+            tool_usage.merge(&unsaved);
+        }
+        Ok(Err((e, batch))) => {
+            error!("Failed to save tool usage stats: {}", e);
+            // Keep the uses for the next attempt
+            unsaved.lock().await.merge(&batch);
+        }
+        Err(e) => error!("Failed to save tool usage stats: {}", e),
+    }
+}
+
+impl Drop for ToolRouteManager {
+    fn drop(&mut self) {
+        let Some(path) = &self.tool_usage_path else {
+            return;
+        };
+        let Ok(mut unsaved) = self.unsaved_tool_usage.try_lock() else {
+            return;
+        };
+        let batch = std::mem::take(&mut *unsaved);
+        if !batch.is_empty() {
+            if let Err(e) = ToolUsageStats::add_to_file(path, &batch) {
+                error!("Failed to save tool usage stats: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::ToolCall;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_rank_by_usage_moves_used_tools_up() {
+        let manager = ToolRouteManager::with_tool_usage_path(None);
+        manager.set_project(Path::new("/repo")).await;
+        for _ in 0..5 {
+            manager.tool_usage.lock().await.record(
+                "developer__text_editor",
+                Some("/repo"),
+                Utc::now(),
+            );
+        }
+
+        let tools = vec![
+            Content::text("Tool: developer__shell\nDescription: Run a command"),
+            Content::text("Tool: developer__list_windows\nDescription: List windows"),
+            Content::text("Tool: developer__text_editor\nDescription: Edit files"),
+        ];
+        let ranked = manager.rank_by_usage(tools).await;
+        let names: Vec<String> = ranked
+            .iter()
+            .map(|content| {
+                content
+                    .as_text()
+                    .unwrap()
+                    .text
+                    .lines()
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Tool: developer__shell",
+                "Tool: developer__text_editor",
+                "Tool: developer__list_windows"
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tool_uses_are_written_in_batches() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tool_usage.json");
+        let manager = ToolRouteManager::with_tool_usage_path(Some(path.clone()));
+        manager.set_project(Path::new("/repo")).await;
+
+        let request = |id: &str| ToolRequest {
+            id: id.to_string(),
+            tool_call: Ok(ToolCall::new("developer__shell", json!({}))),
+        };
+        manager.record_tool_requests(&[request("1")]).await;
+        manager.record_tool_requests(&[request("2")]).await;
+        assert!(!path.exists());
+
+        // Another session saves its uses in the meantime
+        let mut other = ToolUsageStats::default();
+        other.record("developer__shell", None, Utc::now());
+        other.save_to(&path).unwrap();
+
+        tokio::time::sleep(TOOL_USAGE_FLUSH_DELAY * 2).await;
+        // The write itself happens on a blocking thread, the paused clock waits for it
+        for _ in 0..500 {
+            if manager.tool_usage.lock().await.global["developer__shell"].count == 3 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            manager.tool_usage.lock().await.global["developer__shell"].count,
+            3
+        );
+        let saved = ToolUsageStats::load_from(&path);
+        assert_eq!(saved.global["developer__shell"].count, 3);
+        assert_eq!(saved.projects["/repo"]["developer__shell"].count, 2);
+
+        // Uses not written yet are saved when the manager goes away
+        manager.record_tool_requests(&[request("3")]).await;
+        drop(manager);
+        let saved = ToolUsageStats::load_from(&path);
+        assert_eq!(saved.global["developer__shell"].count, 4);
+    }
+}
@@ -6,11 +6,13 @@ use crate::agents::extension_manager::ExtensionManager;
 use crate::agents::platform_tools;
 use crate::agents::router_tool_selector::RouterToolSelector;
 
-/// Manages tool indexing operations for the router when LLM routing is enabled
+/// Manages tool indexing operations for the router when routing is enabled, for both the
+/// LLM and the vector selectors
 pub struct ToolRouterIndexManager;
 
 impl ToolRouterIndexManager {
-    /// Updates the LLM index for tools when extensions are added or removed
+    /// Updates the router index for tools when extensions are added or removed, the
+    /// vector selector only embeds tools it has not seen before
     pub async fn update_extension_tools(
         selector: &Arc<Box<dyn RouterToolSelector>>,
         extension_manager: &ExtensionManager,
@@ -91,6 +93,9 @@ impl ToolRouterIndexManager {
             tools.push(platform_tools::read_resource_tool());
             tools.push(platform_tools::list_resources_tool());
         }
+        if extension_manager.supports_resource_subscriptions().await {
+            tools.push(platform_tools::subscribe_resource_tool());
+        }
 
         // Index all platform tools at once
         selector
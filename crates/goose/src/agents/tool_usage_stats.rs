@@ -0,0 +1,241 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use etcetera::{choose_app_strategy, AppStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Days after which the recency of a tool use counts half
+const RECENCY_HALF_LIFE_DAYS: f64 = 7.0;
+
+/// How much each scope weighs in the usage score, the current session counts the most
+const SESSION_WEIGHT: f64 = 0.5;
+const PROJECT_WEIGHT: f64 = 0.3;
+const GLOBAL_WEIGHT: f64 = 0.2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolUsage {
+    pub count: u64,
+    pub last_used: DateTime<Utc>,
+}
+
+impl ToolUsage {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            count: 0,
+            last_used: now,
+        }
+    }
+
+    fn merge(&mut self, other: &ToolUsage) {
+        self.count += other.count;
+        self.last_used = self.last_used.max(other.last_used);
+    }
+
+    /// Between 0 and 1, higher for tools used often and recently
+    pub fn score(&self, now: DateTime<Utc>) -> f64 {
+        let frequency = 1.0 - 1.0 / (1.0 + self.count as f64);
+        let age_days = (now - self.last_used).num_seconds().max(0) as f64 / 86_400.0;
+        frequency * 0.5_f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+    }
+}
+
+/// How often and how recently tools were called, overall, per project directory and in the
+/// current session. The session stats are only kept in memory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolUsageStats {
+    #[serde(default)]
+    pub global: HashMap<String, ToolUsage>,
+    /// Usage by project directory
+    #[serde(default)]
+    pub projects: HashMap<String, HashMap<String, ToolUsage>>,
+    #[serde(skip)]
+    pub session: HashMap<String, ToolUsage>,
+}
+
+impl ToolUsageStats {
+    /// Where the stats are persisted, in the data dir
+    pub fn path() -> Result<PathBuf> {
+        Ok(
+            choose_app_strategy(crate::config::APP_STRATEGY.clone())?
+                .in_data_dir("tool_usage.json"),
+        )
+    }
+
+    /// Load the stats persisted at `path`, empty stats if there are none yet
+    pub fn load_from(path: &Path) -> Self {
+        Self::try_load_from(path).unwrap_or_default()
+    }
+
+    /// Like `load_from`, but a file that can't be read or parsed is an error rather than
+    /// empty stats, so it doesn't get overwritten
+    pub fn try_load_from(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the stats through a temporary file, so a crash never leaves a partial file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_string(self)?)?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Add a batch of uses to the stats persisted at `path`, keeping what other sessions
+    /// wrote in the meantime. Returns the stats as saved
+    pub fn add_to_file(path: &Path, batch: &ToolUsageStats) -> Result<Self> {
+        let mut stats = Self::try_load_from(path)?;
+        stats.merge(batch);
+        stats.save_to(path)?;
+        Ok(stats)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.projects.is_empty()
+    }
+
+    /// Add the global and project uses of `other`, the session stats are left alone
+    pub fn merge(&mut self, other: &ToolUsageStats) {
+        fn merge_scope(into: &mut HashMap<String, ToolUsage>, from: &HashMap<String, ToolUsage>) {
+            for (tool_name, usage) in from {
+                into.entry(tool_name.clone())
+                    .and_modify(|existing| existing.merge(usage))
+                    .or_insert_with(|| usage.clone());
+            }
+        }
+        merge_scope(&mut self.global, &other.global);
+        for (project, usage) in &other.projects {
+            merge_scope(self.projects.entry(project.clone()).or_default(), usage);
+        }
+    }
+
+    pub fn record(&mut self, tool_name: &str, project: Option<&str>, now: DateTime<Utc>) {
+        let mut scopes = vec![&mut self.global, &mut self.session];
+        let project_usage =
+            project.map(|project| self.projects.entry(project.to_string()).or_default());
+        scopes.extend(project_usage);
+        for usage in scopes {
+            let usage = usage
+                .entry(tool_name.to_string())
+                .or_insert_with(|| ToolUsage::new(now));
+            usage.count += 1;
+            usage.last_used = now;
+        }
+    }
+
+    /// Usage score of a tool blending the session, project and global stats, between 0 and 1
+    pub fn score(&self, tool_name: &str, project: Option<&str>, now: DateTime<Utc>) -> f64 {
+        let score_in = |usage: Option<&HashMap<String, ToolUsage>>| {
+            usage
+                .and_then(|usage| usage.get(tool_name))
+                .map(|usage| usage.score(now))
+                .unwrap_or(0.0)
+        };
+        SESSION_WEIGHT * score_in(Some(&self.session))
+            + PROJECT_WEIGHT * score_in(project.and_then(|project| self.projects.get(project)))
+            + GLOBAL_WEIGHT * score_in(Some(&self.global))
+    }
+
+    /// Tools of a scope sorted by the number of uses, most used first
+    pub fn most_used(usage: &HashMap<String, ToolUsage>) -> Vec<(&String, &ToolUsage)> {
+        let mut sorted: Vec<_> = usage.iter().collect();
+        sorted.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(b.0)));
+        sorted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_score() {
+        let now = Utc::now();
+        let mut stats = ToolUsageStats::default();
+        for _ in 0..3 {
+            stats.record("developer__shell", Some("/repo"), now);
+        }
+        stats.record("developer__text_editor", None, now - Duration::days(30));
+
+        assert_eq!(stats.global["developer__shell"].count, 3);
+        assert_eq!(stats.projects["/repo"]["developer__shell"].count, 3);
+        assert!(!stats.projects["/repo"].contains_key("developer__text_editor"));
+
+        let shell = stats.score("developer__shell", Some("/repo"), now);
+        let editor = stats.score("developer__text_editor", Some("/repo"), now);
+        assert!(shell > editor);
+        assert!(shell <= 1.0);
+        assert_eq!(stats.score("memory__remember", Some("/repo"), now), 0.0);
+    }
+
+    #[test]
+    fn test_recent_use_scores_higher() {
+        let now = Utc::now();
+        let usage = |days: i64| ToolUsage {
+            count: 5,
+            last_used: now - Duration::days(days),
+        };
+        assert!(usage(0).score(now) > usage(7).score(now));
+        assert!((usage(7).score(now) * 2.0 - usage(0).score(now)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_session_stats_are_not_persisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tool_usage.json");
+        let mut stats = ToolUsageStats::default();
+        stats.record("developer__shell", Some("/repo"), Utc::now());
+        stats.save_to(&path).unwrap();
+
+        let loaded = ToolUsageStats::load_from(&path);
+        assert_eq!(loaded.global["developer__shell"].count, 1);
+        assert!(loaded.session.is_empty());
+        assert_eq!(ToolUsageStats::most_used(&loaded.global).len(), 1);
+    }
+
+    #[test]
+    fn test_add_to_file_merges_with_saved_stats() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tool_usage.json");
+        let now = Utc::now();
+
+        // Another session saved its uses first
+        let mut other = ToolUsageStats::default();
+        other.record("developer__shell", Some("/repo"), now - Duration::days(1));
+        other.save_to(&path).unwrap();
+
+        let mut batch = ToolUsageStats::default();
+        batch.record("developer__shell", Some("/repo"), now);
+        batch.record("memory__remember", None, now);
+        let saved = ToolUsageStats::add_to_file(&path, &batch).unwrap();
+        assert_eq!(saved.global["developer__shell"].count, 2);
+        assert_eq!(saved.global["developer__shell"].last_used, now);
+        assert_eq!(saved.projects["/repo"]["developer__shell"].count, 2);
+        assert_eq!(saved.global["memory__remember"].count, 1);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let loaded = ToolUsageStats::load_from(&path);
+        assert_eq!(loaded.global["developer__shell"].count, 2);
+    }
+
+    #[test]
+    fn test_unreadable_stats_are_not_overwritten() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tool_usage.json");
+        std::fs::write(&path, "{not json").unwrap();
+
+        let mut batch = ToolUsageStats::default();
+        batch.record("developer__shell", None, Utc::now());
+        assert!(ToolUsageStats::add_to_file(&path, &batch).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{not json");
+    }
+}
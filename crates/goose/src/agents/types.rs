@@ -55,10 +55,33 @@ impl RetryConfig {
             }
         }
 
+        if self
+            .checks
+            .iter()
+            .any(|check| check.max_attempts() == Some(0))
+        {
+            return Err("max_attempts of a check must be greater than 0 if specified".to_string());
+        }
+
         Ok(())
     }
 }
 
+/// How the agent's last reply ended, so headless callers can report more than success
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyOutcome {
+    /// The agent finished its turn
+    #[default]
+    Completed,
+    /// The recipe's success checks still failed after all retry attempts
+    SuccessChecksFailed,
+    /// The reply stopped at the maximum number of turns
+    MaxTurnsReached,
+    /// The provider returned an error the agent could not recover from
+    ProviderError,
+}
+
 /// A single success check to validate recipe completion
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "type")]
@@ -68,9 +91,40 @@ pub enum SuccessCheck {
     Shell {
         /// The shell command to execute
         command: String,
+        /// Attempts allowed while this check fails, max_retries applies when not set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_attempts: Option<u32>,
+    },
+    /// Validate the final output, or the last assistant message, against a JSON schema
+    #[serde(alias = "json_schema")]
+    JsonSchema {
+        /// The JSON schema the output must match
+        schema: serde_json::Value,
+        /// Attempts allowed while this check fails, max_retries applies when not set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_attempts: Option<u32>,
+    },
+    /// Ask the model whether the output meets the criteria in the prompt
+    #[serde(alias = "llm_judge")]
+    LlmJudge {
+        /// The criteria the output is judged against
+        prompt: String,
+        /// Attempts allowed while this check fails, max_retries applies when not set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_attempts: Option<u32>,
     },
 }
 
+impl SuccessCheck {
+    pub fn max_attempts(&self) -> Option<u32> {
+        match self {
+            SuccessCheck::Shell { max_attempts, .. }
+            | SuccessCheck::JsonSchema { max_attempts, .. }
+            | SuccessCheck::LlmJudge { max_attempts, .. } => *max_attempts,
+        }
+    }
+}
+
 /// A frontend tool that will be executed by the frontend rather than an extension
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrontendTool {
@@ -78,6 +132,60 @@ pub struct FrontendTool {
     pub tool: Tool,
 }
 
+/// The extensions started, restarted and stopped when reloading them from the config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtensionReload {
+    pub started: Vec<String>,
+    pub restarted: Vec<String>,
+    pub stopped: Vec<String>,
+    /// Extensions that could not be started, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Seconds to wait for the user to confirm a tool call in approve mode
+pub const CONFIRMATION_TIMEOUT_KEY: &str = "GOOSE_TOOL_CONFIRMATION_TIMEOUT";
+/// What happens to the tool call when the confirmation times out: `deny` or `cancel`
+pub const CONFIRMATION_TIMEOUT_ACTION_KEY: &str = "GOOSE_TOOL_CONFIRMATION_TIMEOUT_ACTION";
+
+/// What happens to a tool call nobody confirmed in time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmationTimeoutAction {
+    /// Treated as declined, the model is told not to call the tool again
+    #[default]
+    Deny,
+    /// Not run, the model may ask for it again later
+    Cancel,
+}
+
+/// How long a tool confirmation may go unanswered, so headless runs in approve mode can't hang
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ConfirmationTimeout {
+    pub seconds: u64,
+    #[serde(default)]
+    pub action: ConfirmationTimeoutAction,
+}
+
+impl ConfirmationTimeout {
+    /// The session's timeout, or the configured one. None waits for an answer forever
+    pub fn resolve(session: Option<&SessionConfig>) -> Option<Self> {
+        if let Some(timeout) = session.and_then(|s| s.confirmation_timeout) {
+            return Some(timeout);
+        }
+        let config = crate::config::Config::global();
+        let seconds = config.get_param::<u64>(CONFIRMATION_TIMEOUT_KEY).ok()?;
+        let action = match config
+            .get_param::<String>(CONFIRMATION_TIMEOUT_ACTION_KEY)
+            .unwrap_or_default()
+            .as_str()
+        {
+            "cancel" => ConfirmationTimeoutAction::Cancel,
+            _ => ConfirmationTimeoutAction::Deny,
+        };
+        Some(Self { seconds, action })
+    }
+}
+
 /// Session configuration for an agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionConfig {
@@ -94,4 +202,7 @@ pub struct SessionConfig {
     /// Retry configuration for automated validation and recovery
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retry_config: Option<RetryConfig>,
+    /// Overrides the configured tool confirmation timeout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_timeout: Option<ConfirmationTimeout>,
 }
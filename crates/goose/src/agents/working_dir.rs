@@ -0,0 +1,215 @@
+//! Switching the working directory of a session while it runs, from the `/cd` command of a
+//! frontend or the `set_working_dir` tool of the developer extension, and the roots of the
+//! workspace around it.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use mcp_core::ToolResult;
+use rmcp::model::{Content, ErrorCode, ErrorData};
+use tokio_util::sync::CancellationToken;
+
+use crate::config::ProjectOverlay;
+
+use super::Agent;
+
+/// The tool of the developer extension that changes the working directory
+pub const SET_WORKING_DIR_TOOL_NAME: &str = "developer__set_working_dir";
+
+/// The roots of the workspace, given to extensions started for the session as a list of paths
+pub const WORKSPACE_ROOTS_ENV: &str = "GOOSE_WORKSPACE_ROOTS";
+
+/// The roots shared with extensions for a working directory: the directory itself, the roots
+/// declared for the session and the roots of the project workspace it belongs to
+fn roots_for(working_dir: &Path, declared: &[PathBuf]) -> Vec<PathBuf> {
+    let mut roots = vec![working_dir.to_path_buf()];
+    let project_roots = ProjectOverlay::discover(working_dir)
+        .map(|project| project.workspace_roots())
+        .unwrap_or_default();
+    for root in declared.iter().cloned().chain(project_roots) {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+fn expand_tilde(path: &Path) -> PathBuf {
+    let home = dirs::home_dir();
+    match (path.to_str(), home) {
+        (Some("~"), Some(home)) => home,
+        (Some(path), Some(home)) if path.starts_with("~/") => home.join(&path[2..]),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl Agent {
+    /// The directory the session was switched to, None while it is still in the directory it
+    /// started in
+    pub async fn working_dir(&self) -> Option<PathBuf> {
+        self.working_dir.lock().await.clone()
+    }
+
+    /// Switch the session to another directory, relative paths being resolved against the
+    /// current one and `~` against the home directory. The developer extension runs its shell
+    /// there and applies the `.gooseignore` found there, extensions are told about their new
+    /// roots and the system prompt names the new directory. Returns the directory switched to
+    pub async fn set_working_dir(&self, dir: &Path) -> Result<PathBuf> {
+        let dir = self.resolve_dir(dir).await?;
+
+        let developer_running = self
+            .extension_manager
+            .list_extensions()
+            .await?
+            .iter()
+            .any(|name| name == "developer");
+        if developer_running {
+            let tool_call = mcp_core::tool::ToolCall::new(
+                SET_WORKING_DIR_TOOL_NAME,
+                serde_json::json!({ "path": dir }),
+            );
+            self.extension_manager
+                .dispatch_tool_call(tool_call, CancellationToken::new())
+                .await?
+                .result
+                .await
+                .map_err(|e| anyhow!(e.message))?;
+        }
+
+        *self.working_dir.lock().await = Some(dir.clone());
+        self.prompt_manager
+            .lock()
+            .await
+            .set_working_dir(dir.clone());
+        self.share_roots(&dir).await;
+        Ok(dir)
+    }
+
+    /// Declare more roots of the workspace for the session, such as the folders of a monorepo,
+    /// on top of those in the project config. Relative roots are resolved against the working
+    /// directory. Extensions started from now on get them in `GOOSE_WORKSPACE_ROOTS`, running
+    /// ones are told about their new roots and the system prompt lists them. Returns every root
+    /// of the workspace
+    pub async fn set_workspace_roots(&self, roots: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let mut declared = Vec::new();
+        for root in roots {
+            declared.push(self.resolve_dir(root).await?);
+        }
+        *self.workspace_roots.lock().await = declared;
+
+        let roots = self.share_roots(&self.current_dir().await?).await;
+        let paths = std::env::join_paths(&roots)?;
+        self.extension_manager
+            .set_session_env_var(WORKSPACE_ROOTS_ENV, paths.to_string_lossy().into_owned())
+            .await;
+        Ok(roots)
+    }
+
+    /// Tell the extensions and the model about the roots of the workspace around the working
+    /// directory
+    pub(super) async fn share_roots(&self, working_dir: &Path) -> Vec<PathBuf> {
+        let roots = roots_for(working_dir, &self.workspace_roots.lock().await);
+        self.prompt_manager
+            .lock()
+            .await
+            .set_workspace_roots(roots.clone());
+        self.extension_manager.set_roots(&roots).await;
+        roots
+    }
+
+    async fn current_dir(&self) -> Result<PathBuf> {
+        match self.working_dir().await {
+            Some(current) => Ok(current),
+            None => Ok(std::env::current_dir()?),
+        }
+    }
+
+    /// An existing directory, relative to the working directory of the session
+    async fn resolve_dir(&self, dir: &Path) -> Result<PathBuf> {
+        let resolved = self
+            .current_dir()
+            .await?
+            .join(expand_tilde(dir))
+            .canonicalize()
+            .map_err(|e| anyhow!("Cannot find the directory {}: {}", dir.display(), e))?;
+        if !resolved.is_dir() {
+            bail!("{} is not a directory", resolved.display());
+        }
+        Ok(resolved)
+    }
+
+    /// Handle the developer extension's `set_working_dir` tool for the whole session rather
+    /// than the extension alone
+    pub(super) async fn handle_set_working_dir(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> ToolResult<Vec<Content>> {
+        let path = arguments
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    "Missing 'path' parameter".to_string(),
+                    None,
+                )
+            })?;
+        let dir = self
+            .set_working_dir(Path::new(path))
+            .await
+            .map_err(|e| ErrorData::new(ErrorCode::INVALID_PARAMS, e.to_string(), None))?;
+        Ok(vec![Content::text(format!(
+            "The working directory is now {}",
+            dir.display()
+        ))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_set_working_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("service")).unwrap();
+        let agent = Agent::new();
+
+        let switched = agent.set_working_dir(dir.path()).await.unwrap();
+        assert_eq!(switched, dir.path().canonicalize().unwrap());
+
+        // Relative to the directory switched to, not the process
+        let switched = agent.set_working_dir(Path::new("service")).await.unwrap();
+        assert_eq!(switched, dir.path().join("service").canonicalize().unwrap());
+        assert_eq!(agent.working_dir().await, Some(switched.clone()));
+
+        assert!(agent
+            .set_working_dir(Path::new("no-such-dir"))
+            .await
+            .is_err());
+        assert_eq!(agent.working_dir().await, Some(switched));
+    }
+
+    #[tokio::test]
+    async fn test_set_workspace_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in ["frontend", "backend"] {
+            std::fs::create_dir(dir.path().join(folder)).unwrap();
+        }
+        let agent = Agent::new();
+        let root = agent.set_working_dir(dir.path()).await.unwrap();
+
+        let roots = agent
+            .set_workspace_roots(&[PathBuf::from("frontend"), PathBuf::from("backend")])
+            .await
+            .unwrap();
+        assert_eq!(
+            roots,
+            vec![root.clone(), root.join("frontend"), root.join("backend")]
+        );
+        assert!(agent
+            .set_workspace_roots(&[PathBuf::from("no-such-dir")])
+            .await
+            .is_err());
+    }
+}
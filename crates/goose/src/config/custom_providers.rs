@@ -1,14 +1,25 @@
 use crate::config::{Config, APP_STRATEGY};
 use crate::model::ModelConfig;
-use crate::providers::anthropic::AnthropicProvider;
+use crate::providers::anthropic::{AnthropicProvider, ANTHROPIC_API_VERSION};
+use crate::providers::api_client::{ApiClient, AuthMethod};
 use crate::providers::base::ModelInfo;
 use crate::providers::ollama::OllamaProvider;
 use crate::providers::openai::OpenAiProvider;
 use anyhow::Result;
 use etcetera::{choose_app_strategy, AppStrategy};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use utoipa::ToSchema;
+
+/// How long a fetched model list is used before it is fetched again
+const MODELS_REFRESH_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const MODELS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Context limit given to fetched models, as model list endpoints rarely report one
+const DEFAULT_CONTEXT_LIMIT: usize = 128_000;
 
 pub fn custom_providers_dir() -> std::path::PathBuf {
     choose_app_strategy(APP_STRATEGY.clone())
@@ -25,6 +36,33 @@ pub enum ProviderEngine {
     Anthropic,
 }
 
+/// How the API key is sent. Without one each engine uses its usual scheme: `Authorization: Bearer`
+/// for OpenAI, `x-api-key` for Anthropic and no key for Ollama
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CustomProviderAuth {
+    /// A header holding the key. `format` places the key in the value, e.g. `Token {key}`
+    Header {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+    },
+    /// A query parameter holding the key
+    Query { param: String },
+    /// The API takes no key
+    None,
+}
+
+/// The optional parts of a custom provider, for creating one
+#[derive(Debug, Clone, Default)]
+pub struct CustomProviderOptions {
+    pub headers: Option<HashMap<String, String>>,
+    pub auth: Option<CustomProviderAuth>,
+    pub model_params: HashMap<String, Value>,
+    pub supports_streaming: Option<bool>,
+    pub models_endpoint: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CustomProviderConfig {
     pub name: String,
@@ -37,6 +75,18 @@ pub struct CustomProviderConfig {
     pub headers: Option<HashMap<String, String>>,
     pub timeout_seconds: Option<u64>,
     pub supports_streaming: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<CustomProviderAuth>,
+    /// Request parameters sent by default with each model, e.g. `{"gpt-x": {"top_p": 0.9}}`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_params: HashMap<String, Value>,
+    /// Where the model list is fetched from: a URL, or a path on the base URL's host such as
+    /// `/v1/models`. The models listed in the config are kept when fetching fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models_endpoint: Option<String>,
+    /// When the model list was last fetched, in seconds since the epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models_refreshed_at: Option<u64>,
 }
 
 impl CustomProviderConfig {
@@ -52,6 +102,138 @@ impl CustomProviderConfig {
         &self.models
     }
 
+    /// How to authenticate, reading the key from the keyring unless the auth scheme takes none.
+    /// `default` builds the engine's usual auth from the key
+    pub(crate) fn auth_method(
+        &self,
+        default: impl FnOnce(String) -> AuthMethod,
+    ) -> Result<AuthMethod> {
+        if self.auth == Some(CustomProviderAuth::None) {
+            return Ok(AuthMethod::None);
+        }
+        let api_key: String = Config::global()
+            .get_secret(&self.api_key_env)
+            .map_err(|_| anyhow::anyhow!("Missing API key: {}", self.api_key_env))?;
+        Ok(match &self.auth {
+            Some(CustomProviderAuth::Header { name, format }) => AuthMethod::ApiKey {
+                header_name: name.clone(),
+                key: match format {
+                    Some(format) => format.replace("{key}", &api_key),
+                    None => api_key,
+                },
+            },
+            Some(CustomProviderAuth::Query { param }) => AuthMethod::QueryParam {
+                name: param.clone(),
+                key: api_key,
+            },
+            _ => default(api_key),
+        })
+    }
+
+    /// The engine's auth, with the overrides of `auth_method`
+    fn engine_auth_method(&self) -> Result<AuthMethod> {
+        match self.engine {
+            ProviderEngine::Ollama if self.auth.is_none() => Ok(AuthMethod::None),
+            ProviderEngine::Anthropic => self.auth_method(|key| AuthMethod::ApiKey {
+                header_name: "x-api-key".to_string(),
+                key,
+            }),
+            _ => self.auth_method(AuthMethod::BearerToken),
+        }
+    }
+
+    /// Fetch the names of the models from the models endpoint
+    pub async fn fetch_models(&self) -> Result<Vec<String>> {
+        let endpoint = self
+            .models_endpoint
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("{} has no models endpoint", self.display_name))?;
+        let url = url::Url::parse(endpoint)
+            .or_else(|_| url::Url::parse(&self.base_url)?.join(endpoint))?;
+        let origin = url.origin().ascii_serialization();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path().trim_start_matches('/'), query),
+            None => url.path().trim_start_matches('/').to_string(),
+        };
+
+        let mut api_client = self.apply_headers(ApiClient::with_timeout(
+            origin,
+            self.engine_auth_method()?,
+            MODELS_FETCH_TIMEOUT,
+        )?)?;
+        if matches!(self.engine, ProviderEngine::Anthropic) {
+            api_client = api_client.with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
+        }
+        let response = api_client.response_get(&path).await?.error_for_status()?;
+        let models = parse_model_list(&response.json().await?);
+        if models.is_empty() {
+            return Err(anyhow::anyhow!("{} listed no models", url));
+        }
+        Ok(models)
+    }
+
+    /// Replace the model list with the one from the models endpoint and save it. Models that
+    /// were already listed keep their context limit and costs
+    pub async fn refresh_models(&mut self) -> Result<()> {
+        let names = self.fetch_models().await?;
+        let mut known: HashMap<String, ModelInfo> = self
+            .models
+            .drain(..)
+            .map(|model| (model.name.clone(), model))
+            .collect();
+        self.models = names
+            .into_iter()
+            .map(|name| {
+                known
+                    .remove(&name)
+                    .unwrap_or_else(|| ModelInfo::new(name, DEFAULT_CONTEXT_LIMIT))
+            })
+            .collect();
+        self.models_refreshed_at = Some(now());
+        self.save()
+    }
+
+    fn is_stale(&self) -> bool {
+        self.models_endpoint.is_some()
+            && self
+                .models_refreshed_at
+                .is_none_or(|at| now().saturating_sub(at) >= MODELS_REFRESH_INTERVAL.as_secs())
+    }
+
+    pub fn load(id: &str) -> Result<Self> {
+        let path = custom_providers_dir().join(format!("{}.json", id));
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let custom_providers_dir = custom_providers_dir();
+        std::fs::create_dir_all(&custom_providers_dir)?;
+
+        let json_content = serde_json::to_string_pretty(self)?;
+        let file_path = custom_providers_dir.join(format!("{}.json", self.name));
+        std::fs::write(file_path, json_content)?;
+        Ok(())
+    }
+
+    /// Add the static headers to a client. Set them before any header the engine adds, as this
+    /// replaces the client's headers
+    pub(crate) fn apply_headers(&self, api_client: ApiClient) -> Result<ApiClient> {
+        let Some(headers) = &self.headers else {
+            return Ok(api_client);
+        };
+        let mut header_map = HeaderMap::new();
+        for (key, value) in headers {
+            header_map.insert(
+                HeaderName::from_bytes(key.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+        api_client.with_headers(header_map)
+    }
+
     pub fn generate_id(display_name: &str) -> String {
         format!("custom_{}", display_name.to_lowercase().replace(' ', "_"))
     }
@@ -66,13 +248,26 @@ impl CustomProviderConfig {
         api_url: String,
         api_key: String,
         models: Vec<String>,
-        supports_streaming: Option<bool>,
+        options: CustomProviderOptions,
     ) -> Result<Self> {
         let id = Self::generate_id(&display_name);
         let api_key_name = Self::generate_api_key_name(&id);
 
+        if let Some((model, _)) = options
+            .model_params
+            .iter()
+            .find(|(_, params)| !params.is_object())
+        {
+            return Err(anyhow::anyhow!(
+                "The parameters of {} must be a JSON object",
+                model
+            ));
+        }
+
         let config = Config::global();
-        config.set_secret(&api_key_name, serde_json::Value::String(api_key))?;
+        if options.auth != Some(CustomProviderAuth::None) {
+            config.set_secret(&api_key_name, serde_json::Value::String(api_key))?;
+        }
 
         let model_infos: Vec<ModelInfo> = models
             .into_iter()
@@ -92,19 +287,16 @@ impl CustomProviderConfig {
             api_key_env: api_key_name,
             base_url: api_url,
             models: model_infos,
-            headers: None,
+            headers: options.headers,
             timeout_seconds: None,
-            supports_streaming,
+            supports_streaming: options.supports_streaming,
+            auth: options.auth,
+            model_params: options.model_params,
+            models_endpoint: options.models_endpoint,
+            models_refreshed_at: None,
         };
 
-        // save to JSON file
-        let custom_providers_dir = custom_providers_dir();
-        std::fs::create_dir_all(&custom_providers_dir)?;
-
-        let json_content = serde_json::to_string_pretty(&provider_config)?;
-        let file_path = custom_providers_dir.join(format!("{}.json", id));
-        std::fs::write(file_path, json_content)?;
-
+        provider_config.save()?;
         Ok(provider_config)
     }
 
@@ -124,6 +316,72 @@ impl CustomProviderConfig {
     }
 }
 
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Model names from a model list response: OpenAI and Anthropic style `{"data": [{"id": ...}]}`,
+/// Ollama style `{"models": [{"name": ...}]}`, or a plain array of names or objects
+pub fn parse_model_list(response: &Value) -> Vec<String> {
+    let entries = response
+        .get("data")
+        .or_else(|| response.get("models"))
+        .unwrap_or(response)
+        .as_array();
+    let mut names: Vec<String> = entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .as_str()
+                .or_else(|| entry.get("id").and_then(|v| v.as_str()))
+                .or_else(|| entry.get("name").and_then(|v| v.as_str()))
+        })
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Fetch the model lists of custom providers that have a models endpoint and haven't been
+/// refreshed for a day. Providers whose endpoint fails keep their list
+pub async fn refresh_stale_models() {
+    let Ok(configs) = load_custom_providers(&custom_providers_dir()) else {
+        return;
+    };
+    let mut refreshed = false;
+    for mut config in configs.into_iter().filter(|config| config.is_stale()) {
+        match config.refresh_models().await {
+            Ok(()) => refreshed = true,
+            Err(e) => tracing::warn!(
+                "Failed to refresh the models of {}, keeping its list: {}",
+                config.display_name,
+                e
+            ),
+        }
+    }
+    if refreshed {
+        let _ = crate::providers::refresh_custom_providers();
+    }
+}
+
+/// Add a model's default parameters to a request, keeping any the request already sets
+pub fn apply_model_params(model_params: &HashMap<String, Value>, model: &str, payload: &mut Value) {
+    let (Some(params), Some(payload)) = (
+        model_params.get(model).and_then(|p| p.as_object()),
+        payload.as_object_mut(),
+    ) else {
+        return;
+    };
+    for (key, value) in params {
+        payload.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+}
+
 pub fn load_custom_providers(dir: &Path) -> Result<Vec<CustomProviderConfig>> {
     if !dir.exists() {
         return Ok(Vec::new());
@@ -213,3 +471,61 @@ pub fn register_custom_providers(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_model_params() {
+        let model_params = HashMap::from([(
+            "model-a".to_string(),
+            json!({"top_p": 0.9, "temperature": 0.2}),
+        )]);
+
+        let mut payload = json!({"model": "model-a", "temperature": 0.7});
+        apply_model_params(&model_params, "model-a", &mut payload);
+        assert_eq!(
+            payload,
+            json!({"model": "model-a", "temperature": 0.7, "top_p": 0.9})
+        );
+
+        let mut payload = json!({"model": "model-b"});
+        apply_model_params(&model_params, "model-b", &mut payload);
+        assert_eq!(payload, json!({"model": "model-b"}));
+    }
+
+    #[test]
+    fn test_parse_model_list() {
+        let openai = json!({"object": "list", "data": [{"id": "model-b"}, {"id": "model-a"}]});
+        assert_eq!(parse_model_list(&openai), vec!["model-a", "model-b"]);
+
+        let ollama = json!({"models": [{"name": "qwen3:8b", "size": 5}]});
+        assert_eq!(parse_model_list(&ollama), vec!["qwen3:8b"]);
+
+        assert_eq!(parse_model_list(&json!(["model-a"])), vec!["model-a"]);
+        assert!(parse_model_list(&json!({"error": "unauthorized"})).is_empty());
+    }
+
+    #[test]
+    fn test_auth_config_format() {
+        let auth: CustomProviderAuth =
+            serde_json::from_value(json!({"type": "header", "name": "api-key"})).unwrap();
+        assert_eq!(
+            auth,
+            CustomProviderAuth::Header {
+                name: "api-key".to_string(),
+                format: None
+            }
+        );
+        let auth: CustomProviderAuth =
+            serde_json::from_value(json!({"type": "query", "param": "key"})).unwrap();
+        assert_eq!(
+            auth,
+            CustomProviderAuth::Query {
+                param: "key".to_string()
+            }
+        );
+    }
+}
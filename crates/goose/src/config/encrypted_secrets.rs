@@ -0,0 +1,275 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::base::ConfigError;
+
+/// Passphrase the encrypted secrets file is unlocked with
+pub const SECRETS_PASSPHRASE_ENV_VAR: &str = "GOOSE_SECRETS_PASSPHRASE";
+/// Alternatively a base64 encoded 32 byte key, for machines where a key is provisioned
+pub const SECRETS_KEY_ENV_VAR: &str = "GOOSE_SECRETS_KEY";
+
+const FILE_VERSION: u32 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Derived keys by passphrase and salt
+type DerivedKeyCache = HashMap<(String, Vec<u8>), [u8; KEY_LEN]>;
+
+/// Keys derived from a passphrase and salt, as Argon2id is slow on purpose and the secrets
+/// file is read for every secret looked up
+static DERIVED_KEYS: Lazy<Mutex<DerivedKeyCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// How the key of an encrypted secrets file was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum KeySource {
+    Argon2id,
+    Key,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedSecretsFile {
+    version: u32,
+    key_source: KeySource,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Values encrypted with a key derived from a passphrase, for files that carry their own salt
+/// such as exported configuration archives
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PassphraseSealed {
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn encryption_error(message: impl Into<String>) -> ConfigError {
+    ConfigError::EncryptedSecretsError(message.into())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], ConfigError> {
+    let cache_key = (passphrase.to_string(), salt.to_vec());
+    if let Some(key) = DERIVED_KEYS.lock().unwrap().get(&cache_key) {
+        return Ok(*key);
+    }
+    let mut key = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| encryption_error(e.to_string()))?;
+    DERIVED_KEYS.lock().unwrap().insert(cache_key, key);
+    Ok(key)
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Encrypt the values with a fresh nonce, returns the nonce and the ciphertext
+fn encrypt(
+    key: &[u8; KEY_LEN],
+    values: &HashMap<String, Value>,
+) -> Result<([u8; NONCE_LEN], Vec<u8>), ConfigError> {
+    let nonce = random_bytes::<NONCE_LEN>();
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            serde_json::to_vec(values)?.as_slice(),
+        )
+        .map_err(|_| encryption_error("failed to encrypt secrets"))?;
+    Ok((nonce, ciphertext))
+}
+
+fn decode(field: &str) -> Result<Vec<u8>, ConfigError> {
+    general_purpose::STANDARD
+        .decode(field)
+        .map_err(|e| encryption_error(e.to_string()))
+}
+
+fn decrypt(
+    key: &[u8; KEY_LEN],
+    nonce: &str,
+    ciphertext: &str,
+) -> Result<HashMap<String, Value>, ConfigError> {
+    let nonce = decode(nonce)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(encryption_error("invalid nonce"));
+    }
+    let plaintext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(XNonce::from_slice(&nonce), decode(ciphertext)?.as_slice())
+        .map_err(|_| {
+            encryption_error("could not decrypt the secrets, check the passphrase or key")
+        })?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Encrypt values with a key derived from `passphrase` with Argon2id and a fresh salt
+pub fn seal_with_passphrase(
+    values: &HashMap<String, Value>,
+    passphrase: &str,
+) -> Result<PassphraseSealed, ConfigError> {
+    let salt = random_bytes::<SALT_LEN>();
+    let (nonce, ciphertext) = encrypt(&derive_key(passphrase, &salt)?, values)?;
+    Ok(PassphraseSealed {
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    })
+}
+
+pub fn open_with_passphrase(
+    sealed: &PassphraseSealed,
+    passphrase: &str,
+) -> Result<HashMap<String, Value>, ConfigError> {
+    let key = derive_key(passphrase, &decode(&sealed.salt)?)?;
+    decrypt(&key, &sealed.nonce, &sealed.ciphertext)
+}
+
+/// The key for the file, from GOOSE_SECRETS_KEY or derived from GOOSE_SECRETS_PASSPHRASE
+fn unlock_key(key_source: KeySource, salt: &[u8]) -> Result<[u8; KEY_LEN], ConfigError> {
+    match key_source {
+        KeySource::Key => {
+            let encoded = env::var(SECRETS_KEY_ENV_VAR).map_err(|_| {
+                encryption_error(format!("the secrets file needs {}", SECRETS_KEY_ENV_VAR))
+            })?;
+            let decoded = general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| {
+                    encryption_error(format!("{} is not base64: {}", SECRETS_KEY_ENV_VAR, e))
+                })?;
+            decoded.try_into().map_err(|_| {
+                encryption_error(format!("{} must be {} bytes", SECRETS_KEY_ENV_VAR, KEY_LEN))
+            })
+        }
+        KeySource::Argon2id => {
+            let passphrase = env::var(SECRETS_PASSPHRASE_ENV_VAR).map_err(|_| {
+                encryption_error(format!(
+                    "set {} to unlock the secrets file",
+                    SECRETS_PASSPHRASE_ENV_VAR
+                ))
+            })?;
+            derive_key(&passphrase, salt)
+        }
+    }
+}
+
+/// The key source for new files: a provisioned key wins over a passphrase
+fn preferred_key_source() -> Result<KeySource, ConfigError> {
+    if env::var(SECRETS_KEY_ENV_VAR).is_ok() {
+        Ok(KeySource::Key)
+    } else if env::var(SECRETS_PASSPHRASE_ENV_VAR).is_ok() {
+        Ok(KeySource::Argon2id)
+    } else {
+        Err(encryption_error(format!(
+            "set {} or {} to use encrypted secrets",
+            SECRETS_PASSPHRASE_ENV_VAR, SECRETS_KEY_ENV_VAR
+        )))
+    }
+}
+
+/// Read and decrypt the secrets file, which holds no secrets until it is first written
+pub fn load(path: &Path) -> Result<HashMap<String, Value>, ConfigError> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let file: EncryptedSecretsFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    if file.version > FILE_VERSION {
+        return Err(encryption_error(format!(
+            "{} was written by a newer version of goose",
+            path.display()
+        )));
+    }
+    let key = unlock_key(file.key_source, &decode(&file.salt)?)?;
+    decrypt(&key, &file.nonce, &file.ciphertext)
+}
+
+/// Whether a key or passphrase for the secrets file is set
+pub fn is_configured() -> bool {
+    preferred_key_source().is_ok()
+}
+
+/// The salt of the file if it was written with the same key source, so the derived key
+/// can be reused
+fn existing_salt(path: &Path, key_source: KeySource) -> Option<Vec<u8>> {
+    let file: EncryptedSecretsFile =
+        serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    (file.key_source == key_source)
+        .then(|| decode(&file.salt).ok())
+        .flatten()
+        .filter(|salt| salt.len() == SALT_LEN)
+}
+
+/// Encrypt the secrets with a fresh nonce and write them to the file, readable only by the
+/// current user. The salt of the file is kept, a nonce is never reused
+pub fn save(path: &Path, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+    let key_source = preferred_key_source()?;
+    let salt =
+        existing_salt(path, key_source).unwrap_or_else(|| random_bytes::<SALT_LEN>().to_vec());
+    let key = unlock_key(key_source, &salt)?;
+    let (nonce, ciphertext) = encrypt(&key, values)?;
+
+    let file = EncryptedSecretsFile {
+        version: FILE_VERSION,
+        key_source,
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ConfigError::DirectoryError(e.to_string()))?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&file)?)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_derived_key_is_cached_and_salt_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.enc");
+        let values = HashMap::from([("api_key".to_string(), Value::from("sk-secret"))]);
+
+        temp_env::with_vars(
+            [
+                (SECRETS_PASSPHRASE_ENV_VAR, Some("correct horse")),
+                (SECRETS_KEY_ENV_VAR, None),
+            ],
+            || {
+                save(&path, &values).unwrap();
+                let salt = existing_salt(&path, KeySource::Argon2id).unwrap();
+                assert!(DERIVED_KEYS
+                    .lock()
+                    .unwrap()
+                    .contains_key(&("correct horse".to_string(), salt.clone())));
+
+                save(&path, &values).unwrap();
+                assert_eq!(existing_salt(&path, KeySource::Argon2id).unwrap(), salt);
+                assert_eq!(load(&path).unwrap(), values);
+            },
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use jsonwebtoken::{crypto, Algorithm, DecodingKey};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::base::Config;
+use super::extensions::{ExtensionConfigManager, ExtensionEntry};
+use crate::agents::ExtensionConfig;
+
+/// URL of the registry index. Its signature is read from the same URL with `.sig` appended
+pub const REGISTRY_URL_KEY: &str = "GOOSE_EXTENSION_REGISTRY_URL";
+/// Base64 Ed25519 public key the registry index must be signed with
+pub const REGISTRY_PUBLIC_KEY_KEY: &str = "GOOSE_EXTENSION_REGISTRY_PUBLIC_KEY";
+const DEFAULT_REGISTRY_URL: &str = "https://block.github.io/goose/extensions/registry.json";
+
+const CACHE_FILE_NAME: &str = "extension_registry.json";
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// A cache older than this isn't used, even when the registry can't be fetched
+const CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An environment variable an extension from the registry reads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryEnvKey {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// A known MCP server listed in the registry, with the config to install it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryExtension {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_keys: Vec<RegistryEnvKey>,
+    pub config: ExtensionConfig,
+}
+
+impl RegistryExtension {
+    pub fn required_env_keys(&self) -> impl Iterator<Item = &RegistryEnvKey> {
+        self.env_keys.iter().filter(|key| key.required)
+    }
+
+    /// Store the given environment values as the extension's secrets and add the extension to the config,
+    /// enabled. The extension reads the secrets through its `env_keys`
+    pub fn install(&self, env_values: &[(String, String)]) -> Result<ExtensionConfig> {
+        if let Some(missing) = self
+            .required_env_keys()
+            .find(|key| !env_values.iter().any(|(name, _)| *name == key.name))
+        {
+            return Err(anyhow!("{} needs {}", self.name, missing.name));
+        }
+
+        let extension_key = self.config.key();
+        for (name, value) in env_values {
+            ExtensionConfigManager::set_secret(&extension_key, name, value)?;
+        }
+        let mut extension = self.config.clone();
+        match &mut extension {
+            ExtensionConfig::Sse { env_keys, .. }
+            | ExtensionConfig::Stdio { env_keys, .. }
+            | ExtensionConfig::StreamableHttp { env_keys, .. } => {
+                for (name, _) in env_values {
+                    if !env_keys.contains(name) {
+                        env_keys.push(name.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        ExtensionConfigManager::set(ExtensionEntry {
+            enabled: true,
+            eager: false,
+            sampling: None,
+            config: extension.clone(),
+        })?;
+        Ok(extension)
+    }
+}
+
+/// The curated index of extensions, fetched over HTTPS and only used when its signature checks
+/// out against the configured public key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionRegistry {
+    pub version: u32,
+    pub extensions: Vec<RegistryExtension>,
+}
+
+/// Where the registry comes from and the key it must be signed with
+#[derive(Debug, Clone, PartialEq)]
+struct RegistrySource {
+    url: String,
+    public_key: String,
+}
+
+impl RegistrySource {
+    fn from_config() -> Result<Self> {
+        let config = Config::global();
+        let url = config
+            .get_param::<String>(REGISTRY_URL_KEY)
+            .unwrap_or_else(|_| DEFAULT_REGISTRY_URL.to_string());
+        let public_key: String = config.get_param(REGISTRY_PUBLIC_KEY_KEY).map_err(|_| {
+            anyhow!(
+                "Set {} to the registry's signing key to use the extension registry",
+                REGISTRY_PUBLIC_KEY_KEY
+            )
+        })?;
+        if !url.starts_with("https://") {
+            return Err(anyhow!("The extension registry must be served over HTTPS"));
+        }
+        Ok(Self { url, public_key })
+    }
+}
+
+/// The index as fetched, with its signature, so it is checked again whenever it is read back
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedRegistry {
+    fetched_at: u64,
+    url: String,
+    public_key: String,
+    index: String,
+    signature: String,
+}
+
+impl CachedRegistry {
+    fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+
+    /// The registry, if the cache is for this source and its signature checks out
+    fn verify(&self, source: &RegistrySource) -> Result<ExtensionRegistry> {
+        if self.url != source.url || self.public_key != source.public_key {
+            return Err(anyhow!(
+                "The cached extension registry is for another registry"
+            ));
+        }
+        ExtensionRegistry::from_signed(self.index.as_bytes(), &self.signature, &source.public_key)
+    }
+}
+
+fn get_cache_path() -> Result<PathBuf> {
+    let cache_dir = if let Ok(goose_dir) = std::env::var("GOOSE_CACHE_DIR") {
+        PathBuf::from(goose_dir)
+    } else {
+        dirs::cache_dir()
+            .ok_or_else(|| anyhow!("Could not determine cache directory"))?
+            .join("goose")
+    };
+    Ok(cache_dir.join(CACHE_FILE_NAME))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl ExtensionRegistry {
+    /// Parse an index after checking its detached base64 Ed25519 signature
+    pub fn from_signed(index: &[u8], signature: &str, public_key: &str) -> Result<Self> {
+        let public_key = general_purpose::STANDARD
+            .decode(public_key.trim())
+            .context("The registry public key is not base64")?;
+        let signature = general_purpose::STANDARD
+            .decode(signature.trim())
+            .context("The registry signature is not base64")?;
+        let key =
+            DecodingKey::from_ed_components(&general_purpose::URL_SAFE_NO_PAD.encode(public_key))
+                .context("The registry public key is not an Ed25519 key")?;
+        let valid = crypto::verify(
+            &general_purpose::URL_SAFE_NO_PAD.encode(signature),
+            index,
+            &key,
+            Algorithm::EdDSA,
+        )
+        .unwrap_or(false);
+        if !valid {
+            return Err(anyhow!("The extension registry signature is not valid"));
+        }
+        serde_json::from_slice(index).context("The extension registry index is malformed")
+    }
+
+    async fn fetch(source: &RegistrySource) -> Result<(Self, CachedRegistry)> {
+        let client = reqwest::Client::builder().timeout(FETCH_TIMEOUT).build()?;
+        let index = client
+            .get(&source.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let signature = client
+            .get(format!("{}.sig", source.url))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let registry = Self::from_signed(index.as_bytes(), &signature, &source.public_key)?;
+        let cached = CachedRegistry {
+            fetched_at: now(),
+            url: source.url.clone(),
+            public_key: source.public_key.clone(),
+            index,
+            signature,
+        };
+        Ok((registry, cached))
+    }
+
+    /// The cached registry for the source, checked again and no older than the max age
+    fn load_cached(source: &RegistrySource) -> Option<(Duration, Self)> {
+        let data = std::fs::read(get_cache_path().ok()?).ok()?;
+        let cached: CachedRegistry = serde_json::from_slice(&data).ok()?;
+        if cached.age() > CACHE_MAX_AGE {
+            return None;
+        }
+        match cached.verify(source) {
+            Ok(registry) => Some((cached.age(), registry)),
+            Err(e) => {
+                tracing::warn!("Ignoring the cached extension registry: {}", e);
+                None
+            }
+        }
+    }
+
+    fn save_cached(cached: &CachedRegistry) -> Result<()> {
+        let path = get_cache_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(cached)?)?;
+        Ok(())
+    }
+
+    /// The registry, from the cache while it is fresh. When fetching fails a stale cache is
+    /// used rather than nothing, up to its max age
+    pub async fn load() -> Result<Self> {
+        let source = RegistrySource::from_config()?;
+        let cached = Self::load_cached(&source);
+        if let Some((age, registry)) = &cached {
+            if *age < CACHE_TTL {
+                return Ok(registry.clone());
+            }
+        }
+
+        match Self::fetch(&source).await {
+            Ok((registry, fetched)) => {
+                if let Err(e) = Self::save_cached(&fetched) {
+                    tracing::warn!("Failed to cache the extension registry: {}", e);
+                }
+                Ok(registry)
+            }
+            Err(e) => match cached {
+                Some((_, registry)) => {
+                    tracing::warn!("Using the cached extension registry: {}", e);
+                    Ok(registry)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// The registry from the cache only, never fetching it. For the agent, which shouldn't wait
+    /// on the network while searching extensions
+    pub fn cached() -> Result<Self> {
+        let source = RegistrySource::from_config()?;
+        Self::load_cached(&source)
+            .map(|(_, registry)| registry)
+            .ok_or_else(|| anyhow!("The extension registry hasn't been fetched yet"))
+    }
+
+    /// Extensions whose id, name or description contain `query`, ignoring case
+    pub fn search(&self, query: &str) -> Vec<&RegistryExtension> {
+        let query = query.to_lowercase();
+        self.extensions
+            .iter()
+            .filter(|extension| {
+                query.is_empty()
+                    || extension.id.to_lowercase().contains(&query)
+                    || extension.name.to_lowercase().contains(&query)
+                    || extension.description.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&RegistryExtension> {
+        self.extensions.iter().find(|extension| extension.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signed with the Ed25519 key whose seed is the bytes 1 to 32
+    const PUBLIC_KEY: &str = "ebVWLo/mVPlAeLES6KmLp5AfhTrmlb7X4OORC60ElmQ=";
+    const SIGNATURE: &str =
+        "DQ1h3i5PrPtnq1yY/jVRA9fBqcloOx4zKv40cJDLjod8QyMil9WI1nCGRnLvmyYCL9kKHN+J/UvUEZ79O2PWCw==";
+
+    const INDEX: &str = r#"{
+        "version": 1,
+        "extensions": [{
+            "id": "github",
+            "name": "GitHub",
+            "description": "Issues and pull requests",
+            "env_keys": [{"name": "GITHUB_PERSONAL_ACCESS_TOKEN", "description": "Token"}],
+            "config": {
+                "type": "stdio",
+                "name": "github",
+                "cmd": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-github"]
+            }
+        }]
+    }"#;
+
+    #[test]
+    fn test_signed_index() {
+        let registry =
+            ExtensionRegistry::from_signed(INDEX.as_bytes(), SIGNATURE, PUBLIC_KEY).unwrap();
+        assert_eq!(registry.search("pull request").len(), 1);
+        assert!(registry.search("slack").is_empty());
+        let github = registry.get("github").unwrap();
+        assert_eq!(github.required_env_keys().count(), 1);
+
+        let tampered = INDEX.replace("server-github", "server-evil");
+        assert!(
+            ExtensionRegistry::from_signed(tampered.as_bytes(), SIGNATURE, PUBLIC_KEY).is_err()
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_cache_is_verified_on_load() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        std::env::set_var("GOOSE_CACHE_DIR", cache_dir.path());
+        let source = RegistrySource {
+            url: "https://registry.example.com/registry.json".to_string(),
+            public_key: PUBLIC_KEY.to_string(),
+        };
+        let cache = |index: &str, fetched_at: u64| {
+            ExtensionRegistry::save_cached(&CachedRegistry {
+                fetched_at,
+                url: source.url.clone(),
+                public_key: source.public_key.clone(),
+                index: index.to_string(),
+                signature: SIGNATURE.to_string(),
+            })
+            .unwrap();
+        };
+
+        cache(INDEX, now());
+        let (_, registry) = ExtensionRegistry::load_cached(&source).unwrap();
+        assert!(registry.get("github").is_some());
+
+        let other_registry = RegistrySource {
+            url: "https://other.example.com/registry.json".to_string(),
+            ..source.clone()
+        };
+        assert!(ExtensionRegistry::load_cached(&other_registry).is_none());
+
+        cache(&INDEX.replace("server-github", "server-evil"), now());
+        assert!(ExtensionRegistry::load_cached(&source).is_none());
+
+        cache(INDEX, now() - CACHE_MAX_AGE.as_secs() - 1);
+        assert!(ExtensionRegistry::load_cached(&source).is_none());
+        std::env::remove_var("GOOSE_CACHE_DIR");
+    }
+}
@@ -1,7 +1,8 @@
 use super::base::Config;
 use crate::agents::ExtensionConfig;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use utoipa::ToSchema;
 
@@ -9,11 +10,43 @@ pub const DEFAULT_EXTENSION: &str = "developer";
 pub const DEFAULT_EXTENSION_TIMEOUT: u64 = 300;
 pub const DEFAULT_EXTENSION_DESCRIPTION: &str = "";
 pub const DEFAULT_DISPLAY_NAME: &str = "Developer";
+pub const DEFAULT_SAMPLING_TOKEN_BUDGET: u32 = 50_000;
 const EXTENSIONS_CONFIG_KEY: &str = "extensions";
+const TOOL_OVERRIDES_CONFIG_KEY: &str = "tool_overrides";
+/// Set once the global secrets extensions read have been moved into their scopes
+const SECRETS_MIGRATED_CONFIG_KEY: &str = "GOOSE_EXTENSION_SECRETS_MIGRATED";
+const SCOPED_SECRET_PREFIX: &str = "ext:";
+
+fn default_sampling_token_budget() -> u32 {
+    DEFAULT_SAMPLING_TOKEN_BUDGET
+}
+
+/// Allows an extension to request completions from the agent's provider
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+pub struct SamplingPermission {
+    /// Most tokens the extension may spend on completions in one session
+    #[serde(default = "default_sampling_token_budget")]
+    pub token_budget: u32,
+}
+
+/// Renames a tool of an extension or replaces its description, as the model sees it
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, ToSchema)]
+pub struct ToolOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
 
 #[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub struct ExtensionEntry {
     pub enabled: bool,
+    /// Started as soon as the session starts, even when extensions are started lazily
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub eager: bool,
+    /// Set when the extension may request completions, extensions can't sample otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingPermission>,
     #[serde(flatten)]
     pub config: ExtensionConfig,
 }
@@ -25,13 +58,50 @@ pub fn name_to_key(name: &str) -> String {
         .to_lowercase()
 }
 
+/// The secret key of an extension's environment variable. Scoped secrets are only resolved
+/// into that extension's environment
+pub fn scoped_secret_key(extension_key: &str, env_key: &str) -> String {
+    format!("{}{}:{}", SCOPED_SECRET_PREFIX, extension_key, env_key)
+}
+
+fn env_keys(config: &ExtensionConfig) -> &[String] {
+    match config {
+        ExtensionConfig::Sse { env_keys, .. }
+        | ExtensionConfig::Stdio { env_keys, .. }
+        | ExtensionConfig::StreamableHttp { env_keys, .. } => env_keys,
+        _ => &[],
+    }
+}
+
+/// The scoped secrets to create from the global ones extensions read. The globals are kept,
+/// since `${keyring:...}` references, provider settings and recipes may read them too
+fn plan_secret_migration(
+    extensions: &HashMap<String, ExtensionEntry>,
+    secrets: &HashMap<String, Value>,
+) -> Vec<(String, Value)> {
+    let mut scoped = Vec::new();
+    for (extension_key, entry) in extensions {
+        for env_key in env_keys(&entry.config) {
+            let scoped_key = scoped_secret_key(extension_key, env_key);
+            if secrets.contains_key(&scoped_key) {
+                continue;
+            }
+            if let Some(value) = secrets.get(env_key) {
+                scoped.push((scoped_key, value.clone()));
+            }
+        }
+    }
+    scoped.sort_by(|a, b| a.0.cmp(&b.0));
+    scoped
+}
+
 pub struct ExtensionConfigManager;
 
 impl ExtensionConfigManager {
     fn get_extensions_map() -> Result<HashMap<String, ExtensionEntry>> {
         let config = Config::global();
         Ok(config
-            .get_param(EXTENSIONS_CONFIG_KEY)
+            .get_param_unresolved(EXTENSIONS_CONFIG_KEY)
             .unwrap_or_else(|_| HashMap::new()))
     }
 
@@ -49,6 +119,21 @@ impl ExtensionConfigManager {
             .map(|entry| entry.config.clone()))
     }
 
+    /// Whether the extension is marked to start eagerly
+    pub fn is_eager(key: &str) -> bool {
+        Self::get_extensions_map()
+            .ok()
+            .and_then(|extensions| extensions.get(key).map(|entry| entry.eager))
+            .unwrap_or(false)
+    }
+
+    /// The sampling permission of the extension, if it has one
+    pub fn get_sampling(key: &str) -> Option<SamplingPermission> {
+        Self::get_extensions_map()
+            .ok()
+            .and_then(|extensions| extensions.get(key).and_then(|entry| entry.sampling.clone()))
+    }
+
     pub fn set(entry: ExtensionEntry) -> Result<()> {
         let mut extensions = Self::get_extensions_map()?;
         let key = entry.config.key();
@@ -59,7 +144,87 @@ impl ExtensionConfigManager {
     pub fn remove(key: &str) -> Result<()> {
         let mut extensions = Self::get_extensions_map()?;
         extensions.remove(key);
-        Self::save_extensions_map(extensions)
+        Self::save_extensions_map(extensions)?;
+        let removed = Self::list_secrets(key).and_then(|env_keys| {
+            env_keys
+                .iter()
+                .try_for_each(|env_key| Self::delete_secret(key, env_key))
+        });
+        if let Err(e) = removed {
+            tracing::warn!("Failed to remove the secrets of extension {}: {}", key, e);
+        }
+        Ok(())
+    }
+
+    /// Store the value of an environment variable for this extension only
+    pub fn set_secret(key: &str, env_key: &str, value: &str) -> Result<()> {
+        Config::global().set_secret(
+            &scoped_secret_key(key, env_key),
+            Value::String(value.to_string()),
+        )?;
+        Ok(())
+    }
+
+    /// The extension's value for an environment variable. As with other secrets, a variable set
+    /// in goose's own environment wins. Otherwise only the secret stored for this extension is
+    /// read, never a global one such as a provider's API key or another extension's secret
+    pub fn get_secret(key: &str, env_key: &str) -> Result<Value> {
+        Self::get_secret_in(Config::global(), key, env_key)
+    }
+
+    fn get_secret_in(config: &Config, key: &str, env_key: &str) -> Result<Value> {
+        if let Ok(value) = std::env::var(env_key.to_uppercase()) {
+            return Ok(Value::String(value));
+        }
+        Ok(config.get_secret(&scoped_secret_key(key, env_key))?)
+    }
+
+    pub fn delete_secret(key: &str, env_key: &str) -> Result<()> {
+        Config::global().delete_secret(&scoped_secret_key(key, env_key))?;
+        Ok(())
+    }
+
+    /// The environment variables with a value stored for this extension, sorted
+    pub fn list_secrets(key: &str) -> Result<Vec<String>> {
+        let prefix = scoped_secret_key(key, "");
+        let mut names: Vec<String> = Config::global()
+            .load_secrets()?
+            .into_keys()
+            .filter_map(|secret| secret.strip_prefix(&prefix).map(str::to_string))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Copy the global secrets extensions read through their `env_keys` into each extension's
+    /// scope, once. The globals stay where they are
+    pub fn migrate_secrets() -> Result<()> {
+        Self::migrate_secrets_in(Config::global())
+    }
+
+    fn migrate_secrets_in(config: &Config) -> Result<()> {
+        if config
+            .get_param::<bool>(SECRETS_MIGRATED_CONFIG_KEY)
+            .unwrap_or(false)
+        {
+            return Ok(());
+        }
+        let extensions: HashMap<String, ExtensionEntry> = config
+            .get_param_unresolved(EXTENSIONS_CONFIG_KEY)
+            .unwrap_or_default();
+        let scoped = plan_secret_migration(&extensions, &config.load_secrets()?);
+        if !scoped.is_empty() {
+            let keys: Vec<&str> = scoped.iter().map(|(key, _)| key.as_str()).collect();
+            tracing::info!(
+                "Copied extension secrets into their scopes: {}",
+                keys.join(", ")
+            );
+        }
+        for (scoped_key, value) in scoped {
+            config.set_secret(&scoped_key, value)?;
+        }
+        config.set_param(SECRETS_MIGRATED_CONFIG_KEY, Value::Bool(true))?;
+        Ok(())
     }
 
     pub fn set_enabled(key: &str, enabled: bool) -> Result<()> {
@@ -71,6 +236,16 @@ impl ExtensionConfigManager {
         Ok(())
     }
 
+    /// Limit the extension to the given tools, an empty list makes all of them available
+    pub fn set_available_tools(key: &str, tools: Vec<String>) -> Result<()> {
+        let mut extensions = Self::get_extensions_map()?;
+        if let Some(entry) = extensions.get_mut(key) {
+            entry.config.set_available_tools(tools);
+            Self::save_extensions_map(extensions)?;
+        }
+        Ok(())
+    }
+
     pub fn get_all() -> Result<Vec<ExtensionEntry>> {
         let extensions = Self::get_extensions_map()?;
         Ok(extensions.into_values().collect())
@@ -85,4 +260,160 @@ impl ExtensionConfigManager {
         let extensions = Self::get_extensions_map()?;
         Ok(extensions.get(key).map(|e| e.enabled).unwrap_or(false))
     }
+
+    fn get_tool_overrides_map() -> HashMap<String, HashMap<String, ToolOverride>> {
+        Config::global()
+            .get_param(TOOL_OVERRIDES_CONFIG_KEY)
+            .unwrap_or_default()
+    }
+
+    /// The overrides of the extension's tools, keyed by the tool's own name
+    pub fn get_tool_overrides(key: &str) -> HashMap<String, ToolOverride> {
+        Self::get_tool_overrides_map()
+            .remove(key)
+            .unwrap_or_default()
+    }
+
+    /// Override the name or description of a tool, or with `None` show it as the extension
+    /// describes it again
+    pub fn set_tool_override(
+        key: &str,
+        tool: &str,
+        tool_override: Option<ToolOverride>,
+    ) -> Result<()> {
+        if let Some(name) = tool_override.as_ref().and_then(|o| o.name.as_ref()) {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(anyhow!(
+                    "Tool names may only contain letters, digits, '_' and '-', got '{}'",
+                    name
+                ));
+            }
+        }
+
+        let mut overrides = Self::get_tool_overrides_map();
+        let extension = overrides.entry(key.to_string()).or_default();
+        match tool_override {
+            Some(tool_override) if tool_override != ToolOverride::default() => {
+                extension.insert(tool.to_string(), tool_override);
+            }
+            _ => {
+                extension.remove(tool);
+            }
+        }
+        if extension.is_empty() {
+            overrides.remove(key);
+        }
+        Config::global().set_param(TOOL_OVERRIDES_CONFIG_KEY, serde_json::to_value(overrides)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::extension::Envs;
+    use serde_json::json;
+
+    fn stdio(name: &str, env_keys: &[&str]) -> ExtensionEntry {
+        ExtensionEntry {
+            enabled: true,
+            eager: false,
+            sampling: None,
+            config: ExtensionConfig::Stdio {
+                name: name.to_string(),
+                cmd: "server".to_string(),
+                args: vec![],
+                envs: Envs::default(),
+                env_keys: env_keys.iter().map(|key| key.to_string()).collect(),
+                timeout: None,
+                description: None,
+                bundled: None,
+                available_tools: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn test_plan_secret_migration() {
+        let extensions = HashMap::from([
+            ("github".to_string(), stdio("github", &["GITHUB_TOKEN"])),
+            (
+                "search".to_string(),
+                stdio("search", &["OPENAI_API_KEY", "SEARCH_KEY"]),
+            ),
+        ]);
+        let secrets = HashMap::from([
+            ("GITHUB_TOKEN".to_string(), json!("ghp")),
+            ("OPENAI_API_KEY".to_string(), json!("sk")),
+            ("SEARCH_KEY".to_string(), json!("old")),
+            ("ext:search:SEARCH_KEY".to_string(), json!("new")),
+        ]);
+
+        assert_eq!(
+            plan_secret_migration(&extensions, &secrets),
+            vec![
+                ("ext:github:GITHUB_TOKEN".to_string(), json!("ghp")),
+                ("ext:search:OPENAI_API_KEY".to_string(), json!("sk")),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_migration_keeps_referenced_globals() -> Result<()> {
+        let config_file = tempfile::NamedTempFile::new()?;
+        let secrets_file = tempfile::NamedTempFile::new()?;
+        let config = Config::new_with_file_secrets(config_file.path(), secrets_file.path())?;
+        config.set_secret("GITHUB_TOKEN", json!("ghp"))?;
+        config.set_param(
+            EXTENSIONS_CONFIG_KEY,
+            json!({"github": stdio("github", &["GITHUB_TOKEN"])}),
+        )?;
+        config.set_param("review_env", json!({"TOKEN": "${keyring:GITHUB_TOKEN}"}))?;
+
+        ExtensionConfigManager::migrate_secrets_in(&config)?;
+
+        let secrets = config.load_secrets()?;
+        assert_eq!(secrets["ext:github:GITHUB_TOKEN"], json!("ghp"));
+        assert_eq!(secrets["GITHUB_TOKEN"], json!("ghp"));
+        let env: HashMap<String, String> = config.get_param("review_env")?;
+        assert_eq!(env["TOKEN"], "ghp");
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_extension_reads_only_its_own_secrets() -> Result<()> {
+        let config_file = tempfile::NamedTempFile::new()?;
+        let secrets_file = tempfile::NamedTempFile::new()?;
+        let config = Config::new_with_file_secrets(config_file.path(), secrets_file.path())?;
+        config.set_secret("OPENAI_API_KEY", json!("sk-provider"))?;
+        config.set_secret(&scoped_secret_key("github", "GITHUB_TOKEN"), json!("ghp"))?;
+
+        temp_env::with_vars(
+            [("OPENAI_API_KEY", None::<&str>), ("GITHUB_TOKEN", None)],
+            || {
+                assert_eq!(
+                    ExtensionConfigManager::get_secret_in(&config, "github", "GITHUB_TOKEN")
+                        .unwrap(),
+                    json!("ghp")
+                );
+                // Another extension asking for the same variable doesn't get github's token
+                assert!(
+                    ExtensionConfigManager::get_secret_in(&config, "search", "GITHUB_TOKEN")
+                        .is_err()
+                );
+                // Nor does listing a provider's key hand it to the extension
+                assert!(
+                    ExtensionConfigManager::get_secret_in(&config, "search", "OPENAI_API_KEY")
+                        .is_err()
+                );
+            },
+        );
+        Ok(())
+    }
 }
@@ -1,17 +1,25 @@
 pub mod base;
 pub mod custom_providers;
+pub mod encrypted_secrets;
 mod experiments;
+pub mod extension_registry;
 pub mod extensions;
 pub mod permission;
+pub mod policy;
+pub mod project;
+pub mod schema;
 pub mod signup_openrouter;
 pub mod signup_tetrate;
 
 pub use crate::agents::ExtensionConfig;
-pub use base::{Config, ConfigError, APP_STRATEGY};
+pub use base::{Config, ConfigError, ConfigSource, EffectiveValue, APP_STRATEGY};
 pub use custom_providers::CustomProviderConfig;
 pub use experiments::ExperimentManager;
-pub use extensions::{ExtensionConfigManager, ExtensionEntry};
+pub use extension_registry::{ExtensionRegistry, RegistryExtension};
+pub use extensions::{ExtensionConfigManager, ExtensionEntry, SamplingPermission};
 pub use permission::PermissionManager;
+pub use policy::Policy;
+pub use project::{ProjectConfig, ProjectOverlay};
 pub use signup_openrouter::configure_openrouter;
 pub use signup_tetrate::configure_tetrate;
 
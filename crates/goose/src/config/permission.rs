@@ -1,3 +1,4 @@
+use super::project::ProjectOverlay;
 use super::APP_STRATEGY;
 use etcetera::{choose_app_strategy, AppStrategy};
 use serde::{Deserialize, Serialize};
@@ -28,6 +29,23 @@ pub struct PermissionConfig {
 pub struct PermissionManager {
     config_path: PathBuf, // Path to the permission configuration file
     permission_map: HashMap<String, PermissionConfig>, // Mapping of permission names to configurations
+    use_project_config: bool, // Whether the current project's .goose/config.yaml can restrict tools
+}
+
+fn strictness(level: &PermissionLevel) -> u8 {
+    match level {
+        PermissionLevel::AlwaysAllow => 0,
+        PermissionLevel::AskBefore => 1,
+        PermissionLevel::NeverAllow => 2,
+    }
+}
+
+fn stricter(a: PermissionLevel, b: PermissionLevel) -> PermissionLevel {
+    if strictness(&b) > strictness(&a) {
+        b
+    } else {
+        a
+    }
 }
 
 // Constants representing specific permission categories
@@ -59,6 +77,7 @@ impl Default for PermissionManager {
         PermissionManager {
             config_path,
             permission_map,
+            use_project_config: true,
         }
     }
 }
@@ -81,6 +100,7 @@ impl PermissionManager {
         PermissionManager {
             config_path,
             permission_map,
+            use_project_config: false,
         }
     }
 
@@ -89,9 +109,19 @@ impl PermissionManager {
         self.permission_map.keys().cloned().collect()
     }
 
-    /// Retrieves the user permission level for a specific tool.
+    /// Retrieves the user permission level for a specific tool, made stricter by the
+    /// current project's configuration where it restricts the tool further.
     pub fn get_user_permission(&self, principal_name: &str) -> Option<PermissionLevel> {
-        self.get_permission(USER_PERMISSION, principal_name)
+        let user = self.get_permission(USER_PERMISSION, principal_name);
+        let project = if self.use_project_config {
+            ProjectOverlay::current().and_then(|project| project.tool_permission(principal_name))
+        } else {
+            None
+        };
+        match (user, project) {
+            (Some(user), Some(project)) => Some(stricter(user, project)),
+            (user, project) => user.or(project),
+        }
     }
 
     /// Retrieves the smart approve permission level for a specific tool.
@@ -0,0 +1,441 @@
+//! An admin-managed policy that pins configuration keys, so a team can rely on settings users
+//! can't change. The policy is read from a file in a location users can't write, which may point
+//! to a policy to fetch from a URL and check against an ed25519 signature.
+//!
+//! ```yaml
+//! locked:
+//!   GOOSE_TOOLSHIM: false
+//! allowed_providers: [databricks, anthropic]
+//! min_goose_mode: approve
+//! disabled_extensions: [computercontroller]
+//! remote:
+//!   url: https://example.com/goose/policy.yaml
+//!   public_key: MCowBQYDK2VwAyEA...
+//! ```
+//!
+//! A policy that is configured but can't be read or verified fails closed: every change is
+//! refused, no provider may be used and goose only chats.
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::base::ConfigError;
+
+/// How long fetching a remote policy may take before it counts as unavailable
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+const PROVIDER_KEYS: &[&str] = &["GOOSE_PROVIDER", "GOOSE_LEAD_PROVIDER"];
+const MODE_KEY: &str = "GOOSE_MODE";
+const EXTENSIONS_KEY: &str = "extensions";
+/// Goose modes from the one running tools most freely to the most restricted
+const MODES: &[&str] = &["auto", "smart_approve", "approve", "chat"];
+/// The mode goose is held to while a configured policy is unavailable
+const UNAVAILABLE_MODE: &str = "chat";
+/// DER SubjectPublicKeyInfo header of an ed25519 public key, followed by the 32 byte key
+const ED25519_SPKI_PREFIX: &[u8] = &[
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+const ED25519_KEY_LEN: usize = 32;
+
+/// A policy served by the organization, named in the system policy file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemotePolicy {
+    /// URL of the policy. The signature is fetched from the same URL with `.sig` added
+    pub url: String,
+    /// Base64 ed25519 public key the fetched policy must be signed with, either as a PEM body
+    /// (DER SubjectPublicKeyInfo, as `openssl pkey -pubout` writes it) or the raw 32 byte key
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Policy {
+    /// Keys pinned to a value, whatever the environment or config files say
+    #[serde(default)]
+    pub locked: HashMap<String, Value>,
+    /// Providers that may be used, any when empty
+    #[serde(default)]
+    pub allowed_providers: Vec<String>,
+    /// The least restricted goose mode allowed
+    #[serde(default)]
+    pub min_goose_mode: Option<String>,
+    /// Extensions, by key, that can't be enabled
+    #[serde(default)]
+    pub disabled_extensions: Vec<String>,
+    /// Policy to fetch and enforce in place of this one, once goose starts
+    #[serde(default)]
+    pub remote: Option<RemotePolicy>,
+    /// Why the configured policy couldn't be loaded, in which case it denies everything
+    #[serde(skip)]
+    unavailable: Option<String>,
+}
+
+fn mode_rank(mode: &str) -> Option<usize> {
+    MODES.iter().position(|m| *m == mode)
+}
+
+impl Policy {
+    /// The policy for this machine, from the system location only, if there is one
+    pub fn load() -> Option<Self> {
+        Self::load_from(&system_policy_path())
+    }
+
+    /// The policy in `path`, None when there is no file. A file that can't be read gives a
+    /// policy denying everything rather than none, as does one naming a remote policy until
+    /// that is fetched with `resolve_remote`
+    pub fn load_from(path: &Path) -> Option<Self> {
+        if !path.exists() {
+            return None;
+        }
+        match Self::read(path) {
+            Ok(policy) if policy.remote.is_some() => Some(Self {
+                remote: policy.remote,
+                unavailable: Some("The remote goose policy has not been fetched".to_string()),
+                ..Default::default()
+            }),
+            Ok(policy) => Some(policy),
+            Err(e) => Some(Self::unavailable(format!(
+                "Failed to read the goose policy {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        Ok(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// A policy standing in for one that is configured but unavailable, denying everything
+    pub fn unavailable(reason: String) -> Self {
+        tracing::error!("{}, refusing changes and holding goose to chat", reason);
+        Self {
+            unavailable: Some(reason),
+            ..Default::default()
+        }
+    }
+
+    /// Why the configured policy couldn't be loaded, if it couldn't
+    pub fn unavailable_reason(&self) -> Option<&str> {
+        self.unavailable.as_deref()
+    }
+
+    /// The remote policy this one names, fetched and verified, or one denying everything when
+    /// that fails. None when it names no remote policy
+    pub async fn resolve_remote(&self) -> Option<Self> {
+        let remote = self.remote.as_ref()?;
+        Some(match Self::fetch(remote).await {
+            Ok(policy) => policy,
+            Err(e) => Self::unavailable(format!(
+                "Failed to load the goose policy from {}: {}",
+                remote.url, e
+            )),
+        })
+    }
+
+    /// Fetch the policy and its signature, verifying it with the public key of the remote
+    async fn fetch(remote: &RemotePolicy) -> Result<Self, ConfigError> {
+        let failed =
+            |e: reqwest::Error| ConfigError::PolicyViolation(format!("fetch failed: {}", e));
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(failed)?;
+        let get = |url: String| {
+            let client = client.clone();
+            async move {
+                client
+                    .get(url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await
+            }
+        };
+        let content = get(remote.url.clone()).await.map_err(failed)?;
+        let signature = get(format!("{}.sig", remote.url)).await.map_err(failed)?;
+
+        verify_signature(
+            content.as_bytes(),
+            signature.trim(),
+            remote.public_key.trim(),
+        )?;
+        let mut policy: Self = serde_yaml::from_str(&content)?;
+        // A fetched policy can't send goose elsewhere
+        policy.remote = None;
+        Ok(policy)
+    }
+
+    /// Refuse a value for a key the policy controls
+    pub fn check(&self, key: &str, value: &Value) -> Result<(), ConfigError> {
+        let violation = |reason: String| Err(ConfigError::PolicyViolation(reason));
+        if let Some(reason) = &self.unavailable {
+            return violation(reason.clone());
+        }
+        if let Some(locked) = self.locked.get(key) {
+            if locked != value {
+                return violation(format!("{} is locked by policy", key));
+            }
+        }
+        if PROVIDER_KEYS.contains(&key) {
+            if let Some(provider) = value.as_str() {
+                if !self.is_provider_allowed(provider) {
+                    return violation(format!("Provider {} is not allowed by policy", provider));
+                }
+            }
+        }
+        if key == MODE_KEY {
+            if let Some(mode) = value.as_str() {
+                if self.enforce_goose_mode(mode) != mode {
+                    return violation(format!(
+                        "Goose mode {} is less restricted than the policy allows",
+                        mode
+                    ));
+                }
+            }
+        }
+        if key == EXTENSIONS_KEY {
+            if let Some(enabled) = self.enabled_disabled_extension(value) {
+                return violation(format!("Extension {} is disabled by policy", enabled));
+            }
+        }
+        Ok(())
+    }
+
+    /// Refuse removing a locked key
+    pub fn check_delete(&self, key: &str) -> Result<(), ConfigError> {
+        if let Some(reason) = &self.unavailable {
+            return Err(ConfigError::PolicyViolation(reason.clone()));
+        }
+        if self.locked.contains_key(key) {
+            return Err(ConfigError::PolicyViolation(format!(
+                "{} is locked by policy",
+                key
+            )));
+        }
+        Ok(())
+    }
+
+    /// The value goose uses for a key given what the config holds: the locked value, the mode
+    /// raised to the floor, or the extensions with the disabled ones turned off
+    pub fn enforce(&self, key: &str, value: Option<Value>) -> Option<Value> {
+        if let Some(locked) = self.locked.get(key) {
+            return Some(locked.clone());
+        }
+        match (key, value) {
+            (MODE_KEY, _) if self.unavailable.is_some() => {
+                Some(Value::String(UNAVAILABLE_MODE.to_string()))
+            }
+            (MODE_KEY, None) => self.min_goose_mode.clone().map(Value::String),
+            (MODE_KEY, Some(Value::String(mode))) => {
+                Some(Value::String(self.enforce_goose_mode(&mode).to_string()))
+            }
+            (EXTENSIONS_KEY, Some(Value::Object(mut extensions))) => {
+                for (name, entry) in extensions.iter_mut() {
+                    if self.is_extension_disabled(name) {
+                        if let Some(entry) = entry.as_object_mut() {
+                            entry.insert("enabled".to_string(), Value::Bool(false));
+                        }
+                    }
+                }
+                Some(Value::Object(extensions))
+            }
+            (_, value) => value,
+        }
+    }
+
+    pub fn is_provider_allowed(&self, provider: &str) -> bool {
+        if self.unavailable.is_some() {
+            return false;
+        }
+        self.allowed_providers.is_empty() || self.allowed_providers.iter().any(|p| p == provider)
+    }
+
+    pub fn is_extension_disabled(&self, key: &str) -> bool {
+        self.unavailable.is_some()
+            || self
+                .disabled_extensions
+                .iter()
+                .any(|disabled| disabled.eq_ignore_ascii_case(key))
+    }
+
+    /// The mode, raised to the policy's floor when it is less restricted
+    pub fn enforce_goose_mode<'a>(&'a self, mode: &'a str) -> &'a str {
+        if self.unavailable.is_some() {
+            return UNAVAILABLE_MODE;
+        }
+        let Some(floor) = self.min_goose_mode.as_deref() else {
+            return mode;
+        };
+        match (mode_rank(mode), mode_rank(floor)) {
+            (Some(rank), Some(floor_rank)) if rank < floor_rank => floor,
+            _ => mode,
+        }
+    }
+
+    fn enabled_disabled_extension(&self, extensions: &Value) -> Option<String> {
+        extensions.as_object()?.iter().find_map(|(name, entry)| {
+            let enabled = entry.get("enabled").and_then(Value::as_bool) == Some(true);
+            (enabled && self.is_extension_disabled(name)).then(|| name.clone())
+        })
+    }
+}
+
+/// Where admins put the policy, a location only they can write
+fn system_policy_path() -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string()))
+            .join("goose")
+            .join("policy.yaml")
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/goose/policy.yaml")
+    } else {
+        PathBuf::from("/etc/goose/policy.yaml")
+    }
+}
+
+fn verify_signature(content: &[u8], signature: &str, public_key: &str) -> Result<(), ConfigError> {
+    let engine = base64::engine::general_purpose::STANDARD;
+    let invalid = |what: &str| ConfigError::PolicyViolation(format!("Invalid policy {}", what));
+    let signature = engine.decode(signature).map_err(|_| invalid("signature"))?;
+    let public_key = engine
+        .decode(public_key)
+        .map_err(|_| invalid("public key"))?;
+    let public_key = match public_key.strip_prefix(ED25519_SPKI_PREFIX) {
+        Some(key) if key.len() == ED25519_KEY_LEN => key,
+        None if public_key.len() == ED25519_KEY_LEN => &public_key[..],
+        _ => return Err(invalid("public key, it is not an ed25519 public key")),
+    };
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+        .verify(content, &signature)
+        .map_err(|_| invalid("signature, it was not signed with the configured key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use serde_json::json;
+
+    fn policy() -> Policy {
+        Policy {
+            locked: HashMap::from([("GOOSE_TOOLSHIM".to_string(), json!(false))]),
+            allowed_providers: vec!["anthropic".to_string()],
+            min_goose_mode: Some("approve".to_string()),
+            disabled_extensions: vec!["computercontroller".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let policy = policy();
+        assert!(policy.check("GOOSE_TOOLSHIM", &json!(true)).is_err());
+        assert!(policy.check("GOOSE_TOOLSHIM", &json!(false)).is_ok());
+        assert!(policy.check("GOOSE_PROVIDER", &json!("openai")).is_err());
+        assert!(policy.check("GOOSE_PROVIDER", &json!("anthropic")).is_ok());
+        assert!(policy.check("GOOSE_MODE", &json!("auto")).is_err());
+        assert!(policy.check("GOOSE_MODE", &json!("chat")).is_ok());
+        assert!(policy
+            .check(
+                "extensions",
+                &json!({"computercontroller": {"enabled": true}})
+            )
+            .is_err());
+        assert!(policy.check_delete("GOOSE_TOOLSHIM").is_err());
+    }
+
+    #[test]
+    fn test_enforce() {
+        let policy = policy();
+        assert_eq!(
+            policy.enforce("GOOSE_TOOLSHIM", Some(json!(true))),
+            Some(json!(false))
+        );
+        assert_eq!(policy.enforce("GOOSE_MODE", None), Some(json!("approve")));
+        assert_eq!(
+            policy.enforce("GOOSE_MODE", Some(json!("smart_approve"))),
+            Some(json!("approve"))
+        );
+        assert_eq!(
+            policy.enforce(
+                "extensions",
+                Some(json!({"computercontroller": {"enabled": true}}))
+            ),
+            Some(json!({"computercontroller": {"enabled": false}}))
+        );
+        assert_eq!(policy.enforce("GOOSE_MODEL", None), None);
+    }
+
+    #[test]
+    fn test_unreadable_policy_fails_closed() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Policy::load_from(&dir.path().join("policy.yaml")), None);
+
+        let path = dir.path().join("policy.yaml");
+        std::fs::write(
+            &path,
+            "remote:\n  url: https://example.com/policy.yaml\n  public_key: AAAA\n",
+        )
+        .unwrap();
+        let pending = Policy::load_from(&path).unwrap();
+        assert!(pending.remote.is_some());
+        assert!(!pending.is_provider_allowed("anthropic"));
+
+        std::fs::write(&path, "min_goose_mode: [not, a, mode").unwrap();
+        let policy = Policy::load_from(&path).unwrap();
+        assert!(policy.unavailable_reason().is_some());
+        assert!(policy.check("GOOSE_MODEL", &json!("gpt-4o")).is_err());
+        assert!(policy.check_delete("GOOSE_MODEL").is_err());
+        assert!(!policy.is_provider_allowed("anthropic"));
+        assert_eq!(
+            policy.enforce("GOOSE_MODE", Some(json!("auto"))),
+            Some(json!("chat"))
+        );
+        assert_eq!(
+            policy.enforce("extensions", Some(json!({"developer": {"enabled": true}}))),
+            Some(json!({"developer": {"enabled": false}}))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_remote_policy_fails_closed() {
+        let policy: Policy = serde_yaml::from_str(
+            "remote:\n  url: http://127.0.0.1:9/policy.yaml\n  public_key: AAAA\n",
+        )
+        .unwrap();
+        let resolved = policy.resolve_remote().await.unwrap();
+        assert!(resolved.unavailable_reason().is_some());
+        assert!(!resolved.is_provider_allowed("anthropic"));
+
+        assert_eq!(Policy::default().resolve_remote().await, None);
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let engine = base64::engine::general_purpose::STANDARD;
+        let content = b"min_goose_mode: approve\n";
+        let signature = engine.encode(key_pair.sign(content).as_ref());
+        let public_key = engine.encode(key_pair.public_key().as_ref());
+
+        assert!(verify_signature(content, &signature, &public_key).is_ok());
+        assert!(verify_signature(b"min_goose_mode: auto\n", &signature, &public_key).is_err());
+
+        // The documented format, the body of the PEM file `openssl pkey -pubout` writes
+        let spki = [ED25519_SPKI_PREFIX, key_pair.public_key().as_ref()].concat();
+        let public_key = engine.encode(spki);
+        assert!(public_key.starts_with("MCowBQYDK2VwAyEA"));
+        assert!(verify_signature(content, &signature, &public_key).is_ok());
+        assert!(verify_signature(b"min_goose_mode: auto\n", &signature, &public_key).is_err());
+
+        let truncated = engine.encode(&key_pair.public_key().as_ref()[..16]);
+        assert!(verify_signature(content, &signature, &truncated).is_err());
+    }
+}
@@ -0,0 +1,541 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// The type a known configuration key holds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigKeyType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// One of a fixed set of strings
+    Choice(&'static [&'static str]),
+    /// A list of strings
+    List,
+    Object,
+    /// A list of mappings
+    ObjectList,
+}
+
+impl ConfigKeyType {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            ConfigKeyType::String => value.is_string(),
+            ConfigKeyType::Integer => value.is_u64() || value.is_i64(),
+            ConfigKeyType::Number => value.is_number(),
+            ConfigKeyType::Boolean => value.is_boolean(),
+            ConfigKeyType::Choice(choices) => value.as_str().is_some_and(|v| choices.contains(&v)),
+            ConfigKeyType::List => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+            ConfigKeyType::Object => value.is_object(),
+            ConfigKeyType::ObjectList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_object)),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            ConfigKeyType::String => "a string".to_string(),
+            ConfigKeyType::Integer => "a whole number".to_string(),
+            ConfigKeyType::Number => "a number".to_string(),
+            ConfigKeyType::Boolean => "true or false".to_string(),
+            ConfigKeyType::Choice(choices) => format!("one of {}", choices.join(", ")),
+            ConfigKeyType::List => "a list of strings".to_string(),
+            ConfigKeyType::Object => "a mapping".to_string(),
+            ConfigKeyType::ObjectList => "a list of mappings".to_string(),
+        }
+    }
+}
+
+/// A configuration key goose knows about
+pub struct ConfigKeySpec {
+    pub key: &'static str,
+    pub key_type: ConfigKeyType,
+    pub description: &'static str,
+}
+
+/// A key that still works or used to, and what to use instead
+pub struct DeprecatedKey {
+    pub key: &'static str,
+    pub hint: &'static str,
+}
+
+const fn spec(
+    key: &'static str,
+    key_type: ConfigKeyType,
+    description: &'static str,
+) -> ConfigKeySpec {
+    ConfigKeySpec {
+        key,
+        key_type,
+        description,
+    }
+}
+
+pub const KNOWN_KEYS: &[ConfigKeySpec] = &[
+    spec(
+        "GOOSE_PROVIDER",
+        ConfigKeyType::String,
+        "Provider used for sessions",
+    ),
+    spec(
+        "GOOSE_MODEL",
+        ConfigKeyType::String,
+        "Model used for sessions",
+    ),
+    spec(
+        "GOOSE_MODE",
+        ConfigKeyType::Choice(&["auto", "approve", "smart_approve", "chat"]),
+        "When tools need approval",
+    ),
+    spec(
+        "GOOSE_MAX_TURNS",
+        ConfigKeyType::Integer,
+        "Turns the agent takes without user input",
+    ),
+    spec(
+        "GOOSE_TEMPERATURE",
+        ConfigKeyType::Number,
+        "Sampling temperature",
+    ),
+    spec(
+        "GOOSE_CONTEXT_LIMIT",
+        ConfigKeyType::String,
+        "Context window size override, as a quoted number",
+    ),
+    spec(
+        "GOOSE_AUTO_COMPACT_THRESHOLD",
+        ConfigKeyType::Number,
+        "Fraction of the context window at which the conversation is compacted",
+    ),
+    spec(
+        "GOOSE_CONTEXT_STRATEGY",
+        ConfigKeyType::Choice(&["summarize", "truncate", "clear", "prompt"]),
+        "What to do when the context window is full",
+    ),
+    spec(
+        "GOOSE_TOKENIZER",
+        ConfigKeyType::String,
+        "Tokenizer for token estimates: o200k_base, cl100k_base, claude or hf:<repository>",
+    ),
+    spec(
+        "GOOSE_FRONTEND_TOOL_TIMEOUT",
+        ConfigKeyType::Integer,
+        "Seconds to wait for the frontend to return the result of a frontend tool",
+    ),
+    spec(
+        "GOOSE_CONTENT_FILTERS",
+        ConfigKeyType::Object,
+        "Content policy rules and classifier applied to tool outputs and assistant messages",
+    ),
+    spec(
+        "GOOSE_REDACT_SECRETS",
+        ConfigKeyType::Boolean,
+        "Mask secret values in content sent to the model or saved with the session",
+    ),
+    spec(
+        "GOOSE_REDACT_ENV_PATTERNS",
+        ConfigKeyType::List,
+        "Names of the environment variables whose values are masked, like *_API_KEY",
+    ),
+    spec(
+        "GOOSE_REDACT_EXCLUDE",
+        ConfigKeyType::List,
+        "Secrets and environment variables whose values are never masked",
+    ),
+    spec(
+        "GOOSE_TOOL_CONFIRMATION_TIMEOUT",
+        ConfigKeyType::Integer,
+        "Seconds to wait for a tool confirmation in approve mode before giving up on the tool",
+    ),
+    spec(
+        "GOOSE_TOOL_CONFIRMATION_TIMEOUT_ACTION",
+        ConfigKeyType::Choice(&["deny", "cancel"]),
+        "Whether a tool nobody confirmed in time is denied or cancelled",
+    ),
+    spec(
+        "GOOSE_PROVIDER_RATE_LIMITS",
+        ConfigKeyType::Object,
+        "Requests per minute, tokens per minute and concurrent requests allowed per provider",
+    ),
+    spec(
+        "GOOSE_PROVIDER_RECORD",
+        ConfigKeyType::String,
+        "Directory to record provider requests and responses to, with secrets scrubbed",
+    ),
+    spec(
+        "GOOSE_PROVIDER_REPLAY",
+        ConfigKeyType::String,
+        "Directory of recorded provider responses to replay instead of calling the provider",
+    ),
+    spec(
+        "GOOSE_LEAD_PROVIDER",
+        ConfigKeyType::String,
+        "Provider of the lead model",
+    ),
+    spec(
+        "GOOSE_LEAD_MODEL",
+        ConfigKeyType::String,
+        "Lead model for the first turns",
+    ),
+    spec(
+        "GOOSE_LEAD_TURNS",
+        ConfigKeyType::Integer,
+        "Turns that use the lead model",
+    ),
+    spec(
+        "GOOSE_LEAD_FAILURE_THRESHOLD",
+        ConfigKeyType::Integer,
+        "Failures before switching back to the lead model",
+    ),
+    spec(
+        "GOOSE_LEAD_FALLBACK_TURNS",
+        ConfigKeyType::Integer,
+        "Turns on the lead model after a fallback",
+    ),
+    spec(
+        "GOOSE_PLANNER_PROVIDER",
+        ConfigKeyType::String,
+        "Provider used by /plan",
+    ),
+    spec(
+        "GOOSE_PLANNER_MODEL",
+        ConfigKeyType::String,
+        "Model used by /plan",
+    ),
+    spec(
+        "GOOSE_SCHEDULER_TYPE",
+        ConfigKeyType::Choice(&["legacy", "temporal"]),
+        "Scheduler backend",
+    ),
+    spec(
+        "GOOSE_ENABLE_ROUTER",
+        ConfigKeyType::Choice(&["true", "false"]),
+        "Select tools with a router",
+    ),
+    spec(
+        "GOOSE_ROUTER_STRATEGY",
+        ConfigKeyType::Choice(&["llm", "vector"]),
+        "How the router selects tools",
+    ),
+    spec(
+        "GOOSE_LAZY_EXTENSIONS",
+        ConfigKeyType::Choice(&["true", "false"]),
+        "Start extensions with a cached manifest on first use",
+    ),
+    spec(
+        "GOOSE_EXTENSION_REGISTRY_URL",
+        ConfigKeyType::String,
+        "HTTPS URL of the extension registry index",
+    ),
+    spec(
+        "GOOSE_EXTENSION_REGISTRY_PUBLIC_KEY",
+        ConfigKeyType::String,
+        "Ed25519 key the extension registry is signed with",
+    ),
+    spec(
+        "GOOSE_SYSTEM_PROMPT_FILE_PATH",
+        ConfigKeyType::String,
+        "Replacement system prompt",
+    ),
+    spec(
+        "GOOSE_CLI_THEME",
+        ConfigKeyType::Choice(&["light", "dark", "ansi"]),
+        "CLI colors",
+    ),
+    spec(
+        "GOOSE_CLI_BAT_THEME",
+        ConfigKeyType::String,
+        "Syntax theme for markdown in the CLI",
+    ),
+    spec(
+        "GOOSE_CLI_BAT_CACHE_DIR",
+        ConfigKeyType::String,
+        "bat cache directory with custom syntax themes",
+    ),
+    spec(
+        "GOOSE_CLI_RENDER_MARKDOWN",
+        ConfigKeyType::Boolean,
+        "Highlight markdown in the CLI",
+    ),
+    spec(
+        "GOOSE_CLI_SPINNER",
+        ConfigKeyType::Choice(&["dots", "braille", "line", "none"]),
+        "Spinner shown while goose works",
+    ),
+    spec(
+        "GOOSE_SESSION_RETENTION_MAX_SESSIONS",
+        ConfigKeyType::Integer,
+        "Most sessions kept, older ones are removed on startup",
+    ),
+    spec(
+        "GOOSE_SESSION_RETENTION_MAX_SIZE_MB",
+        ConfigKeyType::Integer,
+        "Most disk space sessions take, older ones are removed on startup",
+    ),
+    spec(
+        "GOOSE_SESSION_RETENTION_KEEP_TAGGED",
+        ConfigKeyType::Boolean,
+        "Keep tagged sessions when applying the retention policy",
+    ),
+    spec(
+        "GOOSE_PRICING_OVERRIDES",
+        ConfigKeyType::Object,
+        "Prices in USD per million tokens by provider/model, taking precedence over OpenRouter",
+    ),
+    spec(
+        "GOOSE_CLI_ASCII",
+        ConfigKeyType::Boolean,
+        "Draw the CLI with ASCII symbols only",
+    ),
+    spec(
+        "GOOSE_CLI_PLAIN",
+        ConfigKeyType::Boolean,
+        "Plain text CLI output without colors or spinners",
+    ),
+    spec(
+        "GOOSE_CLI_NOTIFICATIONS",
+        ConfigKeyType::Boolean,
+        "Desktop notifications when goose needs attention",
+    ),
+    spec(
+        "GOOSE_CLI_NOTIFY_AFTER_SECONDS",
+        ConfigKeyType::Integer,
+        "Shortest turn that notifies when it finishes",
+    ),
+    spec(
+        "GOOSE_CLI_SHOW_COST",
+        ConfigKeyType::Boolean,
+        "Show estimated cost in the CLI",
+    ),
+    spec(
+        "GOOSE_CLI_MIN_PRIORITY",
+        ConfigKeyType::Number,
+        "Minimum priority of shown tool output",
+    ),
+    spec(
+        "GOOSE_CLI_TOOL_PARAMS_TRUNCATION_MAX_LENGTH",
+        ConfigKeyType::Integer,
+        "Length at which tool parameters are truncated in the CLI",
+    ),
+    spec(
+        "EDIT_MODE",
+        ConfigKeyType::Choice(&["emacs", "vi"]),
+        "CLI line editing keys",
+    ),
+    spec(
+        "GOOSE_RECIPE_GITHUB_REPO",
+        ConfigKeyType::String,
+        "GitHub repository of shared recipes",
+    ),
+    spec(
+        "GOOSE_CA_CERT_PATH",
+        ConfigKeyType::String,
+        "CA certificate for provider requests",
+    ),
+    spec(
+        "GOOSE_CLIENT_CERT_PATH",
+        ConfigKeyType::String,
+        "Client certificate",
+    ),
+    spec(
+        "GOOSE_CLIENT_KEY_PATH",
+        ConfigKeyType::String,
+        "Client certificate key",
+    ),
+    spec(
+        "otel_exporter_otlp_endpoint",
+        ConfigKeyType::String,
+        "OpenTelemetry endpoint",
+    ),
+    spec(
+        "otel_exporter_otlp_timeout",
+        ConfigKeyType::Integer,
+        "OpenTelemetry timeout in ms",
+    ),
+    spec("extensions", ConfigKeyType::Object, "Configured extensions"),
+    spec(
+        "experiments",
+        ConfigKeyType::Object,
+        "Experimental features",
+    ),
+    spec(
+        "webhooks",
+        ConfigKeyType::ObjectList,
+        "Webhooks notified of session and scheduler events",
+    ),
+];
+
+pub const DEPRECATED_KEYS: &[DeprecatedKey] = &[DeprecatedKey {
+    key: "GOOSE_DISABLE_KEYRING",
+    hint: "it is only read from the environment; set GOOSE_SECRET_BACKEND=file or encrypted_file instead",
+}];
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    UnknownKey,
+    WrongType,
+    Deprecated,
+}
+
+/// Something about a configuration value worth telling the user
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ValidationFinding {
+    pub key: String,
+    pub severity: FindingSeverity,
+    pub kind: FindingKind,
+    pub message: String,
+}
+
+pub fn known_key(key: &str) -> Option<&'static ConfigKeySpec> {
+    KNOWN_KEYS.iter().find(|spec| spec.key == key)
+}
+
+/// The settings of the available providers, such as OPENAI_HOST
+pub fn provider_keys() -> HashSet<String> {
+    crate::providers::providers()
+        .into_iter()
+        .flat_map(|provider| provider.config_keys.into_iter().map(|key| key.name))
+        .collect()
+}
+
+/// Read a value given on the command line for a key: as text for keys holding strings,
+/// otherwise as JSON when it parses, so `50` is a number and `{"a": 1}` a mapping
+pub fn parse_value(key: &str, raw: &str) -> Value {
+    let holds_string = known_key(key).is_some_and(|spec| {
+        matches!(
+            spec.key_type,
+            ConfigKeyType::String | ConfigKeyType::Choice(_)
+        )
+    });
+    if holds_string {
+        return Value::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// An actionable message for a known key holding a value of the wrong type
+pub fn type_error(key: &str, value: &Value) -> Option<String> {
+    let spec = known_key(key)?;
+    (!spec.key_type.accepts(value)).then(|| {
+        format!(
+            "{} should be {} but is {}",
+            key,
+            spec.key_type.describe(),
+            value
+        )
+    })
+}
+
+/// Check configuration values against the known keys. `extra_keys` are other keys known to be
+/// valid, such as the settings of the available providers
+pub fn validate_values(
+    values: &HashMap<String, Value>,
+    extra_keys: &HashSet<String>,
+) -> Vec<ValidationFinding> {
+    let mut keys: Vec<&String> = values.keys().collect();
+    keys.sort();
+
+    let mut findings = Vec::new();
+    for key in keys {
+        if let Some(deprecated) = DEPRECATED_KEYS.iter().find(|d| d.key == key) {
+            findings.push(ValidationFinding {
+                key: key.clone(),
+                severity: FindingSeverity::Warning,
+                kind: FindingKind::Deprecated,
+                message: format!("{} is deprecated: {}", key, deprecated.hint),
+            });
+        } else if known_key(key).is_some() {
+            if let Some(message) = type_error(key, &values[key]) {
+                findings.push(ValidationFinding {
+                    key: key.clone(),
+                    severity: FindingSeverity::Error,
+                    kind: FindingKind::WrongType,
+                    message,
+                });
+            }
+        } else if !extra_keys.contains(key) {
+            let suggestion = KNOWN_KEYS
+                .iter()
+                .map(|spec| spec.key.to_string())
+                .chain(extra_keys.iter().cloned())
+                .find(|known| known.eq_ignore_ascii_case(key));
+            findings.push(ValidationFinding {
+                key: key.clone(),
+                severity: FindingSeverity::Warning,
+                kind: FindingKind::UnknownKey,
+                message: match suggestion {
+                    Some(known) => format!("Unknown key {}, did you mean {}?", key, known),
+                    None => format!("Unknown key {}", key),
+                },
+            });
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("GOOSE_MAX_TURNS", "50"), json!(50));
+        assert_eq!(
+            parse_value("GOOSE_CONTEXT_LIMIT", "128000"),
+            json!("128000")
+        );
+        assert_eq!(parse_value("GOOSE_MODEL", "true"), json!("true"));
+        assert_eq!(parse_value("SOME_SETTING", "not json"), json!("not json"));
+        assert_eq!(
+            type_error("GOOSE_MODE", &parse_value("GOOSE_MODE", "auto")),
+            None
+        );
+        assert!(type_error("GOOSE_MODE", &parse_value("GOOSE_MODE", "yolo")).is_some());
+    }
+
+    #[test]
+    fn test_validate_values() {
+        let values: HashMap<String, Value> = [
+            ("GOOSE_MODE", json!("auto")),
+            ("GOOSE_MAX_TURNS", json!("fifty")),
+            ("GOOSE_ENABLE_ROUTER", json!("true")),
+            ("GOOSE_CLI_SHOW_COST", json!(true)),
+            ("goose_model", json!("gpt-4o")),
+            ("OPENAI_HOST", json!("https://api.openai.com")),
+            ("GOOSE_DISABLE_KEYRING", json!(true)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let extra = HashSet::from(["OPENAI_HOST".to_string()]);
+
+        let findings = validate_values(&values, &extra);
+        assert_eq!(findings.len(), 3);
+        assert_eq!(findings[0].key, "GOOSE_DISABLE_KEYRING");
+        assert_eq!(findings[0].kind, FindingKind::Deprecated);
+        assert_eq!(findings[1].kind, FindingKind::WrongType);
+        assert_eq!(findings[1].severity, FindingSeverity::Error);
+        assert_eq!(
+            findings[1].message,
+            "GOOSE_MAX_TURNS should be a whole number but is \"fifty\""
+        );
+        assert_eq!(
+            findings[2].message,
+            "Unknown key goose_model, did you mean GOOSE_MODEL?"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! Content policy filters applied to tool outputs and assistant messages before they reach the
+//! transcript, for deployments where transcripts are logged. Filters are configured under
+//! `GOOSE_CONTENT_FILTERS`, which a workspace can set in its `.goose/config.yaml`:
+//!
+//! ```yaml
+//! GOOSE_CONTENT_FILTERS:
+//!   rules:
+//!     - name: email
+//!       pattern: '[\w.+-]+@[\w-]+\.[\w.]+'
+//!       action: redact
+//!     - name: internal-host
+//!       pattern: '\.corp\.example\.com'
+//!       action: block
+//!       targets: [tool_output]
+//!   classifier:
+//!     command: pii-classifier
+//!     action: redact
+//! ```
+//!
+//! The classifier is a local command that gets the text on stdin and answers with
+//! `{"flagged": true, "reason": "..."}` on stdout. Other filters can be added to an agent with
+//! `Agent::add_content_filter`. Streamed responses are filtered chunk by chunk, so a rule
+//! matching across chunks is missed.
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use rmcp::model::RawContent;
+use serde::{Deserialize, Serialize};
+use std::ops::{DerefMut, Range};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent};
+
+pub const CONTENT_FILTERS_KEY: &str = "GOOSE_CONTENT_FILTERS";
+const DEFAULT_CLASSIFIER_TIMEOUT_SECS: u64 = 10;
+
+/// What happens to content a filter matches, from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Logged, the content is kept
+    Warn,
+    /// The matched text is replaced
+    Redact,
+    /// The whole text is replaced with a notice
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterTarget {
+    ToolOutput,
+    AssistantMessage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterRule {
+    pub name: String,
+    pub pattern: String,
+    pub action: FilterAction,
+    /// What the rule applies to, everything when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<FilterTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassifierConfig {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    pub action: FilterAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentFiltersConfig {
+    #[serde(default)]
+    pub rules: Vec<FilterRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classifier: Option<ClassifierConfig>,
+}
+
+/// What a filter found in a piece of text
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterMatch {
+    pub filter: String,
+    pub action: FilterAction,
+    /// Byte ranges of the matched text, the whole text when empty
+    pub ranges: Vec<Range<usize>>,
+    pub reason: Option<String>,
+}
+
+#[async_trait]
+pub trait ContentFilter: Send + Sync {
+    async fn check(&self, text: &str, target: FilterTarget) -> Option<FilterMatch>;
+}
+
+pub struct RegexFilter {
+    rule: FilterRule,
+    regex: Regex,
+}
+
+impl RegexFilter {
+    pub fn new(rule: FilterRule) -> Result<Self> {
+        let regex = Regex::new(&rule.pattern)
+            .map_err(|e| anyhow!("Invalid pattern in content filter '{}': {}", rule.name, e))?;
+        Ok(Self { rule, regex })
+    }
+}
+
+#[async_trait]
+impl ContentFilter for RegexFilter {
+    async fn check(&self, text: &str, target: FilterTarget) -> Option<FilterMatch> {
+        if !self.rule.targets.is_empty() && !self.rule.targets.contains(&target) {
+            return None;
+        }
+        let ranges: Vec<_> = self.regex.find_iter(text).map(|m| m.range()).collect();
+        if ranges.is_empty() {
+            return None;
+        }
+        Some(FilterMatch {
+            filter: self.rule.name.clone(),
+            action: self.rule.action,
+            ranges,
+            reason: None,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClassifierVerdict {
+    flagged: bool,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Runs a local classifier command on each text
+pub struct ClassifierFilter {
+    config: ClassifierConfig,
+}
+
+impl ClassifierFilter {
+    pub fn new(config: ClassifierConfig) -> Self {
+        Self { config }
+    }
+
+    async fn classify(&self, text: &str) -> Result<ClassifierVerdict> {
+        let mut child = tokio::process::Command::new(&self.config.command)
+            .args(&self.config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        let timeout = Duration::from_secs(
+            self.config
+                .timeout_secs
+                .unwrap_or(DEFAULT_CLASSIFIER_TIMEOUT_SECS),
+        );
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| anyhow!("timed out after {} seconds", timeout.as_secs()))??;
+        if !output.status.success() {
+            return Err(anyhow!("exited with {}", output.status));
+        }
+        Ok(serde_json::from_slice(&output.stdout)?)
+    }
+}
+
+#[async_trait]
+impl ContentFilter for ClassifierFilter {
+    async fn check(&self, text: &str, _target: FilterTarget) -> Option<FilterMatch> {
+        let flagged = |reason| FilterMatch {
+            filter: "classifier".to_string(),
+            action: self.config.action,
+            ranges: Vec::new(),
+            reason,
+        };
+        match self.classify(text).await {
+            Ok(verdict) => verdict.flagged.then(|| flagged(verdict.reason)),
+            Err(e) => {
+                tracing::warn!("Content classifier {} failed: {}", self.config.command, e);
+                // Content that must be blocked is not let through unchecked
+                (self.config.action == FilterAction::Block)
+                    .then(|| flagged(Some("the classifier failed".to_string())))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ContentFilterPipeline {
+    filters: Vec<Arc<dyn ContentFilter>>,
+}
+
+impl ContentFilterPipeline {
+    pub fn new(filters: Vec<Arc<dyn ContentFilter>>) -> Self {
+        Self { filters }
+    }
+
+    /// The filters configured for the current workspace. Rules that don't compile are logged
+    /// and skipped
+    pub fn from_config() -> Self {
+        let config: ContentFiltersConfig = Config::global()
+            .get_param(CONTENT_FILTERS_KEY)
+            .unwrap_or_default();
+        let mut filters: Vec<Arc<dyn ContentFilter>> = Vec::new();
+        for rule in config.rules {
+            match RegexFilter::new(rule) {
+                Ok(filter) => filters.push(Arc::new(filter)),
+                Err(e) => tracing::warn!("{}", e),
+            }
+        }
+        if let Some(classifier) = config.classifier {
+            filters.push(Arc::new(ClassifierFilter::new(classifier)));
+        }
+        Self { filters }
+    }
+
+    pub fn with_filters(
+        mut self,
+        filters: impl IntoIterator<Item = Arc<dyn ContentFilter>>,
+    ) -> Self {
+        self.filters.extend(filters);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// The text with every filter applied. A block replaces all of it, otherwise matches of
+    /// redacting filters are replaced
+    pub async fn filter_text(&self, text: &str, target: FilterTarget) -> String {
+        let mut matches = Vec::new();
+        for filter in &self.filters {
+            if let Some(found) = filter.check(text, target).await {
+                matches.push(found);
+            }
+        }
+        for found in &matches {
+            tracing::warn!(
+                filter = %found.filter,
+                action = ?found.action,
+                target = ?target,
+                "Content filter matched{}",
+                found.reason.as_ref().map(|r| format!(": {}", r)).unwrap_or_default()
+            );
+        }
+
+        if let Some(blocked) = matches.iter().find(|m| m.action == FilterAction::Block) {
+            return format!(
+                "[This content was blocked by the content policy filter '{}']",
+                blocked.filter
+            );
+        }
+        let mut redactions: Vec<(Range<usize>, &str)> = Vec::new();
+        for found in matches.iter().filter(|m| m.action == FilterAction::Redact) {
+            if found.ranges.is_empty() {
+                redactions.push((0..text.len(), &found.filter));
+            } else {
+                redactions.extend(
+                    found
+                        .ranges
+                        .iter()
+                        .map(|r| (r.clone(), found.filter.as_str())),
+                );
+            }
+        }
+        redact_ranges(text, redactions)
+    }
+
+    /// The message with its text and the text of its tool results filtered
+    pub async fn filter_message(&self, mut message: Message, target: FilterTarget) -> Message {
+        if self.is_empty() {
+            return message;
+        }
+        for content in message.content.iter_mut() {
+            match content {
+                MessageContent::Text(text) => {
+                    text.text = self.filter_text(&text.text, target).await;
+                }
+                MessageContent::ToolResponse(response) => {
+                    if let Ok(result) = response.tool_result.as_mut() {
+                        for item in result.iter_mut() {
+                            if let RawContent::Text(text) = item.deref_mut() {
+                                text.text = self.filter_text(&text.text, target).await;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        message
+    }
+}
+
+/// Replace the ranges with the name of the filter that matched them. A match overlapping an
+/// earlier one only replaces what is left of it
+fn redact_ranges(text: &str, mut redactions: Vec<(Range<usize>, &str)>) -> String {
+    redactions.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+    let mut filtered = String::with_capacity(text.len());
+    let mut position = 0;
+    for (range, filter) in redactions {
+        if range.end <= position {
+            continue;
+        }
+        let start = range.start.max(position);
+        filtered.push_str(&text[position..start]);
+        filtered.push_str(&format!("[FILTERED:{}]", filter));
+        position = range.end;
+    }
+    filtered.push_str(&text[position..]);
+    filtered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+
+    fn rule(name: &str, pattern: &str, action: FilterAction) -> FilterRule {
+        FilterRule {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            action,
+            targets: Vec::new(),
+        }
+    }
+
+    fn pipeline(rules: Vec<FilterRule>) -> ContentFilterPipeline {
+        ContentFilterPipeline::new(
+            rules
+                .into_iter()
+                .map(|rule| Arc::new(RegexFilter::new(rule).unwrap()) as Arc<dyn ContentFilter>)
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_redact_and_warn() {
+        let pipeline = pipeline(vec![
+            rule("email", r"[\w.]+@[\w.]+", FilterAction::Redact),
+            rule("name", "Ada", FilterAction::Warn),
+        ]);
+        let filtered = pipeline
+            .filter_text(
+                "Mail Ada at ada@example.com",
+                FilterTarget::AssistantMessage,
+            )
+            .await;
+        assert_eq!(filtered, "Mail Ada at [FILTERED:email]");
+    }
+
+    #[tokio::test]
+    async fn test_block_tool_output_only() {
+        let pipeline = pipeline(vec![FilterRule {
+            targets: vec![FilterTarget::ToolOutput],
+            ..rule("host", r"corp\.example\.com", FilterAction::Block)
+        }]);
+        let message = Message::user().with_tool_response(
+            "call_1",
+            Ok(vec![Content::text("db.corp.example.com is up")]),
+        );
+        let filtered = pipeline
+            .filter_message(message, FilterTarget::ToolOutput)
+            .await;
+        assert!(filtered.content[0]
+            .as_tool_response_text()
+            .unwrap()
+            .contains("blocked by the content policy filter 'host'"));
+
+        let text = "see db.corp.example.com";
+        assert_eq!(
+            pipeline
+                .filter_text(text, FilterTarget::AssistantMessage)
+                .await,
+            text
+        );
+    }
+
+    #[test]
+    fn test_redact_overlapping_ranges() {
+        assert_eq!(
+            redact_ranges("abcdef", vec![(1..3, "a"), (2..5, "b")]),
+            "a[FILTERED:a][FILTERED:b]f"
+        );
+    }
+}
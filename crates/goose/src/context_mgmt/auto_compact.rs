@@ -2,7 +2,7 @@ use crate::conversation::message::Message;
 use crate::conversation::Conversation;
 use crate::{
     agents::Agent, config::Config, context_mgmt::get_messages_token_counts_async,
-    token_counter::create_async_token_counter,
+    token_counter::create_async_token_counter_for_model,
 };
 use anyhow::Result;
 use tracing::{debug, info};
@@ -66,12 +66,13 @@ pub async fn check_compaction_needed(
     });
 
     let provider = agent.provider().await?;
-    let context_limit = provider.get_model_config().context_limit();
+    let model_config = provider.get_model_config();
+    let context_limit = model_config.context_limit();
 
     let (current_tokens, token_source) = match session_metadata.and_then(|m| m.total_tokens) {
         Some(tokens) => (tokens as usize, "session metadata"),
         None => {
-            let token_counter = create_async_token_counter()
+            let token_counter = create_async_token_counter_for_model(&model_config)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
             let token_counts = get_messages_token_counts_async(&token_counter, messages);
@@ -163,6 +164,12 @@ pub async fn check_and_compact_messages(
         check_result.usage_ratio * 100.0
     );
 
+    compact_messages(agent, messages).await
+}
+
+/// Summarize the messages regardless of how full the context is. If the most recent message is
+/// a user message it is kept as it is after the summary
+pub async fn compact_messages(agent: &Agent, messages: &[Message]) -> Result<AutoCompactResult> {
     // Check if the most recent message is a user message
     let (messages_to_compact, preserved_user_message) = if let Some(last_message) = messages.last()
     {
@@ -260,6 +267,9 @@ mod tests {
         use std::path::PathBuf;
         crate::session::storage::SessionMetadata {
             message_count,
+            turn_count: 0,
+            provider: None,
+            model: None,
             working_dir: PathBuf::from(working_dir),
             description: "Test session".to_string(),
             schedule_id: Some("test_job".to_string()),
@@ -270,6 +280,15 @@ mod tests {
             accumulated_input_tokens: Some(50),
             accumulated_output_tokens: Some(50),
             todo_content: None,
+            todos: Vec::new(),
+            parent_session_id: None,
+            forked_at: None,
+            tags: Vec::new(),
+            plan: None,
+            checkpoint: None,
+            schedule_run_url: None,
+            coverage: None,
+            attachments: Vec::new(),
         }
     }
 
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use chrono::Utc;
+use tracing::info;
+
+use super::auto_compact::check_compaction_needed;
+use crate::agents::Agent;
+use crate::conversation::message::MessageContent;
+use crate::conversation::Conversation;
+use crate::session::storage::SummaryCheckpoint;
+
+/// The summary in a conversation compacted by `Agent::summarize_context`, the message following
+/// its compaction marker
+pub fn summary_text(conversation: &Conversation) -> Option<String> {
+    conversation
+        .messages()
+        .windows(2)
+        .find(|pair| {
+            pair[0]
+                .content
+                .iter()
+                .any(|content| matches!(content, MessageContent::SummarizationRequested(_)))
+        })
+        .map(|pair| pair[1].as_concat_text())
+}
+
+/// Summarize a resumed conversation that is over the auto-compact threshold, returning the
+/// compacted conversation and the checkpoint to store with it. A conversation that starts from
+/// an earlier checkpoint is summarized together with it, so the summary keeps rolling forward.
+/// Returns None when the conversation fits, which is the common case once a checkpoint is stored
+pub async fn compact_for_resume(
+    agent: &Agent,
+    messages: &Conversation,
+    previous: Option<&SummaryCheckpoint>,
+) -> Result<Option<(Conversation, SummaryCheckpoint)>> {
+    let check = check_compaction_needed(agent, messages.messages(), None, None).await?;
+    if !check.needs_compaction {
+        return Ok(None);
+    }
+
+    info!(
+        "Summarizing resumed conversation ({} of {} tokens)",
+        check.current_tokens, check.context_limit
+    );
+    let (compacted, _, _) = agent.summarize_context(messages.messages()).await?;
+    let Some(summary) = summary_text(&compacted) else {
+        return Ok(None);
+    };
+    let checkpoint = SummaryCheckpoint {
+        summary,
+        summarized_messages: previous.map_or(0, |p| p.summarized_messages) + messages.len(),
+        created_at: Utc::now().timestamp(),
+    };
+    Ok(Some((compacted, checkpoint)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::message::Message;
+
+    #[test]
+    fn test_summary_text() {
+        let compacted = Conversation::new_unvalidated(vec![
+            Message::assistant().with_summarization_requested("Conversation compacted"),
+            Message::user().with_text("We fixed the parser and are writing tests"),
+            Message::assistant().with_text("Continue naturally"),
+        ]);
+        assert_eq!(
+            summary_text(&compacted).as_deref(),
+            Some("We fixed the parser and are writing tests")
+        );
+
+        let plain = Conversation::new_unvalidated(vec![Message::user().with_text("Hello")]);
+        assert!(summary_text(&plain).is_none());
+    }
+}
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use rmcp::model::Tool;
 
-use crate::conversation::message::Message;
+use crate::conversation::message::{Message, MessageContent};
 use crate::{
     providers::base::Provider,
     token_counter::{AsyncTokenCounter, TokenCounter},
@@ -98,3 +98,54 @@ pub fn get_token_counts_async(
         messages: messages_token_count,
     }
 }
+
+/// Estimated tokens of a request, by what they are spent on
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ContextBreakdown {
+    pub system_prompt: usize,
+    pub tool_schemas: usize,
+    /// Messages, except for the output of tools
+    pub conversation: usize,
+    pub tool_outputs: usize,
+}
+
+impl ContextBreakdown {
+    pub fn total(&self) -> usize {
+        self.system_prompt + self.tool_schemas + self.conversation + self.tool_outputs
+    }
+}
+
+/// Split the tokens of a request into the system prompt, the tool schemas, the conversation
+/// and the tool outputs in it
+pub fn get_context_breakdown(
+    token_counter: &AsyncTokenCounter,
+    system_prompt: &str,
+    tools: &[Tool],
+    messages: &[Message],
+) -> ContextBreakdown {
+    let mut breakdown = ContextBreakdown {
+        system_prompt: token_counter.count_tokens(system_prompt),
+        tool_schemas: token_counter.count_tokens_for_tools(tools),
+        ..Default::default()
+    };
+    for message in messages {
+        let (outputs, rest): (Vec<_>, Vec<_>) = message
+            .content
+            .iter()
+            .cloned()
+            .partition(|content| matches!(content, MessageContent::ToolResponse(_)));
+        for (content, count) in [
+            (outputs, &mut breakdown.tool_outputs),
+            (rest, &mut breakdown.conversation),
+        ] {
+            if !content.is_empty() {
+                let part = Message {
+                    content,
+                    ..message.clone()
+                };
+                *count += token_counter.count_chat_tokens("", &[part], &[]);
+            }
+        }
+    }
+    breakdown
+}
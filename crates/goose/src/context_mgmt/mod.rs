@@ -1,4 +1,5 @@
 pub mod auto_compact;
+pub mod checkpoint;
 mod common;
 pub mod summarize;
 pub mod truncate;
@@ -0,0 +1,266 @@
+//! Reports what `fix_conversation` would repair in a transcript and where, so mangled sessions
+//! can be debugged instead of being repaired silently before every request.
+use rmcp::model::Role;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+use super::message::{Message, MessageContent};
+use super::{fix_conversation, Conversation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversationIssueKind {
+    EmptyMessage,
+    DuplicateMessageId,
+    DuplicateToolRequestId,
+    OrphanedToolRequest,
+    OrphanedToolResponse,
+    /// Content that can't appear in a message of its role, like a tool request from the user
+    MisplacedContent,
+    /// Two messages in a row from the same role
+    RoleViolation,
+    LeadingAssistantMessage,
+    TrailingAssistantMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ConversationIssue {
+    pub kind: ConversationIssueKind,
+    /// Index of the message in the transcript as stored
+    pub message_index: usize,
+    pub message_id: Option<String>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConversationReport {
+    pub issues: Vec<ConversationIssue>,
+    /// What repairing the conversation changes, in the order the repairs are made
+    pub changes: Vec<String>,
+    pub message_count: usize,
+    pub repaired_message_count: usize,
+}
+
+impl ConversationReport {
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty() && self.changes.is_empty()
+    }
+}
+
+fn role_name(message: &Message) -> &'static str {
+    match message.role {
+        Role::User if message.is_tool_response() => "tool",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+    }
+}
+
+/// Find the issues in a transcript and the changes repairing it makes
+pub fn diagnose_conversation(messages: &[Message]) -> ConversationReport {
+    let mut issues = Vec::new();
+    let mut issue = |kind, index: usize, detail: String| {
+        issues.push(ConversationIssue {
+            kind,
+            message_index: index,
+            message_id: messages[index].id.clone(),
+            detail,
+        })
+    };
+
+    let mut message_ids: HashMap<&str, usize> = HashMap::new();
+    let mut tool_requests: HashMap<&str, usize> = HashMap::new();
+    let mut answered: HashSet<&str> = HashSet::new();
+    let mut previous_role: Option<&str> = None;
+
+    for (index, message) in messages.iter().enumerate() {
+        if message.content.is_empty() {
+            issue(
+                ConversationIssueKind::EmptyMessage,
+                index,
+                "Message has no content".to_string(),
+            );
+        }
+        if let Some(id) = message.id.as_deref() {
+            if let Some(first) = message_ids.insert(id, index) {
+                issue(
+                    ConversationIssueKind::DuplicateMessageId,
+                    index,
+                    format!("Message id '{}' is also used by message {}", id, first),
+                );
+            }
+        }
+
+        for content in &message.content {
+            match (&message.role, content) {
+                (Role::Assistant, MessageContent::ToolRequest(request)) => {
+                    if let Some(first) = tool_requests.insert(&request.id, index) {
+                        issue(
+                            ConversationIssueKind::DuplicateToolRequestId,
+                            index,
+                            format!(
+                                "Tool request '{}' is also made in message {}",
+                                request.id, first
+                            ),
+                        );
+                    }
+                }
+                (Role::User, MessageContent::ToolResponse(response)) => {
+                    if tool_requests.contains_key(response.id.as_str()) {
+                        answered.insert(&response.id);
+                    } else {
+                        issue(
+                            ConversationIssueKind::OrphanedToolResponse,
+                            index,
+                            format!(
+                                "Tool response '{}' has no earlier tool request",
+                                response.id
+                            ),
+                        );
+                    }
+                }
+                (Role::User, MessageContent::ToolRequest(request)) => issue(
+                    ConversationIssueKind::MisplacedContent,
+                    index,
+                    format!("User message contains tool request '{}'", request.id),
+                ),
+                (Role::User, MessageContent::ToolConfirmationRequest(request)) => issue(
+                    ConversationIssueKind::MisplacedContent,
+                    index,
+                    format!(
+                        "User message contains tool confirmation request '{}'",
+                        request.id
+                    ),
+                ),
+                (Role::User, MessageContent::Thinking(_) | MessageContent::RedactedThinking(_)) => {
+                    issue(
+                        ConversationIssueKind::MisplacedContent,
+                        index,
+                        "User message contains thinking content".to_string(),
+                    )
+                }
+                (Role::Assistant, MessageContent::ToolResponse(response)) => issue(
+                    ConversationIssueKind::MisplacedContent,
+                    index,
+                    format!("Assistant message contains tool response '{}'", response.id),
+                ),
+                (Role::Assistant, MessageContent::FrontendToolRequest(request)) => issue(
+                    ConversationIssueKind::MisplacedContent,
+                    index,
+                    format!(
+                        "Assistant message contains frontend tool request '{}'",
+                        request.id
+                    ),
+                ),
+                _ => {}
+            }
+        }
+
+        if !message.content.is_empty() {
+            let role = role_name(message);
+            if previous_role == Some(role) {
+                issue(
+                    ConversationIssueKind::RoleViolation,
+                    index,
+                    format!("Follows another {} message", role),
+                );
+            }
+            previous_role = Some(role);
+        }
+    }
+
+    let mut orphaned: Vec<_> = tool_requests
+        .iter()
+        .filter(|(id, _)| !answered.contains(*id))
+        .map(|(id, index)| (*index, *id))
+        .collect();
+    orphaned.sort();
+    for (index, id) in orphaned {
+        issue(
+            ConversationIssueKind::OrphanedToolRequest,
+            index,
+            format!("Tool request '{}' has no tool response", id),
+        );
+    }
+
+    let with_content: Vec<usize> = (0..messages.len())
+        .filter(|index| !messages[*index].content.is_empty())
+        .collect();
+    if let Some(&first) = with_content.first() {
+        if messages[first].role == Role::Assistant {
+            issue(
+                ConversationIssueKind::LeadingAssistantMessage,
+                first,
+                "Conversation starts with an assistant message".to_string(),
+            );
+        }
+    }
+    if let Some(&last) = with_content.last() {
+        if messages[last].role == Role::Assistant {
+            issue(
+                ConversationIssueKind::TrailingAssistantMessage,
+                last,
+                "Conversation ends with an assistant message".to_string(),
+            );
+        }
+    }
+
+    let (repaired, changes) = fix_conversation(Conversation::new_unvalidated(messages.to_vec()));
+    ConversationReport {
+        issues,
+        changes,
+        message_count: messages.len(),
+        repaired_message_count: repaired.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::tool::ToolCall;
+    use rmcp::model::Content;
+    use serde_json::json;
+
+    #[test]
+    fn test_diagnose_healthy_conversation() {
+        let messages = vec![
+            Message::user().with_text("List the files"),
+            Message::assistant()
+                .with_tool_request("call_1", Ok(ToolCall::new("shell", json!({"cmd": "ls"})))),
+            Message::user().with_tool_response("call_1", Ok(vec![Content::text("a.txt")])),
+            Message::assistant().with_text("There is one file"),
+            Message::user().with_text("Thanks"),
+        ];
+        let report = diagnose_conversation(&messages);
+        assert!(report.is_healthy(), "{:?}", report);
+        assert_eq!(report.repaired_message_count, 5);
+    }
+
+    #[test]
+    fn test_diagnose_mangled_conversation() {
+        let messages = vec![
+            Message::user().with_text("Hello"),
+            Message::user().with_tool_response("call_9", Ok(vec![Content::text("late")])),
+            Message::assistant()
+                .with_tool_request("call_1", Ok(ToolCall::new("shell", json!({"cmd": "ls"})))),
+        ];
+        let report = diagnose_conversation(&messages);
+        let found: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.kind, issue.message_index))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (ConversationIssueKind::OrphanedToolResponse, 1),
+                (ConversationIssueKind::OrphanedToolRequest, 2),
+                (ConversationIssueKind::TrailingAssistantMessage, 2),
+            ]
+        );
+        assert!(report
+            .changes
+            .contains(&"Removed orphaned tool response 'call_9'".to_string()));
+        assert_eq!(report.repaired_message_count, 1);
+    }
+}
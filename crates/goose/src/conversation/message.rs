@@ -364,6 +364,53 @@ impl From<PromptMessage> for Message {
     }
 }
 
+/// How an assistant message was generated
+#[derive(ToSchema, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Time from sending the request to the end of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<i32>,
+    /// `tool_calls` when the model asked for tools, `stop` when it answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+}
+
+impl GenerationMetadata {
+    /// One line summary, like `gpt-4o via openai, 1.2s, 1200 in / 300 out tokens, stop`
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        match (&self.model, &self.provider) {
+            (Some(model), Some(provider)) => parts.push(format!("{} via {}", model, provider)),
+            (Some(name), None) | (None, Some(name)) => parts.push(name.clone()),
+            (None, None) => {}
+        }
+        if let Some(latency_ms) = self.latency_ms {
+            parts.push(format!("{:.1}s", latency_ms as f64 / 1000.0));
+        }
+        if self.input_tokens.is_some() || self.output_tokens.is_some() {
+            let count = |tokens: Option<i32>| tokens.map_or("?".to_string(), |t| t.to_string());
+            parts.push(format!(
+                "{} in / {} out tokens",
+                count(self.input_tokens),
+                count(self.output_tokens)
+            ));
+        }
+        if let Some(finish_reason) = &self.finish_reason {
+            parts.push(finish_reason.clone());
+        }
+        parts.join(", ")
+    }
+}
+
 #[derive(ToSchema, Clone, PartialEq, Serialize, Deserialize)]
 /// A message to or from an LLM
 #[serde(rename_all = "camelCase")]
@@ -374,6 +421,9 @@ pub struct Message {
     pub created: i64,
     #[serde(deserialize_with = "deserialize_sanitized_content")]
     pub content: Vec<MessageContent>,
+    /// Set on assistant messages by the agent loop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<GenerationMetadata>,
 }
 
 impl fmt::Debug for Message {
@@ -400,6 +450,7 @@ impl Message {
             role,
             created,
             content,
+            metadata: None,
         }
     }
     pub fn debug(&self) -> String {
@@ -413,6 +464,7 @@ impl Message {
             role: Role::User,
             created: Utc::now().timestamp(),
             content: Vec::new(),
+            metadata: None,
         }
     }
 
@@ -423,6 +475,7 @@ impl Message {
             role: Role::Assistant,
             created: Utc::now().timestamp(),
             content: Vec::new(),
+            metadata: None,
         }
     }
 
@@ -431,6 +484,11 @@ impl Message {
         self
     }
 
+    pub fn with_metadata(mut self, metadata: GenerationMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
     /// Add any MessageContent to the message
     pub fn with_content(mut self, content: MessageContent) -> Self {
         self.content.push(content);
@@ -591,7 +649,7 @@ impl Message {
 
 #[cfg(test)]
 mod tests {
-    use crate::conversation::message::{Message, MessageContent};
+    use crate::conversation::message::{GenerationMetadata, Message, MessageContent};
     use crate::conversation::*;
     use mcp_core::ToolCall;
     use rmcp::model::{
@@ -655,6 +713,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_generation_metadata() {
+        let message = Message::assistant()
+            .with_text("Done")
+            .with_metadata(GenerationMetadata {
+                provider: Some("openai".to_string()),
+                model: Some("gpt-4o".to_string()),
+                latency_ms: Some(1234),
+                input_tokens: Some(1200),
+                output_tokens: Some(300),
+                finish_reason: Some("stop".to_string()),
+            });
+
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["metadata"]["latencyMs"], 1234);
+        let parsed: Message = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.metadata, message.metadata);
+        assert_eq!(
+            parsed.metadata.unwrap().summary(),
+            "gpt-4o via openai, 1.2s, 1200 in / 300 out tokens, stop"
+        );
+
+        // Messages stored before metadata existed have none
+        let value = serde_json::to_value(Message::user().with_text("Hi")).unwrap();
+        assert!(value.get("metadata").is_none());
+    }
+
     #[test]
     fn test_error_serialization() {
         let message = Message::assistant().with_tool_request(
@@ -4,8 +4,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use thiserror::Error;
 
+pub mod diagnostics;
 pub mod message;
 mod tool_result_serde;
+pub mod tool_result_visibility;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation(Vec<Message>);
@@ -46,6 +48,9 @@ impl Conversation {
             .last_mut()
             .filter(|m| m.id.is_some() && m.id == message.id)
         {
+            if message.metadata.is_some() {
+                last.metadata = message.metadata.clone();
+            }
             match (last.content.last_mut(), message.content.last()) {
                 (Some(MessageContent::Text(ref mut last)), Some(MessageContent::Text(new)))
                     if message.content.len() == 1 =>
@@ -102,6 +107,22 @@ impl Conversation {
         self.0.clear();
     }
 
+    /// Remove the last exchange: the most recent message written by the user and everything
+    /// after it, including tool requests and responses. Returns the removed messages.
+    pub fn undo_last_exchange(&mut self) -> Vec<Message> {
+        let start = self.0.iter().rposition(|message| {
+            message.role == Role::User
+                && message
+                    .content
+                    .iter()
+                    .any(|c| matches!(c, MessageContent::Text(_) | MessageContent::Image(_)))
+        });
+        match start {
+            Some(start) => self.0.split_off(start),
+            None => Vec::new(),
+        }
+    }
+
     fn validate(self) -> Result<Self, InvalidConversation> {
         let (_messages, issues) = fix_messages(self.0.clone());
         if !issues.is_empty() {
@@ -517,6 +538,28 @@ mod tests {
         assert!(issues[1].contains("Merged consecutive assistant messages"));
     }
 
+    #[test]
+    fn test_undo_last_exchange() {
+        let mut conversation = Conversation::new_unvalidated(vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("first answer"),
+            Message::user().with_text("list files"),
+            Message::assistant()
+                .with_tool_request("ls_1", Ok(ToolCall::new("developer__shell", json!({})))),
+            Message::user().with_tool_response("ls_1", Ok(vec![])),
+            Message::assistant().with_text("here they are"),
+        ]);
+
+        let removed = conversation.undo_last_exchange();
+        assert_eq!(removed.len(), 4);
+        assert_eq!(removed[0].as_concat_text(), "list files");
+        assert_eq!(conversation.len(), 2);
+
+        assert_eq!(conversation.undo_last_exchange().len(), 2);
+        assert!(conversation.is_empty());
+        assert!(conversation.undo_last_exchange().is_empty());
+    }
+
     #[test]
     fn test_tool_response_effective_role() {
         let messages = vec![
@@ -0,0 +1,114 @@
+//! Which parts of a tool result are meant for the user, from the audience and priority
+//! annotations tools put on their content. Shared by the CLI renderer and the server's event
+//! stream so every client hides the same content.
+use rmcp::model::{Content, Role};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::message::{Message, MessageContent};
+use crate::config::Config;
+
+/// Content below this priority is not shown to the user
+pub const MIN_PRIORITY_KEY: &str = "GOOSE_CLI_MIN_PRIORITY";
+pub const DEFAULT_MIN_PRIORITY: f32 = 0.5;
+
+pub fn min_priority() -> f32 {
+    Config::global()
+        .get_param::<f32>(MIN_PRIORITY_KEY)
+        .unwrap_or(DEFAULT_MIN_PRIORITY)
+}
+
+/// Whether content is shown to the user. Content for the assistant only is never shown, and
+/// content without a priority only in debug mode
+pub fn is_shown_to_user(content: &Content, min_priority: f32, debug: bool) -> bool {
+    if content
+        .audience()
+        .is_some_and(|audience| !audience.contains(&Role::User))
+    {
+        return false;
+    }
+    match content.priority() {
+        Some(priority) => priority >= min_priority,
+        None => debug,
+    }
+}
+
+/// The annotations of one content item of a tool result
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolResultAnnotation {
+    pub tool_request_id: String,
+    /// Index of the content in the tool result
+    pub index: usize,
+    pub priority: Option<f32>,
+    /// `user`, `assistant` or both. None when the content is for everyone
+    pub audience: Option<Vec<String>>,
+    /// Whether the content is shown with the configured minimum priority outside debug mode
+    pub shown: bool,
+}
+
+fn role_name(role: &Role) -> String {
+    match role {
+        Role::User => "user".to_string(),
+        Role::Assistant => "assistant".to_string(),
+    }
+}
+
+/// Annotations of every successful tool result in the message
+pub fn annotate_tool_results(message: &Message, min_priority: f32) -> Vec<ToolResultAnnotation> {
+    message
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            MessageContent::ToolResponse(response) => response
+                .tool_result
+                .as_ref()
+                .ok()
+                .map(|contents| (response.id.as_str(), contents)),
+            _ => None,
+        })
+        .flat_map(|(id, contents)| {
+            contents
+                .iter()
+                .enumerate()
+                .map(move |(index, content)| ToolResultAnnotation {
+                    tool_request_id: id.to_string(),
+                    index,
+                    priority: content.priority(),
+                    audience: content
+                        .audience()
+                        .map(|audience| audience.iter().map(role_name).collect()),
+                    shown: is_shown_to_user(content, min_priority, false),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_tool_results() {
+        let message = Message::user().with_tool_response(
+            "call_1",
+            Ok(vec![
+                Content::text("for the model").with_audience(vec![Role::Assistant]),
+                Content::text("summary")
+                    .with_audience(vec![Role::User])
+                    .with_priority(0.9),
+                Content::text("details").with_priority(0.2),
+                Content::text("unannotated"),
+            ]),
+        );
+
+        let annotations = annotate_tool_results(&message, 0.5);
+        let shown: Vec<_> = annotations.iter().map(|a| a.shown).collect();
+        assert_eq!(shown, vec![false, true, false, false]);
+        assert_eq!(annotations[1].audience, Some(vec!["user".to_string()]));
+        assert_eq!(annotations[2].priority, Some(0.2));
+
+        assert!(is_shown_to_user(&Content::text("unannotated"), 0.5, true));
+        assert!(annotate_tool_results(&Message::user().with_text("hi"), 0.5).is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod agents;
 pub mod config;
+pub mod content_filter;
 pub mod context_mgmt;
 pub mod conversation;
 pub mod model;
@@ -9,15 +10,18 @@ pub mod prompt_template;
 pub mod providers;
 pub mod recipe;
 pub mod recipe_deeplink;
+pub mod redaction;
 pub mod scheduler;
 pub mod scheduler_factory;
 pub mod scheduler_trait;
 pub mod session;
+pub mod shutdown;
 pub mod temporal_scheduler;
 pub mod token_counter;
 pub mod tool_monitor;
 pub mod tracing;
 pub mod utils;
+pub mod webhooks;
 
 #[cfg(test)]
 mod cron_test;
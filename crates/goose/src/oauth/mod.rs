@@ -7,16 +7,26 @@ use rmcp::transport::auth::OAuthState;
 use rmcp::transport::AuthorizationManager;
 use serde::Deserialize;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 use tokio::sync::{oneshot, Mutex};
 use tracing::warn;
 
-use crate::oauth::persist::{clear_credentials, load_cached_state, save_credentials};
+use crate::oauth::persist::{
+    clear_credentials, current_credentials, load_cached_state, save_credentials, store_credentials,
+    SerializableCredentials,
+};
 
 mod persist;
 
 const CALLBACK_TEMPLATE: &str = include_str!("oauth_callback.html");
 
+/// How often the tokens of a connected extension are checked for a refresh
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Called with the credentials of an extension whenever its tokens were refreshed
+type CredentialStore = Arc<dyn Fn(&SerializableCredentials) -> anyhow::Result<()> + Send + Sync>;
+
 #[derive(Clone)]
 struct AppState {
     code_receiver: Arc<Mutex<Option<oneshot::Sender<String>>>>,
@@ -29,21 +39,92 @@ struct CallbackParams {
     state: Option<String>,
 }
 
-pub async fn oauth_flow(
+/// Authorize goose with a remote MCP server from the cached credentials alone, refreshing
+/// them, without involving the user
+pub async fn cached_oauth(
     mcp_server_url: &String,
     name: &String,
 ) -> Result<AuthorizationManager, anyhow::Error> {
-    if let Ok(oauth_state) = load_cached_state(mcp_server_url, name).await {
-        if let Some(authorization_manager) = oauth_state.into_authorization_manager() {
-            if authorization_manager.refresh_token().await.is_ok() {
-                return Ok(authorization_manager);
+    let oauth_state = load_cached_state(mcp_server_url, name).await?;
+    if let Some(authorization_manager) = oauth_state.into_authorization_manager() {
+        if authorization_manager.refresh_token().await.is_ok() {
+            if let Err(e) = save_credentials(name, &authorization_manager).await {
+                warn!("Failed to save refreshed credentials: {}", e);
             }
+            return Ok(authorization_manager);
         }
+    }
+
+    if let Err(e) = clear_credentials(name) {
+        warn!("error clearing bad credentials: {}", e);
+    }
+    Err(anyhow::anyhow!(
+        "The cached credentials of {} expired",
+        name
+    ))
+}
+
+/// Keep the keyring up to date with the tokens the transport refreshes while the extension is
+/// connected. Stops once the transport dropped the authorization manager
+pub fn persist_refreshed_credentials(name: &str, auth_manager: &Arc<Mutex<AuthorizationManager>>) {
+    let name = name.to_string();
+    let store: CredentialStore = Arc::new(move |credentials| {
+        store_credentials(&name, credentials).map_err(|e| anyhow::anyhow!(e.to_string()))
+    });
+    tokio::spawn(watch_credentials(
+        Arc::downgrade(auth_manager),
+        store,
+        REFRESH_CHECK_INTERVAL,
+    ));
+}
 
-        if let Err(e) = clear_credentials(name) {
-            warn!("error clearing bad credentials: {}", e);
+async fn credentials_of(
+    auth_manager: &Weak<Mutex<AuthorizationManager>>,
+) -> Option<Option<SerializableCredentials>> {
+    let auth_manager = auth_manager.upgrade()?;
+    let auth_manager = auth_manager.lock().await;
+    Some(current_credentials(&auth_manager).await.ok())
+}
+
+async fn watch_credentials(
+    auth_manager: Weak<Mutex<AuthorizationManager>>,
+    store: CredentialStore,
+    interval: Duration,
+) {
+    // The credentials the manager started with were stored when it was authorized
+    let Some(credentials) = credentials_of(&auth_manager).await else {
+        return;
+    };
+    let mut stored_token = credentials.and_then(|c| c.access_token().map(str::to_string));
+
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(credentials) = credentials_of(&auth_manager).await else {
+            return;
+        };
+        let Some(credentials) = credentials else {
+            continue;
+        };
+        let token = credentials.access_token().map(str::to_string);
+        if token.is_some() && token != stored_token {
+            match store(&credentials) {
+                Ok(()) => stored_token = token,
+                Err(e) => warn!("Failed to save refreshed credentials: {}", e),
+            }
         }
     }
+}
+
+/// Authorize goose with a remote MCP server using the MCP authorization flow: cached
+/// credentials are refreshed if possible, otherwise the client registers itself and the
+/// user authorizes it in the browser with an authorization code and PKCE
+pub async fn oauth_flow(
+    mcp_server_url: &String,
+    name: &String,
+) -> Result<AuthorizationManager, anyhow::Error> {
+    if let Ok(authorization_manager) = cached_oauth(mcp_server_url, name).await {
+        return Ok(authorization_manager);
+    }
 
     let (code_sender, code_receiver) = oneshot::channel::<String>();
     let app_state = AppState {
@@ -89,13 +170,104 @@ pub async fn oauth_flow(
     let auth_code = code_receiver.await?;
     oauth_state.handle_callback(&auth_code).await?;
 
-    if let Err(e) = save_credentials(name, &oauth_state).await {
-        warn!("Failed to save credentials: {}", e);
-    }
-
     let auth_manager = oauth_state
         .into_authorization_manager()
         .ok_or_else(|| anyhow::anyhow!("Failed to get authorization manager"))?;
 
+    if let Err(e) = save_credentials(name, &auth_manager).await {
+        warn!("Failed to save credentials: {}", e);
+    }
+
     Ok(auth_manager)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oauth2::{basic::BasicTokenType, EmptyExtraTokenFields, StandardTokenResponse};
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn authorized_manager(server: &MockServer) -> AuthorizationManager {
+        let token_response: StandardTokenResponse<EmptyExtraTokenFields, BasicTokenType> =
+            serde_json::from_value(json!({
+                "access_token": "first",
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "refresh_token": "refresh-1",
+            }))
+            .unwrap();
+        let mut oauth_state = OAuthState::new(server.uri(), None).await.unwrap();
+        oauth_state
+            .set_credentials("goose-client", token_response)
+            .await
+            .unwrap();
+        oauth_state.into_authorization_manager().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_refreshed_tokens_are_persisted() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/.well-known/oauth-authorization-server"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "issuer": server.uri(),
+                "authorization_endpoint": format!("{}/authorize", server.uri()),
+                "token_endpoint": format!("{}/token", server.uri()),
+                "registration_endpoint": format!("{}/register", server.uri()),
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "refreshed",
+                "token_type": "bearer",
+                "expires_in": 3600,
+                "refresh_token": "refresh-2",
+            })))
+            .mount(&server)
+            .await;
+
+        let auth_manager = Arc::new(Mutex::new(authorized_manager(&server).await));
+        let stored = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let store: CredentialStore = {
+            let stored = stored.clone();
+            Arc::new(move |credentials| {
+                stored.lock().unwrap().push(credentials.clone());
+                Ok(())
+            })
+        };
+        let watcher = tokio::spawn(watch_credentials(
+            Arc::downgrade(&auth_manager),
+            store,
+            Duration::from_millis(10),
+        ));
+
+        // Nothing changed yet, so nothing is stored
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(stored.lock().unwrap().is_empty());
+
+        auth_manager.lock().await.refresh_token().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while stored.lock().unwrap().is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        {
+            let stored = stored.lock().unwrap();
+            assert_eq!(stored.len(), 1);
+            assert_eq!(stored[0].access_token(), Some("refreshed"));
+            assert_eq!(stored[0].client_id, "goose-client");
+        }
+
+        drop(auth_manager);
+        tokio::time::timeout(Duration::from_secs(5), watcher)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -1,6 +1,6 @@
-use oauth2::{basic::BasicTokenType, EmptyExtraTokenFields, StandardTokenResponse};
+use oauth2::{basic::BasicTokenType, EmptyExtraTokenFields, StandardTokenResponse, TokenResponse};
 use reqwest::IntoUrl;
-use rmcp::transport::{auth::OAuthState, AuthError};
+use rmcp::transport::{auth::OAuthState, AuthError, AuthorizationManager};
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
@@ -15,25 +15,45 @@ fn secret_key(name: &str) -> String {
     format!("oauth_creds_{name}")
 }
 
-pub async fn save_credentials(
-    name: &str,
-    oauth_state: &OAuthState,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let config = Config::global();
-    let (client_id, token_response) = oauth_state.get_credentials().await?;
+impl SerializableCredentials {
+    pub fn access_token(&self) -> Option<&str> {
+        self.token_response
+            .as_ref()
+            .map(|token_response| token_response.access_token().secret().as_str())
+    }
+}
 
-    let credentials = SerializableCredentials {
+pub async fn current_credentials(
+    authorization_manager: &AuthorizationManager,
+) -> Result<SerializableCredentials, AuthError> {
+    let (client_id, token_response) = authorization_manager.get_credentials().await?;
+    Ok(SerializableCredentials {
         client_id,
         token_response,
-    };
-
-    let value = serde_json::to_value(&credentials)?;
-    let key = secret_key(name);
-    config.set_secret(&key, value)?;
+    })
+}
 
+/// Store the client registration and tokens in the keyring
+pub fn store_credentials(
+    name: &str,
+    credentials: &SerializableCredentials,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::global();
+    let value = serde_json::to_value(credentials)?;
+    config.set_secret(&secret_key(name), value)?;
     Ok(())
 }
 
+/// Store the credentials of the authorization manager. Refresh tokens are rotated on use, so
+/// this needs to happen after every refresh as well as after authorizing
+pub async fn save_credentials(
+    name: &str,
+    authorization_manager: &AuthorizationManager,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let credentials = current_credentials(authorization_manager).await?;
+    store_credentials(name, &credentials)
+}
+
 async fn load_credentials(
     name: &str,
 ) -> Result<SerializableCredentials, Box<dyn std::error::Error>> {
@@ -163,6 +163,31 @@ pub struct PermissionCheckResult {
     pub denied: Vec<ToolRequest>,
 }
 
+/// Tools that need the user's confirmation the first time they run in a session, whatever the
+/// goose mode or stored permission, because they move data the user may not expect to share
+pub const CONFIRM_FIRST_USE_TOOLS: &[&str] = &[
+    "computercontroller__clipboard_get",
+    "computercontroller__clipboard_set",
+];
+
+/// Move approved calls of first-use tools that haven't been confirmed in this session over to
+/// needing approval. Denied calls stay denied
+pub fn require_first_use_confirmation(
+    result: &mut PermissionCheckResult,
+    confirmed_tools: &HashSet<String>,
+) {
+    let (unconfirmed, approved): (Vec<_>, Vec<_>) = std::mem::take(&mut result.approved)
+        .into_iter()
+        .partition(|request| {
+            request.tool_call.as_ref().is_ok_and(|call| {
+                CONFIRM_FIRST_USE_TOOLS.contains(&call.name.as_str())
+                    && !confirmed_tools.contains(&call.name)
+            })
+        });
+    result.approved = approved;
+    result.needs_approval.extend(unconfirmed);
+}
+
 pub async fn check_tool_permissions(
     candidate_requests: &[ToolRequest],
     mode: &str,
@@ -522,4 +547,32 @@ mod tests {
         assert_eq!(result.needs_approval.len(), 0); // data_fetcher should need approval
         assert_eq!(result.denied.len(), 0); // No tool should be denied in this test
     }
+
+    #[test]
+    fn test_require_first_use_confirmation() {
+        let request = |id: &str, name: &str| ToolRequest {
+            id: id.to_string(),
+            tool_call: ToolResult::Ok(ToolCall {
+                name: name.to_string(),
+                arguments: json!({}),
+            }),
+        };
+        let mut result = PermissionCheckResult {
+            approved: vec![
+                request("tool_1", "computercontroller__clipboard_get"),
+                request("tool_2", "computercontroller__clipboard_set"),
+                request("tool_3", "file_reader"),
+            ],
+            needs_approval: vec![],
+            denied: vec![],
+        };
+        let confirmed: HashSet<String> = ["computercontroller__clipboard_set".to_string()].into();
+
+        require_first_use_confirmation(&mut result, &confirmed);
+
+        let ids =
+            |requests: &[ToolRequest]| requests.iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&result.approved), vec!["tool_2", "tool_3"]);
+        assert_eq!(ids(&result.needs_approval), vec!["tool_1"]);
+    }
 }
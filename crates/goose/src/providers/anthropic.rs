@@ -4,6 +4,7 @@ use async_trait::async_trait;
 use futures::TryStreamExt;
 use reqwest::StatusCode;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::io;
 use tokio::pin;
 use tokio_util::io::StreamReader;
@@ -15,7 +16,7 @@ use super::formats::anthropic::{
     create_request, get_usage, response_to_message, response_to_streaming_message,
 };
 use super::utils::{emit_debug_trace, get_model, map_http_error_to_provider_error};
-use crate::config::custom_providers::CustomProviderConfig;
+use crate::config::custom_providers::{apply_model_params, CustomProviderConfig};
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
@@ -37,7 +38,7 @@ const ANTHROPIC_KNOWN_MODELS: &[&str] = &[
 ];
 
 const ANTHROPIC_DOC_URL: &str = "https://docs.anthropic.com/en/docs/about-claude/models";
-const ANTHROPIC_API_VERSION: &str = "2023-06-01";
+pub const ANTHROPIC_API_VERSION: &str = "2023-06-01";
 
 #[derive(serde::Serialize)]
 pub struct AnthropicProvider {
@@ -45,6 +46,8 @@ pub struct AnthropicProvider {
     api_client: ApiClient,
     model: ModelConfig,
     supports_streaming: bool,
+    /// Default request parameters of each model, from a custom provider
+    model_params: HashMap<String, Value>,
 }
 
 impl_provider_default!(AnthropicProvider);
@@ -71,27 +74,25 @@ impl AnthropicProvider {
             api_client,
             model,
             supports_streaming: true,
+            model_params: HashMap::new(),
         })
     }
 
     pub fn from_custom_config(model: ModelConfig, config: CustomProviderConfig) -> Result<Self> {
-        let global_config = crate::config::Config::global();
-        let api_key: String = global_config
-            .get_secret(&config.api_key_env)
-            .map_err(|_| anyhow::anyhow!("Missing API key: {}", config.api_key_env))?;
-
-        let auth = AuthMethod::ApiKey {
+        let auth = config.auth_method(|key| AuthMethod::ApiKey {
             header_name: "x-api-key".to_string(),
-            key: api_key,
-        };
+            key,
+        })?;
 
-        let api_client = ApiClient::new(config.base_url, auth)?
+        let api_client = config
+            .apply_headers(ApiClient::new(config.base_url.clone(), auth)?)?
             .with_header("anthropic-version", ANTHROPIC_API_VERSION)?;
 
         Ok(Self {
             api_client,
             model,
             supports_streaming: config.supports_streaming.unwrap_or(true),
+            model_params: config.model_params,
         })
     }
 
@@ -192,7 +193,8 @@ impl Provider for AnthropicProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let payload = create_request(model_config, system, messages, tools)?;
+        let mut payload = create_request(model_config, system, messages, tools)?;
+        apply_model_params(&self.model_params, &model_config.model_name, &mut payload);
 
         let response = self
             .with_retry(|| async { self.post(&payload).await })
@@ -254,6 +256,7 @@ impl Provider for AnthropicProvider {
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
         let mut payload = create_request(&self.model, system, messages, tools)?;
+        apply_model_params(&self.model_params, &self.model.model_name, &mut payload);
         payload
             .as_object_mut()
             .unwrap()
@@ -16,7 +16,6 @@ pub struct ApiClient {
     auth: AuthMethod,
     default_headers: HeaderMap,
     timeout: Duration,
-    tls_config: Option<TlsConfig>,
 }
 
 pub enum AuthMethod {
@@ -25,6 +24,12 @@ pub enum AuthMethod {
         header_name: String,
         key: String,
     },
+    /// The key as a query parameter, for APIs that take `?key=...`
+    QueryParam {
+        name: String,
+        key: String,
+    },
+    None,
     #[allow(dead_code)]
     OAuth(OAuthConfig),
     Custom(Box<dyn AuthProvider>),
@@ -177,6 +182,12 @@ impl fmt::Debug for AuthMethod {
                 .field("header_name", header_name)
                 .field("key", &"[hidden]")
                 .finish(),
+            AuthMethod::QueryParam { name, .. } => f
+                .debug_struct("QueryParam")
+                .field("name", name)
+                .field("key", &"[hidden]")
+                .finish(),
+            AuthMethod::None => f.write_str("None"),
             AuthMethod::OAuth(_) => f.debug_tuple("OAuth").field(&"[config]").finish(),
             AuthMethod::Custom(_) => f.debug_tuple("Custom").field(&"[provider]").finish(),
         }
@@ -203,15 +214,9 @@ impl ApiClient {
     }
 
     pub fn with_timeout(host: String, auth: AuthMethod, timeout: Duration) -> Result<Self> {
-        let mut client_builder = Client::builder().timeout(timeout);
-
-        // Configure TLS if needed
+        // Connections are pooled with every other client using the same timeout and TLS config
         let tls_config = TlsConfig::from_config()?;
-        if let Some(ref config) = tls_config {
-            client_builder = Self::configure_tls(client_builder, config)?;
-        }
-
-        let client = client_builder.build()?;
+        let client = super::runtime::shared_client_with_tls(timeout, tls_config.as_ref())?;
 
         Ok(Self {
             client,
@@ -219,47 +224,11 @@ impl ApiClient {
             auth,
             default_headers: HeaderMap::new(),
             timeout,
-            tls_config,
         })
     }
 
-    fn rebuild_client(&mut self) -> Result<()> {
-        let mut client_builder = Client::builder()
-            .timeout(self.timeout)
-            .default_headers(self.default_headers.clone());
-
-        // Configure TLS if needed
-        if let Some(ref tls_config) = self.tls_config {
-            client_builder = Self::configure_tls(client_builder, tls_config)?;
-        }
-
-        self.client = client_builder.build()?;
-        Ok(())
-    }
-
-    /// Configure TLS settings on a reqwest ClientBuilder
-    fn configure_tls(
-        mut client_builder: reqwest::ClientBuilder,
-        tls_config: &TlsConfig,
-    ) -> Result<reqwest::ClientBuilder> {
-        if tls_config.is_configured() {
-            // Load client identity (certificate + private key)
-            if let Some(identity) = tls_config.load_identity()? {
-                client_builder = client_builder.identity(identity);
-            }
-
-            // Load CA certificates
-            let ca_certs = tls_config.load_ca_certificates()?;
-            for ca_cert in ca_certs {
-                client_builder = client_builder.add_root_certificate(ca_cert);
-            }
-        }
-        Ok(client_builder)
-    }
-
     pub fn with_headers(mut self, headers: HeaderMap) -> Result<Self> {
         self.default_headers = headers;
-        self.rebuild_client()?;
         Ok(self)
     }
 
@@ -267,7 +236,6 @@ impl ApiClient {
         let header_name = HeaderName::from_bytes(key.as_bytes())?;
         let header_value = HeaderValue::from_str(value)?;
         self.default_headers.insert(header_name, header_value);
-        self.rebuild_client()?;
         Ok(self)
     }
 
@@ -361,13 +329,17 @@ impl<'a> ApiRequestBuilder<'a> {
     {
         let url = self.client.build_url(self.path)?;
         let mut request = request_builder(url, &self.client.client);
-        request = request.headers(self.headers.clone());
+        request = request
+            .headers(self.client.default_headers.clone())
+            .headers(self.headers.clone());
 
         request = match &self.client.auth {
             AuthMethod::BearerToken(token) => {
                 request.header("Authorization", format!("Bearer {}", token))
             }
             AuthMethod::ApiKey { header_name, key } => request.header(header_name.as_str(), key),
+            AuthMethod::QueryParam { name, key } => request.query(&[(name, key)]),
+            AuthMethod::None => request,
             AuthMethod::OAuth(config) => {
                 let token = self.client.get_oauth_token(config).await?;
                 request.header("Authorization", format!("Bearer {}", token))
@@ -252,6 +252,12 @@ pub struct Usage {
     pub input_tokens: Option<i32>,
     pub output_tokens: Option<i32>,
     pub total_tokens: Option<i32>,
+    /// Input tokens read from the prompt cache, counted in `input_tokens` too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<i32>,
+    /// Input tokens written to the prompt cache, counted in `input_tokens` too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<i32>,
 }
 
 fn sum_optionals<T>(a: Option<T>, b: Option<T>) -> Option<T>
@@ -274,6 +280,8 @@ impl Add for Usage {
             input_tokens: sum_optionals(self.input_tokens, other.input_tokens),
             output_tokens: sum_optionals(self.output_tokens, other.output_tokens),
             total_tokens: sum_optionals(self.total_tokens, other.total_tokens),
+            cache_read_tokens: sum_optionals(self.cache_read_tokens, other.cache_read_tokens),
+            cache_write_tokens: sum_optionals(self.cache_write_tokens, other.cache_write_tokens),
         }
     }
 }
@@ -294,8 +302,17 @@ impl Usage {
             input_tokens,
             output_tokens,
             total_tokens,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         }
     }
+
+    /// The prompt cache reads and writes, for providers that report them
+    pub fn with_cache(mut self, read_tokens: Option<i32>, write_tokens: Option<i32>) -> Self {
+        self.cache_read_tokens = read_tokens;
+        self.cache_write_tokens = write_tokens;
+        self
+    }
 }
 
 use async_trait::async_trait;
@@ -374,13 +391,30 @@ pub trait Provider: Send + Sync {
         false
     }
 
-    /// Create embeddings if supported. Default implementation returns an error.
+    /// Create embeddings for a batch of texts if supported. Default implementation returns an error.
     async fn create_embeddings(&self, _texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
         Err(ProviderError::ExecutionError(
             "This provider does not support embeddings".to_string(),
         ))
     }
 
+    /// The model `create_embeddings` uses, when the provider can tell. Vectors of different
+    /// models can't be compared, so callers keeping embeddings key them by this
+    fn embedding_model(&self) -> Option<String> {
+        None
+    }
+
+    /// Embed a single text, built on top of the batched `create_embeddings`
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, ProviderError> {
+        self.create_embeddings(vec![text.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                ProviderError::ExecutionError("Embedding response was empty".to_string())
+            })
+    }
+
     /// Check if this provider is a LeadWorkerProvider
     /// This is used for logging model information at startup
     fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
@@ -445,6 +479,29 @@ pub trait Provider: Send + Sync {
         Ok(safe_truncate(&description, 100))
     }
 
+    /// Generate a few short topical tags for a session based on the conversation history
+    async fn generate_session_tags(
+        &self,
+        messages: &Conversation,
+    ) -> Result<Vec<String>, ProviderError> {
+        let context = self.get_initial_user_messages(messages);
+        let prompt = format!(
+            "Here are the first few user messages:\n{}\n\nList up to {} short topical tags for this session, such as the languages, tools or areas of work involved. Reply *ONLY* with the tags, separated by commas",
+            context.join("\n"),
+            MAX_SESSION_TAGS
+        );
+        let message = Message::user().with_text(&prompt);
+        let result = self
+            .complete_fast(
+                "Reply with only a comma separated list of tags",
+                &[message],
+                &[],
+            )
+            .await?;
+
+        Ok(parse_session_tags(&result.0.as_concat_text()))
+    }
+
     // Generate a prompt for a session name based on the conversation history
     fn create_session_name_prompt(&self, context: &[String]) -> String {
         // Create a prompt for a concise description
@@ -478,6 +535,31 @@ pub trait Provider: Send + Sync {
     }
 }
 
+/// Maximum number of tags kept for a session
+pub const MAX_SESSION_TAGS: usize = 5;
+
+/// Turn a model reply like "Rust, CLI tools, #testing" into normalized tags: lowercase, words
+/// joined with dashes, without duplicates
+pub fn parse_session_tags(reply: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for raw in reply.split([',', '\n']) {
+        let tag = raw
+            .trim()
+            .trim_start_matches(['#', '-', '*'])
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        if !tag.is_empty() && tag.len() <= 30 && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+        if tags.len() == MAX_SESSION_TAGS {
+            break;
+        }
+    }
+    tags
+}
+
 /// A message stream yields partial text content but complete tool calls, all within the Message object
 /// So a message with text will contain potentially just a word of a longer response, but tool calls
 /// messages will only be yielded once concatenated.
@@ -496,6 +578,20 @@ mod tests {
     use std::collections::HashMap;
 
     use serde_json::json;
+    #[test]
+    fn test_parse_session_tags() {
+        assert_eq!(
+            parse_session_tags("Rust, CLI tools, #testing, rust"),
+            vec!["rust", "cli-tools", "testing"]
+        );
+        assert_eq!(
+            parse_session_tags("- docker\n- kubernetes\n"),
+            vec!["docker", "kubernetes"]
+        );
+        assert_eq!(parse_session_tags("a,b,c,d,e,f,g").len(), MAX_SESSION_TAGS);
+        assert!(parse_session_tags("").is_empty());
+    }
+
     #[test]
     fn test_usage_creation() {
         let usage = Usage::new(Some(10), Some(20), Some(30));
@@ -54,10 +54,10 @@ impl BedrockProvider {
         set_aws_env_vars(config.load_values());
         set_aws_env_vars(config.load_secrets());
 
-        let sdk_config = futures::executor::block_on(aws_config::load_from_env());
+        let sdk_config = super::runtime::block_on_setup(aws_config::load_from_env());
 
         // validate credentials or return error back up
-        futures::executor::block_on(
+        super::runtime::block_on_setup(
             sdk_config
                 .credentials_provider()
                 .unwrap()
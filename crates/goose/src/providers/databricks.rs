@@ -5,15 +5,16 @@ use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::io;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::pin;
+use tokio::sync::Mutex;
 use tokio_util::io::StreamReader;
 
 use super::api_client::{ApiClient, AuthMethod, AuthProvider};
 use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::embedding::EmbeddingCapable;
 use super::errors::ProviderError;
-use super::formats::databricks::{create_request, response_to_message};
+use super::formats::databricks::{chat_endpoint_names, create_request, response_to_message};
 use super::oauth;
 use super::retry::ProviderRetry;
 use super::utils::{get_model, handle_response_openai_compat, ImageFormat};
@@ -57,6 +58,12 @@ pub enum DatabricksAuth {
         redirect_url: String,
         scopes: Vec<String>,
     },
+    /// OAuth machine-to-machine auth with a service principal's client secret
+    ServicePrincipal {
+        host: String,
+        client_id: String,
+        client_secret: String,
+    },
 }
 
 impl DatabricksAuth {
@@ -72,10 +79,64 @@ impl DatabricksAuth {
     pub fn token(token: String) -> Self {
         Self::Token(token)
     }
+
+    pub fn service_principal(host: String, client_id: String, client_secret: String) -> Self {
+        Self::ServicePrincipal {
+            host,
+            client_id,
+            client_secret,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ServicePrincipalToken {
+    access_token: String,
+    expires_in: u64,
 }
 
 struct DatabricksAuthProvider {
     auth: DatabricksAuth,
+    /// The service principal's token and when to replace it
+    cached_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl DatabricksAuthProvider {
+    fn new(auth: DatabricksAuth) -> Self {
+        Self {
+            auth,
+            cached_token: Mutex::new(None),
+        }
+    }
+
+    async fn service_principal_token(
+        &self,
+        host: &str,
+        client_id: &str,
+        client_secret: &str,
+    ) -> Result<String> {
+        let mut cached = self.cached_token.lock().await;
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if *expires_at > Instant::now() {
+                return Ok(token.clone());
+            }
+        }
+
+        let response = super::runtime::shared_client(Duration::from_secs(DEFAULT_TIMEOUT_SECS))?
+            .post(format!("{}/oidc/v1/token", host.trim_end_matches('/')))
+            .basic_auth(client_id, Some(client_secret))
+            .form(&[("grant_type", "client_credentials"), ("scope", "all-apis")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ServicePrincipalToken>()
+            .await?;
+        // Refresh a minute early so requests don't race the expiry
+        let expires_at =
+            Instant::now() + Duration::from_secs(response.expires_in.saturating_sub(60));
+        *cached = Some((response.access_token.clone(), expires_at));
+        Ok(response.access_token)
+    }
 }
 
 #[async_trait]
@@ -89,6 +150,14 @@ impl AuthProvider for DatabricksAuthProvider {
                 redirect_url,
                 scopes,
             } => oauth::get_oauth_token_async(host, client_id, redirect_url, scopes).await?,
+            DatabricksAuth::ServicePrincipal {
+                host,
+                client_id,
+                client_secret,
+            } => {
+                self.service_principal_token(host, client_id, client_secret)
+                    .await?
+            }
         };
         Ok(("Authorization".to_string(), format!("Bearer {}", token)))
     }
@@ -126,14 +195,17 @@ impl DatabricksProvider {
         let host = host?;
         let retry_config = Self::load_retry_config(config);
 
+        let client_id: Option<String> = config.get_param("DATABRICKS_CLIENT_ID").ok();
+        let client_secret: Option<String> = config.get_secret("DATABRICKS_CLIENT_SECRET").ok();
         let auth = if let Ok(api_key) = config.get_secret("DATABRICKS_TOKEN") {
             DatabricksAuth::token(api_key)
+        } else if let (Some(client_id), Some(client_secret)) = (client_id, client_secret) {
+            DatabricksAuth::service_principal(host.clone(), client_id, client_secret)
         } else {
             DatabricksAuth::oauth(host.clone())
         };
 
-        let auth_method =
-            AuthMethod::Custom(Box::new(DatabricksAuthProvider { auth: auth.clone() }));
+        let auth_method = AuthMethod::Custom(Box::new(DatabricksAuthProvider::new(auth.clone())));
 
         let api_client =
             ApiClient::with_timeout(host, auth_method, Duration::from_secs(DEFAULT_TIMEOUT_SECS))?;
@@ -148,27 +220,25 @@ impl DatabricksProvider {
         };
 
         // Check if the default fast model exists in the workspace
-        let model_with_fast = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                if let Ok(Some(models)) = provider.fetch_supported_models().await {
-                    if models.contains(&DATABRICKS_DEFAULT_FAST_MODEL.to_string()) {
-                        tracing::debug!(
-                            "Found {} in Databricks workspace, setting as fast model",
-                            DATABRICKS_DEFAULT_FAST_MODEL
-                        );
-                        model.with_fast(DATABRICKS_DEFAULT_FAST_MODEL.to_string())
-                    } else {
-                        tracing::debug!(
-                            "{} not found in Databricks workspace, not setting fast model",
-                            DATABRICKS_DEFAULT_FAST_MODEL
-                        );
-                        model
-                    }
+        let model_with_fast = super::runtime::block_on_setup(async {
+            if let Ok(Some(models)) = provider.fetch_supported_models().await {
+                if models.contains(&DATABRICKS_DEFAULT_FAST_MODEL.to_string()) {
+                    tracing::debug!(
+                        "Found {} in Databricks workspace, setting as fast model",
+                        DATABRICKS_DEFAULT_FAST_MODEL
+                    );
+                    model.with_fast(DATABRICKS_DEFAULT_FAST_MODEL.to_string())
                 } else {
-                    tracing::debug!("Could not fetch Databricks models, not setting fast model");
+                    tracing::debug!(
+                        "{} not found in Databricks workspace, not setting fast model",
+                        DATABRICKS_DEFAULT_FAST_MODEL
+                    );
                     model
                 }
-            })
+            } else {
+                tracing::debug!("Could not fetch Databricks models, not setting fast model");
+                model
+            }
         });
 
         provider.model = model_with_fast;
@@ -210,8 +280,7 @@ impl DatabricksProvider {
 
     pub fn from_params(host: String, api_key: String, model: ModelConfig) -> Result<Self> {
         let auth = DatabricksAuth::token(api_key);
-        let auth_method =
-            AuthMethod::Custom(Box::new(DatabricksAuthProvider { auth: auth.clone() }));
+        let auth_method = AuthMethod::Custom(Box::new(DatabricksAuthProvider::new(auth.clone())));
 
         let api_client = ApiClient::with_timeout(host, auth_method, Duration::from_secs(600))?;
 
@@ -255,6 +324,8 @@ impl Provider for DatabricksProvider {
             vec![
                 ConfigKey::new("DATABRICKS_HOST", true, false, None),
                 ConfigKey::new("DATABRICKS_TOKEN", false, true, None),
+                ConfigKey::new("DATABRICKS_CLIENT_ID", false, false, None),
+                ConfigKey::new("DATABRICKS_CLIENT_SECRET", false, true, None),
             ],
         )
     }
@@ -401,25 +472,7 @@ impl Provider for DatabricksProvider {
             }
         };
 
-        let endpoints = match json.get("endpoints").and_then(|v| v.as_array()) {
-            Some(endpoints) => endpoints,
-            None => {
-                tracing::warn!(
-                    "Unexpected response format from Databricks API: missing 'endpoints' array"
-                );
-                return Ok(None);
-            }
-        };
-
-        let models: Vec<String> = endpoints
-            .iter()
-            .filter_map(|endpoint| {
-                endpoint
-                    .get("name")
-                    .and_then(|v| v.as_str())
-                    .map(|name| name.to_string())
-            })
-            .collect();
+        let models = chat_endpoint_names(&json);
 
         if models.is_empty() {
             tracing::debug!("No serving endpoints found in Databricks workspace");
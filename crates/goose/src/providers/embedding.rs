@@ -1,7 +1,14 @@
+use crate::config::Config;
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+/// Older setting for the embedding model, only read by providers that take OpenAI model names
+pub const EMBEDDING_MODEL_ENV: &str = "GOOSE_EMBEDDING_MODEL";
+
+/// Providers whose embedding models are named like OpenAI's
+const OPENAI_COMPATIBLE_PROVIDERS: &[&str] = &["openai", "litellm"];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingRequest {
     pub input: Vec<String>,
@@ -22,3 +29,109 @@ pub struct EmbeddingData {
 pub trait EmbeddingCapable {
     async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
 }
+
+/// The setting choosing the embedding model of a provider, such as `OLLAMA_EMBEDDING_MODEL`
+pub fn embedding_model_key(provider: &str) -> String {
+    format!("{}_EMBEDDING_MODEL", provider.to_uppercase())
+}
+
+/// The embedding model of a provider: its own setting, then for OpenAI compatible providers
+/// `GOOSE_EMBEDDING_MODEL`, then the provider's default
+pub fn embedding_model(provider: &str, provider_default: &str) -> String {
+    let config = Config::global();
+    config
+        .get_param::<String>(&embedding_model_key(provider))
+        .ok()
+        .or_else(|| {
+            OPENAI_COMPATIBLE_PROVIDERS
+                .contains(&provider)
+                .then(|| config.get_param::<String>(EMBEDDING_MODEL_ENV).ok())
+                .flatten()
+        })
+        .unwrap_or_else(|| provider_default.to_string())
+}
+
+/// Cosine similarity between two embeddings; returns 0.0 for mismatched or zero vectors
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b.iter()) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Rank candidate embeddings against a query, returning `(index, score)` pairs best-first
+pub fn rank_by_similarity(
+    query: &[f32],
+    candidates: &[Vec<f32>],
+    limit: usize,
+) -> Vec<(usize, f32)> {
+    let mut scored: Vec<(usize, f32)> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| (i, cosine_similarity(query, candidate)))
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(limit);
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 2.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_embedding_model_is_chosen_per_provider() {
+        temp_env::with_vars(
+            [
+                (EMBEDDING_MODEL_ENV, Some("text-embedding-3-large")),
+                ("OLLAMA_EMBEDDING_MODEL", Some("mxbai-embed-large")),
+                ("GOOGLE_EMBEDDING_MODEL", None),
+                ("OPENAI_EMBEDDING_MODEL", None),
+            ],
+            || {
+                assert_eq!(
+                    embedding_model("ollama", "nomic-embed-text"),
+                    "mxbai-embed-large"
+                );
+                // An OpenAI model name means nothing to Gemini
+                assert_eq!(
+                    embedding_model("google", "text-embedding-004"),
+                    "text-embedding-004"
+                );
+                assert_eq!(
+                    embedding_model("openai", "text-embedding-3-small"),
+                    "text-embedding-3-large"
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_rank_by_similarity() {
+        let candidates = vec![vec![0.0, 1.0], vec![1.0, 0.1], vec![1.0, 0.0]];
+        let ranked = rank_by_similarity(&[1.0, 0.0], &candidates, 2);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, 2);
+        assert_eq!(ranked[1].0, 1);
+    }
+}
@@ -15,10 +15,13 @@ use super::{
     groq::GroqProvider,
     lead_worker::LeadWorkerProvider,
     litellm::LiteLLMProvider,
+    llamacpp::LlamaCppProvider,
     ollama::OllamaProvider,
     openai::OpenAiProvider,
     openrouter::OpenRouterProvider,
     provider_registry::ProviderRegistry,
+    rate_limit::with_rate_limit,
+    recording::RecordingProvider,
     sagemaker_tgi::SageMakerTgiProvider,
     snowflake::SnowflakeProvider,
     tetrate::TetrateProvider,
@@ -53,6 +56,7 @@ static REGISTRY: Lazy<RwLock<ProviderRegistry>> = Lazy::new(|| {
         registry.register::<GoogleProvider, _>(GoogleProvider::from_env);
         registry.register::<GroqProvider, _>(GroqProvider::from_env);
         registry.register::<LiteLLMProvider, _>(LiteLLMProvider::from_env);
+        registry.register::<LlamaCppProvider, _>(LlamaCppProvider::from_env);
         registry.register::<OllamaProvider, _>(OllamaProvider::from_env);
         registry.register::<OpenAiProvider, _>(OpenAiProvider::from_env);
         registry.register::<OpenRouterProvider, _>(OpenRouterProvider::from_env);
@@ -92,6 +96,25 @@ pub fn refresh_custom_providers() -> Result<()> {
 }
 
 pub fn create(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
+    let (record_dir, replay_dir) = RecordingProvider::configured_dirs();
+    if let Some(replay_dir) = replay_dir {
+        tracing::info!("Replaying provider exchanges from {}", replay_dir.display());
+        return Ok(Arc::new(RecordingProvider::replaying(replay_dir, model)));
+    }
+
+    let provider = create_provider(name, model)?;
+    match record_dir {
+        Some(record_dir) => {
+            tracing::info!("Recording provider exchanges to {}", record_dir.display());
+            Ok(Arc::new(RecordingProvider::recording(
+                provider, record_dir,
+            )?))
+        }
+        None => Ok(provider),
+    }
+}
+
+fn create_provider(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
     let config = crate::config::Config::global();
 
     if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
@@ -99,7 +122,24 @@ pub fn create(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
         return create_lead_worker_from_env(name, &model, &lead_model_name);
     }
 
-    REGISTRY.read().unwrap().create(name, model)
+    create_registered(name, model)
+}
+
+/// Create a registered provider, limited to the rate configured for it
+fn create_registered(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
+    if let Some(policy) = crate::config::Config::global().policy() {
+        if let Some(reason) = policy.unavailable_reason() {
+            anyhow::bail!(
+                "No provider can be used without the goose policy: {}",
+                reason
+            );
+        }
+        if !policy.is_provider_allowed(name) {
+            anyhow::bail!("Provider {} is not allowed by policy", name);
+        }
+    }
+    let provider = REGISTRY.read().unwrap().create(name, model)?;
+    Ok(with_rate_limit(name, provider))
 }
 
 fn create_lead_worker_from_env(
@@ -130,14 +170,8 @@ fn create_lead_worker_from_env(
 
     let worker_model_config = create_worker_model_config(default_model)?;
 
-    let lead_provider = REGISTRY
-        .read()
-        .unwrap()
-        .create(&lead_provider_name, lead_model_config)?;
-    let worker_provider = REGISTRY
-        .read()
-        .unwrap()
-        .create(default_provider_name, worker_model_config)?;
+    let lead_provider = create_registered(&lead_provider_name, lead_model_config)?;
+    let worker_provider = create_registered(default_provider_name, worker_model_config)?;
 
     Ok(Arc::new(LeadWorkerProvider::new_with_settings(
         lead_provider,
@@ -2,6 +2,7 @@ use crate::conversation::message::{Message, MessageContent};
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
+use crate::providers::utils::{convert_image, ImageFormat};
 use anyhow::{anyhow, Result};
 use mcp_core::ToolCall;
 use rmcp::model::{ErrorCode, ErrorData, Role, Tool};
@@ -109,7 +110,9 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                         DATA_FIELD: redacted.data
                     }));
                 }
-                MessageContent::Image(_) => continue, // Anthropic doesn't support image content yet
+                MessageContent::Image(image) => {
+                    content.push(convert_image(image, &ImageFormat::Anthropic));
+                }
                 MessageContent::FrontendToolRequest(tool_request) => {
                     if let Ok(tool_call) = &tool_request.tool_call {
                         content.push(json!({
@@ -263,6 +266,11 @@ pub fn response_to_message(response: &Value) -> Result<Message> {
     Ok(message)
 }
 
+/// Cache token counts are only recorded when the prompt cache was used
+fn cache_tokens(tokens: u64) -> Option<i32> {
+    (tokens > 0).then(|| tokens.min(i32::MAX as u64) as i32)
+}
+
 /// Extract usage information from Anthropic's API response
 pub fn get_usage(data: &Value) -> Result<Usage> {
     // Extract usage data if available
@@ -302,6 +310,10 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
             Some(total_input_i32),
             Some(output_tokens_i32),
             Some(total_tokens_i32),
+        )
+        .with_cache(
+            cache_tokens(cache_read_tokens),
+            cache_tokens(cache_creation_tokens),
         ))
     } else if data.as_object().is_some() {
         // Check if the data itself is the usage object (for message_delta events that might have usage at top level)
@@ -345,6 +357,10 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
                 Some(total_input_i32),
                 Some(output_tokens_i32),
                 Some(total_tokens_i32),
+            )
+            .with_cache(
+                cache_tokens(cache_read_tokens),
+                cache_tokens(cache_creation_tokens),
             ))
         } else {
             tracing::debug!("🔍 Anthropic no token data found in object");
@@ -715,6 +731,8 @@ mod tests {
         assert_eq!(usage.input_tokens, Some(24)); // 12 + 12 = 24 actual tokens
         assert_eq!(usage.output_tokens, Some(15));
         assert_eq!(usage.total_tokens, Some(39)); // 24 + 15
+        assert_eq!(usage.cache_write_tokens, Some(12));
+        assert_eq!(usage.cache_read_tokens, None);
 
         Ok(())
     }
@@ -856,6 +874,21 @@ mod tests {
         assert_eq!(spec[2]["content"][0]["text"], "How are you?");
     }
 
+    #[test]
+    fn test_message_with_image_to_anthropic_spec() {
+        let messages = vec![Message::user()
+            .with_text("What is in this picture?")
+            .with_image("aGVsbG8=", "image/png")];
+
+        let spec = format_messages(&messages);
+
+        assert_eq!(spec.len(), 1);
+        assert_eq!(spec[0]["content"][0]["text"], "What is in this picture?");
+        assert_eq!(spec[0]["content"][1]["type"], "image");
+        assert_eq!(spec[0]["content"][1]["source"]["media_type"], "image/png");
+        assert_eq!(spec[0]["content"][1]["source"]["data"], "aGVsbG8=");
+    }
+
     #[test]
     fn test_tools_to_anthropic_spec() {
         let tools = vec![
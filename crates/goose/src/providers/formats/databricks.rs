@@ -209,12 +209,7 @@ fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<Data
                 }
                 MessageContent::Image(image) => {
                     // Handle direct image content
-                    content_array.push(json!({
-                        "type": "image_url",
-                        "image_url": {
-                            "url": convert_image(image, image_format)
-                        }
-                    }));
+                    content_array.push(convert_image(image, image_format));
                 }
                 MessageContent::FrontendToolRequest(req) => {
                     // Frontend tool requests are converted to text messages
@@ -625,6 +620,33 @@ pub fn create_request(
     Ok(payload)
 }
 
+/// Names of the serving endpoints that can be chatted with, from a list of serving endpoints.
+/// Embedding and custom model endpoints are left out, as are endpoints that aren't ready
+pub fn chat_endpoint_names(response: &Value) -> Vec<String> {
+    let Some(endpoints) = response.get("endpoints").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = endpoints
+        .iter()
+        .filter(|endpoint| {
+            endpoint
+                .get("task")
+                .and_then(|t| t.as_str())
+                .is_none_or(|task| task == "llm/v1/chat")
+        })
+        .filter(|endpoint| {
+            endpoint
+                .pointer("/state/ready")
+                .and_then(|r| r.as_str())
+                .is_none_or(|ready| ready == "READY")
+        })
+        .filter_map(|endpoint| endpoint.get("name").and_then(|n| n.as_str()))
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1226,4 +1248,21 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_chat_endpoint_names() {
+        let response = json!({
+            "endpoints": [
+                {"name": "databricks-claude-sonnet-4", "task": "llm/v1/chat", "state": {"ready": "READY"}},
+                {"name": "databricks-gte-large-en", "task": "llm/v1/embeddings", "state": {"ready": "READY"}},
+                {"name": "databricks-llama-4-maverick", "task": "llm/v1/chat", "state": {"ready": "NOT_READY"}},
+                {"name": "custom-gateway"}
+            ]
+        });
+        assert_eq!(
+            chat_endpoint_names(&response),
+            vec!["custom-gateway", "databricks-claude-sonnet-4"]
+        );
+        assert!(chat_endpoint_names(&json!({})).is_empty());
+    }
 }
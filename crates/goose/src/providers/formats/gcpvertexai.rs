@@ -8,23 +8,78 @@ use serde_json::Value;
 
 use std::fmt;
 
-/// Sensible default values of Google Cloud Platform (GCP) locations for model deployment.
-///
-/// Each variant corresponds to a specific GCP region where models can be hosted.
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
-pub enum GcpLocation {
-    /// Represents the us-central1 region in Iowa
-    Iowa,
-    /// Represents the us-east5 region in Ohio
-    Ohio,
+/// Regions Vertex AI serves generative models from, and `global`
+pub const KNOWN_GCP_LOCATIONS: &[&str] = &[
+    "global",
+    "us-central1",
+    "us-east1",
+    "us-east4",
+    "us-east5",
+    "us-south1",
+    "us-west1",
+    "us-west4",
+    "northamerica-northeast1",
+    "southamerica-east1",
+    "europe-central2",
+    "europe-north1",
+    "europe-southwest1",
+    "europe-west1",
+    "europe-west2",
+    "europe-west3",
+    "europe-west4",
+    "europe-west6",
+    "europe-west8",
+    "europe-west9",
+    "asia-east1",
+    "asia-east2",
+    "asia-northeast1",
+    "asia-northeast3",
+    "asia-south1",
+    "asia-southeast1",
+    "australia-southeast1",
+    "me-central1",
+    "me-central2",
+    "me-west1",
+];
+
+/// A Google Cloud Platform (GCP) location for model deployment: one of the
+/// `KNOWN_GCP_LOCATIONS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcpLocation(String);
+
+impl GcpLocation {
+    /// The us-central1 region in Iowa
+    pub fn iowa() -> Self {
+        Self("us-central1".to_string())
+    }
+
+    /// The us-east5 region in Ohio
+    pub fn ohio() -> Self {
+        Self("us-east5".to_string())
+    }
+
+    /// The global endpoint, which routes requests to any region with capacity
+    pub fn global() -> Self {
+        Self("global".to_string())
+    }
+
+    pub fn is_global(&self) -> bool {
+        self.0 == "global"
+    }
+
+    /// The base URL of the Vertex AI API in this location
+    pub fn host(&self) -> String {
+        if self.is_global() {
+            "https://aiplatform.googleapis.com".to_string()
+        } else {
+            format!("https://{}-aiplatform.googleapis.com", self.0)
+        }
+    }
 }
 
 impl fmt::Display for GcpLocation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Iowa => write!(f, "us-central1"),
-            Self::Ohio => write!(f, "us-east5"),
-        }
+        write!(f, "{}", self.0)
     }
 }
 
@@ -32,10 +87,11 @@ impl TryFrom<&str> for GcpLocation {
     type Error = ModelError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        match s {
-            "us-central1" => Ok(Self::Iowa),
-            "us-east5" => Ok(Self::Ohio),
-            _ => Err(ModelError::UnsupportedLocation(s.to_string())),
+        let location = s.trim();
+        if KNOWN_GCP_LOCATIONS.contains(&location) {
+            Ok(Self(location.to_string()))
+        } else {
+            Err(ModelError::UnsupportedLocation(s.to_string()))
         }
     }
 }
@@ -148,8 +204,34 @@ impl GcpVertexAIModel {
     /// - Gemini models default to Iowa (us-central1)
     pub fn known_location(&self) -> GcpLocation {
         match self {
-            Self::Claude(_) => GcpLocation::Ohio,
-            Self::Gemini(_) => GcpLocation::Iowa,
+            Self::Claude(_) => GcpLocation::ohio(),
+            Self::Gemini(_) => GcpLocation::iowa(),
+        }
+    }
+
+    /// Whether the model is served from the global endpoint, which is the case for Gemini 2
+    /// and later
+    pub fn supports_global(&self) -> bool {
+        match self {
+            Self::Claude(_) => false,
+            Self::Gemini(_) => {
+                let model_id = self.to_string();
+                model_id
+                    .strip_prefix("gemini-")
+                    .and_then(|version| version.split(['.', '-']).next())
+                    .and_then(|major| major.parse::<u32>().ok())
+                    .is_some_and(|major| major >= 2)
+            }
+        }
+    }
+
+    /// The location to send requests to when `configured` is set: the global endpoint falls
+    /// back to the model's known location for models it doesn't serve
+    pub fn location_for(&self, configured: &GcpLocation) -> GcpLocation {
+        if configured.is_global() && !self.supports_global() {
+            self.known_location()
+        } else {
+            configured.clone()
         }
     }
 }
@@ -385,17 +467,17 @@ mod tests {
     #[test]
     fn test_default_locations() -> Result<()> {
         let test_cases = [
-            ("claude-3-5-sonnet@20240620", GcpLocation::Ohio),
-            ("claude-3-5-sonnet-v2@20241022", GcpLocation::Ohio),
-            ("claude-3-7-sonnet@20250219", GcpLocation::Ohio),
-            ("claude-3-5-haiku@20241022", GcpLocation::Ohio),
-            ("claude-sonnet-4@20250514", GcpLocation::Ohio),
-            ("gemini-1.5-pro-002", GcpLocation::Iowa),
-            ("gemini-2.0-flash-001", GcpLocation::Iowa),
-            ("gemini-2.0-pro-exp-02-05", GcpLocation::Iowa),
-            ("gemini-2.5-pro-exp-03-25", GcpLocation::Iowa),
-            ("gemini-2.5-flash-preview-05-20", GcpLocation::Iowa),
-            ("gemini-2.5-pro-preview-05-06", GcpLocation::Iowa),
+            ("claude-3-5-sonnet@20240620", GcpLocation::ohio()),
+            ("claude-3-5-sonnet-v2@20241022", GcpLocation::ohio()),
+            ("claude-3-7-sonnet@20250219", GcpLocation::ohio()),
+            ("claude-3-5-haiku@20241022", GcpLocation::ohio()),
+            ("claude-sonnet-4@20250514", GcpLocation::ohio()),
+            ("gemini-1.5-pro-002", GcpLocation::iowa()),
+            ("gemini-2.0-flash-001", GcpLocation::iowa()),
+            ("gemini-2.0-pro-exp-02-05", GcpLocation::iowa()),
+            ("gemini-2.5-pro-exp-03-25", GcpLocation::iowa()),
+            ("gemini-2.5-flash-preview-05-20", GcpLocation::iowa()),
+            ("gemini-2.5-pro-preview-05-06", GcpLocation::iowa()),
         ];
 
         for (model_id, expected_location) in test_cases {
@@ -435,7 +517,7 @@ mod tests {
                 _ => panic!("Expected Claude generic model for {model_id}"),
             }
             assert_eq!(model.to_string(), model_id);
-            assert_eq!(model.known_location(), GcpLocation::Ohio);
+            assert_eq!(model.known_location(), GcpLocation::ohio());
         }
 
         // Test generic Gemini models
@@ -450,9 +532,44 @@ mod tests {
                 _ => panic!("Expected Gemini generic model for {model_id}"),
             }
             assert_eq!(model.to_string(), model_id);
-            assert_eq!(model.known_location(), GcpLocation::Iowa);
+            assert_eq!(model.known_location(), GcpLocation::iowa());
         }
 
         Ok(())
     }
+
+    #[test]
+    fn test_locations() {
+        assert_eq!(
+            GcpLocation::try_from("europe-west4").unwrap().host(),
+            "https://europe-west4-aiplatform.googleapis.com"
+        );
+        assert_eq!(
+            GcpLocation::global().host(),
+            "https://aiplatform.googleapis.com"
+        );
+        assert!(GcpLocation::try_from("moon-base1").is_err());
+
+        let gemini = GcpVertexAIModel::try_from("gemini-2.5-flash").unwrap();
+        let gemini_15 = GcpVertexAIModel::try_from("gemini-1.5-pro-002").unwrap();
+        let claude = GcpVertexAIModel::try_from("claude-sonnet-4@20250514").unwrap();
+        assert!(gemini.supports_global());
+        assert!(!gemini_15.supports_global());
+        assert_eq!(
+            gemini.location_for(&GcpLocation::global()),
+            GcpLocation::global()
+        );
+        assert_eq!(
+            gemini_15.location_for(&GcpLocation::global()),
+            GcpLocation::iowa()
+        );
+        assert_eq!(
+            claude.location_for(&GcpLocation::global()),
+            GcpLocation::ohio()
+        );
+        assert_eq!(
+            claude.location_for(&GcpLocation::iowa()),
+            GcpLocation::iowa()
+        );
+    }
 }
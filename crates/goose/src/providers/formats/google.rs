@@ -117,6 +117,14 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                             }
                         }
                     }
+                    MessageContent::Image(image) => {
+                        parts.push(json!({
+                            "inline_data": {
+                                "mime_type": image.mime_type,
+                                "data": image.data,
+                            }
+                        }));
+                    }
 
                     _ => {}
                 }
@@ -293,7 +301,13 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
             .get("totalTokenCount")
             .and_then(|v| v.as_u64())
             .map(|v| v as i32);
-        Ok(Usage::new(input_tokens, output_tokens, total_tokens))
+        let cache_read_tokens = usage_meta_data
+            .get("cachedContentTokenCount")
+            .and_then(|v| v.as_u64())
+            .filter(|tokens| *tokens > 0)
+            .map(|v| v as i32);
+        Ok(Usage::new(input_tokens, output_tokens, total_tokens)
+            .with_cache(cache_read_tokens, None))
     } else {
         tracing::debug!(
             "Failed to get usage data: {}",
@@ -304,6 +318,65 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
     }
 }
 
+/// Process a streamGenerateContent response read with `alt=sse`. Every event is a partial
+/// response whose parts are yielded as they arrive, the usage of the last one is the total
+pub fn response_to_streaming_message<S>(
+    mut stream: S,
+) -> impl futures::Stream<
+    Item = anyhow::Result<(
+        Option<Message>,
+        Option<crate::providers::base::ProviderUsage>,
+    )>,
+> + 'static
+where
+    S: futures::Stream<Item = anyhow::Result<String>> + Unpin + Send + 'static,
+{
+    use async_stream::try_stream;
+    use futures::StreamExt;
+
+    try_stream! {
+        // Partial messages share an id so they are shown as one
+        let message_id: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+        let mut model = String::new();
+        let mut usage = None;
+
+        while let Some(line) = stream.next().await {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data: ") else {
+                continue;
+            };
+            let chunk: Value = match serde_json::from_str(data) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    tracing::debug!("Failed to parse streaming chunk: {} - Line: {}", e, data);
+                    continue;
+                }
+            };
+
+            if let Some(version) = chunk.get("modelVersion").and_then(|v| v.as_str()) {
+                model = version.to_string();
+            }
+            if chunk.get("usageMetadata").is_some() {
+                usage = Some(get_usage(&chunk)?);
+            }
+
+            let mut message = response_to_message(chunk)?;
+            if !message.content.is_empty() {
+                message.id = Some(message_id.clone());
+                yield (Some(message), None);
+            }
+        }
+
+        if let Some(usage) = usage {
+            yield (None, Some(crate::providers::base::ProviderUsage::new(model, usage)));
+        }
+    }
+}
+
 /// Create a complete request payload for Google's API
 pub fn create_request(
     model_config: &ModelConfig,
@@ -337,10 +410,45 @@ pub fn create_request(
     Ok(json!(payload))
 }
 
+/// Create a cachedContents request holding the system prompt and tools, which stays
+/// on Google's side for `ttl_secs` so requests can refer to it instead of resending them
+pub fn create_cached_content_request(
+    model_name: &str,
+    system: &str,
+    tools: &[Tool],
+    ttl_secs: u64,
+) -> Value {
+    let mut payload = Map::new();
+    payload.insert("model".to_string(), json!(format!("models/{}", model_name)));
+    payload.insert(
+        "system_instruction".to_string(),
+        json!({"parts": [{"text": system}]}),
+    );
+    if !tools.is_empty() {
+        payload.insert(
+            "tools".to_string(),
+            json!([{"functionDeclarations": format_tools(tools)}]),
+        );
+    }
+    payload.insert("ttl".to_string(), json!(format!("{}s", ttl_secs)));
+    json!(payload)
+}
+
+/// Point a request from `create_request` at the cached content holding its system prompt
+/// and tools, the API rejects requests that send them again next to the cache
+pub fn use_cached_content(payload: &mut Value, cached_content: &str) {
+    if let Some(obj) = payload.as_object_mut() {
+        obj.remove("system_instruction");
+        obj.remove("tools");
+        obj.insert("cachedContent".to_string(), json!(cached_content));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::conversation::message::Message;
+    use futures::StreamExt;
     use rmcp::{model::Content, object};
     use serde_json::json;
 
@@ -393,6 +501,55 @@ mod tests {
         assert_eq!(usage.input_tokens, Some(1));
         assert_eq!(usage.output_tokens, Some(2));
         assert_eq!(usage.total_tokens, Some(3));
+        assert_eq!(usage.cache_read_tokens, None);
+
+        let data = json!({
+            "usageMetadata": {
+                "promptTokenCount": 2000,
+                "cachedContentTokenCount": 1500,
+                "candidatesTokenCount": 10,
+                "totalTokenCount": 2010
+            }
+        });
+        let usage = get_usage(&data).unwrap();
+        assert_eq!(usage.input_tokens, Some(2000));
+        assert_eq!(usage.cache_read_tokens, Some(1500));
+    }
+
+    #[test]
+    fn test_use_cached_content() {
+        let tool = Tool::new(
+            "shell",
+            "Run a command",
+            object!({"type": "object", "properties": {"command": {"type": "string"}}}),
+        );
+        let tools = vec![tool];
+
+        let cache = create_cached_content_request("gemini-2.5-flash", "be helpful", &tools, 300);
+        assert_eq!(cache["model"], "models/gemini-2.5-flash");
+        assert_eq!(
+            cache["system_instruction"]["parts"][0]["text"],
+            "be helpful"
+        );
+        assert_eq!(
+            cache["tools"][0]["functionDeclarations"][0]["name"],
+            "shell"
+        );
+        assert_eq!(cache["ttl"], "300s");
+
+        let messages = vec![set_up_text_message("Hello", Role::User)];
+        let mut payload = create_request(
+            &ModelConfig::new_or_fail("gemini-2.5-flash"),
+            "be helpful",
+            &messages,
+            &tools,
+        )
+        .unwrap();
+        use_cached_content(&mut payload, "cachedContents/abc");
+        assert_eq!(payload["cachedContent"], "cachedContents/abc");
+        assert!(payload.get("system_instruction").is_none());
+        assert!(payload.get("tools").is_none());
+        assert_eq!(payload["contents"][0]["parts"][0]["text"], "Hello");
     }
 
     #[test]
@@ -409,6 +566,21 @@ mod tests {
         assert_eq!(payload[1]["parts"][0]["text"], "World");
     }
 
+    #[test]
+    fn test_message_to_google_spec_image_message() {
+        let messages = vec![Message::user()
+            .with_text("Describe this")
+            .with_image("aGVsbG8=", "image/jpeg")];
+        let payload = format_messages(&messages);
+        assert_eq!(payload.len(), 1);
+        assert_eq!(payload[0]["parts"][0]["text"], "Describe this");
+        assert_eq!(
+            payload[0]["parts"][1]["inline_data"]["mime_type"],
+            "image/jpeg"
+        );
+        assert_eq!(payload[0]["parts"][1]["inline_data"]["data"], "aGVsbG8=");
+    }
+
     #[test]
     fn test_message_to_google_spec_tool_request_message() {
         let arguments = json!({
@@ -811,4 +983,33 @@ mod tests {
 
         assert_eq!(payload, expected_payload);
     }
+
+    #[tokio::test]
+    async fn test_response_to_streaming_message() {
+        let lines = vec![
+            r#"data: {"candidates": [{"content": {"role": "model", "parts": [{"text": "Hello"}]}}], "modelVersion": "gemini-2.5-flash"}"#,
+            "",
+            r#"data: {"candidates": [{"content": {"role": "model", "parts": [{"functionCall": {"name": "shell", "args": {"command": "ls"}}}]}}], "usageMetadata": {"promptTokenCount": 10, "candidatesTokenCount": 5, "totalTokenCount": 15}}"#,
+        ];
+        let stream = futures::stream::iter(
+            lines
+                .into_iter()
+                .map(|line| anyhow::Ok(line.to_string()))
+                .collect::<Vec<_>>(),
+        );
+        let items: Vec<_> = response_to_streaming_message(stream)
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 3);
+        let text = items[0].0.as_ref().unwrap();
+        let tool = items[1].0.as_ref().unwrap();
+        assert_eq!(text.as_concat_text(), "Hello");
+        assert!(tool.content[0].as_tool_request().is_some());
+        assert_eq!(text.id, tool.id);
+        let usage = items[2].1.as_ref().unwrap();
+        assert_eq!(usage.model, "gemini-2.5-flash");
+        assert_eq!(usage.usage.total_tokens, Some(15));
+    }
 }
@@ -14,6 +14,7 @@ use rmcp::model::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use std::borrow::Cow;
 use std::ops::Deref;
 
@@ -203,8 +204,16 @@ pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<
                     // Skip tool confirmation requests
                 }
                 MessageContent::Image(image) => {
-                    // Handle direct image content
-                    converted["content"] = json!([convert_image(image, image_format)]);
+                    // Keep any text already in the message alongside the image
+                    let image_part = convert_image(image, image_format);
+                    match converted.get_mut("content") {
+                        Some(Value::Array(parts)) => parts.push(image_part),
+                        Some(Value::String(text)) => {
+                            converted["content"] =
+                                json!([{"type": "text", "text": text.clone()}, image_part]);
+                        }
+                        _ => converted["content"] = json!([image_part]),
+                    }
                 }
                 MessageContent::FrontendToolRequest(request) => match &request.tool_call {
                     Ok(tool_call) => {
@@ -363,7 +372,26 @@ pub fn get_usage(usage: &Value) -> Usage {
             _ => None,
         });
 
-    Usage::new(input_tokens, output_tokens, total_tokens)
+    let cache_read_tokens = usage
+        .get("prompt_tokens_details")
+        .and_then(|details| details.get("cached_tokens"))
+        .and_then(|v| v.as_i64())
+        .filter(|tokens| *tokens > 0)
+        .map(|v| v as i32);
+
+    Usage::new(input_tokens, output_tokens, total_tokens).with_cache(cache_read_tokens, None)
+}
+
+/// A `prompt_cache_key` for this system prompt and tool set. OpenAI sends requests with the
+/// same key to the same prompt cache, which keeps hits up when many share the long prefix
+pub fn prompt_cache_key(system: &str, tools: &[Tool]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(system.as_bytes());
+    for tool in tools {
+        hasher.update(tool.name.as_bytes());
+    }
+    let digest = format!("{:x}", hasher.finalize());
+    format!("goose-{}", &digest[..32])
 }
 
 /// Validates and fixes tool schemas to ensure they have proper parameter structure.
@@ -540,6 +568,7 @@ where
                         role: Role::Assistant,
                         created: chrono::Utc::now().timestamp(),
                         content: contents,
+                        metadata: None,
                     }),
                     usage,
                 )
@@ -550,6 +579,7 @@ where
                         role: Role::Assistant,
                         created: chrono::Utc::now().timestamp(),
                         content: vec![MessageContent::text(text)],
+                        metadata: None,
                     }),
                     if chunk.choices[0].finish_reason.is_some() {
                         usage
@@ -839,6 +869,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_format_messages_text_with_image() -> anyhow::Result<()> {
+        let message = Message::user()
+            .with_text("What is in this picture?")
+            .with_image("aGVsbG8=", "image/png");
+        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+
+        assert_eq!(spec.len(), 1);
+        assert_eq!(spec[0]["content"][0]["type"], "text");
+        assert_eq!(spec[0]["content"][0]["text"], "What is in this picture?");
+        assert_eq!(spec[0]["content"][1]["type"], "image_url");
+        assert_eq!(
+            spec[0]["content"][1]["image_url"]["url"],
+            "data:image/png;base64,aGVsbG8="
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_format_messages_multiple_content() -> anyhow::Result<()> {
         let mut messages = vec![Message::assistant().with_tool_request(
@@ -1214,4 +1263,31 @@ data: [DONE]
 
         panic!("Expected tool call message with two calls, but did not see it");
     }
+
+    #[test]
+    fn test_prompt_cache_key() {
+        let tools = vec![Tool::new(
+            "shell",
+            "Run a command",
+            object!({"type": "object"}),
+        )];
+        let key = prompt_cache_key("be helpful", &tools);
+        assert!(key.starts_with("goose-"));
+        assert_eq!(key.len(), "goose-".len() + 32);
+        assert_eq!(key, prompt_cache_key("be helpful", &tools));
+        assert_ne!(key, prompt_cache_key("be helpful", &[]));
+        assert_ne!(key, prompt_cache_key("be brief", &tools));
+    }
+
+    #[test]
+    fn test_get_usage_cached_tokens() {
+        let usage = get_usage(&json!({
+            "prompt_tokens": 2000,
+            "completion_tokens": 10,
+            "total_tokens": 2010,
+            "prompt_tokens_details": {"cached_tokens": 1536}
+        }));
+        assert_eq!(usage.input_tokens, Some(2000));
+        assert_eq!(usage.cache_read_tokens, Some(1536));
+    }
 }
@@ -356,6 +356,38 @@ pub fn create_request(
     Ok(payload)
 }
 
+/// Model names from the result of `SHOW MODELS IN SNOWFLAKE.MODELS` run through the SQL API.
+/// Snowflake lists them in upper case, Cortex takes them in lower case
+pub fn model_names(statement_result: &Value) -> Vec<String> {
+    let Some(name_column) = statement_result
+        .pointer("/resultSetMetaData/rowType")
+        .and_then(|row_type| row_type.as_array())
+        .and_then(|columns| {
+            columns.iter().position(|column| {
+                column
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .is_some_and(|name| name.eq_ignore_ascii_case("name"))
+            })
+        })
+    else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = statement_result
+        .get("data")
+        .and_then(|data| data.as_array())
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|row| row.get(name_column).and_then(|name| name.as_str()))
+                .map(|name| name.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+    names
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -710,4 +742,22 @@ data: {"id":"a9537c2c-2017-4906-9817-2456168d89fa","model":"claude-3-5-sonnet","
             assert!(!content.contains("calculator"));
         }
     }
+
+    #[test]
+    fn test_model_names() {
+        let result = json!({
+            "resultSetMetaData": {
+                "rowType": [{"name": "created_on"}, {"name": "name"}, {"name": "model_type"}]
+            },
+            "data": [
+                ["2025-01-01", "CLAUDE-3-7-SONNET", "CORTEX_BASE"],
+                ["2025-01-01", "LLAMA3.1-70B", "CORTEX_BASE"]
+            ]
+        });
+        assert_eq!(
+            model_names(&result),
+            vec!["claude-3-7-sonnet", "llama3.1-70b"]
+        );
+        assert!(model_names(&json!({})).is_empty());
+    }
 }
@@ -49,7 +49,8 @@ impl fmt::Display for AuthToken {
 /// Represents the types of Application Default Credentials (ADC) supported.
 ///
 /// GCP supports multiple credential types for authentication. This enum
-/// represents the two main types: authorized user and service account.
+/// represents the main types: authorized user, service account and external
+/// account (workload identity federation).
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum AdcCredentials {
@@ -57,6 +58,8 @@ enum AdcCredentials {
     AuthorizedUser(AuthorizedUserCredentials),
     /// Credentials for a service account
     ServiceAccount(ServiceAccountCredentials),
+    /// Credentials from another identity provider, exchanged through workload identity federation
+    ExternalAccount(ExternalAccountCredentials),
     /// Credentials for the GCP native default account
     DefaultAccount(TokenResponse),
 }
@@ -92,11 +95,96 @@ struct ServiceAccountCredentials {
     token_uri: String,
 }
 
+/// Credentials for workload identity federation.
+///
+/// These come from a credential configuration file created with
+/// `gcloud iam workload-identity-pools create-cred-config`. A token issued by
+/// another identity provider is read from `credential_source` and exchanged
+/// with the Security Token Service for a Google access token.
+#[derive(Debug, Deserialize)]
+struct ExternalAccountCredentials {
+    /// The workload identity pool provider the token is exchanged for
+    audience: String,
+    /// The type of the token from the identity provider
+    subject_token_type: String,
+    /// URI of the Security Token Service
+    #[serde(default = "default_sts_token_uri")]
+    token_url: String,
+    /// URI to impersonate a service account with the exchanged token
+    service_account_impersonation_url: Option<String>,
+    /// Where the identity provider's token is read from
+    credential_source: CredentialSource,
+}
+
+/// Where the token of an external account is read from: a file or a URL.
+#[derive(Debug, Deserialize)]
+struct CredentialSource {
+    /// A file holding the token
+    file: Option<String>,
+    /// A URL returning the token
+    url: Option<String>,
+    /// Headers sent with the request to `url`
+    #[serde(default)]
+    headers: std::collections::HashMap<String, String>,
+    /// How the token is stored, text by default
+    format: Option<CredentialSourceFormat>,
+}
+
+/// The format of a token from a credential source.
+#[derive(Debug, Deserialize)]
+struct CredentialSourceFormat {
+    /// "text" or "json"
+    #[serde(rename = "type")]
+    format_type: String,
+    /// The field holding the token in a JSON source
+    subject_token_field_name: Option<String>,
+}
+
+/// Response structure for service account impersonation requests.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImpersonatedTokenResponse {
+    /// The access token of the impersonated service account
+    access_token: String,
+    /// When the token expires, as an RFC 3339 timestamp
+    expire_time: String,
+}
+
+const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
 /// Returns the default OAuth 2.0 token endpoint.
 fn default_token_uri() -> String {
     "https://oauth2.googleapis.com/token".to_string()
 }
 
+/// Returns the default Security Token Service endpoint.
+fn default_sts_token_uri() -> String {
+    "https://sts.googleapis.com/v1/token".to_string()
+}
+
+/// Returns the IAM endpoint that issues tokens for the given service account.
+fn impersonation_url(service_account: &str) -> String {
+    format!(
+        "https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/{}:generateAccessToken",
+        service_account
+    )
+}
+
+/// Where GcpAuth loads its credentials from.
+///
+/// With no options set the Application Default Credentials are used. A
+/// service account key or a workload identity federation configuration
+/// replaces them, and any of the three can impersonate a service account.
+#[derive(Debug, Clone, Default)]
+pub struct GcpAuthOptions {
+    /// Path to a service account JSON key file
+    pub service_account_key_path: Option<String>,
+    /// Path to a workload identity federation credential configuration file
+    pub workload_identity_config_path: Option<String>,
+    /// Email of a service account to impersonate with the loaded credentials
+    pub impersonate_service_account: Option<String>,
+}
+
 /// A trait that defines operations for interacting with the filesystem.
 ///
 /// This trait provides an abstraction over filesystem operations, primarily
@@ -199,6 +287,32 @@ impl AdcCredentials {
         ))
     }
 
+    /// Loads the credentials chosen by the options, falling back to the default locations.
+    async fn load_with_options(
+        fs_ops: &impl FilesystemOps,
+        options: &GcpAuthOptions,
+    ) -> Result<Self, AuthError> {
+        if let Some(path) = &options.service_account_key_path {
+            return match Self::load_from_file(fs_ops, path).await? {
+                creds @ AdcCredentials::ServiceAccount(_) => Ok(creds),
+                _ => Err(AuthError::Credentials(format!(
+                    "{} is not a service account key",
+                    path
+                ))),
+            };
+        }
+        if let Some(path) = &options.workload_identity_config_path {
+            return match Self::load_from_file(fs_ops, path).await? {
+                creds @ AdcCredentials::ExternalAccount(_) => Ok(creds),
+                _ => Err(AuthError::Credentials(format!(
+                    "{} is not a workload identity federation configuration",
+                    path
+                ))),
+            };
+        }
+        Self::load().await
+    }
+
     async fn load_from_file(fs_ops: &impl FilesystemOps, path: &str) -> Result<Self, AuthError> {
         let content = fs_ops.read_to_string(path.to_string()).await.map_err(|e| {
             AuthError::Credentials(format!("Failed to read credentials from {}: {}", path, e))
@@ -336,28 +450,30 @@ pub struct GcpAuth {
     client: reqwest::Client,
     /// Thread-safe cache for the current token
     cached_token: Arc<RwLock<Option<CachedToken>>>,
+    /// Service account whose tokens are requested with the loaded credentials
+    impersonate_service_account: Option<String>,
 }
 
 impl GcpAuth {
-    /// Creates a new GCP authentication handler.
+    /// Creates a GCP authentication handler with explicit credentials.
     ///
-    /// Initializes the authentication handler by:
-    /// 1. Loading credentials from default locations
-    /// 2. Setting up an HTTP client for token requests
-    /// 3. Initializing the token cache
-    ///
-    /// The credentials are loaded in the following order:
+    /// Credentials left unset in the options are loaded in the following order:
     /// 1. GOOGLE_APPLICATION_CREDENTIALS environment variable
     /// 2. Default gcloud credentials path
     /// 3. GCP metadata server (when running on GCP)
     ///
+    /// # Arguments
+    /// * `options` - The key file, workload identity configuration or
+    ///   service account to impersonate, the defaults are used for any left unset
+    ///
     /// # Returns
-    /// * `Result<Self, AuthError>` - A new GcpAuth instance or an error if initialization fails
-    pub async fn new() -> Result<Self, AuthError> {
+    /// * `Result<Self, AuthError>` - A new GcpAuth instance or an error if the credentials can't be loaded
+    pub async fn with_options(options: GcpAuthOptions) -> Result<Self, AuthError> {
         Ok(Self {
-            credentials: AdcCredentials::load().await?,
+            credentials: AdcCredentials::load_with_options(&RealFilesystemOps, &options).await?,
             client: reqwest::Client::new(),
             cached_token: Arc::new(RwLock::new(None)),
+            impersonate_service_account: options.impersonate_service_account,
         })
     }
 
@@ -397,6 +513,19 @@ impl GcpAuth {
             AdcCredentials::ServiceAccount(creds) => self.get_service_account_token(creds).await?,
             AdcCredentials::AuthorizedUser(creds) => self.get_authorized_user_token(creds).await?,
             AdcCredentials::DefaultAccount(creds) => self.get_default_access_token(creds).await?,
+            AdcCredentials::ExternalAccount(creds) => {
+                self.get_external_account_token(creds).await?
+            }
+        };
+        let token_response = match &self.impersonate_service_account {
+            Some(service_account) => {
+                self.impersonate(
+                    &impersonation_url(service_account),
+                    &token_response.access_token,
+                )
+                .await?
+            }
+            None => token_response,
         };
 
         let auth_token = AuthToken {
@@ -508,7 +637,7 @@ impl GcpAuth {
         let params = [
             ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
             ("assertion", &jwt),
-            ("scope", "https://www.googleapis.com/auth/cloud-platform"),
+            ("scope", CLOUD_PLATFORM_SCOPE),
         ];
 
         self.exchange_token(&creds.token_uri, &params).await
@@ -530,7 +659,7 @@ impl GcpAuth {
             ("client_secret", creds.client_secret.as_str()),
             ("refresh_token", creds.refresh_token.as_str()),
             ("grant_type", "refresh_token"),
-            ("scope", "https://www.googleapis.com/auth/cloud-platform"),
+            ("scope", CLOUD_PLATFORM_SCOPE),
         ];
 
         self.exchange_token(&creds.token_uri, &params).await
@@ -549,6 +678,144 @@ impl GcpAuth {
     ) -> Result<TokenResponse, AuthError> {
         Ok(creds.clone())
     }
+
+    /// Gets a token using workload identity federation.
+    ///
+    /// The identity provider's token is exchanged with the Security Token
+    /// Service, and the result used to impersonate a service account when the
+    /// configuration names one.
+    ///
+    /// # Arguments
+    /// * `creds` - External account credentials
+    ///
+    /// # Returns
+    /// * `Result<TokenResponse>` - The token response
+    async fn get_external_account_token(
+        &self,
+        creds: &ExternalAccountCredentials,
+    ) -> Result<TokenResponse, AuthError> {
+        let subject_token = self.read_subject_token(&creds.credential_source).await?;
+        let params = [
+            (
+                "grant_type",
+                "urn:ietf:params:oauth:grant-type:token-exchange",
+            ),
+            ("audience", creds.audience.as_str()),
+            ("scope", CLOUD_PLATFORM_SCOPE),
+            (
+                "requested_token_type",
+                "urn:ietf:params:oauth:token-type:access_token",
+            ),
+            ("subject_token", subject_token.as_str()),
+            ("subject_token_type", creds.subject_token_type.as_str()),
+        ];
+        let token_response = self.exchange_token(&creds.token_url, &params).await?;
+
+        match &creds.service_account_impersonation_url {
+            Some(url) => self.impersonate(url, &token_response.access_token).await,
+            None => Ok(token_response),
+        }
+    }
+
+    /// Reads the identity provider's token from a credential source.
+    ///
+    /// # Arguments
+    /// * `source` - The file or URL holding the token
+    ///
+    /// # Returns
+    /// * `Result<String>` - The subject token
+    async fn read_subject_token(&self, source: &CredentialSource) -> Result<String, AuthError> {
+        let content = if let Some(file) = &source.file {
+            tokio::fs::read_to_string(file).await.map_err(|e| {
+                AuthError::Credentials(format!("Failed to read subject token from {}: {}", file, e))
+            })?
+        } else if let Some(url) = &source.url {
+            let mut request = self.client.get(url);
+            for (name, value) in &source.headers {
+                request = request.header(name, value);
+            }
+            request
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| {
+                    AuthError::Credentials(format!("Failed to fetch subject token: {}", e))
+                })?
+                .text()
+                .await
+                .map_err(|e| {
+                    AuthError::Credentials(format!("Failed to fetch subject token: {}", e))
+                })?
+        } else {
+            return Err(AuthError::Credentials(
+                "Only file and url credential sources are supported".to_string(),
+            ));
+        };
+
+        match &source.format {
+            Some(format) if format.format_type == "json" => {
+                let field = format
+                    .subject_token_field_name
+                    .as_deref()
+                    .unwrap_or("access_token");
+                let value: serde_json::Value = serde_json::from_str(&content)
+                    .map_err(|e| AuthError::Credentials(format!("Invalid subject token: {}", e)))?;
+                value
+                    .get(field)
+                    .and_then(|token| token.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        AuthError::Credentials(format!("Subject token has no {} field", field))
+                    })
+            }
+            _ => Ok(content.trim().to_string()),
+        }
+    }
+
+    /// Gets a token for a service account using another access token.
+    ///
+    /// # Arguments
+    /// * `url` - The IAM generateAccessToken endpoint of the service account
+    /// * `access_token` - The token of an identity allowed to impersonate it
+    ///
+    /// # Returns
+    /// * `Result<TokenResponse>` - The service account's token
+    async fn impersonate(&self, url: &str, access_token: &str) -> Result<TokenResponse, AuthError> {
+        let response = self
+            .client
+            .post(url)
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({ "scope": [CLOUD_PLATFORM_SCOPE] }))
+            .send()
+            .await
+            .map_err(|e| AuthError::TokenExchange(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AuthError::TokenExchange(format!(
+                "Impersonation failed with status {}: {}",
+                status, error_text
+            )));
+        }
+
+        let impersonated = response
+            .json::<ImpersonatedTokenResponse>()
+            .await
+            .map_err(|e| AuthError::TokenExchange(format!("Invalid response: {}", e)))?;
+        let expires_in = chrono::DateTime::parse_from_rfc3339(&impersonated.expire_time)
+            .map(|expire_time| (expire_time.timestamp() - chrono::Utc::now().timestamp()).max(0))
+            .unwrap_or(0) as u64;
+
+        Ok(TokenResponse {
+            access_token: impersonated.access_token,
+            expires_in,
+            token_type: "Bearer".to_string(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -556,7 +823,7 @@ mod tests {
     use super::*;
     use mockall::predicate::eq;
     use tokio::time::sleep;
-    use wiremock::matchers::{header, method, path};
+    use wiremock::matchers::{body_string_contains, header, method, path};
     // Only import what we need
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -675,6 +942,7 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         GcpAuth {
             credentials: creds,
             client: reqwest::Client::new(),
+            impersonate_service_account: None,
             cached_token: Arc::new(RwLock::new(None)),
         }
     }
@@ -684,6 +952,7 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         let auth = GcpAuth {
             credentials: AdcCredentials::ServiceAccount(mock_service_account()),
             client: reqwest::Client::new(),
+            impersonate_service_account: None,
             cached_token: Arc::new(RwLock::new(Some(CachedToken {
                 token: AuthToken {
                     token_type: "Bearer".to_string(),
@@ -707,6 +976,7 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         let auth = GcpAuth {
             credentials: AdcCredentials::ServiceAccount(mock_service_account()),
             client: reqwest::Client::new(),
+            impersonate_service_account: None,
             cached_token: Arc::new(RwLock::new(Some(CachedToken {
                 token: AuthToken {
                     token_type: "Bearer".to_string(),
@@ -745,6 +1015,7 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         let auth = Arc::new(GcpAuth {
             credentials: AdcCredentials::ServiceAccount(mock_service_account()),
             client: reqwest::Client::new(),
+            impersonate_service_account: None,
             cached_token: Arc::new(RwLock::new(Some(CachedToken {
                 token: AuthToken {
                     token_type: "Bearer".to_string(),
@@ -776,6 +1047,7 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         let auth = Arc::new(GcpAuth {
             credentials: AdcCredentials::ServiceAccount(mock_service_account()),
             client: reqwest::Client::new(),
+            impersonate_service_account: None,
             cached_token: Arc::new(RwLock::new(Some(CachedToken {
                 token: AuthToken {
                     token_type: "Bearer".to_string(),
@@ -829,6 +1101,7 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         let auth = GcpAuth {
             credentials: AdcCredentials::AuthorizedUser(mock_authorized_user()),
             client: reqwest::Client::new(),
+            impersonate_service_account: None,
             cached_token: Arc::new(RwLock::new(None)),
         };
 
@@ -846,6 +1119,7 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         let auth = GcpAuth {
             credentials: AdcCredentials::ServiceAccount(mock_service_account()),
             client: reqwest::Client::new(),
+            impersonate_service_account: None,
             cached_token: Arc::new(RwLock::new(None)),
         };
 
@@ -1112,4 +1386,73 @@ iXVBc2YmAuU8hiOFUPxtyQfNzG5fQ0rhJSewdtyWxIadJSLj6fsK+AEsNQ==
         .await;
         assert!(matches!(result, Err(AuthError::Credentials(_))));
     }
+
+    #[tokio::test]
+    async fn test_external_account_token() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/token"))
+            .and(body_string_contains("subject_token=oidc-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "federated_token",
+                "expires_in": 3600,
+                "token_type": "Bearer"
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/projects/-/serviceAccounts/sa@test.iam.gserviceaccount.com:generateAccessToken"))
+            .and(header("authorization", "Bearer federated_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accessToken": "impersonated_token",
+                "expireTime": "2999-01-01T00:00:00Z"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let token_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(token_file.path(), "oidc-token\n").unwrap();
+        let config = serde_json::json!({
+            "type": "external_account",
+            "audience": "//iam.googleapis.com/projects/1/locations/global/workloadIdentityPools/pool/providers/github",
+            "subject_token_type": "urn:ietf:params:oauth:token-type:jwt",
+            "token_url": format!("{}/v1/token", mock_server.uri()),
+            "service_account_impersonation_url": format!(
+                "{}/v1/projects/-/serviceAccounts/sa@test.iam.gserviceaccount.com:generateAccessToken",
+                mock_server.uri()
+            ),
+            "credential_source": { "file": token_file.path().to_string_lossy() }
+        });
+        let credentials: AdcCredentials = serde_json::from_value(config).unwrap();
+        assert!(matches!(credentials, AdcCredentials::ExternalAccount(_)));
+
+        let auth = create_test_auth_with_creds(credentials).await;
+        let token = auth.get_token().await.unwrap();
+        assert_eq!(token.token_value, "impersonated_token");
+    }
+
+    #[tokio::test]
+    async fn test_load_with_options_checks_key_type() {
+        let mut fs_mock = MockFilesystemOpsMock::new();
+        fs_mock
+            .expect_read_to_string()
+            .with(eq("/path/to/key.json".to_string()))
+            .times(1)
+            .return_once(|_| {
+                Ok(r#"{
+                    "type": "authorized_user",
+                    "client_id": "test_client",
+                    "client_secret": "test_secret",
+                    "refresh_token": "test_refresh"
+                }"#
+                .to_string())
+            });
+
+        let options = GcpAuthOptions {
+            service_account_key_path: Some("/path/to/key.json".to_string()),
+            ..Default::default()
+        };
+        let result = AdcCredentials::load_with_options(&fs_mock, &options).await;
+        assert!(matches!(result, Err(AuthError::Credentials(_))));
+    }
 }
@@ -1,16 +1,22 @@
 use std::time::Duration;
 
 use anyhow::Result;
+use async_stream::try_stream;
 use async_trait::async_trait;
+use futures::TryStreamExt;
 use once_cell::sync::Lazy;
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, Response, StatusCode};
 use serde_json::Value;
+use std::io;
+use tokio::pin;
 use tokio::time::sleep;
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::io::StreamReader;
 use url::Url;
 
 use crate::conversation::message::Message;
 use crate::model::ModelConfig;
-use crate::providers::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
+use crate::providers::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage};
 
 use crate::providers::errors::ProviderError;
 use crate::providers::formats::gcpvertexai::{
@@ -19,8 +25,9 @@ use crate::providers::formats::gcpvertexai::{
 };
 
 use crate::impl_provider_default;
-use crate::providers::formats::gcpvertexai::GcpLocation::Iowa;
-use crate::providers::gcpauth::GcpAuth;
+use crate::providers::formats::gcpvertexai::GcpLocation;
+use crate::providers::formats::{anthropic, google};
+use crate::providers::gcpauth::{GcpAuth, GcpAuthOptions};
 use crate::providers::retry::RetryConfig;
 use crate::providers::utils::emit_debug_trace;
 use rmcp::model::Tool;
@@ -70,7 +77,7 @@ pub struct GcpVertexAIProvider {
     host: String,
     /// GCP project identifier
     project_id: String,
-    /// GCP region for model deployment
+    /// GCP region for model deployment, or `global`
     location: String,
     /// Configuration for the specific model being used
     model: ModelConfig,
@@ -96,7 +103,26 @@ impl GcpVertexAIProvider {
     /// # Arguments
     /// * `model` - Configuration for the model to be used
     pub fn new(model: ModelConfig) -> Result<Self> {
-        futures::executor::block_on(Self::new_async(model))
+        super::runtime::block_on_setup(Self::new_async(model))
+    }
+
+    /// Reads which credentials to authenticate with, the Application Default
+    /// Credentials are used when none are configured.
+    ///
+    /// # Arguments
+    /// * `config` - Global configuration instance
+    fn auth_options(config: &crate::config::Config) -> GcpAuthOptions {
+        let path = |key: &str| {
+            config
+                .get_param::<String>(key)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        };
+        GcpAuthOptions {
+            service_account_key_path: path("GCP_SERVICE_ACCOUNT_KEY_PATH"),
+            workload_identity_config_path: path("GCP_WORKLOAD_IDENTITY_CONFIG_PATH"),
+            impersonate_service_account: path("GCP_IMPERSONATE_SERVICE_ACCOUNT"),
+        }
     }
 
     /// Async implementation of new provider instance creation.
@@ -107,13 +133,12 @@ impl GcpVertexAIProvider {
         let config = crate::config::Config::global();
         let project_id = config.get_param("GCP_PROJECT_ID")?;
         let location = Self::determine_location(config)?;
-        let host = format!("https://{}-aiplatform.googleapis.com", location);
+        let host = location.host();
+        let location = location.to_string();
 
-        let client = Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
-            .build()?;
+        let client = super::runtime::shared_client(Duration::from_secs(DEFAULT_TIMEOUT_SECS))?;
 
-        let auth = GcpAuth::new().await?;
+        let auth = GcpAuth::with_options(Self::auth_options(config)).await?;
 
         // Load optional retry configuration from environment
         let retry_config = Self::load_retry_config(config);
@@ -167,14 +192,27 @@ impl GcpVertexAIProvider {
     /// Determines the appropriate GCP location for model deployment.
     ///
     /// Location is determined in the following order:
-    /// 1. Custom location from GCP_LOCATION environment variable
-    /// 2. Global default location (Iowa)
-    fn determine_location(config: &crate::config::Config) -> Result<String> {
-        Ok(config
-            .get_param("GCP_LOCATION")
+    /// 1. Custom location from GCP_LOCATION environment variable, which must be a known
+    ///    region or `global`
+    /// 2. Default location (Iowa)
+    fn determine_location(config: &crate::config::Config) -> Result<GcpLocation> {
+        match config
+            .get_param::<String>("GCP_LOCATION")
             .ok()
-            .filter(|location: &String| !location.trim().is_empty())
-            .unwrap_or_else(|| Iowa.to_string()))
+            .filter(|location| !location.trim().is_empty())
+        {
+            Some(location) => Ok(GcpLocation::try_from(location.as_str())?),
+            None => Ok(GcpLocation::iowa()),
+        }
+    }
+
+    /// The location requests for a model go to: the configured one, unless it is the global
+    /// endpoint and the model isn't served there.
+    fn location_for(&self, context: &RequestContext) -> String {
+        match GcpLocation::try_from(self.location.as_str()) {
+            Ok(configured) => context.model.location_for(&configured).to_string(),
+            Err(_) => self.location.clone(),
+        }
     }
 
     /// Retrieves an authentication token for API requests.
@@ -191,26 +229,31 @@ impl GcpVertexAIProvider {
     /// # Arguments
     /// * `provider` - The model provider (Anthropic or Google)
     /// * `location` - The GCP location for model deployment
+    /// * `stream` - Whether the response is streamed as server-sent events
     fn build_request_url(
         &self,
         provider: ModelProvider,
         location: &str,
+        stream: bool,
     ) -> Result<Url, GcpVertexAIError> {
         // Create host URL for the specified location
         let host_url = if self.location == location {
-            &self.host
+            self.host.clone()
         } else {
-            // Only allocate a new string if location differs
-            &self.host.replace(&self.location, location)
+            GcpLocation::try_from(location)
+                .map_err(|e| GcpVertexAIError::InvalidUrl(e.to_string()))?
+                .host()
         };
 
         let base_url =
-            Url::parse(host_url).map_err(|e| GcpVertexAIError::InvalidUrl(e.to_string()))?;
-
-        // Determine endpoint based on provider type
-        let endpoint = match provider {
-            ModelProvider::Anthropic => "streamRawPredict",
-            ModelProvider::Google => "generateContent",
+            Url::parse(&host_url).map_err(|e| GcpVertexAIError::InvalidUrl(e.to_string()))?;
+
+        // Determine endpoint based on provider type. Anthropic models stream when the
+        // payload asks for it
+        let endpoint = match (provider, stream) {
+            (ModelProvider::Anthropic, _) => "streamRawPredict",
+            (ModelProvider::Google, false) => "generateContent",
+            (ModelProvider::Google, true) => "streamGenerateContent",
         };
 
         // Construct path for URL
@@ -223,26 +266,33 @@ impl GcpVertexAIProvider {
             endpoint
         );
 
-        base_url
+        let mut url = base_url
             .join(&path)
-            .map_err(|e| GcpVertexAIError::InvalidUrl(e.to_string()))
+            .map_err(|e| GcpVertexAIError::InvalidUrl(e.to_string()))?;
+        if stream && provider == ModelProvider::Google {
+            url.query_pairs_mut().append_pair("alt", "sse");
+        }
+        Ok(url)
     }
 
-    /// Makes an authenticated POST request to the Vertex AI API at a specific location.
-    /// Includes retry logic for 429 (Too Many Requests) and 529 (API Overloaded) errors.
+    /// Sends an authenticated POST request to the Vertex AI API at a specific location.
+    /// Includes retry logic for 429 (Too Many Requests) and 529 (API Overloaded) errors,
+    /// any other response is returned as it is.
     ///
     /// # Arguments
     /// * `payload` - The request payload to send
     /// * `context` - Request context containing model information
     /// * `location` - The GCP location for the request
-    async fn post_with_location(
+    /// * `stream` - Whether to request a streamed response
+    async fn send_with_location(
         &self,
         payload: &Value,
         context: &RequestContext,
         location: &str,
-    ) -> Result<Value, ProviderError> {
+        stream: bool,
+    ) -> Result<Response, ProviderError> {
         let url = self
-            .build_request_url(context.provider(), location)
+            .build_request_url(context.provider(), location, stream)
             .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
 
         // Initialize separate counters for different error types
@@ -365,35 +415,78 @@ impl GcpVertexAIProvider {
                     sleep(delay).await;
                 }
                 // For any other status codes, process normally
-                _ => {
-                    let response_json = response.json::<Value>().await.map_err(|e| {
-                        ProviderError::RequestFailed(format!("Failed to parse response: {e}"))
-                    })?;
-
-                    return match status {
-                        StatusCode::OK => Ok(response_json),
-                        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
-                            tracing::debug!(
-                                "Authentication failed. Status: {status}, Payload: {payload:?}"
-                            );
-                            Err(ProviderError::Authentication(format!(
-                                "Authentication failed: {response_json:?}"
-                            )))
-                        }
-                        _ => {
-                            tracing::debug!(
-                                "Request failed. Status: {status}, Response: {response_json:?}"
-                            );
-                            Err(ProviderError::RequestFailed(format!(
-                                "Request failed with status {status}: {response_json:?}"
-                            )))
-                        }
-                    };
-                }
+                _ => return Ok(response),
             }
         }
     }
 
+    /// Maps an unsuccessful response to a provider error.
+    fn status_error(status: StatusCode, response_json: Value, payload: &Value) -> ProviderError {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                tracing::debug!("Authentication failed. Status: {status}, Payload: {payload:?}");
+                ProviderError::Authentication(format!("Authentication failed: {response_json:?}"))
+            }
+            _ => {
+                tracing::debug!("Request failed. Status: {status}, Response: {response_json:?}");
+                ProviderError::RequestFailed(format!(
+                    "Request failed with status {status}: {response_json:?}"
+                ))
+            }
+        }
+    }
+
+    /// Makes an authenticated POST request to the Vertex AI API at a specific location.
+    ///
+    /// # Arguments
+    /// * `payload` - The request payload to send
+    /// * `context` - Request context containing model information
+    /// * `location` - The GCP location for the request
+    async fn post_with_location(
+        &self,
+        payload: &Value,
+        context: &RequestContext,
+        location: &str,
+    ) -> Result<Value, ProviderError> {
+        let response = self
+            .send_with_location(payload, context, location, false)
+            .await?;
+        let status = response.status();
+        let response_json = response
+            .json::<Value>()
+            .await
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {e}")))?;
+
+        match status {
+            StatusCode::OK => Ok(response_json),
+            _ => Err(Self::status_error(status, response_json, payload)),
+        }
+    }
+
+    /// Opens a streamed response from the Vertex AI API at a specific location. Errors before
+    /// the stream starts are retried like unary requests.
+    ///
+    /// # Arguments
+    /// * `payload` - The request payload to send
+    /// * `context` - Request context containing model information
+    /// * `location` - The GCP location for the request
+    async fn stream_with_location(
+        &self,
+        payload: &Value,
+        context: &RequestContext,
+        location: &str,
+    ) -> Result<Response, ProviderError> {
+        let response = self
+            .send_with_location(payload, context, location, true)
+            .await?;
+        let status = response.status();
+        if status == StatusCode::OK {
+            return Ok(response);
+        }
+        let response_json = response.json::<Value>().await.unwrap_or_default();
+        Err(Self::status_error(status, response_json, payload))
+    }
+
     /// Makes an authenticated POST request to the Vertex AI API with fallback for invalid locations.
     ///
     /// # Arguments
@@ -405,12 +498,11 @@ impl GcpVertexAIProvider {
         context: &RequestContext,
     ) -> Result<Value, ProviderError> {
         // Try with user-specified location first
-        let result = self
-            .post_with_location(payload, context, &self.location)
-            .await;
+        let location = self.location_for(context);
+        let result = self.post_with_location(payload, context, &location).await;
 
         // If location is already the known location for the model or request succeeded, return result
-        if self.location == context.model.known_location().to_string() || result.is_ok() {
+        if location == context.model.known_location().to_string() || result.is_ok() {
             return result;
         }
 
@@ -418,7 +510,7 @@ impl GcpVertexAIProvider {
         match &result {
             Err(ProviderError::RequestFailed(msg)) => {
                 let model_name = context.model.to_string();
-                let configured_location = &self.location;
+                let configured_location = &location;
                 let known_location = context.model.known_location().to_string();
 
                 tracing::error!(
@@ -432,6 +524,110 @@ impl GcpVertexAIProvider {
             _ => result,
         }
     }
+
+    /// Opens a streamed response, with the same fallback to the model's known location as
+    /// `post`.
+    ///
+    /// # Arguments
+    /// * `payload` - The request payload to send
+    /// * `context` - Request context containing model information
+    async fn open_stream(
+        &self,
+        payload: &Value,
+        context: &RequestContext,
+    ) -> Result<Response, ProviderError> {
+        let location = self.location_for(context);
+        let result = self.stream_with_location(payload, context, &location).await;
+
+        let known_location = context.model.known_location().to_string();
+        match result {
+            Err(ProviderError::RequestFailed(msg)) if location != known_location => {
+                let model_name = context.model.to_string();
+                let configured_location = &location;
+                tracing::error!(
+                    "Trying known location {known_location} for {model_name} instead of {configured_location}: {msg}"
+                );
+                self.stream_with_location(payload, context, &known_location)
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    /// Lists the models a publisher offers on Vertex AI, following every page.
+    ///
+    /// # Arguments
+    /// * `publisher` - The model provider whose models to list
+    async fn list_publisher_models(
+        &self,
+        publisher: ModelProvider,
+    ) -> Result<Vec<String>, ProviderError> {
+        let base_url =
+            Url::parse(&self.host).map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+        let prefix = match publisher {
+            ModelProvider::Anthropic => "claude-",
+            ModelProvider::Google => "gemini-",
+        };
+
+        let mut models = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = base_url
+                .join(&format!("v1beta1/publishers/{}/models", publisher.as_str()))
+                .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+            url.query_pairs_mut().append_pair("pageSize", "100");
+            if let Some(token) = &page_token {
+                url.query_pairs_mut().append_pair("pageToken", token);
+            }
+
+            let auth_header = self
+                .get_auth_header()
+                .await
+                .map_err(|e| ProviderError::Authentication(e.to_string()))?;
+            let response = self
+                .client
+                .get(url)
+                .header("Authorization", auth_header)
+                .header("x-goog-user-project", &self.project_id)
+                .send()
+                .await
+                .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+            let status = response.status();
+            let response_json = response.json::<Value>().await.map_err(|e| {
+                ProviderError::RequestFailed(format!("Failed to parse response: {e}"))
+            })?;
+            if status != StatusCode::OK {
+                return Err(Self::status_error(status, response_json, &Value::Null));
+            }
+
+            let page = response_json["publisherModels"].as_array();
+            for model in page.into_iter().flatten() {
+                let Some(name) = model["name"]
+                    .as_str()
+                    .and_then(|name| name.rsplit('/').next())
+                    .filter(|name| name.starts_with(prefix))
+                else {
+                    continue;
+                };
+                // Claude models are addressed by name and version
+                let version = model["versionId"].as_str().filter(|v| !v.is_empty());
+                match (publisher, version) {
+                    (ModelProvider::Anthropic, Some(version)) => {
+                        models.push(format!("{name}@{version}"))
+                    }
+                    _ => models.push(name.to_string()),
+                }
+            }
+
+            page_token = response_json["nextPageToken"]
+                .as_str()
+                .filter(|token| !token.is_empty())
+                .map(str::to_string);
+            if page_token.is_none() {
+                return Ok(models);
+            }
+        }
+    }
 }
 
 impl_provider_default!(GcpVertexAIProvider);
@@ -476,7 +672,12 @@ impl Provider for GcpVertexAIProvider {
             GCP_VERTEX_AI_DOC_URL,
             vec![
                 ConfigKey::new("GCP_PROJECT_ID", true, false, None),
-                ConfigKey::new("GCP_LOCATION", true, false, Some(Iowa.to_string().as_str())),
+                ConfigKey::new(
+                    "GCP_LOCATION",
+                    true,
+                    false,
+                    Some(GcpLocation::iowa().to_string().as_str()),
+                ),
                 ConfigKey::new(
                     "GCP_MAX_RETRIES",
                     false,
@@ -501,6 +702,9 @@ impl Provider for GcpVertexAIProvider {
                     false,
                     Some(&DEFAULT_MAX_RETRY_INTERVAL_MS.to_string()),
                 ),
+                ConfigKey::new("GCP_SERVICE_ACCOUNT_KEY_PATH", false, false, None),
+                ConfigKey::new("GCP_WORKLOAD_IDENTITY_CONFIG_PATH", false, false, None),
+                ConfigKey::new("GCP_IMPERSONATE_SERVICE_ACCOUNT", false, false, None),
             ],
         )
     }
@@ -542,6 +746,72 @@ impl Provider for GcpVertexAIProvider {
     fn get_model_config(&self) -> ModelConfig {
         self.model.clone()
     }
+
+    /// Lists the Gemini and Claude models available through Vertex AI. When listing fails the
+    /// model is entered by hand instead.
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        let mut models = Vec::new();
+        for publisher in [ModelProvider::Google, ModelProvider::Anthropic] {
+            match self.list_publisher_models(publisher).await {
+                Ok(publisher_models) => models.extend(publisher_models),
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to list {} models on Vertex AI: {}",
+                        publisher.as_str(),
+                        e
+                    );
+                    return Ok(None);
+                }
+            }
+        }
+        models.sort();
+        models.dedup();
+        Ok(Some(models))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    /// Streams a model interaction, from streamGenerateContent for Gemini models and
+    /// streamRawPredict for Claude models.
+    ///
+    /// # Arguments
+    /// * `system` - System prompt or context
+    /// * `messages` - Array of previous messages in the conversation
+    /// * `tools` - Array of available tools for the model
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let (mut request, context) = create_request(&self.model, system, messages, tools)?;
+        let provider = context.provider();
+        if provider == ModelProvider::Anthropic {
+            request["stream"] = Value::Bool(true);
+        }
+
+        let response = self.open_stream(&request, &context).await?;
+        let stream = response.bytes_stream().map_err(io::Error::other);
+        let model = self.model.clone();
+
+        Ok(Box::pin(try_stream! {
+            let stream_reader = StreamReader::new(stream);
+            let framed = FramedRead::new(stream_reader, LinesCodec::new()).map_err(anyhow::Error::from);
+
+            let message_stream: std::pin::Pin<Box<dyn futures::Stream<Item = anyhow::Result<(Option<Message>, Option<ProviderUsage>)>> + Send>> = match provider {
+                ModelProvider::Anthropic => Box::pin(anthropic::response_to_streaming_message(framed)),
+                ModelProvider::Google => Box::pin(google::response_to_streaming_message(framed)),
+            };
+            pin!(message_stream);
+            while let Some(message) = futures::StreamExt::next(&mut message_stream).await {
+                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                emit_debug_trace(&model, &request, &message, &usage.as_ref().map(|f| f.usage).unwrap_or_default());
+                yield (message, usage);
+            }
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -632,7 +902,8 @@ mod tests {
         assert!(model_names.contains(&"claude-3-5-sonnet-v2@20241022".to_string()));
         assert!(model_names.contains(&"gemini-1.5-pro-002".to_string()));
         assert!(model_names.contains(&"gemini-2.5-pro".to_string()));
-        // Should contain the original 2 config keys plus 4 new retry-related ones
-        assert_eq!(metadata.config_keys.len(), 6);
+        // The project and location, 4 retry keys, and the service account key path, workload
+        // identity config path and service account to impersonate for authentication
+        assert_eq!(metadata.config_keys.len(), 9);
     }
 }
@@ -121,9 +121,7 @@ impl_provider_default!(GithubCopilotProvider);
 
 impl GithubCopilotProvider {
     pub fn from_env(model: ModelConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(600))
-            .build()?;
+        let client = super::runtime::shared_client(Duration::from_secs(600))?;
         let cache = DiskCache::new();
         let mu = tokio::sync::Mutex::new(RefCell::new(None));
         Ok(Self {
@@ -1,4 +1,5 @@
 use super::api_client::{ApiClient, AuthMethod};
+use super::embedding::{embedding_model, EmbeddingCapable};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
 use super::utils::{emit_debug_trace, handle_response_google_compat, unescape_json_values};
@@ -6,15 +7,22 @@ use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
 use crate::providers::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
-use crate::providers::formats::google::{create_request, get_usage, response_to_message};
+use crate::providers::formats::google::{
+    create_cached_content_request, create_request, get_usage, response_to_message,
+    use_cached_content,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use rmcp::model::Tool;
-use serde_json::Value;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 
 pub const GOOGLE_API_HOST: &str = "https://generativelanguage.googleapis.com";
 pub const GOOGLE_DEFAULT_MODEL: &str = "gemini-2.5-flash";
 pub const GOOGLE_DEFAULT_FAST_MODEL: &str = "gemini-1.5-flash";
+pub const GOOGLE_DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-004";
 pub const GOOGLE_KNOWN_MODELS: &[&str] = &[
     // Gemini 2.5 models (latest generation)
     "gemini-2.5-pro",
@@ -45,11 +53,28 @@ pub const GOOGLE_KNOWN_MODELS: &[&str] = &[
 
 pub const GOOGLE_DOC_URL: &str = "https://ai.google.dev/gemini-api/docs/models";
 
+/// Replace a cached context this long before it expires, so requests never refer to a
+/// cache that is gone by the time they arrive
+const CONTEXT_CACHE_MARGIN: Duration = Duration::from_secs(30);
+
+/// An explicit context cache for one system prompt and tool set
+#[derive(Debug)]
+struct ContextCache {
+    fingerprint: String,
+    /// The cachedContents name, none when the API refused to cache this context
+    name: Option<String>,
+    expires_at: Instant,
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct GoogleProvider {
     #[serde(skip)]
     api_client: ApiClient,
     model: ModelConfig,
+    /// How long explicit context caches live, caching is off when unset
+    context_cache_ttl: Option<u64>,
+    #[serde(skip)]
+    context_cache: Mutex<Option<ContextCache>>,
 }
 
 impl_provider_default!(GoogleProvider);
@@ -71,8 +96,17 @@ impl GoogleProvider {
 
         let api_client =
             ApiClient::new(host, auth)?.with_header("Content-Type", "application/json")?;
+        let context_cache_ttl: Option<u64> = config
+            .get_param("GOOGLE_CONTEXT_CACHE_TTL")
+            .ok()
+            .filter(|ttl| *ttl > 0);
 
-        Ok(Self { api_client, model })
+        Ok(Self {
+            api_client,
+            model,
+            context_cache_ttl,
+            context_cache: Mutex::new(None),
+        })
     }
 
     async fn post(&self, model_name: &str, payload: &Value) -> Result<Value, ProviderError> {
@@ -80,6 +114,58 @@ impl GoogleProvider {
         let response = self.api_client.response_post(&path, payload).await?;
         handle_response_google_compat(response).await
     }
+
+    /// The cachedContents name holding this system prompt and tool set, created when there
+    /// is none yet. Gemini only caches contexts above a minimum size, a context it refuses
+    /// is remembered until the TTL passes so every request doesn't try again
+    async fn cached_context(
+        &self,
+        model_name: &str,
+        system: &str,
+        tools: &[Tool],
+    ) -> Option<String> {
+        let ttl = self.context_cache_ttl?;
+        let mut hasher = Sha256::new();
+        hasher.update(model_name.as_bytes());
+        hasher.update(system.as_bytes());
+        hasher.update(serde_json::to_vec(tools).unwrap_or_default());
+        let fingerprint = format!("{:x}", hasher.finalize());
+
+        let mut cache = self.context_cache.lock().await;
+        if let Some(existing) = cache.as_ref() {
+            if existing.fingerprint == fingerprint
+                && existing.expires_at > Instant::now() + CONTEXT_CACHE_MARGIN
+            {
+                return existing.name.clone();
+            }
+        }
+
+        let payload = create_cached_content_request(model_name, system, tools, ttl);
+        let name = match self
+            .api_client
+            .response_post("v1beta/cachedContents", &payload)
+            .await
+        {
+            Ok(response) => match handle_response_google_compat(response).await {
+                Ok(json) => json.get("name").and_then(|v| v.as_str()).map(String::from),
+                Err(e) => {
+                    tracing::debug!("Not caching the context: {}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                tracing::debug!("Not caching the context: {}", e);
+                None
+            }
+        };
+
+        *cache = Some(ContextCache {
+            fingerprint,
+            name: name.clone(),
+            expires_at: Instant::now() + Duration::from_secs(ttl),
+        });
+        name
+    }
 }
 
 #[async_trait]
@@ -95,6 +181,7 @@ impl Provider for GoogleProvider {
             vec![
                 ConfigKey::new("GOOGLE_API_KEY", true, true, None),
                 ConfigKey::new("GOOGLE_HOST", false, false, Some(GOOGLE_API_HOST)),
+                ConfigKey::new("GOOGLE_CONTEXT_CACHE_TTL", false, false, None),
             ],
         )
     }
@@ -114,7 +201,13 @@ impl Provider for GoogleProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let payload = create_request(model_config, system, messages, tools)?;
+        let mut payload = create_request(model_config, system, messages, tools)?;
+        if let Some(cached_content) = self
+            .cached_context(&model_config.model_name, system, tools)
+            .await
+        {
+            use_cached_content(&mut payload, &cached_content);
+        }
 
         // Make request
         let response = self
@@ -152,4 +245,61 @@ impl Provider for GoogleProvider {
         models.sort();
         Ok(Some(models))
     }
+
+    fn supports_embeddings(&self) -> bool {
+        true
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        Some(embedding_model("google", GOOGLE_DEFAULT_EMBEDDING_MODEL))
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        EmbeddingCapable::create_embeddings(self, texts)
+            .await
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl EmbeddingCapable for GoogleProvider {
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let model = embedding_model("google", GOOGLE_DEFAULT_EMBEDDING_MODEL);
+        let model_path = format!("models/{}", model);
+        let requests: Vec<Value> = texts
+            .iter()
+            .map(|text| {
+                json!({
+                    "model": model_path,
+                    "content": { "parts": [{ "text": text }] }
+                })
+            })
+            .collect();
+        let payload = json!({ "requests": requests });
+
+        let path = format!("v1beta/{}:batchEmbedContents", model_path);
+        let response = self.api_client.response_post(&path, &payload).await?;
+        let json = handle_response_google_compat(response).await?;
+
+        json.get("embeddings")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Missing embeddings field in response"))?
+            .iter()
+            .map(|item| {
+                item.get("values")
+                    .and_then(|v| v.as_array())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid embedding format"))
+                    .map(|values| {
+                        values
+                            .iter()
+                            .map(|v| v.as_f64().unwrap_or(0.0) as f32)
+                            .collect()
+                    })
+            })
+            .collect()
+    }
 }
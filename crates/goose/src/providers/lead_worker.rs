@@ -433,6 +433,14 @@ impl Provider for LeadWorkerProvider {
         self.lead_provider.supports_embeddings() || self.worker_provider.supports_embeddings()
     }
 
+    fn embedding_model(&self) -> Option<String> {
+        if self.lead_provider.supports_embeddings() {
+            self.lead_provider.embedding_model()
+        } else {
+            self.worker_provider.embedding_model()
+        }
+    }
+
     async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
         // Use the lead provider for embeddings if it supports them, otherwise use worker
         if self.lead_provider.supports_embeddings() {
@@ -2,10 +2,11 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 use super::api_client::{ApiClient, AuthMethod};
 use super::base::{ConfigKey, ModelInfo, Provider, ProviderMetadata, ProviderUsage};
-use super::embedding::EmbeddingCapable;
+use super::embedding::{embedding_model, EmbeddingCapable};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
 use super::utils::{emit_debug_trace, get_model, handle_response_openai_compat, ImageFormat};
@@ -15,6 +16,7 @@ use crate::model::ModelConfig;
 use rmcp::model::Tool;
 
 pub const LITELLM_DEFAULT_MODEL: &str = "gpt-4o-mini";
+pub const LITELLM_DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
 pub const LITELLM_DOC_URL: &str = "https://docs.litellm.ai/docs/";
 
 #[derive(Debug, serde::Serialize)]
@@ -23,6 +25,9 @@ pub struct LiteLLMProvider {
     api_client: ApiClient,
     base_path: String,
     model: ModelConfig,
+    /// Whether the model supports cache control, looked up once
+    #[serde(skip)]
+    cache_control: OnceLock<bool>,
 }
 
 impl_provider_default!(LiteLLMProvider);
@@ -69,6 +74,7 @@ impl LiteLLMProvider {
             api_client,
             base_path,
             model,
+            cache_control: OnceLock::new(),
         })
     }
 
@@ -198,16 +204,26 @@ impl Provider for LiteLLMProvider {
         true
     }
 
+    fn embedding_model(&self) -> Option<String> {
+        Some(embedding_model("litellm", LITELLM_DEFAULT_EMBEDDING_MODEL))
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        EmbeddingCapable::create_embeddings(self, texts)
+            .await
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))
+    }
+
     fn supports_cache_control(&self) -> bool {
-        if let Ok(models) = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(self.fetch_models())
-        }) {
-            if let Some(model_info) = models.iter().find(|m| m.name == self.model.model_name) {
-                return model_info.supports_cache_control.unwrap_or(false);
+        *self.cache_control.get_or_init(|| {
+            if let Ok(models) = super::runtime::block_on_setup(self.fetch_models()) {
+                if let Some(model_info) = models.iter().find(|m| m.name == self.model.model_name) {
+                    return model_info.supports_cache_control.unwrap_or(false);
+                }
             }
-        }
 
-        self.model.model_name.to_lowercase().contains("claude")
+            self.model.model_name.to_lowercase().contains("claude")
+        })
     }
 
     async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
@@ -227,12 +243,9 @@ impl Provider for LiteLLMProvider {
 #[async_trait]
 impl EmbeddingCapable for LiteLLMProvider {
     async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, anyhow::Error> {
-        let embedding_model = std::env::var("GOOSE_EMBEDDING_MODEL")
-            .unwrap_or_else(|_| "text-embedding-3-small".to_string());
-
         let payload = json!({
             "input": texts,
-            "model": embedding_model,
+            "model": embedding_model("litellm", LITELLM_DEFAULT_EMBEDDING_MODEL),
             "encoding_format": "float"
         });
 
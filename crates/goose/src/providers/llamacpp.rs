@@ -0,0 +1,318 @@
+use anyhow::Result;
+use async_stream::try_stream;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mcp_core::tool::ToolCall;
+use rmcp::model::Tool;
+use serde_json::{json, Value};
+use std::io;
+use tokio::pin;
+use tokio_stream::StreamExt;
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::io::StreamReader;
+use uuid::Uuid;
+
+use super::api_client::{ApiClient, AuthMethod};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::errors::ProviderError;
+use super::formats::openai::{
+    create_request, get_usage, response_to_message, response_to_streaming_message,
+};
+use super::retry::ProviderRetry;
+use super::toolshim::{convert_tool_messages_to_text, format_tool_info};
+use super::utils::{
+    emit_debug_trace, get_model, handle_response_openai_compat, handle_status_openai_compat,
+    ImageFormat,
+};
+use crate::conversation::message::Message;
+use crate::impl_provider_default;
+use crate::model::ModelConfig;
+
+pub const LLAMACPP_DEFAULT_HOST: &str = "http://localhost:8080";
+// The server runs whichever model it was started with, LM Studio picks by name
+pub const LLAMACPP_DEFAULT_MODEL: &str = "local-model";
+pub const LLAMACPP_DOC_URL: &str = "https://github.com/ggml-org/llama.cpp/tree/master/tools/server";
+
+/// How tool calls are made: with the server's own tool calling, or by constraining the reply to a
+/// JSON schema of the tools, which works with any model
+const LLAMACPP_TOOL_CALLING_KEY: &str = "LLAMACPP_TOOL_CALLING";
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+enum ToolCalling {
+    Native,
+    Grammar,
+}
+
+#[derive(serde::Serialize)]
+pub struct LlamaCppProvider {
+    #[serde(skip)]
+    api_client: ApiClient,
+    model: ModelConfig,
+    tool_calling: ToolCalling,
+}
+
+impl_provider_default!(LlamaCppProvider);
+
+impl LlamaCppProvider {
+    pub fn from_env(model: ModelConfig) -> Result<Self> {
+        let config = crate::config::Config::global();
+        let host: String = config
+            .get_param("LLAMACPP_HOST")
+            .unwrap_or_else(|_| LLAMACPP_DEFAULT_HOST.to_string());
+        // LM Studio shows its address with the /v1 suffix, the paths below include it
+        let host = host
+            .trim_end_matches('/')
+            .trim_end_matches("/v1")
+            .to_string();
+        let api_key: String = config.get_secret("LLAMACPP_API_KEY").unwrap_or_default();
+        let tool_calling = match config
+            .get_param::<String>(LLAMACPP_TOOL_CALLING_KEY)
+            .unwrap_or_default()
+            .as_str()
+        {
+            "grammar" => ToolCalling::Grammar,
+            _ => ToolCalling::Native,
+        };
+
+        let auth = AuthMethod::BearerToken(api_key);
+        let api_client = ApiClient::new(host, auth)?;
+
+        Ok(Self {
+            api_client,
+            model,
+            tool_calling,
+        })
+    }
+
+    async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
+        let response = self
+            .api_client
+            .response_post("v1/chat/completions", payload)
+            .await?;
+        handle_response_openai_compat(response).await
+    }
+
+    fn create_payload(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<Value, ProviderError> {
+        if self.tool_calling == ToolCalling::Native || tools.is_empty() {
+            return Ok(create_request(
+                model_config,
+                system,
+                messages,
+                tools,
+                &ImageFormat::OpenAi,
+            )?);
+        }
+
+        let system = format!(
+            "{}\n\n# Tools\n\n{}Reply with a JSON object. To call a tool use {{\"tool\": \"tool_name\", \"arguments\": {{...}}}}, calling one tool at a time. To answer the user use {{\"message\": \"your answer\"}}.",
+            system,
+            format_tool_info(tools)
+        );
+        let messages = convert_tool_messages_to_text(messages);
+        let mut payload = create_request(
+            model_config,
+            &system,
+            messages.messages(),
+            &[],
+            &ImageFormat::OpenAi,
+        )?;
+        payload["response_format"] = json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": "reply",
+                "strict": true,
+                "schema": reply_schema(tools),
+            },
+        });
+        Ok(payload)
+    }
+}
+
+/// The schema the reply is constrained to: a message or a call to one of the tools
+fn reply_schema(tools: &[Tool]) -> Value {
+    let mut choices = vec![json!({
+        "type": "object",
+        "properties": { "message": { "type": "string" } },
+        "required": ["message"],
+    })];
+    choices.extend(tools.iter().map(|tool| {
+        json!({
+            "type": "object",
+            "properties": {
+                "tool": { "const": tool.name },
+                "arguments": Value::Object(tool.input_schema.as_ref().clone()),
+            },
+            "required": ["tool", "arguments"],
+        })
+    }));
+    json!({ "oneOf": choices })
+}
+
+/// Turn a reply constrained by `reply_schema` back into text or a tool request. Replies that
+/// don't follow the schema are kept as they are
+fn parse_constrained_reply(message: Message) -> Message {
+    let Ok(reply) = serde_json::from_str::<Value>(message.as_concat_text().trim()) else {
+        return message;
+    };
+    if let Some(tool) = reply.get("tool").and_then(|t| t.as_str()) {
+        let arguments = reply.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        return Message::assistant().with_tool_request(
+            Uuid::new_v4().to_string(),
+            Ok(ToolCall::new(tool, arguments)),
+        );
+    }
+    match reply.get("message").and_then(|m| m.as_str()) {
+        Some(text) => Message::assistant().with_text(text),
+        None => message,
+    }
+}
+
+#[async_trait]
+impl Provider for LlamaCppProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            "llamacpp",
+            "llama.cpp / LM Studio",
+            "Local models served by llama.cpp server or LM Studio",
+            LLAMACPP_DEFAULT_MODEL,
+            vec![],
+            LLAMACPP_DOC_URL,
+            vec![
+                ConfigKey::new("LLAMACPP_HOST", true, false, Some(LLAMACPP_DEFAULT_HOST)),
+                ConfigKey::new("LLAMACPP_API_KEY", false, true, None),
+                ConfigKey::new(LLAMACPP_TOOL_CALLING_KEY, false, false, Some("native")),
+            ],
+        )
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model.clone()
+    }
+
+    #[tracing::instrument(
+        skip(self, model_config, system, messages, tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn complete_with_model(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let payload = self.create_payload(model_config, system, messages, tools)?;
+        let response = self.with_retry(|| self.post(&payload)).await?;
+
+        let mut message = response_to_message(&response)?;
+        if payload.get("response_format").is_some() {
+            message = parse_constrained_reply(message);
+        }
+        let usage = response.get("usage").map(get_usage).unwrap_or_else(|| {
+            tracing::debug!("Failed to get usage data");
+            Usage::default()
+        });
+        let response_model = get_model(&response);
+        emit_debug_trace(model_config, &payload, &response, &usage);
+        Ok((message, ProviderUsage::new(response_model, usage)))
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        let response = self.api_client.response_get("v1/models").await?;
+        let json = handle_response_openai_compat(response).await?;
+        let data = json.get("data").and_then(|v| v.as_array()).ok_or_else(|| {
+            ProviderError::UsageError("Missing data field in JSON response".into())
+        })?;
+        let mut models: Vec<String> = data
+            .iter()
+            .filter_map(|m| m.get("id").and_then(|v| v.as_str()).map(str::to_string))
+            .collect();
+        models.sort();
+        Ok(Some(models))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        // A constrained reply has to be complete before it can be parsed
+        self.tool_calling == ToolCalling::Native
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = self.create_payload(&self.model, system, messages, tools)?;
+        payload["stream"] = Value::Bool(true);
+        payload["stream_options"] = json!({
+            "include_usage": true,
+        });
+
+        let response = self
+            .api_client
+            .response_post("v1/chat/completions", &payload)
+            .await?;
+        let response = handle_status_openai_compat(response).await?;
+
+        let stream = response.bytes_stream().map_err(io::Error::other);
+
+        let model_config = self.model.clone();
+
+        Ok(Box::pin(try_stream! {
+            let stream_reader = StreamReader::new(stream);
+            let framed = FramedRead::new(stream_reader, LinesCodec::new()).map_err(anyhow::Error::from);
+
+            let message_stream = response_to_streaming_message(framed);
+            pin!(message_stream);
+            while let Some(message) = message_stream.next().await {
+                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                emit_debug_trace(&model_config, &payload, &message, &usage.as_ref().map(|f| f.usage).unwrap_or_default());
+                yield (message, usage);
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::object;
+
+    #[test]
+    fn test_parse_constrained_reply() {
+        let reply = Message::assistant()
+            .with_text(r#"{"tool": "developer__shell", "arguments": {"command": "ls"}}"#);
+        let message = parse_constrained_reply(reply);
+        let request = message.content[0].as_tool_request().unwrap();
+        let tool_call = request.tool_call.as_ref().unwrap();
+        assert_eq!(tool_call.name, "developer__shell");
+        assert_eq!(tool_call.arguments, json!({"command": "ls"}));
+
+        let reply = Message::assistant().with_text(r#"{"message": "Done"}"#);
+        assert_eq!(parse_constrained_reply(reply).as_concat_text(), "Done");
+
+        let reply = Message::assistant().with_text("not json");
+        assert_eq!(parse_constrained_reply(reply).as_concat_text(), "not json");
+    }
+
+    #[test]
+    fn test_reply_schema() {
+        let tool = Tool::new(
+            "developer__shell",
+            "Run a command",
+            object!({"type": "object", "properties": {"command": {"type": "string"}}}),
+        );
+        let schema = reply_schema(&[tool]);
+        let choices = schema["oneOf"].as_array().unwrap();
+        assert_eq!(choices.len(), 2);
+        assert_eq!(
+            choices[1]["properties"]["tool"]["const"],
+            json!("developer__shell")
+        );
+    }
+}
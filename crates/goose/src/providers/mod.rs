@@ -1,5 +1,5 @@
 pub mod anthropic;
-mod api_client;
+pub(crate) mod api_client;
 pub mod azure;
 pub mod azureauth;
 pub mod base;
@@ -19,13 +19,17 @@ pub mod google;
 pub mod groq;
 pub mod lead_worker;
 pub mod litellm;
+pub mod llamacpp;
 pub mod oauth;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
 pub mod pricing;
 pub mod provider_registry;
+pub mod rate_limit;
+pub mod recording;
 mod retry;
+mod runtime;
 pub mod sagemaker_tgi;
 pub mod snowflake;
 pub mod testprovider;
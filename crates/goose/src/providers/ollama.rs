@@ -1,9 +1,10 @@
 use super::api_client::{ApiClient, AuthMethod};
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::embedding::{embedding_model, EmbeddingCapable};
 use super::errors::ProviderError;
 use super::retry::ProviderRetry;
 use super::utils::{get_model, handle_response_openai_compat};
-use crate::config::custom_providers::CustomProviderConfig;
+use crate::config::custom_providers::{apply_model_params, CustomProviderConfig};
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
 use crate::impl_provider_default;
@@ -12,9 +13,12 @@ use crate::providers::formats::openai::{create_request, get_usage, response_to_m
 use crate::utils::safe_truncate;
 use anyhow::Result;
 use async_trait::async_trait;
+use futures::StreamExt;
 use regex::Regex;
 use rmcp::model::Tool;
-use serde_json::Value;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::time::Duration;
 use url::Url;
 
@@ -22,16 +26,61 @@ pub const OLLAMA_HOST: &str = "localhost";
 pub const OLLAMA_TIMEOUT: u64 = 600; // seconds
 pub const OLLAMA_DEFAULT_PORT: u16 = 11434;
 pub const OLLAMA_DEFAULT_MODEL: &str = "qwen2.5";
+pub const OLLAMA_DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
 // Ollama can run many models, we only provide the default
 pub const OLLAMA_KNOWN_MODELS: &[&str] = &[OLLAMA_DEFAULT_MODEL];
 pub const OLLAMA_DOC_URL: &str = "https://ollama.com/library";
 
+/// A progress update while a model is pulled
+#[derive(Debug, Clone, Deserialize)]
+pub struct OllamaPullProgress {
+    pub status: String,
+    /// Layer being downloaded, with its size and the bytes done so far
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
+
+/// A model loaded in memory and how much of it is on the GPU
+#[derive(Debug, Clone, Deserialize)]
+pub struct OllamaLoadedModel {
+    pub name: String,
+    pub size: u64,
+    #[serde(default)]
+    pub size_vram: u64,
+}
+
+impl OllamaLoadedModel {
+    /// Percentage of the model held in GPU memory, the rest runs on the CPU
+    pub fn gpu_percent(&self) -> u64 {
+        if self.size == 0 {
+            return 0;
+        }
+        self.size_vram.min(self.size) * 100 / self.size
+    }
+
+    /// How the model is split between processors, in the format of `ollama ps`
+    pub fn processor(&self) -> String {
+        match self.gpu_percent() {
+            100 => "100% GPU".to_string(),
+            0 => "100% CPU".to_string(),
+            gpu => format!("{}%/{}% CPU/GPU", 100 - gpu, gpu),
+        }
+    }
+}
+
 #[derive(serde::Serialize)]
 pub struct OllamaProvider {
     #[serde(skip)]
     api_client: ApiClient,
     model: ModelConfig,
     supports_streaming: bool,
+    /// How long the model stays loaded after a request, such as "10m" or "-1" for always
+    keep_alive: Option<String>,
+    /// Context window the model is loaded with
+    num_ctx: Option<usize>,
+    /// Default request parameters of each model, from a custom provider
+    model_params: HashMap<String, Value>,
 }
 
 impl_provider_default!(OllamaProvider);
@@ -79,6 +128,9 @@ impl OllamaProvider {
             api_client,
             model,
             supports_streaming: false,
+            keep_alive: config.get_param("OLLAMA_KEEP_ALIVE").ok(),
+            num_ctx: config.get_param("OLLAMA_NUM_CTX").ok(),
+            model_params: HashMap::new(),
         })
     }
 
@@ -107,14 +159,25 @@ impl OllamaProvider {
                 .map_err(|_| anyhow::anyhow!("Failed to set default port"))?;
         }
 
-        // No authentication for Ollama
-        let auth = AuthMethod::Custom(Box::new(NoAuth));
-        let api_client = ApiClient::with_timeout(base_url.to_string(), auth, timeout)?;
+        // No authentication for Ollama, unless it sits behind a proxy that asks for it
+        let auth = match config.auth {
+            Some(_) => config.auth_method(AuthMethod::BearerToken)?,
+            None => AuthMethod::Custom(Box::new(NoAuth)),
+        };
+        let api_client = config.apply_headers(ApiClient::with_timeout(
+            base_url.to_string(),
+            auth,
+            timeout,
+        )?)?;
 
+        let global_config = crate::config::Config::global();
         Ok(Self {
             api_client,
             model,
             supports_streaming: config.supports_streaming.unwrap_or(true),
+            keep_alive: global_config.get_param("OLLAMA_KEEP_ALIVE").ok(),
+            num_ctx: global_config.get_param("OLLAMA_NUM_CTX").ok(),
+            model_params: config.model_params,
         })
     }
 
@@ -125,6 +188,73 @@ impl OllamaProvider {
             .await?;
         handle_response_openai_compat(response).await
     }
+
+    /// Add the keep-alive and context window settings to a chat request
+    fn apply_load_options(&self, payload: &mut Value) {
+        let Some(object) = payload.as_object_mut() else {
+            return;
+        };
+        if let Some(keep_alive) = &self.keep_alive {
+            // A bare number is a duration in seconds
+            let keep_alive = match keep_alive.parse::<i64>() {
+                Ok(seconds) => json!(seconds),
+                Err(_) => json!(keep_alive),
+            };
+            object.insert("keep_alive".to_string(), keep_alive);
+        }
+        if let Some(num_ctx) = self.num_ctx {
+            object.insert("options".to_string(), json!({ "num_ctx": num_ctx }));
+        }
+    }
+
+    /// Download a model, reporting progress as the server streams it
+    pub async fn pull_model<F>(&self, model: &str, mut on_progress: F) -> Result<()>
+    where
+        F: FnMut(&OllamaPullProgress),
+    {
+        let payload = json!({ "model": model, "stream": true });
+        let response = self
+            .api_client
+            .response_post("api/pull", &payload)
+            .await?
+            .error_for_status()?;
+
+        // The progress is sent as one JSON object per line
+        let mut stream = response.bytes_stream();
+        let mut buffer = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            buffer.extend_from_slice(&chunk?);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let value: Value = match serde_json::from_slice(&line) {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                };
+                if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+                    return Err(anyhow::anyhow!("Failed to pull {}: {}", model, error));
+                }
+                let progress: OllamaPullProgress = serde_json::from_value(value)?;
+                let success = progress.status == "success";
+                on_progress(&progress);
+                if success {
+                    return Ok(());
+                }
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Pulling {} ended before it finished",
+            model
+        ))
+    }
+
+    /// The models currently loaded, to tell whether they run on the GPU or the CPU
+    pub async fn loaded_models(&self) -> Result<Vec<OllamaLoadedModel>> {
+        let response = self.api_client.response_get("api/ps").await?;
+        let json = handle_response_openai_compat(response).await?;
+        Ok(serde_json::from_value(
+            json.get("models").cloned().unwrap_or_else(|| json!([])),
+        )?)
+    }
 }
 
 // No authentication provider for Ollama
@@ -156,6 +286,8 @@ impl Provider for OllamaProvider {
                     false,
                     Some(&(OLLAMA_TIMEOUT.to_string())),
                 ),
+                ConfigKey::new("OLLAMA_KEEP_ALIVE", false, false, None),
+                ConfigKey::new("OLLAMA_NUM_CTX", false, false, None),
             ],
         )
     }
@@ -179,13 +311,15 @@ impl Provider for OllamaProvider {
         let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
         let filtered_tools = if goose_mode == "chat" { &[] } else { tools };
 
-        let payload = create_request(
+        let mut payload = create_request(
             &self.model,
             system,
             messages,
             filtered_tools,
             &super::utils::ImageFormat::OpenAi,
         )?;
+        self.apply_load_options(&mut payload);
+        apply_model_params(&self.model_params, &self.model.model_name, &mut payload);
         let response = self
             .with_retry(|| async {
                 let payload_clone = payload.clone();
@@ -225,9 +359,66 @@ impl Provider for OllamaProvider {
         Ok(safe_truncate(&description, 100))
     }
 
+    /// The models pulled on the server
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        let response = self.api_client.response_get("api/tags").await?;
+        let json = handle_response_openai_compat(response).await?;
+        let models = json
+            .get("models")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| {
+                ProviderError::UsageError("Missing models field in JSON response".into())
+            })?;
+        let mut models: Vec<String> = models
+            .iter()
+            .filter_map(|m| m.get("name").and_then(|v| v.as_str()).map(str::to_string))
+            .collect();
+        models.sort();
+        Ok(Some(models))
+    }
+
     fn supports_streaming(&self) -> bool {
         self.supports_streaming
     }
+
+    fn supports_embeddings(&self) -> bool {
+        true
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        Some(embedding_model("ollama", OLLAMA_DEFAULT_EMBEDDING_MODEL))
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        EmbeddingCapable::create_embeddings(self, texts)
+            .await
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl EmbeddingCapable for OllamaProvider {
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // The native endpoint accepts a batch of inputs, unlike the OpenAI-compatible one on older servers
+        let payload = json!({
+            "model": embedding_model("ollama", OLLAMA_DEFAULT_EMBEDDING_MODEL),
+            "input": texts,
+        });
+
+        let response = self.api_client.response_post("api/embed", &payload).await?;
+        let json = handle_response_openai_compat(response).await?;
+
+        let embeddings: Vec<Vec<f32>> = serde_json::from_value(
+            json.get("embeddings")
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Missing embeddings field in response"))?,
+        )?;
+        Ok(embeddings)
+    }
 }
 
 impl OllamaProvider {
@@ -273,3 +464,20 @@ impl OllamaProvider {
         filtered
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loaded_model_processor() {
+        let model = |size_vram| OllamaLoadedModel {
+            name: "qwen2.5:latest".to_string(),
+            size: 4_000,
+            size_vram,
+        };
+        assert_eq!(model(4_000).processor(), "100% GPU");
+        assert_eq!(model(0).processor(), "100% CPU");
+        assert_eq!(model(3_000).processor(), "25%/75% CPU/GPU");
+    }
+}
@@ -13,14 +13,14 @@ use tokio_util::io::StreamReader;
 
 use super::api_client::{ApiClient, AuthMethod};
 use super::base::{ConfigKey, ModelInfo, Provider, ProviderMetadata, ProviderUsage, Usage};
-use super::embedding::{EmbeddingCapable, EmbeddingRequest, EmbeddingResponse};
+use super::embedding::{embedding_model, EmbeddingCapable, EmbeddingRequest, EmbeddingResponse};
 use super::errors::ProviderError;
-use super::formats::openai::{create_request, get_usage, response_to_message};
+use super::formats::openai::{create_request, get_usage, prompt_cache_key, response_to_message};
 use super::utils::{
     emit_debug_trace, get_model, handle_response_openai_compat, handle_status_openai_compat,
     ImageFormat,
 };
-use crate::config::custom_providers::CustomProviderConfig;
+use crate::config::custom_providers::{apply_model_params, CustomProviderConfig};
 use crate::conversation::message::Message;
 use crate::impl_provider_default;
 use crate::model::ModelConfig;
@@ -28,8 +28,10 @@ use crate::providers::base::MessageStream;
 use crate::providers::formats::openai::response_to_streaming_message;
 use rmcp::model::Tool;
 
+pub const OPEN_AI_API_HOST: &str = "https://api.openai.com";
 pub const OPEN_AI_DEFAULT_MODEL: &str = "gpt-4o";
 pub const OPEN_AI_DEFAULT_FAST_MODEL: &str = "gpt-4o-mini";
+pub const OPEN_AI_DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
 pub const OPEN_AI_KNOWN_MODELS: &[(&str, usize)] = &[
     ("gpt-4o", 128_000),
     ("gpt-4o-mini", 128_000),
@@ -54,6 +56,10 @@ pub struct OpenAiProvider {
     model: ModelConfig,
     custom_headers: Option<HashMap<String, String>>,
     supports_streaming: bool,
+    /// Whether to send a `prompt_cache_key`, only OpenAI's own API knows it
+    prompt_caching: bool,
+    /// Default request parameters of each model, from a custom provider
+    model_params: HashMap<String, Value>,
 }
 
 impl_provider_default!(OpenAiProvider);
@@ -66,7 +72,8 @@ impl OpenAiProvider {
         let api_key: String = config.get_secret("OPENAI_API_KEY")?;
         let host: String = config
             .get_param("OPENAI_HOST")
-            .unwrap_or_else(|_| "https://api.openai.com".to_string());
+            .unwrap_or_else(|_| OPEN_AI_API_HOST.to_string());
+        let prompt_caching = host.trim_end_matches('/') == OPEN_AI_API_HOST;
         let base_path: String = config
             .get_param("OPENAI_BASE_PATH")
             .unwrap_or_else(|_| "v1/chat/completions".to_string());
@@ -109,14 +116,13 @@ impl OpenAiProvider {
             model,
             custom_headers,
             supports_streaming: true,
+            prompt_caching,
+            model_params: HashMap::new(),
         })
     }
 
     pub fn from_custom_config(model: ModelConfig, config: CustomProviderConfig) -> Result<Self> {
-        let global_config = crate::config::Config::global();
-        let api_key: String = global_config
-            .get_secret(&config.api_key_env)
-            .map_err(|_e| anyhow::anyhow!("Missing API key: {}", config.api_key_env))?;
+        let auth = config.auth_method(AuthMethod::BearerToken)?;
 
         let url = url::Url::parse(&config.base_url)
             .map_err(|e| anyhow::anyhow!("Invalid base URL '{}': {}", config.base_url, e))?;
@@ -139,20 +145,11 @@ impl OpenAiProvider {
         };
 
         let timeout_secs = config.timeout_seconds.unwrap_or(600);
-        let auth = AuthMethod::BearerToken(api_key);
-        let mut api_client =
-            ApiClient::with_timeout(host, auth, std::time::Duration::from_secs(timeout_secs))?;
-
-        // Add custom headers if present
-        if let Some(headers) = &config.headers {
-            let mut header_map = reqwest::header::HeaderMap::new();
-            for (key, value) in headers {
-                let header_name = reqwest::header::HeaderName::from_bytes(key.as_bytes())?;
-                let header_value = reqwest::header::HeaderValue::from_str(value)?;
-                header_map.insert(header_name, header_value);
-            }
-            api_client = api_client.with_headers(header_map)?;
-        }
+        let api_client = config.apply_headers(ApiClient::with_timeout(
+            host,
+            auth,
+            std::time::Duration::from_secs(timeout_secs),
+        )?)?;
 
         Ok(Self {
             api_client,
@@ -162,6 +159,8 @@ impl OpenAiProvider {
             model,
             custom_headers: config.headers,
             supports_streaming: config.supports_streaming.unwrap_or(true),
+            prompt_caching: false,
+            model_params: config.model_params,
         })
     }
 
@@ -215,7 +214,12 @@ impl Provider for OpenAiProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError> {
-        let payload = create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+        let mut payload =
+            create_request(model_config, system, messages, tools, &ImageFormat::OpenAi)?;
+        apply_model_params(&self.model_params, &model_config.model_name, &mut payload);
+        if self.prompt_caching {
+            payload["prompt_cache_key"] = json!(prompt_cache_key(system, tools));
+        }
 
         let json_response = self.post(&payload).await?;
 
@@ -259,6 +263,10 @@ impl Provider for OpenAiProvider {
         true
     }
 
+    fn embedding_model(&self) -> Option<String> {
+        Some(embedding_model("openai", OPEN_AI_DEFAULT_EMBEDDING_MODEL))
+    }
+
     async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
         EmbeddingCapable::create_embeddings(self, texts)
             .await
@@ -277,6 +285,10 @@ impl Provider for OpenAiProvider {
     ) -> Result<MessageStream, ProviderError> {
         let mut payload =
             create_request(&self.model, system, messages, tools, &ImageFormat::OpenAi)?;
+        apply_model_params(&self.model_params, &self.model.model_name, &mut payload);
+        if self.prompt_caching {
+            payload["prompt_cache_key"] = json!(prompt_cache_key(system, tools));
+        }
         payload["stream"] = serde_json::Value::Bool(true);
         payload["stream_options"] = json!({
             "include_usage": true,
@@ -325,12 +337,9 @@ impl EmbeddingCapable for OpenAiProvider {
             return Ok(vec![]);
         }
 
-        let embedding_model = std::env::var("GOOSE_EMBEDDING_MODEL")
-            .unwrap_or_else(|_| "text-embedding-3-small".to_string());
-
         let request = EmbeddingRequest {
             input: texts,
-            model: embedding_model,
+            model: embedding_model("openai", OPEN_AI_DEFAULT_EMBEDDING_MODEL),
         };
 
         let response = self
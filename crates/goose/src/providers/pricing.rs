@@ -40,6 +40,55 @@ pub struct PricingInfo {
     pub context_length: Option<u32>,
 }
 
+/// Config key for prices users set themselves, e.g. for enterprise contracts or local models
+pub const PRICING_OVERRIDES_KEY: &str = "GOOSE_PRICING_OVERRIDES";
+
+/// A configured price, keyed by `provider/model` or `provider/*` for all models of a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceOverride {
+    /// USD per million input tokens
+    pub input: f64,
+    /// USD per million output tokens
+    pub output: f64,
+    #[serde(default)]
+    pub context_length: Option<u32>,
+}
+
+impl From<&PriceOverride> for PricingInfo {
+    fn from(price: &PriceOverride) -> Self {
+        Self {
+            input_cost: price.input / 1_000_000.0,
+            output_cost: price.output / 1_000_000.0,
+            context_length: price.context_length,
+        }
+    }
+}
+
+/// The price overrides from config
+pub fn configured_overrides() -> HashMap<String, PriceOverride> {
+    crate::config::Config::global()
+        .get_param(PRICING_OVERRIDES_KEY)
+        .unwrap_or_default()
+}
+
+/// The configured price of the model, if any
+pub fn price_override(provider: &str, model: &str) -> Option<PricingInfo> {
+    find_override(&configured_overrides(), provider, model)
+}
+
+/// The override for the model, an exact `provider/model` entry before a `provider/*` one
+fn find_override(
+    overrides: &HashMap<String, PriceOverride>,
+    provider: &str,
+    model: &str,
+) -> Option<PricingInfo> {
+    let provider = provider.to_lowercase();
+    overrides
+        .get(&format!("{}/{}", provider, model))
+        .or_else(|| overrides.get(&format!("{}/*", provider)))
+        .map(PricingInfo::from)
+}
+
 /// Cache for OpenRouter pricing data with disk persistence
 pub struct PricingCache {
     /// In-memory cache
@@ -303,8 +352,11 @@ pub async fn initialize_pricing_cache() -> Result<()> {
     PRICING_CACHE.initialize().await
 }
 
-/// Get pricing for a specific model
+/// Get pricing for a specific model, configured overrides take precedence over OpenRouter
 pub async fn get_model_pricing(provider: &str, model: &str) -> Option<PricingInfo> {
+    if let Some(pricing) = price_override(provider, model) {
+        return Some(pricing);
+    }
     PRICING_CACHE.get_model_pricing(provider, model).await
 }
 
@@ -313,8 +365,39 @@ pub async fn refresh_pricing() -> Result<()> {
     PRICING_CACHE.refresh().await
 }
 
-/// Get all cached pricing data
+/// Get all pricing data, the cached OpenRouter prices with the configured overrides applied
 pub async fn get_all_pricing() -> HashMap<String, HashMap<String, PricingInfo>> {
+    let mut pricing = get_cached_pricing().await;
+    merge_overrides(&mut pricing, &configured_overrides());
+    pricing
+}
+
+/// Add the overrides for specific models, `provider/*` entries apply to the models listed
+fn merge_overrides(
+    pricing: &mut HashMap<String, HashMap<String, PricingInfo>>,
+    overrides: &HashMap<String, PriceOverride>,
+) {
+    for (key, price) in overrides {
+        let Some((provider, model)) = key.split_once('/') else {
+            continue;
+        };
+        if model != "*" {
+            pricing
+                .entry(provider.to_lowercase())
+                .or_default()
+                .insert(model.to_string(), PricingInfo::from(price));
+        }
+    }
+    for (provider, models) in pricing.iter_mut() {
+        for (model, info) in models.iter_mut() {
+            if let Some(price) = find_override(overrides, provider, model) {
+                *info = price;
+            }
+        }
+    }
+}
+
+async fn get_cached_pricing() -> HashMap<String, HashMap<String, PricingInfo>> {
     let cache = PRICING_CACHE.memory_cache.read().await;
     if let Some(cached) = &*cache {
         cached.pricing.clone()
@@ -389,6 +472,50 @@ mod tests {
         );
     }
 
+    fn overrides() -> HashMap<String, PriceOverride> {
+        serde_yaml::from_str(
+            "ollama/*: {input: 0, output: 0}\n\
+             databricks/contract-claude: {input: 2.5, output: 10, context_length: 200000}\n\
+             anthropic/claude-sonnet-4: {input: 1, output: 5}\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_override() {
+        let overrides = overrides();
+        let contract = find_override(&overrides, "Databricks", "contract-claude").unwrap();
+        assert_eq!(contract.input_cost, 2.5 / 1_000_000.0);
+        assert_eq!(contract.context_length, Some(200000));
+
+        let local = find_override(&overrides, "ollama", "qwen3").unwrap();
+        assert_eq!((local.input_cost, local.output_cost), (0.0, 0.0));
+        assert!(find_override(&overrides, "openai", "gpt-4o").is_none());
+    }
+
+    #[test]
+    fn test_merge_overrides() {
+        let mut pricing = HashMap::from([(
+            "anthropic".to_string(),
+            HashMap::from([(
+                "claude-sonnet-4".to_string(),
+                PricingInfo {
+                    input_cost: 0.000003,
+                    output_cost: 0.000015,
+                    context_length: Some(200000),
+                },
+            )]),
+        )]);
+        merge_overrides(&mut pricing, &overrides());
+
+        assert_eq!(
+            pricing["anthropic"]["claude-sonnet-4"].output_cost,
+            5.0 / 1_000_000.0
+        );
+        assert!(pricing["databricks"].contains_key("contract-claude"));
+        assert!(!pricing.contains_key("ollama"));
+    }
+
     #[test]
     fn test_convert_pricing() {
         assert_eq!(convert_pricing("0.000003"), Some(0.000003));
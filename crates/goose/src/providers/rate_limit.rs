@@ -0,0 +1,375 @@
+//! Per-provider rate limits shared by every provider instance in the process, so parallel
+//! subagents and batch runs stay under the account limits instead of running into 429s.
+//!
+//! Limits are configured per provider name under `GOOSE_PROVIDER_RATE_LIMITS`:
+//!
+//! ```yaml
+//! GOOSE_PROVIDER_RATE_LIMITS:
+//!   openai:
+//!     requests_per_minute: 500
+//!     tokens_per_minute: 200000
+//!     max_concurrent: 4
+//! ```
+use anyhow::Result;
+use async_stream::try_stream;
+use async_trait::async_trait;
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::base::{
+    LeadWorkerProviderTrait, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage,
+};
+use super::errors::ProviderError;
+use super::retry::RetryConfig;
+use crate::config::Config;
+use crate::conversation::message::Message;
+use crate::model::ModelConfig;
+
+pub const RATE_LIMITS_KEY: &str = "GOOSE_PROVIDER_RATE_LIMITS";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub tokens_per_minute: Option<u32>,
+    /// Requests in flight at once
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+}
+
+impl RateLimitConfig {
+    fn is_unlimited(&self) -> bool {
+        self.requests_per_minute.is_none()
+            && self.tokens_per_minute.is_none()
+            && self.max_concurrent.is_none()
+    }
+}
+
+/// A bucket holding up to a minute's worth of capacity, refilled continuously
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    refill_per_sec: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(limit: u32, now: Instant) -> Self {
+        let capacity = limit.max(1) as f64;
+        Self {
+            capacity,
+            available: capacity,
+            refill_per_sec: capacity / 60.0,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.available = (self.available + elapsed * self.refill_per_sec).min(self.capacity);
+        self.refilled_at = now;
+    }
+
+    /// How long until `amount` is available
+    fn wait_for(&mut self, amount: f64, now: Instant) -> Duration {
+        self.refill(now);
+        if self.available >= amount {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((amount - self.available) / self.refill_per_sec)
+        }
+    }
+
+    /// Take `amount`, going into debt when there isn't enough
+    fn take(&mut self, amount: f64) {
+        self.available -= amount;
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    requests: Option<TokenBucket>,
+    tokens: Option<TokenBucket>,
+}
+
+/// The limits of one provider. Requests wait for a request slot and for the token budget to be
+/// out of debt. Tokens are only known once a response arrives, so they are charged afterwards
+#[derive(Debug)]
+pub struct RateLimiter {
+    buckets: Mutex<Buckets>,
+    concurrency: Option<Arc<Semaphore>>,
+}
+
+/// Held for as long as a request is in flight
+pub struct RateLimitPermit {
+    _concurrency: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            buckets: Mutex::new(Buckets {
+                requests: config
+                    .requests_per_minute
+                    .map(|limit| TokenBucket::per_minute(limit, now)),
+                tokens: config
+                    .tokens_per_minute
+                    .map(|limit| TokenBucket::per_minute(limit, now)),
+            }),
+            concurrency: config
+                .max_concurrent
+                .map(|limit| Arc::new(Semaphore::new(limit.max(1)))),
+        }
+    }
+
+    /// How long the next request has to wait, taking its request slot when it doesn't
+    fn try_acquire(&self, now: Instant) -> Duration {
+        let mut buckets = self.buckets.lock().unwrap();
+        let request_wait = buckets
+            .requests
+            .as_mut()
+            .map_or(Duration::ZERO, |bucket| bucket.wait_for(1.0, now));
+        let token_wait = buckets
+            .tokens
+            .as_mut()
+            .map_or(Duration::ZERO, |bucket| bucket.wait_for(0.0, now));
+        let wait = request_wait.max(token_wait);
+        if wait.is_zero() {
+            if let Some(bucket) = buckets.requests.as_mut() {
+                bucket.take(1.0);
+            }
+        }
+        wait
+    }
+
+    pub async fn acquire(&self) -> RateLimitPermit {
+        let concurrency = match &self.concurrency {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        loop {
+            let wait = self.try_acquire(Instant::now());
+            if wait.is_zero() {
+                break;
+            }
+            tracing::debug!("Waiting {:?} for the provider rate limit", wait);
+            tokio::time::sleep(wait).await;
+        }
+        RateLimitPermit {
+            _concurrency: concurrency,
+        }
+    }
+
+    pub fn record_usage(&self, usage: &Usage) {
+        let Some(tokens) = usage.total_tokens.or_else(|| {
+            usage
+                .input_tokens
+                .zip(usage.output_tokens)
+                .map(|(input, output)| input + output)
+        }) else {
+            return;
+        };
+        if let Some(bucket) = self.buckets.lock().unwrap().tokens.as_mut() {
+            bucket.refill(Instant::now());
+            bucket.take(tokens.max(0) as f64);
+        }
+    }
+}
+
+/// Limiters by provider name, shared so every instance of a provider draws from the same budget
+static LIMITERS: Lazy<Mutex<HashMap<String, Arc<RateLimiter>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn configured_limits(provider_name: &str) -> Option<RateLimitConfig> {
+    let limits: HashMap<String, RateLimitConfig> =
+        Config::global().get_param(RATE_LIMITS_KEY).ok()?;
+    limits
+        .get(provider_name)
+        .filter(|config| !config.is_unlimited())
+        .cloned()
+}
+
+/// The limiter for a provider, or None when it has no limits configured
+pub fn limiter_for(provider_name: &str) -> Option<Arc<RateLimiter>> {
+    let mut limiters = LIMITERS.lock().unwrap();
+    if let Some(limiter) = limiters.get(provider_name) {
+        return Some(limiter.clone());
+    }
+    let limiter = Arc::new(RateLimiter::new(&configured_limits(provider_name)?));
+    limiters.insert(provider_name.to_string(), limiter.clone());
+    Some(limiter)
+}
+
+/// Wrap a provider in its configured rate limits, if it has any
+pub fn with_rate_limit(provider_name: &str, provider: Arc<dyn Provider>) -> Arc<dyn Provider> {
+    match limiter_for(provider_name) {
+        Some(limiter) => Arc::new(RateLimitedProvider {
+            inner: provider,
+            limiter,
+        }),
+        None => provider,
+    }
+}
+
+pub struct RateLimitedProvider {
+    inner: Arc<dyn Provider>,
+    limiter: Arc<RateLimiter>,
+}
+
+#[async_trait]
+impl Provider for RateLimitedProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            "rate_limited",
+            "Rate Limited Provider",
+            "Applies the configured rate limits to another provider",
+            "",
+            vec![],
+            "",
+            vec![],
+        )
+    }
+
+    async fn complete_with_model(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let _permit = self.limiter.acquire().await;
+        let (message, usage) = self
+            .inner
+            .complete_with_model(model_config, system, messages, tools)
+            .await?;
+        self.limiter.record_usage(&usage.usage);
+        Ok((message, usage))
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.inner.get_model_config()
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        self.inner.retry_config()
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        self.inner.fetch_supported_models().await
+    }
+
+    fn supports_embeddings(&self) -> bool {
+        self.inner.supports_embeddings()
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        self.inner.embedding_model()
+    }
+
+    fn supports_cache_control(&self) -> bool {
+        self.inner.supports_cache_control()
+    }
+
+    fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
+        self.inner.as_lead_worker()
+    }
+
+    async fn configure_oauth(&self) -> Result<(), ProviderError> {
+        self.inner.configure_oauth().await
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        let _permit = self.limiter.acquire().await;
+        self.inner.create_embeddings(texts).await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let permit = self.limiter.acquire().await;
+        let mut stream = self.inner.stream(system, messages, tools).await?;
+        let limiter = self.limiter.clone();
+        Ok(Box::pin(try_stream! {
+            // The request stays in flight until the stream is done
+            let _permit = permit;
+            while let Some(item) = stream.next().await {
+                let (message, usage) = item?;
+                if let Some(usage) = &usage {
+                    limiter.record_usage(&usage.usage);
+                }
+                yield (message, usage);
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_bucket() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            requests_per_minute: Some(2),
+            ..Default::default()
+        });
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_zero());
+        assert!(limiter.try_acquire(now).is_zero());
+        let wait = limiter.try_acquire(now);
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+        assert!(limiter.try_acquire(now + Duration::from_secs(30)).is_zero());
+    }
+
+    #[test]
+    fn test_tokens_charged_after_response() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            tokens_per_minute: Some(600),
+            ..Default::default()
+        });
+        let now = Instant::now();
+        assert!(limiter.try_acquire(now).is_zero());
+        limiter.record_usage(&Usage::new(Some(700), Some(500), None));
+        // 600 tokens in debt, refilled at 10 a second
+        let wait = limiter.try_acquire(Instant::now());
+        assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            max_concurrent: Some(1),
+            ..Default::default()
+        });
+        let permit = limiter.acquire().await;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), limiter.acquire())
+                .await
+                .is_err()
+        );
+        drop(permit);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), limiter.acquire())
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Record what a provider is asked and answers to a directory, and replay it later without the
+//! provider. Replays make the agent loop testable offline and bug reports reproducible.
+//!
+//! `GOOSE_PROVIDER_RECORD=dir` wraps the configured provider and writes one file per exchange,
+//! with secrets scrubbed. `GOOSE_PROVIDER_REPLAY=dir` answers from those files instead of
+//! creating the provider, so no credentials or network are needed.
+use anyhow::Result;
+use async_stream::try_stream;
+use async_trait::async_trait;
+use futures::StreamExt;
+use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::base::{
+    LeadWorkerProviderTrait, MessageStream, Provider, ProviderMetadata, ProviderUsage,
+};
+use super::errors::ProviderError;
+use super::retry::RetryConfig;
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use crate::redaction::Redactor;
+
+pub const RECORD_DIR_KEY: &str = "GOOSE_PROVIDER_RECORD";
+pub const REPLAY_DIR_KEY: &str = "GOOSE_PROVIDER_REPLAY";
+
+/// One request to the provider and its answer
+#[derive(Debug, Serialize, Deserialize)]
+struct Exchange {
+    model: String,
+    system: String,
+    messages: Vec<Message>,
+    tools: Vec<Tool>,
+    response: Message,
+    usage: ProviderUsage,
+}
+
+/// Records the exchanges of the provider it wraps, or replays recorded ones when it wraps none
+pub struct RecordingProvider {
+    inner: Option<Arc<dyn Provider>>,
+    dir: PathBuf,
+    model: ModelConfig,
+    /// Scrubs the secrets from the config out of recordings
+    redactor: Redactor,
+}
+
+impl RecordingProvider {
+    pub fn recording(inner: Arc<dyn Provider>, dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            model: inner.get_model_config(),
+            inner: Some(inner),
+            dir,
+            redactor: Redactor::from_config(),
+        })
+    }
+
+    pub fn replaying(dir: impl Into<PathBuf>, model: ModelConfig) -> Self {
+        Self {
+            inner: None,
+            dir: dir.into(),
+            model,
+            redactor: Redactor::default(),
+        }
+    }
+
+    /// The directory to record to or replay from, if either is configured. Replay wins when
+    /// both are set
+    pub fn configured_dirs() -> (Option<PathBuf>, Option<PathBuf>) {
+        let config = Config::global();
+        let dir = |key| {
+            config
+                .get_param::<String>(key)
+                .ok()
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+        };
+        (dir(RECORD_DIR_KEY), dir(REPLAY_DIR_KEY))
+    }
+
+    /// Exchanges are keyed by the model and the conversation only. The system prompt carries the
+    /// date and other details that change between runs, and message ids and timestamps are new
+    /// on every run
+    fn exchange_key(model: &str, messages: &[Message]) -> String {
+        let stable_messages: Vec<_> = messages
+            .iter()
+            .map(|message| (&message.role, &message.content))
+            .collect();
+        let serialized = serde_json::to_string(&(model, stable_messages)).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(serialized.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn exchange_path(dir: &Path, key: &str) -> PathBuf {
+        dir.join(format!("{}.json", key))
+    }
+
+    fn save(dir: &Path, redactor: &Redactor, key: &str, exchange: &Exchange) {
+        let result = serde_json::to_string_pretty(exchange)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| {
+                std::fs::write(Self::exchange_path(dir, key), redactor.redact(&content))?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to record provider exchange {}: {}", key, e);
+        }
+    }
+
+    fn load(path: &Path) -> Result<Exchange> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+#[async_trait]
+impl Provider for RecordingProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            "recording",
+            "Recording Provider",
+            "Records provider exchanges to a directory or replays them",
+            "",
+            vec![],
+            "",
+            vec![],
+        )
+    }
+
+    async fn complete_with_model(
+        &self,
+        model_config: &ModelConfig,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let key = Self::exchange_key(&model_config.model_name, messages);
+
+        let Some(inner) = &self.inner else {
+            let path = Self::exchange_path(&self.dir, &key);
+            let exchange = Self::load(&path).map_err(|e| {
+                ProviderError::ExecutionError(format!(
+                    "No recorded response for this request in {} ({}): {}",
+                    self.dir.display(),
+                    key,
+                    e
+                ))
+            })?;
+            return Ok((exchange.response, exchange.usage));
+        };
+
+        let (response, usage) = inner
+            .complete_with_model(model_config, system, messages, tools)
+            .await?;
+        let exchange = Exchange {
+            model: model_config.model_name.clone(),
+            system: system.to_string(),
+            messages: messages.to_vec(),
+            tools: tools.to_vec(),
+            response,
+            usage,
+        };
+        Self::save(&self.dir, &self.redactor, &key, &exchange);
+        Ok((exchange.response, exchange.usage))
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model.clone()
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        match &self.inner {
+            Some(inner) => inner.fetch_supported_models().await,
+            None => Ok(None),
+        }
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        match &self.inner {
+            Some(inner) => inner.retry_config(),
+            None => RetryConfig::default(),
+        }
+    }
+
+    fn supports_embeddings(&self) -> bool {
+        self.inner
+            .as_ref()
+            .is_some_and(|inner| inner.supports_embeddings())
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.embedding_model())
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        match &self.inner {
+            Some(inner) => inner.create_embeddings(texts).await,
+            None => Err(ProviderError::ExecutionError(
+                "Embeddings are not recorded and cannot be replayed".to_string(),
+            )),
+        }
+    }
+
+    fn supports_cache_control(&self) -> bool {
+        self.inner
+            .as_ref()
+            .is_some_and(|inner| inner.supports_cache_control())
+    }
+
+    fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
+        self.inner.as_ref().and_then(|inner| inner.as_lead_worker())
+    }
+
+    /// Replays answer through `complete_with_model`, so only recording streams
+    fn supports_streaming(&self) -> bool {
+        self.inner
+            .as_ref()
+            .is_some_and(|inner| inner.supports_streaming())
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let Some(inner) = &self.inner else {
+            return Err(ProviderError::NotImplemented(
+                "streaming is not replayed".to_string(),
+            ));
+        };
+
+        let mut stream = inner.stream(system, messages, tools).await?;
+        let key = Self::exchange_key(&self.model.model_name, messages);
+        let dir = self.dir.clone();
+        let redactor = self.redactor.clone();
+        let mut exchange = Exchange {
+            model: self.model.model_name.clone(),
+            system: system.to_string(),
+            messages: messages.to_vec(),
+            tools: tools.to_vec(),
+            response: Message::assistant(),
+            usage: ProviderUsage::new(self.model.model_name.clone(), Default::default()),
+        };
+        Ok(Box::pin(try_stream! {
+            let mut usage_seen = false;
+            while let Some(item) = stream.next().await {
+                let (message, usage) = item?;
+                if let Some(message) = &message {
+                    // Chunks of text are joined so the recording replays as one answer
+                    for content in &message.content {
+                        match (exchange.response.content.last_mut(), content) {
+                            (Some(MessageContent::Text(last)), MessageContent::Text(new)) => {
+                                last.text.push_str(&new.text);
+                            }
+                            _ => exchange.response.content.push(content.clone()),
+                        }
+                    }
+                }
+                if let Some(usage) = &usage {
+                    exchange.usage = usage.clone();
+                    usage_seen = true;
+                }
+                yield (message, usage);
+            }
+            if usage_seen {
+                Self::save(&dir, &redactor, &key, &exchange);
+            }
+        }))
+    }
+
+    async fn configure_oauth(&self) -> Result<(), ProviderError> {
+        match &self.inner {
+            Some(inner) => inner.configure_oauth().await,
+            None => Err(ProviderError::ExecutionError(
+                "OAuth configuration is not available while replaying".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+
+    struct EchoProvider {
+        model_config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::new("echo", "Echo", "", "echo-model", vec![], "", vec![])
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            let text = messages
+                .last()
+                .map(|m| m.as_concat_text())
+                .unwrap_or_default();
+            Ok((
+                Message::assistant().with_text(format!("echo: {}", text)),
+                ProviderUsage::new("echo-model".to_string(), Usage::default()),
+            ))
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        fn supports_streaming(&self) -> bool {
+            true
+        }
+
+        async fn stream(
+            &self,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<MessageStream, ProviderError> {
+            let chunks: Vec<Result<_, ProviderError>> = vec![
+                Ok((Some(Message::assistant().with_text("echo: ")), None)),
+                Ok((
+                    Some(Message::assistant().with_text("streamed")),
+                    Some(ProviderUsage::new(
+                        "echo-model".to_string(),
+                        Usage::default(),
+                    )),
+                )),
+            ];
+            Ok(Box::pin(futures::stream::iter(chunks)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let model = ModelConfig::new_or_fail("echo-model");
+        let messages = vec![Message::user().with_text("my key is sk-abcdefghijklmnopqrstuvwx")];
+
+        let recorder = RecordingProvider::recording(
+            Arc::new(EchoProvider {
+                model_config: model.clone(),
+            }),
+            dir.path(),
+        )
+        .unwrap();
+        recorder
+            .complete("system at 10:00", &messages, &[])
+            .await
+            .unwrap();
+
+        let recorded = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(recorded.len(), 1);
+        assert!(!recorded[0].contains("sk-abcdefghijklmnopqrstuvwx"));
+        assert!(recorded[0].contains(crate::redaction::REDACTED));
+
+        let replayer = RecordingProvider::replaying(dir.path(), model);
+        let (response, _) = replayer
+            .complete("system at 10:05", &messages, &[])
+            .await
+            .unwrap();
+        assert!(response.as_concat_text().starts_with("echo: my key is"));
+
+        let other = vec![Message::user().with_text("something else")];
+        assert!(replayer.complete("system", &other, &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_record_stream_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let model = ModelConfig::new_or_fail("echo-model");
+        let messages = vec![Message::user().with_text("hello")];
+
+        let recorder = RecordingProvider::recording(
+            Arc::new(EchoProvider {
+                model_config: model.clone(),
+            }),
+            dir.path(),
+        )
+        .unwrap();
+        assert!(recorder.supports_streaming());
+        let stream = recorder.stream("system", &messages, &[]).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        assert_eq!(chunks.len(), 2);
+
+        let replayer = RecordingProvider::replaying(dir.path(), model);
+        assert!(!replayer.supports_streaming());
+        let (response, _) = replayer.complete("system", &messages, &[]).await.unwrap();
+        assert_eq!(response.as_concat_text(), "echo: streamed");
+    }
+}
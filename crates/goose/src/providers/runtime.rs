@@ -0,0 +1,153 @@
+//! Keeping provider work off the async runtime's worker threads: the HTTP clients shared by all
+//! providers, so connections are pooled and reused across providers and provider instances, and
+//! a way for the synchronous constructors to finish async setup without stalling the runtime.
+use super::api_client::TlsConfig;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime, RuntimeFlavor};
+
+/// How long an unused connection stays in the pool
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const POOL_MAX_IDLE_PER_HOST: usize = 16;
+
+/// Clients differ only by what can't be set on a request: the overall timeout and TLS identity
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    timeout: Duration,
+    client_identity: Option<(PathBuf, PathBuf)>,
+    ca_cert_path: Option<PathBuf>,
+}
+
+static CLIENTS: Lazy<Mutex<HashMap<ClientKey, Client>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The shared client for requests with the given timeout
+pub fn shared_client(timeout: Duration) -> Result<Client> {
+    shared_client_with_tls(timeout, None)
+}
+
+/// The shared client for requests with the given timeout, presenting the configured client
+/// certificate and trusting the configured CA
+pub fn shared_client_with_tls(timeout: Duration, tls: Option<&TlsConfig>) -> Result<Client> {
+    let key = ClientKey {
+        timeout,
+        client_identity: tls
+            .and_then(|tls| tls.client_identity.as_ref())
+            .map(|pair| (pair.cert_path.clone(), pair.key_path.clone())),
+        ca_cert_path: tls.and_then(|tls| tls.ca_cert_path.clone()),
+    };
+    let mut clients = CLIENTS.lock().unwrap();
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+
+    let mut builder = Client::builder()
+        .timeout(timeout)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST);
+    if let Some(tls) = tls.filter(|tls| tls.is_configured()) {
+        if let Some(identity) = tls.load_identity()? {
+            builder = builder.identity(identity);
+        }
+        for certificate in tls.load_ca_certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    let client = builder.build()?;
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+/// Runs provider setup that can't use the caller's runtime. It lives as long as the process, so
+/// the connections setup opens, which the shared clients pool and reuse, stay usable afterwards
+static SETUP_RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("goose-provider-setup")
+        .enable_all()
+        .build()
+        .expect("failed to start the runtime for provider setup")
+});
+
+/// Wait for the async part of setting up a provider from synchronous code. On a multi-threaded
+/// runtime the worker hands its other tasks to the rest of the pool while it waits; anywhere else
+/// the future runs on the setup runtime, since blocking a current-thread runtime on a future
+/// that needs that runtime would never finish
+pub fn block_on_setup<F>(future: F) -> F::Output
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        Ok(_) => std::thread::scope(|scope| {
+            scope
+                .spawn(|| SETUP_RUNTIME.block_on(future))
+                .join()
+                .expect("provider setup panicked")
+        }),
+        Err(_) => SETUP_RUNTIME.block_on(future),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clients_are_shared_by_timeout() {
+        shared_client(Duration::from_secs(600)).unwrap();
+        shared_client(Duration::from_secs(600)).unwrap();
+        shared_client(Duration::from_secs(5)).unwrap();
+        let clients = CLIENTS.lock().unwrap();
+        let count = |timeout| clients.keys().filter(|key| key.timeout == timeout).count();
+        assert_eq!(count(Duration::from_secs(600)), 1);
+        assert_eq!(count(Duration::from_secs(5)), 1);
+    }
+
+    #[test]
+    fn test_block_on_setup_outside_runtime() {
+        assert_eq!(block_on_setup(async { 1 + 1 }), 2);
+    }
+
+    #[tokio::test]
+    async fn test_block_on_setup_on_current_thread_runtime() {
+        let value = block_on_setup(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            "done"
+        });
+        assert_eq!(value, "done");
+    }
+
+    #[tokio::test]
+    async fn test_block_on_setup_keeps_spawned_tasks_running() {
+        // Pooled connections are driven by tasks spawned during setup, they must outlive it
+        let (tx, rx) = std::sync::mpsc::channel();
+        block_on_setup(async move {
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                tx.send("still running").unwrap();
+            });
+        });
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "still running"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_block_on_setup_on_multi_thread_runtime() {
+        let value = block_on_setup(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            "done"
+        });
+        assert_eq!(value, "done");
+    }
+}
@@ -54,10 +54,10 @@ impl SageMakerTgiProvider {
         set_aws_env_vars(config.load_values());
         set_aws_env_vars(config.load_secrets());
 
-        let aws_config = futures::executor::block_on(aws_config::load_from_env());
+        let aws_config = super::runtime::block_on_setup(aws_config::load_from_env());
 
         // Validate credentials
-        futures::executor::block_on(
+        super::runtime::block_on_setup(
             aws_config
                 .credentials_provider()
                 .unwrap()
@@ -303,11 +303,11 @@ impl Provider for SageMakerTgiProvider {
         let message = self.parse_tgi_response(response)?;
 
         // TGI doesn't provide usage statistics, so we estimate
-        let usage = Usage {
-            input_tokens: Some(0),  // Would need to tokenize input to get accurate count
-            output_tokens: Some(0), // Would need to tokenize output to get accurate count
-            total_tokens: Some(0),
-        };
+        let usage = Usage::new(
+            Some(0), // Would need to tokenize input to get accurate count
+            Some(0), // Would need to tokenize output to get accurate count
+            Some(0),
+        );
 
         // Add debug trace
         let debug_payload = serde_json::json!({
@@ -6,7 +6,7 @@ use serde_json::{json, Value};
 use super::api_client::{ApiClient, AuthMethod};
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
-use super::formats::snowflake::{create_request, get_usage, response_to_message};
+use super::formats::snowflake::{create_request, get_usage, model_names, response_to_message};
 use super::retry::ProviderRetry;
 use super::utils::{get_model, map_http_error_to_provider_error, ImageFormat};
 use crate::config::ConfigError;
@@ -18,6 +18,11 @@ use rmcp::model::Tool;
 pub const SNOWFLAKE_DEFAULT_MODEL: &str = "claude-3-7-sonnet";
 pub const SNOWFLAKE_KNOWN_MODELS: &[&str] = &["claude-3-7-sonnet", "claude-3-5-sonnet"];
 
+/// Which kind of token SNOWFLAKE_TOKEN holds: PROGRAMMATIC_ACCESS_TOKEN, OAUTH or KEYPAIR_JWT.
+/// Snowflake guesses from the token when it isn't set
+const SNOWFLAKE_TOKEN_TYPE_KEY: &str = "SNOWFLAKE_TOKEN_TYPE";
+const SNOWFLAKE_TOKEN_TYPES: &[&str] = &["PROGRAMMATIC_ACCESS_TOKEN", "OAUTH", "KEYPAIR_JWT"];
+
 pub const SNOWFLAKE_DOC_URL: &str =
     "https://docs.snowflake.com/user-guide/snowflake-cortex/aisql#choosing-a-model";
 
@@ -87,7 +92,19 @@ impl SnowflakeProvider {
         };
 
         let auth = AuthMethod::BearerToken(token?);
-        let api_client = ApiClient::new(base_url, auth)?.with_header("User-Agent", "Goose")?;
+        let mut api_client = ApiClient::new(base_url, auth)?.with_header("User-Agent", "Goose")?;
+        if let Ok(token_type) = config.get_param::<String>(SNOWFLAKE_TOKEN_TYPE_KEY) {
+            let token_type = token_type.to_uppercase();
+            if !SNOWFLAKE_TOKEN_TYPES.contains(&token_type.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "{} must be one of {}",
+                    SNOWFLAKE_TOKEN_TYPE_KEY,
+                    SNOWFLAKE_TOKEN_TYPES.join(", ")
+                ));
+            }
+            api_client =
+                api_client.with_header("X-Snowflake-Authorization-Token-Type", &token_type)?;
+        }
 
         Ok(Self {
             api_client,
@@ -290,6 +307,7 @@ impl Provider for SnowflakeProvider {
             vec![
                 ConfigKey::new("SNOWFLAKE_HOST", true, false, None),
                 ConfigKey::new("SNOWFLAKE_TOKEN", true, true, None),
+                ConfigKey::new(SNOWFLAKE_TOKEN_TYPE_KEY, false, false, None),
             ],
         )
     }
@@ -326,4 +344,36 @@ impl Provider for SnowflakeProvider {
 
         Ok((message, ProviderUsage::new(response_model, usage)))
     }
+    async fn fetch_supported_models(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        let payload = json!({
+            "statement": "SHOW MODELS IN SNOWFLAKE.MODELS",
+            "timeout": 30,
+        });
+        let response = match self
+            .api_client
+            .response_post("api/v2/statements", &payload)
+            .await
+        {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                tracing::warn!("Failed to fetch Snowflake models: {}", response.status());
+                return Ok(None);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to fetch Snowflake models: {}", e);
+                return Ok(None);
+            }
+        };
+
+        match response.json::<Value>().await {
+            Ok(json) => {
+                let models = model_names(&json);
+                Ok((!models.is_empty()).then_some(models))
+            }
+            Err(e) => {
+                tracing::warn!("Failed to parse Snowflake models: {}", e);
+                Ok(None)
+            }
+        }
+    }
 }
@@ -28,8 +28,14 @@ struct TestOutput {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TestRecord {
-    input: TestInput,
-    output: TestOutput,
+    /// Left out when the request overflowed the context, it is too large to keep
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input: Option<TestInput>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output: Option<TestOutput>,
+    /// The provider refused the request as too long for its context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    context_length_exceeded: Option<String>,
 }
 
 pub struct TestProvider {
@@ -122,18 +128,32 @@ impl Provider for TestProvider {
         let hash = Self::hash_input(messages);
 
         if let Some(inner) = &self.inner {
-            let (message, usage) = inner.complete(system, messages, tools).await?;
+            let (message, usage) = match inner.complete(system, messages, tools).await {
+                Ok(response) => response,
+                Err(ProviderError::ContextLengthExceeded(msg)) => {
+                    // Recorded so the replay overflows the same way
+                    let record = TestRecord {
+                        input: None,
+                        output: None,
+                        context_length_exceeded: Some(msg.clone()),
+                    };
+                    self.records.lock().unwrap().insert(hash, record);
+                    return Err(ProviderError::ContextLengthExceeded(msg));
+                }
+                Err(e) => return Err(e),
+            };
 
             let record = TestRecord {
-                input: TestInput {
+                input: Some(TestInput {
                     system: system.to_string(),
                     messages: messages.to_vec(),
                     tools: tools.to_vec(),
-                },
-                output: TestOutput {
+                }),
+                output: Some(TestOutput {
                     message: message.clone(),
                     usage: usage.clone(),
-                },
+                }),
+                context_length_exceeded: None,
             };
 
             {
@@ -144,13 +164,19 @@ impl Provider for TestProvider {
             Ok((message, usage))
         } else {
             let records = self.records.lock().unwrap();
-            if let Some(record) = records.get(&hash) {
-                Ok((record.output.message.clone(), record.output.usage.clone()))
-            } else {
-                Err(ProviderError::ExecutionError(format!(
+            match records.get(&hash) {
+                Some(TestRecord {
+                    context_length_exceeded: Some(msg),
+                    ..
+                }) => Err(ProviderError::ContextLengthExceeded(msg.clone())),
+                Some(TestRecord {
+                    output: Some(output),
+                    ..
+                }) => Ok((output.message.clone(), output.usage.clone())),
+                _ => Err(ProviderError::ExecutionError(format!(
                     "No recorded response found for input hash: {}",
                     hash
-                )))
+                ))),
             }
         }
     }
@@ -283,4 +309,56 @@ mod tests {
 
         let _ = fs::remove_file(temp_file);
     }
+
+    struct OverflowProvider;
+
+    #[async_trait]
+    impl Provider for OverflowProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Err(ProviderError::ContextLengthExceeded(
+                "prompt is too long".to_string(),
+            ))
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new_or_fail("mock-model")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay_context_length_exceeded() {
+        let temp_file = format!(
+            "{}/test_overflow_{}.json",
+            env::temp_dir().display(),
+            std::process::id()
+        );
+        let messages = vec![Message::user().with_text("hello hello hello")];
+
+        let test_provider = TestProvider::new_recording(Arc::new(OverflowProvider), &temp_file);
+        let result = test_provider.complete("system", &messages, &[]).await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::ContextLengthExceeded(_))
+        ));
+        test_provider.finish_recording().unwrap();
+
+        let replay_provider = TestProvider::new_replaying(&temp_file).unwrap();
+        let result = replay_provider.complete("system", &messages, &[]).await;
+        assert_eq!(
+            result.unwrap_err(),
+            ProviderError::ContextLengthExceeded("prompt is too long".to_string())
+        );
+
+        let _ = fs::remove_file(temp_file);
+    }
 }
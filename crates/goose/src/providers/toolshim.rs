@@ -71,10 +71,8 @@ pub struct OllamaInterpreter {
 
 impl OllamaInterpreter {
     pub fn new() -> Result<Self, ProviderError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(600))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = super::runtime::shared_client(Duration::from_secs(600))
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))?;
 
         let base_url = Self::get_ollama_base_url()?;
 
@@ -378,6 +378,90 @@ pub fn load_image_file(path: &str) -> Result<ImageContent, ProviderError> {
     .no_annotation())
 }
 
+/// Longest edge in pixels accepted by every supported vision provider without server-side resizing
+pub const MAX_IMAGE_DIMENSION: u32 = 2048;
+
+/// Largest encoded image the strictest supported provider (Anthropic) accepts
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Load an image the user attached, downscaling and re-encoding it so it fits provider limits
+///
+/// Small PNG and JPEG files are passed through untouched. Anything else is decoded, scaled so
+/// its longest edge is at most `MAX_IMAGE_DIMENSION`, and re-encoded as PNG (or JPEG when the
+/// PNG would exceed `MAX_IMAGE_BYTES`).
+pub fn prepare_image_attachment(path: &Path) -> Result<ImageContent, ProviderError> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| ProviderError::RequestFailed(format!("Failed to read image file: {}", e)))?;
+
+    let format = image::guess_format(&bytes)
+        .map_err(|_| ProviderError::RequestFailed("File is not a valid image".to_string()))?;
+    let decoded = image::load_from_memory_with_format(&bytes, format)
+        .map_err(|e| ProviderError::RequestFailed(format!("Failed to decode image: {}", e)))?;
+
+    let fits = decoded.width() <= MAX_IMAGE_DIMENSION
+        && decoded.height() <= MAX_IMAGE_DIMENSION
+        && bytes.len() <= MAX_IMAGE_BYTES;
+    let passthrough_mime = match format {
+        image::ImageFormat::Png => Some("image/png"),
+        image::ImageFormat::Jpeg => Some("image/jpeg"),
+        _ => None,
+    };
+    if let (true, Some(mime_type)) = (fits, passthrough_mime) {
+        return Ok(encode_image_content(&bytes, mime_type));
+    }
+
+    let mut resized = downscale_image(decoded, MAX_IMAGE_DIMENSION);
+    let png = encode_image(&resized, image::ImageOutputFormat::Png)?;
+    if png.len() <= MAX_IMAGE_BYTES {
+        return Ok(encode_image_content(&png, "image/png"));
+    }
+
+    // Photos rarely fit as PNG at full size, fall back to progressively smaller JPEGs
+    loop {
+        let jpeg = encode_image(&resized, image::ImageOutputFormat::Jpeg(85))?;
+        if jpeg.len() <= MAX_IMAGE_BYTES || resized.width().max(resized.height()) <= 256 {
+            return Ok(encode_image_content(&jpeg, "image/jpeg"));
+        }
+        let longest = resized.width().max(resized.height());
+        resized = downscale_image(resized, longest * 3 / 4);
+    }
+}
+
+fn downscale_image(image: image::DynamicImage, max_dimension: u32) -> image::DynamicImage {
+    if image.width() <= max_dimension && image.height() <= max_dimension {
+        return image;
+    }
+    image.resize(
+        max_dimension,
+        max_dimension,
+        image::imageops::FilterType::Lanczos3,
+    )
+}
+
+fn encode_image(
+    image: &image::DynamicImage,
+    format: image::ImageOutputFormat,
+) -> Result<Vec<u8>, ProviderError> {
+    let mut bytes = Vec::new();
+    // JPEG has no alpha channel, so flatten before encoding
+    let image = match format {
+        image::ImageOutputFormat::Jpeg(_) => image::DynamicImage::ImageRgb8(image.to_rgb8()),
+        _ => image.clone(),
+    };
+    image
+        .write_to(&mut std::io::Cursor::new(&mut bytes), format)
+        .map_err(|e| ProviderError::RequestFailed(format!("Failed to encode image: {}", e)))?;
+    Ok(bytes)
+}
+
+fn encode_image_content(bytes: &[u8], mime_type: &str) -> ImageContent {
+    RawImageContent {
+        mime_type: mime_type.to_string(),
+        data: base64::prelude::BASE64_STANDARD.encode(bytes),
+    }
+    .no_annotation()
+}
+
 pub fn unescape_json_values(value: &Value) -> Value {
     match value {
         Value::Object(map) => {
@@ -536,6 +620,38 @@ mod tests {
         assert_eq!(detect_image_path(text), None);
     }
 
+    #[test]
+    fn test_prepare_image_attachment() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // Small PNGs are passed through untouched
+        let small_path = temp_dir.path().join("small.png");
+        image::RgbImage::new(16, 8).save(&small_path).unwrap();
+        let image = prepare_image_attachment(&small_path).unwrap();
+        assert_eq!(image.mime_type, "image/png");
+        let expected = base64::prelude::BASE64_STANDARD.encode(std::fs::read(&small_path).unwrap());
+        assert_eq!(image.data, expected);
+
+        // Oversized images are downscaled preserving aspect ratio
+        let large_path = temp_dir.path().join("large.bmp");
+        image::RgbImage::new(MAX_IMAGE_DIMENSION * 2, MAX_IMAGE_DIMENSION)
+            .save(&large_path)
+            .unwrap();
+        let image = prepare_image_attachment(&large_path).unwrap();
+        assert_eq!(image.mime_type, "image/png");
+        let bytes = base64::prelude::BASE64_STANDARD
+            .decode(&image.data)
+            .unwrap();
+        let decoded = image::load_from_memory(&bytes).unwrap();
+        assert_eq!(decoded.width(), MAX_IMAGE_DIMENSION);
+        assert_eq!(decoded.height(), MAX_IMAGE_DIMENSION / 2);
+
+        // Non-images are rejected
+        let text_path = temp_dir.path().join("notes.png");
+        std::fs::write(&text_path, b"not a real png").unwrap();
+        assert!(prepare_image_attachment(&text_path).is_err());
+    }
+
     #[test]
     fn test_load_image_file() {
         // Create a temporary PNG file with valid PNG magic numbers
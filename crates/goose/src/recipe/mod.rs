@@ -69,6 +69,7 @@ fn default_version() -> String {
 ///     response: None,
 ///     sub_recipes: None,
 ///     retry: None,
+///     schedule: None,
 /// };
 ///
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
@@ -115,6 +116,9 @@ pub struct Recipe {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retry: Option<RetryConfig>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<RecipeSchedule>, // when to run the recipe once installed
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
@@ -138,6 +142,18 @@ pub struct Settings {
     pub temperature: Option<f32>,
 }
 
+/// When an installed recipe runs, see `goose recipe install`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct RecipeSchedule {
+    pub cron: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>, // IANA name such as Europe/Berlin, UTC when unset
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify: Option<String>, // name of the webhook told about each run
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct Response {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -251,6 +267,7 @@ pub struct RecipeBuilder {
     response: Option<Response>,
     sub_recipes: Option<Vec<SubRecipe>>,
     retry: Option<RetryConfig>,
+    schedule: Option<RecipeSchedule>,
 }
 
 impl Recipe {
@@ -303,6 +320,7 @@ impl Recipe {
             response: None,
             sub_recipes: None,
             retry: None,
+            schedule: None,
         }
     }
     pub fn from_content(content: &str) -> Result<Self> {
@@ -419,6 +437,12 @@ impl RecipeBuilder {
         self
     }
 
+    /// Sets when the Recipe runs once installed
+    pub fn schedule(mut self, schedule: RecipeSchedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
     /// Builds the Recipe instance
     ///
     /// Returns an error if any required fields are missing
@@ -445,6 +469,7 @@ impl RecipeBuilder {
             response: self.response,
             sub_recipes: self.sub_recipes,
             retry: self.retry,
+            schedule: self.schedule,
         })
     }
 }
@@ -673,6 +698,28 @@ sub_recipes:
         assert_eq!(author.contact, Some("test@example.com".to_string()));
     }
 
+    #[test]
+    fn test_from_content_with_schedule() {
+        let content = r#"title: Nightly triage
+description: Triage new issues
+prompt: Triage the issues opened today
+schedule:
+  cron: "0 0 18 * * 1-5"
+  timezone: Europe/Berlin
+  notify: team-chat"#;
+
+        let recipe = Recipe::from_content(content).unwrap();
+
+        assert_eq!(
+            recipe.schedule,
+            Some(RecipeSchedule {
+                cron: "0 0 18 * * 1-5".to_string(),
+                timezone: Some("Europe/Berlin".to_string()),
+                notify: Some("team-chat".to_string()),
+            })
+        );
+    }
+
     #[test]
     fn test_inline_python_extension() {
         let content = r#"{
@@ -784,6 +831,7 @@ isGlobal: true"#;
             response: None,
             sub_recipes: None,
             retry: None,
+            schedule: None,
         };
 
         assert!(!recipe.check_for_security_warnings());
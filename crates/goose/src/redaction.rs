@@ -0,0 +1,256 @@
+//! Masking of secrets in text that leaves the machine, like provider recordings, shared session
+//! bundles and everything sent to the model. Secrets are found by value, from the keyring or
+//! secrets file and from environment variables with secret names, and by shape, for credentials
+//! goose doesn't know about.
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+
+use crate::config::Config;
+use crate::conversation::message::Message;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Whether secrets are masked in content sent to the model or saved with the session, on by
+/// default
+pub const REDACT_SECRETS_KEY: &str = "GOOSE_REDACT_SECRETS";
+/// Names of the environment variables holding secrets, `*` matches any characters
+pub const REDACT_ENV_PATTERNS_KEY: &str = "GOOSE_REDACT_ENV_PATTERNS";
+/// Names of secrets and environment variables that are never masked
+pub const REDACT_EXCLUDE_KEY: &str = "GOOSE_REDACT_EXCLUDE";
+const DEFAULT_ENV_PATTERNS: &[&str] = &["*_API_KEY", "*_TOKEN", "*_SECRET", "*_PASSWORD"];
+/// Secrets shorter than this are too likely to match ordinary text to be redacted by value
+const MIN_SECRET_LEN: usize = 8;
+
+/// Tokens that look like credentials whether or not goose knows them
+static CREDENTIAL_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"sk-[A-Za-z0-9_\-]{16,}|gh[pousr]_[A-Za-z0-9]{20,}|AKIA[0-9A-Z]{16}|xox[abpr]-[A-Za-z0-9\-]{10,}|(?i:bearer)\s+[A-Za-z0-9._\-]{16,}",
+    )
+    .unwrap()
+});
+
+/// The redactor for model-bound content, built on first use and dropped when secrets change
+static MODEL_BOUND: Lazy<RwLock<Option<Arc<Redactor>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Secrets handed to goose outside the config, like the secret environment variables of a session
+static REGISTERED: Lazy<RwLock<Vec<String>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    /// Longest first, so a secret containing another is redacted whole
+    secrets: Vec<String>,
+    match_credential_shapes: bool,
+}
+
+impl Redactor {
+    pub fn new(secrets: impl IntoIterator<Item = String>) -> Self {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .filter(|secret| secret.len() >= MIN_SECRET_LEN)
+            .collect();
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        secrets.dedup();
+        Self {
+            secrets,
+            match_credential_shapes: true,
+        }
+    }
+
+    /// Add the explicitly registered secrets, which skip the minimum length
+    fn with_registered(mut self, registered: Vec<String>) -> Self {
+        self.secrets
+            .extend(registered.into_iter().filter(|secret| !secret.is_empty()));
+        self.secrets
+            .sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        self.secrets.dedup();
+        self
+    }
+
+    /// A redactor for the secret values stored in the config
+    pub fn from_config() -> Self {
+        Self::new(
+            Config::global()
+                .load_secrets()
+                .unwrap_or_default()
+                .into_values()
+                .filter_map(|value| value.as_str().map(str::to_string)),
+        )
+    }
+
+    /// The redactor for messages sent to the model or saved with the session. It only masks
+    /// known secret values, credential shaped text the user typed on purpose still reaches the
+    /// model
+    pub fn model_bound() -> Arc<Redactor> {
+        if let Some(redactor) = MODEL_BOUND.read().unwrap().as_ref() {
+            return redactor.clone();
+        }
+        let redactor = Arc::new(Self::configured_for_model());
+        *MODEL_BOUND.write().unwrap() = Some(redactor.clone());
+        redactor
+    }
+
+    /// Drop the model-bound redactor so the next use picks up changed secrets
+    pub fn forget_model_bound() {
+        *MODEL_BOUND.write().unwrap() = None;
+    }
+
+    /// Mask these values in model-bound content from now on, even when redaction of configured
+    /// secrets is turned off and however short they are, since they were marked secret explicitly
+    pub fn register_secrets(secrets: impl IntoIterator<Item = String>) {
+        REGISTERED.write().unwrap().extend(secrets);
+        Self::forget_model_bound();
+    }
+
+    fn configured_for_model() -> Self {
+        let config = Config::global();
+        let registered = REGISTERED.read().unwrap().clone();
+        if !config.get_param::<bool>(REDACT_SECRETS_KEY).unwrap_or(true) {
+            return Self::default().with_registered(registered);
+        }
+        let excluded: Vec<String> = config.get_param(REDACT_EXCLUDE_KEY).unwrap_or_default();
+        let is_excluded = |name: &str| excluded.iter().any(|key| key.eq_ignore_ascii_case(name));
+        let patterns: Vec<String> = config
+            .get_param(REDACT_ENV_PATTERNS_KEY)
+            .unwrap_or_else(|_| DEFAULT_ENV_PATTERNS.iter().map(|p| p.to_string()).collect());
+
+        let from_secrets = config
+            .load_secrets()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(key, _)| !is_excluded(key))
+            .filter_map(|(_, value)| value.as_str().map(str::to_string));
+        let from_env = std::env::vars()
+            .filter(|(name, _)| {
+                !is_excluded(name) && patterns.iter().any(|pattern| name_matches(pattern, name))
+            })
+            .map(|(_, value)| value);
+
+        Self {
+            match_credential_shapes: false,
+            ..Self::new(from_secrets.chain(from_env).collect::<Vec<_>>())
+        }
+        .with_registered(registered)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty() && !self.match_credential_shapes
+    }
+
+    /// Replace the known secrets and, unless only values are redacted, anything shaped like a
+    /// credential
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for secret in &self.secrets {
+            text = text.replace(secret.as_str(), REDACTED);
+        }
+        if self.match_credential_shapes {
+            text = CREDENTIAL_PATTERN.replace_all(&text, REDACTED).into_owned();
+        }
+        text
+    }
+
+    /// The message with secrets masked in every string it holds: text, thinking, tool arguments
+    /// and tool results
+    pub fn redact_message(&self, message: &Message) -> Message {
+        if self.is_empty() {
+            return message.clone();
+        }
+        let redacted = serde_json::to_value(message).and_then(|mut value| {
+            self.redact_value(&mut value);
+            serde_json::from_value(value)
+        });
+        match redacted {
+            Ok(redacted) => redacted,
+            Err(e) => {
+                tracing::warn!("Failed to redact message, masking its content: {}", e);
+                Message::new(message.role.clone(), message.created, vec![]).with_text(REDACTED)
+            }
+        }
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => {
+                if self
+                    .secrets
+                    .iter()
+                    .any(|secret| text.contains(secret.as_str()))
+                    || (self.match_credential_shapes && CREDENTIAL_PATTERN.is_match(text))
+                {
+                    *text = self.redact(text);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::Object(fields) => fields
+                .values_mut()
+                .for_each(|field| self.redact_value(field)),
+            _ => {}
+        }
+    }
+}
+
+/// Whether an environment variable name matches a pattern like `*_API_KEY`, ignoring case
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let pattern = format!("^(?i){}$", regex::escape(pattern).replace(r"\*", ".*"));
+    Regex::new(&pattern).is_ok_and(|pattern| pattern.is_match(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let redactor = Redactor::new(vec!["hunter2hunter2".to_string(), "short".to_string()]);
+        assert_eq!(
+            redactor.redact("password hunter2hunter2, header Bearer abcdefghijklmnopqrstu"),
+            "password [REDACTED], header [REDACTED]"
+        );
+        assert_eq!(redactor.redact("short"), "short");
+    }
+
+    #[test]
+    fn test_redact_message_values_only() {
+        let redactor = Redactor {
+            match_credential_shapes: false,
+            ..Redactor::new(vec!["hunter2hunter2".to_string()])
+        };
+        let message = Message::user().with_tool_response(
+            "call_1",
+            Ok(vec![rmcp::model::Content::text(
+                "PASSWORD=hunter2hunter2\nKEY=sk-abcdefghijklmnopqrstuvwx",
+            )]),
+        );
+        let redacted = redactor.redact_message(&message);
+        let text = redacted.content[0].as_tool_response_text().unwrap();
+        assert_eq!(text, "PASSWORD=[REDACTED]\nKEY=sk-abcdefghijklmnopqrstuvwx");
+        assert_eq!(redacted.id, message.id);
+    }
+
+    #[test]
+    fn test_registered_secrets_are_model_bound() {
+        Redactor::register_secrets(vec!["session-only-secret-value".to_string()]);
+        assert_eq!(
+            Redactor::model_bound().redact("DEPLOY_KEY=session-only-secret-value"),
+            "DEPLOY_KEY=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_short_registered_secrets_are_model_bound() {
+        Redactor::register_secrets(vec!["pin4321".to_string()]);
+        assert_eq!(
+            Redactor::model_bound().redact("the pin is pin4321"),
+            "the pin is [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_name_matches() {
+        assert!(name_matches("*_API_KEY", "OPENAI_API_KEY"));
+        assert!(name_matches("*_token", "GITHUB_TOKEN"));
+        assert!(!name_matches("*_API_KEY", "OPENAI_API_KEY_FILE"));
+    }
+}
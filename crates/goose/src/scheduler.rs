@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::fs;
+use std::future::Future;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
@@ -23,6 +25,7 @@ use crate::recipe::Recipe;
 use crate::scheduler_trait::SchedulerTrait;
 use crate::session;
 use crate::session::storage::SessionMetadata;
+use crate::webhooks::{self, WebhookEvent};
 
 // Track running tasks with their abort handles
 type RunningTasksMap = HashMap<String, tokio::task::AbortHandle>;
@@ -160,6 +163,37 @@ pub struct ScheduledJob {
     pub process_start_time: Option<DateTime<Utc>>,
     #[serde(default)]
     pub execution_mode: Option<String>, // "foreground" or "background"
+    /// IANA timezone the cron expression is in, UTC when unset
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Name of the webhook told about each run
+    #[serde(default)]
+    pub notify: Option<String>,
+    /// The recipe file the job was installed from with `goose recipe install`
+    #[serde(default)]
+    pub recipe_origin: Option<String>,
+}
+
+/// The IANA timezone, such as `Europe/Berlin`, that a job's cron expression is in
+pub fn parse_timezone(timezone: &str) -> Result<chrono_tz::Tz, SchedulerError> {
+    timezone
+        .parse()
+        .map_err(|_| SchedulerError::CronParseError(format!("Unknown timezone '{}'", timezone)))
+}
+
+/// A job running `run` on the cron expression, in the given IANA timezone or else in UTC
+fn cron_job<T>(cron: &str, timezone: Option<&str>, run: T) -> Result<Job, SchedulerError>
+where
+    T: FnMut(JobId, TokioJobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>>
+        + Send
+        + Sync
+        + 'static,
+{
+    let job = match timezone {
+        Some(timezone) => Job::new_async_tz(cron, parse_timezone(timezone)?, run),
+        None => Job::new_async(cron, run),
+    };
+    job.map_err(|e| SchedulerError::CronParseError(e.to_string()))
 }
 
 async fn persist_jobs_from_arc(
@@ -287,7 +321,8 @@ impl Scheduler {
                 tokio_cron
             );
         }
-        let cron_task = Job::new_async(&tokio_cron, move |_uuid, _l| {
+        let timezone = stored_job.timezone.clone();
+        let cron_task = cron_job(&tokio_cron, timezone.as_deref(), move |_uuid, _l| {
             let task_job_id = job_for_task.id.clone();
             let current_jobs_arc = jobs_arc_for_task.clone();
             let local_storage_path = storage_path_for_task.clone();
@@ -403,8 +438,7 @@ impl Scheduler {
                     }
                 }
             })
-        })
-        .map_err(|e| SchedulerError::CronParseError(e.to_string()))?;
+        })?;
 
         let job_uuid = self
             .internal_scheduler
@@ -465,7 +499,8 @@ impl Scheduler {
                     tokio_cron
                 );
             }
-            let cron_task = Job::new_async(&tokio_cron, move |_uuid, _l| {
+            let timezone = job_to_load.timezone.clone();
+            let cron_task = cron_job(&tokio_cron, timezone.as_deref(), move |_uuid, _l| {
                 let task_job_id = job_for_task.id.clone();
                 let current_jobs_arc = jobs_arc_for_task.clone();
                 let local_storage_path = storage_path_for_task.clone();
@@ -584,8 +619,7 @@ impl Scheduler {
                         }
                     }
                 })
-            })
-            .map_err(|e| SchedulerError::CronParseError(e.to_string()))?;
+            })?;
 
             let job_uuid = self
                 .internal_scheduler
@@ -845,7 +879,8 @@ impl Scheduler {
                         tokio_cron
                     );
                 }
-                let cron_task = Job::new_async(&tokio_cron, move |_uuid, _l| {
+                let timezone = job_def.timezone.clone();
+                let cron_task = cron_job(&tokio_cron, timezone.as_deref(), move |_uuid, _l| {
                     let task_job_id = job_for_task.id.clone();
                     let current_jobs_arc = jobs_arc_for_task.clone();
                     let local_storage_path = storage_path_for_task.clone();
@@ -973,8 +1008,7 @@ impl Scheduler {
                             }
                         }
                     })
-                })
-                .map_err(|e| SchedulerError::CronParseError(e.to_string()))?;
+                })?;
 
                 let new_job_uuid = self
                     .internal_scheduler
@@ -1056,6 +1090,31 @@ impl Scheduler {
             None => Err(SchedulerError::JobNotFound(sched_id.to_string())),
         }
     }
+
+    /// Stop firing jobs and abort the running ones, which are recorded as no longer running so
+    /// they don't look stuck after a restart
+    pub async fn shutdown(&self) -> Result<(), SchedulerError> {
+        let mut jobs_guard = self.jobs.lock().await;
+        {
+            let mut running_tasks_guard = self.running_tasks.lock().await;
+            for (sched_id, abort_handle) in running_tasks_guard.drain() {
+                tracing::info!("Aborting job '{}' on shutdown", sched_id);
+                abort_handle.abort();
+            }
+        }
+        for (_, job_def) in jobs_guard.values_mut() {
+            job_def.currently_running = false;
+            job_def.current_session_id = None;
+            job_def.process_start_time = None;
+        }
+        self.persist_jobs_to_storage_with_guard(&jobs_guard).await?;
+
+        self.internal_scheduler
+            .clone()
+            .shutdown()
+            .await
+            .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))
+    }
 }
 
 #[derive(Debug)]
@@ -1069,6 +1128,30 @@ async fn run_scheduled_job_internal(
     provider_override: Option<Arc<dyn GooseProvider>>, // New optional parameter
     jobs_arc: Option<Arc<Mutex<JobsMap>>>,
     job_id: Option<String>,
+) -> std::result::Result<String, JobExecutionError> {
+    let notify = job.notify.clone();
+    let job_name = job.id.clone();
+    let result = execute_scheduled_job(job, provider_override, jobs_arc, job_id).await;
+    match &result {
+        Ok(session_id) => webhooks::notify_with_target(
+            WebhookEvent::ScheduledRunCompleted,
+            serde_json::json!({"job_id": job_name, "session_id": session_id}),
+            notify.as_deref(),
+        ),
+        Err(e) => webhooks::notify_with_target(
+            WebhookEvent::ScheduledRunFailed,
+            serde_json::json!({"job_id": e.job_id, "error": e.error}),
+            notify.as_deref(),
+        ),
+    }
+    result
+}
+
+async fn execute_scheduled_job(
+    job: ScheduledJob,
+    provider_override: Option<Arc<dyn GooseProvider>>,
+    jobs_arc: Option<Arc<Mutex<JobsMap>>>,
+    job_id: Option<String>,
 ) -> std::result::Result<String, JobExecutionError> {
     tracing::info!("Executing job: {} (Source: {})", job.id, job.source);
 
@@ -1222,6 +1305,7 @@ async fn run_scheduled_job_internal(
             execution_mode: job.execution_mode.clone(),
             max_turns: None,
             retry_config: None,
+            confirmation_timeout: None,
         };
 
         match agent
@@ -1255,6 +1339,9 @@ async fn run_scheduled_job_internal(
                         Ok(AgentEvent::HistoryReplaced(_)) => {
                             // Handle history replacement events if needed
                         }
+                        Ok(AgentEvent::PlanUpdate(_)) | Ok(AgentEvent::TodoUpdate(_)) => {
+                            // The plan and TODO list are saved in the session metadata by the agent
+                        }
                         Err(e) => {
                             tracing::error!(
                                 "[Job {}] Error receiving message from agent: {}",
@@ -1269,16 +1356,17 @@ async fn run_scheduled_job_internal(
                 match crate::session::storage::read_metadata(&session_file_path) {
                     Ok(mut updated_metadata) => {
                         updated_metadata.message_count = all_session_messages.len();
-                        if let Err(e) = crate::session::storage::save_messages_with_metadata(
+                        match crate::session::storage::save_messages_with_metadata(
                             &session_file_path,
                             &updated_metadata,
                             &all_session_messages,
                         ) {
-                            tracing::error!(
+                            Ok(()) => crate::session::journal::commit(&session_file_path),
+                            Err(e) => tracing::error!(
                                 "[Job {}] Failed to persist final messages: {}",
                                 job.id,
                                 e
-                            );
+                            ),
                         }
                     }
                     Err(e) => {
@@ -1292,6 +1380,9 @@ async fn run_scheduled_job_internal(
                             description: String::new(),
                             schedule_id: Some(job.id.clone()),
                             message_count: all_session_messages.len(),
+                            turn_count: 0,
+                            provider: None,
+                            model: None,
                             total_tokens: None,
                             input_tokens: None,
                             output_tokens: None,
@@ -1299,13 +1390,23 @@ async fn run_scheduled_job_internal(
                             accumulated_input_tokens: None,
                             accumulated_output_tokens: None,
                             todo_content: None,
+                            todos: Vec::new(),
+                            parent_session_id: None,
+                            forked_at: None,
+                            tags: Vec::new(),
+                            plan: None,
+                            checkpoint: None,
+                            schedule_run_url: None,
+                            coverage: None,
+                            attachments: Vec::new(),
                         };
-                        if let Err(e_fb) = crate::session::storage::save_messages_with_metadata(
+                        match crate::session::storage::save_messages_with_metadata(
                             &session_file_path,
                             &fallback_metadata,
                             &all_session_messages,
                         ) {
-                            tracing::error!("[Job {}] Failed to persist final messages with fallback metadata: {}", job.id, e_fb);
+                            Ok(()) => crate::session::journal::commit(&session_file_path),
+                            Err(e_fb) => tracing::error!("[Job {}] Failed to persist final messages with fallback metadata: {}", job.id, e_fb),
                         }
                     }
                 }
@@ -1452,6 +1553,7 @@ mod tests {
             response: None,
             sub_recipes: None,
             retry: None,
+            schedule: None,
         };
         let mut recipe_file = File::create(&recipe_filename)?;
         writeln!(
@@ -1472,6 +1574,9 @@ mod tests {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some("background".to_string()), // Default for test
+            timezone: None,
+            notify: None,
+            recipe_origin: None,
         };
 
         let mock_model_config = ModelConfig::new_or_fail("test_model");
@@ -1524,6 +1629,22 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_cron_job_in_timezone() {
+        assert!(parse_timezone("Europe/Berlin").is_ok());
+        assert!(matches!(
+            parse_timezone("Mars/Olympus_Mons"),
+            Err(SchedulerError::CronParseError(_))
+        ));
+
+        fn run(_uuid: JobId, _l: TokioJobScheduler) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+            Box::pin(async {})
+        }
+        assert!(cron_job("0 0 9 * * *", Some("America/New_York"), run).is_ok());
+        assert!(cron_job("0 0 9 * * *", None, run).is_ok());
+        assert!(cron_job("0 0 9 * * *", Some("Nowhere"), run).is_err());
+    }
 }
 
 #[async_trait]
@@ -1578,4 +1699,8 @@ impl SchedulerTrait for Scheduler {
     ) -> Result<Option<(String, DateTime<Utc>)>, SchedulerError> {
         self.get_running_job_info(sched_id).await
     }
+
+    async fn shutdown(&self) -> Result<(), SchedulerError> {
+        self.shutdown().await
+    }
 }
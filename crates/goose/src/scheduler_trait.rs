@@ -44,4 +44,9 @@ pub trait SchedulerTrait: Send + Sync {
         &self,
         sched_id: &str,
     ) -> Result<Option<(String, DateTime<Utc>)>, SchedulerError>;
+
+    /// Stop the scheduler when goose exits, running jobs are aborted
+    async fn shutdown(&self) -> Result<(), SchedulerError> {
+        Ok(())
+    }
 }
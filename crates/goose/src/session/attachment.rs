@@ -0,0 +1,294 @@
+use crate::utils::safe_truncate;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use super::storage::ensure_session_dir;
+
+/// URI scheme under which attachments are exposed as resources
+pub const ATTACHMENT_URI_SCHEME: &str = "attachment://";
+
+/// Number of characters of an attachment inlined into the conversation
+pub const ATTACHMENT_PREVIEW_CHARS: usize = 4000;
+
+const MAX_ATTACHMENT_SIZE: usize = 10 * 1024 * 1024; // 10MB
+
+/// How long downloading an attached URL may take
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A file or web page the user attached to a session
+///
+/// The full content is copied into the session data dir so it stays readable through the
+/// platform resource tools even if the original file changes or the URL goes away.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Attachment {
+    /// Display name, usually the file name
+    pub name: String,
+    /// Resource URI the agent can read the full content from
+    pub uri: String,
+    /// Original path or URL the attachment was loaded from
+    pub source: String,
+    pub mime_type: String,
+    /// Where the stored copy lives
+    #[schema(value_type = String)]
+    pub path: PathBuf,
+    pub size: usize,
+}
+
+impl Attachment {
+    /// Read the stored content, or None when the attachment is not text
+    pub fn read_text(&self) -> Result<Option<String>> {
+        let bytes = std::fs::read(&self.path)?;
+        Ok(String::from_utf8(bytes).ok())
+    }
+
+    /// Render the representation injected into the conversation: a header pointing at the
+    /// resource plus the content, truncated to `ATTACHMENT_PREVIEW_CHARS`
+    pub fn preview(&self) -> Result<String> {
+        let header = format!(
+            "[Attached {} from {} ({} bytes, {})]",
+            self.name, self.source, self.size, self.mime_type
+        );
+        let text = match self.read_text()? {
+            Some(text) => text,
+            None => {
+                return Ok(format!(
+                    "{}\nBinary content is not shown, it is available as resource {}",
+                    header, self.uri
+                ))
+            }
+        };
+
+        if text.chars().count() <= ATTACHMENT_PREVIEW_CHARS {
+            Ok(format!("{}\n```\n{}\n```", header, text))
+        } else {
+            Ok(format!(
+                "{}\n```\n{}\n```\nThis is a truncated preview. Read the full content with the read_resource tool using uri {}",
+                header,
+                safe_truncate(&text, ATTACHMENT_PREVIEW_CHARS),
+                self.uri
+            ))
+        }
+    }
+}
+
+/// Directory holding the attachments of a session, created if needed
+pub fn attachments_dir(session_id: &str) -> Result<PathBuf> {
+    let dir = ensure_session_dir()?.join("attachments").join(session_id);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Load a local file or URL and store a copy of it in `dir`
+pub async fn store_attachment(source: &str, dir: &Path) -> Result<Attachment> {
+    let (name, mime_type, bytes) = if is_url(source) {
+        fetch_url(source).await?
+    } else {
+        read_file(source).await?
+    };
+
+    if bytes.len() > MAX_ATTACHMENT_SIZE {
+        return Err(too_large(source, bytes.len()));
+    }
+
+    tokio::fs::create_dir_all(dir).await?;
+    let file_name = unique_file_name(dir, &name);
+    let path = dir.join(&file_name);
+    tokio::fs::write(&path, &bytes).await?;
+
+    Ok(Attachment {
+        name,
+        uri: format!("{}{}", ATTACHMENT_URI_SCHEME, file_name),
+        source: source.to_string(),
+        mime_type,
+        path,
+        size: bytes.len(),
+    })
+}
+
+fn too_large(source: &str, size: usize) -> anyhow::Error {
+    anyhow!(
+        "{} is too large ({} bytes or more), the maximum attachment size is {} bytes",
+        source,
+        size,
+        MAX_ATTACHMENT_SIZE
+    )
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+async fn read_file(source: &str) -> Result<(String, String, Vec<u8>)> {
+    let path = Path::new(source);
+    let bytes = tokio::fs::read(path)
+        .await
+        .map_err(|e| anyhow!("Failed to read {}: {}", source, e))?;
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("attachment")
+        .to_string();
+    let mime_type = guess_mime_type(&name, &bytes);
+    Ok((name, mime_type, bytes))
+}
+
+async fn fetch_url(source: &str) -> Result<(String, String, Vec<u8>)> {
+    let url = url::Url::parse(source)?;
+    let client = reqwest::Client::builder().timeout(FETCH_TIMEOUT).build()?;
+    let mut response = client.get(url.clone()).send().await?.error_for_status()?;
+    if let Some(length) = response.content_length() {
+        if length > MAX_ATTACHMENT_SIZE as u64 {
+            return Err(too_large(source, length as usize));
+        }
+    }
+    let mime_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_string());
+    // The length may be missing or wrong, so the download stops as soon as it goes over
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if bytes.len() + chunk.len() > MAX_ATTACHMENT_SIZE {
+            return Err(too_large(source, bytes.len() + chunk.len()));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .or_else(|| url.host_str())
+        .unwrap_or("attachment")
+        .to_string();
+    let mime_type = mime_type.unwrap_or_else(|| guess_mime_type(&name, &bytes));
+    Ok((name, mime_type, bytes))
+}
+
+fn guess_mime_type(name: &str, bytes: &[u8]) -> String {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    let mime_type = match extension.as_deref() {
+        Some("md") => "text/markdown",
+        Some("html") | Some("htm") => "text/html",
+        Some("json") => "application/json",
+        Some("yaml") | Some("yml") => "application/yaml",
+        Some("csv") => "text/csv",
+        Some("pdf") => "application/pdf",
+        _ if std::str::from_utf8(bytes).is_ok() => "text/plain",
+        _ => "application/octet-stream",
+    };
+    mime_type.to_string()
+}
+
+/// Avoid clobbering an earlier attachment with the same name
+fn unique_file_name(dir: &Path, name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !dir.join(&sanitized).exists() {
+        return sanitized;
+    }
+    (1..)
+        .map(|i| format!("{}-{}", i, sanitized))
+        .find(|candidate| !dir.join(candidate).exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_store_attachment_from_file() {
+        let source_dir = tempdir().unwrap();
+        let source = source_dir.path().join("notes.md");
+        std::fs::write(&source, "# Notes\nremember the milk").unwrap();
+        let store = tempdir().unwrap();
+
+        let attachment = store_attachment(source.to_str().unwrap(), store.path())
+            .await
+            .unwrap();
+        assert_eq!(attachment.name, "notes.md");
+        assert_eq!(attachment.uri, "attachment://notes.md");
+        assert_eq!(attachment.mime_type, "text/markdown");
+        assert!(attachment.path.starts_with(store.path()));
+        assert!(attachment.preview().unwrap().contains("remember the milk"));
+
+        // A second attachment with the same name gets its own copy
+        let second = store_attachment(source.to_str().unwrap(), store.path())
+            .await
+            .unwrap();
+        assert_eq!(second.uri, "attachment://1-notes.md");
+    }
+
+    #[tokio::test]
+    async fn test_preview_truncates_large_text() {
+        let source_dir = tempdir().unwrap();
+        let source = source_dir.path().join("big.txt");
+        std::fs::write(&source, "x".repeat(ATTACHMENT_PREVIEW_CHARS * 2)).unwrap();
+        let store = tempdir().unwrap();
+
+        let attachment = store_attachment(source.to_str().unwrap(), store.path())
+            .await
+            .unwrap();
+        let preview = attachment.preview().unwrap();
+        assert!(preview.len() < ATTACHMENT_PREVIEW_CHARS + 500);
+        assert!(preview.contains("truncated preview"));
+        assert!(preview.contains("attachment://big.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_store_attachment_from_url_is_capped() {
+        use wiremock::matchers::path;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(path("/notes.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("from the web"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/huge.bin"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_bytes(vec![0u8; MAX_ATTACHMENT_SIZE + 1]),
+            )
+            .mount(&server)
+            .await;
+        let store = tempdir().unwrap();
+
+        let attachment = store_attachment(&format!("{}/notes.txt", server.uri()), store.path())
+            .await
+            .unwrap();
+        assert_eq!(attachment.name, "notes.txt");
+        assert_eq!(attachment.read_text().unwrap().unwrap(), "from the web");
+
+        let error = store_attachment(&format!("{}/huge.bin", server.uri()), store.path())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("too large"));
+        assert!(!store.path().join("huge.bin").exists());
+    }
+
+    #[tokio::test]
+    async fn test_store_attachment_missing_file() {
+        let store = tempdir().unwrap();
+        assert!(store_attachment("/does/not/exist.txt", store.path())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! A journal of the turn in progress, kept next to the session file while the agent replies.
+//!
+//! The frontend removes the journal with [`commit`] once it saved the messages of the turn. One
+//! that is still there when a session is loaded means goose crashed, lost power or failed to save
+//! mid-turn, and [`recover`] reconciles the session: tool calls that never finished are closed
+//! with an error so the conversation stays valid, and the turn can be replayed.
+use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::Conversation;
+use crate::session::storage::{read_messages, read_metadata, save_messages_with_metadata};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use rmcp::model::{ErrorCode, ErrorData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Streamed text is written at most this often, tool calls are written as they change
+const TEXT_SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Journals of the turns this process is running, which aren't recovered from under them
+static OPEN_JOURNALS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingToolCall {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TurnJournal {
+    /// Unix timestamp of when the turn started
+    pub started_at: i64,
+    /// Tool calls requested in this turn that have no result yet
+    pub pending_tool_calls: Vec<PendingToolCall>,
+    /// Text of the assistant message being streamed
+    pub partial_text: String,
+    #[serde(skip)]
+    partial_id: Option<String>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(skip)]
+    last_save: Option<Instant>,
+}
+
+/// Where the journal of a session file is kept
+pub fn journal_path(session_file: &Path) -> PathBuf {
+    session_file.with_extension("journal")
+}
+
+impl TurnJournal {
+    /// Start the journal of a turn of the session. It stays on disk until [`commit`]
+    pub fn begin(session_file: &Path) -> Self {
+        let path = journal_path(session_file);
+        OPEN_JOURNALS.lock().unwrap().insert(path.clone());
+        let mut journal = Self {
+            started_at: chrono::Utc::now().timestamp(),
+            pending_tool_calls: Vec::new(),
+            partial_text: String::new(),
+            partial_id: None,
+            path: Some(path),
+            last_save: None,
+        };
+        journal.save();
+        journal
+    }
+
+    pub fn load(session_file: &Path) -> Result<Option<Self>> {
+        let path = journal_path(session_file);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    /// Track the tool calls and streamed text of a message of the turn
+    pub fn record(&mut self, message: &Message) {
+        let mut tools_changed = false;
+        for content in &message.content {
+            match content {
+                MessageContent::ToolRequest(request) => {
+                    let name = match &request.tool_call {
+                        Ok(tool_call) => tool_call.name.clone(),
+                        Err(_) => String::new(),
+                    };
+                    self.pending_tool_calls.push(PendingToolCall {
+                        id: request.id.clone(),
+                        name,
+                    });
+                    tools_changed = true;
+                }
+                MessageContent::ToolResponse(response) => {
+                    self.pending_tool_calls
+                        .retain(|call| call.id != response.id);
+                    tools_changed = true;
+                }
+                MessageContent::Text(text) if message.role == rmcp::model::Role::Assistant => {
+                    if message.id.is_none() || message.id != self.partial_id {
+                        self.partial_text.clear();
+                        self.partial_id = message.id.clone();
+                    }
+                    self.partial_text.push_str(&text.text);
+                }
+                _ => {}
+            }
+        }
+
+        let text_due = self
+            .last_save
+            .is_none_or(|saved| saved.elapsed() >= TEXT_SAVE_INTERVAL);
+        if tools_changed || text_due {
+            self.save();
+        }
+    }
+
+    /// Write the journal, a turn goes on when it can't be written
+    fn save(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let temp = path.with_extension("journal.tmp");
+        let result = serde_json::to_vec(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(fs::write(&temp, json)?))
+            .and_then(|_| Ok(fs::rename(&temp, path)?));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write the turn journal: {}", e);
+        }
+        self.last_save = Some(Instant::now());
+    }
+}
+
+/// The turn has stopped, but its journal is kept until the messages are saved
+impl Drop for TurnJournal {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            OPEN_JOURNALS.lock().unwrap().remove(path);
+        }
+    }
+}
+
+/// The messages of the turn are saved in the session file, its journal isn't needed anymore
+pub fn commit(session_file: &Path) {
+    let path = journal_path(session_file);
+    if let Err(e) = fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("Failed to remove the turn journal: {}", e);
+        }
+    }
+}
+
+/// What [`recover`] did to a session whose last turn was cut short
+#[derive(Debug)]
+pub struct Recovery {
+    pub conversation: Conversation,
+    /// Tool calls closed with an error because they never finished
+    pub aborted_tool_calls: Vec<PendingToolCall>,
+    /// Text of the assistant message that was being streamed, which was lost
+    pub partial_text: String,
+}
+
+/// Reconcile a session whose last turn didn't finish, None when it finished normally or is still
+/// running in this process. The session file is rewritten with the reconciled conversation and
+/// the journal removed
+pub fn recover(session_file: &Path) -> Result<Option<Recovery>> {
+    if OPEN_JOURNALS
+        .lock()
+        .unwrap()
+        .contains(&journal_path(session_file))
+    {
+        return Ok(None);
+    }
+    let Some(mut journal) = TurnJournal::load(session_file).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read the turn journal: {}", e);
+        Some(TurnJournal::default())
+    }) else {
+        return Ok(None);
+    };
+
+    let messages = read_messages(session_file)?;
+    let (conversation, aborted) = close_unfinished_tool_calls(messages, &journal);
+    if !aborted.is_empty() {
+        let metadata = read_metadata(session_file)?;
+        save_messages_with_metadata(session_file, &metadata, &conversation)?;
+    }
+    fs::remove_file(journal_path(session_file))?;
+
+    Ok(Some(Recovery {
+        conversation,
+        aborted_tool_calls: aborted,
+        partial_text: std::mem::take(&mut journal.partial_text),
+    }))
+}
+
+/// Answer the tool requests that have no response with an error, the journal names the calls
+/// when it was written in time
+fn close_unfinished_tool_calls(
+    conversation: Conversation,
+    journal: &TurnJournal,
+) -> (Conversation, Vec<PendingToolCall>) {
+    let mut messages = conversation.messages().clone();
+    let answered: HashSet<String> = messages
+        .iter()
+        .flat_map(|message| &message.content)
+        .filter_map(|content| match content {
+            MessageContent::ToolResponse(response) => Some(response.id.clone()),
+            _ => None,
+        })
+        .collect();
+    let unanswered: Vec<PendingToolCall> = messages
+        .last()
+        .filter(|message| message.role == rmcp::model::Role::Assistant)
+        .map(|message| {
+            message
+                .content
+                .iter()
+                .filter_map(|content| match content {
+                    MessageContent::ToolRequest(request) if !answered.contains(&request.id) => {
+                        let name = journal
+                            .pending_tool_calls
+                            .iter()
+                            .find(|call| call.id == request.id)
+                            .map(|call| call.name.clone())
+                            .or_else(|| request.tool_call.as_ref().ok().map(|c| c.name.clone()))
+                            .unwrap_or_default();
+                        Some(PendingToolCall {
+                            id: request.id.clone(),
+                            name,
+                        })
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if !unanswered.is_empty() {
+        let mut response = Message::user();
+        for call in &unanswered {
+            response = response.with_tool_response(
+                call.id.clone(),
+                Err(ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    "goose stopped before this tool call finished, its effects are unknown"
+                        .to_string(),
+                    None,
+                )),
+            );
+        }
+        messages.push(response);
+    }
+    (Conversation::new_unvalidated(messages), unanswered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::tool::ToolCall;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn tool_request(id: &str) -> Message {
+        Message::assistant().with_tool_request(id, Ok(ToolCall::new("developer__shell", json!({}))))
+    }
+
+    #[test]
+    fn test_journal_kept_until_committed() {
+        let dir = tempdir().unwrap();
+        let session_file = dir.path().join("session.jsonl");
+        let mut journal = TurnJournal::begin(&session_file);
+        journal.record(&tool_request("1"));
+
+        let saved = TurnJournal::load(&session_file).unwrap().unwrap();
+        assert_eq!(saved.pending_tool_calls[0].id, "1");
+        assert!(recover(&session_file).unwrap().is_none());
+
+        journal.record(&Message::user().with_tool_response("1", Ok(vec![])));
+        assert!(journal.pending_tool_calls.is_empty());
+        drop(journal);
+        assert!(journal_path(&session_file).exists());
+
+        commit(&session_file);
+        assert!(!journal_path(&session_file).exists());
+    }
+
+    #[test]
+    fn test_partial_text() {
+        let mut journal = TurnJournal::default();
+        let mut chunk = Message::assistant().with_text("Hello");
+        chunk.id = Some("a".to_string());
+        journal.record(&chunk);
+        let mut chunk = Message::assistant().with_text(" world");
+        chunk.id = Some("a".to_string());
+        journal.record(&chunk);
+        assert_eq!(journal.partial_text, "Hello world");
+
+        journal.record(&Message::assistant().with_text("Next"));
+        assert_eq!(journal.partial_text, "Next");
+    }
+
+    #[test]
+    fn test_close_unfinished_tool_calls() {
+        let conversation = Conversation::new_unvalidated(vec![
+            Message::user().with_text("List the files"),
+            tool_request("1")
+                .with_tool_request("2", Ok(ToolCall::new("developer__text_editor", json!({})))),
+        ]);
+        let (conversation, aborted) =
+            close_unfinished_tool_calls(conversation, &TurnJournal::default());
+
+        assert_eq!(
+            aborted.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(),
+            ["1", "2"]
+        );
+        let last = conversation.messages().last().unwrap();
+        assert_eq!(last.role, rmcp::model::Role::User);
+        assert_eq!(last.content.len(), 2);
+
+        let (_, aborted) = close_unfinished_tool_calls(conversation, &TurnJournal::default());
+        assert!(aborted.is_empty());
+    }
+}
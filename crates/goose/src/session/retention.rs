@@ -0,0 +1,266 @@
+//! Removing old sessions so the session directory doesn't grow without bound, either on demand
+//! with `goose session prune` or on startup following the retention policy in config.
+use crate::config::Config;
+use crate::session::journal::journal_path;
+use crate::session::storage::{ensure_session_dir, read_metadata};
+use crate::session::usage::usage_path;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const MAX_SESSIONS_KEY: &str = "GOOSE_SESSION_RETENTION_MAX_SESSIONS";
+pub const MAX_SIZE_MB_KEY: &str = "GOOSE_SESSION_RETENTION_MAX_SIZE_MB";
+pub const KEEP_TAGGED_KEY: &str = "GOOSE_SESSION_RETENTION_KEEP_TAGGED";
+
+/// Which sessions to remove. Sessions are considered newest first, a session is removed once it
+/// is older than `older_than` or the newer ones already reach `max_sessions` or `max_total_bytes`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionPolicy {
+    pub older_than: Option<Duration>,
+    pub max_sessions: Option<usize>,
+    pub max_total_bytes: Option<u64>,
+    /// Never remove sessions that have tags
+    pub keep_tagged: bool,
+}
+
+impl RetentionPolicy {
+    /// The policy set in config, None when no limit is configured
+    pub fn from_config() -> Option<Self> {
+        let config = Config::global();
+        let policy = Self {
+            older_than: None,
+            max_sessions: config.get_param(MAX_SESSIONS_KEY).ok(),
+            max_total_bytes: config
+                .get_param::<u64>(MAX_SIZE_MB_KEY)
+                .ok()
+                .map(|mb| mb * 1024 * 1024),
+            keep_tagged: config.get_param(KEEP_TAGGED_KEY).unwrap_or(false),
+        };
+        policy.has_limit().then_some(policy)
+    }
+
+    fn has_limit(&self) -> bool {
+        self.older_than.is_some() || self.max_sessions.is_some() || self.max_total_bytes.is_some()
+    }
+}
+
+/// A session file as seen by the retention policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionFile {
+    pub id: String,
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    /// Size of the session file and its attachments
+    pub size: u64,
+    pub tagged: bool,
+}
+
+/// The session files in `dir`, newest first
+pub fn scan_sessions(dir: &Path) -> Result<Vec<SessionFile>> {
+    let mut sessions = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "jsonl") {
+            continue;
+        }
+        let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let file_metadata = fs::metadata(&path)?;
+        let tagged = read_metadata(&path)
+            .map(|metadata| !metadata.tags.is_empty())
+            .unwrap_or(false);
+        sessions.push(SessionFile {
+            size: file_metadata.len() + dir_size(&attachments_path(dir, &id)),
+            modified: file_metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            id,
+            path,
+            tagged,
+        });
+    }
+    sessions.sort_by(|a, b| b.modified.cmp(&a.modified));
+    Ok(sessions)
+}
+
+/// The sessions the policy removes, given sessions sorted newest first. The `keep` session is
+/// never removed, nor are tagged sessions when the policy keeps them, but both still count
+/// towards the limits
+pub fn select_for_pruning(
+    sessions: &[SessionFile],
+    policy: &RetentionPolicy,
+    keep: Option<&Path>,
+    now: SystemTime,
+) -> Vec<SessionFile> {
+    let mut kept_count = 0;
+    let mut kept_bytes = 0;
+    let mut removed = Vec::new();
+    for session in sessions {
+        let protected =
+            keep.is_some_and(|keep| keep == session.path) || (policy.keep_tagged && session.tagged);
+        let too_old = policy.older_than.is_some_and(|older_than| {
+            now.duration_since(session.modified)
+                .is_ok_and(|age| age > older_than)
+        });
+        let over_count = policy.max_sessions.is_some_and(|max| kept_count >= max);
+        let over_size = policy
+            .max_total_bytes
+            .is_some_and(|max| kept_bytes + session.size > max);
+
+        if !protected && (too_old || over_count || over_size) {
+            removed.push(session.clone());
+        } else {
+            kept_count += 1;
+            kept_bytes += session.size;
+        }
+    }
+    removed
+}
+
+/// Delete the sessions along with their attachments
+pub fn remove_sessions(sessions: &[SessionFile]) -> Result<()> {
+    for session in sessions {
+        fs::remove_file(&session.path)?;
+        let _ = fs::remove_file(journal_path(&session.path));
+        let _ = fs::remove_file(usage_path(&session.path));
+        if let Some(dir) = session.path.parent() {
+            let attachments = attachments_path(dir, &session.id);
+            if attachments.exists() {
+                fs::remove_dir_all(attachments)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply the retention policy from config to the session directory, sparing the `keep` session.
+/// Returns the removed sessions
+pub fn apply_configured_policy(keep: Option<&Path>) -> Result<Vec<SessionFile>> {
+    let Some(policy) = RetentionPolicy::from_config() else {
+        return Ok(Vec::new());
+    };
+    let sessions = scan_sessions(&ensure_session_dir()?)?;
+    let removed = select_for_pruning(&sessions, &policy, keep, SystemTime::now());
+    remove_sessions(&removed)?;
+    if !removed.is_empty() {
+        tracing::info!(
+            "Removed {} sessions following the retention policy",
+            removed.len()
+        );
+    }
+    Ok(removed)
+}
+
+fn attachments_path(session_dir: &Path, id: &str) -> PathBuf {
+    session_dir.join("attachments").join(id)
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn session(id: &str, age_days: u32, size: u64, tagged: bool, now: SystemTime) -> SessionFile {
+        SessionFile {
+            id: id.to_string(),
+            path: PathBuf::from(format!("/sessions/{}.jsonl", id)),
+            modified: now - DAY * age_days,
+            size,
+            tagged,
+        }
+    }
+
+    fn ids(sessions: &[SessionFile]) -> Vec<&str> {
+        sessions.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_select_by_age_and_tags() {
+        let now = SystemTime::now();
+        let sessions = vec![
+            session("new", 1, 10, false, now),
+            session("old_tagged", 100, 10, true, now),
+            session("old", 120, 10, false, now),
+        ];
+        let mut policy = RetentionPolicy {
+            older_than: Some(DAY * 90),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&select_for_pruning(&sessions, &policy, None, now)),
+            ["old_tagged", "old"]
+        );
+
+        policy.keep_tagged = true;
+        assert_eq!(
+            ids(&select_for_pruning(&sessions, &policy, None, now)),
+            ["old"]
+        );
+        let keep = PathBuf::from("/sessions/old.jsonl");
+        assert!(select_for_pruning(&sessions, &policy, Some(&keep), now).is_empty());
+    }
+
+    #[test]
+    fn test_select_by_count_and_size() {
+        let now = SystemTime::now();
+        let sessions = vec![
+            session("a", 1, 40, false, now),
+            session("b", 2, 40, true, now),
+            session("c", 3, 40, false, now),
+            session("d", 4, 10, false, now),
+        ];
+        let policy = RetentionPolicy {
+            max_sessions: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&select_for_pruning(&sessions, &policy, None, now)),
+            ["c", "d"]
+        );
+
+        let policy = RetentionPolicy {
+            max_total_bytes: Some(100),
+            keep_tagged: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&select_for_pruning(&sessions, &policy, None, now)),
+            ["c"]
+        );
+    }
+
+    #[test]
+    fn test_scan_and_remove() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("one.jsonl"), "{}\n")?;
+        fs::write(dir.path().join("notes.txt"), "not a session")?;
+        let attachments = attachments_path(dir.path(), "one");
+        fs::create_dir_all(&attachments)?;
+        fs::write(attachments.join("image.png"), [0u8; 16])?;
+
+        let sessions = scan_sessions(dir.path())?;
+        assert_eq!(ids(&sessions), ["one"]);
+        assert_eq!(sessions[0].size, 3 + 16);
+
+        remove_sessions(&sessions)?;
+        assert!(!dir.path().join("one.jsonl").exists());
+        assert!(!attachments.exists());
+        Ok(())
+    }
+}
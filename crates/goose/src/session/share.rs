@@ -0,0 +1,188 @@
+//! A sanitized bundle of a session for attaching to bug reports: the transcript together with
+//! the configuration, extensions and models it ran with, with secrets redacted and optionally
+//! the user's paths anonymized.
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::storage::{read_messages, read_metadata, SessionMetadata};
+use crate::config::{Config, ExtensionConfigManager};
+use crate::conversation::message::Message;
+use crate::redaction::Redactor;
+
+const WORKING_DIR_PLACEHOLDER: &str = "<working_dir>";
+const HOME_PLACEHOLDER: &str = "~";
+
+#[derive(Debug, Clone, Default)]
+pub struct ShareOptions {
+    /// Replace the session's working directory and the home directory in the bundle
+    pub anonymize_paths: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtensionSummary {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub enabled: bool,
+}
+
+/// A provider and model that answered in the session, from the messages' generation metadata
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ModelUsage {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub messages: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionBundle {
+    pub goose_version: String,
+    pub created_at: String,
+    pub session_id: String,
+    /// The configured provider and model, which may differ from the ones the session used
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub models_used: Vec<ModelUsage>,
+    pub extensions: Vec<ExtensionSummary>,
+    pub config: BTreeMap<String, Value>,
+    pub metadata: SessionMetadata,
+    pub messages: Vec<Message>,
+}
+
+impl SessionBundle {
+    /// Collect a session and the setup it ran with. Nothing is redacted yet
+    pub fn collect(session_id: &str, session_file: &Path) -> Result<Self> {
+        let config = Config::global();
+        let metadata = read_metadata(session_file)?;
+        let messages = read_messages(session_file)?.messages().clone();
+
+        let extensions = ExtensionConfigManager::get_all()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| ExtensionSummary {
+                name: entry.config.name(),
+                kind: serde_json::to_value(&entry.config)
+                    .ok()
+                    .and_then(|value| value.get("type")?.as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                enabled: entry.enabled,
+            })
+            .collect();
+
+        Ok(Self {
+            goose_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            session_id: session_id.to_string(),
+            provider: config.get_param("GOOSE_PROVIDER").ok(),
+            model: config.get_param("GOOSE_MODEL").ok(),
+            models_used: models_used(&messages),
+            extensions,
+            config: config
+                .effective_values()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(key, effective)| (key, effective.value))
+                .collect(),
+            metadata,
+            messages,
+        })
+    }
+
+    /// The bundle as pretty JSON with secrets redacted and, if asked, paths anonymized
+    pub fn to_sanitized_json(&self, redactor: &Redactor, options: &ShareOptions) -> Result<String> {
+        let mut json = redactor.redact(&serde_json::to_string_pretty(self)?);
+        if options.anonymize_paths {
+            let mut replacements =
+                vec![(self.metadata.working_dir.clone(), WORKING_DIR_PLACEHOLDER)];
+            if let Some(home) = dirs::home_dir() {
+                replacements.push((home, HOME_PLACEHOLDER));
+            }
+            json = anonymize_paths(&json, &replacements);
+        }
+        Ok(json)
+    }
+}
+
+fn models_used(messages: &[Message]) -> Vec<ModelUsage> {
+    let mut counts: BTreeMap<(Option<String>, Option<String>), usize> = BTreeMap::new();
+    for metadata in messages
+        .iter()
+        .filter_map(|message| message.metadata.as_ref())
+    {
+        *counts
+            .entry((metadata.provider.clone(), metadata.model.clone()))
+            .or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|((provider, model), messages)| ModelUsage {
+            provider,
+            model,
+            messages,
+        })
+        .collect()
+}
+
+/// Replace paths in serialized JSON, in the order given so a directory inside another is
+/// replaced first. Paths are matched as JSON escapes them, so Windows paths match too
+fn anonymize_paths(json: &str, replacements: &[(PathBuf, &str)]) -> String {
+    let mut json = json.to_string();
+    for (path, placeholder) in replacements {
+        let Ok(escaped) = serde_json::to_string(&path.to_string_lossy()) else {
+            continue;
+        };
+        let escaped = escaped.trim_matches('"');
+        if escaped.len() > 1 {
+            json = json.replace(escaped, placeholder);
+        }
+    }
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::message::GenerationMetadata;
+
+    #[test]
+    fn test_anonymize_paths() {
+        let json = r#"{"dir": "/home/ada/project/src", "other": "/home/ada/.config"}"#;
+        let anonymized = anonymize_paths(
+            json,
+            &[
+                (PathBuf::from("/home/ada/project"), WORKING_DIR_PLACEHOLDER),
+                (PathBuf::from("/home/ada"), HOME_PLACEHOLDER),
+            ],
+        );
+        assert_eq!(
+            anonymized,
+            r#"{"dir": "<working_dir>/src", "other": "~/.config"}"#
+        );
+    }
+
+    #[test]
+    fn test_models_used() {
+        let answer = |model: &str| {
+            Message::assistant()
+                .with_text("hi")
+                .with_metadata(GenerationMetadata {
+                    provider: Some("openai".to_string()),
+                    model: Some(model.to_string()),
+                    ..Default::default()
+                })
+        };
+        let messages = vec![
+            Message::user().with_text("hello"),
+            answer("gpt-4o"),
+            answer("gpt-4o-mini"),
+            answer("gpt-4o"),
+        ];
+        let used = models_used(&messages);
+        assert_eq!(used.len(), 2);
+        assert_eq!(used[0].model.as_deref(), Some("gpt-4o"));
+        assert_eq!(used[0].messages, 2);
+    }
+}
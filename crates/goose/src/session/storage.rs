@@ -5,9 +5,14 @@
 // - Backup creation
 // Additional debug logging can be added if needed for troubleshooting.
 
-use crate::conversation::message::Message;
+use crate::agents::coverage_tool::CoverageSnapshot;
+use crate::agents::plan_tools::Plan;
+use crate::agents::todo_tools::TodoItem;
+use crate::conversation::message::{Message, MessageContent};
 use crate::conversation::Conversation;
 use crate::providers::base::Provider;
+use crate::redaction::Redactor;
+use crate::session::attachment::Attachment;
 use crate::utils::safe_truncate;
 use anyhow::Result;
 use chrono::Local;
@@ -45,13 +50,20 @@ pub struct SessionMetadata {
     /// Working directory for the session
     #[schema(value_type = String, example = "/home/user/sessions/session1")]
     pub working_dir: PathBuf,
-    /// A short description of the session, typically 3 words or less
+    /// A short title for the session, typically 4 words or less, generated by the provider
     pub description: String,
+    /// Topical tags generated by the provider, used to filter sessions
+    pub tags: Vec<String>,
     /// ID of the schedule that triggered this session, if any
     pub schedule_id: Option<String>,
 
     /// Number of messages in the session
     pub message_count: usize,
+    /// Number of messages the user typed, not counting tool results
+    pub turn_count: usize,
+    /// Provider and model that answered most recently
+    pub provider: Option<String>,
+    pub model: Option<String>,
     /// The total number of tokens used in the session. Retrieved from the provider's last usage.
     pub total_tokens: Option<i32>,
     /// The number of input tokens used in the session. Retrieved from the provider's last usage.
@@ -64,8 +76,38 @@ pub struct SessionMetadata {
     pub accumulated_input_tokens: Option<i32>,
     /// The number of output tokens used in the session. Accumulated across all messages.
     pub accumulated_output_tokens: Option<i32>,
-    /// Session-scoped TODO list content
+    /// Freeform TODO list content written by older versions, converted to `todos` when the
+    /// agent picks up the session
     pub todo_content: Option<String>,
+    /// Session-scoped TODO list
+    pub todos: Vec<TodoItem>,
+    /// ID of the session this one was forked from, if any
+    pub parent_session_id: Option<String>,
+    /// Number of messages copied from the parent session when this session was forked
+    pub forked_at: Option<usize>,
+    /// Plan the agent is working through in this session, if any
+    pub plan: Option<Plan>,
+    /// Summary of the conversation made when it no longer fit the context, if any
+    pub checkpoint: Option<SummaryCheckpoint>,
+    /// Link to the scheduler's page for the run that started this session, such as the
+    /// workflow run in the Temporal UI
+    pub schedule_run_url: Option<String>,
+    /// Line coverage measured by the last coverage report of the session, compared with the
+    /// next one
+    pub coverage: Option<CoverageSnapshot>,
+    /// Files and pages the user attached, readable by the agent as resources
+    pub attachments: Vec<Attachment>,
+}
+
+/// A rolling summary of a session's conversation, made when a resumed session no longer fits the
+/// context and stored so later resumes start from it instead of summarizing the history again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SummaryCheckpoint {
+    pub summary: String,
+    /// Messages folded into the summary, including those of earlier checkpoints
+    pub summarized_messages: usize,
+    /// Unix timestamp of when the summary was made
+    pub created_at: i64,
 }
 
 // Custom deserializer to handle old sessions without working_dir and todo_content
@@ -78,6 +120,10 @@ impl<'de> Deserialize<'de> for SessionMetadata {
         struct Helper {
             description: String,
             message_count: usize,
+            #[serde(default)]
+            turn_count: usize,
+            provider: Option<String>,
+            model: Option<String>,
             schedule_id: Option<String>, // For backward compatibility
             total_tokens: Option<i32>,
             input_tokens: Option<i32>,
@@ -87,6 +133,18 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             accumulated_output_tokens: Option<i32>,
             working_dir: Option<PathBuf>,
             todo_content: Option<String>, // For backward compatibility
+            #[serde(default)]
+            todos: Vec<TodoItem>,
+            parent_session_id: Option<String>,
+            forked_at: Option<usize>,
+            #[serde(default)]
+            tags: Vec<String>,
+            plan: Option<Plan>,
+            checkpoint: Option<SummaryCheckpoint>,
+            schedule_run_url: Option<String>,
+            coverage: Option<CoverageSnapshot>,
+            #[serde(default)]
+            attachments: Vec<Attachment>,
         }
 
         let helper = Helper::deserialize(deserializer)?;
@@ -100,6 +158,9 @@ impl<'de> Deserialize<'de> for SessionMetadata {
         Ok(SessionMetadata {
             description: helper.description,
             message_count: helper.message_count,
+            turn_count: helper.turn_count,
+            provider: helper.provider,
+            model: helper.model,
             schedule_id: helper.schedule_id,
             total_tokens: helper.total_tokens,
             input_tokens: helper.input_tokens,
@@ -109,6 +170,15 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             accumulated_output_tokens: helper.accumulated_output_tokens,
             working_dir,
             todo_content: helper.todo_content,
+            todos: helper.todos,
+            parent_session_id: helper.parent_session_id,
+            forked_at: helper.forked_at,
+            tags: helper.tags,
+            plan: helper.plan,
+            checkpoint: helper.checkpoint,
+            schedule_run_url: helper.schedule_run_url,
+            coverage: helper.coverage,
+            attachments: helper.attachments,
         })
     }
 }
@@ -127,6 +197,9 @@ impl SessionMetadata {
             description: String::new(),
             schedule_id: None,
             message_count: 0,
+            turn_count: 0,
+            provider: None,
+            model: None,
             total_tokens: None,
             input_tokens: None,
             output_tokens: None,
@@ -134,6 +207,15 @@ impl SessionMetadata {
             accumulated_input_tokens: None,
             accumulated_output_tokens: None,
             todo_content: None,
+            todos: Vec::new(),
+            parent_session_id: None,
+            forked_at: None,
+            tags: Vec::new(),
+            plan: None,
+            checkpoint: None,
+            schedule_run_url: None,
+            coverage: None,
+            attachments: Vec::new(),
         }
     }
 }
@@ -687,7 +769,6 @@ fn parse_message_with_truncation(
 
 /// Truncate content within a message in place
 fn truncate_message_content_in_place(message: &mut Message, max_content_size: usize) {
-    use crate::conversation::message::MessageContent;
     use rmcp::model::{RawContent, ResourceContents};
 
     for content in &mut message.content {
@@ -1060,6 +1141,32 @@ pub async fn persist_messages(
     persist_messages_with_schedule_id(session_file, messages, provider, None, working_dir).await
 }
 
+/// Number of user messages after which tags are generated for a session
+const TAGGING_USER_MESSAGES: usize = 3;
+
+/// Count the messages with text written by the user
+fn count_user_messages(messages: &Conversation) -> usize {
+    messages
+        .iter()
+        .filter(|m| m.role == rmcp::model::Role::User && !m.as_concat_text().trim().is_empty())
+        .count()
+}
+
+/// Tags need a bit of conversation to be meaningful, so they are generated once the user has sent
+/// a few messages. Until tags are stored, every save with a provider tries again
+async fn generate_missing_tags(
+    metadata: &mut SessionMetadata,
+    messages: &Conversation,
+    provider: &Arc<dyn Provider>,
+) {
+    if metadata.tags.is_empty() && count_user_messages(messages) >= TAGGING_USER_MESSAGES {
+        match provider.generate_session_tags(messages).await {
+            Ok(tags) => metadata.tags = tags,
+            Err(e) => tracing::warn!("Failed to generate session tags: {}", e),
+        }
+    }
+}
+
 /// Write messages to a session file with metadata, including an optional scheduled job ID
 ///
 /// Overwrites the file with metadata as the first line, followed by all messages in JSONL format.
@@ -1085,11 +1192,7 @@ pub async fn persist_messages_with_schedule_id(
         return Err(anyhow::anyhow!("Too many messages"));
     }
 
-    // Count user messages
-    let user_message_count = messages
-        .iter()
-        .filter(|m| m.role == rmcp::model::Role::User && !m.as_concat_text().trim().is_empty())
-        .count();
+    let user_message_count = count_user_messages(messages);
 
     // Check if we need to update the description (after 1st or 3rd user message)
     match provider {
@@ -1124,6 +1227,10 @@ pub async fn persist_messages_with_schedule_id(
                 metadata.schedule_id = schedule_id;
             }
 
+            if let Some(provider) = &provider {
+                generate_missing_tags(&mut metadata, messages, provider).await;
+            }
+
             // Write the file with metadata and messages
             save_messages_with_metadata(&secure_path, &metadata, messages)
         }
@@ -1206,16 +1313,21 @@ pub fn save_messages_with_metadata(
     {
         let mut writer = io::BufWriter::new(&file);
 
-        // Write metadata as the first line
+        // Write metadata as the first line, with the turn count kept in step with the messages
+        let metadata = SessionMetadata {
+            turn_count: count_user_messages(messages),
+            ..metadata.clone()
+        };
         serde_json::to_writer(&mut writer, &metadata).map_err(|e| {
             tracing::error!("Failed to serialize metadata: {}", e);
             anyhow::anyhow!("Failed to write session metadata")
         })?;
         writeln!(writer)?;
 
-        // Write all messages with progress tracking
+        // Write all messages with progress tracking, with configured secrets masked
+        let redactor = Redactor::model_bound();
         for (i, message) in messages.iter().enumerate() {
-            serde_json::to_writer(&mut writer, &message).map_err(|e| {
+            serde_json::to_writer(&mut writer, &redactor.redact_message(message)).map_err(|e| {
                 tracing::error!("Failed to serialize message {}: {}", i, e);
                 anyhow::anyhow!("Failed to write session message")
             })?;
@@ -1314,6 +1426,8 @@ pub async fn generate_description_with_schedule_id(
         SessionMetadata::new(work_dir)
     };
 
+    generate_missing_tags(&mut metadata, messages, &provider).await;
+
     // Update description and schedule_id
     metadata.description = sanitized_description;
     if schedule_id.is_some() {
@@ -1345,10 +1459,96 @@ pub async fn update_metadata(session_file: &Path, metadata: &SessionMetadata) ->
     save_messages_with_metadata(&secure_path, metadata, &messages)
 }
 
+/// Fork a session into a new one that starts with the same history
+///
+/// The new session gets the first `at` messages of the source (all of them when `at` is None),
+/// minus a trailing tool request whose response would be cut off, and records the source
+/// session as its parent. Returns the new session's ID and path.
+pub fn fork_session(source_file: &Path, at: Option<usize>) -> Result<(String, PathBuf)> {
+    let source_path = get_path(Identifier::Path(source_file.to_path_buf()))?;
+    if !source_path.exists() {
+        return Err(anyhow::anyhow!(
+            "Session file not found: {}",
+            source_path.display()
+        ));
+    }
+
+    let mut metadata = read_metadata(&source_path)?;
+    let mut messages = read_messages(&source_path)?;
+    if let Some(at) = at {
+        if at > messages.len() {
+            return Err(anyhow::anyhow!(
+                "Cannot fork at message {}, the session only has {} messages",
+                at,
+                messages.len()
+            ));
+        }
+        messages.truncate(at);
+
+        // A tool request whose response was cut off would leave the fork unusable
+        if messages.last().is_some_and(|message| {
+            message
+                .content
+                .iter()
+                .any(|c| matches!(c, MessageContent::ToolRequest(_)))
+        }) {
+            messages.pop();
+        }
+    }
+
+    let parent_id = source_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_string());
+
+    // Forks live next to their parent, with a suffix when another session has the same ID
+    let base_id = generate_session_id();
+    let (forked_id, forked_path) = (0..)
+        .map(|i| {
+            let id = if i == 0 {
+                base_id.clone()
+            } else {
+                format!("{}_{}", base_id, i)
+            };
+            let path = source_path.with_file_name(format!("{}.jsonl", id));
+            (id, path)
+        })
+        .find(|(_, path)| !path.exists())
+        .unwrap();
+
+    metadata.parent_session_id = parent_id;
+    metadata.forked_at = Some(messages.len());
+    metadata.message_count = messages.len();
+    // The tokens spent so far were spent by the parent, the fork counts its own
+    metadata.accumulated_total_tokens = None;
+    metadata.accumulated_input_tokens = None;
+    metadata.accumulated_output_tokens = None;
+    save_messages_with_metadata(&forked_path, &metadata, &messages)?;
+
+    Ok((forked_id, forked_path))
+}
+
+/// Remove the last user exchange from a session file, see [`Conversation::undo_last_exchange`]
+///
+/// Returns the remaining conversation and the removed messages. The file is left untouched
+/// when there is nothing to undo.
+pub fn undo_last_exchange(session_file: &Path) -> Result<(Conversation, Vec<Message>)> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+
+    let metadata = read_metadata(&secure_path)?;
+    let mut messages = read_messages(&secure_path)?;
+    let removed = messages.undo_last_exchange();
+    if !removed.is_empty() {
+        save_messages_with_metadata(&secure_path, &metadata, &messages)?;
+    }
+    Ok((messages, removed))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::conversation::message::{Message, MessageContent};
+    use crate::providers::errors::ProviderError;
     use tempfile::tempdir;
 
     #[test]
@@ -1460,6 +1660,144 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_undo_last_exchange() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("undo.jsonl");
+
+        let messages = Conversation::new_unvalidated(vec![
+            Message::user().with_text("Hello"),
+            Message::assistant().with_text("Hi there"),
+            Message::user().with_text("Oops"),
+            Message::assistant().with_text("Sure"),
+        ]);
+        persist_messages(&file_path, &messages, None, None).await?;
+        assert_eq!(read_metadata(&file_path)?.turn_count, 2);
+
+        let (remaining, removed) = undo_last_exchange(&file_path)?;
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(removed.len(), 2);
+        assert_eq!(read_messages(&file_path)?.len(), 2);
+        assert_eq!(read_metadata(&file_path)?.turn_count, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fork_session() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("source.jsonl");
+
+        let messages = Conversation::new_unvalidated(vec![
+            Message::user().with_text("Hello"),
+            Message::assistant().with_text("Hi there"),
+            Message::user().with_text("Try one approach"),
+            Message::assistant().with_text("Done"),
+        ]);
+        persist_messages(&file_path, &messages, None, None).await?;
+        update_metadata(
+            &file_path,
+            &SessionMetadata {
+                accumulated_total_tokens: Some(1200),
+                accumulated_input_tokens: Some(1000),
+                accumulated_output_tokens: Some(200),
+                ..read_metadata(&file_path)?
+            },
+        )
+        .await?;
+
+        let (forked_id, forked_path) = fork_session(&file_path, Some(2))?;
+        let forked_metadata = read_metadata(&forked_path)?;
+        assert_eq!(forked_metadata.parent_session_id.as_deref(), Some("source"));
+        assert_eq!(forked_metadata.forked_at, Some(2));
+        assert_eq!(forked_metadata.accumulated_total_tokens, None);
+        assert_eq!(forked_metadata.accumulated_input_tokens, None);
+        assert_eq!(forked_metadata.accumulated_output_tokens, None);
+        assert_eq!(
+            read_metadata(&file_path)?.accumulated_total_tokens,
+            Some(1200)
+        );
+        assert_eq!(read_messages(&forked_path)?.len(), 2);
+        assert_eq!(forked_path, dir.path().join(format!("{}.jsonl", forked_id)));
+
+        // The source session is untouched and a second fork gets its own file
+        assert_eq!(read_messages(&file_path)?.len(), 4);
+        let (_, second_path) = fork_session(&file_path, None)?;
+        assert_ne!(second_path, forked_path);
+        assert_eq!(read_messages(&second_path)?.len(), 4);
+        assert!(fork_session(&file_path, Some(10)).is_err());
+        Ok(())
+    }
+
+    /// Names sessions and fails to tag them the first time it is asked
+    struct FlakyTagProvider {
+        tag_requests: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for FlakyTagProvider {
+        fn metadata() -> crate::providers::base::ProviderMetadata {
+            crate::providers::base::ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> crate::model::ModelConfig {
+            crate::model::ModelConfig::new("test-model").unwrap()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &crate::model::ModelConfig,
+            system: &str,
+            _messages: &[Message],
+            _tools: &[rmcp::model::Tool],
+        ) -> Result<(Message, crate::providers::base::ProviderUsage), ProviderError> {
+            let text = if system.contains("tags") {
+                let requests = self
+                    .tag_requests
+                    .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                if requests == 0 {
+                    return Err(ProviderError::RequestFailed("unavailable".to_string()));
+                }
+                "rust, cli"
+            } else {
+                "Fixing the build"
+            };
+            Ok((
+                Message::assistant().with_text(text),
+                crate::providers::base::ProviderUsage::new(
+                    "test-model".to_string(),
+                    Default::default(),
+                ),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tags_retried_until_stored() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("tags.jsonl");
+        let provider: Arc<dyn Provider> = Arc::new(FlakyTagProvider {
+            tag_requests: Default::default(),
+        });
+
+        let mut messages = Conversation::new_unvalidated(vec![]);
+        for text in ["Hello", "The build fails", "It's the CLI crate"] {
+            messages.push(Message::user().with_text(text));
+            messages.push(Message::assistant().with_text("Ok"));
+        }
+        persist_messages(&file_path, &messages, Some(provider.clone()), None).await?;
+        assert!(read_metadata(&file_path)?.tags.is_empty());
+
+        // A save past the messages that name the session still generates the missing tags
+        messages.push(Message::user().with_text("Thanks"));
+        persist_messages(&file_path, &messages, Some(provider.clone()), None).await?;
+        messages.push(Message::user().with_text("One more thing"));
+        persist_messages(&file_path, &messages, Some(provider), None).await?;
+        let metadata = read_metadata(&file_path)?;
+        assert_eq!(metadata.tags, vec!["rust", "cli"]);
+        assert_eq!(metadata.description, "Fixing the build");
+        Ok(())
+    }
+
     #[test]
     fn test_empty_file() -> Result<()> {
         let dir = tempdir()?;
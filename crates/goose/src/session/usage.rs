@@ -0,0 +1,128 @@
+//! A ledger of the provider calls of a session, kept next to the session file.
+//!
+//! The session metadata only holds rolling totals. The ledger has one line per call, recording
+//! when it was made, which model served it, its tokens and how long it took, so reports can
+//! attribute usage to the right month and model even when a session spans several of either.
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::providers::base::ProviderUsage;
+
+/// The usage of one provider call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Unix timestamp of when the response was received
+    pub timestamp: i64,
+    pub provider: Option<String>,
+    pub model: String,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    /// Input tokens read from the provider's prompt cache, included in `input_tokens`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<i32>,
+    /// Input tokens written to the provider's prompt cache, included in `input_tokens`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<i32>,
+    pub latency_ms: u64,
+}
+
+impl UsageRecord {
+    pub fn new(provider: Option<String>, usage: &ProviderUsage, latency: Duration) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().timestamp(),
+            provider,
+            model: usage.model.clone(),
+            input_tokens: usage.usage.input_tokens,
+            output_tokens: usage.usage.output_tokens,
+            cache_read_tokens: usage.usage.cache_read_tokens,
+            cache_write_tokens: usage.usage.cache_write_tokens,
+            latency_ms: latency.as_millis() as u64,
+        }
+    }
+}
+
+/// Where the usage ledger of a session file is kept
+pub fn usage_path(session_file: &Path) -> PathBuf {
+    session_file.with_extension("usage")
+}
+
+/// Add a call to the ledger of the session
+pub fn append_usage(session_file: &Path, record: &UsageRecord) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(usage_path(session_file))?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// The calls recorded for the session, oldest first. Empty for sessions from before the ledger
+/// was kept; a line that can't be parsed, such as one cut short by a crash, is skipped
+pub fn read_usage(session_file: &Path) -> Result<Vec<UsageRecord>> {
+    let path = usage_path(session_file);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                tracing::warn!("Skipping an unreadable usage record: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_append_and_read_usage() {
+        let dir = tempdir().unwrap();
+        let session_file = dir.path().join("20250101_000000.jsonl");
+        assert!(read_usage(&session_file).unwrap().is_empty());
+
+        let usage = ProviderUsage::new(
+            "claude-sonnet-4".to_string(),
+            Usage::new(Some(120), Some(30), Some(150)).with_cache(Some(100), None),
+        );
+        let first = UsageRecord::new(
+            Some("anthropic".to_string()),
+            &usage,
+            Duration::from_millis(850),
+        );
+        append_usage(&session_file, &first).unwrap();
+        let worker = ProviderUsage::new(
+            "gpt-4o-mini".to_string(),
+            Usage::new(Some(10), Some(5), Some(15)),
+        );
+        let second = UsageRecord::new(
+            Some("openai".to_string()),
+            &worker,
+            Duration::from_millis(200),
+        );
+        append_usage(&session_file, &second).unwrap();
+
+        // A line cut short by a crash
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(usage_path(&session_file))
+            .unwrap();
+        write!(file, "{{\"timestamp\": 17").unwrap();
+
+        let records = read_usage(&session_file).unwrap();
+        assert_eq!(records, vec![first, second]);
+        assert_eq!(records[0].cache_read_tokens, Some(100));
+        assert_eq!(records[0].latency_ms, 850);
+    }
+}
@@ -0,0 +1,245 @@
+//! Coordinating a graceful shutdown on SIGINT or SIGTERM: frontends cancel the [`token`] so
+//! running turns stop and flush their sessions, then terminate the process groups of the
+//! extensions goose started, along with any background processes those left behind.
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// How long process groups get to exit after SIGTERM before they are killed
+pub const TERMINATE_GRACE: Duration = Duration::from_secs(3);
+
+/// How often a tracked process group is checked for having exited
+const PROCESS_GROUP_POLL: Duration = Duration::from_secs(2);
+
+static SHUTDOWN: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
+/// Tracked process groups, with the registration they belong to
+static PROCESS_GROUPS: Lazy<Mutex<HashMap<u32, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_REGISTRATION: AtomicU64 = AtomicU64::new(0);
+
+/// Cancelled once shutdown was requested
+pub fn token() -> CancellationToken {
+    SHUTDOWN.clone()
+}
+
+pub fn request() {
+    SHUTDOWN.cancel();
+}
+
+pub fn requested() -> bool {
+    SHUTDOWN.is_cancelled()
+}
+
+/// Track a child process started as the leader of its own process group
+pub fn register_process_group(pid: u32) {
+    register(pid);
+}
+
+fn register(pid: u32) -> u64 {
+    let registration = NEXT_REGISTRATION.fetch_add(1, Ordering::Relaxed);
+    PROCESS_GROUPS.lock().unwrap().insert(pid, registration);
+    registration
+}
+
+/// Stop tracking a process group, returns whether it was tracked
+pub fn unregister_process_group(pgid: u32) -> bool {
+    PROCESS_GROUPS.lock().unwrap().remove(&pgid).is_some()
+}
+
+/// Forget a process group only if it is still tracked for the given registration, and not
+/// for a later process that was given the same id
+fn unregister(pgid: u32, registration: u64) -> bool {
+    let mut groups = PROCESS_GROUPS.lock().unwrap();
+    if groups.get(&pgid) == Some(&registration) {
+        groups.remove(&pgid);
+        true
+    } else {
+        false
+    }
+}
+
+fn is_registered(pgid: u32, registration: u64) -> bool {
+    PROCESS_GROUPS.lock().unwrap().get(&pgid) == Some(&registration)
+}
+
+/// The process group of a running extension. It is forgotten once all of its processes
+/// exited, as the id can then be reused by an unrelated process, and terminated when this is
+/// dropped because the extension was removed or restarted
+pub struct ProcessGroup {
+    pgid: u32,
+    registration: u64,
+}
+
+impl ProcessGroup {
+    /// Track a child process started as the leader of its own process group
+    pub fn track(pid: u32) -> Self {
+        let registration = register(pid);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(PROCESS_GROUP_POLL).await;
+                if !is_registered(pid, registration) {
+                    return;
+                }
+                if !group_exists(pid) {
+                    unregister(pid, registration);
+                    return;
+                }
+            }
+        });
+        Self {
+            pgid: pid,
+            registration,
+        }
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if !unregister(self.pgid, self.registration) {
+            return;
+        }
+        // Ask right away, the runtime may be on its way out
+        signal_terminate(self.pgid);
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(terminate(vec![self.pgid], TERMINATE_GRACE));
+        }
+    }
+}
+
+/// Terminate the tracked process groups, killing those still running after `grace`
+pub async fn terminate_process_groups(grace: Duration) {
+    let groups: Vec<u32> = PROCESS_GROUPS
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(pgid, _)| pgid)
+        .collect();
+    if groups.is_empty() {
+        return;
+    }
+    tracing::info!("Terminating {} extension process groups", groups.len());
+    terminate(groups, grace).await;
+}
+
+#[cfg(unix)]
+fn group_exists(pgid: u32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::killpg;
+    use nix::unistd::Pid;
+
+    // A group we may not signal still exists
+    !matches!(killpg(Pid::from_raw(pgid as i32), None), Err(Errno::ESRCH))
+}
+
+#[cfg(unix)]
+fn signal_terminate(pgid: u32) {
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+
+    let _ = killpg(Pid::from_raw(pgid as i32), Signal::SIGTERM);
+}
+
+#[cfg(unix)]
+async fn terminate(groups: Vec<u32>, grace: Duration) {
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+
+    let alive = |pgid: &u32| killpg(Pid::from_raw(*pgid as i32), None).is_ok();
+    for pgid in &groups {
+        let _ = killpg(Pid::from_raw(*pgid as i32), Signal::SIGTERM);
+    }
+
+    let deadline = tokio::time::Instant::now() + grace;
+    while groups.iter().any(alive) && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    for pgid in groups.iter().filter(|pgid| alive(pgid)) {
+        tracing::warn!("Process group {} ignored SIGTERM, killing it", pgid);
+        let _ = killpg(Pid::from_raw(*pgid as i32), Signal::SIGKILL);
+    }
+}
+
+/// Child processes on Windows are stopped when their handles are dropped
+#[cfg(not(unix))]
+async fn terminate(_groups: Vec<u32>, _grace: Duration) {}
+
+#[cfg(not(unix))]
+fn group_exists(_pgid: u32) -> bool {
+    true
+}
+
+#[cfg(not(unix))]
+fn signal_terminate(_pgid: u32) {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    fn spawn_group(script: &str) -> u32 {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        std::thread::spawn(move || child.wait());
+        pid
+    }
+
+    fn is_tracked(pgid: u32) -> bool {
+        PROCESS_GROUPS.lock().unwrap().contains_key(&pgid)
+    }
+
+    #[tokio::test]
+    async fn test_exited_group_is_forgotten() {
+        let pgid = spawn_group("exit 0");
+        let group = ProcessGroup::track(pgid);
+        assert!(is_tracked(pgid));
+
+        tokio::time::timeout(PROCESS_GROUP_POLL * 5, async {
+            while is_tracked(pgid) {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+        // Dropping the handle of a group that is gone signals nothing
+        drop(group);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_group_is_terminated_and_forgotten() {
+        let pgid = spawn_group("sleep 60 & wait");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let group = ProcessGroup::track(pgid);
+
+        drop(group);
+        assert!(!is_tracked(pgid));
+        tokio::time::timeout(TERMINATE_GRACE * 2, async {
+            while group_exists(pgid) {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_later_registration_of_the_same_id_is_kept() {
+        let pgid = u32::MAX - 1;
+        let group = ProcessGroup {
+            pgid,
+            registration: register(pgid),
+        };
+        // The id was given to another process group in the meantime
+        let later = register(pgid);
+        drop(group);
+        assert!(is_registered(pgid, later));
+        assert!(unregister_process_group(pgid));
+    }
+}
@@ -1,3 +1,4 @@
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
 use std::time::Duration;
@@ -20,6 +21,11 @@ const TEMPORAL_SERVICE_HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(5
 // to avoid conflicts with common services
 const DEFAULT_HTTP_PORTS: &[u16] = &[58080, 58081, 58082, 58083, 58084, 58085];
 
+/// How often a scheduled run tells its Temporal activity it is still making progress, well
+/// within the activity's heartbeat timeout
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_NAMESPACE: &str = "default";
+
 #[derive(Serialize, Deserialize, Debug)]
 struct JobRequest {
     action: String,
@@ -27,6 +33,10 @@ struct JobRequest {
     cron: Option<String>,
     recipe_path: Option<String>,
     execution_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -48,6 +58,8 @@ struct TemporalJobStatus {
     paused: bool,
     created_at: String,
     execution_mode: Option<String>,
+    #[serde(default)]
+    timezone: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -55,6 +67,17 @@ struct RunNowResponse {
     session_id: String,
 }
 
+/// The workflow run a heartbeat was recorded for
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct HeartbeatResponse {
+    #[serde(default)]
+    namespace: Option<String>,
+    #[serde(default)]
+    workflow_id: Option<String>,
+    #[serde(default)]
+    run_id: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PortConfig {
     http_port: u16,
@@ -120,6 +143,43 @@ impl TemporalScheduler {
         Ok(final_scheduler)
     }
 
+    /// Connect to the Temporal service that is already running, without starting one. Used by
+    /// the runs the service starts to report back to it
+    pub async fn connect() -> Result<Arc<Self>, SchedulerError> {
+        let http_client = Client::new();
+        let env_port = std::env::var("PORT")
+            .ok()
+            .and_then(|port| port.parse().ok());
+        let mut http_port = None;
+        for port in env_port
+            .into_iter()
+            .chain(DEFAULT_HTTP_PORTS.iter().copied())
+        {
+            if Self::is_temporal_service_running(&http_client, port).await {
+                http_port = Some(port);
+                break;
+            }
+        }
+        let http_port = http_port.ok_or_else(|| {
+            SchedulerError::SchedulerInternalError("No Temporal service is running".to_string())
+        })?;
+
+        let mut scheduler = Self {
+            http_client,
+            service_url: format!("http://localhost:{}", http_port),
+            port_config: PortConfig {
+                http_port,
+                temporal_port: 7233,
+                ui_port: 8233,
+            },
+        };
+        match scheduler.fetch_port_config().await {
+            Ok(port_config) => scheduler.port_config = port_config,
+            Err(e) => warn!("Using the default Temporal ports: {}", e),
+        }
+        Ok(Arc::new(scheduler))
+    }
+
     async fn discover_http_port(http_client: &Client) -> Result<u16, SchedulerError> {
         info!("Discovering Temporal service port...");
 
@@ -562,6 +622,8 @@ impl TemporalScheduler {
             cron: Some(normalized_cron.clone()),
             recipe_path: Some(job.source.clone()),
             execution_mode: job.execution_mode.clone(),
+            timezone: job.timezone.clone(),
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -582,6 +644,8 @@ impl TemporalScheduler {
             cron: None,
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -601,6 +665,9 @@ impl TemporalScheduler {
                         current_session_id: None, // Not provided by Temporal service
                         process_start_time: None, // Not provided by Temporal service
                         execution_mode: tj.execution_mode,
+                        timezone: tj.timezone,
+                        notify: None,
+                        recipe_origin: None,
                     }
                 })
                 .collect();
@@ -617,6 +684,8 @@ impl TemporalScheduler {
             cron: None,
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -636,6 +705,8 @@ impl TemporalScheduler {
             cron: None,
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -655,6 +726,8 @@ impl TemporalScheduler {
             cron: None,
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -675,6 +748,8 @@ impl TemporalScheduler {
             cron: None,
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -699,6 +774,72 @@ impl TemporalScheduler {
         }
     }
 
+    /// Record a heartbeat on the activity running the job, so a long recipe run isn't mistaken
+    /// for a stuck one and retried. Returns the link to the workflow run in the Temporal UI when
+    /// the service reports which run it is
+    pub async fn heartbeat(
+        &self,
+        job_id: &str,
+        session_id: &str,
+    ) -> Result<Option<String>, SchedulerError> {
+        let request = JobRequest {
+            action: "heartbeat".to_string(),
+            job_id: Some(job_id.to_string()),
+            cron: None,
+            recipe_path: None,
+            execution_mode: None,
+            timezone: None,
+            session_id: Some(session_id.to_string()),
+        };
+
+        let response = self.make_request(request).await?;
+        if !response.success {
+            return Err(SchedulerError::SchedulerInternalError(response.message));
+        }
+        let run: HeartbeatResponse = response
+            .data
+            .and_then(|data| serde_json::from_value(data).ok())
+            .unwrap_or_default();
+        Ok(match (run.workflow_id, run.run_id) {
+            (Some(workflow_id), Some(run_id)) => Some(self.run_url(
+                run.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE),
+                &workflow_id,
+                &run_id,
+            )),
+            _ => None,
+        })
+    }
+
+    /// The page of a workflow run in the Temporal UI
+    pub fn run_url(&self, namespace: &str, workflow_id: &str, run_id: &str) -> String {
+        format!(
+            "http://localhost:{}/namespaces/{}/workflows/{}/{}/history",
+            self.port_config.ui_port, namespace, workflow_id, run_id
+        )
+    }
+
+    /// Send heartbeats for the run of the job in the session until the task is dropped or
+    /// aborted, and record the run's Temporal UI link in the session once it is known
+    pub async fn keep_run_alive(self: Arc<Self>, job_id: String, session_file: PathBuf) {
+        let session_id = session_file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let mut recorded = false;
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            interval.tick().await;
+            match self.heartbeat(&job_id, &session_id).await {
+                Ok(Some(url)) if !recorded => {
+                    recorded = record_run_url(&session_file, url).await;
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to send a heartbeat for job '{}': {}", job_id, e),
+            }
+        }
+    }
+
     // Note: This method fetches sessions from the session storage directly
     // since Temporal service doesn't track session metadata
     pub async fn sessions(
@@ -775,6 +916,8 @@ impl TemporalScheduler {
             cron: Some(normalized_cron),
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -799,6 +942,8 @@ impl TemporalScheduler {
             cron: None,
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -825,6 +970,8 @@ impl TemporalScheduler {
                     cron: None,
                     recipe_path: None,
                     execution_mode: None,
+                    timezone: None,
+                    session_id: None,
                 };
 
                 match self.make_request(request).await {
@@ -907,6 +1054,8 @@ impl TemporalScheduler {
                         cron: None,
                         recipe_path: None,
                         execution_mode: None,
+                        timezone: None,
+                        session_id: None,
                     };
 
                     if let Err(e) = self.make_request(request).await {
@@ -954,6 +1103,8 @@ impl TemporalScheduler {
             cron: None,
             recipe_path: None,
             execution_mode: None,
+            timezone: None,
+            session_id: None,
         };
 
         let response = self.make_request(request).await?;
@@ -1054,6 +1205,22 @@ impl TemporalScheduler {
     }
 }
 
+/// Store the link to the scheduler's page for the run in the session's metadata, true once
+/// it is stored
+async fn record_run_url(session_file: &Path, url: String) -> bool {
+    let result = match crate::session::storage::read_metadata(session_file) {
+        Ok(mut metadata) => {
+            metadata.schedule_run_url = Some(url);
+            crate::session::storage::update_metadata(session_file, &metadata).await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = &result {
+        warn!("Failed to record the run link in the session: {}", e);
+    }
+    result.is_ok()
+}
+
 impl Drop for TemporalScheduler {
     fn drop(&mut self) {
         // Services continue running independently - no cleanup needed
@@ -1317,6 +1484,33 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_run_url_from_heartbeat() {
+        let scheduler = TemporalScheduler {
+            http_client: Client::new(),
+            service_url: "http://localhost:58080".to_string(),
+            port_config: PortConfig {
+                http_port: 58080,
+                temporal_port: 7233,
+                ui_port: 8234,
+            },
+        };
+        let run: HeartbeatResponse = serde_json::from_value(serde_json::json!({
+            "workflow_id": "recipe-daily-report",
+            "run_id": "0f3c2a"
+        }))
+        .unwrap();
+        assert_eq!(run.namespace, None);
+        assert_eq!(
+            scheduler.run_url(
+                run.namespace.as_deref().unwrap_or(DEFAULT_NAMESPACE),
+                run.workflow_id.as_deref().unwrap(),
+                run.run_id.as_deref().unwrap()
+            ),
+            "http://localhost:8234/namespaces/default/workflows/recipe-daily-report/0f3c2a/history"
+        );
+    }
+
     #[test]
     fn test_port_check_functionality() {
         // Test the port checking functionality
@@ -1,34 +1,168 @@
 use ahash::AHasher;
+use anyhow::{anyhow, Result};
 use dashmap::DashMap;
+use once_cell::sync::Lazy;
 use rmcp::model::Tool;
 use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tiktoken_rs::CoreBPE;
-use tokio::sync::OnceCell;
 
+use crate::config::Config;
 use crate::conversation::message::Message;
+use crate::model::ModelConfig;
 
-// Global tokenizer instance to avoid repeated initialization
-static TOKENIZER: OnceCell<Arc<CoreBPE>> = OnceCell::const_new();
+/// Overrides the tokenizer picked from the model name: `o200k_base`, `cl100k_base`, `claude` or
+/// `hf:<huggingface repository>`
+pub const TOKENIZER_CONFIG_KEY: &str = "GOOSE_TOKENIZER";
+
+// Loaded tokenizers, shared by all counters
+static TOKENIZERS: Lazy<DashMap<TokenizerKind, Arc<Tokenizer>>> = Lazy::new(DashMap::new);
 
 // Cache size limits to prevent unbounded growth
 const MAX_TOKEN_CACHE_SIZE: usize = 10_000;
 
+// Claude's tokenizer is not public, it produces roughly this many tokens per cl100k_base token
+const CLAUDE_TOKEN_RATIO: f64 = 1.15;
+
+const TOKENIZER_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Substrings of model names, without dashes, underscores and spaces, and the tokenizer they use.
+// The first match wins. Open model families are matched with their version, since versions
+// change the tokenizer, and versions not listed are estimated with the default
+static MODEL_TOKENIZERS: Lazy<Vec<(&'static str, TokenizerKind)>> = Lazy::new(|| {
+    vec![
+        ("claude", TokenizerKind::Claude),
+        // Llama 3.1 to 3.3 keep the tokenizer of Llama 3
+        (
+            "llama3",
+            TokenizerKind::HuggingFace("NousResearch/Meta-Llama-3-8B".to_string()),
+        ),
+        (
+            "llama2",
+            TokenizerKind::HuggingFace("NousResearch/Llama-2-7b-hf".to_string()),
+        ),
+        (
+            "qwen3",
+            TokenizerKind::HuggingFace("Qwen/Qwen3-8B".to_string()),
+        ),
+        // Qwen 2 and 2.5 share a tokenizer
+        (
+            "qwen2",
+            TokenizerKind::HuggingFace("Qwen/Qwen2.5-Coder-32B-Instruct".to_string()),
+        ),
+        ("gpt4o", TokenizerKind::O200kBase),
+        ("gpt4.1", TokenizerKind::O200kBase),
+        ("gpt5", TokenizerKind::O200kBase),
+        ("gptoss", TokenizerKind::O200kBase),
+        ("gpt4", TokenizerKind::Cl100kBase),
+        ("gpt3.5", TokenizerKind::Cl100kBase),
+        ("o1", TokenizerKind::O200kBase),
+        ("o3", TokenizerKind::O200kBase),
+        ("o4", TokenizerKind::O200kBase),
+    ]
+});
+
+// HuggingFace repositories whose tokenizer is being downloaded
+static DOWNLOADS: Lazy<DashMap<String, ()>> = Lazy::new(DashMap::new);
+
+/// The tokenizer token counts are estimated with
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TokenizerKind {
+    /// tiktoken o200k_base, used by GPT-4o and later OpenAI models and the default for others
+    O200kBase,
+    /// tiktoken cl100k_base, used by GPT-4 and GPT-3.5
+    Cl100kBase,
+    /// cl100k_base scaled to the token counts of Claude models
+    Claude,
+    /// The tokenizer.json of a HuggingFace repository, downloaded in the background on first use.
+    /// Estimated with o200k_base until it is available
+    HuggingFace(String),
+}
+
+impl TokenizerKind {
+    /// The tokenizer for a model, unless one is set with GOOSE_TOKENIZER
+    pub fn for_model(model: &ModelConfig) -> Self {
+        Self::for_model_name(&model.model_name)
+    }
+
+    pub fn for_model_name(model_name: &str) -> Self {
+        if let Ok(name) = Config::global().get_param::<String>(TOKENIZER_CONFIG_KEY) {
+            match Self::parse(&name) {
+                Some(kind) => return kind,
+                None => tracing::warn!("Unknown {} {}", TOKENIZER_CONFIG_KEY, name),
+            }
+        }
+        Self::from_model_name(model_name)
+    }
+
+    fn from_model_name(model_name: &str) -> Self {
+        let model_name = model_name.to_lowercase().replace(['-', '_', ' '], "");
+        MODEL_TOKENIZERS
+            .iter()
+            .find(|(pattern, _)| model_name.contains(pattern))
+            .map(|(_, kind)| kind.clone())
+            .unwrap_or(TokenizerKind::O200kBase)
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim() {
+            "o200k_base" => Some(Self::O200kBase),
+            "cl100k_base" => Some(Self::Cl100kBase),
+            "claude" => Some(Self::Claude),
+            name => name
+                .strip_prefix("hf:")
+                .filter(|repo| repo.contains('/'))
+                .map(|repo| Self::HuggingFace(repo.to_string())),
+        }
+    }
+}
+
+/// A loaded tokenizer
+enum Tokenizer {
+    Tiktoken(CoreBPE),
+    /// Counts of the tiktoken encoding multiplied by the ratio
+    Scaled(CoreBPE, f64),
+    HuggingFace(Box<tokenizers::Tokenizer>),
+}
+
+impl Tokenizer {
+    fn count(&self, text: &str) -> usize {
+        match self {
+            Tokenizer::Tiktoken(bpe) => bpe.encode_with_special_tokens(text).len(),
+            Tokenizer::Scaled(bpe, ratio) => {
+                (bpe.encode_with_special_tokens(text).len() as f64 * ratio).ceil() as usize
+            }
+            Tokenizer::HuggingFace(tokenizer) => match tokenizer.encode(text, false) {
+                Ok(encoding) => encoding.len(),
+                // Roughly four characters per token
+                Err(_) => text.len().div_ceil(4),
+            },
+        }
+    }
+}
+
 /// Async token counter with caching capabilities
 pub struct AsyncTokenCounter {
-    tokenizer: Arc<CoreBPE>,
+    tokenizer: Arc<Tokenizer>,
     token_cache: Arc<DashMap<u64, usize>>, // content hash -> token count
 }
 
 /// Legacy synchronous token counter for backward compatibility
 pub struct TokenCounter {
-    tokenizer: Arc<CoreBPE>,
+    tokenizer: Arc<Tokenizer>,
 }
 
 impl AsyncTokenCounter {
-    /// Creates a new async token counter with caching
+    /// Creates a new async token counter with caching, using o200k_base
     pub async fn new() -> Result<Self, String> {
-        let tokenizer = get_tokenizer().await?;
+        Self::with_tokenizer(TokenizerKind::O200kBase).await
+    }
+
+    /// Creates a new async token counter with caching, using the given tokenizer
+    pub async fn with_tokenizer(kind: TokenizerKind) -> Result<Self, String> {
+        let tokenizer = get_tokenizer(kind).await?;
         Ok(Self {
             tokenizer,
             token_cache: Arc::new(DashMap::new()),
@@ -48,8 +182,7 @@ impl AsyncTokenCounter {
         }
 
         // Compute and cache result with size management
-        let tokens = self.tokenizer.encode_with_special_tokens(text);
-        let count = tokens.len();
+        let count = self.tokenizer.count(text);
 
         // Manage cache size to prevent unbounded growth
         if self.token_cache.len() >= MAX_TOKEN_CACHE_SIZE {
@@ -210,10 +343,9 @@ impl TokenCounter {
         Self { tokenizer }
     }
 
-    /// Count tokens for a piece of text using our single tokenizer.
+    /// Count tokens for a piece of text using o200k_base.
     pub fn count_tokens(&self, text: &str) -> usize {
-        let tokens = self.tokenizer.encode_with_special_tokens(text);
-        tokens.len()
+        self.tokenizer.count(text)
     }
 
     pub fn count_tokens_for_tools(&self, tools: &[Tool]) -> usize {
@@ -339,37 +471,124 @@ impl TokenCounter {
     }
 }
 
-/// Get the global tokenizer instance (async version)
-/// Fixed encoding for all tokenization - using o200k_base for GPT-4o and o1 models
-async fn get_tokenizer() -> Result<Arc<CoreBPE>, String> {
-    let tokenizer = TOKENIZER
-        .get_or_init(|| async {
-            match tiktoken_rs::o200k_base() {
-                Ok(bpe) => Arc::new(bpe),
-                Err(e) => panic!("Failed to initialize o200k_base tokenizer: {}", e),
-            }
-        })
-        .await;
-    Ok(tokenizer.clone())
+fn get_tokenizer_dir() -> Result<PathBuf> {
+    let cache_dir = if let Ok(goose_dir) = std::env::var("GOOSE_CACHE_DIR") {
+        PathBuf::from(goose_dir)
+    } else {
+        dirs::cache_dir()
+            .ok_or_else(|| anyhow!("Could not determine cache directory"))?
+            .join("goose")
+    };
+    Ok(cache_dir.join("tokenizers"))
+}
+
+fn huggingface_tokenizer_path(repo: &str) -> Result<PathBuf> {
+    Ok(get_tokenizer_dir()?
+        .join(repo.replace('/', "--"))
+        .join("tokenizer.json"))
+}
+
+/// Download the tokenizer.json of a HuggingFace repository in the background, so counters
+/// created after it finishes use it. Does nothing while a download of it is running
+fn download_huggingface_tokenizer(repo: &str, path: PathBuf) {
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    if DOWNLOADS.insert(repo.to_string(), ()).is_some() {
+        return;
+    }
+    let repo = repo.to_string();
+    runtime.spawn(async move {
+        if let Err(e) = fetch_huggingface_tokenizer(&repo, &path).await {
+            tracing::warn!("Failed to download the {} tokenizer: {}", repo, e);
+        }
+        DOWNLOADS.remove(&repo);
+    });
+}
+
+async fn fetch_huggingface_tokenizer(repo: &str, path: &Path) -> Result<()> {
+    let url = format!(
+        "https://huggingface.co/{}/resolve/main/tokenizer.json",
+        repo
+    );
+    let client = reqwest::Client::builder()
+        .timeout(TOKENIZER_DOWNLOAD_TIMEOUT)
+        .build()?;
+    let bytes = client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("No directory for {}", path.display()))?;
+    tokio::fs::create_dir_all(parent).await?;
+    // Written aside and moved in place, so a partial download is never loaded
+    let partial = path.with_extension("json.partial");
+    tokio::fs::write(&partial, &bytes).await?;
+    tokio::fs::rename(&partial, path).await?;
+    Ok(())
 }
 
-/// Get the global tokenizer instance (blocking version for backward compatibility)
-fn get_tokenizer_blocking() -> Result<Arc<CoreBPE>, String> {
-    // For the blocking version, we need to handle the case where the tokenizer hasn't been initialized yet
-    if let Some(tokenizer) = TOKENIZER.get() {
+fn load_tiktoken(kind: &TokenizerKind) -> Result<Tokenizer, String> {
+    match kind {
+        TokenizerKind::Cl100kBase => tiktoken_rs::cl100k_base().map(Tokenizer::Tiktoken),
+        TokenizerKind::Claude => {
+            tiktoken_rs::cl100k_base().map(|bpe| Tokenizer::Scaled(bpe, CLAUDE_TOKEN_RATIO))
+        }
+        _ => tiktoken_rs::o200k_base().map(Tokenizer::Tiktoken),
+    }
+    .map_err(|e| format!("Failed to initialize {:?} tokenizer: {}", kind, e))
+}
+
+/// Get the shared instance of a tokenizer, loading it on first use. A HuggingFace tokenizer
+/// that isn't downloaded yet or can't be loaded is estimated with o200k_base, which isn't
+/// kept for it so a later call can still load it
+async fn get_tokenizer(kind: TokenizerKind) -> Result<Arc<Tokenizer>, String> {
+    if let Some(tokenizer) = TOKENIZERS.get(&kind) {
         return Ok(tokenizer.clone());
     }
 
-    // Initialize the tokenizer synchronously
-    match tiktoken_rs::o200k_base() {
-        Ok(bpe) => {
-            let tokenizer = Arc::new(bpe);
-            // Try to set it in the OnceCell, but it's okay if another thread beat us to it
-            let _ = TOKENIZER.set(tokenizer.clone());
-            Ok(tokenizer)
+    let tokenizer = match &kind {
+        TokenizerKind::HuggingFace(repo) => {
+            let path = huggingface_tokenizer_path(repo).map_err(|e| e.to_string())?;
+            if !path.exists() {
+                download_huggingface_tokenizer(repo, path);
+                return get_tokenizer_blocking();
+            }
+            match tokenizers::Tokenizer::from_file(&path) {
+                Ok(tokenizer) => Tokenizer::HuggingFace(Box::new(tokenizer)),
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load the {} tokenizer, estimating with o200k_base: {}",
+                        repo,
+                        e
+                    );
+                    return get_tokenizer_blocking();
+                }
+            }
         }
-        Err(e) => Err(format!("Failed to initialize o200k_base tokenizer: {}", e)),
+        kind => load_tiktoken(kind)?,
+    };
+    // Another task may have loaded it meanwhile, keep whichever was stored first
+    Ok(TOKENIZERS
+        .entry(kind)
+        .or_insert_with(|| Arc::new(tokenizer))
+        .clone())
+}
+
+/// Get the shared o200k_base tokenizer (blocking version for backward compatibility)
+fn get_tokenizer_blocking() -> Result<Arc<Tokenizer>, String> {
+    if let Some(tokenizer) = TOKENIZERS.get(&TokenizerKind::O200kBase) {
+        return Ok(tokenizer.clone());
     }
+    let tokenizer = load_tiktoken(&TokenizerKind::O200kBase)?;
+    Ok(TOKENIZERS
+        .entry(TokenizerKind::O200kBase)
+        .or_insert_with(|| Arc::new(tokenizer))
+        .clone())
 }
 
 /// Factory function for creating async token counters with proper error handling
@@ -377,6 +596,13 @@ pub async fn create_async_token_counter() -> Result<AsyncTokenCounter, String> {
     AsyncTokenCounter::new().await
 }
 
+/// Create an async token counter with the tokenizer of the given model
+pub async fn create_async_token_counter_for_model(
+    model: &ModelConfig,
+) -> Result<AsyncTokenCounter, String> {
+    AsyncTokenCounter::with_tokenizer(TokenizerKind::for_model(model)).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -671,6 +897,82 @@ mod tests {
         assert!(counter.cache_size() <= MAX_TOKEN_CACHE_SIZE);
     }
 
+    #[test]
+    fn test_tokenizer_for_model_name() {
+        let hf = |repo: &str| TokenizerKind::HuggingFace(repo.to_string());
+        assert_eq!(
+            TokenizerKind::from_model_name("claude-sonnet-4-20250514"),
+            TokenizerKind::Claude
+        );
+        assert_eq!(
+            TokenizerKind::from_model_name("llama3.2:3b"),
+            hf("NousResearch/Meta-Llama-3-8B")
+        );
+        assert_eq!(
+            TokenizerKind::from_model_name("meta-llama/Llama-3.1-8B-Instruct"),
+            hf("NousResearch/Meta-Llama-3-8B")
+        );
+        assert_eq!(
+            TokenizerKind::from_model_name("qwen3:8b"),
+            hf("Qwen/Qwen3-8B")
+        );
+        assert_eq!(
+            TokenizerKind::from_model_name("qwen2.5-coder:32b"),
+            hf("Qwen/Qwen2.5-Coder-32B-Instruct")
+        );
+        // A version without a known tokenizer gets the default estimate
+        assert_eq!(
+            TokenizerKind::from_model_name("codellama:13b"),
+            TokenizerKind::O200kBase
+        );
+        assert_eq!(
+            TokenizerKind::from_model_name("gpt-4-turbo"),
+            TokenizerKind::Cl100kBase
+        );
+        assert_eq!(
+            TokenizerKind::from_model_name("gpt-4o-mini"),
+            TokenizerKind::O200kBase
+        );
+        assert_eq!(
+            TokenizerKind::from_model_name("some-new-model"),
+            TokenizerKind::O200kBase
+        );
+        assert_eq!(
+            TokenizerKind::parse("hf:Qwen/Qwen3-8B"),
+            Some(TokenizerKind::HuggingFace("Qwen/Qwen3-8B".to_string()))
+        );
+        assert_eq!(TokenizerKind::parse("hf:missing-owner"), None);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_unloadable_huggingface_tokenizer_is_not_cached() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        std::env::set_var("GOOSE_CACHE_DIR", cache_dir.path());
+        let repo = "example/broken-tokenizer";
+        let kind = TokenizerKind::HuggingFace(repo.to_string());
+        let path = huggingface_tokenizer_path(repo).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "not a tokenizer").unwrap();
+
+        let counter = AsyncTokenCounter::with_tokenizer(kind.clone()).await;
+        std::env::remove_var("GOOSE_CACHE_DIR");
+        assert!(counter.unwrap().count_tokens("hello world") > 0);
+        assert!(!TOKENIZERS.contains_key(&kind));
+    }
+
+    #[tokio::test]
+    async fn test_claude_estimate_is_scaled() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let cl100k = AsyncTokenCounter::with_tokenizer(TokenizerKind::Cl100kBase)
+            .await
+            .unwrap();
+        let claude = AsyncTokenCounter::with_tokenizer(TokenizerKind::Claude)
+            .await
+            .unwrap();
+        assert!(claude.count_tokens(text) > cl100k.count_tokens(text));
+    }
+
     #[test]
     fn test_tokenizer_consistency() {
         // Test that both sync and async versions give the same results
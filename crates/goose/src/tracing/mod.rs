@@ -2,6 +2,7 @@ pub mod langfuse_layer;
 mod observation_layer;
 pub mod otlp_layer;
 pub mod rate_limiter;
+pub mod tool_calls;
 
 pub use langfuse_layer::{create_langfuse_observer, LangfuseBatchManager};
 pub use observation_layer::{
@@ -0,0 +1,87 @@
+//! Correlation ids for tool calls, so a failing tool call can be followed through the logs,
+//! including the stderr of the extension that ran it
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+tokio::task_local! {
+    static TOOL_CALL_ID: String;
+}
+
+/// The tool calls each extension is running, by extension name
+static IN_FLIGHT: Lazy<Mutex<HashMap<String, Vec<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Run `fut` as part of the tool call with correlation id `id`
+pub async fn with_tool_call_id<F: Future>(id: String, fut: F) -> F::Output {
+    TOOL_CALL_ID.scope(id, fut).await
+}
+
+/// The correlation id of the tool call being run, if any
+pub fn current_tool_call_id() -> Option<String> {
+    TOOL_CALL_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Records that an extension is running a tool call until it is dropped
+pub struct InFlightToolCall {
+    extension: String,
+    id: String,
+}
+
+impl Drop for InFlightToolCall {
+    fn drop(&mut self) {
+        let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(ids) = in_flight.get_mut(&self.extension) {
+            if let Some(position) = ids.iter().position(|id| *id == self.id) {
+                ids.remove(position);
+            }
+            if ids.is_empty() {
+                in_flight.remove(&self.extension);
+            }
+        }
+    }
+}
+
+/// Mark the current tool call as running in `extension`
+pub fn track_in_extension(extension: &str) -> Option<InFlightToolCall> {
+    let id = current_tool_call_id()?;
+    IN_FLIGHT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(extension.to_string())
+        .or_default()
+        .push(id.clone());
+    Some(InFlightToolCall {
+        extension: extension.to_string(),
+        id,
+    })
+}
+
+/// The correlation ids of the tool calls `extension` is running, comma separated
+pub fn in_flight_ids(extension: &str) -> String {
+    IN_FLIGHT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(extension)
+        .map(|ids| ids.join(","))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tool_call_tracked_while_running() {
+        assert_eq!(current_tool_call_id(), None);
+        with_tool_call_id("call_1".to_string(), async {
+            assert_eq!(current_tool_call_id().as_deref(), Some("call_1"));
+            let guard = track_in_extension("test_extension");
+            assert_eq!(in_flight_ids("test_extension"), "call_1");
+            drop(guard);
+        })
+        .await;
+        assert_eq!(in_flight_ids("test_extension"), "");
+    }
+}
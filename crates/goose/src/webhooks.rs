@@ -0,0 +1,325 @@
+//! Outbound webhooks, so goose can be wired into chat or incident tooling. Webhooks are listed in
+//! the config:
+//!
+//! ```yaml
+//! webhooks:
+//!   - name: slack
+//!     url: https://hooks.example.com/goose
+//!     events: [approval_needed, scheduled_run_failed]
+//! ```
+//!
+//! A webhook without events gets all of them. A scheduled recipe can also name a webhook to
+//! notify, which then hears about its runs whatever events it is for. When the secret `GOOSE_WEBHOOK_SECRET_<NAME>` is
+//! set, the body is signed with HMAC-SHA256 and the signature sent in `X-Goose-Signature`.
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::config::Config;
+
+pub const WEBHOOKS_KEY: &str = "webhooks";
+const SIGNATURE_HEADER: &str = "X-Goose-Signature";
+const EVENT_HEADER: &str = "X-Goose-Event";
+const MAX_ATTEMPTS: u32 = 3;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long the end of a session waits for its webhooks, so exiting doesn't drop them
+pub const SESSION_END_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    SessionStart,
+    SessionEnd,
+    ApprovalNeeded,
+    BudgetExceeded,
+    ScheduledRunCompleted,
+    ScheduledRunFailed,
+    /// Sent by `goose webhooks test`, whatever events the webhook is for
+    Test,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::SessionStart => "session_start",
+            WebhookEvent::SessionEnd => "session_end",
+            WebhookEvent::ApprovalNeeded => "approval_needed",
+            WebhookEvent::BudgetExceeded => "budget_exceeded",
+            WebhookEvent::ScheduledRunCompleted => "scheduled_run_completed",
+            WebhookEvent::ScheduledRunFailed => "scheduled_run_failed",
+            WebhookEvent::Test => "test",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub name: String,
+    pub url: String,
+    /// Events sent to the webhook, all of them when empty
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl WebhookConfig {
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        event == WebhookEvent::Test || self.events.is_empty() || self.events.contains(&event)
+    }
+
+    pub fn secret_key(&self) -> String {
+        format!(
+            "GOOSE_WEBHOOK_SECRET_{}",
+            self.name.to_uppercase().replace(['-', ' '], "_")
+        )
+    }
+}
+
+/// The configured webhooks, none when the config has no valid list
+pub fn configured() -> Vec<WebhookConfig> {
+    match Config::global().get_param::<Vec<WebhookConfig>>(WEBHOOKS_KEY) {
+        Ok(webhooks) => webhooks,
+        Err(crate::config::ConfigError::NotFound(_)) => Vec::new(),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid webhooks config: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Send the event to the webhooks that want it, in the background
+pub fn notify(event: WebhookEvent, data: Value) {
+    notify_with_target(event, data, None)
+}
+
+/// Send the event to the webhooks that want it and to the `target` webhook whatever its events,
+/// in the background
+pub fn notify_with_target(event: WebhookEvent, data: Value, target: Option<&str>) {
+    let configured = configured();
+    if let Some(target) = target {
+        if !configured.iter().any(|webhook| webhook.name == target) {
+            tracing::warn!("No webhook named {} to notify", target);
+        }
+    }
+    let webhooks = recipients(configured, event, target);
+    if webhooks.is_empty() {
+        return;
+    }
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        tracing::warn!("No runtime to send the {} webhook on", event.as_str());
+        return;
+    };
+    runtime.spawn(deliver_all(webhooks, event, data));
+}
+
+/// Send the event to the webhooks that want it and wait for the deliveries, giving up on them
+/// after `timeout`. For events sent right before the process exits, like the end of a session
+pub async fn notify_and_wait(event: WebhookEvent, data: Value, timeout: Duration) {
+    let webhooks = recipients(configured(), event, None);
+    deliver_within(webhooks, event, data, timeout).await
+}
+
+async fn deliver_within(
+    webhooks: Vec<WebhookConfig>,
+    event: WebhookEvent,
+    data: Value,
+    timeout: Duration,
+) {
+    if webhooks.is_empty() {
+        return;
+    }
+    if tokio::time::timeout(timeout, deliver_all(webhooks, event, data))
+        .await
+        .is_err()
+    {
+        tracing::warn!(
+            "Gave up on the {} webhooks after {:?}",
+            event.as_str(),
+            timeout
+        );
+    }
+}
+
+async fn deliver_all(webhooks: Vec<WebhookConfig>, event: WebhookEvent, data: Value) {
+    let deliveries = webhooks.iter().map(|webhook| {
+        let data = data.clone();
+        async move {
+            if let Err(e) = deliver(webhook, event, data).await {
+                tracing::error!("Webhook {} failed: {}", webhook.name, e);
+            }
+        }
+    });
+    futures::future::join_all(deliveries).await;
+}
+
+fn recipients(
+    webhooks: Vec<WebhookConfig>,
+    event: WebhookEvent,
+    target: Option<&str>,
+) -> Vec<WebhookConfig> {
+    webhooks
+        .into_iter()
+        .filter(|webhook| webhook.wants(event) || target == Some(webhook.name.as_str()))
+        .collect()
+}
+
+pub fn payload(event: WebhookEvent, data: Value) -> Value {
+    json!({
+        "event": event.as_str(),
+        "timestamp": Utc::now().to_rfc3339(),
+        "data": data,
+    })
+}
+
+/// Hex HMAC-SHA256 of the body, as `sha256=<hex>`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = ring::hmac::sign(&key, body);
+    let hex: String = tag
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+/// Post the event, retrying with backoff on connection errors, rate limits and server errors
+pub async fn deliver(webhook: &WebhookConfig, event: WebhookEvent, data: Value) -> Result<()> {
+    let body = serde_json::to_vec(&payload(event, data))?;
+    let secret: Option<String> = Config::global().get_secret(&webhook.secret_key()).ok();
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+
+    let mut last_error = None;
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await;
+        }
+        let mut request = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.as_str())
+            .body(body.clone());
+        if let Some(secret) = &secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+        }
+        match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                let error = anyhow!("{} answered {}", webhook.url, status);
+                if !(status.is_server_error() || status.as_u16() == 429) {
+                    return Err(error);
+                }
+                last_error = Some(error);
+            }
+            Err(e) => last_error = Some(anyhow!("Failed to reach {}: {}", webhook.url, e)),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| anyhow!("Webhook was not sent")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wants() {
+        let webhook = WebhookConfig {
+            name: "slack".to_string(),
+            url: "https://example.com".to_string(),
+            events: vec![WebhookEvent::ApprovalNeeded],
+        };
+        assert!(webhook.wants(WebhookEvent::ApprovalNeeded));
+        assert!(webhook.wants(WebhookEvent::Test));
+        assert!(!webhook.wants(WebhookEvent::SessionEnd));
+        assert_eq!(webhook.secret_key(), "GOOSE_WEBHOOK_SECRET_SLACK");
+    }
+
+    #[test]
+    fn test_recipients_include_target() {
+        let webhook = |name: &str, events: Vec<WebhookEvent>| WebhookConfig {
+            name: name.to_string(),
+            url: "https://example.com".to_string(),
+            events,
+        };
+        let webhooks = vec![
+            webhook("pager", vec![WebhookEvent::ScheduledRunFailed]),
+            webhook("team", vec![WebhookEvent::ApprovalNeeded]),
+            webhook("audit", vec![]),
+        ];
+        let names = |target| -> Vec<String> {
+            recipients(
+                webhooks.clone(),
+                WebhookEvent::ScheduledRunCompleted,
+                target,
+            )
+            .into_iter()
+            .map(|webhook| webhook.name)
+            .collect()
+        };
+        assert_eq!(names(None), vec!["audit"]);
+        assert_eq!(names(Some("team")), vec!["team", "audit"]);
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deliver_within_waits_for_delivery() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let fast = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&fast)
+            .await;
+        let slow = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(30)))
+            .mount(&slow)
+            .await;
+        let webhook = |name: &str, url: String| WebhookConfig {
+            name: name.to_string(),
+            url,
+            events: vec![],
+        };
+
+        let started = std::time::Instant::now();
+        deliver_within(
+            vec![webhook("fast", fast.uri()), webhook("slow", slow.uri())],
+            WebhookEvent::SessionEnd,
+            json!({"session_id": "test"}),
+            Duration::from_millis(500),
+        )
+        .await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let received = fast.received_requests().await.unwrap();
+        assert_eq!(received.len(), 1);
+        let body: Value = serde_json::from_slice(&received[0].body).unwrap();
+        assert_eq!(body["event"], "session_end");
+    }
+
+    #[test]
+    fn test_config_parses() {
+        let webhooks: Vec<WebhookConfig> = serde_yaml::from_str(
+            "- name: pager\n  url: https://example.com\n  events: [scheduled_run_failed]\n",
+        )
+        .unwrap();
+        assert_eq!(webhooks[0].events, vec![WebhookEvent::ScheduledRunFailed]);
+    }
+}
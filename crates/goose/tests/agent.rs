@@ -138,7 +138,15 @@ async fn run_truncate_test(
     let mut responses = Vec::new();
     while let Some(response_result) = reply_stream.next().await {
         match response_result {
-            Ok(AgentEvent::Message(response)) => responses.push(response),
+            Ok(AgentEvent::Message(response)) => {
+                // Exceeding the context compacts the history once before the error is reported
+                if !matches!(
+                    response.content.first(),
+                    Some(goose::conversation::message::MessageContent::SummarizationRequested(_))
+                ) {
+                    responses.push(response)
+                }
+            }
             Ok(AgentEvent::McpNotification(n)) => {
                 println!("MCP Notification: {n:?}");
             }
@@ -148,6 +156,7 @@ async fn run_truncate_test(
             Ok(AgentEvent::HistoryReplaced(_)) => {
                 // Handle history replacement events if needed
             }
+            Ok(AgentEvent::PlanUpdate(_)) | Ok(AgentEvent::TodoUpdate(_)) => {}
             Err(e) => {
                 println!("Error: {:?}", e);
                 return Err(e);
@@ -877,6 +886,7 @@ mod retry_tests {
             max_retries: 3,
             checks: vec![SuccessCheck::Shell {
                 command: "echo 'success check'".to_string(),
+                max_attempts: None,
             }],
             on_failure: Some("echo 'cleanup executed'".to_string()),
             timeout_seconds: Some(30),
@@ -895,6 +905,7 @@ mod retry_tests {
             execution_mode: None,
             max_turns: None,
             retry_config: Some(retry_config),
+            confirmation_timeout: None,
         };
 
         let conversation =
@@ -933,6 +944,7 @@ mod retry_tests {
 
         let success_checks = vec![SuccessCheck::Shell {
             command: "echo 'test'".to_string(),
+            max_attempts: None,
         }];
 
         let result = execute_success_checks(&success_checks, &retry_config).await;
@@ -941,6 +953,7 @@ mod retry_tests {
 
         let fail_checks = vec![SuccessCheck::Shell {
             command: "false".to_string(),
+            max_attempts: None,
         }];
 
         let result = execute_success_checks(&fail_checks, &retry_config).await;
@@ -1074,6 +1087,7 @@ mod max_turns_tests {
             execution_mode: None,
             max_turns: Some(1),
             retry_config: None,
+            confirmation_timeout: None,
         };
         let conversation = Conversation::new(vec![Message::user().with_text("Hello")]).unwrap();
 
@@ -1102,6 +1116,7 @@ mod max_turns_tests {
                 Ok(AgentEvent::McpNotification(_)) => {}
                 Ok(AgentEvent::ModelChange { .. }) => {}
                 Ok(AgentEvent::HistoryReplaced(_)) => {}
+                Ok(AgentEvent::PlanUpdate(_)) | Ok(AgentEvent::TodoUpdate(_)) => {}
                 Err(e) => {
                     return Err(e);
                 }
@@ -1124,6 +1139,207 @@ mod max_turns_tests {
         } else {
             panic!("Expected text content in last message");
         }
+        assert_eq!(
+            agent.reply_outcome().await,
+            goose::agents::ReplyOutcome::MaxTurnsReached
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod context_length_tests {
+    use super::*;
+    use async_trait::async_trait;
+    use goose::agents::ReplyOutcome;
+    use goose::conversation::message::MessageContent;
+    use goose::providers::base::{ProviderMetadata, ProviderUsage, Usage};
+    use goose::providers::errors::ProviderError;
+    use rmcp::model::Tool;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// Answers with the scripted replies in order, summaries included
+    struct ScriptedProvider {
+        replies: Mutex<VecDeque<Result<Message, ProviderError>>>,
+        calls: Mutex<usize>,
+    }
+
+    impl ScriptedProvider {
+        fn new(replies: Vec<Result<Message, ProviderError>>) -> Self {
+            Self {
+                replies: Mutex::new(replies.into()),
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("mock-model").unwrap()
+        }
+
+        async fn complete_with_model(
+            &self,
+            _model_config: &ModelConfig,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            *self.calls.lock().unwrap() += 1;
+            let reply = self
+                .replies
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| {
+                    Err(ProviderError::ExecutionError(
+                        "no scripted reply left".to_string(),
+                    ))
+                })?;
+            Ok((
+                reply,
+                ProviderUsage::new(
+                    "mock-model".to_string(),
+                    Usage::new(Some(10), Some(5), Some(15)),
+                ),
+            ))
+        }
+    }
+
+    fn context_length_exceeded() -> Result<Message, ProviderError> {
+        Err(ProviderError::ContextLengthExceeded(
+            "prompt is too long".to_string(),
+        ))
+    }
+
+    fn conversation() -> Conversation {
+        Conversation::new(vec![
+            Message::user().with_text("what is 2 + 2?"),
+            Message::assistant().with_text("4"),
+            Message::user().with_text("and 3 + 3?"),
+        ])
+        .unwrap()
+    }
+
+    async fn run_reply(
+        provider: Arc<ScriptedProvider>,
+        conversation: Conversation,
+    ) -> Result<(Vec<Message>, usize, ReplyOutcome)> {
+        let agent = Agent::new();
+        agent.update_provider(provider).await?;
+
+        let reply_stream = agent.reply(conversation, None, None).await?;
+        tokio::pin!(reply_stream);
+
+        let mut messages = Vec::new();
+        let mut history_replacements = 0;
+        while let Some(event) = reply_stream.next().await {
+            match event? {
+                AgentEvent::Message(message) => messages.push(message),
+                AgentEvent::HistoryReplaced(_) => history_replacements += 1,
+                _ => {}
+            }
+        }
+        Ok((messages, history_replacements, agent.reply_outcome().await))
+    }
+
+    fn count_content(messages: &[Message], matches: fn(&MessageContent) -> bool) -> usize {
+        messages
+            .iter()
+            .flat_map(|message| &message.content)
+            .filter(|content| matches(content))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_compacts_once_and_retries() -> Result<()> {
+        let provider = Arc::new(ScriptedProvider::new(vec![
+            context_length_exceeded(),
+            Ok(Message::assistant().with_text("The user asked what 2 + 2 is, it is 4.")),
+            Ok(Message::assistant().with_text("6")),
+        ]));
+
+        let (messages, history_replacements, outcome) =
+            run_reply(provider.clone(), conversation()).await?;
+
+        // The failed turn, the summary and the retried turn
+        assert_eq!(provider.calls(), 3);
+        assert_eq!(history_replacements, 1);
+        assert_eq!(
+            count_content(&messages, |c| matches!(
+                c,
+                MessageContent::SummarizationRequested(_)
+            )),
+            1
+        );
+        assert_eq!(
+            count_content(&messages, |c| matches!(
+                c,
+                MessageContent::ContextLengthExceeded(_)
+            )),
+            0
+        );
+        assert_eq!(messages.last().unwrap().as_concat_text(), "6");
+        assert_eq!(outcome, ReplyOutcome::Completed);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_second_overflow_surfaces_error() -> Result<()> {
+        let provider = Arc::new(ScriptedProvider::new(vec![
+            context_length_exceeded(),
+            Ok(Message::assistant().with_text("The user asked what 2 + 2 is, it is 4.")),
+            context_length_exceeded(),
+        ]));
+
+        let (messages, history_replacements, outcome) =
+            run_reply(provider.clone(), conversation()).await?;
+
+        // No second compaction after the retry overflowed too
+        assert_eq!(provider.calls(), 3);
+        assert_eq!(history_replacements, 1);
+        assert_eq!(
+            count_content(&messages, |c| matches!(
+                c,
+                MessageContent::ContextLengthExceeded(_)
+            )),
+            1
+        );
+        // goose run exits with the provider error code instead of 0
+        assert_eq!(outcome, ReplyOutcome::ProviderError);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_without_summary() -> Result<()> {
+        let provider = Arc::new(ScriptedProvider::new(vec![context_length_exceeded()]));
+        let conversation =
+            Conversation::new(vec![Message::user().with_text("and 3 + 3?")]).unwrap();
+
+        let (messages, history_replacements, outcome) =
+            run_reply(provider.clone(), conversation).await?;
+
+        // Only the last user message is left, there is nothing to summarize
+        assert_eq!(provider.calls(), 1);
+        assert_eq!(history_replacements, 0);
+        assert_eq!(
+            count_content(&messages, |c| matches!(
+                c,
+                MessageContent::ContextLengthExceeded(_)
+            )),
+            1
+        );
+        assert_eq!(outcome, ReplyOutcome::ProviderError);
         Ok(())
     }
 }
@@ -0,0 +1,86 @@
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::time::Duration;
+
+use goose::scheduler::{ScheduledJob, Scheduler};
+use goose::shutdown::{register_process_group, terminate_process_groups};
+use nix::sys::signal::killpg;
+use nix::unistd::Pid;
+use serial_test::serial;
+use tempfile::tempdir;
+
+fn group_alive(pgid: u32) -> bool {
+    killpg(Pid::from_raw(pgid as i32), None).is_ok()
+}
+
+fn spawn_group(script: &str) -> u32 {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .process_group(0)
+        .spawn()
+        .expect("failed to start sh");
+    let pid = child.id();
+    // Reap the leader as it exits so the group disappears with its last member
+    std::thread::spawn(move || child.wait());
+    pid
+}
+
+#[tokio::test]
+#[serial]
+async fn test_terminates_background_processes() {
+    // Like a shell command that left a server running in the background
+    let pgid = spawn_group("sleep 60 & sleep 60 & wait");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    register_process_group(pgid);
+
+    terminate_process_groups(Duration::from_secs(2)).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!group_alive(pgid));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_kills_processes_ignoring_sigterm() {
+    let pgid = spawn_group("trap '' TERM; sleep 60 & wait");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    register_process_group(pgid);
+
+    terminate_process_groups(Duration::from_millis(200)).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!group_alive(pgid));
+}
+
+#[tokio::test]
+async fn test_scheduler_shutdown_clears_running_jobs() {
+    let dir = tempdir().unwrap();
+    let recipe = dir.path().join("recipe.yaml");
+    fs::write(&recipe, "title: test\n").unwrap();
+    let storage = dir.path().join("schedules.json");
+    let job = ScheduledJob {
+        id: "nightly".to_string(),
+        source: recipe.to_string_lossy().to_string(),
+        cron: "0 0 0 * * *".to_string(),
+        last_run: None,
+        currently_running: true,
+        paused: false,
+        current_session_id: Some("20250101_000000".to_string()),
+        process_start_time: Some(chrono::Utc::now()),
+        execution_mode: Some("background".to_string()),
+        timezone: None,
+        notify: None,
+        recipe_origin: None,
+    };
+    fs::write(&storage, serde_json::to_string(&vec![job]).unwrap()).unwrap();
+
+    let scheduler = Scheduler::new(storage.clone()).await.unwrap();
+    scheduler.shutdown().await.unwrap();
+
+    let jobs: Vec<ScheduledJob> =
+        serde_json::from_str(&fs::read_to_string(&storage).unwrap()).unwrap();
+    assert!(!jobs[0].currently_running);
+    assert_eq!(jobs[0].current_session_id, None);
+}
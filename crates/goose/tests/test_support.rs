@@ -363,6 +363,9 @@ impl ScheduleToolTestBuilder {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some("background".to_string()),
+            timezone: None,
+            notify: None,
+            recipe_origin: None,
         };
         {
             let mut jobs = self.scheduler.jobs.lock().await;
@@ -402,6 +405,9 @@ impl ScheduleToolTestBuilder {
 pub fn create_test_session_metadata(message_count: usize, working_dir: &str) -> SessionMetadata {
     SessionMetadata {
         message_count,
+        turn_count: 0,
+        provider: None,
+        model: None,
         working_dir: PathBuf::from(working_dir),
         description: "Test session".to_string(),
         schedule_id: Some("test_job".to_string()),
@@ -412,5 +418,14 @@ pub fn create_test_session_metadata(message_count: usize, working_dir: &str) ->
         accumulated_input_tokens: Some(50),
         accumulated_output_tokens: Some(50),
         todo_content: None,
+        todos: Vec::new(),
+        parent_session_id: None,
+        forked_at: None,
+        tags: Vec::new(),
+        plan: None,
+        checkpoint: None,
+        schedule_run_url: None,
+        coverage: None,
+        attachments: Vec::new(),
     }
 }
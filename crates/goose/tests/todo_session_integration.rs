@@ -1,4 +1,5 @@
 use futures::StreamExt;
+use goose::agents::todo_tools::TodoStatus;
 use goose::agents::types::SessionConfig;
 use goose::agents::{Agent, AgentEvent};
 use goose::conversation::message::Message;
@@ -139,6 +140,7 @@ async fn test_todo_add_persists_to_session() {
         max_turns: Some(10),
         execution_mode: Some("auto".to_string()),
         retry_config: None,
+        confirmation_timeout: None,
     };
 
     // Process the conversation
@@ -188,6 +190,7 @@ async fn test_todo_list_reads_from_session() {
         max_turns: Some(10),
         execution_mode: Some("auto".to_string()),
         retry_config: None,
+        confirmation_timeout: None,
     };
 
     // Process the conversation
@@ -266,6 +269,7 @@ async fn test_todo_clear_removes_from_session() {
         max_turns: Some(10),
         execution_mode: Some("auto".to_string()),
         retry_config: None,
+        confirmation_timeout: None,
     };
 
     // Process the conversation
@@ -439,3 +443,41 @@ async fn test_todo_update_preserves_other_metadata() {
     assert_eq!(metadata_read.total_tokens, Some(1000));
     assert_eq!(metadata_read.todo_content, Some("Updated TODO".to_string()));
 }
+
+#[tokio::test]
+async fn test_legacy_todo_content_is_converted_to_items() {
+    let temp_dir = create_test_session_dir().await;
+    let session_id = session::Identifier::Name(format!("test_session_{}", Uuid::new_v4()));
+    let agent = create_test_agent_with_mock_provider().await;
+
+    let session_path = goose::session::storage::get_path(session_id.clone()).unwrap();
+    let metadata = SessionMetadata {
+        todo_content: Some("- [x] Task 1\n- [ ] Task 2".to_string()),
+        ..Default::default()
+    };
+    goose::session::storage::update_metadata(&session_path, &metadata)
+        .await
+        .unwrap();
+
+    let conversation = Conversation::new(vec![Message::user().with_text("Continue")]).unwrap();
+    let session_config = SessionConfig {
+        id: session_id.clone(),
+        working_dir: temp_dir.path().to_path_buf(),
+        schedule_id: None,
+        max_turns: Some(10),
+        execution_mode: Some("auto".to_string()),
+        retry_config: None,
+        confirmation_timeout: None,
+    };
+    let mut stream = agent
+        .reply(conversation, Some(session_config), None)
+        .await
+        .unwrap();
+    while stream.next().await.is_some() {}
+
+    let todos = agent.todos().await;
+    assert_eq!(todos.len(), 2);
+    assert_eq!(todos[0].title, "Task 1");
+    assert_eq!(todos[0].status, TodoStatus::Completed);
+    assert_eq!(todos[1].status, TodoStatus::Pending);
+}
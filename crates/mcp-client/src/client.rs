@@ -2,15 +2,20 @@ use rmcp::{
     model::{
         CallToolRequest, CallToolRequestParam, CallToolResult, CancelledNotification,
         CancelledNotificationMethod, CancelledNotificationParam, ClientCapabilities, ClientInfo,
-        ClientRequest, GetPromptRequest, GetPromptRequestParam, GetPromptResult, Implementation,
+        ClientRequest, CreateMessageRequestMethod, CreateMessageRequestParam, CreateMessageResult,
+        ErrorData, GetPromptRequest, GetPromptRequestParam, GetPromptResult, Implementation,
         InitializeResult, ListPromptsRequest, ListPromptsResult, ListResourcesRequest,
-        ListResourcesResult, ListToolsRequest, ListToolsResult, LoggingMessageNotification,
-        LoggingMessageNotificationMethod, PaginatedRequestParam, ProgressNotification,
-        ProgressNotificationMethod, ProtocolVersion, ReadResourceRequest, ReadResourceRequestParam,
-        ReadResourceResult, RequestId, ServerNotification, ServerResult,
+        ListResourcesResult, ListRootsResult, ListToolsRequest, ListToolsResult,
+        LoggingMessageNotification, LoggingMessageNotificationMethod, PaginatedRequestParam,
+        PingRequest, ProgressNotification, ProgressNotificationMethod, ProtocolVersion,
+        ReadResourceRequest, ReadResourceRequestParam, ReadResourceResult, RequestId,
+        ResourceUpdatedNotification, ResourceUpdatedNotificationMethod,
+        ResourceUpdatedNotificationParam, Root, ServerNotification, ServerResult, SubscribeRequest,
+        SubscribeRequestParam, UnsubscribeRequest, UnsubscribeRequestParam,
     },
     service::{
-        ClientInitializeError, PeerRequestOptions, RequestHandle, RunningService, ServiceRole,
+        ClientInitializeError, PeerRequestOptions, RequestContext, RequestHandle, RunningService,
+        ServiceRole,
     },
     transport::IntoTransport,
     ClientHandler, Peer, RoleClient, ServiceError, ServiceExt,
@@ -70,16 +75,64 @@ pub trait McpClientTrait: Send + Sync {
     async fn subscribe(&self) -> mpsc::Receiver<ServerNotification>;
 
     fn get_info(&self) -> Option<&InitializeResult>;
+
+    /// Check that the server is still responding
+    async fn ping(&self, _cancel_token: CancellationToken) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Tell the server the roots it was given have changed
+    async fn notify_roots_list_changed(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Ask the server to send a notification whenever the resource changes
+    async fn subscribe_resource(
+        &self,
+        _uri: &str,
+        _cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        Err(ServiceError::UnexpectedResponse)
+    }
+
+    /// Stop the notifications for a resource subscribed to earlier
+    async fn unsubscribe_resource(
+        &self,
+        _uri: &str,
+        _cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        Err(ServiceError::UnexpectedResponse)
+    }
+}
+
+/// The roots shared with servers, normally the session's working directory
+pub type SharedRoots = Arc<Mutex<Vec<Root>>>;
+
+/// Answers `sampling/createMessage` requests, letting a server ask the client for a completion
+#[async_trait::async_trait]
+pub trait SamplingHandler: Send + Sync {
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+    ) -> Result<CreateMessageResult, ErrorData>;
 }
 
 pub struct GooseClient {
     notification_handlers: Arc<Mutex<Vec<Sender<ServerNotification>>>>,
+    sampling: Option<Arc<dyn SamplingHandler>>,
+    roots: SharedRoots,
 }
 
 impl GooseClient {
-    pub fn new(handlers: Arc<Mutex<Vec<Sender<ServerNotification>>>>) -> Self {
+    pub fn new(
+        handlers: Arc<Mutex<Vec<Sender<ServerNotification>>>>,
+        sampling: Option<Arc<dyn SamplingHandler>>,
+        roots: SharedRoots,
+    ) -> Self {
         GooseClient {
             notification_handlers: handlers,
+            sampling,
+            roots,
         }
     }
 }
@@ -125,10 +178,58 @@ impl ClientHandler for GooseClient {
             });
     }
 
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        context: rmcp::service::NotificationContext<rmcp::RoleClient>,
+    ) {
+        self.notification_handlers
+            .lock()
+            .await
+            .iter()
+            .for_each(|handler| {
+                let _ = handler.try_send(ServerNotification::ResourceUpdatedNotification(
+                    ResourceUpdatedNotification {
+                        params: params.clone(),
+                        method: ResourceUpdatedNotificationMethod,
+                        extensions: context.extensions.clone(),
+                    },
+                ));
+            });
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        match &self.sampling {
+            Some(sampling) => sampling.create_message(params).await,
+            None => Err(ErrorData::method_not_found::<CreateMessageRequestMethod>()),
+        }
+    }
+
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        Ok(ListRootsResult {
+            roots: self.roots.lock().await.clone(),
+        })
+    }
+
     fn get_info(&self) -> ClientInfo {
+        let builder = ClientCapabilities::builder()
+            .enable_roots()
+            .enable_roots_list_changed();
+        let capabilities = if self.sampling.is_some() {
+            builder.enable_sampling().build()
+        } else {
+            builder.build()
+        };
         ClientInfo {
             protocol_version: ProtocolVersion::V_2025_03_26,
-            capabilities: ClientCapabilities::builder().build(),
+            capabilities,
             client_info: Implementation {
                 name: "goose".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_owned(),
@@ -146,9 +247,13 @@ pub struct McpClient {
 }
 
 impl McpClient {
+    /// Connect to a server, answering its sampling requests with `sampling` if given and
+    /// its roots requests with `roots`
     pub async fn connect<T, E, A>(
         transport: T,
         timeout: std::time::Duration,
+        sampling: Option<Arc<dyn SamplingHandler>>,
+        roots: SharedRoots,
     ) -> Result<Self, ClientInitializeError>
     where
         T: IntoTransport<RoleClient, E, A>,
@@ -157,7 +262,7 @@ impl McpClient {
         let notification_subscribers =
             Arc::new(Mutex::new(Vec::<mpsc::Sender<ServerNotification>>::new()));
 
-        let client = GooseClient::new(notification_subscribers.clone());
+        let client = GooseClient::new(notification_subscribers.clone(), sampling, roots);
         let client: rmcp::service::RunningService<rmcp::RoleClient, GooseClient> =
             client.serve(transport).await?;
         let server_info = client.peer_info().cloned();
@@ -386,4 +491,73 @@ impl McpClientTrait for McpClient {
         self.notification_subscribers.lock().await.push(tx);
         rx
     }
+
+    async fn notify_roots_list_changed(&self) -> Result<(), Error> {
+        self.client.lock().await.notify_roots_list_changed().await
+    }
+
+    async fn ping(&self, cancel_token: CancellationToken) -> Result<(), Error> {
+        let res = self
+            .send_request(
+                ClientRequest::PingRequest(PingRequest {
+                    method: Default::default(),
+                    extensions: Default::default(),
+                }),
+                cancel_token,
+            )
+            .await?;
+
+        match res {
+            ServerResult::EmptyResult(_) => Ok(()),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    async fn subscribe_resource(
+        &self,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        let res = self
+            .send_request(
+                ClientRequest::SubscribeRequest(SubscribeRequest {
+                    params: SubscribeRequestParam {
+                        uri: uri.to_string(),
+                    },
+                    method: Default::default(),
+                    extensions: Default::default(),
+                }),
+                cancel_token,
+            )
+            .await?;
+
+        match res {
+            ServerResult::EmptyResult(_) => Ok(()),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    async fn unsubscribe_resource(
+        &self,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        let res = self
+            .send_request(
+                ClientRequest::UnsubscribeRequest(UnsubscribeRequest {
+                    params: UnsubscribeRequestParam {
+                        uri: uri.to_string(),
+                    },
+                    method: Default::default(),
+                    extensions: Default::default(),
+                }),
+                cancel_token,
+            )
+            .await?;
+
+        match res {
+            ServerResult::EmptyResult(_) => Ok(()),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
 }
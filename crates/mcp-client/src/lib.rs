@@ -1,3 +1,3 @@
 pub mod client;
 
-pub use client::{Error, McpClient, McpClientTrait};
+pub use client::{Error, McpClient, McpClientTrait, SamplingHandler, SharedRoots};